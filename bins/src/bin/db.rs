@@ -6,9 +6,16 @@
 //! The OpenTimeline website ([www.open-timeline.org](www.open-timeline.org))
 //!
 
+use bool_tag_expr::BoolTagExpr;
 use clap::{CommandFactory, Parser, ValueEnum, builder::PossibleValue};
-use open_timeline_crud::{db_url_from_path, restore, setup_database_at_path};
-use sqlx::{Connection, SqliteConnection};
+use open_timeline_core::{DateShift, HasIdAndName, OpenTimelineId};
+use open_timeline_crud::{
+    BulkEntitySelection, SyncConflictResolution, SyncItemKind, SyncRemote, db_url_from_path,
+    restore, setup_database_at_path, shift_entity_dates, sync,
+};
+use open_timeline_import::{Importers, save_import};
+use open_timeline_scripting::run_script;
+use sqlx::{Connection, SqliteConnection, SqlitePool};
 use std::path::PathBuf;
 
 /// OpenTimeline entry point
@@ -16,6 +23,10 @@ use std::path::PathBuf;
 /// One of:
 /// - Backup the database
 /// - Restore the database
+/// - Shift the dates of many entities at once
+/// - Sync the database with a remote OpenTimeline API
+/// - Run a script that edits the database
+/// - Import entities & timelines from other formats
 /// - Serve the website and API
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -83,6 +94,304 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         (Command::Stats, _database, _) => {
             todo!()
         }
+        (Command::ShiftDates, database, _) => {
+            // Which entities to shift
+            let selection = match (&args.bool_expr, &args.timeline) {
+                (Some(bool_expr), None) => match BoolTagExpr::from(bool_expr.to_owned()) {
+                    Ok(bool_expr) => BulkEntitySelection::BoolExpr(bool_expr),
+                    Err(error) => {
+                        eprintln!("Error parsing boolean expression: {error}");
+                        std::process::exit(1);
+                    }
+                },
+                (None, Some(timeline)) => match OpenTimelineId::from(timeline) {
+                    Ok(timeline_id) => BulkEntitySelection::Timeline(timeline_id),
+                    Err(error) => {
+                        eprintln!("Error parsing timeline ID: {error}");
+                        std::process::exit(1);
+                    }
+                },
+                _ => {
+                    eprintln!("CLI Error: give one of --bool-expr or --timeline");
+                    std::process::exit(1);
+                }
+            };
+            let shift = DateShift::from(args.years, args.months, args.days);
+
+            // Open database connection
+            let db_url = db_url_from_path(database);
+            let mut connection = match SqliteConnection::connect(&db_url).await {
+                Ok(connection) => connection,
+                Err(error) => {
+                    eprintln!("Error connecting to database: {error}");
+                    std::process::exit(1);
+                }
+            };
+
+            // Begin database transaction (all entities are shifted, or none)
+            let mut transaction: sqlx::Transaction<'_, sqlx::Sqlite> =
+                match connection.begin().await {
+                    Ok(transaction) => transaction,
+                    Err(error) => {
+                        eprintln!("Error starting transaction: {error}");
+                        std::process::exit(1);
+                    }
+                };
+
+            // Shift the dates
+            let shifted = match selection.fetch_entity_ids(&mut transaction).await {
+                Ok(entity_ids) => shift_entity_dates(&mut transaction, &entity_ids, &shift).await,
+                Err(error) => Err(error),
+            };
+            let shifted = match shifted {
+                Ok(shifted) => shifted,
+                Err(error) => {
+                    eprintln!("Error shifting dates: {error}");
+                    std::process::exit(1);
+                }
+            };
+            for shifted in &shifted {
+                println!(
+                    "{}: {} -> {}",
+                    shifted.name,
+                    shifted.start.as_era_date_format(),
+                    shifted.new_start.as_era_date_format()
+                );
+            }
+
+            // Commit the transaction (unless it's a dry run)
+            if args.dry_run {
+                println!("Dry run: {} entities would be shifted", shifted.len());
+            } else {
+                match transaction.commit().await {
+                    Ok(()) => println!("Successfully shifted {} entities", shifted.len()),
+                    Err(error) => {
+                        eprintln!("Error committing transaction: {error}");
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+        (Command::Sync, database, _) => {
+            // Where to sync with, and how to resolve conflicts
+            let Some(remote) = &args.remote else {
+                eprintln!("CLI Error: give --remote");
+                std::process::exit(1);
+            };
+            let remote = SyncRemote::new(remote, args.token.to_owned());
+            let resolution = match args.prefer.as_deref() {
+                None => SyncConflictResolution::Skip,
+                Some("local") => SyncConflictResolution::PreferLocal,
+                Some("remote") => SyncConflictResolution::PreferRemote,
+                Some(_) => {
+                    eprintln!("CLI Error: --prefer must be one of local or remote");
+                    std::process::exit(1);
+                }
+            };
+
+            // Open database connection
+            let db_url = db_url_from_path(database);
+            let mut connection = match SqliteConnection::connect(&db_url).await {
+                Ok(connection) => connection,
+                Err(error) => {
+                    eprintln!("Error connecting to database: {error}");
+                    std::process::exit(1);
+                }
+            };
+
+            // Begin database transaction
+            let mut transaction: sqlx::Transaction<'_, sqlx::Sqlite> =
+                match connection.begin().await {
+                    Ok(transaction) => transaction,
+                    Err(error) => {
+                        eprintln!("Error starting transaction: {error}");
+                        std::process::exit(1);
+                    }
+                };
+
+            // Sync
+            let report = match sync(&mut transaction, &remote, resolution).await {
+                Ok(report) => report,
+                Err(error) => {
+                    eprintln!("Error syncing with {}: {error}", remote.url());
+                    std::process::exit(1);
+                }
+            };
+            for conflict in &report.conflicts {
+                let kind = match conflict.kind {
+                    SyncItemKind::Entity => "entity",
+                    SyncItemKind::Timeline => "timeline",
+                };
+                println!(
+                    "Conflict (changed on both sides, skipped): {kind} '{}' ({})",
+                    conflict.name, conflict.id
+                );
+            }
+
+            // Commit the transaction
+            match transaction.commit().await {
+                Ok(()) => println!(
+                    "Successfully synced: pulled {} entities & {} timelines, pushed {} entities & {} timelines, {} conflicts",
+                    report.pulled_entities,
+                    report.pulled_timelines,
+                    report.pushed_entities,
+                    report.pushed_timelines,
+                    report.conflicts.len()
+                ),
+                Err(error) => {
+                    eprintln!("Error committing transaction: {error}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        (Command::Script, database, _) => {
+            // Read the script
+            let Some(script_path) = &args.script else {
+                eprintln!("CLI Error: give --script");
+                std::process::exit(1);
+            };
+            let script = match std::fs::read_to_string(script_path) {
+                Ok(script) => script,
+                Err(error) => {
+                    eprintln!("Error reading script: {error}");
+                    std::process::exit(1);
+                }
+            };
+
+            // Open database connection
+            let db_url = db_url_from_path(database);
+            let pool = match SqlitePool::connect(&db_url).await {
+                Ok(pool) => pool,
+                Err(error) => {
+                    eprintln!("Error connecting to database: {error}");
+                    std::process::exit(1);
+                }
+            };
+
+            // Run the script (all changes are made, or none)
+            let report = match run_script(&pool, &script, args.dry_run).await {
+                Ok(report) => report,
+                Err(error) => {
+                    eprintln!("Error running script: {error}");
+                    std::process::exit(1);
+                }
+            };
+            for line in &report.output {
+                println!("{line}");
+            }
+            for change in &report.changes {
+                println!("{change}");
+            }
+            if let Some(error) = &report.error {
+                eprintln!("Script error (nothing was changed): {error}");
+                std::process::exit(1);
+            }
+            if report.committed {
+                println!("Successfully made {} changes", report.changes.len());
+            } else {
+                println!("Dry run: {} changes would be made", report.changes.len());
+            }
+        }
+        (Command::Import, database, _) => {
+            // Which importer to use
+            let Some(input) = &args.input else {
+                eprintln!("CLI Error: give --input");
+                std::process::exit(1);
+            };
+            let importers = Importers::new();
+            let importer = match &args.format {
+                Some(format) => importers.by_name(format),
+                None => importers.for_path(input),
+            };
+            let importer = match importer {
+                Ok(importer) => importer,
+                Err(error) => {
+                    eprintln!("{error}.  Give --format as one of:");
+                    for importer in importers.iter() {
+                        eprintln!("  {}: {}", importer.name(), importer.description());
+                    }
+                    std::process::exit(1);
+                }
+            };
+
+            // Parse the input
+            let import = match std::fs::read(input) {
+                Ok(bytes) => importer.parse(&bytes),
+                Err(error) => Err(error.into()),
+            };
+            let import = match import {
+                Ok(import) => import,
+                Err(error) => {
+                    eprintln!("Error importing {}: {error}", input.display());
+                    std::process::exit(1);
+                }
+            };
+            for issue in &import.issues {
+                println!("Left out: {issue}");
+            }
+            if args.dry_run {
+                for entity in &import.entities {
+                    println!("Entity: '{}'", entity.name());
+                }
+                for timeline in &import.timelines {
+                    println!("Timeline: '{}'", timeline.name());
+                }
+            }
+
+            // Open database connection
+            let db_url = db_url_from_path(database);
+            let mut connection = match SqliteConnection::connect(&db_url).await {
+                Ok(connection) => connection,
+                Err(error) => {
+                    eprintln!("Error connecting to database: {error}");
+                    std::process::exit(1);
+                }
+            };
+
+            // Begin database transaction (everything is imported, or nothing)
+            let mut transaction: sqlx::Transaction<'_, sqlx::Sqlite> =
+                match connection.begin().await {
+                    Ok(transaction) => transaction,
+                    Err(error) => {
+                        eprintln!("Error starting transaction: {error}");
+                        std::process::exit(1);
+                    }
+                };
+
+            // Save
+            let report = match save_import(&mut transaction, import).await {
+                Ok(report) => report,
+                Err(error) => {
+                    eprintln!("Error saving import: {error}");
+                    std::process::exit(1);
+                }
+            };
+            for name in &report.skipped_entities {
+                println!("Skipped (already exists): entity '{name}'");
+            }
+            for name in &report.skipped_timelines {
+                println!("Skipped (already exists): timeline '{name}'");
+            }
+
+            // Commit the transaction (unless it's a dry run)
+            if args.dry_run {
+                println!(
+                    "Dry run: {} entities & {} timelines would be imported",
+                    report.created_entities, report.created_timelines
+                );
+            } else {
+                match transaction.commit().await {
+                    Ok(()) => println!(
+                        "Successfully imported {} entities & {} timelines",
+                        report.created_entities, report.created_timelines
+                    ),
+                    Err(error) => {
+                        eprintln!("Error committing transaction: {error}");
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
         //----------------------------------------------------------------------
         // Invalid
         //----------------------------------------------------------------------
@@ -115,6 +424,58 @@ pub struct Cli {
     /// Path to the dir that contains the JSON
     #[arg(long)]
     pub json: Option<PathBuf>,
+
+    /// Shift the dates of the entities that match this boolean tag expression
+    #[arg(long)]
+    pub bool_expr: Option<String>,
+
+    /// Shift the dates of the entities in the timeline with this ID (or any of
+    /// its subtimelines)
+    #[arg(long)]
+    pub timeline: Option<String>,
+
+    /// The number of years to shift dates by (negative is earlier)
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub years: i64,
+
+    /// The number of months to shift dates by (negative is earlier)
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub months: i64,
+
+    /// The number of days to shift dates by (negative is earlier)
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub days: i64,
+
+    /// Show what would be shifted (or changed by the script, or imported)
+    /// without changing the database
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// The URL of the site serving the OpenTimeline API to sync with
+    #[arg(long)]
+    pub remote: Option<String>,
+
+    /// The token to send to the remote API (if it needs one)
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// How to resolve entities & timelines changed on both sides since the
+    /// last sync ("local" or "remote").  They're skipped if not given
+    #[arg(long)]
+    pub prefer: Option<String>,
+
+    /// Path to the Rhai script to run
+    #[arg(long)]
+    pub script: Option<PathBuf>,
+
+    /// Path to the file to import
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+
+    /// The format of the file to import (worked out from the file extension if
+    /// not given)
+    #[arg(long)]
+    pub format: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -124,6 +485,10 @@ pub enum Command {
     Restore,
     Merge,
     Stats,
+    ShiftDates,
+    Sync,
+    Script,
+    Import,
 }
 
 impl ValueEnum for Command {
@@ -134,6 +499,10 @@ impl ValueEnum for Command {
             Self::Restore,
             Self::Merge,
             Self::Stats,
+            Self::ShiftDates,
+            Self::Sync,
+            Self::Script,
+            Self::Import,
         ]
     }
 
@@ -155,6 +524,22 @@ impl ValueEnum for Command {
                     .help("Merge into the database at path the JSON in dir at path"),
             ),
             Command::Stats => Some(PossibleValue::new("stats").help("Print database stats")),
+            Command::ShiftDates => Some(
+                PossibleValue::new("shift-dates")
+                    .help("Shift the dates of the entities matching --bool-expr or in --timeline"),
+            ),
+            Command::Sync => Some(
+                PossibleValue::new("sync")
+                    .help("Two-way sync the database at path with the API at --remote"),
+            ),
+            Command::Script => Some(
+                PossibleValue::new("script")
+                    .help("Run the Rhai script at --script against the database at path"),
+            ),
+            Command::Import => Some(
+                PossibleValue::new("import")
+                    .help("Import the entities & timelines in the file at --input (see --format)"),
+            ),
         }
     }
 }
@@ -5,29 +5,26 @@
 //!
 
 use eframe::egui::{IconData, ViewportBuilder};
-use open_timeline_gui::{DEFAULT_WINDOW_SIZES, OpenTimelineApp};
-use simplelog::{
-    ColorChoice, CombinedLogger, ConfigBuilder, LevelFilter, TermLogger, TerminalMode,
+use open_timeline_gui::{
+    ActivityLog, Config, DEFAULT_LOG_LEVELS, DEFAULT_WINDOW_SIZES, OpenTimelineApp,
 };
-
-#[macro_use]
-extern crate log;
-extern crate simplelog;
+use tracing::info;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
 
 /// Entry point for the native GUI desktop application
 fn main() -> Result<(), eframe::Error> {
-    // Setup logging
-    let config_log = ConfigBuilder::new()
-        .add_filter_allow_str("open_timeline")
-        .build();
-
-    CombinedLogger::init(vec![TermLogger::new(
-        LevelFilter::Debug,
-        config_log,
-        TerminalMode::Mixed,
-        ColorChoice::Auto,
-    )])
-    .unwrap();
+    // Setup logging (to the terminal at the configured levels, & to the
+    // activity log shown in the app)
+    let log_filter = match Config::load() {
+        Ok(config) => config.log_filter(),
+        Err(_) => DEFAULT_LOG_LEVELS.parse::<Targets>().unwrap(),
+    };
+    let activity_log = ActivityLog::new();
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(log_filter))
+        .with(activity_log.layer())
+        .init();
 
     // Create a new tokio runtime so that we can use `tokio::spawn` elsewhere
     // without requiring every function be `async` (waiting is not acceptable
@@ -47,7 +44,7 @@ fn main() -> Result<(), eframe::Error> {
     });
 
     // Create the OpenTimeline application
-    let open_timeline_app = OpenTimelineApp::new();
+    let open_timeline_app = OpenTimelineApp::new(activity_log);
 
     // Setup the main window's default options
     let main_viewport_options = ViewportBuilder::default()
@@ -5,12 +5,13 @@
 //!
 
 use clap::{CommandFactory, Parser};
-use open_timeline_www_api::{ApiAccessMode, ApiMode, prepare_api_router};
+use open_timeline_www_api::{
+    ApiAccessMode, ApiConfig, ApiConfigError, ApiMode, CorsAllowedOrigins, ID_SCHEME_ENV_VAR,
+    LOG_LEVELS_ENV_VAR, from_config, parse_log_levels,
+};
 use std::path::PathBuf;
-
-#[macro_use]
-extern crate log;
-extern crate simplelog;
+use tracing::info;
+use tracing_subscriber::prelude::*;
 
 /// OpenTimeline www API entry point (serve the www JSON API)
 #[tokio::main]
@@ -18,49 +19,83 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Cli::parse();
 
     // Check the options
-    match (&args.database, &args.read_only, &args.dynamic) {
+    let config = match (&args.config, &args.database, &args.read_only, &args.dynamic) {
         //----------------------------------------------------------------------
-        // Invalid
-        // TODO: update the read_only part
+        // Config file (& environment variables)
+        //----------------------------------------------------------------------
+        (Some(config), _, _, _) => ApiConfig::load(Some(config))?,
         //----------------------------------------------------------------------
-        (database, Some(read_only), Some(dynamic)) => {
-            let db_url = format!("sqlite://{}", database.to_string_lossy());
-            serve(&db_url, *read_only, *dynamic).await
+        // CLI options
+        //----------------------------------------------------------------------
+        (None, Some(database), Some(read_only), Some(dynamic)) => {
+            // The CLI option takes precedence over the environment variable
+            let cors_allowed_origins = match &args.cors_allowed_origins {
+                Some(origins) => CorsAllowedOrigins::parse(origins)?,
+                None => CorsAllowedOrigins::from_env()?,
+            };
+            // The CLI option takes precedence over the environment variable
+            let log_levels = match args
+                .log_levels
+                .clone()
+                .or_else(|| std::env::var(LOG_LEVELS_ENV_VAR).ok())
+            {
+                Some(log_levels) => parse_log_levels(&log_levels)?,
+                None => ApiConfig::new(database.clone()).log_levels,
+            };
+            // The CLI option takes precedence over the environment variable
+            let id_scheme = match args
+                .id_scheme
+                .clone()
+                .or_else(|| std::env::var(ID_SCHEME_ENV_VAR).ok())
+            {
+                Some(id_scheme) => id_scheme
+                    .parse()
+                    .map_err(|_| ApiConfigError::IdScheme(id_scheme))?,
+                None => ApiConfig::new(database.clone()).id_scheme,
+            };
+            ApiConfig {
+                access_mode: if *read_only {
+                    ApiAccessMode::Read
+                } else {
+                    ApiAccessMode::ReadWrite
+                },
+                api_mode: if *dynamic {
+                    ApiMode::Dynamic
+                } else {
+                    ApiMode::Static
+                },
+                cors_allowed_origins,
+                log_levels,
+                id_scheme,
+                ..ApiConfig::new(database.clone())
+            }
         }
         //----------------------------------------------------------------------
         // Invalid
         //----------------------------------------------------------------------
         _ => {
-            error!("CLI Error: invalid options");
+            eprintln!("CLI Error: invalid options");
             Cli::command().print_long_help().unwrap();
             std::process::exit(1);
         }
-    }
+    };
 
+    // Setup logging (including the structured request logs)
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(config.log_levels.clone()))
+        .init();
+
+    serve(&config).await;
     Ok(())
 }
 
 /// Serve the website and API
-async fn serve(db_url: &str, read_only: bool, dynamic: bool) {
-    // Setup up the API modes
-    let access_mode = if read_only {
-        ApiAccessMode::Read
-    } else {
-        ApiAccessMode::ReadWrite
-    };
-    let api_mode = if dynamic {
-        ApiMode::Dynamic
-    } else {
-        ApiMode::Static
-    };
-
+async fn serve(config: &ApiConfig) {
     // Get the router
-    let api_router = prepare_api_router(db_url, access_mode, api_mode)
-        .await
-        .unwrap();
+    let api_router = from_config(config).await.unwrap();
 
-    // Specify the IP addr and port number
-    let addr = "0.0.0.0:2408";
+    // The IP addr and port number
+    let addr = config.bind_address;
 
     // Bind the listener for new connections
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
@@ -80,9 +115,15 @@ async fn serve(db_url: &str, read_only: bool, dynamic: bool) {
     after_help = "This is intended for use when deploying to a server and in CI"
 )]
 pub struct Cli {
+    /// Path to a TOML config file (see the `open_timeline_www_api::ApiConfig`
+    /// docs).  Environment variables override its settings.  If given, the
+    /// other options are ignored.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
     /// Path to the database
     #[arg(long)]
-    pub database: PathBuf,
+    pub database: Option<PathBuf>,
 
     /// Whether the database should be read-only
     ///
@@ -100,4 +141,30 @@ pub struct Cli {
     /// rather than `--dynamic`
     #[arg(long)]
     pub dynamic: Option<bool>,
+
+    /// Origins that browsers may call the API from (comma separated, or `*`
+    /// for any origin)
+    ///
+    /// If not given, the origins are read from the
+    /// `OPEN_TIMELINE_CORS_ALLOWED_ORIGINS` environment variable.  By default
+    /// no other origins are allowed.
+    #[arg(long)]
+    pub cors_allowed_origins: Option<String>,
+
+    /// The level logged at for each module (comma separated `module=level`,
+    /// e.g. `open_timeline=info,sqlx::query=debug`)
+    ///
+    /// If not given, the levels are read from the
+    /// `OPEN_TIMELINE_API_LOG_LEVELS` environment variable.  By default
+    /// `open_timeline=info` is used.
+    #[arg(long)]
+    pub log_levels: Option<String>,
+
+    /// How the IDs of new entities & timelines are generated (`random` or
+    /// `time-sortable`)
+    ///
+    /// If not given, the scheme is read from the `OPEN_TIMELINE_API_ID_SCHEME`
+    /// environment variable.  By default IDs are random.
+    #[arg(long)]
+    pub id_scheme: Option<String>,
 }
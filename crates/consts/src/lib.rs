@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! *Part of the wider OpenTimeline project*
+//!
+//! This crate holds the bounds of the OpenTimeline date types.  They are used
+//! both by the core crate (when validating at runtime) and by the macros crate
+//! (when checking literals at compile time), which can't depend on the core
+//! crate as the core crate depends on it.  Having them in one place means the
+//! two can't drift apart.
+//!
+//! Anything outside of the OpenTimeline project should use the bounds as
+//! re-exported by the core crate.
+//!
+
+/// The minimum day allowed in the OpenTimeline system
+pub const MIN_DAY: i64 = 1;
+
+/// The maximum day allowed in the OpenTimeline system
+pub const MAX_DAY: i64 = 31;
+
+/// The minimum month allowed in the OpenTimeline system
+pub const MIN_MONTH: i64 = 1;
+
+/// The maximum month allowed in the OpenTimeline system
+pub const MAX_MONTH: i64 = 12;
+
+/// The minimum year allowed in the OpenTimeline system
+pub const MIN_YEAR: i64 = -50000;
+
+/// The maximum year allowed in the OpenTimeline system
+pub const MAX_YEAR: i64 = 10000;
+
+/// Whether the day is within [`MIN_DAY`] and [`MAX_DAY`] (inclusive)
+pub const fn is_valid_day(day: i64) -> bool {
+    MIN_DAY <= day && day <= MAX_DAY
+}
+
+/// Whether the month is within [`MIN_MONTH`] and [`MAX_MONTH`] (inclusive)
+pub const fn is_valid_month(month: i64) -> bool {
+    MIN_MONTH <= month && month <= MAX_MONTH
+}
+
+/// Whether the year is within [`MIN_YEAR`] and [`MAX_YEAR`] (inclusive)
+pub const fn is_valid_year(year: i64) -> bool {
+    MIN_YEAR <= year && year <= MAX_YEAR
+}
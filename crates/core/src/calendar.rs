@@ -0,0 +1,248 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Julian and Gregorian calendar support for historical dates
+//!
+
+use crate::{Date, DateError};
+use serde::{Deserialize, Serialize};
+
+/// The first day of the Gregorian calendar (15 Oct 1582)
+const GREGORIAN_REFORM: (i64, i64, i64) = (1582, 10, 15);
+
+/// The year the Julian calendar came into use (45 BCE)
+const JULIAN_INTRODUCTION_YEAR: i64 = -45;
+
+/// The calendar a [`Date`] was recorded in.  The arithmetic treats years as
+/// astronomical years (i.e. there is a year 0).
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(rename_all = "lowercase"))]
+pub enum Calendar {
+    /// The Gregorian calendar (proleptic before 15 Oct 1582)
+    #[default]
+    Gregorian,
+
+    /// The Julian calendar (proleptic before 45 BCE)
+    Julian,
+}
+
+impl Calendar {
+    /// All calendars (useful for drawing a picker)
+    pub const ALL: [Calendar; 2] = [Calendar::Gregorian, Calendar::Julian];
+
+    /// The name of the calendar
+    pub fn name(&self) -> &'static str {
+        match self {
+            Calendar::Gregorian => "Gregorian",
+            Calendar::Julian => "Julian",
+        }
+    }
+}
+
+impl Date {
+    /// Convert the [`Date`] to the given calendar (dates without a calendar are
+    /// treated as Gregorian).  Only dates with a day and month can be
+    /// converted exactly - partial dates keep their fields and just change
+    /// calendar.
+    pub fn to_calendar(&self, calendar: Calendar) -> Result<Date, DateError> {
+        let from = self.calendar().unwrap_or_default();
+        let (Some(day), Some(month)) = (self.day(), self.month()) else {
+            return Ok(self.with_calendar(Some(calendar)));
+        };
+        if from == calendar {
+            return Ok(self.with_calendar(Some(calendar)));
+        }
+        let year = i64::from(self.year().value());
+        let jdn = julian_day_number(from, year, month.value().into(), day.value().into());
+        let (year, month, day) = date_from_julian_day_number(calendar, jdn);
+        Ok(Date::from(Some(day), Some(month), year)?.with_calendar(Some(calendar)))
+    }
+
+    /// The (proleptic) Gregorian equivalent of the [`Date`], which is what
+    /// dates are ordered and positioned by.  A date that would fall outside
+    /// the allowed years when converted is returned unchanged.
+    pub fn to_gregorian(&self) -> Date {
+        match self.calendar() {
+            None | Some(Calendar::Gregorian) => *self,
+            Some(Calendar::Julian) => self.to_calendar(Calendar::Gregorian).unwrap_or(*self),
+        }
+    }
+
+    /// Whether the [`Date`] is before its calendar came into use
+    pub fn is_proleptic(&self) -> bool {
+        let year = i64::from(self.year().value());
+        match self.calendar().unwrap_or_default() {
+            Calendar::Gregorian => {
+                let month = self.month().map_or(1, |month| month.value().into());
+                let day = self.day().map_or(1, |day| day.value().into());
+                (year, month, day) < GREGORIAN_REFORM
+            }
+            Calendar::Julian => year < JULIAN_INTRODUCTION_YEAR,
+        }
+    }
+
+    /// The day of the year (1st Jan is day 1) in the [`Date`]'s calendar,
+    /// accounting for month lengths and leap years.  A missing month or day is
+    /// taken to be the first.
+    pub fn day_of_year(&self) -> i64 {
+        let calendar = self.calendar().unwrap_or_default();
+        let year = i64::from(self.year().value());
+        let month = self.month().map_or(1, |month| month.value().into());
+        let day = self.day().map_or(1, |day| day.value().into());
+        julian_day_number(calendar, year, month, day) - julian_day_number(calendar, year, 1, 1) + 1
+    }
+
+    /// The number of days in the [`Date`]'s year (in its calendar)
+    pub fn days_in_year(&self) -> i64 {
+        let calendar = self.calendar().unwrap_or_default();
+        let year = i64::from(self.year().value());
+        julian_day_number(calendar, year + 1, 1, 1) - julian_day_number(calendar, year, 1, 1)
+    }
+
+    /// How far through its year the start of the [`Date`] is (0 at the start
+    /// of 1st Jan, approaching 1 at the end of 31st Dec), e.g. for
+    /// positioning it on a timeline
+    pub fn fraction_of_year(&self) -> f64 {
+        (self.day_of_year() - 1) as f64 / self.days_in_year() as f64
+    }
+}
+
+/// The Julian day number of the date in the given calendar
+pub(crate) fn julian_day_number(calendar: Calendar, year: i64, month: i64, day: i64) -> i64 {
+    let a = (14 - month).div_euclid(12);
+    let y = year + 4800 - a;
+    let m = month + 12 * a - 3;
+    let days = day + (153 * m + 2).div_euclid(5) + 365 * y + y.div_euclid(4);
+    match calendar {
+        Calendar::Gregorian => days - y.div_euclid(100) + y.div_euclid(400) - 32045,
+        Calendar::Julian => days - 32083,
+    }
+}
+
+/// The number of days in the month in the given calendar
+pub(crate) fn days_in_month(calendar: Calendar, year: i64, month: i64) -> i64 {
+    let (next_year, next_month) = match month {
+        12 => (year + 1, 1),
+        _ => (year, month + 1),
+    };
+    julian_day_number(calendar, next_year, next_month, 1)
+        - julian_day_number(calendar, year, month, 1)
+}
+
+/// The year, month and day in the given calendar of the Julian day number
+pub(crate) fn date_from_julian_day_number(calendar: Calendar, jdn: i64) -> (i64, i64, i64) {
+    let (b, c) = match calendar {
+        Calendar::Gregorian => {
+            let a = jdn + 32044;
+            let b = (4 * a + 3).div_euclid(146097);
+            (b, a - (146097 * b).div_euclid(4))
+        }
+        Calendar::Julian => (0, jdn + 32082),
+    };
+    let d = (4 * c + 3).div_euclid(1461);
+    let e = c - (1461 * d).div_euclid(4);
+    let m = (5 * e + 2).div_euclid(153);
+    let day = e - (153 * m + 2).div_euclid(5) + 1;
+    let month = m + 3 - 12 * m.div_euclid(10);
+    let year = 100 * b + d - 4800 + m.div_euclid(10);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Calendar;
+    use crate::Date;
+
+    fn date(day: i64, month: i64, year: i64, calendar: Option<Calendar>) -> Date {
+        Date::from(Some(day), Some(month), year)
+            .unwrap()
+            .with_calendar(calendar)
+    }
+
+    #[test]
+    fn convert() {
+        // The day after 4 Oct 1582 (Julian) was 15 Oct 1582 (Gregorian)
+        let julian = date(5, 10, 1582, Some(Calendar::Julian));
+        let gregorian = date(15, 10, 1582, Some(Calendar::Gregorian));
+        assert_eq!(julian.to_calendar(Calendar::Gregorian).unwrap(), gregorian);
+        assert_eq!(gregorian.to_calendar(Calendar::Julian).unwrap(), julian);
+
+        // Newton was born on 25 Dec 1642 (Julian), i.e. 4 Jan 1643 (Gregorian)
+        let newton = date(25, 12, 1642, Some(Calendar::Julian));
+        assert_eq!(
+            newton.to_gregorian(),
+            date(4, 1, 1643, Some(Calendar::Gregorian))
+        );
+
+        // Partial dates keep their fields
+        let year_only = Date::from(None, None, 1500)
+            .unwrap()
+            .with_calendar(Some(Calendar::Julian));
+        assert_eq!(year_only.to_gregorian().year().value(), 1500);
+
+        // Dates without a calendar are Gregorian already
+        let unspecified = date(1, 1, 1000, None);
+        assert_eq!(unspecified.to_gregorian(), unspecified);
+    }
+
+    #[test]
+    fn order_across_calendars() {
+        // 1 Jan 1600 (Julian) is 11 Jan 1600 (Gregorian)
+        let julian = date(1, 1, 1600, Some(Calendar::Julian));
+        let gregorian = date(5, 1, 1600, None);
+        assert!(julian > gregorian);
+        assert_eq!(julian.cmp(&gregorian), std::cmp::Ordering::Greater);
+    }
+
+    #[test]
+    fn is_proleptic() {
+        assert!(date(14, 10, 1582, None).is_proleptic());
+        assert!(!date(15, 10, 1582, Some(Calendar::Gregorian)).is_proleptic());
+        assert!(!date(1, 1, 1000, Some(Calendar::Julian)).is_proleptic());
+        assert!(date(1, 1, -100, Some(Calendar::Julian)).is_proleptic());
+    }
+
+    #[test]
+    fn day_of_year_matches_chrono() {
+        use chrono::{Datelike, NaiveDate};
+
+        // Every day of leap & common years (including century years)
+        for year in [1900, 2000, 2023, 2024, -4, 1] {
+            let mut chrono_date = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+            while chrono_date.year() == year {
+                let date = date(
+                    chrono_date.day().into(),
+                    chrono_date.month().into(),
+                    year.into(),
+                    None,
+                );
+                assert_eq!(date.day_of_year(), i64::from(chrono_date.ordinal()));
+                assert_eq!(
+                    date.days_in_year(),
+                    if chrono_date.leap_year() { 366 } else { 365 }
+                );
+                let fraction = f64::from(chrono_date.ordinal0()) / date.days_in_year() as f64;
+                assert_eq!(date.fraction_of_year(), fraction);
+                chrono_date = chrono_date.succ_opt().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn day_of_year_of_partial_and_julian_dates() {
+        // A missing month or day is the first
+        assert_eq!(Date::from(None, None, 1900).unwrap().day_of_year(), 1);
+        assert_eq!(Date::from(None, Some(3), 2024).unwrap().day_of_year(), 61);
+        assert_eq!(
+            Date::from(None, None, 2024).unwrap().fraction_of_year(),
+            0.0
+        );
+
+        // 1900 is a leap year in the Julian calendar (but not the Gregorian)
+        assert_eq!(date(1, 3, 1900, Some(Calendar::Julian)).day_of_year(), 61);
+        assert_eq!(date(1, 3, 1900, None).day_of_year(), 60);
+        assert_eq!(date(1, 1, 1900, Some(Calendar::Julian)).days_in_year(), 366);
+    }
+}
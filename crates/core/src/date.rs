@@ -4,24 +4,27 @@
 //! The OpenTimeline date type
 //!
 
+use crate::Calendar;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::cmp::Ordering;
 use thiserror::Error;
 
-/// The minimum year allowed in the OpenTimeline system
-pub const MIN_YEAR: i64 = -50000;
-
-/// The maximum year allowed in the OpenTimeline system
-pub const MAX_YEAR: i64 = 10000;
+// The bounds are shared with the macros crate (so that compile time checks
+// match the runtime validation)
+pub use open_timeline_consts::{
+    MAX_DAY, MAX_MONTH, MAX_YEAR, MIN_DAY, MIN_MONTH, MIN_YEAR, is_valid_day, is_valid_month,
+    is_valid_year,
+};
 
 /// Errors that can arise in relation to a [`Date`]
-#[derive(Error, Debug, Clone)]
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum DateError {
-    /// The day number is not allowed (must be 1 <= day <= 31)
+    /// The day number is not allowed (must be [`MIN_DAY`] <= day <= [`MAX_DAY`])
     #[error("Day `{0}` is not allowed")]
     InvalidDay(i64),
 
-    /// The month number is not allowed (must be 1 <= day <= 12)
+    /// The month number is not allowed (must be [`MIN_MONTH`] <= month <=
+    /// [`MAX_MONTH`])
     #[error("Month `{0}` is not allowed")]
     InvalidMonth(i64),
 
@@ -40,11 +43,16 @@ pub enum DateError {
 /// The year field must be set but the day and month fields are optional.  If
 /// the day field is set the month field must be set, but if the month field is
 /// set, the day field is optional.
+///
+/// The calendar the date was recorded in is optional.  Dates without one are
+/// treated as (proleptic) Gregorian dates.
 #[derive(Serialize, PartialEq, Eq, Clone, Copy, Debug, Hash)]
 pub struct Date {
     day: Option<Day>,
     month: Option<Month>,
     year: Year,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    calendar: Option<Calendar>,
 }
 
 /// The OpenTimeline day type
@@ -115,7 +123,7 @@ impl Year {
 impl TryFrom<i64> for Day {
     type Error = DateError;
     fn try_from(value: i64) -> Result<Self, Self::Error> {
-        if (1..=31).contains(&value) {
+        if is_valid_day(value) {
             Ok(Day(value as u8))
         } else {
             Err(DateError::InvalidDay(value))
@@ -126,7 +134,7 @@ impl TryFrom<i64> for Day {
 impl TryFrom<i64> for Month {
     type Error = DateError;
     fn try_from(value: i64) -> Result<Self, Self::Error> {
-        if (1..=12).contains(&value) {
+        if is_valid_month(value) {
             Ok(Month(value as u8))
         } else {
             Err(DateError::InvalidMonth(value))
@@ -137,7 +145,7 @@ impl TryFrom<i64> for Month {
 impl TryFrom<i64> for Year {
     type Error = DateError;
     fn try_from(value: i64) -> Result<Self, Self::Error> {
-        if (MIN_YEAR..=MAX_YEAR).contains(&value) {
+        if is_valid_year(value) {
             Ok(Year(value as i32))
         } else {
             Err(DateError::InvalidYear(value))
@@ -191,6 +199,7 @@ impl Date {
             day: None,
             month: None,
             year: Year(0),
+            calendar: None,
         };
         date.set_year(year)?;
         date.set_month(month)?;
@@ -313,6 +322,34 @@ impl Date {
         self.year
     }
 
+    /// Get the calendar the [`Date`] was recorded in (if specified)
+    pub fn calendar(&self) -> Option<Calendar> {
+        self.calendar
+    }
+
+    /// Set the calendar the [`Date`] was recorded in (the day, month and year
+    /// are unchanged - see [`Date::to_calendar()`] for converting)
+    pub fn set_calendar(&mut self, calendar: Option<Calendar>) {
+        self.calendar = calendar;
+    }
+
+    /// The same [`Date`] recorded in the given calendar (the day, month and
+    /// year are unchanged - see [`Date::to_calendar()`] for converting)
+    pub fn with_calendar(mut self, calendar: Option<Calendar>) -> Self {
+        self.calendar = calendar;
+        self
+    }
+
+    /// Both dates in the same calendar, so that they can be compared
+    fn comparable_with(&self, other: &Self) -> (Self, Self) {
+        let calendar = self.calendar.unwrap_or_default();
+        if calendar == other.calendar.unwrap_or_default() {
+            (*self, *other)
+        } else {
+            (self.to_gregorian(), other.to_gregorian())
+        }
+    }
+
     /// Check if the [`Date`] is valid
     fn is_valid(&self) -> Result<(), DateError> {
         match (self.day, self.month, self.year) {
@@ -329,12 +366,13 @@ impl Date {
 
 impl PartialOrd for Date {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self.year.cmp(&other.year) {
+        let (this, other) = self.comparable_with(other);
+        match this.year.cmp(&other.year) {
             Ordering::Less => return Some(Ordering::Less),
             Ordering::Greater => return Some(Ordering::Greater),
             Ordering::Equal => (),
         };
-        if let (Some(this_month), Some(other_month)) = (self.month, other.month) {
+        if let (Some(this_month), Some(other_month)) = (this.month, other.month) {
             match this_month.cmp(&other_month) {
                 Ordering::Less => return Some(Ordering::Less),
                 Ordering::Greater => return Some(Ordering::Greater),
@@ -343,7 +381,7 @@ impl PartialOrd for Date {
         } else {
             return None;
         }
-        if let (Some(this_day), Some(other_day)) = (self.day, other.day) {
+        if let (Some(this_day), Some(other_day)) = (this.day, other.day) {
             match this_day.cmp(&other_day) {
                 Ordering::Less => Some(Ordering::Less),
                 Ordering::Greater => Some(Ordering::Greater),
@@ -358,13 +396,14 @@ impl PartialOrd for Date {
 // Beware!
 impl Ord for Date {
     fn cmp(&self, other: &Self) -> Ordering {
-        let this_month = self.month().map(|m| m.value()).unwrap_or(1);
+        let (this, other) = self.comparable_with(other);
+        let this_month = this.month().map(|m| m.value()).unwrap_or(1);
         let other_month = other.month().map(|m| m.value()).unwrap_or(1);
 
-        let this_day = self.day().map(|d| d.value()).unwrap_or(1);
+        let this_day = this.day().map(|d| d.value()).unwrap_or(1);
         let other_day = other.day().map(|d| d.value()).unwrap_or(1);
 
-        (self.year, this_month, this_day).cmp(&(other.year, other_month, other_day))
+        (this.year, this_month, this_day).cmp(&(other.year, other_month, other_day))
     }
 }
 
@@ -373,6 +412,8 @@ struct RawDate {
     day: Option<i64>,
     month: Option<i64>,
     year: i64,
+    #[serde(default)]
+    calendar: Option<Calendar>,
 }
 
 impl<'de> Deserialize<'de> for Date {
@@ -384,7 +425,7 @@ impl<'de> Deserialize<'de> for Date {
         let raw_date = RawDate::deserialize(deserializer)?;
         let date = Date::from(raw_date.day, raw_date.month, raw_date.year);
         match date {
-            Ok(date) => Ok(date),
+            Ok(date) => Ok(date.with_calendar(raw_date.calendar)),
             Err(error) => Err(serde::de::Error::custom(error)),
         }
     }
@@ -392,7 +433,8 @@ impl<'de> Deserialize<'de> for Date {
 
 #[cfg(test)]
 mod test {
-    use super::Date;
+    use super::*;
+    use open_timeline_macros::year;
 
     #[test]
     fn from() {
@@ -407,6 +449,18 @@ mod test {
         assert!(Date::from(Some(1), Some(1), 1).is_ok());
     }
 
+    #[test]
+    fn bounds_match_the_macros() {
+        assert_eq!(year!(-50000), Year::min());
+        assert_eq!(year!(10000), Year::max());
+        assert_eq!(Year::min().value() as i64, MIN_YEAR);
+        assert_eq!(Year::max().value() as i64, MAX_YEAR);
+        assert!(Year::try_from(MIN_YEAR - 1).is_err());
+        assert!(Year::try_from(MAX_YEAR + 1).is_err());
+        assert!(Day::try_from(MAX_DAY).is_ok());
+        assert!(Month::try_from(MAX_MONTH + 1).is_err());
+    }
+
     #[test]
     fn cmp() {
         // Year only
@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Configurable ways of displaying [`Date`]s (e.g. "12 Mar 1945", "Mar 12,
+//! 1945", "1945-03-12", "500 BCE")
+//!
+
+use crate::{Calendar, Date};
+use serde::{Deserialize, Serialize};
+
+/// The abbreviated month names
+const MONTH_ABBREVIATIONS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// The order a date's day, month and year are displayed in
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum DateOrder {
+    /// e.g. 12 Mar 1945
+    #[default]
+    Dmy,
+
+    /// e.g. Mar 12, 1945
+    Mdy,
+
+    /// e.g. 1945-03-12 (months are always numbers)
+    Iso,
+}
+
+/// Whether months are displayed as names or numbers
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum MonthStyle {
+    /// e.g. 12 Mar 1945
+    #[default]
+    Name,
+
+    /// e.g. 12/3/1945
+    Number,
+}
+
+/// How years before year 0 are displayed
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EraStyle {
+    /// e.g. -500
+    #[default]
+    Negative,
+
+    /// e.g. 500 BCE
+    Bce,
+
+    /// e.g. 500 BC
+    Bc,
+}
+
+impl EraStyle {
+    /// The suffix for years before year 0 (if there is one)
+    fn suffix(&self) -> Option<&'static str> {
+        match self {
+            EraStyle::Negative => None,
+            EraStyle::Bce => Some("BCE"),
+            EraStyle::Bc => Some("BC"),
+        }
+    }
+}
+
+/// How [`Date`]s are displayed.  The default matches
+/// [`Date::as_long_date_format()`].
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(default)]
+pub struct DateFormat {
+    /// The order of the day, month and year
+    pub order: DateOrder,
+
+    /// Whether months are names or numbers
+    pub month_style: MonthStyle,
+
+    /// How years before year 0 are displayed
+    pub era_style: EraStyle,
+}
+
+impl DateFormat {
+    /// Display the [`Date`] in this format
+    pub fn format_date(&self, date: &Date) -> String {
+        let year = date.year().value();
+        let (year_str, era_suffix) = self.year_and_era_suffix(year);
+        let day = date.day().map(|day| day.value());
+        let month = date.month().map(|month| month.value());
+
+        let date_str = match (self.order, self.month_style) {
+            (DateOrder::Iso, _) => {
+                let mut parts = vec![year_str];
+                parts.extend(month.map(|month| format!("{month:02}")));
+                parts.extend(day.map(|day| format!("{day:02}")));
+                parts.join("-")
+            }
+            (order, MonthStyle::Number) => {
+                let mut parts = Vec::new();
+                if order == DateOrder::Mdy {
+                    parts.extend(month.map(|month| month.to_string()));
+                    parts.extend(day.map(|day| day.to_string()));
+                } else {
+                    parts.extend(day.map(|day| day.to_string()));
+                    parts.extend(month.map(|month| month.to_string()));
+                }
+                parts.push(year_str);
+                parts.join("/")
+            }
+            (DateOrder::Dmy, MonthStyle::Name) => {
+                let mut parts = Vec::new();
+                parts.extend(day.map(|day| day.to_string()));
+                parts.extend(month.map(month_abbreviation));
+                parts.push(year_str);
+                parts.join(" ")
+            }
+            (DateOrder::Mdy, MonthStyle::Name) => match (month, day) {
+                (Some(month), Some(day)) => {
+                    format!("{} {day}, {year_str}", month_abbreviation(month))
+                }
+                (Some(month), None) => format!("{} {year_str}", month_abbreviation(month)),
+                _ => year_str,
+            },
+        };
+
+        let date_str = match era_suffix {
+            Some(suffix) => format!("{date_str} {suffix}"),
+            None => date_str,
+        };
+        with_calendar_suffix(date_str, date)
+    }
+
+    /// Display a year in this format (e.g. 1945, 500 BCE)
+    pub fn format_year(&self, year: i32) -> String {
+        match self.year_and_era_suffix(year) {
+            (year, Some(suffix)) => format!("{year} {suffix}"),
+            (year, None) => year,
+        }
+    }
+
+    /// Display a decade in this format (e.g. 1940s, 500s BCE)
+    pub fn format_decade(&self, decade: i32) -> String {
+        match self.year_and_era_suffix(decade) {
+            (decade, Some(suffix)) => format!("{decade}s {suffix}"),
+            (decade, None) => format!("{decade}s"),
+        }
+    }
+
+    /// Display a month (which must be 1 <= month <= 12) in this format (e.g.
+    /// Mar, or 3 if months are numbers)
+    pub fn format_month(&self, month: u8) -> String {
+        match (self.order, self.month_style) {
+            (DateOrder::Iso, _) => format!("{month:02}"),
+            (_, MonthStyle::Number) => month.to_string(),
+            (_, MonthStyle::Name) => month_abbreviation(month),
+        }
+    }
+
+    /// Display a start and optional end date in this format (e.g. "12 Mar 1945
+    /// to 8 May 1945" or "12 Mar 1945 onwards")
+    pub fn format_date_range(&self, start: &Date, end: Option<&Date>) -> String {
+        match end {
+            Some(end) => format!("{} to {}", self.format_date(start), self.format_date(end)),
+            None => format!("{} onwards", self.format_date(start)),
+        }
+    }
+
+    /// The year's digits (zero padded for ISO) and the era suffix to use
+    /// (if any)
+    fn year_and_era_suffix(&self, year: i32) -> (String, Option<&'static str>) {
+        let suffix = if year < 0 {
+            self.era_style.suffix()
+        } else {
+            None
+        };
+        let year = match suffix {
+            Some(_) => -year,
+            None => year,
+        };
+        let year_str = match self.order {
+            DateOrder::Iso if year < 0 => format!("-{:04}", -year),
+            DateOrder::Iso => format!("{year:04}"),
+            _ => year.to_string(),
+        };
+        (year_str, suffix)
+    }
+}
+
+impl Date {
+    /// Display the [`Date`] in the given format
+    pub fn format(&self, date_format: &DateFormat) -> String {
+        date_format.format_date(self)
+    }
+}
+
+/// Mark dates recorded in the Julian calendar (e.g. "25 Dec 1642 (Julian)")
+pub(crate) fn with_calendar_suffix(date_str: String, date: &Date) -> String {
+    match date.calendar() {
+        Some(Calendar::Julian) => format!("{date_str} ({})", Calendar::Julian.name()),
+        _ => date_str,
+    }
+}
+
+/// The abbreviated name of the month (which must be 1 <= month <= 12)
+fn month_abbreviation(month: u8) -> String {
+    MONTH_ABBREVIATIONS[usize::from(month) - 1].to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(day: Option<i64>, month: Option<i64>, year: i64) -> Date {
+        Date::from(day, month, year).unwrap()
+    }
+
+    fn format(order: DateOrder, month_style: MonthStyle, era_style: EraStyle) -> DateFormat {
+        DateFormat {
+            order,
+            month_style,
+            era_style,
+        }
+    }
+
+    #[test]
+    fn default_matches_long_date_format() {
+        for date in [
+            date(Some(12), Some(3), 1945),
+            date(None, Some(3), 1945),
+            date(None, None, -500),
+        ] {
+            assert_eq!(
+                date.format(&DateFormat::default()),
+                date.as_long_date_format()
+            );
+        }
+    }
+
+    #[test]
+    fn format_date() {
+        use {DateOrder::*, EraStyle::*, MonthStyle::*};
+        let full = date(Some(12), Some(3), 1945);
+        let partial = date(None, Some(3), -500);
+        for (date_format, full_str, partial_str) in [
+            (format(Dmy, Number, Bce), "12/3/1945", "3/500 BCE"),
+            (format(Mdy, Name, Bc), "Mar 12, 1945", "Mar 500 BC"),
+            (format(Mdy, Number, Negative), "3/12/1945", "3/-500"),
+            (format(Iso, Name, Negative), "1945-03-12", "-0500-03"),
+            (format(Iso, Number, Bce), "1945-03-12", "0500-03 BCE"),
+        ] {
+            assert_eq!(full.format(&date_format), full_str);
+            assert_eq!(partial.format(&date_format), partial_str);
+        }
+    }
+
+    #[test]
+    fn format_years_and_decades() {
+        let date_format = format(DateOrder::Dmy, MonthStyle::Name, EraStyle::Bce);
+        assert_eq!(date_format.format_year(1945), "1945");
+        assert_eq!(date_format.format_year(-500), "500 BCE");
+        assert_eq!(date_format.format_decade(-500), "500s BCE");
+        assert_eq!(DateFormat::default().format_decade(-500), "-500s");
+        assert_eq!(date_format.format_month(3), "Mar");
+        let date_format = format(DateOrder::Mdy, MonthStyle::Number, EraStyle::Bce);
+        assert_eq!(date_format.format_month(3), "3");
+        let date_format = format(DateOrder::Iso, MonthStyle::Name, EraStyle::Bce);
+        assert_eq!(date_format.format_month(3), "03");
+    }
+
+    #[test]
+    fn format_julian_dates() {
+        let date = date(Some(25), Some(12), 1642).with_calendar(Some(Calendar::Julian));
+        assert_eq!(date.format(&DateFormat::default()), "25 Dec 1642 (Julian)");
+    }
+}
@@ -0,0 +1,401 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Parsing [`Date`]s from the ways people commonly write them (e.g. "12 March
+//! 1945", "March 1945", "1945-03-12", "12/3/1945", "c. 500 BCE")
+//!
+
+use crate::{Calendar, Date, DateError, DateFormat, EraStyle};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Errors that can arise when parsing a [`Date`] from a string
+#[derive(Error, Debug, Clone)]
+pub enum DateParseError {
+    /// There was nothing to parse
+    #[error("No date given")]
+    Empty,
+
+    /// Part of the string isn't a day, month, year or era
+    #[error("`{0}` isn't recognised")]
+    Unrecognised(String),
+
+    /// There was no year in the string
+    #[error("No year given")]
+    NoYear,
+
+    /// There were more numbers than a day, month and year
+    #[error("Too many numbers given")]
+    TooManyNumbers,
+
+    /// A year was given with both a minus sign and an era (e.g. "-500 BCE")
+    #[error("A year with an era must not be negative")]
+    NegativeEraYear,
+
+    /// The parts were found but don't make a valid [`Date`]
+    #[error(transparent)]
+    Date(#[from] DateError),
+}
+
+/// The month names (matched by any prefix of at least 3 letters)
+const MONTH_NAMES: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Words and symbols that mark a date as approximate.  [`Date`] has no notion
+/// of approximation, so these are dropped.
+const CIRCA_PREFIXES: [&str; 4] = ["c.", "ca.", "approx.", "~"];
+const CIRCA_WORDS: [&str; 6] = ["c", "ca", "circa", "approx", "about", "around"];
+
+/// Calendar markers ("old style" and "new style" dates are Julian and
+/// Gregorian respectively)
+const JULIAN_MARKERS: [&str; 4] = ["(julian)", "julian", "o.s.", "os"];
+const GREGORIAN_MARKERS: [&str; 4] = ["(gregorian)", "gregorian", "n.s.", "ns"];
+
+/// Era markers (dots optional)
+const BCE_MARKERS: [&str; 4] = ["b.c.e", "bce", "b.c", "bc"];
+const CE_MARKERS: [&str; 4] = ["c.e", "ce", "a.d", "ad"];
+
+/// The era a year was written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Era {
+    Bce,
+    Ce,
+}
+
+/// The parts of a date as parsed (not yet validated)
+type DateParts = (Option<i64>, Option<i64>, i64);
+
+impl FromStr for Date {
+    type Err = DateParseError;
+
+    /// Parse a [`Date`] from a human-written string.  Days, months,
+    /// approximation markers and calendars (e.g. "(Julian)", "O.S.") are
+    /// optional.  Years marked BCE/BC are negative
+    /// (i.e. "500 BCE" is year -500).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim().to_lowercase();
+        let text = strip_circa(&text);
+        let (text, calendar) = strip_calendar(text);
+        let (text, era) = strip_era(text);
+        if text.is_empty() {
+            return Err(DateParseError::Empty);
+        }
+
+        let (day, month, year) = if text.contains('/') {
+            parse_slashed(text)?
+        } else if text.contains('-') && text.chars().all(|c| c.is_ascii_digit() || c == '-') {
+            parse_dashed(text)?
+        } else {
+            parse_words(text)?
+        };
+
+        let year = match era {
+            None => year,
+            Some(_) if year < 0 => return Err(DateParseError::NegativeEraYear),
+            Some(Era::Ce) => year,
+            Some(Era::Bce) => -year,
+        };
+        Ok(Date::from(day, month, year)?.with_calendar(calendar))
+    }
+}
+
+impl Date {
+    /// e.g. 12 Mar 1945 or 500 BCE format (can be parsed back into the same
+    /// [`Date`])
+    pub fn as_era_date_format(&self) -> String {
+        let date_format = DateFormat {
+            era_style: EraStyle::Bce,
+            ..DateFormat::default()
+        };
+        self.format(&date_format)
+    }
+}
+
+/// Remove any leading approximation marker
+fn strip_circa(text: &str) -> &str {
+    if text.starts_with("c.e") {
+        return text;
+    }
+    for prefix in CIRCA_PREFIXES {
+        if let Some(rest) = text.strip_prefix(prefix) {
+            return rest.trim_start();
+        }
+    }
+    if let Some((word, rest)) = text.split_once(char::is_whitespace)
+        && CIRCA_WORDS.contains(&word)
+    {
+        return rest.trim_start();
+    }
+    text
+}
+
+/// Remove a trailing calendar marker (e.g. "25 Dec 1642 (Julian)")
+fn strip_calendar(text: &str) -> (&str, Option<Calendar>) {
+    let markers = JULIAN_MARKERS
+        .iter()
+        .map(|marker| (marker, Calendar::Julian))
+        .chain(
+            GREGORIAN_MARKERS
+                .iter()
+                .map(|marker| (marker, Calendar::Gregorian)),
+        );
+    for (marker, calendar) in markers {
+        if let Some(rest) = text.strip_suffix(marker)
+            && rest.ends_with(char::is_whitespace)
+        {
+            return (rest.trim_end(), Some(calendar));
+        }
+    }
+    (text, None)
+}
+
+/// Remove a leading (e.g. "AD 43") or trailing (e.g. "500 BCE") era marker
+fn strip_era(text: &str) -> (&str, Option<Era>) {
+    let markers = BCE_MARKERS
+        .iter()
+        .map(|marker| (marker, Era::Bce))
+        .chain(CE_MARKERS.iter().map(|marker| (marker, Era::Ce)));
+    let end = text.trim_end_matches('.');
+    for (marker, era) in markers {
+        // The marker must be a separate word or directly follow the year
+        if let Some(rest) = end.strip_suffix(marker)
+            && rest.ends_with(|c: char| c.is_whitespace() || c.is_ascii_digit())
+        {
+            return (rest.trim(), Some(era));
+        }
+        if let Some(rest) = text.strip_prefix(marker) {
+            let rest = rest.strip_prefix('.').unwrap_or(rest);
+            if rest.starts_with(char::is_whitespace) {
+                return (rest.trim(), Some(era));
+            }
+        }
+    }
+    (text.trim(), None)
+}
+
+/// Parse a number, or `None` if the part is empty or a placeholder ("-")
+fn parse_optional_number(part: &str) -> Result<Option<i64>, DateParseError> {
+    let part = part.trim();
+    if part.is_empty() || part == "-" {
+        return Ok(None);
+    }
+    part.parse()
+        .map(Some)
+        .map_err(|_| DateParseError::Unrecognised(part.to_string()))
+}
+
+/// Parse day/month/year, month/year, or year (e.g. "12/3/1945" or
+/// "- / 3 / 1945")
+fn parse_slashed(text: &str) -> Result<DateParts, DateParseError> {
+    let parts = text
+        .split('/')
+        .map(parse_optional_number)
+        .collect::<Result<Vec<_>, _>>()?;
+    let (day, month, year) = match parts.as_slice() {
+        [year] => (None, None, *year),
+        [month, year] => (None, *month, *year),
+        [day, month, year] => (*day, *month, *year),
+        _ => return Err(DateParseError::TooManyNumbers),
+    };
+    Ok((day, month, year.ok_or(DateParseError::NoYear)?))
+}
+
+/// Parse year-month-day (e.g. "1945-03-12" or "-500-03") or day-month-year
+/// (e.g. "12-03-1945")
+fn parse_dashed(text: &str) -> Result<DateParts, DateParseError> {
+    let (negative, rest) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let parts: Vec<&str> = rest.split('-').collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err(DateParseError::Unrecognised(text.to_string()));
+    }
+    let number = |part: &str| {
+        part.parse::<i64>()
+            .map_err(|_| DateParseError::Unrecognised(part.to_string()))
+    };
+    let sign = if negative { -1 } else { 1 };
+    match parts.as_slice() {
+        [year] => Ok((None, None, sign * number(year)?)),
+        [year, month] => Ok((None, Some(number(month)?), sign * number(year)?)),
+        [day, month, year] if !negative && day.len() <= 2 && year.len() > 2 => {
+            Ok((Some(number(day)?), Some(number(month)?), number(year)?))
+        }
+        [year, month, day] => Ok((
+            Some(number(day)?),
+            Some(number(month)?),
+            sign * number(year)?,
+        )),
+        _ => Err(DateParseError::TooManyNumbers),
+    }
+}
+
+/// Parse a date written with words (e.g. "12 March 1945", "March 12, 1945",
+/// "the 3rd of May 1900", "1945")
+fn parse_words(text: &str) -> Result<DateParts, DateParseError> {
+    let mut month = None;
+    let mut numbers = Vec::new();
+    for word in text.split(|c: char| c.is_whitespace() || c == ',') {
+        let word = word.trim_end_matches('.');
+        if word.is_empty() || word == "the" || word == "of" {
+            continue;
+        }
+        if let Some(index) = month_index(word)
+            && month.is_none()
+        {
+            month = Some(index);
+            continue;
+        }
+        let (digits, is_ordinal) = match ["st", "nd", "rd", "th"]
+            .iter()
+            .find_map(|suffix| word.strip_suffix(suffix))
+        {
+            Some(digits) => (digits, true),
+            None => (word, false),
+        };
+        let number = digits
+            .parse::<i64>()
+            .map_err(|_| DateParseError::Unrecognised(word.to_string()))?;
+        numbers.push((number, is_ordinal));
+    }
+
+    match (month, numbers.as_slice()) {
+        (_, []) | (_, [(_, true)]) => Err(DateParseError::NoYear),
+        (_, [(year, false)]) => Ok((None, month, *year)),
+        (Some(_), [(year, false), (day, true)]) => Ok((Some(*day), month, *year)),
+        (Some(_), [(day, _), (year, _)]) => Ok((Some(*day), month, *year)),
+        (None, [(month, _), (year, _)]) => Ok((None, Some(*month), *year)),
+        (None, [(day, _), (month, _), (year, _)]) => Ok((Some(*day), Some(*month), *year)),
+        _ => Err(DateParseError::TooManyNumbers),
+    }
+}
+
+/// The month number if the word is (the start of) a month name
+fn month_index(word: &str) -> Option<i64> {
+    if word.len() < 3 {
+        return None;
+    }
+    MONTH_NAMES
+        .iter()
+        .position(|name| name.starts_with(word))
+        .map(|index| index as i64 + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::DateParseError;
+    use crate::{Calendar, Date};
+
+    fn date(day: Option<i64>, month: Option<i64>, year: i64) -> Date {
+        Date::from(day, month, year).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let expected = date(Some(12), Some(3), 1945);
+        for text in [
+            "12 March 1945",
+            "12 mar 1945",
+            "March 12, 1945",
+            "the 12th of March, 1945",
+            "12/3/1945",
+            "12 / 03 / 1945",
+            "1945-03-12",
+            "12-03-1945",
+            "  12 March 1945 AD ",
+        ] {
+            assert_eq!(text.parse::<Date>().unwrap(), expected, "{text}");
+        }
+        assert_eq!(
+            "Sept 1945".parse::<Date>().unwrap(),
+            date(None, Some(9), 1945)
+        );
+        assert_eq!(
+            "- / - / 1945".parse::<Date>().unwrap(),
+            date(None, None, 1945)
+        );
+        assert_eq!("-500".parse::<Date>().unwrap(), date(None, None, -500));
+    }
+
+    #[test]
+    fn parse_circa_and_era() {
+        let expected = date(None, None, -500);
+        for text in [
+            "c. 500 BCE",
+            "c.500 BC",
+            "circa 500 b.c.",
+            "~500bce",
+            "500 B.C.E.",
+        ] {
+            assert_eq!(text.parse::<Date>().unwrap(), expected, "{text}");
+        }
+        assert_eq!("AD 43".parse::<Date>().unwrap(), date(None, None, 43));
+        assert_eq!("c. 43 CE".parse::<Date>().unwrap(), date(None, None, 43));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(matches!("".parse::<Date>(), Err(DateParseError::Empty)));
+        assert!(matches!(
+            "March".parse::<Date>(),
+            Err(DateParseError::NoYear)
+        ));
+        assert!(matches!(
+            "12 Marchish 1945".parse::<Date>(),
+            Err(DateParseError::Unrecognised(_))
+        ));
+        assert!(matches!(
+            "-500 BCE".parse::<Date>(),
+            Err(DateParseError::NegativeEraYear)
+        ));
+        assert!(matches!(
+            "1 2 3 4".parse::<Date>(),
+            Err(DateParseError::TooManyNumbers)
+        ));
+        assert!(matches!(
+            "32 March 1945".parse::<Date>(),
+            Err(DateParseError::Date(_))
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        for date in [
+            date(Some(12), Some(3), 1945),
+            date(None, Some(12), 1945),
+            date(None, None, 0),
+            date(None, None, -500),
+            date(Some(1), Some(1), -44),
+        ] {
+            for text in [
+                date.as_era_date_format(),
+                date.as_long_date_format(),
+                date.as_short_date_format(),
+            ] {
+                assert_eq!(text.parse::<Date>().unwrap(), date, "{text}");
+            }
+        }
+        assert_eq!(date(None, None, -500).as_era_date_format(), "500 BCE");
+
+        // The calendar is kept
+        let date = date(Some(25), Some(12), 1642).with_calendar(Some(Calendar::Julian));
+        assert_eq!(date.as_era_date_format().parse::<Date>().unwrap(), date);
+        assert_eq!(
+            "25 Dec 1642 O.S.".parse::<Date>().unwrap().calendar(),
+            Some(Calendar::Julian)
+        );
+    }
+}
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! The OpenTimeline date range type
+//!
+
+use crate::{Date, MAX_YEAR, MIN_YEAR};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors that can arise in relation to a [`DateRange`]
+#[derive(Error, Debug, Clone)]
+pub enum DateRangeError {
+    /// The end date is before the start date
+    #[error("The end date is before the start date")]
+    EndBeforeStart,
+}
+
+/// A span of time from a start [`Date`] to an optional end [`Date`].  A range
+/// without an end date is ongoing (i.e. it runs until today).
+///
+/// Missing months and days are treated as the first month/day (as when
+/// ordering [`Date`]s).
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct DateRange {
+    start: Date,
+    end: Option<Date>,
+}
+
+impl DateRange {
+    /// Create a new [`DateRange`] if the end isn't before the start
+    pub fn from(start: Date, end: Option<Date>) -> Result<Self, DateRangeError> {
+        if end.is_some_and(|end| end < start) {
+            return Err(DateRangeError::EndBeforeStart);
+        }
+        Ok(Self { start, end })
+    }
+
+    /// Get the range's start [`Date`]
+    pub fn start(&self) -> Date {
+        self.start
+    }
+
+    /// Get the range's end [`Date`] (`None` if ongoing)
+    pub fn end(&self) -> Option<Date> {
+        self.end
+    }
+
+    /// Get the range's end [`Date`], or today if ongoing
+    pub fn end_or_today(&self) -> Date {
+        self.end.unwrap_or(Date::today())
+    }
+
+    /// Whether the two ranges share any time
+    pub fn overlaps(&self, other: &DateRange) -> bool {
+        self.overlap(other).is_some()
+    }
+
+    /// The time the two ranges share (if any)
+    pub fn overlap(&self, other: &DateRange) -> Option<DateRange> {
+        let start = self.start.max(other.start);
+        let end = match (self.end, other.end) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (Some(end), None) | (None, Some(end)) => Some(end),
+            (None, None) => None,
+        };
+        DateRange::from(start, end).ok()
+    }
+
+    /// The range widened to whole decades (e.g. 1953 to 1961 becomes 1950 to
+    /// 1969).  An ongoing range ends with the current decade.
+    pub fn decades(&self) -> DateRange {
+        let decade_year = |year: i32, last: bool| {
+            let year = i64::from(year).div_euclid(10) * 10 + if last { 9 } else { 0 };
+            let year = year.clamp(MIN_YEAR, MAX_YEAR);
+            Date::from(None, None, year).expect("year is within the allowed range")
+        };
+        let start = decade_year(self.start.year().value(), false);
+        let end = decade_year(self.end_or_today().year().value(), true);
+        DateRange {
+            start,
+            end: Some(end),
+        }
+    }
+
+    /// The number of whole years from the start to the end (or today if
+    /// ongoing)
+    pub fn whole_years(&self) -> i32 {
+        let end = self.end_or_today();
+        let years = end.year().value() - self.start.year().value();
+        let month_and_day = |date: Date| {
+            (
+                date.month().map_or(1, |month| month.value()),
+                date.day().map_or(1, |day| day.value()),
+            )
+        };
+        let years = if month_and_day(end) < month_and_day(self.start) {
+            years - 1
+        } else {
+            years
+        };
+
+        // e.g. 5 June 1950 to 1950
+        years.max(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DateRange;
+    use crate::Date;
+
+    fn range(start: (Option<i64>, Option<i64>, i64), end: Option<i64>) -> DateRange {
+        let (day, month, year) = start;
+        DateRange::from(
+            Date::from(day, month, year).unwrap(),
+            end.map(|year| Date::from(None, None, year).unwrap()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn from() {
+        let start = Date::from(None, None, 1900).unwrap();
+        let end = Date::from(None, None, 1800).unwrap();
+        assert!(DateRange::from(start, Some(end)).is_err());
+        assert!(DateRange::from(end, Some(start)).is_ok());
+        assert!(DateRange::from(start, None).is_ok());
+    }
+
+    #[test]
+    fn overlap() {
+        let a = range((None, None, 1900), Some(1950));
+        let b = range((None, None, 1940), None);
+        let c = range((None, None, 1960), Some(1970));
+        assert_eq!(a.overlap(&b), Some(range((None, None, 1940), Some(1950))));
+        assert_eq!(b.overlap(&c), Some(c));
+        assert!(!a.overlaps(&c));
+    }
+
+    #[test]
+    fn decades() {
+        let decades = range((Some(5), Some(6), 1953), Some(1961)).decades();
+        assert_eq!(decades, range((None, None, 1950), Some(1969)));
+        let decades = range((None, None, -44), Some(-44)).decades();
+        assert_eq!(decades, range((None, None, -50), Some(-41)));
+        assert!(range((None, None, 2001), None).decades().end().is_some());
+    }
+
+    #[test]
+    fn whole_years() {
+        assert_eq!(range((None, None, 1900), Some(1950)).whole_years(), 50);
+        assert_eq!(
+            range((Some(5), Some(6), 1900), Some(1950)).whole_years(),
+            49
+        );
+    }
+}
@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Moving dates by a number of years, months and days
+//!
+
+use crate::calendar::{date_from_julian_day_number, days_in_month, julian_day_number};
+use crate::{Date, DateError};
+use serde::{Deserialize, Serialize};
+
+/// An amount to move a [`Date`] by (e.g. to correct dates that were imported
+/// a year out).  Each part is only applied to dates precise enough to have it:
+/// a date without a month isn't moved by `months` and a date without a day
+/// isn't moved by `days`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateShift {
+    /// The number of years to move by (negative is earlier)
+    pub years: i64,
+
+    /// The number of months to move by (negative is earlier)
+    pub months: i64,
+
+    /// The number of days to move by (negative is earlier)
+    pub days: i64,
+}
+
+impl DateShift {
+    /// Create a new [`DateShift`]
+    pub fn from(years: i64, months: i64, days: i64) -> Self {
+        Self {
+            years,
+            months,
+            days,
+        }
+    }
+
+    /// Whether the shift doesn't move dates at all
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Date {
+    /// The [`Date`] moved by the shift (in its own calendar).  Years and
+    /// months are added first, with the day kept within the resulting month
+    /// (e.g. 31st Jan + 1 month is the last day of Feb), and then days.
+    pub fn shifted(&self, shift: &DateShift) -> Result<Date, DateError> {
+        let calendar = self.calendar().unwrap_or_default();
+        let year = i64::from(self.year().value()).saturating_add(shift.years);
+        let Some(month) = self.month() else {
+            return Ok(Date::from(None, None, year)?.with_calendar(self.calendar()));
+        };
+
+        // Count months from year 0 so that they carry into years
+        let months = year
+            .saturating_mul(12)
+            .saturating_add(i64::from(month.value()) - 1)
+            .saturating_add(shift.months);
+        let (year, month) = (months.div_euclid(12), months.rem_euclid(12) + 1);
+        let Some(day) = self.day() else {
+            return Ok(Date::from(None, Some(month), year)?.with_calendar(self.calendar()));
+        };
+
+        let day = i64::from(day.value()).min(days_in_month(calendar, year, month));
+        let jdn = julian_day_number(calendar, year, month, day).saturating_add(shift.days);
+        let (year, month, day) = date_from_julian_day_number(calendar, jdn);
+        Ok(Date::from(Some(day), Some(month), year)?.with_calendar(self.calendar()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DateShift;
+    use crate::{Calendar, Date};
+
+    #[test]
+    fn shifted() {
+        let date = Date::from(Some(31), Some(1), 2024).unwrap();
+
+        // Years, months (carrying into years) and days (carrying into months)
+        let shift = DateShift::from(1, 0, 0);
+        assert_eq!(
+            date.shifted(&shift).unwrap(),
+            Date::from(Some(31), Some(1), 2025).unwrap()
+        );
+        let shift = DateShift::from(0, -2, 0);
+        assert_eq!(
+            date.shifted(&shift).unwrap(),
+            Date::from(Some(30), Some(11), 2023).unwrap()
+        );
+        let shift = DateShift::from(0, 0, 30);
+        assert_eq!(
+            date.shifted(&shift).unwrap(),
+            Date::from(Some(1), Some(3), 2024).unwrap()
+        );
+
+        // The day is kept within the month (2024 is a leap year)
+        let shift = DateShift::from(0, 1, 0);
+        assert_eq!(
+            date.shifted(&shift).unwrap(),
+            Date::from(Some(29), Some(2), 2024).unwrap()
+        );
+
+        // Only the parts the date has are shifted
+        let date = Date::from(None, None, 1900).unwrap();
+        let shift = DateShift::from(-1, 5, 40);
+        assert_eq!(
+            date.shifted(&shift).unwrap(),
+            Date::from(None, None, 1899).unwrap()
+        );
+
+        // In the date's own calendar (1900 is a leap year in the Julian one)
+        let date = Date::from(Some(28), Some(2), 1900)
+            .unwrap()
+            .with_calendar(Some(Calendar::Julian));
+        let shift = DateShift::from(0, 0, 1);
+        assert_eq!(
+            date.shifted(&shift).unwrap(),
+            Date::from(Some(29), Some(2), 1900)
+                .unwrap()
+                .with_calendar(Some(Calendar::Julian))
+        );
+
+        // Can't move beyond the allowed years
+        let shift = DateShift::from(1_000_000, 0, 0);
+        assert!(date.shifted(&shift).is_err());
+        assert!(DateShift::default().is_zero());
+    }
+}
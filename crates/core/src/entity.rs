@@ -4,15 +4,23 @@
 //! The OpenTimeline entity type
 //!
 
-use crate::{Date, Day, HasIdAndName, Month, Name, OpenTimelineId, Year};
+use crate::{
+    Calendar, Coordinates, Date, DateRange, Day, HasIdAndName, Location, Month, Name,
+    OpenTimelineId, Source, SourceCoverage, SourceScope, Sources, TagHierarchy, TagQuery, TypedTag,
+    TypedTags, Year,
+};
 use bool_tag_expr::{BoolTagExpr, Node, Tag, Tags};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use thiserror::Error;
 
+/// Alternative names an [`Entity`] is known by (e.g. "Octavian" for Augustus)
+pub type Aliases = BTreeSet<Name>;
+
 // TODO: improve (add more fine grain variants)?
 /// Errors that can arise in relation to an [`Entity`]
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum EntityError {
     #[error("The entity dates are invalid")]
     Dates,
@@ -35,6 +43,27 @@ pub struct Entity {
 
     /// Tags for the entity
     tags: Option<Tags>,
+
+    /// Tags for the entity whose values are numbers or dates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    typed_tags: Option<TypedTags>,
+
+    /// Other names the entity is known by
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aliases: Option<Aliases>,
+
+    /// Sources cited for the entity and its dates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sources: Option<Sources>,
+
+    /// Where the entity happened/existed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<Location>,
+
+    /// The version of the entity in the database when it was fetched (if it
+    /// was), used to detect conflicting edits
+    #[serde(skip)]
+    version: Option<i64>,
 }
 
 // TODO: write a derive macro to derive Ord only from the ID for use with
@@ -69,6 +98,11 @@ impl Entity {
             start,
             end,
             tags,
+            typed_tags: None,
+            aliases: None,
+            sources: None,
+            location: None,
+            version: None,
         };
 
         if entity.has_valid_dates() {
@@ -83,6 +117,18 @@ impl Entity {
         self.id = None;
     }
 
+    /// Get the version of the entity in the database when it was fetched (if
+    /// known)
+    pub fn version(&self) -> Option<i64> {
+        self.version
+    }
+
+    /// Set the version of the entity in the database.  Updates fail if the
+    /// version is set and no longer matches the database's.
+    pub fn set_version(&mut self, version: Option<i64>) {
+        self.version = version;
+    }
+
     /// Whether the entity has valid dates
     fn has_valid_dates(&self) -> bool {
         if let Some(end) = &self.end {
@@ -123,6 +169,116 @@ impl Entity {
         }
     }
 
+    /// Get the entity's [`TypedTags`]
+    pub fn typed_tags(&self) -> &Option<TypedTags> {
+        &self.typed_tags
+    }
+
+    /// Set the entity's [`TypedTags`]
+    pub fn set_typed_tags(&mut self, typed_tags: TypedTags) {
+        self.typed_tags = (!typed_tags.is_empty()).then_some(typed_tags);
+    }
+
+    /// Add a typed tag to the entity
+    pub fn add_typed_tag(&mut self, typed_tag: TypedTag) {
+        self.typed_tags
+            .get_or_insert_with(TypedTags::new)
+            .insert(typed_tag);
+    }
+
+    /// Get the entity's [`Aliases`]
+    pub fn aliases(&self) -> &Option<Aliases> {
+        &self.aliases
+    }
+
+    /// Set the entity's [`Aliases`] (the entity's own name is never an alias)
+    pub fn set_aliases(&mut self, mut aliases: Aliases) {
+        aliases.remove(&self.name);
+        self.aliases = (!aliases.is_empty()).then_some(aliases);
+    }
+
+    /// Add an alias to the entity (unless it's the entity's own name)
+    pub fn add_alias(&mut self, alias: Name) {
+        if alias != self.name {
+            self.aliases.get_or_insert_with(Aliases::new).insert(alias);
+        }
+    }
+
+    /// Remove an alias from the entity
+    pub fn remove_alias(&mut self, alias: &Name) {
+        if let Some(aliases) = self.aliases.as_mut() {
+            aliases.remove(alias);
+            if aliases.is_empty() {
+                self.aliases = None
+            }
+        }
+    }
+
+    /// Get the entity's [`Sources`]
+    pub fn sources(&self) -> &Option<Sources> {
+        &self.sources
+    }
+
+    /// Set the entity's [`Sources`]
+    pub fn set_sources(&mut self, sources: Sources) {
+        self.sources = (!sources.is_empty()).then_some(sources);
+    }
+
+    /// Add a source to the entity
+    pub fn add_source(&mut self, source: Source) {
+        self.sources.get_or_insert_with(Sources::new).insert(source);
+    }
+
+    /// Remove a source from the entity
+    pub fn remove_source(&mut self, source: &Source) {
+        if let Some(sources) = self.sources.as_mut() {
+            sources.remove(source);
+            if sources.is_empty() {
+                self.sources = None
+            }
+        }
+    }
+
+    /// How well sourced the entity is (i.e. which of the entity, its start
+    /// date, and its end date have been cited)
+    pub fn source_coverage(&self) -> SourceCoverage {
+        let mut citable = vec![SourceScope::Entity, SourceScope::Start];
+        if self.end.is_some() {
+            citable.push(SourceScope::End);
+        }
+        let cited = citable
+            .iter()
+            .filter(|scope| {
+                self.sources
+                    .iter()
+                    .flatten()
+                    .any(|source| source.scope() == **scope)
+            })
+            .count();
+        SourceCoverage {
+            cited,
+            citable: citable.len(),
+        }
+    }
+
+    /// Get the entity's [`Location`]
+    pub fn location(&self) -> &Option<Location> {
+        &self.location
+    }
+
+    /// Set (or clear) the entity's [`Location`]
+    pub fn set_location(&mut self, location: Option<Location>) {
+        self.location = location;
+    }
+
+    /// Whether the entity is within `radius_km` kilometres of the coordinates
+    /// (entities without coordinates never are)
+    pub fn is_near(&self, coordinates: &Coordinates, radius_km: f64) -> bool {
+        self.location
+            .as_ref()
+            .is_some_and(|location| location.is_near(coordinates, radius_km))
+    }
+
     /// Get the entity's start [`Date`]
     pub fn start(&self) -> Date {
         self.start
@@ -155,6 +311,12 @@ impl Entity {
         Ok(())
     }
 
+    /// Get the entity's [`DateRange`] (from start to end)
+    pub fn date_range(&self) -> DateRange {
+        // Valid entities never end before they start
+        DateRange::from(self.start, self.end).unwrap()
+    }
+
     /// Check if the entity's end year is set
     pub fn end_year_is_set(&self) -> bool {
         self.end_year().is_some()
@@ -190,27 +352,75 @@ impl Entity {
         self.start.day()
     }
 
+    /// Get the calendar the entity's start date was recorded in (if specified)
+    pub fn start_calendar(&self) -> Option<Calendar> {
+        self.start.calendar()
+    }
+
+    /// Get the calendar the entity's end date was recorded in (if specified)
+    pub fn end_calendar(&self) -> Option<Calendar> {
+        self.end.and_then(|date| date.calendar())
+    }
+
     /// Whether the entity in question matches the boolean tag expression.  This
     /// can be used to filter a list of entities by a boolean tag expression.
-    pub fn matches_bool_tag_expr(&self, bool_tag_expr: &BoolTagExpr) -> bool {
-        let Some(tags) = self.tags() else {
+    /// A tag without a name matches a tag with that value whatever its name, a
+    /// tag also matches its descendants in the [`TagHierarchy`], and an entity
+    /// without tags matches nothing.
+    pub fn matches_bool_tag_expr(
+        &self,
+        bool_tag_expr: &BoolTagExpr,
+        tag_hierarchy: &TagHierarchy,
+    ) -> bool {
+        self.matches_tag_query(&bool_tag_expr.clone().into(), tag_hierarchy)
+    }
+
+    /// Whether the entity matches the [`TagQuery`] (see
+    /// [`Entity::matches_bool_tag_expr()`])
+    pub fn matches_tag_query(&self, tag_query: &TagQuery, tag_hierarchy: &TagHierarchy) -> bool {
+        let tags = self.tags.as_ref().filter(|tags| !tags.is_empty());
+        let typed_tags = self.typed_tags.as_ref();
+        if tags.is_none() && typed_tags.is_none() {
             return false;
-        };
+        }
 
         // TODO: move into bool-tag-expr crate
-        /// Evaluate a `BooleanTagExpr` tree against a list of `Tags`
-        fn evaluate_in_one(expr: Node, tags: &Tags) -> bool {
+        /// Evaluate a `BooleanTagExpr` tree against the tags
+        fn evaluate_in_one(
+            expr: Node,
+            entity: &Entity,
+            tag_query: &TagQuery,
+            tag_hierarchy: &TagHierarchy,
+        ) -> bool {
+            let tags = entity.tags.as_ref();
+            let evaluate =
+                |expr: Box<Node>| evaluate_in_one(*expr, entity, tag_query, tag_hierarchy);
             match expr {
-                Node::And(l, r) => evaluate_in_one(*l, tags) && evaluate_in_one(*r, tags),
-                Node::Or(l, r) => evaluate_in_one(*l, tags) || evaluate_in_one(*r, tags),
-                Node::Not(e) => !evaluate_in_one(*e, tags),
-                Node::Tag(tag) => tags.contains(&tag),
+                Node::And(l, r) => evaluate(l) && evaluate(r),
+                Node::Or(l, r) => evaluate(l) || evaluate(r),
+                Node::Not(e) => !evaluate(e),
+                Node::Tag(tag) => match (tag_query.range(&tag), &tag.name) {
+                    (Some(range), _) => range.matches(tags, entity.typed_tags.as_ref()),
+                    (None, Some(_)) => tags
+                        .into_iter()
+                        .flatten()
+                        .any(|other| tag_hierarchy.is_or_descends_from(other, &tag)),
+                    (None, None) => tags
+                        .into_iter()
+                        .flatten()
+                        .any(|other| tag_hierarchy.value_is_or_descends_from(other, &tag.value)),
+                },
                 Node::Bool(_) => panic!(),
             }
         }
 
         // TODO: add a .as_node()/.node() method to bool-tag-expr crate so no cloning
-        evaluate_in_one(bool_tag_expr.clone().into_node(), tags)
+        evaluate_in_one(
+            tag_query.bool_expr().clone().into_node(),
+            self,
+            tag_query,
+            tag_hierarchy,
+        )
     }
 }
 
@@ -246,8 +456,40 @@ struct RawEntity {
     id: Option<OpenTimelineId>,
     name: Name,
     start: Date,
-    end: Option<RawEndDate>,
+    #[serde(default, deserialize_with = "deserialize_end")]
+    end: Option<Date>,
     tags: Option<Tags>,
+    #[serde(default)]
+    aliases: Option<Aliases>,
+    #[serde(default)]
+    sources: Option<Sources>,
+    #[serde(default)]
+    location: Option<Location>,
+    #[serde(default)]
+    typed_tags: Option<TypedTags>,
+}
+
+/// Deserialise an entity's end date, dealing with the incoming JSON having
+/// `"end":{"year":null,"month":null,"day":null}` (i.e. end isn't null but
+/// should be)
+fn deserialize_end<'de, D>(deserializer: D) -> Result<Option<Date>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let end = match Option::<RawEndDate>::deserialize(deserializer)? {
+        None => return Ok(None),
+        Some(end) => end,
+    };
+    match end.year {
+        None if end.day.is_none() && end.month.is_none() => Ok(None),
+        // i.e. year is None, but day OR month are Some
+        None => Err(serde::de::Error::custom(
+            "End year is invalid (day and/or month is set, but year isn't)",
+        )),
+        Some(year) => Date::from(end.day, end.month, year)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
 }
 
 impl<'de> Deserialize<'de> for Entity {
@@ -258,48 +500,32 @@ impl<'de> Deserialize<'de> for Entity {
         // TODO: look into serde Visitors (and do without RawEntity)
         let raw_entity = RawEntity::deserialize(deserializer)?;
 
-        // Deal with the incoming JSON having:
-        // "end":{"year":null,"month":null,"day":null}
-        // ie end isn't null but should be
-        let end = match raw_entity.end {
-            None => None,
-            Some(end) => {
-                if end.day.is_none() && end.month.is_none() && end.year.is_none() {
-                    None
-                } else if end.year.is_none() {
-                    // i.e. year is None, but day OR month are Some
-                    let err_msg = String::from(
-                        "End year is invalid (day and/or month is set, but year isn't",
-                    );
-                    return Err(serde::de::Error::custom(err_msg));
-                } else {
-                    match Date::from(end.day, end.month, end.year.unwrap()) {
-                        Ok(end) => Some(end),
-                        Err(_) => {
-                            // TODO: improve
-                            let err_msg = String::from("End year is invalid");
-                            return Err(serde::de::Error::custom(err_msg));
-                        }
-                    }
-                }
-            }
-        };
-
-        Entity::from(
+        let mut entity = Entity::from(
             raw_entity.id,
             raw_entity.name,
             raw_entity.start,
-            end,
+            raw_entity.end,
             raw_entity.tags,
         )
-        .map_err(serde::de::Error::custom)
+        .map_err(serde::de::Error::custom)?;
+        if let Some(aliases) = raw_entity.aliases {
+            entity.set_aliases(aliases);
+        }
+        if let Some(sources) = raw_entity.sources {
+            entity.set_sources(sources);
+        }
+        entity.set_location(raw_entity.location);
+        if let Some(typed_tags) = raw_entity.typed_tags {
+            entity.set_typed_tags(typed_tags);
+        }
+        Ok(entity)
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use bool_tag_expr::TagValue;
+    use bool_tag_expr::{TagName, TagValue};
     use open_timeline_macros::{day, month, year};
     use std::{
         collections::BTreeSet,
@@ -430,6 +656,45 @@ mod test {
         assert_eq!(entity.end_day(), Some(day!(4)));
     }
 
+    #[test]
+    fn aliases() {
+        let mut entity = valid_entity();
+        assert!(entity.aliases().is_none());
+
+        // The entity's own name is never an alias
+        entity.add_alias(Name::from("Noam").unwrap());
+        assert!(entity.aliases().is_none());
+
+        // Add and remove an alias
+        let alias = Name::from("Avram Noam").unwrap();
+        entity.add_alias(alias.clone());
+        assert_eq!(entity.aliases(), &Some(Aliases::from([alias.clone()])));
+        entity.remove_alias(&alias);
+        assert!(entity.aliases().is_none());
+
+        // Aliases survive a JSON round trip
+        entity.add_alias(alias.clone());
+        let json = serde_json::to_string(&entity).unwrap();
+        let deserialised: Entity = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialised.aliases(), entity.aliases());
+    }
+
+    #[test]
+    fn source_coverage() {
+        let mut entity = valid_entity();
+        assert!(entity.source_coverage().is_unsourced());
+
+        // The entity, its start, and its end can all be cited
+        entity.add_source(Source::from("A", None, None, SourceScope::Entity).unwrap());
+        entity.add_source(Source::from("B", None, None, SourceScope::Start).unwrap());
+        let coverage = entity.source_coverage();
+        assert_eq!((coverage.cited, coverage.citable), (2, 3));
+        assert!(!coverage.is_well_sourced());
+
+        entity.add_source(Source::from("C", None, None, SourceScope::End).unwrap());
+        assert!(entity.source_coverage().is_well_sourced());
+    }
+
     #[test]
     fn deserialisation() {
         let path_to_test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test-data");
@@ -488,6 +753,8 @@ mod test {
 
     #[test]
     fn matches_bool_tag_expr() -> Result<(), Box<dyn std::error::Error>> {
+        let no_hierarchy = TagHierarchy::new();
+
         //
         // 1. expr with 1 tag value
         //
@@ -501,7 +768,7 @@ mod test {
         entity_a.tags = Some(tags);
 
         // Should match
-        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr));
+        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr, &no_hierarchy));
 
         //
         // 2. expr with 2 tag values
@@ -516,7 +783,7 @@ mod test {
         entity_a.tags = Some(tags);
 
         // Shouldn't match
-        assert!(!entity_a.matches_bool_tag_expr(&bool_tag_expr));
+        assert!(!entity_a.matches_bool_tag_expr(&bool_tag_expr, &no_hierarchy));
 
         // Add 2nd tag to entity
         entity_a
@@ -525,7 +792,7 @@ mod test {
             .insert(Tag::from(None, TagValue::from(&"b")?));
 
         // Should match
-        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr));
+        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr, &no_hierarchy));
 
         //
         // 2. expr with 2 tag values, 1 is NOT (reverse expected results of test 2)
@@ -540,7 +807,7 @@ mod test {
         entity_a.tags = Some(tags);
 
         // Should match this time (doesn't have tag `b`)
-        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr));
+        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr, &no_hierarchy));
 
         // Add 2nd tag to entity
         entity_a
@@ -549,7 +816,7 @@ mod test {
             .insert(Tag::from(None, TagValue::from(&"b")?));
 
         // Shouldn't match
-        assert!(!entity_a.matches_bool_tag_expr(&bool_tag_expr));
+        assert!(!entity_a.matches_bool_tag_expr(&bool_tag_expr, &no_hierarchy));
 
         //
         // 3. Advanced expr
@@ -562,7 +829,7 @@ mod test {
         let tags = Tags::from([Tag::from(None, TagValue::from(&"a")?)]);
         let mut entity_a = valid_entity();
         entity_a.tags = Some(tags);
-        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr));
+        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr, &no_hierarchy));
 
         // Add only tag `a` to the entity (shouldn't match)
         let tags = Tags::from([
@@ -571,7 +838,7 @@ mod test {
             Tag::from(None, TagValue::from(&"e")?),
         ]);
         entity_a.tags = Some(tags);
-        assert!(!entity_a.matches_bool_tag_expr(&bool_tag_expr));
+        assert!(!entity_a.matches_bool_tag_expr(&bool_tag_expr, &no_hierarchy));
 
         // Add only tag `a` to the entity (shouldn't match)
         let tags = Tags::from([
@@ -582,7 +849,31 @@ mod test {
             Tag::from(None, TagValue::from(&"superfluous")?),
         ]);
         entity_a.tags = Some(tags);
-        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr));
+        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr, &no_hierarchy));
+
+        //
+        // 4. A tag matches its descendants (& only through the hierarchy)
+        //
+
+        let bool_tag_expr = BoolTagExpr::from("region=europe & world")?;
+        let tags = Tags::from([
+            Tag::from(Some(TagName::from(&"region")?), TagValue::from(&"france")?),
+            Tag::from(None, TagValue::from(&"world-war-two")?),
+        ]);
+        let mut entity_a = valid_entity();
+        entity_a.tags = Some(tags);
+        assert!(!entity_a.matches_bool_tag_expr(&bool_tag_expr, &no_hierarchy));
+        let mut tag_hierarchy = TagHierarchy::new();
+        tag_hierarchy.set_parent(
+            Tag::from(Some(TagName::from(&"region")?), TagValue::from(&"france")?),
+            TagValue::from(&"europe")?,
+        )?;
+        assert!(!entity_a.matches_bool_tag_expr(&bool_tag_expr, &tag_hierarchy));
+        tag_hierarchy.set_parent(
+            Tag::from(None, TagValue::from(&"world-war-two")?),
+            TagValue::from(&"world")?,
+        )?;
+        assert!(entity_a.matches_bool_tag_expr(&bool_tag_expr, &tag_hierarchy));
 
         Ok(())
     }
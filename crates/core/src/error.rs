@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! The errors of the OpenTimeline types, stable (machine readable) error codes,
+//! & displaying an error along with its causes
+//!
+
+use crate::{
+    DateError, DateParseError, DateRangeError, EntityError, LocationError, NameError, SlugError,
+    SourceError, TagHierarchyError, TagQueryError, TypedTagError,
+};
+use std::error::Error;
+use std::fmt;
+use thiserror::Error;
+
+/// Implementing errors have a stable, machine readable code (e.g. sent by the
+/// web API so that clients don't have to match on messages).  Codes are
+/// `snake_case` & once released they don't change.
+pub trait ErrorCode {
+    /// Get the error's code
+    fn error_code(&self) -> &'static str;
+}
+
+/// Errors that can be returned by OpenTimeline (i.e. when creating any of the
+/// OpenTimeline types).  The message is the underlying error's.
+#[derive(Error, Debug, Clone)]
+pub enum OpenTimelineError {
+    #[error(transparent)]
+    Date(#[from] DateError),
+
+    #[error(transparent)]
+    DateParse(#[from] DateParseError),
+
+    #[error(transparent)]
+    DateRange(#[from] DateRangeError),
+
+    #[error(transparent)]
+    Entity(#[from] EntityError),
+
+    #[error(transparent)]
+    Location(#[from] LocationError),
+
+    #[error(transparent)]
+    Name(#[from] NameError),
+
+    #[error(transparent)]
+    Slug(#[from] SlugError),
+
+    #[error(transparent)]
+    Source(#[from] SourceError),
+
+    #[error(transparent)]
+    TagHierarchy(#[from] TagHierarchyError),
+
+    #[error(transparent)]
+    TagQuery(#[from] TagQueryError),
+
+    #[error(transparent)]
+    TypedTag(#[from] TypedTagError),
+}
+
+impl ErrorCode for OpenTimelineError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::Date(_) | Self::DateParse(_) => "invalid_date",
+            Self::DateRange(_) => "invalid_date_range",
+            Self::Entity(_) => "invalid_entity",
+            Self::Location(_) => "invalid_location",
+            Self::Name(_) => "invalid_name",
+            Self::Slug(_) => "invalid_slug",
+            Self::Source(_) => "invalid_source",
+            Self::TagHierarchy(_) => "invalid_tag_hierarchy",
+            Self::TagQuery(_) => "invalid_tag_query",
+            Self::TypedTag(_) => "invalid_typed_tag",
+        }
+    }
+}
+
+/// Display an error followed by its causes (e.g. "There was an error with the
+/// database: database is locked"), for logs & details shown on request
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorChain<'a>(pub &'a (dyn Error + 'a));
+
+impl fmt::Display for ErrorChain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        let mut source = self.0.source();
+        while let Some(error) = source {
+            write!(f, ": {error}")?;
+            source = error.source();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// An error with a cause
+    #[derive(Error, Debug)]
+    #[error("Couldn't save")]
+    struct SaveError(#[source] OpenTimelineError);
+
+    #[test]
+    fn error_chain() {
+        let error = SaveError(NameError::Empty.into());
+        assert_eq!(error.to_string(), "Couldn't save");
+        assert_eq!(
+            ErrorChain(&error).to_string(),
+            "Couldn't save: Name cannot be empty"
+        );
+        assert_eq!(
+            ErrorChain(&NameError::Empty).to_string(),
+            "Name cannot be empty"
+        );
+    }
+
+    #[test]
+    fn error_codes() {
+        let error = OpenTimelineError::from(NameError::Empty);
+        assert_eq!(error.error_code(), "invalid_name");
+        assert_eq!(error.to_string(), "Name cannot be empty");
+        let error = OpenTimelineError::from(DateRangeError::EndBeforeStart);
+        assert_eq!(error.error_code(), "invalid_date_range");
+    }
+}
@@ -4,10 +4,61 @@
 //! Functions for ID management (create a globally unique one, or check if it
 //! already exists)
 //!
+//! New IDs are random (UUIDv4) by default, but can instead be time-sortable
+//! (UUIDv7, which like a ULID starts with the millisecond it was created in) so
+//! that sorting IDs sorts by creation time (see [`set_id_scheme()`]).  Either
+//! way they're stored as UUIDs, so both kinds can be mixed in one database.
+//!
 
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use uuid::Uuid;
 
-/// The OpenTimeline ID type is a UUIDv4
+/// Whether new IDs are time-sortable (see [`set_id_scheme()`])
+static TIME_SORTABLE_IDS: AtomicBool = AtomicBool::new(false);
+
+/// How new [`OpenTimelineId`]s are generated
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdScheme {
+    /// Random (UUIDv4)
+    #[default]
+    Random,
+
+    /// Time-sortable (UUIDv7)
+    TimeSortable,
+}
+
+impl FromStr for IdScheme {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            "random" => Ok(IdScheme::Random),
+            "time-sortable" => Ok(IdScheme::TimeSortable),
+            _ => Err(format!(
+                "Invalid ID scheme `{text}` (expected `random` or `time-sortable`)"
+            )),
+        }
+    }
+}
+
+/// Set how new IDs are generated (for the whole process)
+pub fn set_id_scheme(scheme: IdScheme) {
+    debug!("Generating {scheme:?} IDs");
+    TIME_SORTABLE_IDS.store(scheme == IdScheme::TimeSortable, Ordering::Relaxed);
+}
+
+/// How new IDs are generated (see [`set_id_scheme()`])
+pub fn id_scheme() -> IdScheme {
+    match TIME_SORTABLE_IDS.load(Ordering::Relaxed) {
+        true => IdScheme::TimeSortable,
+        false => IdScheme::Random,
+    }
+}
+
+/// The OpenTimeline ID type is a UUID (v4, or v7 if time-sortable)
 #[rustfmt::skip]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[derive(derive_more::Display, serde::Serialize, serde::Deserialize)]
@@ -17,12 +68,36 @@ use uuid::Uuid;
 pub struct OpenTimelineId(Uuid);
 
 impl OpenTimelineId {
-    /// Create a new `OpenTimelineId`
+    /// Create a new `OpenTimelineId` (using the [`id_scheme()`])
     pub fn new() -> Self {
         debug!("Creating new OpenTimelineId");
+        match id_scheme() {
+            IdScheme::Random => Self::new_random(),
+            IdScheme::TimeSortable => Self::new_time_sortable(),
+        }
+    }
+
+    /// Create a new random `OpenTimelineId` (a UUIDv4)
+    pub fn new_random() -> Self {
         Self(Uuid::new_v4())
     }
 
+    /// Create a new time-sortable `OpenTimelineId` (a UUIDv7).  IDs created
+    /// later sort after those created earlier (to the millisecond).
+    pub fn new_time_sortable() -> Self {
+        Self(Uuid::now_v7())
+    }
+
+    /// When the ID was created (as a unix time in milliseconds), if it's
+    /// time-sortable
+    pub fn created_at(&self) -> Option<u64> {
+        if self.0.get_version_num() != 7 {
+            return None;
+        }
+        let (seconds, nanoseconds) = self.0.get_timestamp()?.to_unix();
+        Some(seconds * 1000 + u64::from(nanoseconds) / 1_000_000)
+    }
+
     /// Create an ID from a string if the string is a valid ID
     pub fn from<S: ToString>(string: S) -> Result<Self, uuid::Error> {
         let string = string.to_string();
@@ -55,4 +130,29 @@ mod test {
         assert_eq!(json, r#""550e8400-e29b-41d4-a716-446655440000""#);
         assert_eq!(id.to_string(), "550e8400-e29b-41d4-a716-446655440000");
     }
+
+    #[test]
+    fn time_sortable_ids() {
+        // Random IDs have no creation time
+        assert_eq!(OpenTimelineId::new_random().created_at(), None);
+        let id = OpenTimelineId::from("550e8400-e29b-41d4-a716-446655440000").unwrap();
+        assert_eq!(id.created_at(), None);
+
+        // Time-sortable IDs sort by when they were created
+        let earlier = OpenTimelineId::new_time_sortable();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let later = OpenTimelineId::new_time_sortable();
+        assert!(earlier < later);
+        assert!(earlier.to_string() < later.to_string());
+        assert!(earlier.created_at().unwrap() < later.created_at().unwrap());
+
+        // The creation time is read from the ID
+        let id = OpenTimelineId::from("01932c07-a76e-7f0c-8000-000000000000").unwrap();
+        assert_eq!(id.created_at(), Some(1_731_610_519_406));
+
+        // The scheme can be parsed (e.g. from config)
+        assert_eq!("time-sortable".parse(), Ok(IdScheme::TimeSortable));
+        assert_eq!(" random ".parse(), Ok(IdScheme::Random));
+        assert!("ulid".parse::<IdScheme>().is_err());
+    }
 }
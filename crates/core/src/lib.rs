@@ -14,37 +14,53 @@
 //! instantiated, the developer can be sure it's valid.
 //!
 
+mod calendar;
 mod date;
+mod date_format;
+mod date_parse;
+mod date_range;
+mod date_shift;
 mod entity;
+mod error;
 mod id;
+mod location;
 mod name;
 mod reduced;
+mod slug;
+mod source;
+mod story;
+mod tag_hierarchy;
+mod tag_query;
 mod timeline_edit;
 mod timeline_view;
+mod timelinejs;
+mod typed_tag;
 
+pub use calendar::*;
 pub use date::*;
+pub use date_format::*;
+pub use date_parse::*;
+pub use date_range::*;
+pub use date_shift::*;
 pub use entity::*;
+pub use error::*;
 pub use id::*;
+pub use location::*;
 pub use name::*;
 pub use reduced::*;
+pub use slug::*;
+pub use source::*;
+pub use story::*;
+pub use tag_hierarchy::*;
+pub use tag_query::*;
 pub use timeline_edit::*;
 pub use timeline_view::*;
+pub use timelinejs::*;
+pub use typed_tag::*;
 
 #[macro_use]
 extern crate log;
 
-// TODO: is this used anywhere (variants could/should hold the more specific Errors)
-/// Errors that can be returned by OpenTimeline
-#[derive(Debug)]
-pub enum OpenTimelineError {
-    Date,
-    Entity,
-    Id,
-    Name,
-    Tags,
-    Timeline,
-}
-
 /// Mark that a type has both an [`OpenTimelineId`] and a [`Name`], and setup
 /// getters and setters for both
 pub trait HasIdAndName {
@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Where an entity happened/existed (a place name and/or coordinates)
+//!
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The mean radius of the Earth in kilometres
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Coordinates are stored to the nearest millionth of a degree (~11cm)
+const MICRODEGREES_PER_DEGREE: f64 = 1_000_000.0;
+
+/// Errors that can arise in relation to a [`Location`]
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LocationError {
+    #[error("The latitude must be between -90 and 90")]
+    Latitude,
+
+    #[error("The longitude must be between -180 and 180")]
+    Longitude,
+
+    #[error("A location needs a place name and/or coordinates")]
+    Empty,
+}
+
+/// A point on the Earth's surface (in decimal degrees)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(into = "RawCoordinates", try_from = "RawCoordinates")]
+pub struct Coordinates {
+    /// The latitude in millionths of a degree
+    latitude: i32,
+
+    /// The longitude in millionths of a degree
+    longitude: i32,
+}
+
+impl Coordinates {
+    /// Create valid [`Coordinates`] if the latitude and longitude are in range
+    pub fn from(latitude: f64, longitude: f64) -> Result<Coordinates, LocationError> {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(LocationError::Latitude);
+        }
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(LocationError::Longitude);
+        }
+        Ok(Coordinates {
+            latitude: (latitude * MICRODEGREES_PER_DEGREE).round() as i32,
+            longitude: (longitude * MICRODEGREES_PER_DEGREE).round() as i32,
+        })
+    }
+
+    /// Get the latitude (in decimal degrees)
+    pub fn latitude(&self) -> f64 {
+        f64::from(self.latitude) / MICRODEGREES_PER_DEGREE
+    }
+
+    /// Get the longitude (in decimal degrees)
+    pub fn longitude(&self) -> f64 {
+        f64::from(self.longitude) / MICRODEGREES_PER_DEGREE
+    }
+
+    /// The great-circle distance to the other coordinates in kilometres
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let (lat_1, lat_2) = (self.latitude().to_radians(), other.latitude().to_radians());
+        let delta_lat = lat_2 - lat_1;
+        let delta_lon = (other.longitude() - self.longitude()).to_radians();
+        let a = (delta_lat / 2.0).sin().powi(2)
+            + lat_1.cos() * lat_2.cos() * (delta_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+
+    /// A link to the coordinates on OpenStreetMap
+    pub fn map_url(&self) -> String {
+        format!(
+            "https://www.openstreetmap.org/?mlat={lat}&mlon={lon}#map=8/{lat}/{lon}",
+            lat = self.latitude(),
+            lon = self.longitude()
+        )
+    }
+}
+
+/// Used only for (de)serialisation (so that coordinates are in degrees and are
+/// validated)
+#[derive(Serialize, Deserialize)]
+struct RawCoordinates {
+    latitude: f64,
+    longitude: f64,
+}
+
+impl From<Coordinates> for RawCoordinates {
+    fn from(coordinates: Coordinates) -> Self {
+        RawCoordinates {
+            latitude: coordinates.latitude(),
+            longitude: coordinates.longitude(),
+        }
+    }
+}
+
+impl TryFrom<RawCoordinates> for Coordinates {
+    type Error = LocationError;
+
+    fn try_from(raw: RawCoordinates) -> Result<Self, Self::Error> {
+        Coordinates::from(raw.latitude, raw.longitude)
+    }
+}
+
+/// Where an entity happened/existed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "RawLocation")]
+pub struct Location {
+    /// The name of the place (e.g. "Hastings, England")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    place: Option<String>,
+
+    /// The place's coordinates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coordinates: Option<Coordinates>,
+}
+
+impl Location {
+    /// Create a valid [`Location`] if there is a (non-empty) place name and/or
+    /// coordinates
+    pub fn from(
+        place: Option<&str>,
+        coordinates: Option<Coordinates>,
+    ) -> Result<Location, LocationError> {
+        let place = place.map(str::trim).filter(|place| !place.is_empty());
+        if place.is_none() && coordinates.is_none() {
+            return Err(LocationError::Empty);
+        }
+        Ok(Location {
+            place: place.map(String::from),
+            coordinates,
+        })
+    }
+
+    /// Get the place name (if there is one)
+    pub fn place(&self) -> Option<&str> {
+        self.place.as_deref()
+    }
+
+    /// Get the coordinates (if there are any)
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        self.coordinates
+    }
+
+    /// Whether the location is within `radius_km` kilometres of the
+    /// coordinates (locations without coordinates never are)
+    pub fn is_near(&self, coordinates: &Coordinates, radius_km: f64) -> bool {
+        self.coordinates
+            .is_some_and(|own| own.distance_km(coordinates) <= radius_km)
+    }
+}
+
+/// Used only by the deserialiser (so that locations are validated)
+#[derive(Deserialize)]
+struct RawLocation {
+    place: Option<String>,
+    coordinates: Option<Coordinates>,
+}
+
+impl TryFrom<RawLocation> for Location {
+    type Error = LocationError;
+
+    fn try_from(raw: RawLocation) -> Result<Self, Self::Error> {
+        Location::from(raw.place.as_deref(), raw.coordinates)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn coordinates() {
+        assert_eq!(Coordinates::from(91.0, 0.0), Err(LocationError::Latitude));
+        assert_eq!(
+            Coordinates::from(0.0, -181.0),
+            Err(LocationError::Longitude)
+        );
+
+        let hastings = Coordinates::from(50.911, 0.488).unwrap();
+        assert_eq!(hastings.latitude(), 50.911);
+        assert_eq!(hastings.longitude(), 0.488);
+
+        // London to Paris is ~344km
+        let london = Coordinates::from(51.5074, -0.1278).unwrap();
+        let paris = Coordinates::from(48.8566, 2.3522).unwrap();
+        assert!((london.distance_km(&paris) - 344.0).abs() < 2.0);
+        assert_eq!(london.distance_km(&london), 0.0);
+    }
+
+    #[test]
+    fn location() {
+        assert_eq!(Location::from(Some(" "), None), Err(LocationError::Empty));
+
+        let london = Coordinates::from(51.5074, -0.1278).unwrap();
+        let paris = Coordinates::from(48.8566, 2.3522).unwrap();
+        let location = Location::from(Some("London"), Some(london)).unwrap();
+        assert!(location.is_near(&paris, 400.0));
+        assert!(!location.is_near(&paris, 300.0));
+        assert!(
+            !Location::from(Some("Paris"), None)
+                .unwrap()
+                .is_near(&paris, 1.0)
+        );
+    }
+
+    #[test]
+    fn serialisation() {
+        let json = r#"{"place":"Hastings","coordinates":{"latitude":50.911,"longitude":0.488}}"#;
+        let location: Location = serde_json::from_str(json).unwrap();
+        assert_eq!(location.place(), Some("Hastings"));
+        assert_eq!(serde_json::to_string(&location).unwrap(), json);
+
+        // Invalid coordinates can't be deserialised
+        let json = r#"{"coordinates":{"latitude":100.0,"longitude":0.0}}"#;
+        assert!(serde_json::from_str::<Location>(json).is_err());
+    }
+}
@@ -9,7 +9,7 @@ use thiserror::Error;
 
 // TODO: should these <Type>Error enums have (de)serialising errors too?
 /// Errors that can arise in relation to a [`Name`]
-#[derive(Error, Debug, Clone)]
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum NameError {
     #[error("Name cannot be empty")]
     Empty,
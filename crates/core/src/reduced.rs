@@ -6,11 +6,13 @@
 
 mod entities;
 mod entity;
+mod render_entity;
 mod timeline;
 mod timelines;
 
 pub use entities::*;
 pub use entity::*;
+pub use render_entity::*;
 pub use timeline::*;
 pub use timelines::*;
 
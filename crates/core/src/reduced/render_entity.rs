@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Reduced render entity
+//!
+
+use crate::{Date, Entity, EntityError, Name, OpenTimelineId};
+use bool_tag_expr::Tags;
+use serde::{Deserialize, Serialize};
+
+/// The reduced entity type used when rendering a timeline - holds only what is
+/// needed to draw an [`Entity`] (its ID, name, dates, and the tags used to
+/// colour it).
+///
+/// Serialises to a subset of an [`Entity`], so can also be deserialised as one.
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Debug, Clone)]
+pub struct ReducedRenderEntity {
+    /// The entity's ID
+    id: OpenTimelineId,
+
+    /// The entity's name
+    name: Name,
+
+    /// When did the entity begin/start
+    start: Date,
+
+    /// When did the entity end/finish (if it has)
+    end: Option<Date>,
+
+    /// Tags for the entity
+    tags: Option<Tags>,
+}
+
+impl ReducedRenderEntity {
+    /// Create a [`ReducedRenderEntity`]
+    pub fn from(
+        id: OpenTimelineId,
+        name: Name,
+        start: Date,
+        end: Option<Date>,
+        tags: Option<Tags>,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            start,
+            end,
+            tags,
+        }
+    }
+
+    /// Get the entity's ID
+    pub fn id(&self) -> OpenTimelineId {
+        self.id
+    }
+
+    /// Borrow the entity's name
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// Get the entity's start date
+    pub fn start(&self) -> Date {
+        self.start
+    }
+
+    /// Get the entity's end date (if it has one)
+    pub fn end(&self) -> Option<Date> {
+        self.end
+    }
+
+    /// Borrow the entity's tags
+    pub fn tags(&self) -> &Option<Tags> {
+        &self.tags
+    }
+}
+
+impl TryFrom<ReducedRenderEntity> for Entity {
+    type Error = EntityError;
+
+    fn try_from(entity: ReducedRenderEntity) -> Result<Self, Self::Error> {
+        Entity::from(
+            Some(entity.id),
+            entity.name,
+            entity.start,
+            entity.end,
+            entity.tags,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::HasIdAndName;
+    use bool_tag_expr::{Tag, TagValue};
+
+    fn render_entity() -> ReducedRenderEntity {
+        let tags = Tags::from([Tag::from(None, TagValue::from(&"person").unwrap())]);
+        ReducedRenderEntity::from(
+            OpenTimelineId::new(),
+            Name::from("Augustus").unwrap(),
+            Date::from(None, None, -63).unwrap(),
+            Some(Date::from(None, None, 14).unwrap()),
+            Some(tags),
+        )
+    }
+
+    #[test]
+    fn deserialises_as_entity() {
+        let render_entity = render_entity();
+        let json = serde_json::to_string(&render_entity).unwrap();
+        let entity: Entity = serde_json::from_str(&json).unwrap();
+        assert_eq!(entity, Entity::try_from(render_entity.clone()).unwrap());
+        assert_eq!(entity.id(), Some(render_entity.id()));
+        assert_eq!(entity.tags(), render_entity.tags());
+    }
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! The OpenTimeline slug type (used in permalinks)
+//!
+
+use crate::Name;
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+
+/// The maximum length of a [`Slug`]
+pub const MAX_SLUG_LEN: usize = 80;
+
+/// Used when a name has no characters that can be used in a slug
+const FALLBACK_SLUG: &str = "untitled";
+
+/// Errors that can arise in relation to a [`Slug`]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SlugError {
+    #[error("Slug cannot be empty")]
+    Empty,
+
+    #[error("Slug cannot be longer than {MAX_SLUG_LEN} characters")]
+    TooLong,
+
+    #[error("Slug can only contain lowercase letters, digits & single hyphens between them")]
+    Invalid,
+}
+
+/// The OpenTimeline [`Slug`] type.  A URL-safe identifier derived from a
+/// [`Name`] (e.g. "Julius Caesar" -> "julius-caesar"), made up of lowercase
+/// ASCII letters & digits separated by single hyphens.
+#[derive(derive_more::Display, Serialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(transparent))]
+pub struct Slug(String);
+
+impl Slug {
+    /// Create a slug from a string if it is a valid slug
+    pub fn from<S: ToString>(slug: S) -> Result<Self, SlugError> {
+        let slug = slug.to_string();
+        if slug.is_empty() {
+            return Err(SlugError::Empty);
+        }
+        if slug.len() > MAX_SLUG_LEN {
+            return Err(SlugError::TooLong);
+        }
+        let valid_chars = slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid_chars || slug.starts_with('-') || slug.ends_with('-') || slug.contains("--") {
+            return Err(SlugError::Invalid);
+        }
+        Ok(Slug(slug))
+    }
+
+    /// Generate a slug from a name
+    pub fn from_name(name: &Name) -> Self {
+        let mut slug = String::new();
+        for c in name.as_str().chars().flat_map(char::to_lowercase) {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c);
+            } else if let Some(folded) = fold_to_ascii(c) {
+                slug.push_str(folded);
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                // Anything else separates words
+                slug.push('-');
+            }
+        }
+        Slug::truncated(&slug).unwrap_or_else(|| Slug(FALLBACK_SLUG.to_string()))
+    }
+
+    /// The slug with a numeric suffix (used to make slugs unique)
+    pub fn with_suffix(&self, suffix: u32) -> Self {
+        let suffix = format!("-{suffix}");
+        let base = &self.0[..self.0.len().min(MAX_SLUG_LEN - suffix.len())];
+        let base = Slug::truncated(base).map(|slug| slug.0).unwrap_or_default();
+        Slug(
+            format!("{base}{suffix}")
+                .trim_start_matches('-')
+                .to_string(),
+        )
+    }
+
+    /// Get the underlying `&str`
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Limit the length & trim hyphens (none if nothing is left)
+    fn truncated(slug: &str) -> Option<Self> {
+        let slug = &slug[..slug.len().min(MAX_SLUG_LEN)];
+        let slug = slug.trim_matches('-');
+        (!slug.is_empty()).then(|| Slug(slug.to_string()))
+    }
+}
+
+/// The ASCII letters (if any) that can stand in for a lowercase non-ASCII
+/// letter
+fn fold_to_ascii(c: char) -> Option<&'static str> {
+    let folded = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => "i",
+        'ł' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'œ' => "oe",
+        'ř' => "r",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'ť' | 'ţ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(folded)
+}
+
+impl<'de> Deserialize<'de> for Slug {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        Slug::from(string).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn slug_for(name: &str) -> String {
+        Slug::from_name(&Name::from(name).unwrap()).to_string()
+    }
+
+    #[test]
+    fn from() {
+        assert!(Slug::from("julius-caesar").is_ok());
+        assert!(Slug::from("ww2").is_ok());
+        assert_eq!(Slug::from(""), Err(SlugError::Empty));
+        assert_eq!(Slug::from("Julius-Caesar"), Err(SlugError::Invalid));
+        assert_eq!(Slug::from("julius--caesar"), Err(SlugError::Invalid));
+        assert_eq!(Slug::from("-julius"), Err(SlugError::Invalid));
+        assert_eq!(Slug::from("julius caesar"), Err(SlugError::Invalid));
+        assert_eq!(Slug::from("a".repeat(81)), Err(SlugError::TooLong));
+    }
+
+    #[test]
+    fn from_name() {
+        assert_eq!(slug_for("Julius Caesar"), "julius-caesar");
+        assert_eq!(
+            slug_for("  The Battle of Hastings (1066)! "),
+            "the-battle-of-hastings-1066"
+        );
+        assert_eq!(slug_for("Ælfred the Great"), "aelfred-the-great");
+        assert_eq!(slug_for("Émile Zola"), "emile-zola");
+        assert_eq!(slug_for("秦始皇"), "untitled");
+        assert_eq!(slug_for(&"a".repeat(100)).len(), MAX_SLUG_LEN);
+        assert!(Slug::from(slug_for("São Paulo -- 1554 / founding")).is_ok());
+    }
+
+    #[test]
+    fn with_suffix() {
+        let slug = Slug::from("julius-caesar").unwrap();
+        assert_eq!(slug.with_suffix(2).as_str(), "julius-caesar-2");
+        let long = Slug::from("a".repeat(MAX_SLUG_LEN)).unwrap();
+        let suffixed = long.with_suffix(12);
+        assert_eq!(suffixed.as_str().len(), MAX_SLUG_LEN);
+        assert!(Slug::from(suffixed.as_str()).is_ok());
+    }
+}
@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Sources (citations) for what is recorded about an entity
+//!
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+/// The sources cited for an [`Entity`](crate::Entity)
+pub type Sources = BTreeSet<Source>;
+
+/// Errors that can arise in relation to a [`Source`]
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SourceError {
+    #[error("The source title cannot be empty")]
+    EmptyTitle,
+
+    #[error("The source URL must start with http:// or https://")]
+    Url,
+}
+
+/// What a [`Source`] is cited for
+#[derive(
+    Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(rename_all = "lowercase"))]
+pub enum SourceScope {
+    /// The entity as a whole (e.g. that it happened/existed)
+    #[default]
+    Entity,
+
+    /// The entity's start date
+    Start,
+
+    /// The entity's end date
+    End,
+}
+
+impl SourceScope {
+    /// All scopes (useful for drawing a picker)
+    pub const ALL: [SourceScope; 3] = [SourceScope::Entity, SourceScope::Start, SourceScope::End];
+
+    /// The name of the scope
+    pub fn name(&self) -> &'static str {
+        match self {
+            SourceScope::Entity => "Entity",
+            SourceScope::Start => "Start date",
+            SourceScope::End => "End date",
+        }
+    }
+}
+
+/// A source cited for an entity (e.g. a book, article or web page)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(try_from = "RawSource")]
+pub struct Source {
+    /// The source's title
+    title: String,
+
+    /// Where the source can be found online (if it can be)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+
+    /// The full citation (e.g. author, publisher, page numbers)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    citation: Option<String>,
+
+    /// What the source is cited for
+    #[serde(default)]
+    scope: SourceScope,
+}
+
+impl Source {
+    /// Create a valid [`Source`] if it is possible to do so with the values
+    /// passed in (empty URLs and citations are treated as `None`)
+    pub fn from(
+        title: &str,
+        url: Option<&str>,
+        citation: Option<&str>,
+        scope: SourceScope,
+    ) -> Result<Source, SourceError> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(SourceError::EmptyTitle);
+        }
+        let url = url.map(str::trim).filter(|url| !url.is_empty());
+        if let Some(url) = url
+            && !(url.starts_with("http://") || url.starts_with("https://"))
+        {
+            return Err(SourceError::Url);
+        }
+        let citation = citation
+            .map(str::trim)
+            .filter(|citation| !citation.is_empty());
+        Ok(Source {
+            title: title.to_string(),
+            url: url.map(String::from),
+            citation: citation.map(String::from),
+            scope,
+        })
+    }
+
+    /// Get the source's title
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Get the source's URL (if it has one)
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// Get the source's citation (if it has one)
+    pub fn citation(&self) -> Option<&str> {
+        self.citation.as_deref()
+    }
+
+    /// Get what the source is cited for
+    pub fn scope(&self) -> SourceScope {
+        self.scope
+    }
+}
+
+/// Used only by the deserialiser (so that sources are validated)
+#[derive(Deserialize)]
+struct RawSource {
+    title: String,
+    url: Option<String>,
+    citation: Option<String>,
+    #[serde(default)]
+    scope: SourceScope,
+}
+
+impl TryFrom<RawSource> for Source {
+    type Error = SourceError;
+
+    fn try_from(raw: RawSource) -> Result<Self, Self::Error> {
+        Source::from(
+            &raw.title,
+            raw.url.as_deref(),
+            raw.citation.as_deref(),
+            raw.scope,
+        )
+    }
+}
+
+/// How well an entity is sourced, i.e. how many of the things that can be cited
+/// (the entity itself, its start date, and its end date if it has one) have
+/// been
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceCoverage {
+    /// The number of things that have at least one source
+    pub cited: usize,
+
+    /// The number of things that can be cited
+    pub citable: usize,
+}
+
+impl SourceCoverage {
+    /// Nothing is cited
+    pub fn is_unsourced(&self) -> bool {
+        self.cited == 0
+    }
+
+    /// Everything is cited
+    pub fn is_well_sourced(&self) -> bool {
+        self.cited == self.citable
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from() {
+        assert_eq!(
+            Source::from("  ", None, None, SourceScope::Entity),
+            Err(SourceError::EmptyTitle)
+        );
+        assert_eq!(
+            Source::from("Title", Some("example.com"), None, SourceScope::Entity),
+            Err(SourceError::Url)
+        );
+
+        // Empty optional fields are None
+        let source = Source::from(" Title ", Some(""), Some(" "), SourceScope::Start).unwrap();
+        assert_eq!(source.title(), "Title");
+        assert_eq!(source.url(), None);
+        assert_eq!(source.citation(), None);
+        assert_eq!(source.scope(), SourceScope::Start);
+    }
+
+    #[test]
+    fn deserialisation() {
+        let source: Source =
+            serde_json::from_str(r#"{"title":"Title","url":"https://example.com"}"#).unwrap();
+        assert_eq!(source.url(), Some("https://example.com"));
+        assert_eq!(source.scope(), SourceScope::Entity);
+
+        // Invalid sources can't be deserialised
+        assert!(serde_json::from_str::<Source>(r#"{"title":""}"#).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Stories (guided tours through a timeline, one step at a time)
+//!
+
+use crate::OpenTimelineId;
+use serde::{Deserialize, Serialize};
+
+/// What a step of a story shows
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StoryStepTarget {
+    /// An entity (which is highlighted)
+    Entity(OpenTimelineId),
+
+    /// A range of (fractional) years
+    Years {
+        /// The first year shown
+        start_year: f64,
+
+        /// The last year shown
+        end_year: f64,
+    },
+}
+
+/// A step of a story: something on the timeline to show, and a caption saying
+/// why it matters
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoryStep {
+    /// What the step shows
+    pub target: StoryStepTarget,
+
+    /// The text shown with the step
+    pub caption: String,
+}
+
+impl StoryStep {
+    /// Create a new story step
+    pub fn from(target: StoryStepTarget, caption: String) -> Self {
+        Self { target, caption }
+    }
+
+    /// Get the ID of the entity the step shows (if it shows one)
+    pub fn entity_id(&self) -> Option<OpenTimelineId> {
+        match self.target {
+            StoryStepTarget::Entity(entity_id) => Some(entity_id),
+            StoryStepTarget::Years { .. } => None,
+        }
+    }
+}
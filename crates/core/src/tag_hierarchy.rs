@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Hierarchical tags.  A tag can be given a parent tag (with the same name),
+//! e.g. `region=france` a child of `region=europe`, which is a child of
+//! `region=world`.  A tag in a boolean tag expression also matches its
+//! descendants.
+//!
+
+use bool_tag_expr::{Tag, TagValue};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Which tags are the children of which (a tag's parent has the same name as
+/// it, & a tag has at most one parent)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagHierarchy {
+    /// The value of each child tag's parent
+    parents: BTreeMap<Tag, TagValue>,
+}
+
+impl TagHierarchy {
+    /// Create an empty hierarchy (no tag has a parent)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the tag's parent (replacing any it had), unless the parent is the
+    /// tag or one of its descendants
+    pub fn set_parent(&mut self, tag: Tag, parent: TagValue) -> Result<(), TagHierarchyError> {
+        let parent_tag = Tag::from(tag.name.clone(), parent.clone());
+        if self.is_or_descends_from(&parent_tag, &tag) {
+            return Err(TagHierarchyError::Cycle);
+        }
+        self.parents.insert(tag, parent);
+        Ok(())
+    }
+
+    /// Remove the tag's parent (if it has one)
+    pub fn remove_parent(&mut self, tag: &Tag) {
+        self.parents.remove(tag);
+    }
+
+    /// Get the tag's parent (if it has one)
+    pub fn parent(&self, tag: &Tag) -> Option<Tag> {
+        let value = self.parents.get(tag)?;
+        Some(Tag::from(tag.name.clone(), value.clone()))
+    }
+
+    /// Get all of the tag's ancestors (nearest first)
+    pub fn ancestors(&self, tag: &Tag) -> Vec<Tag> {
+        let mut ancestors: Vec<Tag> = Vec::new();
+        let mut current = self.parent(tag);
+        while let Some(parent) = current {
+            // Parents set elsewhere (e.g. in the database) could form a cycle
+            if &parent == tag || ancestors.contains(&parent) {
+                break;
+            }
+            current = self.parent(&parent);
+            ancestors.push(parent);
+        }
+        ancestors
+    }
+
+    /// Get the tag's children
+    pub fn children(&self, tag: &Tag) -> Vec<Tag> {
+        self.parents
+            .iter()
+            .filter(|(child, parent)| child.name == tag.name && **parent == tag.value)
+            .map(|(child, _)| child.clone())
+            .collect()
+    }
+
+    /// Whether `tag` is `ancestor` or one of its descendants
+    pub fn is_or_descends_from(&self, tag: &Tag, ancestor: &Tag) -> bool {
+        tag == ancestor || self.ancestors(tag).contains(ancestor)
+    }
+
+    /// Whether `tag` has the value or descends from a tag (with the same name)
+    /// that has it
+    pub fn value_is_or_descends_from(&self, tag: &Tag, ancestor: &TagValue) -> bool {
+        tag.value == *ancestor
+            || self
+                .ancestors(tag)
+                .iter()
+                .any(|parent| parent.value == *ancestor)
+    }
+
+    /// Iterate over every tag that has a parent, & its parent
+    pub fn iter(&self) -> impl Iterator<Item = (&Tag, Tag)> {
+        self.parents
+            .iter()
+            .map(|(tag, value)| (tag, Tag::from(tag.name.clone(), value.clone())))
+    }
+
+    /// Whether no tag has a parent
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+}
+
+/// Each tag & its parent's value (cycles aren't checked for, see
+/// [`TagHierarchy::ancestors()`])
+impl FromIterator<(Tag, TagValue)> for TagHierarchy {
+    fn from_iter<T: IntoIterator<Item = (Tag, TagValue)>>(iter: T) -> Self {
+        Self {
+            parents: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Errors when changing a [`TagHierarchy`]
+#[derive(Error, Debug, Clone, Hash, PartialEq, Eq)]
+pub enum TagHierarchyError {
+    #[error("A tag can't be a descendant of itself")]
+    Cycle,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bool_tag_expr::TagName;
+
+    fn tag(name: Option<&str>, value: &str) -> Tag {
+        Tag::from(
+            name.map(|name| TagName::from(&name).unwrap()),
+            TagValue::from(&value).unwrap(),
+        )
+    }
+
+    fn value(value: &str) -> TagValue {
+        TagValue::from(&value).unwrap()
+    }
+
+    #[test]
+    fn parents_and_ancestors() {
+        let mut hierarchy = TagHierarchy::new();
+        let france = tag(Some("region"), "france");
+        let europe = tag(Some("region"), "europe");
+        let world = tag(Some("region"), "world");
+        hierarchy
+            .set_parent(france.clone(), value("europe"))
+            .unwrap();
+        hierarchy
+            .set_parent(europe.clone(), value("world"))
+            .unwrap();
+        assert_eq!(hierarchy.parent(&france), Some(europe.clone()));
+        assert_eq!(
+            hierarchy.ancestors(&france),
+            vec![europe.clone(), world.clone()]
+        );
+        assert_eq!(hierarchy.parent(&world), None);
+        assert_eq!(hierarchy.children(&europe), vec![france.clone()]);
+        assert!(hierarchy.is_or_descends_from(&france, &world));
+        assert!(hierarchy.is_or_descends_from(&france, &france));
+        assert!(!hierarchy.is_or_descends_from(&world, &france));
+        assert!(hierarchy.value_is_or_descends_from(&france, &value("world")));
+
+        // Only tags with the same name are related
+        let unnamed = tag(None, "france");
+        assert_eq!(hierarchy.parent(&unnamed), None);
+        assert!(!hierarchy.is_or_descends_from(&unnamed, &tag(None, "europe")));
+
+        // Hyphens have no special meaning
+        let war = tag(None, "world-war-two");
+        assert!(!hierarchy.value_is_or_descends_from(&war, &value("world")));
+
+        // A tag can't become its own descendant
+        let result = hierarchy.set_parent(world.clone(), value("france"));
+        assert_eq!(result, Err(TagHierarchyError::Cycle));
+        let result = hierarchy.set_parent(world, value("world"));
+        assert_eq!(result, Err(TagHierarchyError::Cycle));
+
+        // Cycles from elsewhere don't loop forever
+        let hierarchy: TagHierarchy = [(tag(None, "a"), value("b")), (tag(None, "b"), value("a"))]
+            .into_iter()
+            .collect();
+        assert_eq!(hierarchy.ancestors(&tag(None, "a")), vec![tag(None, "b")]);
+    }
+}
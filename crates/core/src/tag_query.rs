@@ -0,0 +1,422 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! [`TagQuery`] - a boolean tag expression that can also compare the values of
+//! tags (e.g. `population > 1000000 & !capital`)
+//!
+
+use crate::{TagValueType, TypedTagValue, TypedTags};
+use bool_tag_expr::{BoolTagExpr, ParseError, Tag, TagError, TagName, TagValue, Tags};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt::Display;
+use thiserror::Error;
+
+/// Errors that can arise when parsing a [`TagQuery`]
+#[derive(Error, Debug, Clone, Hash, PartialEq, Eq)]
+pub enum TagQueryError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+
+    #[error("Invalid tag name in comparison: {0}")]
+    Name(#[from] TagError),
+
+    #[error("There is no tag name before `{0}`")]
+    MissingName(RangeOperator),
+
+    #[error("There is no value after `{0}`")]
+    MissingValue(RangeOperator),
+
+    #[error("`{0}` isn't a number, date, or tag value")]
+    Value(String),
+}
+
+/// How a [`TagRange`] compares a tag's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RangeOperator {
+    LessThan,
+    LessThanOrEqualTo,
+    GreaterThan,
+    GreaterThanOrEqualTo,
+}
+
+impl RangeOperator {
+    /// The operator as written in expressions (& SQL)
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            RangeOperator::LessThan => "<",
+            RangeOperator::LessThanOrEqualTo => "<=",
+            RangeOperator::GreaterThan => ">",
+            RangeOperator::GreaterThanOrEqualTo => ">=",
+        }
+    }
+
+    /// Whether a value that compares to the range's value like this is in the
+    /// range
+    fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            RangeOperator::LessThan => ordering.is_lt(),
+            RangeOperator::LessThanOrEqualTo => ordering.is_le(),
+            RangeOperator::GreaterThan => ordering.is_gt(),
+            RangeOperator::GreaterThanOrEqualTo => ordering.is_ge(),
+        }
+    }
+}
+
+impl Display for RangeOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// A comparison of the value of the tags with a name (e.g.
+/// `population > 1000000`).  The value is compared with the number & date
+/// [`TypedTag`](crate::TypedTag)s with the name if it can be parsed as their
+/// type, and (alphabetically) with the ordinary tags with the name if it is a
+/// valid tag value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TagRange {
+    name: TagName,
+    operator: RangeOperator,
+    typed_values: Vec<TypedTagValue>,
+    string_value: Option<TagValue>,
+}
+
+impl TagRange {
+    /// Create a range from the tag name, operator, & (unparsed) value
+    pub fn parse(name: &str, operator: RangeOperator, value: &str) -> Result<Self, TagQueryError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(TagQueryError::MissingValue(operator));
+        }
+        let typed_values: Vec<TypedTagValue> = TagValueType::TYPED
+            .iter()
+            .filter_map(|value_type| TypedTagValue::parse(*value_type, value).ok())
+            .collect();
+        let string_value = TagValue::from(&value).ok();
+        if typed_values.is_empty() && string_value.is_none() {
+            return Err(TagQueryError::Value(value.to_string()));
+        }
+        Ok(Self {
+            name: TagName::from(&name)?,
+            operator,
+            typed_values,
+            string_value,
+        })
+    }
+
+    /// Get the name of the tags compared
+    pub fn name(&self) -> &TagName {
+        &self.name
+    }
+
+    /// Get how the tags' values are compared
+    pub fn operator(&self) -> RangeOperator {
+        self.operator
+    }
+
+    /// Get the value as each type of typed tag it can be parsed as
+    pub fn typed_values(&self) -> &[TypedTagValue] {
+        &self.typed_values
+    }
+
+    /// Get the value as an ordinary tag value (if it is a valid one)
+    pub fn string_value(&self) -> Option<&TagValue> {
+        self.string_value.as_ref()
+    }
+
+    /// Whether any of the tags are in the range
+    pub fn matches(&self, tags: Option<&Tags>, typed_tags: Option<&TypedTags>) -> bool {
+        let typed_match = typed_tags.into_iter().flatten().any(|tag| {
+            tag.name == self.name
+                && self.typed_values.iter().any(|value| {
+                    value.value_type() == tag.value.value_type()
+                        && self.operator.holds(tag.value.cmp(value))
+                })
+        });
+        let string_match = self.string_value.as_ref().is_some_and(|value| {
+            tags.into_iter().flatten().any(|tag| {
+                tag.name.as_ref() == Some(&self.name)
+                    && self.operator.holds(tag.value.as_str().cmp(value.as_str()))
+            })
+        });
+        typed_match || string_match
+    }
+}
+
+/// A boolean tag expression that can also compare the values of tags using
+/// `<`, `<=`, `>`, & `>=` (e.g. `population > 1000000 & !capital`)
+///
+/// Each comparison is stood in for, in the [`BoolTagExpr`], by a placeholder
+/// tag (whose name doesn't appear anywhere in the query).  Use
+/// [`TagQuery::range()`] to get the [`TagRange`] a tag stands in for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TagQuery {
+    /// The query as written
+    text: String,
+
+    /// The query with placeholder tags in place of the comparisons
+    bool_expr: BoolTagExpr,
+
+    /// The name of the placeholder tags (whose values are the comparisons'
+    /// indices written in letters)
+    placeholder: TagName,
+
+    /// The comparisons
+    ranges: Vec<TagRange>,
+}
+
+impl TagQuery {
+    /// Parse a query
+    pub fn from<S: ToString>(text: S) -> Result<Self, TagQueryError> {
+        let text = text.to_string();
+        let placeholder = placeholder_name(&text);
+
+        let mut rewritten = String::new();
+        let mut ranges = Vec::new();
+        let mut rest = text.as_str();
+        while let Some(index) = rest.find(['<', '>']) {
+            // The operator
+            let after = &rest[index + 1..];
+            let (operator, after) = match (rest.as_bytes()[index], after.strip_prefix('=')) {
+                (b'<', None) => (RangeOperator::LessThan, after),
+                (b'<', Some(after)) => (RangeOperator::LessThanOrEqualTo, after),
+                (_, None) => (RangeOperator::GreaterThan, after),
+                (_, Some(after)) => (RangeOperator::GreaterThanOrEqualTo, after),
+            };
+
+            // The tag name is the tag-like word before the operator
+            let before = rest[..index].trim_end();
+            let name_start = before
+                .char_indices()
+                .rev()
+                .take_while(|(_, c)| c.is_ascii_alphabetic() || *c == '-')
+                .last()
+                .map_or(before.len(), |(start, _)| start);
+            let name = &before[name_start..];
+            if name.is_empty() {
+                return Err(TagQueryError::MissingName(operator));
+            }
+
+            // The value runs until the next `&` or `|` (or an unopened `)`)
+            let mut depth = 0;
+            let value_end = after
+                .char_indices()
+                .find(|(_, c)| match c {
+                    '(' => {
+                        depth += 1;
+                        false
+                    }
+                    ')' if depth > 0 => {
+                        depth -= 1;
+                        false
+                    }
+                    ')' | '&' | '|' => true,
+                    _ => false,
+                })
+                .map_or(after.len(), |(end, _)| end);
+
+            rewritten.push_str(&before[..name_start]);
+            rewritten.push_str(&format!(
+                " {placeholder}={} ",
+                placeholder_value(ranges.len())
+            ));
+            ranges.push(TagRange::parse(name, operator, &after[..value_end])?);
+            rest = &after[value_end..];
+        }
+        rewritten.push_str(rest);
+
+        Ok(Self {
+            bool_expr: BoolTagExpr::from(rewritten.trim())?,
+            text,
+            placeholder,
+            ranges,
+        })
+    }
+
+    /// Get the boolean tag expression, with placeholder tags in place of the
+    /// comparisons
+    pub fn bool_expr(&self) -> &BoolTagExpr {
+        &self.bool_expr
+    }
+
+    /// Get the comparisons
+    pub fn ranges(&self) -> &[TagRange] {
+        &self.ranges
+    }
+
+    /// Get the comparison a tag of the [`TagQuery::bool_expr()`] stands in for
+    /// (if it's a placeholder)
+    pub fn range(&self, tag: &Tag) -> Option<&TagRange> {
+        if tag.name.as_ref() != Some(&self.placeholder) {
+            return None;
+        }
+        (0..self.ranges.len())
+            .find(|index| placeholder_value(*index) == tag.value.as_str())
+            .map(|index| &self.ranges[index])
+    }
+
+    /// Get the query as written
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl From<BoolTagExpr> for TagQuery {
+    fn from(bool_expr: BoolTagExpr) -> Self {
+        let text = bool_expr.clone().to_boolean_expression();
+        Self {
+            placeholder: placeholder_name(&text),
+            text,
+            bool_expr,
+            ranges: Vec::new(),
+        }
+    }
+}
+
+impl Display for TagQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl Serialize for TagQuery {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for TagQuery {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let text = String::deserialize(deserializer)?;
+        TagQuery::from(text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A name for placeholder tags that doesn't appear anywhere in the text
+fn placeholder_name(text: &str) -> TagName {
+    let text = text.to_lowercase();
+    let name = (0..)
+        .map(|index| match index {
+            0 => String::from("range"),
+            index => format!("range-{}", placeholder_value(index)),
+        })
+        .find(|name| !text.contains(name.as_str()))
+        .unwrap();
+    TagName::from(&name).unwrap()
+}
+
+/// A placeholder tag's value (the index written in letters, i.e. `a`, `b`, ...
+/// `z`, `ba`, `bb`, ...)
+fn placeholder_value(mut index: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(b'a' + (index % 26) as u8);
+        index /= 26;
+        if index == 0 {
+            break;
+        }
+    }
+    letters.iter().rev().map(|letter| *letter as char).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{TagNumber, TypedTag};
+
+    fn tag(name: Option<&str>, value: &str) -> Tag {
+        Tag::from(
+            name.map(|name| TagName::from(&name).unwrap()),
+            TagValue::from(&value).unwrap(),
+        )
+    }
+
+    #[test]
+    fn parse() {
+        let query = TagQuery::from("population > 1,000,000 & !capital").unwrap();
+        assert_eq!(query.ranges().len(), 1);
+        let range = &query.ranges()[0];
+        assert_eq!(range.name().as_str(), "population");
+        assert_eq!(range.operator(), RangeOperator::GreaterThan);
+        assert_eq!(
+            range.typed_values(),
+            [TypedTagValue::Number(TagNumber::try_from(1e6).unwrap())]
+        );
+        assert_eq!(query.as_str(), "population > 1,000,000 & !capital");
+
+        // Dates (even with brackets & spaces) and `<=`/`>=`
+        let query =
+            TagQuery::from("(founded<=12 March 1945 (Julian)) | (ended >= 1990 & range)").unwrap();
+        assert_eq!(query.ranges().len(), 2);
+        assert_eq!(
+            query.ranges()[0].operator(),
+            RangeOperator::LessThanOrEqualTo
+        );
+        assert_eq!(
+            query.ranges()[1].operator(),
+            RangeOperator::GreaterThanOrEqualTo
+        );
+        assert_eq!(query.placeholder.as_str(), "range-b");
+
+        // Each placeholder tag stands in for its comparison
+        let placeholder = |index| tag(Some("range-b"), &placeholder_value(index));
+        assert_eq!(query.range(&placeholder(0)), Some(&query.ranges()[0]));
+        assert_eq!(query.range(&placeholder(1)), Some(&query.ranges()[1]));
+        assert_eq!(query.range(&tag(None, "range")), None);
+
+        // Errors
+        assert!(TagQuery::from("> 5").is_err());
+        assert!(TagQuery::from("population >").is_err());
+        assert!(TagQuery::from("population > ?").is_err());
+        assert!(TagQuery::from("population > 5 &").is_err());
+
+        // Plain boolean tag expressions
+        let query = TagQuery::from("a & b").unwrap();
+        assert!(query.ranges().is_empty());
+        assert_eq!(query.bool_expr(), &BoolTagExpr::from("a & b").unwrap());
+    }
+
+    #[test]
+    fn placeholder_values() {
+        assert_eq!(placeholder_value(0), "a");
+        assert_eq!(placeholder_value(25), "z");
+        assert_eq!(placeholder_value(26), "ba");
+    }
+
+    #[test]
+    fn range_matches() {
+        let typed_tags = TypedTags::from([
+            TypedTag::parse("population", TagValueType::Number, "5000").unwrap(),
+            TypedTag::parse("founded", TagValueType::Date, "1066").unwrap(),
+        ]);
+        let tags = Tags::from([tag(Some("colour"), "green")]);
+        let matches = |name, operator, value| {
+            TagRange::parse(name, operator, value)
+                .unwrap()
+                .matches(Some(&tags), Some(&typed_tags))
+        };
+        assert!(matches("population", RangeOperator::GreaterThan, "1000"));
+        assert!(matches(
+            "population",
+            RangeOperator::LessThanOrEqualTo,
+            "5000"
+        ));
+        assert!(!matches("population", RangeOperator::LessThan, "5000"));
+        assert!(matches(
+            "founded",
+            RangeOperator::LessThan,
+            "14 October 1067"
+        ));
+        assert!(!matches("founded", RangeOperator::GreaterThan, "1066"));
+        assert!(matches("colour", RangeOperator::GreaterThan, "blue"));
+        assert!(!matches("colour", RangeOperator::GreaterThan, "red"));
+        assert!(!matches("missing", RangeOperator::GreaterThan, "1"));
+    }
+}
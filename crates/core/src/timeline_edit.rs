@@ -4,7 +4,9 @@
 //! The OpenTimeline timeline view type
 //!
 
-use crate::{HasIdAndName, Name, OpenTimelineId, ReducedEntities, ReducedTimelines};
+use crate::{
+    HasIdAndName, IsReducedCollection, Name, OpenTimelineId, ReducedEntities, ReducedTimelines,
+};
 use bool_tag_expr::{BoolTagExpr, Tags};
 use serde::{Deserialize, Serialize};
 
@@ -30,6 +32,11 @@ pub struct TimelineEdit {
 
     /// The timeline's tags
     tags: Option<Tags>,
+
+    /// The version of the timeline in the database when it was fetched (if it
+    /// was), used to detect conflicting edits
+    #[serde(skip)]
+    version: Option<i64>,
 }
 
 impl TimelineEdit {
@@ -49,6 +56,7 @@ impl TimelineEdit {
             entities: None,
             subtimelines: None,
             tags: None,
+            version: None,
         };
 
         // TODO: some validation?
@@ -88,6 +96,21 @@ impl TimelineEdit {
         &self.bool_expr
     }
 
+    /// Whether the timeline is dynamic, i.e. defined purely by its boolean tag
+    /// expr (it has no direct entities or subtimelines), so its entities
+    /// change as entities gain/lose tags
+    pub fn is_dynamic(&self) -> bool {
+        let has_entities = self
+            .entities
+            .as_ref()
+            .is_some_and(|entities| !entities.collection().is_empty());
+        let has_subtimelines = self
+            .subtimelines
+            .as_ref()
+            .is_some_and(|subtimelines| !subtimelines.collection().is_empty());
+        self.bool_expr.is_some() && !has_entities && !has_subtimelines
+    }
+
     /// Borrow the timeline's entities
     pub fn entities(&self) -> &Option<ReducedEntities> {
         &self.entities
@@ -102,6 +125,18 @@ impl TimelineEdit {
     pub fn tags(&self) -> &Option<Tags> {
         &self.tags
     }
+
+    /// Get the version of the timeline in the database when it was fetched (if
+    /// known)
+    pub fn version(&self) -> Option<i64> {
+        self.version
+    }
+
+    /// Set the version of the timeline in the database.  Updates fail if the
+    /// version is set and no longer matches the database's.
+    pub fn set_version(&mut self, version: Option<i64>) {
+        self.version = version;
+    }
 }
 
 impl HasIdAndName for TimelineEdit {
@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Knight Lab's [TimelineJS](https://timeline.knightlab.com) JSON format
+//!
+//! Only the parts of the schema that OpenTimeline has equivalents for are
+//! included (media, backgrounds, eras, etc. are ignored).
+//!
+
+use crate::{Entity, HasIdAndName, TimelineView};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// A TimelineJS timeline
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimelineJs {
+    /// The title slide
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<TimelineJsSlide>,
+
+    /// The events
+    #[serde(default)]
+    pub events: Vec<TimelineJsSlide>,
+}
+
+/// A TimelineJS slide (the title or an event)
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimelineJsSlide {
+    /// When the event starts (required for events, not for the title)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<TimelineJsDate>,
+
+    /// When the event ends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<TimelineJsDate>,
+
+    /// The headline & (HTML) text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<TimelineJsText>,
+
+    /// Events in the same group are shown in the same row
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// An ID for the slide (unique within the timeline)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique_id: Option<String>,
+}
+
+/// A TimelineJS date.  The numbers can be given as JSON numbers or strings
+/// (the latter is what TimelineJS's spreadsheet template produces).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TimelineJsDate {
+    /// The year (negative is BCE)
+    #[serde(deserialize_with = "deserialize_number")]
+    pub year: i64,
+
+    /// The month (1-12)
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub month: Option<i64>,
+
+    /// The day of the month
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_number",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub day: Option<i64>,
+}
+
+/// A TimelineJS slide's text
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimelineJsText {
+    /// The headline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headline: Option<String>,
+
+    /// The body (which may contain HTML)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// Used to accept numbers given as strings
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrString {
+    Number(i64),
+    String(String),
+}
+
+impl NumberOrString {
+    /// The number (if there is one)
+    fn number<E: serde::de::Error>(self) -> Result<Option<i64>, E> {
+        match self {
+            Self::Number(number) => Ok(Some(number)),
+            Self::String(string) if string.trim().is_empty() => Ok(None),
+            Self::String(string) => string
+                .trim()
+                .parse()
+                .map(Some)
+                .map_err(|_| E::custom(format!("'{string}' is not a whole number"))),
+        }
+    }
+}
+
+/// Deserialise a number given as a JSON number or string
+fn deserialize_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    NumberOrString::deserialize(deserializer)?
+        .number()?
+        .ok_or_else(|| serde::de::Error::custom("missing number"))
+}
+
+/// Deserialise an optional number given as a JSON number, string, or null (an
+/// empty string is considered missing)
+fn deserialize_optional_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<i64>, D::Error> {
+    match Option::<NumberOrString>::deserialize(deserializer)? {
+        Some(number_or_string) => number_or_string.number(),
+        None => Ok(None),
+    }
+}
+
+impl From<&TimelineView> for TimelineJs {
+    /// Each of the timeline's entities is an event (OpenTimeline entities don't
+    /// have descriptions, so the text lists an entity's aliases & tags)
+    fn from(timeline: &TimelineView) -> Self {
+        let title = TimelineJsSlide {
+            text: Some(TimelineJsText {
+                headline: Some(escape_html(timeline.name().as_str())),
+                text: None,
+            }),
+            ..Default::default()
+        };
+        let events = timeline
+            .entities()
+            .iter()
+            .flatten()
+            .map(TimelineJsSlide::from)
+            .collect();
+        Self {
+            title: Some(title),
+            events,
+        }
+    }
+}
+
+impl From<&Entity> for TimelineJsSlide {
+    fn from(entity: &Entity) -> Self {
+        let start_date = TimelineJsDate {
+            year: entity.start_year().value().into(),
+            month: entity.start_month().map(|month| month.value().into()),
+            day: entity.start_day().map(|day| day.value().into()),
+        };
+        let end_date = entity.end_year().map(|year| TimelineJsDate {
+            year: year.value().into(),
+            month: entity.end_month().map(|month| month.value().into()),
+            day: entity.end_day().map(|day| day.value().into()),
+        });
+
+        let mut text = Vec::new();
+        if let Some(aliases) = entity
+            .aliases()
+            .as_ref()
+            .filter(|aliases| !aliases.is_empty())
+        {
+            let aliases: Vec<&str> = aliases.iter().map(|alias| alias.as_str()).collect();
+            text.push(format!(
+                "<p>Also known as: {}</p>",
+                escape_html(&aliases.join(", "))
+            ));
+        }
+        if let Some(tags) = entity.tags().as_ref().filter(|tags| !tags.is_empty()) {
+            let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+            text.push(format!("<p>Tags: {}</p>", escape_html(&tags.join(", "))));
+        }
+
+        Self {
+            start_date: Some(start_date),
+            end_date,
+            text: Some(TimelineJsText {
+                headline: Some(escape_html(entity.name().as_str())),
+                text: (!text.is_empty()).then(|| text.concat()),
+            }),
+            group: None,
+            unique_id: entity.id().map(|id| id.to_string()),
+        }
+    }
+}
+
+/// Escape the characters that have special meaning in HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Date, Name, OpenTimelineId};
+    use bool_tag_expr::{Tag, TagValue, Tags};
+
+    #[test]
+    fn from_timeline_view() {
+        let tags = Tags::from([Tag::from(None, TagValue::from(&"scientist").unwrap())]);
+        let entity = Entity::from(
+            Some(OpenTimelineId::new()),
+            Name::from("Marie Curie").unwrap(),
+            Date::from(Some(7), Some(11), 1867).unwrap(),
+            Some(Date::from(None, None, 1934).unwrap()),
+            Some(tags),
+        )
+        .unwrap();
+        let timeline = TimelineView::from(
+            OpenTimelineId::new(),
+            Name::from("Science & Medicine").unwrap(),
+            Some(vec![entity.clone()]),
+        );
+
+        let timeline_js = TimelineJs::from(&timeline);
+        let title = timeline_js.title.unwrap().text.unwrap();
+        assert_eq!(title.headline.unwrap(), "Science &amp; Medicine");
+
+        let event = &timeline_js.events[0];
+        assert_eq!(
+            event.start_date,
+            Some(TimelineJsDate {
+                year: 1867,
+                month: Some(11),
+                day: Some(7)
+            })
+        );
+        assert_eq!(event.end_date.as_ref().unwrap().year, 1934);
+        assert_eq!(event.end_date.as_ref().unwrap().month, None);
+        let text = event.text.as_ref().unwrap();
+        assert_eq!(text.headline.as_deref(), Some("Marie Curie"));
+        assert_eq!(text.text.as_deref(), Some("<p>Tags: =scientist</p>"));
+        assert_eq!(event.unique_id, entity.id().map(|id| id.to_string()));
+    }
+
+    #[test]
+    fn numbers_can_be_strings() {
+        let json = r#"{"events": [{"start_date": {"year": "-500", "month": "", "day": 3}}]}"#;
+        let timeline_js: TimelineJs = serde_json::from_str(json).unwrap();
+        let start_date = timeline_js.events[0].start_date.as_ref().unwrap();
+        assert_eq!(start_date.year, -500);
+        assert_eq!(start_date.month, None);
+        assert_eq!(start_date.day, Some(3));
+
+        let json = r#"{"events": [{"start_date": {"year": "soon"}}]}"#;
+        assert!(serde_json::from_str::<TimelineJs>(json).is_err());
+    }
+}
@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Typed tags - tags whose value is a number or a date (e.g. a `population` of
+//! `1000000`, or a `founded` date of `12 March 1945`).  Ordinary tags are
+//! strings (and their values can only contain letters & hyphens).  Typed tags
+//! can be compared in a [`TagQuery`](crate::TagQuery) (e.g.
+//! `population > 1000000`).
+//!
+
+use crate::{Date, DateParseError};
+use bool_tag_expr::{TagError, TagName};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The typed tags of an [`Entity`](crate::Entity)
+pub type TypedTags = BTreeSet<TypedTag>;
+
+/// Errors that can arise in relation to a [`TypedTag`]
+#[derive(Error, Debug, Clone)]
+pub enum TypedTagError {
+    #[error("Invalid tag name: {0}")]
+    Name(#[from] TagError),
+
+    #[error("`{0}` isn't a number")]
+    Number(String),
+
+    #[error("Invalid date: {0}")]
+    Date(#[from] DateParseError),
+
+    #[error("Tags with string values are ordinary tags")]
+    String,
+}
+
+/// The type of a tag's value
+#[derive(
+    Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(rename_all = "lowercase"))]
+pub enum TagValueType {
+    /// An ordinary tag (see [`Tag`](bool_tag_expr::Tag))
+    String,
+
+    /// A [`TagNumber`]
+    #[default]
+    Number,
+
+    /// A [`Date`]
+    Date,
+}
+
+impl TagValueType {
+    /// The types a [`TypedTag`] can have (useful for drawing a picker)
+    pub const TYPED: [TagValueType; 2] = [TagValueType::Number, TagValueType::Date];
+
+    /// The name of the type
+    pub fn name(&self) -> &'static str {
+        match self {
+            TagValueType::String => "String",
+            TagValueType::Number => "Number",
+            TagValueType::Date => "Date",
+        }
+    }
+
+    /// An example of a value of this type (e.g. for a text input's hint)
+    pub fn example(&self) -> &'static str {
+        match self {
+            TagValueType::String => "e.g. british",
+            TagValueType::Number => "e.g. 1000000",
+            TagValueType::Date => "e.g. 12 March 1945",
+        }
+    }
+}
+
+/// A finite number (so that, unlike `f64`, it has a total ordering)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(try_from = "f64")]
+pub struct TagNumber(f64);
+
+impl TagNumber {
+    /// Get the number
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+}
+
+impl TryFrom<f64> for TagNumber {
+    type Error = TypedTagError;
+
+    fn try_from(number: f64) -> Result<Self, Self::Error> {
+        match number.is_finite() {
+            true => Ok(Self(number)),
+            false => Err(TypedTagError::Number(number.to_string())),
+        }
+    }
+}
+
+impl FromStr for TagNumber {
+    type Err = TypedTagError;
+
+    /// Parse a number, allowing `_` & `,` as thousands separators (e.g.
+    /// `1,000,000`)
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let digits: String = text.trim().chars().filter(|c| !"_,".contains(*c)).collect();
+        digits
+            .parse::<f64>()
+            .map_err(|_| TypedTagError::Number(text.to_string()))
+            .and_then(TagNumber::try_from)
+    }
+}
+
+impl Display for TagNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PartialEq for TagNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for TagNumber {}
+
+impl PartialOrd for TagNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TagNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for TagNumber {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+/// The value of a [`TypedTag`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum TypedTagValue {
+    Number(TagNumber),
+    Date(Date),
+}
+
+impl TypedTagValue {
+    /// Parse a value of the type (strings aren't typed tag values, so can't be
+    /// parsed)
+    pub fn parse(value_type: TagValueType, text: &str) -> Result<Self, TypedTagError> {
+        match value_type {
+            TagValueType::String => Err(TypedTagError::String),
+            TagValueType::Number => Ok(TypedTagValue::Number(TagNumber::from_str(text)?)),
+            TagValueType::Date => Ok(TypedTagValue::Date(Date::from_str(text)?)),
+        }
+    }
+
+    /// Get the type of the value
+    pub fn value_type(&self) -> TagValueType {
+        match self {
+            TypedTagValue::Number(_) => TagValueType::Number,
+            TypedTagValue::Date(_) => TagValueType::Date,
+        }
+    }
+
+    /// A number that orders values of the same type in the same way as the
+    /// values themselves (dates are ordered as Gregorian dates, with missing
+    /// months & days as the first)
+    pub fn sort_key(&self) -> f64 {
+        match self {
+            TypedTagValue::Number(number) => number.value(),
+            TypedTagValue::Date(date) => {
+                let date = date.to_gregorian();
+                let month = date.month().map_or(1, |month| month.value());
+                let day = date.day().map_or(1, |day| day.value());
+                date.year().value() as f64 * 10_000.0 + month as f64 * 100.0 + day as f64
+            }
+        }
+    }
+}
+
+impl Display for TypedTagValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypedTagValue::Number(number) => write!(f, "{number}"),
+            TypedTagValue::Date(date) => write!(f, "{}", date.as_era_date_format()),
+        }
+    }
+}
+
+/// A tag whose value is a number or a date
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TypedTag {
+    pub name: TagName,
+    pub value: TypedTagValue,
+}
+
+impl TypedTag {
+    /// Create a [`TypedTag`] using a function rather than a struct literal
+    pub const fn from(name: TagName, value: TypedTagValue) -> Self {
+        Self { name, value }
+    }
+
+    /// Parse a typed tag from its name & a value of the type
+    pub fn parse(name: &str, value_type: TagValueType, value: &str) -> Result<Self, TypedTagError> {
+        Ok(Self {
+            name: TagName::from(&name)?,
+            value: TypedTagValue::parse(value_type, value)?,
+        })
+    }
+}
+
+impl Display for TypedTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let tag = TypedTag::parse("population", TagValueType::Number, " 1,000,000 ").unwrap();
+        assert_eq!(tag.value, TypedTagValue::Number(TagNumber(1_000_000.0)));
+        let tag = TypedTag::parse("Founded", TagValueType::Date, "12 March 1945").unwrap();
+        assert_eq!(tag.name.as_str(), "founded");
+        assert_eq!(tag.value.value_type(), TagValueType::Date);
+
+        assert!(TypedTag::parse("population", TagValueType::Number, "many").is_err());
+        assert!(TypedTag::parse("population", TagValueType::Number, "inf").is_err());
+        assert!(TypedTag::parse("founded", TagValueType::Date, "soon").is_err());
+        assert!(TypedTag::parse("in-2020", TagValueType::Number, "1").is_err());
+
+        // Values are displayed in a form that parses back to them
+        for (value_type, value) in [
+            (TagValueType::Number, "-2.5"),
+            (TagValueType::Number, "1e6"),
+            (TagValueType::Date, "12 March 1945"),
+            (TagValueType::Date, "500 BCE"),
+        ] {
+            let value = TypedTagValue::parse(value_type, value).unwrap();
+            let text = value.to_string();
+            assert_eq!(TypedTagValue::parse(value_type, &text).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn sort_keys_order_like_values() {
+        let dates = ["500 BCE", "12 March 1945", "April 1945", "1946"]
+            .map(|date| TypedTagValue::parse(TagValueType::Date, date).unwrap());
+        for pair in dates.windows(2) {
+            assert!(pair[0] < pair[1]);
+            assert!(pair[0].sort_key() < pair[1].sort_key());
+        }
+        let numbers = ["-3.5", "0", "10", "1e6"]
+            .map(|number| TypedTagValue::parse(TagValueType::Number, number).unwrap());
+        for pair in numbers.windows(2) {
+            assert!(pair[0] < pair[1]);
+            assert!(pair[0].sort_key() < pair[1].sort_key());
+        }
+    }
+}
@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Property tests for (de)serialising the core types.
+//!
+//! Valid values must survive a JSON round trip unchanged, & malformed JSON
+//! (wrong types, missing fields, out of range values, or just noise) must be
+//! rejected with an error rather than a panic or an invalid value.
+//!
+
+use bool_tag_expr::{Tag, TagName, TagValue, Tags};
+use open_timeline_core::{
+    Date, Entity, HasIdAndName, IsReducedType, MAX_YEAR, MIN_YEAR, Name, OpenTimelineId,
+    ReducedEntities, ReducedEntity, TimelineEdit,
+};
+use proptest::prelude::*;
+use serde_json::{Value, json};
+
+/// Any valid year
+fn year() -> impl Strategy<Value = i64> {
+    MIN_YEAR..=MAX_YEAR
+}
+
+/// Any valid date (with or without a month & day)
+fn date() -> impl Strategy<Value = Date> {
+    (
+        prop::option::of(1i64..=31),
+        prop::option::of(1i64..=12),
+        year(),
+    )
+        .prop_filter_map("valid date", |(day, month, year)| {
+            Date::from(day, month, year).ok()
+        })
+}
+
+/// Any valid name (which can contain any characters apart from only
+/// whitespace)
+fn name() -> impl Strategy<Value = Name> {
+    "\\PC{1,40}".prop_filter_map("valid name", |name| Name::from(name).ok())
+}
+
+/// Any ID
+fn id() -> impl Strategy<Value = OpenTimelineId> {
+    any::<u128>().prop_map(|bits| OpenTimelineId::from(format!("{bits:032x}")).unwrap())
+}
+
+/// Any valid tag, with or without a name
+fn tag() -> impl Strategy<Value = Tag> {
+    let component = "[a-z]{1,6}(-[a-z]{1,6})?";
+    (prop::option::of(component), component).prop_map(|(name, value)| {
+        Tag::from(
+            name.map(|name| TagName::from(&name).unwrap()),
+            TagValue::from(&value).unwrap(),
+        )
+    })
+}
+
+/// Any valid set of tags (or none)
+fn tags() -> impl Strategy<Value = Option<Tags>> {
+    prop::option::of(prop::collection::btree_set(tag(), 0..5))
+}
+
+/// Any valid entity
+fn entity() -> impl Strategy<Value = Entity> {
+    (
+        prop::option::of(id()),
+        name(),
+        date(),
+        prop::option::of(date()),
+        tags(),
+        prop::collection::vec(name(), 0..3),
+    )
+        .prop_filter_map("valid dates", |(id, name, start, end, tags, aliases)| {
+            let mut entity = Entity::from(id, name, start, end, tags).ok()?;
+            for alias in aliases {
+                entity.add_alias(alias);
+            }
+            Some(entity)
+        })
+}
+
+/// Any valid timeline
+fn timeline() -> impl Strategy<Value = TimelineEdit> {
+    let entities = prop::collection::vec((id(), name()), 0..5).prop_map(|entities| {
+        entities
+            .into_iter()
+            .map(|(id, name)| ReducedEntity::from_id_and_name(id, name))
+            .collect::<ReducedEntities>()
+    });
+    (
+        prop::option::of(id()),
+        name(),
+        prop::option::of(entities),
+        tags(),
+    )
+        .prop_map(|(id, name, entities, tags)| {
+            TimelineEdit::from(id, name, None, entities, None, tags).unwrap()
+        })
+}
+
+/// JSON values that are never valid names or dates
+fn wrong_type() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        Just(json!([])),
+        Just(json!(["a"])),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn date_round_trip(date in date()) {
+        let json = serde_json::to_string(&date).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Date>(&json).unwrap(), date);
+    }
+
+    #[test]
+    fn name_round_trip(name in name()) {
+        let json = serde_json::to_string(&name).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Name>(&json).unwrap(), name);
+    }
+
+    #[test]
+    fn entity_round_trip(entity in entity()) {
+        let json = serde_json::to_string(&entity).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Entity>(&json).unwrap(), entity);
+    }
+
+    #[test]
+    fn timeline_round_trip(timeline in timeline()) {
+        let json = serde_json::to_string(&timeline).unwrap();
+        prop_assert_eq!(serde_json::from_str::<TimelineEdit>(&json).unwrap(), timeline);
+    }
+
+    #[test]
+    fn names_are_trimmed_and_not_blank(text in "\\PC{0,20}") {
+        let name = serde_json::from_str::<Name>(&json!(text).to_string());
+        match text.trim() {
+            "" => prop_assert!(name.is_err()),
+            trimmed => {
+                let name = name.unwrap();
+                prop_assert_eq!(name.as_str(), trimmed);
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range_dates_are_rejected(
+        (day, month, year) in prop_oneof![
+            (Just(Some(1)), Just(Some(1)), (MAX_YEAR + 1)..i64::from(i32::MAX)),
+            (Just(Some(1)), Just(Some(1)), i64::from(i32::MIN)..MIN_YEAR),
+            (Just(Some(1)), (13i64..1000).prop_map(Some), year()),
+            (Just(Some(1)), (-1000i64..1).prop_map(Some), year()),
+            ((32i64..1000).prop_map(Some), Just(Some(1)), year()),
+            ((1i64..=31).prop_map(Some), Just(None), year()),
+        ]
+    ) {
+        let json = json!({ "day": day, "month": month, "year": year }).to_string();
+        prop_assert!(serde_json::from_str::<Date>(&json).is_err(), "{}", json);
+    }
+
+    #[test]
+    fn entities_ending_before_they_start_are_rejected(
+        start in date(),
+        years_before in 1i64..1000,
+    ) {
+        let end_year = start.year().value() as i64 - years_before;
+        prop_assume!(end_year >= MIN_YEAR);
+        let mut json = json!({ "name": "A", "start": start, "end": { "year": end_year } });
+        prop_assert!(serde_json::from_value::<Entity>(json.clone()).is_err());
+
+        // An end with a day or month but no year is rejected too
+        json["end"] = json!({ "day": 1, "month": 1, "year": null });
+        prop_assert!(serde_json::from_value::<Entity>(json).is_err());
+    }
+
+    #[test]
+    fn entities_with_wrong_types_are_rejected(
+        entity in entity(),
+        field in prop::sample::select(vec!["name", "start"]),
+        value in wrong_type(),
+    ) {
+        let mut json = serde_json::to_value(&entity).unwrap();
+        json[field] = value;
+        prop_assert!(serde_json::from_value::<Entity>(json).is_err());
+    }
+
+    #[test]
+    fn entities_missing_fields_are_rejected(
+        entity in entity(),
+        field in prop::sample::select(vec!["name", "start"]),
+    ) {
+        let mut json = serde_json::to_value(&entity).unwrap();
+        json.as_object_mut().unwrap().remove(field);
+        prop_assert!(serde_json::from_value::<Entity>(json).is_err());
+    }
+
+    #[test]
+    fn timelines_with_wrong_types_are_rejected(
+        timeline in timeline(),
+        field in prop::sample::select(vec!["id", "name", "entities", "tags"]),
+        value in prop_oneof![Just(json!(1)), Just(json!("")), Just(json!([1]))],
+    ) {
+        let mut json = serde_json::to_value(&timeline).unwrap();
+        json[field] = value;
+        prop_assert!(serde_json::from_value::<TimelineEdit>(json).is_err());
+    }
+
+    #[test]
+    fn noise_does_not_panic(text in "\\PC{0,64}") {
+        // Anything can be sent, so none of these may panic
+        let _ = serde_json::from_str::<Date>(&text);
+        let _ = serde_json::from_str::<Name>(&text);
+        let _ = serde_json::from_str::<Entity>(&text);
+        let _ = serde_json::from_str::<TimelineEdit>(&text);
+    }
+
+    #[test]
+    fn truncated_json_is_rejected(entity in entity(), cut in any::<prop::sample::Index>()) {
+        let json = serde_json::to_string(&entity).unwrap();
+        let cut = cut.index(json.len());
+        prop_assume!(json.is_char_boundary(cut));
+        prop_assert!(serde_json::from_str::<Entity>(&json[..cut]).is_err());
+    }
+}
+
+#[test]
+fn ids_are_kept_through_a_round_trip() {
+    let id = OpenTimelineId::from("6474cd74-244d-449b-a3d1-3a74019ec6f5").unwrap();
+    let entity = Entity::from(
+        Some(id),
+        Name::from("Noam").unwrap(),
+        Date::from(None, None, 1928).unwrap(),
+        None,
+        None,
+    )
+    .unwrap();
+    let json = serde_json::to_string(&entity).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Entity>(&json).unwrap().id(),
+        Some(id)
+    );
+}
@@ -0,0 +1,8 @@
+
+--------------------------------------------------------------------------------
+-- The calendar each entity date was recorded in ('gregorian', 'julian', or
+-- NULL if unspecified)
+--------------------------------------------------------------------------------
+
+ALTER TABLE entities ADD COLUMN start_calendar TEXT;
+ALTER TABLE entities ADD COLUMN end_calendar TEXT;
@@ -0,0 +1,16 @@
+--------------------------------------------------------------------------------
+-- Other names entities are known by
+--------------------------------------------------------------------------------
+
+CREATE TABLE entity_aliases (
+    entity_id          TEXT NOT NULL,
+    alias              TEXT NOT NULL,
+
+    UNIQUE (entity_id, alias),
+    FOREIGN KEY (entity_id) REFERENCES entities (id)
+);
+
+CREATE INDEX idx_entity_aliases_entity_id
+    ON entity_aliases(entity_id);
+CREATE INDEX idx_entity_aliases_alias
+    ON entity_aliases(alias);
@@ -0,0 +1,28 @@
+--------------------------------------------------------------------------------
+-- Sources (citations) and what they're cited for
+--------------------------------------------------------------------------------
+
+CREATE TABLE sources (
+    id                 TEXT NOT NULL UNIQUE,
+    title              TEXT NOT NULL,
+    url                TEXT,
+    citation           TEXT,
+
+    PRIMARY KEY (id)
+);
+
+-- `scope` is what the source is cited for ('entity', 'start' or 'end')
+CREATE TABLE entity_sources (
+    entity_id          TEXT NOT NULL,
+    source_id          TEXT NOT NULL,
+    scope              TEXT NOT NULL,
+
+    UNIQUE (entity_id, source_id, scope),
+    FOREIGN KEY (entity_id) REFERENCES entities (id),
+    FOREIGN KEY (source_id) REFERENCES sources (id)
+);
+
+CREATE INDEX idx_entity_sources_entity_id
+    ON entity_sources(entity_id);
+CREATE INDEX idx_entity_sources_source_id
+    ON entity_sources(source_id);
@@ -0,0 +1,11 @@
+--------------------------------------------------------------------------------
+-- Where each entity happened/existed (a place name and/or coordinates in
+-- decimal degrees, all NULL if unspecified)
+--------------------------------------------------------------------------------
+
+ALTER TABLE entities ADD COLUMN place TEXT;
+ALTER TABLE entities ADD COLUMN latitude REAL;
+ALTER TABLE entities ADD COLUMN longitude REAL;
+
+CREATE INDEX idx_entities_latitude
+    ON entities(latitude);
@@ -0,0 +1,13 @@
+--------------------------------------------------------------------------------
+-- Starred and recently opened entities & timelines
+--------------------------------------------------------------------------------
+
+CREATE TABLE favourites (
+    item_id            TEXT NOT NULL UNIQUE,
+    kind               TEXT NOT NULL,
+    starred            BOOLEAN NOT NULL DEFAULT FALSE,
+    opened_order       INTEGER
+);
+
+CREATE INDEX idx_favourites_opened_order
+    ON favourites(opened_order);
@@ -0,0 +1,165 @@
+--------------------------------------------------------------------------------
+-- Materialised table row counts (kept up to date by triggers so that counting
+-- rows doesn't require scanning the tables)
+--------------------------------------------------------------------------------
+
+CREATE TABLE table_row_counts (
+    table_name         TEXT NOT NULL UNIQUE,
+    row_count          INTEGER NOT NULL,
+    updated_at         INTEGER NOT NULL DEFAULT (unixepoch()),
+
+    PRIMARY KEY (table_name)
+);
+
+INSERT INTO table_row_counts (table_name, row_count)
+    SELECT 'entities', COUNT(*) FROM entities;
+
+CREATE TRIGGER trg_entities_row_count_insert AFTER INSERT ON entities
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count + 1, updated_at = unixepoch()
+    WHERE table_name = 'entities';
+END;
+
+CREATE TRIGGER trg_entities_row_count_delete AFTER DELETE ON entities
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count - 1, updated_at = unixepoch()
+    WHERE table_name = 'entities';
+END;
+
+INSERT INTO table_row_counts (table_name, row_count)
+    SELECT 'entity_tags', COUNT(*) FROM entity_tags;
+
+CREATE TRIGGER trg_entity_tags_row_count_insert AFTER INSERT ON entity_tags
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count + 1, updated_at = unixepoch()
+    WHERE table_name = 'entity_tags';
+END;
+
+CREATE TRIGGER trg_entity_tags_row_count_delete AFTER DELETE ON entity_tags
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count - 1, updated_at = unixepoch()
+    WHERE table_name = 'entity_tags';
+END;
+
+INSERT INTO table_row_counts (table_name, row_count)
+    SELECT 'entity_aliases', COUNT(*) FROM entity_aliases;
+
+CREATE TRIGGER trg_entity_aliases_row_count_insert AFTER INSERT ON entity_aliases
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count + 1, updated_at = unixepoch()
+    WHERE table_name = 'entity_aliases';
+END;
+
+CREATE TRIGGER trg_entity_aliases_row_count_delete AFTER DELETE ON entity_aliases
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count - 1, updated_at = unixepoch()
+    WHERE table_name = 'entity_aliases';
+END;
+
+INSERT INTO table_row_counts (table_name, row_count)
+    SELECT 'sources', COUNT(*) FROM sources;
+
+CREATE TRIGGER trg_sources_row_count_insert AFTER INSERT ON sources
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count + 1, updated_at = unixepoch()
+    WHERE table_name = 'sources';
+END;
+
+CREATE TRIGGER trg_sources_row_count_delete AFTER DELETE ON sources
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count - 1, updated_at = unixepoch()
+    WHERE table_name = 'sources';
+END;
+
+INSERT INTO table_row_counts (table_name, row_count)
+    SELECT 'entity_sources', COUNT(*) FROM entity_sources;
+
+CREATE TRIGGER trg_entity_sources_row_count_insert AFTER INSERT ON entity_sources
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count + 1, updated_at = unixepoch()
+    WHERE table_name = 'entity_sources';
+END;
+
+CREATE TRIGGER trg_entity_sources_row_count_delete AFTER DELETE ON entity_sources
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count - 1, updated_at = unixepoch()
+    WHERE table_name = 'entity_sources';
+END;
+
+INSERT INTO table_row_counts (table_name, row_count)
+    SELECT 'timelines', COUNT(*) FROM timelines;
+
+CREATE TRIGGER trg_timelines_row_count_insert AFTER INSERT ON timelines
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count + 1, updated_at = unixepoch()
+    WHERE table_name = 'timelines';
+END;
+
+CREATE TRIGGER trg_timelines_row_count_delete AFTER DELETE ON timelines
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count - 1, updated_at = unixepoch()
+    WHERE table_name = 'timelines';
+END;
+
+INSERT INTO table_row_counts (table_name, row_count)
+    SELECT 'subtimelines', COUNT(*) FROM subtimelines;
+
+CREATE TRIGGER trg_subtimelines_row_count_insert AFTER INSERT ON subtimelines
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count + 1, updated_at = unixepoch()
+    WHERE table_name = 'subtimelines';
+END;
+
+CREATE TRIGGER trg_subtimelines_row_count_delete AFTER DELETE ON subtimelines
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count - 1, updated_at = unixepoch()
+    WHERE table_name = 'subtimelines';
+END;
+
+INSERT INTO table_row_counts (table_name, row_count)
+    SELECT 'timeline_entities', COUNT(*) FROM timeline_entities;
+
+CREATE TRIGGER trg_timeline_entities_row_count_insert AFTER INSERT ON timeline_entities
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count + 1, updated_at = unixepoch()
+    WHERE table_name = 'timeline_entities';
+END;
+
+CREATE TRIGGER trg_timeline_entities_row_count_delete AFTER DELETE ON timeline_entities
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count - 1, updated_at = unixepoch()
+    WHERE table_name = 'timeline_entities';
+END;
+
+INSERT INTO table_row_counts (table_name, row_count)
+    SELECT 'timeline_tags', COUNT(*) FROM timeline_tags;
+
+CREATE TRIGGER trg_timeline_tags_row_count_insert AFTER INSERT ON timeline_tags
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count + 1, updated_at = unixepoch()
+    WHERE table_name = 'timeline_tags';
+END;
+
+CREATE TRIGGER trg_timeline_tags_row_count_delete AFTER DELETE ON timeline_tags
+BEGIN
+    UPDATE table_row_counts
+    SET row_count = row_count - 1, updated_at = unixepoch()
+    WHERE table_name = 'timeline_tags';
+END;
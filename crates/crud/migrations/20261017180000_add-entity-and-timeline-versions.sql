@@ -0,0 +1,7 @@
+--------------------------------------------------------------------------------
+-- Versions of entities & timelines (incremented on each update so that
+-- conflicting edits can be detected)
+--------------------------------------------------------------------------------
+
+ALTER TABLE entities ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE timelines ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,23 @@
+--------------------------------------------------------------------------------
+-- URL-safe slugs for entities & timelines (used in permalinks), and the slugs
+-- they used to have (so that old permalinks can be redirected)
+--------------------------------------------------------------------------------
+
+ALTER TABLE entities ADD COLUMN slug TEXT;
+ALTER TABLE timelines ADD COLUMN slug TEXT;
+
+CREATE UNIQUE INDEX idx_entities_slug
+    ON entities(slug);
+CREATE UNIQUE INDEX idx_timelines_slug
+    ON timelines(slug);
+
+CREATE TABLE slug_redirects (
+    slug               TEXT NOT NULL,
+    kind               TEXT NOT NULL,
+    item_id            TEXT NOT NULL,
+
+    UNIQUE (kind, slug)
+);
+
+CREATE INDEX idx_slug_redirects_item_id
+    ON slug_redirects(item_id);
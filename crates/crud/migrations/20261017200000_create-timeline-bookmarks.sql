@@ -0,0 +1,15 @@
+--------------------------------------------------------------------------------
+-- Named positions on a timeline (so the user can jump between them)
+--------------------------------------------------------------------------------
+
+CREATE TABLE timeline_bookmarks (
+    timeline_id        TEXT NOT NULL,
+    name               TEXT NOT NULL,
+    start_year         REAL NOT NULL,
+    offset_y           REAL NOT NULL,
+    zoom               REAL NOT NULL,
+    datetime_scale     REAL NOT NULL,
+
+    UNIQUE (timeline_id, name),
+    FOREIGN KEY (timeline_id) REFERENCES timelines (id)
+);
@@ -0,0 +1,18 @@
+--------------------------------------------------------------------------------
+-- Stories (guided tours through a timeline, one step at a time).  Each step
+-- shows either an entity or a range of years.
+--------------------------------------------------------------------------------
+
+CREATE TABLE timeline_story_steps (
+    timeline_id        TEXT NOT NULL,
+    position           INTEGER NOT NULL,
+    entity_id          TEXT,
+    start_year         REAL,
+    end_year           REAL,
+    caption            TEXT NOT NULL,
+
+    UNIQUE (timeline_id, position),
+    CHECK (entity_id IS NOT NULL OR (start_year IS NOT NULL AND end_year IS NOT NULL)),
+    FOREIGN KEY (timeline_id) REFERENCES timelines (id),
+    FOREIGN KEY (entity_id) REFERENCES entities (id)
+);
@@ -0,0 +1,117 @@
+--------------------------------------------------------------------------------
+-- When entities & timelines were last modified (unix time, in seconds), so
+-- that syncing clients can fetch only what has changed.  Kept up to date by
+-- triggers, including when the rows that belong to them (tags, members, etc)
+-- change.  Rows updated without changing `updated_at` get the current time.
+--------------------------------------------------------------------------------
+
+ALTER TABLE entities ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE timelines ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0;
+
+UPDATE entities SET updated_at = unixepoch();
+UPDATE timelines SET updated_at = unixepoch();
+
+CREATE INDEX idx_entities_updated_at
+    ON entities(updated_at);
+CREATE INDEX idx_timelines_updated_at
+    ON timelines(updated_at);
+
+--------------------------------------------------------------------------------
+-- Entities
+--------------------------------------------------------------------------------
+
+CREATE TRIGGER trg_entities_updated_at_insert AFTER INSERT ON entities
+BEGIN
+    UPDATE entities SET updated_at = unixepoch() WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER trg_entities_updated_at_update AFTER UPDATE ON entities
+WHEN NEW.updated_at IS OLD.updated_at
+BEGIN
+    UPDATE entities SET updated_at = unixepoch() WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER trg_entity_tags_updated_at_insert AFTER INSERT ON entity_tags
+BEGIN
+    UPDATE entities SET updated_at = unixepoch() WHERE id = NEW.entity_id;
+END;
+
+CREATE TRIGGER trg_entity_tags_updated_at_delete AFTER DELETE ON entity_tags
+BEGIN
+    UPDATE entities SET updated_at = unixepoch() WHERE id = OLD.entity_id;
+END;
+
+CREATE TRIGGER trg_entity_aliases_updated_at_insert AFTER INSERT ON entity_aliases
+BEGIN
+    UPDATE entities SET updated_at = unixepoch() WHERE id = NEW.entity_id;
+END;
+
+CREATE TRIGGER trg_entity_aliases_updated_at_delete AFTER DELETE ON entity_aliases
+BEGIN
+    UPDATE entities SET updated_at = unixepoch() WHERE id = OLD.entity_id;
+END;
+
+CREATE TRIGGER trg_entity_sources_updated_at_insert AFTER INSERT ON entity_sources
+BEGIN
+    UPDATE entities SET updated_at = unixepoch() WHERE id = NEW.entity_id;
+END;
+
+CREATE TRIGGER trg_entity_sources_updated_at_delete AFTER DELETE ON entity_sources
+BEGIN
+    UPDATE entities SET updated_at = unixepoch() WHERE id = OLD.entity_id;
+END;
+
+--------------------------------------------------------------------------------
+-- Timelines
+--------------------------------------------------------------------------------
+
+CREATE TRIGGER trg_timelines_updated_at_insert AFTER INSERT ON timelines
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER trg_timelines_updated_at_update AFTER UPDATE ON timelines
+WHEN NEW.updated_at IS OLD.updated_at
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER trg_timeline_tags_updated_at_insert AFTER INSERT ON timeline_tags
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = NEW.timeline_id;
+END;
+
+CREATE TRIGGER trg_timeline_tags_updated_at_delete AFTER DELETE ON timeline_tags
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = OLD.timeline_id;
+END;
+
+CREATE TRIGGER trg_timeline_entities_updated_at_insert AFTER INSERT ON timeline_entities
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = NEW.timeline_id;
+END;
+
+CREATE TRIGGER trg_timeline_entities_updated_at_delete AFTER DELETE ON timeline_entities
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = OLD.timeline_id;
+END;
+
+CREATE TRIGGER trg_subtimelines_updated_at_insert AFTER INSERT ON subtimelines
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = NEW.timeline_parent_id;
+END;
+
+CREATE TRIGGER trg_subtimelines_updated_at_delete AFTER DELETE ON subtimelines
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = OLD.timeline_parent_id;
+END;
+
+CREATE TRIGGER trg_timeline_story_steps_updated_at_insert AFTER INSERT ON timeline_story_steps
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = NEW.timeline_id;
+END;
+
+CREATE TRIGGER trg_timeline_story_steps_updated_at_delete AFTER DELETE ON timeline_story_steps
+BEGIN
+    UPDATE timelines SET updated_at = unixepoch() WHERE id = OLD.timeline_id;
+END;
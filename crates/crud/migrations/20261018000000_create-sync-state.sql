@@ -0,0 +1,23 @@
+--------------------------------------------------------------------------------
+-- Syncing with remote OpenTimeline web APIs.  For each remote: when changes
+-- were last pulled (the remote's time) & pushed (this database's time), and
+-- a hash of each entity & timeline as it was when last synced (to tell which
+-- side changed it since).
+--------------------------------------------------------------------------------
+
+CREATE TABLE sync_state (
+    remote_url         TEXT NOT NULL UNIQUE,
+    pulled_at          INTEGER NOT NULL,
+    pushed_at          INTEGER NOT NULL,
+
+    PRIMARY KEY (remote_url)
+);
+
+CREATE TABLE sync_items (
+    remote_url         TEXT NOT NULL,
+    item_id            TEXT NOT NULL,
+    content_hash       INTEGER NOT NULL,
+
+    UNIQUE (remote_url, item_id),
+    FOREIGN KEY (remote_url) REFERENCES sync_state (remote_url)
+);
@@ -0,0 +1,15 @@
+--------------------------------------------------------------------------------
+-- Named entity & timeline searches (a partial name and/or a boolean tag
+-- expression), and when each was last run (unix time, in seconds) so that new
+-- matches since can be counted
+--------------------------------------------------------------------------------
+
+CREATE TABLE saved_searches (
+    name               TEXT NOT NULL,
+    kind               TEXT NOT NULL,
+    partial_name       TEXT,
+    bool_tag_expr      TEXT,
+    last_run_at        INTEGER NOT NULL DEFAULT (unixepoch()),
+
+    PRIMARY KEY (kind, name)
+);
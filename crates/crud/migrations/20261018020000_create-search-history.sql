@@ -0,0 +1,16 @@
+--------------------------------------------------------------------------------
+-- Recently used (and pinned) search queries, by what they search by (a partial
+-- name or a boolean tag expression)
+--------------------------------------------------------------------------------
+
+CREATE TABLE search_history (
+    kind               TEXT NOT NULL,
+    query              TEXT NOT NULL,
+    pinned             BOOLEAN NOT NULL DEFAULT FALSE,
+    used_order         INTEGER NOT NULL,
+
+    UNIQUE (kind, query)
+);
+
+CREATE INDEX idx_search_history_used_order
+    ON search_history(used_order);
@@ -0,0 +1,26 @@
+--------------------------------------------------------------------------------
+-- Entity tags whose values are numbers or dates.  `value_type` is 'number' or
+-- 'date'.  Numbers are in `number`, and dates in `day`, `month`, `year`, &
+-- `calendar` (as for the entities' dates).  `sort_key` orders values of the
+-- same type (so that they can be compared, e.g. `population > 1000000`).
+--------------------------------------------------------------------------------
+
+CREATE TABLE entity_typed_tags (
+    entity_id          TEXT NOT NULL,
+    name               TEXT NOT NULL,
+    value_type         TEXT NOT NULL,
+    number             REAL,
+    day                INTEGER,
+    month              INTEGER,
+    year               INTEGER,
+    calendar           TEXT,
+    sort_key           REAL NOT NULL,
+
+    UNIQUE (entity_id, name, value_type, sort_key),
+    FOREIGN KEY (entity_id) REFERENCES entities (id)
+);
+
+CREATE INDEX idx_entity_typed_tags_entity_id
+    ON entity_typed_tags(entity_id);
+CREATE INDEX idx_entity_typed_tags_name_sort_key
+    ON entity_typed_tags(name, value_type, sort_key);
@@ -0,0 +1,26 @@
+--------------------------------------------------------------------------------
+-- When entities & timelines were created (unix time, in seconds), so that
+-- recent changes can say what is new.  Set by triggers.  Existing rows get the
+-- time they were last modified (the best that is known).
+--------------------------------------------------------------------------------
+
+ALTER TABLE entities ADD COLUMN created_at INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE timelines ADD COLUMN created_at INTEGER NOT NULL DEFAULT 0;
+
+UPDATE entities SET created_at = updated_at;
+UPDATE timelines SET created_at = updated_at;
+
+CREATE INDEX idx_entities_created_at
+    ON entities(created_at);
+CREATE INDEX idx_timelines_created_at
+    ON timelines(created_at);
+
+CREATE TRIGGER trg_entities_created_at_insert AFTER INSERT ON entities
+BEGIN
+    UPDATE entities SET created_at = unixepoch() WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER trg_timelines_created_at_insert AFTER INSERT ON timelines
+BEGIN
+    UPDATE timelines SET created_at = unixepoch() WHERE id = NEW.id;
+END;
@@ -0,0 +1,12 @@
+--------------------------------------------------------------------------------
+-- Settings that belong to the database (rather than to the app using it).  A
+-- single row.  `automatic_period_tags` is whether entities are given `decade`
+-- & `century` typed tags derived from their dates.
+--------------------------------------------------------------------------------
+
+CREATE TABLE database_settings (
+    id                     INTEGER PRIMARY KEY CHECK (id = 1),
+    automatic_period_tags  BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+INSERT INTO database_settings (id) VALUES (1);
@@ -0,0 +1,30 @@
+--------------------------------------------------------------------------------
+-- Hierarchical tags.  A tag (`name` & `value`) can have a parent tag with the
+-- same name (e.g. `region=france`'s parent is `region=europe`), & a tag in a
+-- boolean tag expression also matches its descendants.  `tag_ancestors` holds
+-- every tag that has a parent plus the values of all of its ancestors.
+--------------------------------------------------------------------------------
+
+CREATE TABLE tag_parents (
+    name          TEXT,
+    value         TEXT NOT NULL,
+    parent_value  TEXT NOT NULL,
+
+    CHECK (value != parent_value)
+);
+
+CREATE UNIQUE INDEX idx_tag_parents_name_value
+    ON tag_parents(ifnull(name, ''), value);
+
+CREATE VIEW tag_ancestors AS
+    WITH RECURSIVE ancestors (name, value, ancestor_value) AS (
+        SELECT name, value, parent_value
+        FROM tag_parents
+        UNION
+        SELECT ancestors.name, ancestors.value, tag_parents.parent_value
+        FROM ancestors
+        JOIN tag_parents
+            ON tag_parents.name IS ancestors.name
+            AND tag_parents.value = ancestors.ancestor_value
+    )
+    SELECT name, value, ancestor_value FROM ancestors;
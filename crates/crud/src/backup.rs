@@ -6,13 +6,22 @@
 
 use crate::crud::{Create, CrudError, FetchById, Update};
 use crate::{is_entity_id_in_db, is_timeline_id_in_db};
-use log::warn;
-use open_timeline_core::{Entity, HasIdAndName, OpenTimelineId, TimelineEdit};
+use open_timeline_core::{Entity, ErrorCode, HasIdAndName, OpenTimelineId, TimelineEdit};
+use serde::de::{DeserializeOwned, IgnoredAny, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
 use sqlx::{Sqlite, Transaction};
+use std::fmt;
 use std::fs::File;
 use std::io::{BufReader, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use thiserror::Error;
+use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender};
+use tracing::warn;
+
+/// How many merged in/restored items can be read ahead of being saved
+const STREAM_BUFFER_SIZE: usize = 64;
 
 /// Possible operations & used to indicate success
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
@@ -51,6 +60,116 @@ pub enum BackupRestoreMergeError {
     /// An error when fetching from a web API.
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
+
+    /// The merge/restore was cancelled (nothing has been changed)
+    #[error("cancelled")]
+    Cancelled,
+}
+
+impl ErrorCode for BackupRestoreMergeError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::CrudError(error) => error.error_code(),
+            Self::StdIo(_) => "io_error",
+            Self::Sqlx(_) => "database_error",
+            Self::SerdeJson(_) => "json_error",
+            Self::Reqwest(_) => "remote_api_error",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// The stages of a merge/restore (reported along with progress)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RestorePhase {
+    /// Counting the entities & timelines to merge in/restore
+    Counting,
+
+    /// Deleting everything in the database (restore only)
+    Clearing,
+
+    /// Merging in the entities
+    Entities,
+
+    /// Merging in the timelines (without their subtimelines)
+    Timelines,
+
+    /// Linking the timelines to their subtimelines
+    Subtimelines,
+}
+
+/// The progress of a merge/restore
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RestoreProgress {
+    /// What is being done
+    pub phase: RestorePhase,
+
+    /// The number of items (entities & timelines) processed so far
+    pub processed: usize,
+
+    /// The total number of items to process (timelines are processed twice)
+    pub total: usize,
+}
+
+impl RestoreProgress {
+    /// The fraction of the items processed (between 0 and 1)
+    pub fn fraction(&self) -> f32 {
+        match self.total {
+            0 => 0.0,
+            total => self.processed as f32 / total as f32,
+        }
+    }
+}
+
+/// Used to receive the progress of a merge/restore and to cancel it.  Clones
+/// share the cancellation flag (so one can be kept to cancel the operation).
+#[derive(Debug, Clone, Default)]
+pub struct RestoreMonitor {
+    /// Where progress is sent (if anywhere)
+    tx_progress: Option<UnboundedSender<RestoreProgress>>,
+
+    /// Whether cancellation has been requested
+    cancelled: Arc<AtomicBool>,
+}
+
+impl RestoreMonitor {
+    /// Create a new monitor that sends progress down the given channel
+    pub fn new(tx_progress: UnboundedSender<RestoreProgress>) -> Self {
+        Self {
+            tx_progress: Some(tx_progress),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Request that the merge/restore stops (it is stopped before the next
+    /// item is processed)
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Send progress (if anything is listening)
+    fn report(&self, phase: RestorePhase, processed: usize, total: usize) {
+        if let Some(tx) = &self.tx_progress {
+            let _ = tx.send(RestoreProgress {
+                phase,
+                processed,
+                total,
+            });
+        }
+    }
+
+    /// Return an error if cancellation has been requested
+    fn check_cancelled(&self) -> Result<(), BackupRestoreMergeError> {
+        match self.is_cancelled() {
+            true => Err(BackupRestoreMergeError::Cancelled),
+            false => Ok(()),
+        }
+    }
 }
 
 /// Backup the database to JSON
@@ -58,7 +177,8 @@ pub async fn backup(
     transaction: &mut Transaction<'_, Sqlite>,
     backup_dir_path: PathBuf,
 ) -> Result<(), BackupRestoreMergeError> {
-    backup_or_restore_or_merge(transaction, backup_dir_path, BackupMergeRestore::Backup).await
+    backup_entities(transaction, backup_dir_path.clone()).await?;
+    backup_timelines(transaction, backup_dir_path).await
 }
 
 /// Merge the database to JSON
@@ -66,7 +186,7 @@ pub async fn merge(
     transaction: &mut Transaction<'_, Sqlite>,
     merge_dir_path: PathBuf,
 ) -> Result<(), BackupRestoreMergeError> {
-    backup_or_restore_or_merge(transaction, merge_dir_path, BackupMergeRestore::Merge).await
+    merge_with_progress(transaction, merge_dir_path, &RestoreMonitor::default()).await
 }
 
 /// Restore the database to JSON
@@ -74,33 +194,86 @@ pub async fn restore(
     transaction: &mut Transaction<'_, Sqlite>,
     restore_dir_path: PathBuf,
 ) -> Result<(), BackupRestoreMergeError> {
-    backup_or_restore_or_merge(transaction, restore_dir_path, BackupMergeRestore::Restore).await
+    restore_with_progress(transaction, restore_dir_path, &RestoreMonitor::default()).await
+}
+
+/// Merge the database to JSON, reporting progress & stopping if cancelled
+pub async fn merge_with_progress(
+    transaction: &mut Transaction<'_, Sqlite>,
+    merge_dir_path: PathBuf,
+    monitor: &RestoreMonitor,
+) -> Result<(), BackupRestoreMergeError> {
+    restore_or_merge(transaction, merge_dir_path, false, monitor).await
+}
+
+/// Restore the database to JSON, reporting progress & stopping if cancelled
+pub async fn restore_with_progress(
+    transaction: &mut Transaction<'_, Sqlite>,
+    restore_dir_path: PathBuf,
+    monitor: &RestoreMonitor,
+) -> Result<(), BackupRestoreMergeError> {
+    restore_or_merge(transaction, restore_dir_path, true, monitor).await
 }
 
-/// Backup, merge, or restore a database
-async fn backup_or_restore_or_merge(
+/// Merge in (after clearing the database if restoring) the entities & timelines
+/// in a backup directory.  The files are read one item at a time, so large
+/// backups aren't held in memory.
+async fn restore_or_merge(
     transaction: &mut Transaction<'_, Sqlite>,
     backup_dir_path: PathBuf,
-    backup_merge_restore: BackupMergeRestore,
+    clear_first: bool,
+    monitor: &RestoreMonitor,
 ) -> Result<(), BackupRestoreMergeError> {
-    match backup_merge_restore {
-        BackupMergeRestore::Backup => {
-            backup_entities(transaction, backup_dir_path.clone()).await?;
-            backup_timelines(transaction, backup_dir_path.clone()).await?;
-        }
-        BackupMergeRestore::Merge => {
-            merge_entities(transaction, backup_dir_path.clone()).await?;
-            merge_timelines(transaction, backup_dir_path.clone()).await?;
-        }
-        BackupMergeRestore::Restore => {
-            clear_db(transaction).await?;
-            merge_entities(transaction, backup_dir_path.clone()).await?;
-            merge_timelines(transaction, backup_dir_path.clone()).await?;
-        }
+    let entities_path = backup_dir_path.join("entities.json");
+    let timelines_path = backup_dir_path.join("timelines.json");
+
+    // Count the items so that progress can be reported
+    monitor.report(RestorePhase::Counting, 0, 0);
+    let entity_count = count_json_array_items(&entities_path)?;
+    let timeline_count = count_json_array_items(&timelines_path)?;
+    let mut progress = Progress {
+        monitor,
+        processed: 0,
+        total: entity_count + (2 * timeline_count),
+    };
+
+    if clear_first {
+        progress.report(RestorePhase::Clearing);
+        clear_db(transaction).await?;
+    }
+    if entity_count > 0 {
+        merge_entities(transaction, entities_path, &mut progress).await?;
+    }
+    if timeline_count > 0 {
+        merge_timelines(transaction, timelines_path, &mut progress).await?;
+    } else {
+        warn!("No timelines to restore: {timelines_path:?} is empty");
     }
     Ok(())
 }
 
+/// Tracks the number of items processed during a merge/restore
+struct Progress<'a> {
+    monitor: &'a RestoreMonitor,
+    processed: usize,
+    total: usize,
+}
+
+impl Progress<'_> {
+    /// Report the current progress
+    fn report(&self, phase: RestorePhase) {
+        self.monitor.report(phase, self.processed, self.total);
+    }
+
+    /// Record that an item has been processed (checking for cancellation first)
+    fn item_processed(&mut self, phase: RestorePhase) -> Result<(), BackupRestoreMergeError> {
+        self.monitor.check_cancelled()?;
+        self.processed += 1;
+        self.report(phase);
+        Ok(())
+    }
+}
+
 /// Backup entities in the database to JSON
 async fn backup_entities(
     transaction: &mut Transaction<'_, Sqlite>,
@@ -174,30 +347,55 @@ async fn backup_timelines(
 ///
 /// Every entity to be merged in must have an ID, else an error is returned.  If
 /// the entity ID is already in the database, the existing entity is replaced by
-/// the incoming entity.  If the entity ID is not already in the database, the
-/// incoming entity is inserted.
+/// the incoming entity (keeping the existing entity's aliases, and its name as
+/// an alias if the name has changed).  If the entity ID is not already in the
+/// database, the incoming entity is inserted.
 async fn merge_entities(
     transaction: &mut Transaction<'_, Sqlite>,
-    mut backup_dir: PathBuf,
+    entities_path: PathBuf,
+    progress: &mut Progress<'_>,
 ) -> Result<(), BackupRestoreMergeError> {
-    backup_dir.push("entities.json");
-    let reader = open_file_for_reading(backup_dir.to_str().unwrap().to_string()).await?;
-    let entities: Vec<Entity> = serde_json::from_reader(reader).unwrap();
-    for mut entity in entities {
-        // The entity must have an ID
-        let entity_id = entity
-            .id()
-            .ok_or(CrudError::IdNotSetForEntity(entity.name().to_owned()))?;
-
-        // If the entity ID is already in the database, the update the entity,
-        // otherwise create it
-        match is_entity_id_in_db(transaction, &entity_id).await? {
-            true => entity.update(transaction).await,
-            false => entity.create(transaction).await,
+    progress.report(RestorePhase::Entities);
+    let mut entities = stream_json_array_items::<Entity>(entities_path);
+    while let Some(entity) = entities.recv().await {
+        let entity = entity?;
+        progress.item_processed(RestorePhase::Entities)?;
+        merge_entity(transaction, entity).await?;
+    }
+    progress.monitor.check_cancelled()
+}
+
+/// Merge in an entity (see [`merge_entities()`])
+async fn merge_entity(
+    transaction: &mut Transaction<'_, Sqlite>,
+    mut entity: Entity,
+) -> Result<(), BackupRestoreMergeError> {
+    // The entity must have an ID
+    let entity_id = entity
+        .id()
+        .ok_or(CrudError::IdNotSetForEntity(entity.name().to_owned()))?;
+
+    // If the entity ID is already in the database, the update the entity,
+    // otherwise create it
+    match is_entity_id_in_db(transaction, &entity_id).await? {
+        true => {
+            let existing = Entity::fetch_by_id(transaction, &entity_id).await?;
+            attach_superseded_names(&mut entity, existing);
+            entity.update(transaction).await
         }
-        .map_err(BackupRestoreMergeError::CrudError)?;
+        false => entity.create(transaction).await,
     }
-    Ok(())
+    .map_err(BackupRestoreMergeError::CrudError)
+}
+
+/// Keep the existing entity's aliases, and its name as an alias if the incoming
+/// entity has been renamed
+fn attach_superseded_names(incoming: &mut Entity, existing: Entity) {
+    let existing_name = existing.name().clone();
+    for alias in existing.aliases().iter().flatten() {
+        incoming.add_alias(alias.clone());
+    }
+    incoming.add_alias(existing_name);
 }
 
 /// Merge timelines from backup.
@@ -208,47 +406,74 @@ async fn merge_entities(
 /// the database, the incoming timeline is inserted.
 async fn merge_timelines(
     transaction: &mut Transaction<'_, Sqlite>,
-    mut backup_dir: PathBuf,
+    timelines_path: PathBuf,
+    progress: &mut Progress<'_>,
 ) -> Result<(), BackupRestoreMergeError> {
-    backup_dir.push("timelines.json");
+    // Insert timelines without subtimelines (FOREIGN KEYs would fail otherwise)
+    progress.report(RestorePhase::Timelines);
+    let mut timelines = stream_json_array_items::<TimelineEdit>(timelines_path.clone());
+    while let Some(timeline) = timelines.recv().await {
+        let timeline = timeline?;
+        progress.item_processed(RestorePhase::Timelines)?;
+        merge_timeline_without_subtimelines(transaction, timeline).await?;
+    }
 
-    // TODO (do for restore_entities too) (keep?)
-    let metadata = std::fs::metadata(backup_dir.clone()).map_err(BackupRestoreMergeError::StdIo)?;
-    if metadata.len() == 0 {
-        warn!("No timelines to restore: {backup_dir:?} is empty");
-        return Ok(());
+    // Update timelines to save their subtimelines (read from the file again)
+    progress.report(RestorePhase::Subtimelines);
+    let mut timelines = stream_json_array_items::<TimelineEdit>(timelines_path);
+    while let Some(timeline) = timelines.recv().await {
+        let mut timeline = timeline?;
+        progress.item_processed(RestorePhase::Subtimelines)?;
+        timeline
+            .update(transaction)
+            .await
+            .map_err(BackupRestoreMergeError::CrudError)?;
     }
 
-    let reader = open_file_for_reading(backup_dir.to_str().unwrap().to_string()).await?;
-    let backup_timelines: Vec<TimelineEdit> =
-        serde_json::from_reader(reader).map_err(BackupRestoreMergeError::SerdeJson)?;
+    progress.monitor.check_cancelled()
+}
 
-    // Insert timelines without subtimelines (FOREIGN KEYs would fail otherwise)
-    for mut timeline in backup_timelines.clone() {
-        timeline.clear_subtimelines();
-
-        // The timeline must have an ID
-        let timeline_id = timeline
-            .id()
-            .ok_or(CrudError::IdNotSetForEntity(timeline.name().to_owned()))?;
-
-        // If the timeline ID is already in the database, the update the timeline,
-        // otherwise create it (without subtimelines)
-        match is_timeline_id_in_db(transaction, &timeline_id).await? {
-            true => timeline.update(transaction).await,
-            false => timeline.create(transaction).await,
-        }
-        .map_err(BackupRestoreMergeError::CrudError)?;
+/// Merge in a timeline without its subtimelines (which may not be in the
+/// database yet)
+async fn merge_timeline_without_subtimelines(
+    transaction: &mut Transaction<'_, Sqlite>,
+    mut timeline: TimelineEdit,
+) -> Result<(), BackupRestoreMergeError> {
+    timeline.clear_subtimelines();
+
+    // The timeline must have an ID
+    let timeline_id = timeline
+        .id()
+        .ok_or(CrudError::IdNotSetForEntity(timeline.name().to_owned()))?;
+
+    // If the timeline ID is already in the database, the update the timeline,
+    // otherwise create it (without subtimelines)
+    match is_timeline_id_in_db(transaction, &timeline_id).await? {
+        true => timeline.update(transaction).await,
+        false => timeline.create(transaction).await,
     }
+    .map_err(BackupRestoreMergeError::CrudError)
+}
 
-    // Update timelines to save their subtimelines
-    for mut timeline in backup_timelines {
+/// Merge in entities & timelines that are already in memory (e.g. those
+/// fetched from a web API when syncing), in the same way as [`merge()`]
+pub async fn merge_entities_and_timelines(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entities: Vec<Entity>,
+    timelines: Vec<TimelineEdit>,
+) -> Result<(), BackupRestoreMergeError> {
+    for entity in entities {
+        merge_entity(transaction, entity).await?;
+    }
+    for timeline in &timelines {
+        merge_timeline_without_subtimelines(transaction, timeline.clone()).await?;
+    }
+    for mut timeline in timelines {
         timeline
             .update(transaction)
             .await
             .map_err(BackupRestoreMergeError::CrudError)?;
     }
-
     Ok(())
 }
 
@@ -259,11 +484,19 @@ async fn clear_db(
     let mut queries = Vec::new();
 
     // Create the queries (order important because of FOREIGN KEY constraints)
+    queries.push(sqlx::query!("DELETE FROM favourites;"));
+    queries.push(sqlx::query!("DELETE FROM slug_redirects;"));
+    queries.push(sqlx::query!("DELETE FROM timeline_bookmarks;"));
+    queries.push(sqlx::query!("DELETE FROM timeline_story_steps;"));
     queries.push(sqlx::query!("DELETE FROM timeline_tags;"));
     queries.push(sqlx::query!("DELETE FROM timeline_entities;"));
     queries.push(sqlx::query!("DELETE FROM subtimelines;"));
     queries.push(sqlx::query!("DELETE FROM timelines;"));
     queries.push(sqlx::query!("DELETE FROM entity_tags;"));
+    queries.push(sqlx::query!("DELETE FROM entity_aliases;"));
+    queries.push(sqlx::query!("DELETE FROM entity_sources;"));
+    queries.push(sqlx::query!("DELETE FROM entity_typed_tags;"));
+    queries.push(sqlx::query!("DELETE FROM sources;"));
     queries.push(sqlx::query!("DELETE FROM entities;"));
 
     // Execute all the DELETE queries (not committed)
@@ -277,13 +510,83 @@ async fn clear_db(
     Ok(())
 }
 
-/// Open the file in read-only mode and return the buffer
-async fn open_file_for_reading(
-    path_string: String,
-) -> Result<BufReader<File>, BackupRestoreMergeError> {
-    let path = Path::new(&path_string);
-    let file = File::open(path).map_err(BackupRestoreMergeError::StdIo)?;
-    Ok(BufReader::new(file))
+/// Count the items in a file containing a JSON array without deserialising
+/// them (an empty file counts as an empty array)
+fn count_json_array_items(path: &Path) -> Result<usize, BackupRestoreMergeError> {
+    if std::fs::metadata(path)?.len() == 0 {
+        return Ok(0);
+    }
+    let reader = BufReader::new(File::open(path)?);
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let count = (&mut deserializer).deserialize_seq(CountVisitor)?;
+    deserializer.end()?;
+    Ok(count)
+}
+
+/// Read the items in a file containing a JSON array one at a time (on a
+/// blocking thread), sending each down the returned channel.  Reading stops if
+/// the receiver is dropped.
+fn stream_json_array_items<T>(path: PathBuf) -> Receiver<Result<T, BackupRestoreMergeError>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let (tx, rx) = tokio::sync::mpsc::channel(STREAM_BUFFER_SIZE);
+    tokio::task::spawn_blocking(move || {
+        let result: Result<(), BackupRestoreMergeError> = (|| {
+            let reader = BufReader::new(File::open(&path)?);
+            let mut deserializer = serde_json::Deserializer::from_reader(reader);
+            (&mut deserializer).deserialize_seq(SendEachVisitor { tx: tx.clone() })?;
+            deserializer.end()?;
+            Ok(())
+        })();
+        if let Err(error) = result {
+            let _ = tx.blocking_send(Err(error));
+        }
+    });
+    rx
+}
+
+/// Counts the items in a JSON array (ignoring their content)
+struct CountVisitor;
+
+impl<'de> Visitor<'de> for CountVisitor {
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<usize, A::Error> {
+        let mut count = 0;
+        while seq.next_element::<IgnoredAny>()?.is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// Sends each item in a JSON array down a channel as it is deserialised
+struct SendEachVisitor<T> {
+    tx: Sender<Result<T, BackupRestoreMergeError>>,
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SendEachVisitor<T> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(item) = seq.next_element::<T>()? {
+            // The receiver has gone (e.g. the merge/restore failed or was
+            // cancelled)
+            if self.tx.blocking_send(Ok(item)).is_err() {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Write a string to file at some path
@@ -301,7 +604,7 @@ async fn create_and_write_to_file(
 mod test {
     use super::*;
     use crate::{DatabaseRowCount, test::path_to_test_data};
-    use open_timeline_core::OpenTimelineId;
+    use open_timeline_core::{Aliases, Name, OpenTimelineId};
     use sqlx::{Pool, Sqlite};
     use std::{fs, path::PathBuf};
 
@@ -354,4 +657,62 @@ mod test {
         assert_eq!(original_entities, new_entities);
         assert_eq!(original_timelines, new_timelines);
     }
+
+    #[sqlx::test]
+    async fn restore_reports_progress(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let monitor = RestoreMonitor::new(tx);
+        restore_with_progress(&mut transaction, path_to_test_data().join("seed"), &monitor)
+            .await
+            .unwrap();
+
+        // 3 entities, and 2 timelines (which are processed twice)
+        let mut updates = Vec::new();
+        while let Ok(progress) = rx.try_recv() {
+            updates.push(progress);
+        }
+        assert_eq!(updates.first().unwrap().phase, RestorePhase::Counting);
+        let last = updates.last().unwrap();
+        assert_eq!(last.phase, RestorePhase::Subtimelines);
+        assert_eq!((last.processed, last.total), (7, 7));
+        assert_eq!(last.fraction(), 1.0);
+        assert!(updates.iter().any(|p| p.phase == RestorePhase::Clearing));
+        assert!(
+            updates
+                .windows(2)
+                .all(|pair| pair[0].processed <= pair[1].processed)
+        );
+    }
+
+    #[sqlx::test]
+    async fn restore_can_be_cancelled(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let monitor = RestoreMonitor::default();
+        monitor.cancel();
+        let result =
+            restore_with_progress(&mut transaction, path_to_test_data().join("seed"), &monitor)
+                .await;
+        assert!(matches!(result, Err(BackupRestoreMergeError::Cancelled)));
+    }
+
+    // Merging in a renamed entity keeps the old name (and old aliases) as aliases
+    #[test]
+    fn merge_keeps_superseded_names() {
+        let mut existing = crate::test::valid_entity();
+        existing.add_alias(Name::from("Old Alias").unwrap());
+
+        let mut incoming = existing.clone();
+        incoming.set_name(Name::from("New Name").unwrap());
+        incoming.set_aliases(Aliases::new());
+
+        attach_superseded_names(&mut incoming, existing.clone());
+        assert_eq!(
+            incoming.aliases(),
+            &Some(Aliases::from([
+                existing.name().clone(),
+                Name::from("Old Alias").unwrap(),
+            ]))
+        );
+    }
 }
@@ -0,0 +1,309 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! An in-memory cache of database statistics (row counts, entity counts, tag
+//! counts, etc.) so that they aren't recomputed every time they're displayed
+//!
+
+use crate::{
+    CrudError, DatabaseRowCount, EntityCounts, EntityInsights, TagCounts, TimelineCounts,
+    fetch_all_entity_tag_counts, fetch_all_timeline_tag_counts,
+};
+use async_trait::async_trait;
+use sqlx::{Sqlite, Transaction};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// How long a cached statistic is used for (if not invalidated) by default
+pub const DEFAULT_MAX_STAT_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// A statistic that can be cached by [`CachedStats`]
+#[allow(async_fn_in_trait)]
+#[async_trait]
+pub trait Stat: Send + Sync + 'static {
+    /// The statistic's type
+    type Value: Clone + Send + Sync + 'static;
+
+    /// Compute the statistic (bypassing the cache)
+    async fn compute(transaction: &mut Transaction<'_, Sqlite>) -> Result<Self::Value, CrudError>;
+}
+
+/// The row count of each table ([`DatabaseRowCount`])
+#[derive(Debug)]
+pub struct RowCountsStat;
+
+/// How the entities are distributed ([`EntityInsights`])
+#[derive(Debug)]
+pub struct EntityInsightsStat;
+
+/// The tag count etc. of each entity ([`EntityCounts`])
+#[derive(Debug)]
+pub struct EntityCountsStat;
+
+/// The number of entities with each tag ([`TagCounts`])
+#[derive(Debug)]
+pub struct EntityTagCountsStat;
+
+/// The number of timelines with each tag ([`TagCounts`])
+#[derive(Debug)]
+pub struct TimelineTagCountsStat;
+
+/// The entity count etc. of each timeline ([`TimelineCounts`])
+#[derive(Debug)]
+pub struct TimelineCountsStat;
+
+#[async_trait]
+impl Stat for RowCountsStat {
+    type Value = DatabaseRowCount;
+
+    async fn compute(transaction: &mut Transaction<'_, Sqlite>) -> Result<Self::Value, CrudError> {
+        DatabaseRowCount::all(transaction).await
+    }
+}
+
+#[async_trait]
+impl Stat for EntityInsightsStat {
+    type Value = EntityInsights;
+
+    async fn compute(transaction: &mut Transaction<'_, Sqlite>) -> Result<Self::Value, CrudError> {
+        EntityInsights::fetch(transaction).await
+    }
+}
+
+#[async_trait]
+impl Stat for EntityCountsStat {
+    type Value = EntityCounts;
+
+    async fn compute(transaction: &mut Transaction<'_, Sqlite>) -> Result<Self::Value, CrudError> {
+        EntityCounts::fetch_all(transaction).await
+    }
+}
+
+#[async_trait]
+impl Stat for EntityTagCountsStat {
+    type Value = TagCounts;
+
+    async fn compute(transaction: &mut Transaction<'_, Sqlite>) -> Result<Self::Value, CrudError> {
+        fetch_all_entity_tag_counts(transaction).await
+    }
+}
+
+#[async_trait]
+impl Stat for TimelineTagCountsStat {
+    type Value = TagCounts;
+
+    async fn compute(transaction: &mut Transaction<'_, Sqlite>) -> Result<Self::Value, CrudError> {
+        fetch_all_timeline_tag_counts(transaction).await
+    }
+}
+
+#[async_trait]
+impl Stat for TimelineCountsStat {
+    type Value = TimelineCounts;
+
+    async fn compute(transaction: &mut Transaction<'_, Sqlite>) -> Result<Self::Value, CrudError> {
+        TimelineCounts::fetch_all(transaction).await
+    }
+}
+
+/// A statistic along with how fresh it is
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    /// The statistic
+    value: T,
+
+    /// When the statistic was computed
+    computed_at: SystemTime,
+
+    /// Whether the statistic came from the cache (rather than being computed
+    /// for this request)
+    from_cache: bool,
+}
+
+impl<T> Cached<T> {
+    /// Get the statistic
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Take the statistic
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// When the statistic was computed
+    pub fn computed_at(&self) -> SystemTime {
+        self.computed_at
+    }
+
+    /// How long ago the statistic was computed
+    pub fn age(&self) -> Duration {
+        self.computed_at.elapsed().unwrap_or_default()
+    }
+
+    /// Whether the statistic came from the cache
+    pub fn from_cache(&self) -> bool {
+        self.from_cache
+    }
+}
+
+/// A cache entry (the statistic along with the cache generation at the time it
+/// was computed)
+type Entry<T> = Option<(Cached<T>, u64)>;
+
+/// One cache entry per type of statistic
+type Entries = HashMap<TypeId, Arc<tokio::sync::Mutex<Box<dyn Any + Send + Sync>>>>;
+
+/// A cache of database statistics.  Cheap to clone (clones share the cache).
+///
+/// Statistics are recomputed when requested if the cache has been invalidated
+/// (which should be done after any CRUD operation) or if they are older than
+/// the maximum age.
+#[derive(Debug, Clone)]
+pub struct CachedStats {
+    /// The cached statistics
+    entries: Arc<Mutex<Entries>>,
+
+    /// Incremented each time the cache is invalidated
+    generation: Arc<AtomicU64>,
+
+    /// How long a statistic is used for (if the cache isn't invalidated)
+    max_age: Duration,
+}
+
+impl Default for CachedStats {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_STAT_AGE)
+    }
+}
+
+impl CachedStats {
+    /// Create an empty cache whose statistics are used for up to `max_age`
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            generation: Arc::new(AtomicU64::new(0)),
+            max_age,
+        }
+    }
+
+    /// Mark all cached statistics as out of date (they are recomputed when next
+    /// requested)
+    pub fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Get a statistic (from the cache if it's up to date, otherwise computing
+    /// and caching it).  Concurrent requests for the same statistic only
+    /// compute it once.
+    pub async fn get<S: Stat>(
+        &self,
+        transaction: &mut Transaction<'_, Sqlite>,
+    ) -> Result<Cached<S::Value>, CrudError> {
+        let entry = Arc::clone(
+            self.entries
+                .lock()
+                .unwrap()
+                .entry(TypeId::of::<S>())
+                .or_insert_with(|| {
+                    Arc::new(tokio::sync::Mutex::new(Box::new(Entry::<S::Value>::None)))
+                }),
+        );
+        let mut entry = entry.lock().await;
+        let entry = entry.downcast_mut::<Entry<S::Value>>().unwrap();
+
+        // Use the cached statistic if it's up to date
+        let generation = self.generation.load(Ordering::SeqCst);
+        if let Some((cached, cached_generation)) = entry.as_ref()
+            && *cached_generation == generation
+            && cached.age() < self.max_age
+        {
+            return Ok(Cached {
+                from_cache: true,
+                ..cached.clone()
+            });
+        }
+
+        // Otherwise compute & cache it
+        let cached = Cached {
+            value: S::compute(transaction).await?,
+            computed_at: SystemTime::now(),
+            from_cache: false,
+        };
+        *entry = Some((cached.clone(), generation));
+        Ok(cached)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, DeleteById};
+    use open_timeline_core::{Entity, HasIdAndName};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn cached_until_invalidated(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut ids = Vec::new();
+        for mut entity in valid_entities() {
+            entity.create(&mut transaction).await.unwrap();
+            ids.push(entity.id().unwrap());
+        }
+        let cached_stats = CachedStats::default();
+
+        // Computed once, then cached
+        let row_counts = cached_stats
+            .get::<RowCountsStat>(&mut transaction)
+            .await
+            .unwrap();
+        assert!(!row_counts.from_cache());
+        let entity_counts = cached_stats
+            .get::<EntityCountsStat>(&mut transaction)
+            .await
+            .unwrap();
+        assert!(!entity_counts.from_cache());
+        let cached_row_counts = cached_stats
+            .get::<RowCountsStat>(&mut transaction)
+            .await
+            .unwrap();
+        assert!(cached_row_counts.from_cache());
+        assert_eq!(cached_row_counts.value(), row_counts.value());
+
+        // Not recomputed after a change until invalidated
+        Entity::delete_by_id(&mut transaction, &ids[0])
+            .await
+            .unwrap();
+        let row_counts = cached_stats
+            .get::<RowCountsStat>(&mut transaction)
+            .await
+            .unwrap();
+        assert!(row_counts.from_cache());
+        cached_stats.invalidate();
+        let row_counts = cached_stats
+            .get::<RowCountsStat>(&mut transaction)
+            .await
+            .unwrap();
+        assert!(!row_counts.from_cache());
+        assert_eq!(
+            row_counts.value().entities,
+            cached_row_counts.value().entities - 1
+        );
+
+        // Statistics older than the maximum age are recomputed
+        let cached_stats = CachedStats::new(Duration::ZERO);
+        cached_stats
+            .get::<RowCountsStat>(&mut transaction)
+            .await
+            .unwrap();
+        let row_counts = cached_stats
+            .get::<RowCountsStat>(&mut transaction)
+            .await
+            .unwrap();
+        assert!(!row_counts.from_cache());
+    }
+}
@@ -5,13 +5,37 @@
 //!
 
 mod automatic_tags;
+mod bool_tag_expr_sql;
+mod bulk;
 mod common;
+mod database_settings;
 mod entity;
+mod favourites;
+mod modified_since;
+mod pages;
+mod recent_changes;
+mod references;
+mod saved_searches;
+mod search_history;
+mod slugs;
+mod tag_hierarchy;
 mod tags;
 mod timeline;
 
 pub use automatic_tags::*;
+pub(crate) use bool_tag_expr_sql::*;
+pub use bulk::*;
 pub use common::*;
+pub use database_settings::*;
 pub use entity::*;
+pub use favourites::*;
+pub use modified_since::*;
+pub use pages::*;
+pub use recent_changes::*;
+pub use references::*;
+pub use saved_searches::*;
+pub use search_history::*;
+pub use slugs::*;
+pub use tag_hierarchy::*;
 pub use tags::*;
 pub use timeline::*;
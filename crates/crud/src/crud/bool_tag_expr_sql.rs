@@ -0,0 +1,413 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Compile a [`BoolTagExpr`] into an SQL condition, so that the database
+//! evaluates it (using the tag tables' indexes) rather than everything being
+//! fetched and filtered in Rust.
+//!
+//! Each tag becomes an `EXISTS` subquery over the entity's (or timeline's)
+//! tags, and these are combined with `AND`, `OR`, & `NOT`.  Tag names & values
+//! are bound as parameters, never formatted into the SQL.
+//!
+//! The comparisons of a [`TagQuery`] (e.g. `population > 1000000`) become
+//! `EXISTS` subqueries over the entity's typed tags (timelines don't have any)
+//! and ordinary tags with the name.
+//!
+//! The result is the same as [`Entity::matches_tag_query()`]: a tag without a
+//! name matches a tag with that value whatever its name, and nothing without
+//! tags matches (not even `!a`).  A tag also matches its descendants, found
+//! through the `tag_ancestors` view (see [`TagHierarchy`]).
+//!
+//! [`Entity::matches_tag_query()`]: open_timeline_core::Entity::matches_tag_query
+//! [`TagHierarchy`]: open_timeline_core::TagHierarchy
+//!
+
+use bool_tag_expr::{BoolTagExpr, Node, Tag};
+use open_timeline_core::{TagQuery, TagRange, TagValueType};
+
+/// Whether a boolean tag expression selects entities or timelines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Tagged {
+    Entities,
+    Timelines,
+}
+
+impl Tagged {
+    /// The table holding the tags, & its column of entity/timeline IDs
+    const fn tags_table(self) -> (&'static str, &'static str) {
+        match self {
+            Tagged::Entities => ("entity_tags", "entity_id"),
+            Tagged::Timelines => ("timeline_tags", "timeline_id"),
+        }
+    }
+}
+
+/// A [`BoolTagExpr`] compiled into an SQL condition, with the values to bind
+/// to its parameters (in order)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BoolTagExprSql {
+    condition: String,
+    binds: Vec<String>,
+}
+
+impl BoolTagExprSql {
+    /// Compile the expression into a condition on an ID column of the outer
+    /// query (e.g. `entities.id`)
+    pub(crate) fn compile(
+        bool_expr: &BoolTagExpr,
+        tagged: Tagged,
+        id_column: &'static str,
+    ) -> Self {
+        Self::compile_query(&bool_expr.clone().into(), tagged, id_column)
+    }
+
+    /// Compile the query into a condition on an ID column of the outer query
+    /// (e.g. `entities.id`)
+    pub(crate) fn compile_query(
+        tag_query: &TagQuery,
+        tagged: Tagged,
+        id_column: &'static str,
+    ) -> Self {
+        let (table, tagged_id_column) = tagged.tags_table();
+        let mut binds = Vec::new();
+
+        // TODO: add a .as_node()/.node() method to bool-tag-expr crate so no cloning
+        let expr = compile_node(tag_query.bool_expr().clone().into_node(), &mut |tag| {
+            if let Some(range) = tag_query.range(&tag) {
+                return compile_range(range, tagged, id_column, &mut binds);
+            }
+            let mut sql = format!(
+                "EXISTS (SELECT 1 FROM {table} AS tags WHERE tags.{tagged_id_column} = {id_column}"
+            );
+            if let Some(name) = tag.name {
+                sql.push_str(" AND tags.name = ?");
+                binds.push(name.to_string());
+            }
+            sql.push_str(
+                " AND (tags.value = ? OR EXISTS (SELECT 1 FROM tag_ancestors AS ancestors WHERE ancestors.name IS tags.name AND ancestors.value = tags.value AND ancestors.ancestor_value = ?)))",
+            );
+            let value = tag.value.to_string();
+            binds.extend([value.clone(), value]);
+            sql
+        });
+
+        let mut has_tags = format!(
+            "EXISTS (SELECT 1 FROM {table} AS tags WHERE tags.{tagged_id_column} = {id_column})"
+        );
+        if tagged == Tagged::Entities {
+            has_tags = format!(
+                "({has_tags} OR EXISTS (SELECT 1 FROM entity_typed_tags AS typed WHERE typed.entity_id = {id_column}))"
+            );
+        }
+        let condition = format!("({has_tags} AND {expr})");
+        Self { condition, binds }
+    }
+
+    /// Get the SQL condition
+    pub(crate) fn condition(&self) -> &str {
+        &self.condition
+    }
+
+    /// Get the values to bind to the condition's parameters (in order)
+    pub(crate) fn binds(&self) -> &[String] {
+        &self.binds
+    }
+}
+
+/// Compile a comparison of the values of the tags with a name (typed tags are
+/// compared by their sort keys, & ordinary tags alphabetically)
+fn compile_range(
+    range: &TagRange,
+    tagged: Tagged,
+    id_column: &'static str,
+    binds: &mut Vec<String>,
+) -> String {
+    let (table, tagged_id_column) = tagged.tags_table();
+    let operator = range.operator().symbol();
+    let mut conditions = Vec::new();
+
+    if tagged == Tagged::Entities && !range.typed_values().is_empty() {
+        binds.push(range.name().to_string());
+        let values: Vec<String> = range
+            .typed_values()
+            .iter()
+            .map(|value| {
+                let value_type = match value.value_type() {
+                    TagValueType::String => "string",
+                    TagValueType::Number => "number",
+                    TagValueType::Date => "date",
+                };
+                binds.extend([value_type.to_string(), value.sort_key().to_string()]);
+                format!("(typed.value_type = ? AND typed.sort_key {operator} CAST(? AS REAL))")
+            })
+            .collect();
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM entity_typed_tags AS typed WHERE typed.entity_id = {id_column} AND typed.name = ? AND ({}))",
+            values.join(" OR ")
+        ));
+    }
+
+    if let Some(value) = range.string_value() {
+        binds.extend([range.name().to_string(), value.to_string()]);
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM {table} AS tags WHERE tags.{tagged_id_column} = {id_column} AND tags.name = ? AND tags.value {operator} ?)"
+        ));
+    }
+
+    match conditions.is_empty() {
+        true => String::from("FALSE"),
+        false => format!("({})", conditions.join(" OR ")),
+    }
+}
+
+/// Recursively compile a [`Node`], using `compile_tag` for each tag
+fn compile_node(node: Node, compile_tag: &mut impl FnMut(Tag) -> String) -> String {
+    match node {
+        Node::And(l, r) => {
+            let l = compile_node(*l, compile_tag);
+            let r = compile_node(*r, compile_tag);
+            format!("({l} AND {r})")
+        }
+        Node::Or(l, r) => {
+            let l = compile_node(*l, compile_tag);
+            let r = compile_node(*r, compile_tag);
+            format!("({l} OR {r})")
+        }
+        Node::Not(e) => format!("(NOT {})", compile_node(*e, compile_tag)),
+        Node::Tag(tag) => compile_tag(tag),
+        Node::Bool(true) => String::from("TRUE"),
+        Node::Bool(false) => String::from("FALSE"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Create, FetchAll, FetchByBoolTagExpr, Limit, fetch_entities_by_ids,
+        fetch_entity_ids_by_bool_expr, fetch_tag_hierarchy, set_tag_parent,
+    };
+    use bool_tag_expr::{TagName, TagValue, Tags};
+    use open_timeline_core::{
+        Date, Entity, HasIdAndName, IsReducedCollection, IsReducedType, Name, OpenTimelineId,
+        ReducedEntities, TagHierarchy, TagValueType, TypedTag, TypedTags,
+    };
+    use sqlx::{Pool, Sqlite};
+    use std::collections::BTreeSet;
+
+    /// Parse `name=value` (or `value`) tags
+    fn tags(tags: &[&str]) -> Option<Tags> {
+        let tags: Tags = tags
+            .iter()
+            .map(|tag| match tag.split_once('=') {
+                Some((name, value)) => Tag::from(
+                    Some(TagName::from(&name).unwrap()),
+                    TagValue::from(&value).unwrap(),
+                ),
+                None => Tag::from(None, TagValue::from(tag).unwrap()),
+            })
+            .collect();
+        (!tags.is_empty()).then_some(tags)
+    }
+
+    #[test]
+    fn compile() {
+        let bool_expr = BoolTagExpr::from("a & !colour=red").unwrap();
+        let sql = BoolTagExprSql::compile(&bool_expr, Tagged::Entities, "entities.id");
+        assert_eq!(sql.condition().matches("EXISTS").count(), 6);
+        assert_eq!(sql.condition().matches("tag_ancestors").count(), 2);
+        assert!(sql.condition().contains("NOT"));
+        assert_eq!(sql.binds(), ["a", "a", "colour", "red", "red"]);
+
+        let tag_query = TagQuery::from("population > 1000").unwrap();
+        let sql = BoolTagExprSql::compile_query(&tag_query, Tagged::Timelines, "timelines.id");
+        assert!(!sql.condition().contains("typed"));
+        assert_eq!(sql.condition().matches("EXISTS").count(), 1);
+        assert_eq!(sql.binds(), [] as [&str; 0]);
+    }
+
+    /// The database gives the same results as the in-memory evaluator
+    #[sqlx::test]
+    async fn equivalent_to_in_memory_evaluation(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let entity_tags: [&[&str]; 10] = [
+            &[],
+            &["a"],
+            &["b"],
+            &["a", "b"],
+            &["colour=red", "c"],
+            &["colour=blue", "a"],
+            &["red", "b", "c"],
+            &["france", "colour=crimson"],
+            &["europe", "place=japan"],
+            &["world-war-two", "b"],
+        ];
+        for (child, parent) in [
+            ("france", "europe"),
+            ("europe", "world"),
+            ("colour=crimson", "red"),
+            ("place=japan", "asia"),
+            ("place=asia", "world"),
+        ] {
+            let child = tags(&[child]).unwrap().pop_first().unwrap();
+            set_tag_parent(
+                &mut transaction,
+                &child,
+                Some(&TagValue::from(&parent).unwrap()),
+            )
+            .await
+            .unwrap();
+        }
+        let tag_hierarchy = fetch_tag_hierarchy(&mut transaction).await.unwrap();
+        for (i, entity_tags) in entity_tags.into_iter().enumerate() {
+            let mut entity = Entity::from(
+                None,
+                Name::from(format!("Entity {i}")).unwrap(),
+                Date::from(None, None, 1900 + i as i64).unwrap(),
+                None,
+                tags(entity_tags),
+            )
+            .unwrap();
+            entity.create(&mut transaction).await.unwrap();
+        }
+        let all_ids: Vec<OpenTimelineId> = ReducedEntities::fetch_all(&mut transaction)
+            .await
+            .unwrap()
+            .collection()
+            .iter()
+            .map(|entity| entity.id())
+            .collect();
+        let entities = fetch_entities_by_ids(&mut transaction, &all_ids)
+            .await
+            .unwrap();
+
+        for bool_expr in [
+            "a",
+            "!a",
+            "a & b",
+            "a | b",
+            "a & !b",
+            "!(a | b)",
+            "red",
+            "=red",
+            "colour=red",
+            "colour=red | colour=blue",
+            "(a | c) & !colour=blue",
+            "!a & !b & !c",
+            "missing",
+            "!missing",
+            "world",
+            "europe",
+            "france",
+            "!europe & b",
+            "asia",
+            "place=world",
+            "place=europe",
+            "crimson",
+            "=crimson",
+            "world-war-two | colour=red",
+        ] {
+            let bool_expr = BoolTagExpr::from(bool_expr).unwrap();
+            let expected: BTreeSet<OpenTimelineId> = entities
+                .iter()
+                .filter(|entity| entity.matches_bool_tag_expr(&bool_expr, &tag_hierarchy))
+                .map(|entity| entity.id().unwrap())
+                .collect();
+            let ids: BTreeSet<OpenTimelineId> =
+                fetch_entity_ids_by_bool_expr(&mut transaction, &bool_expr)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .collect();
+            assert_eq!(ids, expected, "{bool_expr:?}");
+            let reduced: BTreeSet<OpenTimelineId> =
+                ReducedEntities::fetch_by_bool_tag_expr(&mut transaction, Limit(100), bool_expr)
+                    .await
+                    .unwrap()
+                    .collection()
+                    .iter()
+                    .map(|entity| entity.id())
+                    .collect();
+            assert_eq!(reduced, expected);
+        }
+    }
+
+    /// A typed tag's name, type, & value
+    type TypedTagInput = (&'static str, TagValueType, &'static str);
+
+    /// Comparisons of typed & ordinary tags give the same results as the
+    /// in-memory evaluator
+    #[sqlx::test]
+    async fn ranges_equivalent_to_in_memory_evaluation(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let entity_tags: [(&[&str], &[TypedTagInput]); 6] = [
+            (&[], &[]),
+            (&["a"], &[("population", TagValueType::Number, "500")]),
+            (
+                &["a"],
+                &[
+                    ("population", TagValueType::Number, "2,000,000"),
+                    ("founded", TagValueType::Date, "12 March 1945"),
+                ],
+            ),
+            (&[], &[("founded", TagValueType::Date, "1800")]),
+            (&["colour=red"], &[]),
+            (&["colour=blue", "b"], &[]),
+        ];
+        for (i, (entity_tags, typed)) in entity_tags.into_iter().enumerate() {
+            let mut entity = Entity::from(
+                None,
+                Name::from(format!("Entity {i}")).unwrap(),
+                Date::from(None, None, 1900 + i as i64).unwrap(),
+                None,
+                tags(entity_tags),
+            )
+            .unwrap();
+            let typed: TypedTags = typed
+                .iter()
+                .map(|(name, value_type, value)| TypedTag::parse(name, *value_type, value).unwrap())
+                .collect();
+            entity.set_typed_tags(typed);
+            entity.create(&mut transaction).await.unwrap();
+        }
+        let all_ids: Vec<OpenTimelineId> = ReducedEntities::fetch_all(&mut transaction)
+            .await
+            .unwrap()
+            .collection()
+            .iter()
+            .map(|entity| entity.id())
+            .collect();
+        let entities = fetch_entities_by_ids(&mut transaction, &all_ids)
+            .await
+            .unwrap();
+
+        for tag_query in [
+            "population > 1000000",
+            "population <= 500",
+            "population >= 500 & a",
+            "a & !(population > 1000)",
+            "founded < 1900",
+            "founded >= March 1945",
+            "founded > 1000 | b",
+            "colour > m",
+            "colour <= red & !colour < red",
+            "missing > 1",
+        ] {
+            let tag_query = TagQuery::from(tag_query).unwrap();
+            let expected: BTreeSet<OpenTimelineId> = entities
+                .iter()
+                .filter(|entity| entity.matches_tag_query(&tag_query, &TagHierarchy::new()))
+                .map(|entity| entity.id().unwrap())
+                .collect();
+            let reduced: BTreeSet<OpenTimelineId> =
+                ReducedEntities::fetch_by_tag_query(&mut transaction, Limit(100), &tag_query)
+                    .await
+                    .unwrap()
+                    .collection()
+                    .iter()
+                    .map(|entity| entity.id())
+                    .collect();
+            assert_eq!(reduced, expected, "{tag_query}");
+        }
+    }
+}
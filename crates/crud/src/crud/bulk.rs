@@ -0,0 +1,383 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Bulk operations on many entities & timelines at once (e.g. on a selection
+//! of search results)
+//!
+
+use crate::{
+    CrudError, DeleteById, FetchById, Update, fetch_entity_ids_by_bool_expr,
+    fetch_timeline_entity_ids, insert_timeline_entity,
+};
+use bool_tag_expr::{BoolTagExpr, Tag};
+use open_timeline_core::{
+    Date, DateShift, Entity, EntityError, HasIdAndName, Name, OpenTimelineId, TimelineEdit,
+};
+use sqlx::{Sqlite, Transaction};
+use std::collections::BTreeSet;
+
+/// Which entities a bulk operation is run on (when they aren't picked by hand)
+#[derive(Debug, Clone)]
+pub enum BulkEntitySelection {
+    /// The entities that match the boolean tag expression
+    BoolExpr(BoolTagExpr),
+
+    /// The entities in the timeline (or any of its subtimelines)
+    Timeline(OpenTimelineId),
+}
+
+impl BulkEntitySelection {
+    /// Fetch from the database the IDs of the selected entities
+    pub async fn fetch_entity_ids(
+        &self,
+        transaction: &mut Transaction<'_, Sqlite>,
+    ) -> Result<BTreeSet<OpenTimelineId>, CrudError> {
+        let entity_ids = match self {
+            Self::BoolExpr(bool_expr) => {
+                fetch_entity_ids_by_bool_expr(transaction, bool_expr).await?
+            }
+            Self::Timeline(timeline_id) => {
+                fetch_timeline_entity_ids(transaction, timeline_id).await?
+            }
+        };
+        Ok(entity_ids.into_iter().collect())
+    }
+}
+
+/// An entity's dates before and after a bulk date shift
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShiftedEntityDates {
+    /// The entity's ID
+    pub id: OpenTimelineId,
+
+    /// The entity's name
+    pub name: Name,
+
+    /// The start date before the shift
+    pub start: Date,
+
+    /// The end date before the shift
+    pub end: Option<Date>,
+
+    /// The start date after the shift
+    pub new_start: Date,
+
+    /// The end date after the shift
+    pub new_end: Option<Date>,
+}
+
+impl ShiftedEntityDates {
+    /// Work out the entity's dates after the shift
+    fn from(entity: &Entity, shift: &DateShift) -> Result<Self, CrudError> {
+        let name = entity.name().clone();
+        let Some(id) = entity.id() else {
+            return Err(CrudError::IdNotSetForEntity(name));
+        };
+        let shift_err = |_| CrudError::DateShift(entity.name().clone());
+        let new_start = entity.start().shifted(shift).map_err(shift_err)?;
+        let new_end = entity
+            .end()
+            .map(|end| end.shifted(shift))
+            .transpose()
+            .map_err(shift_err)?;
+        Ok(Self {
+            id,
+            name,
+            start: entity.start(),
+            end: entity.end(),
+            new_start,
+            new_end,
+        })
+    }
+
+    /// Set the entity's dates to the shifted ones
+    fn apply(&self, entity: &mut Entity) -> Result<(), EntityError> {
+        // Move the end first when moving later, so that the entity never ends
+        // before it starts along the way
+        if self.new_start.cmp(&self.start).is_gt() {
+            if let Some(new_end) = self.new_end {
+                entity.set_end(new_end)?;
+            }
+            entity.set_start(self.new_start)
+        } else {
+            entity.set_start(self.new_start)?;
+            match self.new_end {
+                Some(new_end) => entity.set_end(new_end),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Work out what shifting the dates of the entities would do, without changing
+/// anything (i.e. a dry run of [`shift_entity_dates()`])
+pub async fn preview_entity_date_shift(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_ids: &BTreeSet<OpenTimelineId>,
+    shift: &DateShift,
+) -> Result<Vec<ShiftedEntityDates>, CrudError> {
+    let mut shifted = Vec::with_capacity(entity_ids.len());
+    for entity_id in entity_ids {
+        let entity = Entity::fetch_by_id(transaction, entity_id).await?;
+        shifted.push(ShiftedEntityDates::from(&entity, shift)?);
+    }
+    Ok(shifted)
+}
+
+/// Shift the start and end dates of each of the entities (see
+/// [`Date::shifted()`]).  Nothing is changed if any of the entities can't be
+/// shifted, so long as the transaction isn't committed.
+pub async fn shift_entity_dates(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_ids: &BTreeSet<OpenTimelineId>,
+    shift: &DateShift,
+) -> Result<Vec<ShiftedEntityDates>, CrudError> {
+    let mut shifted = Vec::with_capacity(entity_ids.len());
+    for entity_id in entity_ids {
+        let mut entity = Entity::fetch_by_id(transaction, entity_id).await?;
+        let shifted_dates = ShiftedEntityDates::from(&entity, shift)?;
+        shifted_dates
+            .apply(&mut entity)
+            .map_err(|_| CrudError::DateShift(shifted_dates.name.clone()))?;
+        entity.update(transaction).await?;
+        shifted.push(shifted_dates);
+    }
+    Ok(shifted)
+}
+
+/// Add the entities to the timeline (as direct members).  Entities that are
+/// already direct members are skipped.
+pub async fn add_entities_to_timeline(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+    entity_ids: &BTreeSet<OpenTimelineId>,
+) -> Result<(), CrudError> {
+    for entity_id in entity_ids {
+        insert_timeline_entity(transaction, timeline_id, entity_id).await?;
+    }
+    Ok(())
+}
+
+/// Add the tag to each of the entities (those that already have it are left
+/// as they are)
+pub async fn add_tag_to_entities(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_ids: &BTreeSet<OpenTimelineId>,
+    tag: &Tag,
+) -> Result<(), CrudError> {
+    for entity_id in entity_ids {
+        let mut entity = Entity::fetch_by_id(transaction, entity_id).await?;
+        if entity
+            .tags()
+            .as_ref()
+            .is_some_and(|tags| tags.contains(tag))
+        {
+            continue;
+        }
+        entity.add_tag(tag.clone());
+        entity.update(transaction).await?;
+    }
+    Ok(())
+}
+
+/// Remove the tag from each of the entities (those without it are left as they
+/// are)
+pub async fn remove_tag_from_entities(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_ids: &BTreeSet<OpenTimelineId>,
+    tag: &Tag,
+) -> Result<(), CrudError> {
+    for entity_id in entity_ids {
+        let mut entity = Entity::fetch_by_id(transaction, entity_id).await?;
+        if !entity
+            .tags()
+            .as_ref()
+            .is_some_and(|tags| tags.contains(tag))
+        {
+            continue;
+        }
+        entity.remove_tag(tag);
+        entity.update(transaction).await?;
+    }
+    Ok(())
+}
+
+/// Delete all of the entities (see [`Entity::delete_by_id()`])
+pub async fn delete_entities(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_ids: &BTreeSet<OpenTimelineId>,
+) -> Result<(), CrudError> {
+    for entity_id in entity_ids {
+        Entity::delete_by_id(transaction, entity_id).await?;
+    }
+    Ok(())
+}
+
+/// Delete all of the timelines (see [`TimelineEdit::delete_by_id()`])
+pub async fn delete_timelines(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_ids: &BTreeSet<OpenTimelineId>,
+) -> Result<(), CrudError> {
+    for timeline_id in timeline_ids {
+        TimelineEdit::delete_by_id(transaction, timeline_id).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, fetch_timeline_direct_member_entity_ids_by_timeline_id};
+    use bool_tag_expr::TagValue;
+    use open_timeline_core::HasIdAndName;
+    use sqlx::Pool;
+
+    /// Create the valid test entities, returning their IDs
+    async fn create_entities(
+        transaction: &mut Transaction<'_, Sqlite>,
+    ) -> BTreeSet<OpenTimelineId> {
+        let mut entity_ids = BTreeSet::new();
+        for mut entity in valid_entities().into_iter().take(3) {
+            entity.create(transaction).await.unwrap();
+            entity_ids.insert(entity.id().unwrap());
+        }
+        entity_ids
+    }
+
+    #[sqlx::test]
+    async fn add_and_remove_tag(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let entity_ids = create_entities(&mut transaction).await;
+        let tag = Tag::from(None, TagValue::from(&"bulk").unwrap());
+
+        // Adding (twice, to check entities that already have it are skipped)
+        add_tag_to_entities(&mut transaction, &entity_ids, &tag)
+            .await
+            .unwrap();
+        add_tag_to_entities(&mut transaction, &entity_ids, &tag)
+            .await
+            .unwrap();
+        for entity_id in &entity_ids {
+            let entity = Entity::fetch_by_id(&mut transaction, entity_id)
+                .await
+                .unwrap();
+            assert!(entity.tags().as_ref().unwrap().contains(&tag));
+        }
+
+        // Removing
+        remove_tag_from_entities(&mut transaction, &entity_ids, &tag)
+            .await
+            .unwrap();
+        for entity_id in &entity_ids {
+            let entity = Entity::fetch_by_id(&mut transaction, entity_id)
+                .await
+                .unwrap();
+            assert!(
+                !entity
+                    .tags()
+                    .as_ref()
+                    .is_some_and(|tags| tags.contains(&tag))
+            );
+        }
+    }
+
+    #[sqlx::test]
+    async fn shift_dates(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let entity_ids = create_entities(&mut transaction).await;
+        let shift = DateShift::from(1, 0, 0);
+
+        // A preview doesn't change anything
+        let preview = preview_entity_date_shift(&mut transaction, &entity_ids, &shift)
+            .await
+            .unwrap();
+        assert_eq!(preview.len(), entity_ids.len());
+        for shifted in &preview {
+            let entity = Entity::fetch_by_id(&mut transaction, &shifted.id)
+                .await
+                .unwrap();
+            assert_eq!(entity.start(), shifted.start);
+            assert_eq!(
+                shifted.new_start.year().value(),
+                shifted.start.year().value() + 1
+            );
+        }
+
+        // Shifting does what the preview said it would
+        let shifted = shift_entity_dates(&mut transaction, &entity_ids, &shift)
+            .await
+            .unwrap();
+        assert_eq!(shifted, preview);
+        for shifted in &shifted {
+            let entity = Entity::fetch_by_id(&mut transaction, &shifted.id)
+                .await
+                .unwrap();
+            assert_eq!(entity.start(), shifted.new_start);
+            assert_eq!(entity.end(), shifted.new_end);
+        }
+
+        // Shifting back (earlier) again
+        let shift = DateShift::from(-1, 0, 0);
+        shift_entity_dates(&mut transaction, &entity_ids, &shift)
+            .await
+            .unwrap();
+        for shifted in &shifted {
+            let entity = Entity::fetch_by_id(&mut transaction, &shifted.id)
+                .await
+                .unwrap();
+            assert_eq!(entity.start(), shifted.start);
+        }
+
+        // Too far
+        let shift = DateShift::from(1_000_000, 0, 0);
+        assert!(matches!(
+            shift_entity_dates(&mut transaction, &entity_ids, &shift).await,
+            Err(CrudError::DateShift(_))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn add_to_timeline_and_delete(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let entity_ids = create_entities(&mut transaction).await;
+        let mut timeline = valid_timeline_no_subtimelines();
+        timeline.clear_entities();
+        timeline.create(&mut transaction).await.unwrap();
+        let timeline_id = timeline.id().unwrap();
+
+        // Adding (twice, to check direct members are skipped)
+        add_entities_to_timeline(&mut transaction, &timeline_id, &entity_ids)
+            .await
+            .unwrap();
+        add_entities_to_timeline(&mut transaction, &timeline_id, &entity_ids)
+            .await
+            .unwrap();
+        let members =
+            fetch_timeline_direct_member_entity_ids_by_timeline_id(&mut transaction, &timeline_id)
+                .await
+                .unwrap()
+                .unwrap();
+        assert_eq!(members.into_iter().collect::<BTreeSet<_>>(), entity_ids);
+
+        // Deleting
+        delete_entities(&mut transaction, &entity_ids)
+            .await
+            .unwrap();
+        for entity_id in &entity_ids {
+            assert!(
+                Entity::fetch_by_id(&mut transaction, entity_id)
+                    .await
+                    .is_err()
+            );
+        }
+        delete_timelines(&mut transaction, &BTreeSet::from([timeline_id]))
+            .await
+            .unwrap();
+        assert!(
+            TimelineEdit::fetch_by_id(&mut transaction, &timeline_id)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -7,10 +7,15 @@
 use async_trait::async_trait;
 use bool_tag_expr::{BoolTagExpr, ParseError, Tag};
 use open_timeline_core::{
-    IsReducedType, Name, OpenTimelineId, ReducedEntities, ReducedTimeline, ReducedTimelines,
+    DateError, EntityError, ErrorCode, IsReducedType, LocationError, Name, NameError,
+    OpenTimelineId, ReducedEntities, ReducedTimeline, ReducedTimelines, Slug, SourceError,
+    TagHierarchyError, TagQuery, TagQueryError,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::{Sqlite, Transaction};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Alias of u64
@@ -117,6 +122,18 @@ pub trait FetchByPartialNameAndBoolTagExpr:
         limit: Limit,
         partial_name: &str,
         bool_tag_expr: BoolTagExpr,
+    ) -> Result<Self, CrudError> {
+        let tag_query = bool_tag_expr.into();
+        Self::fetch_by_partial_name_and_tag_query(transaction, limit, partial_name, &tag_query)
+            .await
+    }
+
+    /// Fetch the thing using a partial name and a [`TagQuery`]
+    async fn fetch_by_partial_name_and_tag_query(
+        transaction: &mut Transaction<'_, Sqlite>,
+        limit: Limit,
+        partial_name: &str,
+        tag_query: &TagQuery,
     ) -> Result<Self, CrudError>;
 }
 
@@ -129,6 +146,16 @@ pub trait FetchByBoolTagExpr: Sized + Send {
         transaction: &mut Transaction<'_, Sqlite>,
         limit: Limit,
         bool_tag_expr: BoolTagExpr,
+    ) -> Result<Self, CrudError> {
+        Self::fetch_by_tag_query(transaction, limit, &bool_tag_expr.into()).await
+    }
+
+    /// Fetch the thing using a [`TagQuery`] (a boolean tag expression that can
+    /// also compare the values of tags)
+    async fn fetch_by_tag_query(
+        transaction: &mut Transaction<'_, Sqlite>,
+        limit: Limit,
+        tag_query: &TagQuery,
     ) -> Result<Self, CrudError>;
 }
 
@@ -198,8 +225,9 @@ pub trait Update {
     async fn update(&mut self, transaction: &mut Transaction<'_, Sqlite>) -> Result<(), CrudError>;
 }
 
-// TODO: crush database errors into one (connection, etc, not missing from DB)
-/// All errors that could occur when running CRUD operations
+/// All errors that could occur when running CRUD operations.  The messages are
+/// suitable for showing to users (the causes, e.g. the underlying database
+/// error, can be displayed with [`open_timeline_core::ErrorChain`]).
 #[derive(Debug, Error, Clone, Hash, PartialEq, Eq)]
 pub enum CrudError {
     #[error("The name field is not set")]
@@ -208,13 +236,29 @@ pub enum CrudError {
     #[error("{0}")]
     BoolExprParse(ParseError),
 
-    // TODO: really should impl From<NameError> for CrudError
-    #[error("Name error")]
-    Name,
+    #[error("{0}")]
+    TagQueryParse(TagQueryError),
+
+    #[error(transparent)]
+    Name(#[from] NameError),
 
-    // TODO: really should impl From<DateError> for CrudError
-    #[error("Date error")]
-    Date,
+    #[error("The name is already in use")]
+    NameAlreadyInUse,
+
+    #[error(transparent)]
+    Date(#[from] DateError),
+
+    #[error(transparent)]
+    Entity(#[from] EntityError),
+
+    #[error("The dates of entity '{0}' can't be shifted that far")]
+    DateShift(Name),
+
+    #[error(transparent)]
+    Source(#[from] SourceError),
+
+    #[error(transparent)]
+    Location(#[from] LocationError),
 
     #[error("The ID field is not set for entity '{0}'")]
     IdNotSetForEntity(Name),
@@ -243,17 +287,11 @@ pub enum CrudError {
     #[error("Not unique in the database: {0}")]
     NotUniqueInDb(String),
 
-    #[error("There was a error with the database")]
-    DbError,
-
-    #[error("Error when trying to establish a new connection to the database")]
-    DbNewConnection,
+    #[error("There was an error with the database")]
+    Db(#[source] DbError),
 
-    #[error("Error when trying to establish a new transaction with the database")]
-    DbNewTransaction,
-
-    #[error("SQLx database error: {0}")]
-    SqlxDbError(String),
+    #[error("The database contains invalid data: {0}")]
+    InvalidData(String),
 
     #[error("The ID is not in the database")]
     IdNotInDb,
@@ -273,6 +311,12 @@ pub enum CrudError {
     #[error("Error when updating the name")]
     UpdatingName,
 
+    #[error("It has been changed elsewhere since it was opened")]
+    StaleWrite,
+
+    #[error("The slug is already in use: {0}")]
+    SlugAlreadyInUse(Slug),
+
     #[error("Error when fetching the timeline's direct member entities")]
     FetchingTimelineDirectMemberEntities,
 
@@ -285,6 +329,15 @@ pub enum CrudError {
     #[error("Error when fetching the timeline's tags")]
     FetchingTimelineTags,
 
+    #[error("The timeline isn't dynamic (defined purely by a boolean tag expression)")]
+    NotADynamicTimeline,
+
+    #[error("A timeline can't be a subtimeline of itself (directly or indirectly)")]
+    SubtimelineCycle,
+
+    #[error(transparent)]
+    TagHierarchy(#[from] TagHierarchyError),
+
     #[error("It is neither an Id nor a Name")]
     NeitherIdNorName,
 
@@ -303,7 +356,6 @@ pub enum CrudError {
 }
 
 impl From<sqlx::Error> for CrudError {
-    // TODO: do this properly
     fn from(value: sqlx::Error) -> Self {
         if let Some(db_err) = value.as_database_error() {
             if db_err.is_unique_violation() {
@@ -311,8 +363,94 @@ impl From<sqlx::Error> for CrudError {
                 return CrudError::NotUniqueInDb(db_err.message().to_string());
             }
         }
+        match value {
+            sqlx::Error::RowNotFound => Self::NotInDb,
+            value => Self::Db(DbError(Arc::new(value))),
+        }
+    }
+}
+
+impl ErrorCode for CrudError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::NameNotSet
+            | Self::IdNotSetForEntity(_)
+            | Self::IdNotSetForTimeline(_)
+            | Self::EntityStartYearNotSet
+            | Self::NeitherIdNorNameSet
+            | Self::BothIdAndNameSet
+            | Self::NeitherIdNorName => "missing_field",
+            Self::BoolExprParse(_) | Self::TagQueryParse(_) => "invalid_tag_query",
+            Self::Name(_) => "invalid_name",
+            Self::Date(_) | Self::DateShift(_) => "invalid_date",
+            Self::Entity(_) => "invalid_entity",
+            Self::Source(_) => "invalid_source",
+            Self::Location(_) => "invalid_location",
+            Self::NameAlreadyInUse
+            | Self::EntityIdAlreadyInUse
+            | Self::EntityNameAlreadyInUse(_)
+            | Self::SlugAlreadyInUse(_)
+            | Self::NotUniqueInDb(_) => "already_exists",
+            Self::IdNotInDb
+            | Self::NameNotInDb
+            | Self::NotInDb
+            | Self::TimelineNotInDb
+            | Self::EntityNotInDb => "not_found",
+            Self::StaleWrite => "stale_write",
+            Self::NotADynamicTimeline => "not_a_dynamic_timeline",
+            Self::SubtimelineCycle => "invalid_subtimeline",
+            Self::TagHierarchy(_) => "invalid_tag_hierarchy",
+            Self::NoDbSelected => "no_database",
+            Self::DbMigrate(_) => "database_migration_error",
+            Self::Db(_)
+            | Self::InvalidData(_)
+            | Self::UpdatingName
+            | Self::FetchingTimelineDirectMemberEntities
+            | Self::FetchingTimelineDirectSubtimelineIds
+            | Self::FetchingTimelineAllEntityIds
+            | Self::FetchingTimelineTags => "database_error",
+            Self::Io(_) => "io_error",
+            Self::Json(_) => "json_error",
+            Self::Config => "config_error",
+        }
+    }
+}
+
+/// An error from the database (shared, so that [`CrudError`]s can be cloned).
+/// Errors are equal if their messages are.
+#[derive(Debug, Clone)]
+pub struct DbError(Arc<sqlx::Error>);
+
+impl DbError {
+    /// Get the underlying database error
+    pub fn sqlx_error(&self) -> &sqlx::Error {
+        &self.0
+    }
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for DbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl PartialEq for DbError {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+impl Eq for DbError {}
 
-        Self::SqlxDbError(value.to_string())
+impl Hash for DbError {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_string().hash(state);
     }
 }
 
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Settings that belong to a database (rather than to the app using it), so
+//! that they apply however the database is changed
+//!
+
+use crate::{CrudError, update_all_entity_period_tags};
+use sqlx::{Sqlite, Transaction};
+
+/// The settings stored in the database
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DatabaseSettings {
+    /// Whether entities are given `decade` & `century` typed tags derived from
+    /// their dates (kept up to date whenever they are saved)
+    pub automatic_period_tags: bool,
+}
+
+/// Fetch the database's settings
+pub async fn fetch_database_settings(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<DatabaseSettings, CrudError> {
+    let automatic_period_tags =
+        sqlx::query_scalar("SELECT automatic_period_tags FROM database_settings WHERE id = 1")
+            .fetch_one(&mut **transaction)
+            .await?;
+    Ok(DatabaseSettings {
+        automatic_period_tags,
+    })
+}
+
+/// Change the database's settings.  Enabling automatic period tags gives every
+/// entity its period tags (disabling it leaves them as they are).
+pub async fn update_database_settings(
+    transaction: &mut Transaction<'_, Sqlite>,
+    settings: &DatabaseSettings,
+) -> Result<(), CrudError> {
+    let current = fetch_database_settings(transaction).await?;
+    sqlx::query("UPDATE database_settings SET automatic_period_tags = ? WHERE id = 1")
+        .bind(settings.automatic_period_tags)
+        .execute(&mut **transaction)
+        .await?;
+    if settings.automatic_period_tags && !current.automatic_period_tags {
+        update_all_entity_period_tags(transaction).await?;
+    }
+    Ok(())
+}
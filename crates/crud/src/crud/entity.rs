@@ -4,16 +4,31 @@
 //! All CRUD functionality for entities
 //!
 
+mod aliases;
 mod counts;
 mod entities;
 mod entity;
+mod near;
+mod on_this_day;
+mod overlaps;
+mod period_tags;
 mod reduced_entities;
 mod reduced_entity;
+mod reduced_render_entity;
 mod search;
+mod sources;
+mod typed_tags;
 
+pub use aliases::*;
 pub use counts::*;
 pub use entities::*;
 pub use entity::*;
+pub use near::*;
+pub use on_this_day::*;
+pub use overlaps::*;
+pub use period_tags::*;
 pub use reduced_entities::*;
 pub use reduced_entity::*;
 pub use search::*;
+pub use sources::*;
+pub use typed_tags::*;
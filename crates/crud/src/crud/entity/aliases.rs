@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! All CRUD functionality for entity aliases (other names an entity is known
+//! by)
+//!
+
+use crate::{CrudError, is_entity_id_in_db};
+use open_timeline_core::{Aliases, Name, OpenTimelineId};
+use sqlx::{Sqlite, Transaction};
+
+/// Fetch all of an entity's aliases
+pub async fn fetch_entity_aliases(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+) -> Result<Aliases, CrudError> {
+    Ok(sqlx::query!(
+        r#"
+            SELECT alias AS "alias: Name"
+            FROM entity_aliases
+            WHERE entity_id=?
+        "#,
+        entity_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| row.alias)
+    .collect())
+}
+
+/// Add an alias to an entity (adding an alias it already has does nothing)
+pub async fn add_entity_alias(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+    alias: &Name,
+) -> Result<(), CrudError> {
+    if !is_entity_id_in_db(transaction, entity_id).await? {
+        return Err(CrudError::IdNotInDb);
+    }
+    insert_entity_aliases(transaction, entity_id, &Aliases::from([alias.clone()])).await
+}
+
+/// Remove an alias from an entity
+pub async fn remove_entity_alias(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+    alias: &Name,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM entity_aliases
+            WHERE entity_id=? AND alias=?
+        "#,
+        entity_id,
+        alias
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Fetch the ID of the entity known by the alias.  If more than one entity
+/// shares the alias, the choice is arbitrary.
+pub async fn entity_id_from_alias(
+    transaction: &mut Transaction<'_, Sqlite>,
+    alias: &Name,
+) -> Result<Option<OpenTimelineId>, CrudError> {
+    Ok(sqlx::query!(
+        r#"
+            SELECT entity_id AS "entity_id: OpenTimelineId"
+            FROM entity_aliases
+            WHERE alias=?
+            LIMIT 1
+        "#,
+        alias
+    )
+    .fetch_optional(&mut **transaction)
+    .await?
+    .map(|row| row.entity_id))
+}
+
+/// Insert an entity's aliases into the database
+pub(crate) async fn insert_entity_aliases(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+    aliases: &Aliases,
+) -> Result<(), CrudError> {
+    for alias in aliases {
+        sqlx::query!(
+            r#"
+                INSERT OR IGNORE INTO entity_aliases (entity_id, alias)
+                VALUES (?, ?)
+            "#,
+            entity_id,
+            alias
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Delete an entity's aliases from the database
+pub(crate) async fn delete_entity_aliases(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM entity_aliases
+            WHERE entity_id=?
+        "#,
+        entity_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, FetchById};
+    use open_timeline_core::{Entity, HasIdAndName};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn add_fetch_and_remove(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+
+        // Insert an entity
+        let mut entity = valid_entity();
+        entity.create(&mut transaction).await.unwrap();
+        let id = entity.id().unwrap();
+
+        // Add an alias (twice) and find the entity by it
+        let alias = Name::from("Another Name").unwrap();
+        add_entity_alias(&mut transaction, &id, &alias)
+            .await
+            .unwrap();
+        add_entity_alias(&mut transaction, &id, &alias)
+            .await
+            .unwrap();
+        let aliases = fetch_entity_aliases(&mut transaction, &id).await.unwrap();
+        assert_eq!(aliases, Aliases::from([alias.clone()]));
+        assert_eq!(
+            entity_id_from_alias(&mut transaction, &alias)
+                .await
+                .unwrap(),
+            Some(id)
+        );
+
+        // The alias is part of the fetched entity
+        let fetched = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+        assert_eq!(fetched.aliases(), &Some(aliases));
+
+        // Remove the alias
+        remove_entity_alias(&mut transaction, &id, &alias)
+            .await
+            .unwrap();
+        assert!(
+            fetch_entity_aliases(&mut transaction, &id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            entity_id_from_alias(&mut transaction, &alias)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Aliases can't be added to entities that aren't in the database
+        assert!(
+            add_entity_alias(&mut transaction, &OpenTimelineId::new(), &alias)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -5,10 +5,10 @@
 //!
 
 use crate::{CrudError, FetchById, SortAlphabetically, SortByNumber};
-use log::info;
 use open_timeline_core::{Date, Entity, HasIdAndName, Name, OpenTimelineId};
 use serde::{Deserialize, Serialize};
 use sqlx::{Sqlite, Transaction};
+use tracing::info;
 
 /// Holds a timeline and the number of entities it has
 #[derive(Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
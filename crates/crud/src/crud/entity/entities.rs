@@ -4,8 +4,9 @@
 //! All functionality that relates only to [`Entity`]s
 //!
 
-use crate::{CrudError, FetchById, FetchByPartialName, Limit};
-use open_timeline_core::{Entity, OpenTimelineId, ReducedEntities};
+use crate::{BoolTagExprSql, CrudError, FetchById, Limit, Tagged};
+use bool_tag_expr::BoolTagExpr;
+use open_timeline_core::{Entity, OpenTimelineId};
 use sqlx::{Sqlite, Transaction};
 
 // TODO: much copied form timeline.rs - can any be a macro/generic?
@@ -35,16 +36,49 @@ pub async fn fetch_random_entities_with_an_end_year(
     Ok(entities)
 }
 
-/// Fetch some number of random entities
+/// Fetch some number of random entities (that match the boolean tag expr, if
+/// given)
 pub async fn fetch_random_entities(
     transaction: &mut Transaction<'_, Sqlite>,
     Limit(limit): Limit,
+    bool_expr: Option<&BoolTagExpr>,
 ) -> Result<Vec<Entity>, CrudError> {
-    let reduced_entities =
-        ReducedEntities::fetch_by_partial_name(transaction, Limit(limit), "").await?;
+    let entity_ids: Vec<OpenTimelineId> = match bool_expr {
+        Some(bool_expr) => {
+            let bool_expr_sql = BoolTagExprSql::compile(bool_expr, Tagged::Entities, "entities.id");
+            let sql = format!(
+                r#"
+                    SELECT id
+                    FROM entities
+                    WHERE {}
+                    ORDER BY RANDOM()
+                    LIMIT ?
+                "#,
+                bool_expr_sql.condition()
+            );
+            let mut query = sqlx::query_scalar(&sql);
+            for bind in bool_expr_sql.binds() {
+                query = query.bind(bind);
+            }
+            query.bind(limit).fetch_all(&mut **transaction).await?
+        }
+        None => {
+            sqlx::query_scalar(
+                r#"
+                    SELECT id
+                    FROM entities
+                    ORDER BY RANDOM()
+                    LIMIT ?
+                "#,
+            )
+            .bind(limit)
+            .fetch_all(&mut **transaction)
+            .await?
+        }
+    };
     let mut entities = Vec::new();
-    for id in reduced_entities.ids() {
-        entities.push(Entity::fetch_by_id(transaction, &id).await?);
+    for entity_id in entity_ids {
+        entities.push(Entity::fetch_by_id(transaction, &entity_id).await?);
     }
     Ok(entities)
 }
@@ -6,12 +6,23 @@
 
 use crate::crud::common::*;
 use crate::crud::common::{Create, Update};
+use crate::{
+    SlugKind, delete_entity_aliases, delete_entity_sources, delete_entity_story_steps,
+    delete_entity_typed_tags, delete_favourite, delete_slug_redirects, entity_id_from_alias,
+    fetch_entity_aliases, fetch_entity_sources, fetch_entity_typed_tags, generate_slug,
+    insert_entity_aliases, insert_entity_sources, insert_entity_typed_tags,
+    update_entity_period_tags,
+};
 use bool_tag_expr::{Tag, TagName, TagValue, Tags};
-use open_timeline_core::{Date, Entity, HasIdAndName, Name, OpenTimelineId};
+use open_timeline_core::{
+    Calendar, Coordinates, Date, Entity, EntityError, HasIdAndName, Location, Name, OpenTimelineId,
+};
 use sqlx::{Sqlite, Transaction};
+use tracing::instrument;
 
 impl Create for Entity {
     /// Create an [`Entity`] in the database
+    #[instrument(name = "create_entity", skip_all, fields(name = %self.name()))]
     async fn create(&mut self, transaction: &mut Transaction<'_, Sqlite>) -> Result<(), CrudError> {
         if self.id().is_none() {
             self.set_id(OpenTimelineId::new());
@@ -27,6 +38,9 @@ impl Create for Entity {
             let end_year = self.end_year();
             let end_month = self.end_month();
             let end_day = self.end_day();
+            let start_calendar = self.start_calendar();
+            let end_calendar = self.end_calendar();
+            let (place, latitude, longitude) = location_columns(self.location());
 
             sqlx::query!(
                 r#"
@@ -39,9 +53,14 @@ impl Create for Entity {
                     start_day,
                     end_year,
                     end_month,
-                    end_day
+                    end_day,
+                    start_calendar,
+                    end_calendar,
+                    place,
+                    latitude,
+                    longitude
                 )
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
                 entity_id,
                 entity_name,
@@ -50,7 +69,12 @@ impl Create for Entity {
                 start_day,
                 end_year,
                 end_month,
-                end_day
+                end_day,
+                start_calendar,
+                end_calendar,
+                place,
+                latitude,
+                longitude
             )
             .execute(&mut **transaction)
             .await
@@ -62,18 +86,45 @@ impl Create for Entity {
                 }
                 CrudError::into(error.into())
             })?;
+            self.set_version(Some(0));
         }
 
+        // Slug
+        generate_slug(
+            transaction,
+            SlugKind::Entity,
+            &self.id().unwrap(),
+            self.name(),
+        )
+        .await?;
+
         // Tags
         if let Some(tags) = &self.tags() {
             insert_entity_tags(transaction, &self.id().unwrap(), tags).await?;
         }
 
+        // Typed tags
+        if let Some(typed_tags) = &self.typed_tags() {
+            insert_entity_typed_tags(transaction, &self.id().unwrap(), typed_tags).await?;
+        }
+        update_entity_period_tags(transaction, &self.id().unwrap(), &self.date_range()).await?;
+
+        // Aliases
+        if let Some(aliases) = &self.aliases() {
+            insert_entity_aliases(transaction, &self.id().unwrap(), aliases).await?;
+        }
+
+        // Sources
+        if let Some(sources) = &self.sources() {
+            insert_entity_sources(transaction, &self.id().unwrap(), sources).await?;
+        }
+
         Ok(())
     }
 }
 
 impl FetchByName for Entity {
+    #[instrument(name = "fetch_entity", skip_all, fields(%name))]
     async fn fetch_by_name(
         transaction: &mut Transaction<'_, Sqlite>,
         name: &Name,
@@ -87,6 +138,7 @@ impl FetchById for Entity {
     // TODO: might be able to use this in generics if we remove `async` and instead
     // return `Pin<Box<dyn Future<Output = Result<Entity, CrudError>> + Send + 'static>>;`
     // But that might be a pain (macro for now - see gui/main.rs)
+    #[instrument(name = "fetch_entity", skip_all, fields(%id))]
     async fn fetch_by_id(
         transaction: &mut Transaction<'_, Sqlite>,
         id: &OpenTimelineId,
@@ -94,12 +146,12 @@ impl FetchById for Entity {
         match is_entity_id_in_db(transaction, id).await {
             Ok(true) => (),
             Ok(false) => return Err(CrudError::IdNotInDb),
-            Err(_) => return Err(CrudError::DbError),
+            Err(error) => return Err(error),
         }
 
         // NOTE: the "id: OpenTimelineId" is essential
         // Name & Dates
-        let (entity_name, entity_start, entity_end) = {
+        let (entity_name, entity_start, entity_end, entity_location, entity_version) = {
             let record = sqlx::query!(
                 r#"
                 SELECT
@@ -110,7 +162,13 @@ impl FetchById for Entity {
                     start_day,
                     end_year,
                     end_month,
-                    end_day
+                    end_day,
+                    start_calendar AS "start_calendar: Calendar",
+                    end_calendar AS "end_calendar: Calendar",
+                    place,
+                    latitude,
+                    longitude,
+                    version
                 FROM entities
                 WHERE id=?
             "#,
@@ -123,19 +181,27 @@ impl FetchById for Entity {
             let name = record.name;
 
             // Start date
-            let start = Date::from(record.start_day, record.start_month, record.start_year)
-                .map_err(|_| CrudError::Date)?;
+            let start = Date::from(record.start_day, record.start_month, record.start_year)?
+                .with_calendar(record.start_calendar);
 
             // End date
             let end = if let Some(end_year) = record.end_year {
                 Some(
-                    Date::from(record.end_day, record.end_month, end_year)
-                        .map_err(|_| CrudError::Date)?,
+                    Date::from(record.end_day, record.end_month, end_year)?
+                        .with_calendar(record.end_calendar),
                 )
             } else {
                 None
             };
-            (name, start, end)
+
+            // Location
+            let coordinates = match (record.latitude, record.longitude) {
+                (Some(latitude), Some(longitude)) => Some(Coordinates::from(latitude, longitude)?),
+                _ => None,
+            };
+            let location = Location::from(record.place.as_deref(), coordinates).ok();
+
+            (name, start, end, location, record.version)
         };
 
         // Tags
@@ -159,20 +225,35 @@ impl FetchById for Entity {
             (!tags.is_empty()).then_some(tags)
         };
 
+        // Typed tags
+        let entity_typed_tags = fetch_entity_typed_tags(transaction, id).await?;
+
+        // Aliases
+        let entity_aliases = fetch_entity_aliases(transaction, id).await?;
+
+        // Sources
+        let entity_sources = fetch_entity_sources(transaction, id).await?;
+
         // Return entity
-        Entity::from(
+        let mut entity = Entity::from(
             Some(*id),
             entity_name,
             entity_start,
             entity_end,
             entity_tags,
-        )
-        .map_err(|_| CrudError::Name)
+        )?;
+        entity.set_typed_tags(entity_typed_tags);
+        entity.set_aliases(entity_aliases);
+        entity.set_sources(entity_sources);
+        entity.set_location(entity_location);
+        entity.set_version(Some(entity_version));
+        Ok(entity)
     }
 }
 
 impl Update for Entity {
     /// Update an Entity
+    #[instrument(name = "update_entity", skip_all, fields(name = %self.name()))]
     async fn update(&mut self, transaction: &mut Transaction<'_, Sqlite>) -> Result<(), CrudError> {
         if self.id().is_none() {
             return Err(CrudError::IdNotSetForEntity(self.name().to_owned()));
@@ -180,31 +261,39 @@ impl Update for Entity {
         let entity_id = self.id().unwrap();
         let entity_name = self.name();
 
-        // Name
+        // Noted so that the slug can be regenerated if it is renamed
+        let old_name = entity_name_from_id(transaction, &entity_id).await.ok();
+
+        // Name (& version, which must still be the one fetched if it's known)
         {
-            // TODO: check if update, or if nothing to update (ie failed)
-            match sqlx::query!(
+            let expected_version = self.version();
+            let new_version = sqlx::query_scalar!(
                 r#"
                     UPDATE entities
-                    SET name = ?
-                    WHERE id = ?
+                    SET name = ?1, version = version + 1
+                    WHERE id = ?2 AND (?3 IS NULL OR version = ?3)
+                    RETURNING version
                 "#,
                 entity_name,
                 entity_id,
+                expected_version,
             )
-            .execute(&mut **transaction)
-            .await
-            {
-                Ok(result) => {
-                    if result.rows_affected() != 1 {
-                        Err(CrudError::UpdatingName)?
-                    }
-                }
-                Err(_) => {
-                    // TODO: likely the name already exists
-                    Err(CrudError::UpdatingName)?
+            .fetch_optional(&mut **transaction)
+            .await?;
+            match new_version {
+                Some(new_version) => self.set_version(Some(new_version)),
+                None if expected_version.is_some()
+                    && is_entity_id_in_db(transaction, &entity_id).await? =>
+                {
+                    Err(CrudError::StaleWrite)?
                 }
-            };
+                None => Err(CrudError::UpdatingName)?,
+            }
+        }
+
+        // Slug
+        if old_name.as_ref() != Some(self.name()) {
+            generate_slug(transaction, SlugKind::Entity, &entity_id, self.name()).await?;
         }
 
         // Dates
@@ -215,6 +304,9 @@ impl Update for Entity {
             let end_year = self.end_year();
             let end_month = self.end_month();
             let end_day = self.end_day();
+            let start_calendar = self.start_calendar();
+            let end_calendar = self.end_calendar();
+            let (place, latitude, longitude) = location_columns(self.location());
             sqlx::query!(
                 r#"UPDATE entities
                 SET
@@ -223,7 +315,12 @@ impl Update for Entity {
                     start_day = ?,
                     end_year = ?,
                     end_month = ?,
-                    end_day = ?
+                    end_day = ?,
+                    start_calendar = ?,
+                    end_calendar = ?,
+                    place = ?,
+                    latitude = ?,
+                    longitude = ?
                 WHERE id = ?
             "#,
                 start_year,
@@ -232,6 +329,11 @@ impl Update for Entity {
                 end_year,
                 end_month,
                 end_day,
+                start_calendar,
+                end_calendar,
+                place,
+                latitude,
+                longitude,
                 entity_id,
             )
             .execute(&mut **transaction)
@@ -246,11 +348,37 @@ impl Update for Entity {
             }
         }
 
+        // Typed tags
+        {
+            delete_entity_typed_tags(transaction, &self.id().unwrap()).await?;
+            if let Some(typed_tags) = &self.typed_tags() {
+                insert_entity_typed_tags(transaction, &self.id().unwrap(), typed_tags).await?;
+            }
+            update_entity_period_tags(transaction, &self.id().unwrap(), &self.date_range()).await?;
+        }
+
+        // Aliases
+        {
+            delete_entity_aliases(transaction, &self.id().unwrap()).await?;
+            if let Some(aliases) = &self.aliases() {
+                insert_entity_aliases(transaction, &self.id().unwrap(), aliases).await?;
+            }
+        }
+
+        // Sources
+        {
+            delete_entity_sources(transaction, &self.id().unwrap()).await?;
+            if let Some(sources) = &self.sources() {
+                insert_entity_sources(transaction, &self.id().unwrap(), sources).await?;
+            }
+        }
+
         Ok(())
     }
 }
 
 impl DeleteByName for Entity {
+    #[instrument(name = "delete_entity", skip_all, fields(%name))]
     async fn delete_by_name(
         transaction: &mut Transaction<'_, Sqlite>,
         name: &Name,
@@ -262,6 +390,7 @@ impl DeleteByName for Entity {
 
 // TODO: should this fail if it's not in the database?
 impl DeleteById for Entity {
+    #[instrument(name = "delete_entity", skip_all, fields(%id))]
     async fn delete_by_id(
         transaction: &mut Transaction<'_, Sqlite>,
         id: &OpenTimelineId,
@@ -272,6 +401,24 @@ impl DeleteById for Entity {
         // Tags
         delete_entity_tags(transaction, id).await?;
 
+        // Typed tags
+        delete_entity_typed_tags(transaction, id).await?;
+
+        // Aliases
+        delete_entity_aliases(transaction, id).await?;
+
+        // Sources
+        delete_entity_sources(transaction, id).await?;
+
+        // Starred/recently opened
+        delete_favourite(transaction, id).await?;
+
+        // Old slugs
+        delete_slug_redirects(transaction, id).await?;
+
+        // Story steps
+        delete_entity_story_steps(transaction, id).await?;
+
         // ID, Name and Dates
         sqlx::query!(
             r#"
@@ -287,6 +434,19 @@ impl DeleteById for Entity {
     }
 }
 
+/// The `place`, `latitude` and `longitude` column values for a location
+fn location_columns(location: &Option<Location>) -> (Option<&str>, Option<f64>, Option<f64>) {
+    let place = location.as_ref().and_then(|location| location.place());
+    let coordinates = location
+        .as_ref()
+        .and_then(|location| location.coordinates());
+    (
+        place,
+        coordinates.map(|coordinates| coordinates.latitude()),
+        coordinates.map(|coordinates| coordinates.longitude()),
+    )
+}
+
 /// Insert and entity's tags into the database
 async fn insert_entity_tags(
     transaction: &mut Transaction<'_, Sqlite>,
@@ -382,7 +542,8 @@ pub async fn is_entity_name_in_db(
 }
 
 // TODO: Almost a perfect copy of timeline_id_or_name (merge?)
-/// If the string could be an ID or Name, check the database
+/// If the string could be an ID or Name, check the database.  A name that
+/// isn't an entity's name but is an entity's alias gives that entity's ID.
 pub async fn entity_id_or_name(
     transaction: &mut Transaction<'_, Sqlite>,
     id_or_name: String,
@@ -399,6 +560,8 @@ pub async fn entity_id_or_name(
         Some(IdOrName::Name(name)) => {
             if is_entity_name_in_db(transaction, &name).await? {
                 Ok(Some(IdOrName::Name(name)))
+            } else if let Some(id) = entity_id_from_alias(transaction, &name).await? {
+                Ok(Some(IdOrName::Id(id)))
             } else {
                 Err(CrudError::NameNotInDb)
             }
@@ -450,12 +613,38 @@ pub async fn entity_id_from_name(
     .id)
 }
 
+/// Change the entity's start & end dates in the database, returning the
+/// updated entity.  An end date can't be removed this way (`end` must only be
+/// `None` if the entity has no end date).
+pub async fn update_entity_dates(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+    start: Date,
+    end: Option<Date>,
+) -> Result<Entity, CrudError> {
+    let mut entity = Entity::fetch_by_id(transaction, entity_id).await?;
+
+    // Change the end first if the start is moving past it, so that the entity
+    // never ends before it starts along the way
+    let result = match end {
+        Some(end) if entity.end().is_some_and(|current| start > current) => {
+            entity.set_end(end).and_then(|_| entity.set_start(start))
+        }
+        Some(end) => entity.set_start(start).and_then(|_| entity.set_end(end)),
+        None if entity.end().is_some() => return Err(EntityError::Dates.into()),
+        None => entity.set_start(start),
+    };
+    result?;
+    entity.update(transaction).await?;
+    Ok(entity)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::DatabaseRowCount;
     use crate::test::*;
-    use open_timeline_core::Entity;
+    use open_timeline_core::{Entity, TagValueType, TypedTag, TypedTagValue};
     use sqlx::Pool;
 
     mod create {
@@ -577,6 +766,60 @@ mod test {
             assert_eq!(fetched_by_name, entity);
             assert_eq!(fetched_by_id, entity);
         }
+
+        // The calendar each date was recorded in should be kept
+        #[sqlx::test]
+        fn date_calendars(pool: Pool<Sqlite>) {
+            // Get the transaction
+            let mut transaction = pool.begin().await.unwrap();
+
+            // Inset an entity with a Julian start date into the database
+            let mut entity = valid_entity();
+            let start = Date::from(Some(25), Some(12), 1642)
+                .unwrap()
+                .with_calendar(Some(Calendar::Julian));
+            entity.set_start(start).unwrap();
+            entity.create(&mut transaction).await.unwrap();
+
+            // Check
+            let fetched = Entity::fetch_by_id(&mut transaction, &entity.id().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(fetched.start_calendar(), Some(Calendar::Julian));
+            assert_eq!(fetched.end_calendar(), entity.end_calendar());
+            assert_eq!(fetched, entity);
+        }
+
+        // Typed tags (including dates' calendars) should be kept
+        #[sqlx::test]
+        fn typed_tags(pool: Pool<Sqlite>) {
+            // Get the transaction
+            let mut transaction = pool.begin().await.unwrap();
+
+            // Inset an entity with typed tags into the database
+            let mut entity = valid_entity();
+            let founded = Date::from(Some(25), Some(12), 1642)
+                .unwrap()
+                .with_calendar(Some(Calendar::Julian));
+            entity.add_typed_tag(
+                TypedTag::parse("population", TagValueType::Number, "-2.5").unwrap(),
+            );
+            entity.add_typed_tag(TypedTag::from(
+                TagName::from(&"founded").unwrap(),
+                TypedTagValue::Date(founded),
+            ));
+            entity.create(&mut transaction).await.unwrap();
+
+            // Check
+            let fetched = Entity::fetch_by_id(&mut transaction, &entity.id().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(
+                fetched.typed_tags().as_ref().map(|tags| tags.len()),
+                Some(2)
+            );
+            assert_eq!(fetched, entity);
+        }
     }
 
     mod update {
@@ -668,6 +911,85 @@ mod test {
             let row_counts = DatabaseRowCount::all(&mut transaction).await.unwrap();
             assert_eq!(row_counts.entities, 2);
         }
+
+        // Saving an entity that has been changed since it was fetched fails
+        #[sqlx::test]
+        async fn stale_write(pool: Pool<Sqlite>) {
+            // Get the transaction
+            let mut transaction = pool.begin().await.unwrap();
+
+            // Insert into the database & fetch it twice (e.g. in two windows)
+            let mut entity = valid_entity();
+            entity.create(&mut transaction).await.unwrap();
+            let id = entity.id().unwrap();
+            let mut first = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+            let mut second = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+            assert_eq!(first.version(), Some(0));
+
+            // The first save succeeds (and updates the version)
+            first.set_name(Name::from("First").unwrap());
+            first.update(&mut transaction).await.unwrap();
+            assert_eq!(first.version(), Some(1));
+            first.update(&mut transaction).await.unwrap();
+            assert_eq!(first.version(), Some(2));
+
+            // The second doesn't
+            second.set_name(Name::from("Second").unwrap());
+            assert!(matches!(
+                second.update(&mut transaction).await,
+                Err(CrudError::StaleWrite)
+            ));
+            let fetched = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+            assert_eq!(fetched.name().as_str(), "First");
+
+            // Updates without a version aren't checked
+            second.set_version(None);
+            second.update(&mut transaction).await.unwrap();
+            let fetched = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+            assert_eq!(fetched.name().as_str(), "Second");
+            assert_eq!(fetched.version(), Some(3));
+        }
+
+        // Dates can be moved past each other, but not made invalid
+        #[sqlx::test]
+        fn dates(pool: Pool<Sqlite>) {
+            // Get the transaction
+            let mut transaction = pool.begin().await.unwrap();
+
+            // Insert into the database
+            let mut entity = valid_entity();
+            entity
+                .set_start(Date::from(None, None, 1900).unwrap())
+                .unwrap();
+            entity
+                .set_end(Date::from(None, None, 1910).unwrap())
+                .unwrap();
+            entity.create(&mut transaction).await.unwrap();
+            let id = entity.id().unwrap();
+
+            // Move both dates later than the current end
+            let start = Date::from(None, None, 1920).unwrap();
+            let end = Date::from(None, Some(6), 1930).unwrap();
+            update_entity_dates(&mut transaction, &id, start, Some(end))
+                .await
+                .unwrap();
+            let fetched = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+            assert_eq!((fetched.start(), fetched.end()), (start, Some(end)));
+
+            // An entity can't end before it starts, or lose its end date
+            assert!(
+                update_entity_dates(&mut transaction, &id, end, Some(start))
+                    .await
+                    .is_err()
+            );
+            assert!(
+                update_entity_dates(&mut transaction, &id, start, None)
+                    .await
+                    .is_err()
+            );
+            let fetched = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+            assert_eq!((fetched.start(), fetched.end()), (start, Some(end)));
+        }
     }
 
     mod delete {
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Finding [`Entity`]s that are near a place
+//!
+
+use crate::{CrudError, FetchById, Limit};
+use open_timeline_core::{Coordinates, Entity, OpenTimelineId};
+use sqlx::{Sqlite, Transaction};
+
+/// The (approximate) number of kilometres per degree of latitude
+const KM_PER_DEGREE_OF_LATITUDE: f64 = 111.0;
+
+/// Fetch the entities within `radius_km` kilometres of the coordinates
+/// (nearest first)
+pub async fn entities_near(
+    transaction: &mut Transaction<'_, Sqlite>,
+    coordinates: &Coordinates,
+    radius_km: f64,
+    Limit(limit): Limit,
+) -> Result<Vec<Entity>, CrudError> {
+    // Narrow the entities down by latitude, then compare the full distances
+    let degrees = radius_km / KM_PER_DEGREE_OF_LATITUDE + 0.01;
+    let min_latitude = coordinates.latitude() - degrees;
+    let max_latitude = coordinates.latitude() + degrees;
+    let entity_ids = sqlx::query_scalar!(
+        r#"
+            SELECT id AS "id: OpenTimelineId"
+            FROM entities
+            WHERE latitude BETWEEN ? AND ?
+            AND longitude IS NOT NULL
+        "#,
+        min_latitude,
+        max_latitude
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let mut entities = Vec::new();
+    for entity_id in entity_ids {
+        let entity = Entity::fetch_by_id(transaction, &entity_id).await?;
+        if entity.is_near(coordinates, radius_km) {
+            entities.push(entity);
+        }
+    }
+    entities.sort_by(|a, b| distance_km(a, coordinates).total_cmp(&distance_km(b, coordinates)));
+    entities.truncate(limit as usize);
+    Ok(entities)
+}
+
+/// The distance from the entity to the coordinates (entities without
+/// coordinates are infinitely far away)
+fn distance_km(entity: &Entity, coordinates: &Coordinates) -> f64 {
+    entity
+        .location()
+        .as_ref()
+        .and_then(|location| location.coordinates())
+        .map_or(f64::INFINITY, |own| own.distance_km(coordinates))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Create;
+    use crate::test::*;
+    use open_timeline_core::{HasIdAndName, Location};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn nearest_first(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+
+        // London, Paris & somewhere without coordinates
+        let london = Coordinates::from(51.5074, -0.1278).unwrap();
+        let paris = Coordinates::from(48.8566, 2.3522).unwrap();
+        let mut entities = valid_entities();
+        let mut in_london = entities.pop().unwrap();
+        let mut in_paris = entities.pop().unwrap();
+        let mut unplaced = entities.pop().unwrap();
+        in_london.set_location(Some(Location::from(Some("London"), Some(london)).unwrap()));
+        in_paris.set_location(Some(Location::from(None, Some(paris)).unwrap()));
+        unplaced.set_location(Some(Location::from(Some("Somewhere"), None).unwrap()));
+        for entity in [&mut in_london, &mut in_paris, &mut unplaced] {
+            entity.create(&mut transaction).await.unwrap();
+        }
+
+        // The location is kept
+        let fetched = Entity::fetch_by_id(&mut transaction, &in_london.id().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(fetched.location(), in_london.location());
+
+        // Only London is within 100km of London
+        let near = entities_near(&mut transaction, &london, 100.0, Limit(10))
+            .await
+            .unwrap();
+        assert_eq!(near, vec![in_london.clone()]);
+
+        // Both are within 400km, nearest first
+        let near = entities_near(&mut transaction, &paris, 400.0, Limit(10))
+            .await
+            .unwrap();
+        assert_eq!(near, vec![in_paris, in_london]);
+    }
+}
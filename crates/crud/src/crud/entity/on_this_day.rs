@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! "On this day" - the [`Entity`]s that started or ended on a day of the year
+//!
+
+use crate::{CrudError, FetchById};
+use open_timeline_core::{Entity, OpenTimelineId};
+use serde::Serialize;
+use sqlx::{Sqlite, Transaction};
+
+/// The entities that started or ended on a day of the year (oldest first)
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct OnThisDay {
+    /// The entities that started (e.g. were born) on the day
+    pub started: Vec<Entity>,
+
+    /// The entities that ended (e.g. died) on the day
+    pub ended: Vec<Entity>,
+}
+
+impl OnThisDay {
+    /// Whether nothing started or ended on the day
+    pub fn is_empty(&self) -> bool {
+        self.started.is_empty() && self.ended.is_empty()
+    }
+
+    /// Fetch the entities that started or ended on the day of the year
+    pub async fn fetch(
+        transaction: &mut Transaction<'_, Sqlite>,
+        month: u8,
+        day: u8,
+    ) -> Result<Self, CrudError> {
+        let started_ids: Vec<OpenTimelineId> = sqlx::query_scalar(
+            r#"
+                SELECT id
+                FROM entities
+                WHERE start_month = ? AND start_day = ?
+                ORDER BY start_year
+            "#,
+        )
+        .bind(month)
+        .bind(day)
+        .fetch_all(&mut **transaction)
+        .await?;
+        let ended_ids: Vec<OpenTimelineId> = sqlx::query_scalar(
+            r#"
+                SELECT id
+                FROM entities
+                WHERE end_month = ? AND end_day = ?
+                ORDER BY end_year
+            "#,
+        )
+        .bind(month)
+        .bind(day)
+        .fetch_all(&mut **transaction)
+        .await?;
+
+        let mut on_this_day = Self::default();
+        for entity_id in started_ids {
+            on_this_day
+                .started
+                .push(Entity::fetch_by_id(transaction, &entity_id).await?);
+        }
+        for entity_id in ended_ids {
+            on_this_day
+                .ended
+                .push(Entity::fetch_by_id(transaction, &entity_id).await?);
+        }
+        Ok(on_this_day)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Create;
+    use open_timeline_core::{Date, HasIdAndName, Name};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn started_and_ended(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut ids = Vec::new();
+        for (name, start, end) in [
+            ("A", Date::from(Some(14), Some(7), 1900), None),
+            ("B", Date::from(Some(14), Some(7), 1800), None),
+            (
+                "C",
+                Date::from(Some(1), Some(1), 1700),
+                Some(Date::from(Some(14), Some(7), 1750).unwrap()),
+            ),
+            ("D", Date::from(None, Some(7), 1600), None),
+        ] {
+            let mut entity =
+                Entity::from(None, Name::from(name).unwrap(), start.unwrap(), end, None).unwrap();
+            entity.create(&mut transaction).await.unwrap();
+            ids.push(entity.id().unwrap());
+        }
+
+        let on_this_day = OnThisDay::fetch(&mut transaction, 7, 14).await.unwrap();
+        let started: Vec<_> = on_this_day
+            .started
+            .iter()
+            .map(|e| e.id().unwrap())
+            .collect();
+        let ended: Vec<_> = on_this_day.ended.iter().map(|e| e.id().unwrap()).collect();
+        assert_eq!(started, vec![ids[1], ids[0]]);
+        assert_eq!(ended, vec![ids[2]]);
+
+        assert!(
+            OnThisDay::fetch(&mut transaction, 7, 15)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Finding [`Entity`]s that overlap in time
+//!
+
+use crate::{CrudError, FetchById, Limit};
+use open_timeline_core::{DateRange, Entity, HasIdAndName, OpenTimelineId};
+use sqlx::{Sqlite, Transaction};
+use std::cmp::Reverse;
+
+/// Fetch the entities that exist at some point during the date range (ordered
+/// by start date)
+pub async fn entities_alive_during(
+    transaction: &mut Transaction<'_, Sqlite>,
+    date_range: &DateRange,
+) -> Result<Vec<Entity>, CrudError> {
+    // Narrow the entities down by year, then compare the full dates
+    let start_year = date_range.start().year().value();
+    let end_year = date_range.end_or_today().year().value();
+    let entity_ids = sqlx::query_scalar!(
+        r#"
+            SELECT id AS "id: OpenTimelineId"
+            FROM entities
+            WHERE start_year <= ?
+            AND (end_year IS NULL OR end_year >= ?)
+        "#,
+        end_year,
+        start_year
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+
+    let mut entities = Vec::new();
+    for entity_id in entity_ids {
+        let entity = Entity::fetch_by_id(transaction, &entity_id).await?;
+        if entity.date_range().overlaps(date_range) {
+            entities.push(entity);
+        }
+    }
+    entities.sort_by_key(|entity| entity.start());
+    Ok(entities)
+}
+
+/// Fetch the entities that exist at some point during the given entity's life
+/// (ordered by start date, and not including the given entity)
+pub async fn entities_overlapping(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+) -> Result<Vec<Entity>, CrudError> {
+    let entity = Entity::fetch_by_id(transaction, entity_id).await?;
+    let mut entities = entities_alive_during(transaction, &entity.date_range()).await?;
+    entities.retain(|other| other.id() != Some(*entity_id));
+    Ok(entities)
+}
+
+/// Fetch the given entity's contemporaries (the entities that overlap it the
+/// longest, longest first)
+pub async fn contemporaries_of(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+    Limit(limit): Limit,
+) -> Result<Vec<Entity>, CrudError> {
+    let date_range = Entity::fetch_by_id(transaction, entity_id)
+        .await?
+        .date_range();
+    let mut entities = entities_overlapping(transaction, entity_id).await?;
+    entities.sort_by_cached_key(|other| {
+        let overlap = other.date_range().overlap(&date_range);
+        Reverse(overlap.map_or(0, |overlap| overlap.whole_years()))
+    });
+    entities.truncate(limit as usize);
+    Ok(entities)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::seed_db_with_entities;
+    use open_timeline_core::Date;
+    use sqlx::Pool;
+
+    fn names(entities: &[Entity]) -> Vec<&str> {
+        entities
+            .iter()
+            .map(|entity| entity.name().as_str())
+            .collect()
+    }
+
+    fn range(start: i64, end: i64) -> DateRange {
+        DateRange::from(
+            Date::from(None, None, start).unwrap(),
+            Some(Date::from(None, None, end).unwrap()),
+        )
+        .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn alive_during(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        seed_db_with_entities(&mut transaction).await;
+
+        let entities = entities_alive_during(&mut transaction, &range(1953, 1960))
+            .await
+            .unwrap();
+        assert_eq!(names(&entities), vec!["Bob", "Alice"]);
+
+        let entities = entities_alive_during(&mut transaction, &range(1800, 1900))
+            .await
+            .unwrap();
+        assert!(entities.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn overlapping_and_contemporaries(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        seed_db_with_entities(&mut transaction).await;
+        let alice = OpenTimelineId::from("9db472a5-609d-4d70-b6c6-c4e91a7d9a4c").unwrap();
+
+        let entities = entities_overlapping(&mut transaction, &alice)
+            .await
+            .unwrap();
+        assert_eq!(names(&entities), vec!["Bob", "Dave"]);
+
+        // Dave overlaps Alice for 18 years, Bob for 4
+        let entities = contemporaries_of(&mut transaction, &alice, Limit(1))
+            .await
+            .unwrap();
+        assert_eq!(names(&entities), vec!["Dave"]);
+    }
+}
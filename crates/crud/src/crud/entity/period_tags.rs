@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Automatic period tags - typed tags derived from an entity's dates, so that
+//! entities can be searched for by period without tagging them by hand, e.g.
+//! `decade=1910` (the 1910s) & `century=20` (the 20th century).  An entity is
+//! given a tag for every decade & century it spans (ongoing entities run to
+//! this year).  They are maintained (if enabled in the
+//! [`DatabaseSettings`](crate::DatabaseSettings)) whenever an entity is saved,
+//! replacing any `decade` & `century` typed tags it was given by hand.
+//!
+
+use crate::{CrudError, fetch_database_settings, insert_entity_typed_tags};
+use bool_tag_expr::TagName;
+use open_timeline_core::{
+    DateRange, OpenTimelineId, TagNumber, TagValueType, TypedTag, TypedTagValue, TypedTags, Year,
+};
+use sqlx::{Sqlite, Transaction};
+
+/// The name of the typed tag holding the first year of a decade the entity spans
+pub const DECADE_TAG_NAME: &str = "decade";
+
+/// The name of the typed tag holding a century the entity spans
+pub const CENTURY_TAG_NAME: &str = "century";
+
+/// The most tags of each kind an entity is given.  Entities spanning more
+/// decades (or centuries) than this aren't given any tags of that kind (they
+/// wouldn't usefully slice anything).
+const MAX_PERIOD_TAGS: usize = 100;
+
+/// The century the year is in (e.g. 1900 to 1999 are the 20th century, & -100
+/// to -1 are the 1st century BC, i.e. `-1`)
+fn century(year: i32) -> i32 {
+    match year >= 0 {
+        true => year / 100 + 1,
+        false => -((-year - 1) / 100 + 1),
+    }
+}
+
+/// The period tags for an entity spanning the years (inclusive)
+pub fn period_tags(start_year: i32, end_year: i32) -> TypedTags {
+    let decades = (start_year.div_euclid(10)..=end_year.div_euclid(10)).map(|decade| decade * 10);
+    let centuries = century(start_year)..=century(end_year);
+    let mut typed_tags = TypedTags::new();
+    for (name, values) in [
+        (DECADE_TAG_NAME, decades.collect::<Vec<_>>()),
+        (CENTURY_TAG_NAME, centuries.collect()),
+    ] {
+        if values.len() > MAX_PERIOD_TAGS {
+            continue;
+        }
+        let name = TagName::from(&name).expect("period tag names are valid");
+        for value in values {
+            let number = TagNumber::try_from(f64::from(value)).expect("years are finite");
+            typed_tags.insert(TypedTag::from(name.clone(), TypedTagValue::Number(number)));
+        }
+    }
+    typed_tags
+}
+
+/// Replace the entity's period tags with those derived from its dates (if
+/// automatic period tags are enabled)
+pub(crate) async fn update_entity_period_tags(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+    date_range: &DateRange,
+) -> Result<(), CrudError> {
+    if !fetch_database_settings(transaction)
+        .await?
+        .automatic_period_tags
+    {
+        return Ok(());
+    }
+    let start_year = date_range.start().year().value();
+    let end_year = date_range.end_or_today().year().value();
+    replace_entity_period_tags(transaction, entity_id, start_year, end_year).await
+}
+
+/// Give every entity the period tags derived from its dates
+pub(crate) async fn update_all_entity_period_tags(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<(), CrudError> {
+    let entities: Vec<(OpenTimelineId, i32, Option<i32>)> =
+        sqlx::query_as("SELECT id, start_year, end_year FROM entities")
+            .fetch_all(&mut **transaction)
+            .await?;
+    for (entity_id, start_year, end_year) in entities {
+        let end_year = end_year.unwrap_or(Year::current().value());
+        replace_entity_period_tags(transaction, &entity_id, start_year, end_year).await?;
+    }
+    Ok(())
+}
+
+/// Replace the entity's period tags with those for the years
+async fn replace_entity_period_tags(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+    start_year: i32,
+    end_year: i32,
+) -> Result<(), CrudError> {
+    sqlx::query(
+        r#"
+            DELETE FROM entity_typed_tags
+            WHERE entity_id = ? AND value_type = ? AND name IN (?, ?)
+        "#,
+    )
+    .bind(entity_id)
+    .bind(TagValueType::Number)
+    .bind(DECADE_TAG_NAME)
+    .bind(CENTURY_TAG_NAME)
+    .execute(&mut **transaction)
+    .await?;
+    let typed_tags = period_tags(start_year, end_year);
+    insert_entity_typed_tags(transaction, entity_id, &typed_tags).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Create, DatabaseSettings, FetchById, Update, fetch_entity_typed_tags,
+        update_database_settings,
+    };
+    use open_timeline_core::{Date, Entity, HasIdAndName, Name};
+    use sqlx::Pool;
+
+    /// The values of the entity's typed tags with the name
+    fn values(typed_tags: &TypedTags, name: &str) -> Vec<f64> {
+        typed_tags
+            .iter()
+            .filter(|typed_tag| typed_tag.name.as_str() == name)
+            .filter_map(|typed_tag| match typed_tag.value {
+                TypedTagValue::Number(number) => Some(number.value()),
+                TypedTagValue::Date(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn centuries() {
+        assert_eq!(century(1900), 20);
+        assert_eq!(century(1999), 20);
+        assert_eq!(century(2000), 21);
+        assert_eq!(century(0), 1);
+        assert_eq!(century(-1), -1);
+        assert_eq!(century(-100), -1);
+        assert_eq!(century(-101), -2);
+    }
+
+    #[test]
+    fn tags_span_the_years() {
+        let typed_tags = period_tags(1914, 1921);
+        assert_eq!(values(&typed_tags, DECADE_TAG_NAME), [1910.0, 1920.0]);
+        assert_eq!(values(&typed_tags, CENTURY_TAG_NAME), [20.0]);
+
+        let typed_tags = period_tags(-44, -44);
+        assert_eq!(values(&typed_tags, DECADE_TAG_NAME), [-50.0]);
+        assert_eq!(values(&typed_tags, CENTURY_TAG_NAME), [-1.0]);
+
+        // Too many decades to be useful, but few enough centuries
+        let typed_tags = period_tags(0, 1500);
+        assert!(values(&typed_tags, DECADE_TAG_NAME).is_empty());
+        assert_eq!(values(&typed_tags, CENTURY_TAG_NAME).len(), 16);
+    }
+
+    #[sqlx::test]
+    async fn tags_follow_the_dates(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut entity = Entity::from(
+            None,
+            Name::from("Entity").unwrap(),
+            Date::from(None, None, 1914).unwrap(),
+            Some(Date::from(None, None, 1918).unwrap()),
+            None,
+        )
+        .unwrap();
+        entity.create(&mut transaction).await.unwrap();
+        let id = entity.id().unwrap();
+
+        // Not enabled by default
+        let typed_tags = fetch_entity_typed_tags(&mut transaction, &id)
+            .await
+            .unwrap();
+        assert!(typed_tags.is_empty());
+
+        // Enabling it tags the existing entities
+        let settings = DatabaseSettings {
+            automatic_period_tags: true,
+        };
+        update_database_settings(&mut transaction, &settings)
+            .await
+            .unwrap();
+        let typed_tags = fetch_entity_typed_tags(&mut transaction, &id)
+            .await
+            .unwrap();
+        assert_eq!(values(&typed_tags, DECADE_TAG_NAME), [1910.0]);
+        assert_eq!(values(&typed_tags, CENTURY_TAG_NAME), [20.0]);
+
+        // Changing the dates replaces them (even though the saved entity still
+        // has the old ones)
+        let mut entity = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+        entity
+            .set_end(Date::from(None, None, 2003).unwrap())
+            .unwrap();
+        entity.update(&mut transaction).await.unwrap();
+        let typed_tags = fetch_entity_typed_tags(&mut transaction, &id)
+            .await
+            .unwrap();
+        assert_eq!(values(&typed_tags, DECADE_TAG_NAME).len(), 10);
+        assert_eq!(values(&typed_tags, CENTURY_TAG_NAME), [20.0, 21.0]);
+    }
+}
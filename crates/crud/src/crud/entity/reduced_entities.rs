@@ -5,13 +5,13 @@
 //!
 
 use crate::{
-    CrudError, FetchAll, FetchAllWithTag, FetchByBoolTagExpr, FetchById, FetchByPartialName,
-    FetchByPartialNameAndBoolTagExpr, Limit,
+    BoolTagExprSql, CrudError, FetchAll, FetchAllWithTag, FetchByBoolTagExpr, FetchByPartialName,
+    FetchByPartialNameAndBoolTagExpr, Limit, Tagged,
 };
 use async_trait::async_trait;
-use bool_tag_expr::{BoolTagExpr, Tag};
+use bool_tag_expr::Tag;
 use open_timeline_core::{
-    IsReducedCollection, IsReducedType, Name, OpenTimelineId, ReducedEntities, ReducedEntity,
+    IsReducedType, Name, OpenTimelineId, ReducedEntities, ReducedEntity, TagQuery,
 };
 use sqlx::{Sqlite, Transaction};
 
@@ -75,44 +75,41 @@ impl FetchAllWithTag for ReducedEntities {
 
 #[async_trait]
 impl FetchByBoolTagExpr for ReducedEntities {
-    // TODO: given we're generating SQL, this probably needs better error checking
-    /// Fetch all entities that match a [`BoolTagExpr`]
-    async fn fetch_by_bool_tag_expr(
+    /// Fetch all entities that match a [`TagQuery`]
+    async fn fetch_by_tag_query(
         transaction: &mut Transaction<'_, Sqlite>,
         Limit(limit): Limit,
-        bool_expr: BoolTagExpr,
+        tag_query: &TagQuery,
     ) -> Result<Self, CrudError> {
-        let table_info =
-            bool_tag_expr::DbTableInfo::from("entity_tags", "entity_id", "name", "value").unwrap();
-
-        let bool_expr_sql = bool_expr.to_sql(&table_info);
-
+        let bool_expr_sql =
+            BoolTagExprSql::compile_query(tag_query, Tagged::Entities, "entities.id");
         let sql = format!(
             r#"
-                SELECT DISTINCT entity_id  AS "entity_id: OpenTimelineId"
-                FROM ({bool_expr_sql})
+                SELECT id, name
+                FROM entities
+                WHERE {}
                 LIMIT ?
-            "#
+            "#,
+            bool_expr_sql.condition()
         );
-
-        // TODO: does this work? Needs testing
-        let entity_ids: Vec<OpenTimelineId> = sqlx::query_scalar(&sql)
+        let mut query = sqlx::query_as::<_, (OpenTimelineId, Name)>(&sql);
+        for bind in bool_expr_sql.binds() {
+            query = query.bind(bind);
+        }
+        Ok(query
             .bind(limit)
             .fetch_all(&mut **transaction)
-            .await?;
-
-        let mut reduced_entities = ReducedEntities::new();
-        for entity_id in entity_ids {
-            let reduced_entity = ReducedEntity::fetch_by_id(transaction, &entity_id).await?;
-            reduced_entities.collection_mut().insert(reduced_entity);
-        }
-
-        Ok(reduced_entities)
+            .await?
+            .into_iter()
+            .map(|(id, name)| ReducedEntity::from_id_and_name(id, name))
+            .collect())
     }
 }
 
 #[async_trait]
 impl FetchByPartialName for ReducedEntities {
+    /// Fetch entities whose name or one of whose aliases matches the partial
+    /// name
     async fn fetch_by_partial_name(
         transaction: &mut Transaction<'_, Sqlite>,
         Limit(limit): Limit,
@@ -125,11 +122,18 @@ impl FetchByPartialName for ReducedEntities {
                     id AS "id: OpenTimelineId",
                     name AS "name: Name"
                 FROM entities
-                WHERE name LIKE CONCAT('%', ?, '%')
+                WHERE
+                    name LIKE CONCAT('%', ?, '%')
+                    OR id IN (
+                        SELECT entity_id
+                        FROM entity_aliases
+                        WHERE alias LIKE CONCAT('%', ?, '%')
+                    )
                 ORDER BY RANDOM()
                 LIMIT ?
             "#,
             partial_name,
+            partial_name,
             limit
         )
         .fetch_all(&mut **transaction)
@@ -140,25 +144,47 @@ impl FetchByPartialName for ReducedEntities {
     }
 }
 
-// TODO: do properly with JOIN(s)
 #[async_trait]
 impl FetchByPartialNameAndBoolTagExpr for ReducedEntities {
-    async fn fetch_by_partial_name_and_bool_tag_expr(
+    async fn fetch_by_partial_name_and_tag_query(
         transaction: &mut Transaction<'_, Sqlite>,
         Limit(limit): Limit,
         partial_name: &str,
-        bool_tag_expr: BoolTagExpr,
+        tag_query: &TagQuery,
     ) -> Result<Self, CrudError> {
-        let from_expr =
-            Self::fetch_by_bool_tag_expr(transaction, Limit(u32::MAX), bool_tag_expr).await?;
-        let from_name =
-            Self::fetch_by_partial_name(transaction, Limit(u32::MAX), partial_name).await?;
-        let both = from_expr
-            .collection()
-            .intersection(from_name.collection())
-            .take(limit as usize)
-            .cloned()
-            .collect();
-        Ok(both)
+        let bool_expr_sql =
+            BoolTagExprSql::compile_query(tag_query, Tagged::Entities, "entities.id");
+        let sql = format!(
+            r#"
+                SELECT id, name
+                FROM entities
+                WHERE
+                    (
+                        name LIKE CONCAT('%', ?, '%')
+                        OR id IN (
+                            SELECT entity_id
+                            FROM entity_aliases
+                            WHERE alias LIKE CONCAT('%', ?, '%')
+                        )
+                    )
+                    AND {}
+                ORDER BY RANDOM()
+                LIMIT ?
+            "#,
+            bool_expr_sql.condition()
+        );
+        let mut query = sqlx::query_as::<_, (OpenTimelineId, Name)>(&sql)
+            .bind(partial_name)
+            .bind(partial_name);
+        for bind in bool_expr_sql.binds() {
+            query = query.bind(bind);
+        }
+        Ok(query
+            .bind(limit)
+            .fetch_all(&mut **transaction)
+            .await?
+            .into_iter()
+            .map(|(id, name)| ReducedEntity::from_id_and_name(id, name))
+            .collect())
     }
 }
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! CRUD trait implementations for [`ReducedRenderEntity`]
+//!
+
+use crate::{CrudError, FetchById};
+use bool_tag_expr::{Tag, TagName, TagValue, Tags};
+use open_timeline_core::{Calendar, Date, Name, OpenTimelineId, ReducedRenderEntity};
+use sqlx::{Sqlite, Transaction};
+
+impl FetchById for ReducedRenderEntity {
+    /// Fetch only the columns needed to render the entity
+    async fn fetch_by_id(
+        transaction: &mut Transaction<'_, Sqlite>,
+        id: &OpenTimelineId,
+    ) -> Result<Self, CrudError> {
+        // Name & Dates
+        let record = sqlx::query!(
+            r#"
+                SELECT
+                    name AS "name: Name",
+                    start_year,
+                    start_month,
+                    start_day,
+                    end_year,
+                    end_month,
+                    end_day,
+                    start_calendar AS "start_calendar: Calendar",
+                    end_calendar AS "end_calendar: Calendar"
+                FROM entities
+                WHERE id=?
+            "#,
+            id
+        )
+        .fetch_optional(&mut **transaction)
+        .await?
+        .ok_or(CrudError::IdNotInDb)?;
+
+        let start = Date::from(record.start_day, record.start_month, record.start_year)?
+            .with_calendar(record.start_calendar);
+        let end = match record.end_year {
+            Some(end_year) => Some(
+                Date::from(record.end_day, record.end_month, end_year)?
+                    .with_calendar(record.end_calendar),
+            ),
+            None => None,
+        };
+
+        // Tags
+        let tags: Tags = sqlx::query!(
+            r#"
+                SELECT
+                    name AS "name: TagName",
+                    value AS "value: TagValue"
+                FROM entity_tags
+                WHERE entity_id=?
+            "#,
+            id
+        )
+        .fetch_all(&mut **transaction)
+        .await?
+        .into_iter()
+        .map(|row| Tag::from(row.name, row.value))
+        .collect();
+
+        Ok(ReducedRenderEntity::from(
+            *id,
+            record.name,
+            start,
+            end,
+            (!tags.is_empty()).then_some(tags),
+        ))
+    }
+}
@@ -4,7 +4,7 @@
 //! Proper searching
 //!
 
-use crate::{CrudError, FetchById, Limit};
+use crate::{BoolTagExprSql, CrudError, FetchById, Limit, Tagged};
 use bool_tag_expr::BoolTagExpr;
 use open_timeline_core::{
     Date, Entity, IsReducedCollection, Name, OpenTimelineId, ReducedEntities, ReducedEntity,
@@ -139,9 +139,11 @@ async fn fetch_entity_ids_using_search(
     let where_clauses = {
         let mut where_clauses = Vec::new();
 
-        // Partial name
+        // Partial name (or alias)
         if search.partial_name.is_some() {
-            where_clauses.push(String::from("name LIKE CONCAT('%', ?, '%')"));
+            where_clauses.push(String::from(
+                "(name LIKE CONCAT('%', ?1, '%') OR id IN (SELECT entity_id FROM entity_aliases WHERE alias LIKE CONCAT('%', ?1, '%')))",
+            ));
         }
 
         // Start
@@ -192,28 +194,31 @@ async fn fetch_entity_ids_using_search(
         "#
     );
 
-    let mut entity_ids: Vec<OpenTimelineId> = sqlx::query_scalar(&sql)
-        .fetch_all(&mut **transaction)
-        .await?;
+    let mut query = sqlx::query_scalar(&sql);
+    if let Some(partial_name) = search.partial_name.as_ref() {
+        query = query.bind(partial_name);
+    }
+    let mut entity_ids: Vec<OpenTimelineId> = query.fetch_all(&mut **transaction).await?;
 
     // Bool expr
     if let Some(bool_expr) = search.bool_expr.as_ref() {
-        let table_info =
-            bool_tag_expr::DbTableInfo::from("entity_tags", "entity_id", "name", "value").unwrap();
-
-        let bool_expr_sql = bool_expr.clone().to_sql(&table_info);
+        let bool_expr_sql = BoolTagExprSql::compile(bool_expr, Tagged::Entities, "entities.id");
 
         let sql = format!(
             r#"
-                SELECT DISTINCT entity_id  AS "entity_id: OpenTimelineId"
-                FROM ({bool_expr_sql})
+                SELECT id AS "id: OpenTimelineId"
+                FROM entities
+                WHERE {}
                 {limit_clause}
-            "#
+            "#,
+            bool_expr_sql.condition()
         );
 
-        let bool_expr_entity_ids: Vec<OpenTimelineId> = sqlx::query_scalar(&sql)
-            .fetch_all(&mut **transaction)
-            .await?;
+        let mut query = sqlx::query_scalar(&sql);
+        for bind in bool_expr_sql.binds() {
+            query = query.bind(bind);
+        }
+        let bool_expr_entity_ids: Vec<OpenTimelineId> = query.fetch_all(&mut **transaction).await?;
 
         entity_ids.extend(bool_expr_entity_ids);
     }
@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! All CRUD functionality for the sources cited for entities.  Sources are
+//! shared, so an entity citing a source already in the database links to it.
+//!
+
+use crate::CrudError;
+use open_timeline_core::{OpenTimelineId, Source, SourceScope, Sources};
+use sqlx::{Sqlite, Transaction};
+
+/// Fetch all sources cited for an entity
+pub async fn fetch_entity_sources(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+) -> Result<Sources, CrudError> {
+    sqlx::query!(
+        r#"
+            SELECT
+                sources.title,
+                sources.url,
+                sources.citation,
+                entity_sources.scope AS "scope: SourceScope"
+            FROM entity_sources
+            JOIN sources ON sources.id = entity_sources.source_id
+            WHERE entity_sources.entity_id=?
+        "#,
+        entity_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| {
+        Source::from(
+            &row.title,
+            row.url.as_deref(),
+            row.citation.as_deref(),
+            row.scope,
+        )
+        .map_err(CrudError::from)
+    })
+    .collect()
+}
+
+/// Insert an entity's sources into the database (reusing identical sources
+/// already in the database)
+pub(crate) async fn insert_entity_sources(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+    sources: &Sources,
+) -> Result<(), CrudError> {
+    for source in sources {
+        let source_id = source_id_or_insert(transaction, source).await?;
+        let scope = source.scope();
+        sqlx::query!(
+            r#"
+                INSERT OR IGNORE INTO entity_sources (entity_id, source_id, scope)
+                VALUES (?, ?, ?)
+            "#,
+            entity_id,
+            source_id,
+            scope
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Delete the links between an entity and its sources, and any sources that
+/// are no longer cited at all
+pub(crate) async fn delete_entity_sources(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM entity_sources
+            WHERE entity_id=?
+        "#,
+        entity_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+            DELETE FROM sources
+            WHERE id NOT IN (SELECT source_id FROM entity_sources)
+        "#
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Get the ID of an identical source in the database, inserting the source if
+/// there isn't one
+async fn source_id_or_insert(
+    transaction: &mut Transaction<'_, Sqlite>,
+    source: &Source,
+) -> Result<OpenTimelineId, CrudError> {
+    let title = source.title();
+    let url = source.url();
+    let citation = source.citation();
+
+    let existing = sqlx::query!(
+        r#"
+            SELECT id AS "id: OpenTimelineId"
+            FROM sources
+            WHERE title=? AND url IS ? AND citation IS ?
+        "#,
+        title,
+        url,
+        citation
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    if let Some(row) = existing {
+        return Ok(row.id);
+    }
+
+    let id = OpenTimelineId::new();
+    sqlx::query!(
+        r#"
+            INSERT INTO sources (id, title, url, citation)
+            VALUES (?, ?, ?, ?)
+        "#,
+        id,
+        title,
+        url,
+        citation
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(id)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, DatabaseRowCount, DeleteById, FetchById, Update};
+    use open_timeline_core::{Entity, HasIdAndName};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn shared_and_cleaned_up(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+
+        // Two entities citing the same source share it
+        let source = Source::from(
+            "A History",
+            Some("https://example.com"),
+            None,
+            SourceScope::Start,
+        )
+        .unwrap();
+        let mut entities = valid_entities();
+        let mut entity_1 = entities.pop().unwrap();
+        let mut entity_2 = entities.pop().unwrap();
+        entity_1.add_source(source.clone());
+        entity_2.add_source(source.clone());
+        entity_1.create(&mut transaction).await.unwrap();
+        entity_2.create(&mut transaction).await.unwrap();
+        let row_counts = DatabaseRowCount::all(&mut transaction).await.unwrap();
+        assert_eq!(row_counts.sources, 1);
+        assert_eq!(row_counts.entity_sources, 2);
+
+        // Sources are part of the fetched entity
+        let id_1 = entity_1.id().unwrap();
+        let fetched = Entity::fetch_by_id(&mut transaction, &id_1).await.unwrap();
+        assert_eq!(fetched.sources(), &Some(Sources::from([source])));
+
+        // The source is kept while it is still cited
+        entity_1.set_sources(Sources::new());
+        entity_1.update(&mut transaction).await.unwrap();
+        let row_counts = DatabaseRowCount::all(&mut transaction).await.unwrap();
+        assert_eq!(row_counts.sources, 1);
+        assert_eq!(row_counts.entity_sources, 1);
+
+        // And removed once it isn't
+        Entity::delete_by_id(&mut transaction, &entity_2.id().unwrap())
+            .await
+            .unwrap();
+        let row_counts = DatabaseRowCount::all(&mut transaction).await.unwrap();
+        assert_eq!(row_counts.sources, 0);
+        assert_eq!(row_counts.entity_sources, 0);
+    }
+}
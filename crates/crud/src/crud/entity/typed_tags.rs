@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! All CRUD functionality for entities' typed tags (tags whose values are
+//! numbers or dates)
+//!
+
+use crate::CrudError;
+use bool_tag_expr::TagName;
+use open_timeline_core::{
+    Calendar, Date, OpenTimelineId, TagNumber, TagValueType, TypedTag, TypedTagValue, TypedTags,
+};
+use sqlx::{Sqlite, Transaction};
+
+/// Fetch all of an entity's typed tags
+pub async fn fetch_entity_typed_tags(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+) -> Result<TypedTags, CrudError> {
+    sqlx::query!(
+        r#"
+            SELECT
+                name AS "name!: TagName",
+                value_type AS "value_type!: TagValueType",
+                number,
+                day,
+                month,
+                year,
+                calendar AS "calendar: Calendar"
+            FROM entity_typed_tags
+            WHERE entity_id=?
+        "#,
+        entity_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let value = match (row.value_type, row.number, row.year) {
+            (TagValueType::Number, Some(number), _) => TypedTagValue::Number(
+                TagNumber::try_from(number)
+                    .map_err(|error| CrudError::InvalidData(error.to_string()))?,
+            ),
+            (TagValueType::Date, _, Some(year)) => TypedTagValue::Date(
+                Date::from(row.day, row.month, year)?.with_calendar(row.calendar),
+            ),
+            _ => {
+                let error = format!("typed tag '{}' has no value", row.name);
+                return Err(CrudError::InvalidData(error));
+            }
+        };
+        Ok(TypedTag::from(row.name, value))
+    })
+    .collect()
+}
+
+/// Insert an entity's typed tags into the database
+pub(crate) async fn insert_entity_typed_tags(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+    typed_tags: &TypedTags,
+) -> Result<(), CrudError> {
+    for typed_tag in typed_tags {
+        let value_type = typed_tag.value.value_type();
+        let sort_key = typed_tag.value.sort_key();
+        let (number, date) = match typed_tag.value {
+            TypedTagValue::Number(number) => (Some(number.value()), None),
+            TypedTagValue::Date(date) => (None, Some(date)),
+        };
+        let day = date.and_then(|date| date.day());
+        let month = date.and_then(|date| date.month());
+        let year = date.map(|date| date.year());
+        let calendar = date.and_then(|date| date.calendar());
+        sqlx::query!(
+            r#"
+                INSERT OR IGNORE INTO entity_typed_tags
+                    (entity_id, name, value_type, number, day, month, year, calendar, sort_key)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            entity_id,
+            typed_tag.name,
+            value_type,
+            number,
+            day,
+            month,
+            year,
+            calendar,
+            sort_key
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Delete all of an entity's typed tags from the database
+pub(crate) async fn delete_entity_typed_tags(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM entity_typed_tags
+            WHERE entity_id=?
+        "#,
+        entity_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Starred and recently opened entities & timelines (for quick access)
+//!
+
+use crate::CrudError;
+use open_timeline_core::{Name, OpenTimelineId};
+use sqlx::{Sqlite, Transaction};
+use std::collections::BTreeSet;
+
+/// Whether a [`Favourite`] is an entity or a timeline
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[sqlx(rename_all = "lowercase")]
+pub enum FavouriteKind {
+    Entity,
+    Timeline,
+}
+
+/// A starred and/or recently opened entity or timeline
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Favourite {
+    id: OpenTimelineId,
+    name: Name,
+    kind: FavouriteKind,
+    starred: bool,
+}
+
+impl Favourite {
+    /// Get the entity's/timeline's ID
+    pub fn id(&self) -> OpenTimelineId {
+        self.id
+    }
+
+    /// Get the entity's/timeline's name
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// Get whether it is an entity or a timeline
+    pub fn kind(&self) -> FavouriteKind {
+        self.kind
+    }
+
+    /// Whether it has been starred
+    pub fn starred(&self) -> bool {
+        self.starred
+    }
+}
+
+/// Star (or unstar) an entity or timeline
+pub async fn set_starred(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: FavouriteKind,
+    id: &OpenTimelineId,
+    starred: bool,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            INSERT INTO favourites (item_id, kind, starred)
+            VALUES (?, ?, ?)
+            ON CONFLICT (item_id) DO UPDATE SET starred = excluded.starred
+        "#,
+        id,
+        kind,
+        starred
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Whether an entity or timeline has been starred
+pub async fn is_starred(
+    transaction: &mut Transaction<'_, Sqlite>,
+    id: &OpenTimelineId,
+) -> Result<bool, CrudError> {
+    Ok(sqlx::query!(
+        r#"
+            SELECT COUNT(*) AS count
+            FROM favourites
+            WHERE item_id=? AND starred
+        "#,
+        id
+    )
+    .fetch_one(&mut **transaction)
+    .await?
+    .count
+        > 0)
+}
+
+/// Fetch the IDs of all starred entities and timelines
+pub async fn fetch_starred_ids(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<BTreeSet<OpenTimelineId>, CrudError> {
+    Ok(sqlx::query!(
+        r#"
+            SELECT item_id AS "item_id: OpenTimelineId"
+            FROM favourites
+            WHERE starred
+        "#
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| row.item_id)
+    .collect())
+}
+
+/// Record that an entity or timeline has been opened (making it the most
+/// recently opened)
+pub async fn record_opened(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: FavouriteKind,
+    id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            INSERT INTO favourites (item_id, kind, opened_order)
+            VALUES (?, ?, (SELECT COALESCE(MAX(opened_order), 0) + 1 FROM favourites))
+            ON CONFLICT (item_id) DO UPDATE SET opened_order = excluded.opened_order
+        "#,
+        id,
+        kind
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Fetch all starred entities and timelines (ordered by name)
+pub async fn fetch_starred(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<Vec<Favourite>, CrudError> {
+    Ok(sqlx::query_as!(
+        Favourite,
+        r#"
+            SELECT
+                favourites.item_id AS "id!: OpenTimelineId",
+                COALESCE(entities.name, timelines.name) AS "name!: Name",
+                favourites.kind AS "kind!: FavouriteKind",
+                favourites.starred AS "starred!: bool"
+            FROM favourites
+            LEFT JOIN entities ON entities.id = favourites.item_id
+            LEFT JOIN timelines ON timelines.id = favourites.item_id
+            WHERE favourites.starred
+                AND COALESCE(entities.name, timelines.name) IS NOT NULL
+            ORDER BY COALESCE(entities.name, timelines.name)
+        "#
+    )
+    .fetch_all(&mut **transaction)
+    .await?)
+}
+
+/// Fetch the most recently opened entities and timelines (most recent first)
+pub async fn fetch_recently_opened(
+    transaction: &mut Transaction<'_, Sqlite>,
+    limit: u32,
+) -> Result<Vec<Favourite>, CrudError> {
+    Ok(sqlx::query_as!(
+        Favourite,
+        r#"
+            SELECT
+                favourites.item_id AS "id!: OpenTimelineId",
+                COALESCE(entities.name, timelines.name) AS "name!: Name",
+                favourites.kind AS "kind!: FavouriteKind",
+                favourites.starred AS "starred!: bool"
+            FROM favourites
+            LEFT JOIN entities ON entities.id = favourites.item_id
+            LEFT JOIN timelines ON timelines.id = favourites.item_id
+            WHERE favourites.opened_order IS NOT NULL
+                AND COALESCE(entities.name, timelines.name) IS NOT NULL
+            ORDER BY favourites.opened_order DESC
+            LIMIT ?
+        "#,
+        limit
+    )
+    .fetch_all(&mut **transaction)
+    .await?)
+}
+
+/// Delete an entity or timeline from the favourites (done when it is deleted)
+pub(crate) async fn delete_favourite(
+    transaction: &mut Transaction<'_, Sqlite>,
+    id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM favourites
+            WHERE item_id=?
+        "#,
+        id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, DeleteById};
+    use open_timeline_core::{Entity, HasIdAndName};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn starred_and_recently_opened(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut entities = valid_entities().into_iter();
+        let mut entity_1 = entities.next().unwrap();
+        let mut entity_2 = entities.next().unwrap();
+        entity_1.create(&mut transaction).await.unwrap();
+        entity_2.create(&mut transaction).await.unwrap();
+        let id_1 = entity_1.id().unwrap();
+        let id_2 = entity_2.id().unwrap();
+
+        // Starring
+        set_starred(&mut transaction, FavouriteKind::Entity, &id_1, true)
+            .await
+            .unwrap();
+        assert!(is_starred(&mut transaction, &id_1).await.unwrap());
+        assert!(!is_starred(&mut transaction, &id_2).await.unwrap());
+        let starred = fetch_starred(&mut transaction).await.unwrap();
+        assert_eq!(starred.len(), 1);
+        assert_eq!(starred[0].name(), entity_1.name());
+        assert_eq!(starred[0].kind(), FavouriteKind::Entity);
+
+        // Opening (most recent first, & opening doesn't unstar)
+        record_opened(&mut transaction, FavouriteKind::Entity, &id_1)
+            .await
+            .unwrap();
+        record_opened(&mut transaction, FavouriteKind::Entity, &id_2)
+            .await
+            .unwrap();
+        let recent = fetch_recently_opened(&mut transaction, 10).await.unwrap();
+        let recent_ids: Vec<_> = recent.iter().map(Favourite::id).collect();
+        assert_eq!(recent_ids, vec![id_2, id_1]);
+        assert!(recent[1].starred());
+        record_opened(&mut transaction, FavouriteKind::Entity, &id_1)
+            .await
+            .unwrap();
+        let recent = fetch_recently_opened(&mut transaction, 1).await.unwrap();
+        assert_eq!(recent[0].id(), id_1);
+
+        // Unstarring
+        set_starred(&mut transaction, FavouriteKind::Entity, &id_1, false)
+            .await
+            .unwrap();
+        assert!(
+            fetch_starred_ids(&mut transaction)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Deleting removes it
+        Entity::delete_by_id(&mut transaction, &id_1).await.unwrap();
+        let recent = fetch_recently_opened(&mut transaction, 10).await.unwrap();
+        assert_eq!(recent.len(), 1);
+    }
+}
@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Fetching what has been modified since a time (e.g. so that syncing clients
+//! & static site builds only fetch what has changed).  Times are unix times (in
+//! seconds), as recorded by the database.
+//!
+
+use crate::CrudError;
+use open_timeline_core::OpenTimelineId;
+use sqlx::{Sqlite, Transaction};
+use std::collections::BTreeSet;
+
+/// Get the database's current (unix) time.  Use this as the `since` of the next
+/// fetch of what has been modified, as it's the clock that modifications are
+/// recorded with.
+pub async fn database_time(transaction: &mut Transaction<'_, Sqlite>) -> Result<i64, CrudError> {
+    Ok(sqlx::query_scalar("SELECT unixepoch()")
+        .fetch_one(&mut **transaction)
+        .await?)
+}
+
+/// Fetch from the database the IDs of the timelines modified at or after the
+/// (unix) time.  A timeline is modified when it, its tags, its members (entities
+/// & subtimelines), or its story are changed (but not when its members' entities
+/// are).
+pub async fn fetch_timeline_ids_modified_since(
+    transaction: &mut Transaction<'_, Sqlite>,
+    since: i64,
+) -> Result<BTreeSet<OpenTimelineId>, CrudError> {
+    let timeline_ids: Vec<OpenTimelineId> = sqlx::query_scalar(
+        r#"
+            SELECT id
+            FROM timelines
+            WHERE updated_at >= ?
+        "#,
+    )
+    .bind(since)
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(timeline_ids.into_iter().collect())
+}
+
+/// Fetch from the database the IDs of the entities modified at or after the
+/// (unix) time.  An entity is modified when it, its tags, its aliases, or its
+/// sources are changed.
+pub async fn fetch_entity_ids_modified_since(
+    transaction: &mut Transaction<'_, Sqlite>,
+    since: i64,
+) -> Result<BTreeSet<OpenTimelineId>, CrudError> {
+    let entity_ids: Vec<OpenTimelineId> = sqlx::query_scalar(
+        r#"
+            SELECT id
+            FROM entities
+            WHERE updated_at >= ?
+        "#,
+    )
+    .bind(since)
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(entity_ids.into_iter().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{FetchById, Update, delete_timeline_entity, insert_timeline_entity};
+    use open_timeline_core::{Entity, HasIdAndName, Name};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn changes_are_tracked(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        seed_db(&mut transaction).await;
+
+        // Everything was just created
+        let now = database_time(&mut transaction).await.unwrap();
+        assert!(
+            !fetch_entity_ids_modified_since(&mut transaction, now)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Make sure the entity isn't a member of the timeline, then pretend
+        // nothing has been modified for a long time
+        let timeline_id = valid_timeline_no_subtimelines().id().unwrap();
+        let entity_id = valid_entity().id().unwrap();
+        delete_timeline_entity(&mut transaction, &timeline_id, &entity_id)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE entities SET updated_at = 0")
+            .execute(&mut *transaction)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE timelines SET updated_at = 0")
+            .execute(&mut *transaction)
+            .await
+            .unwrap();
+        assert!(
+            fetch_timeline_ids_modified_since(&mut transaction, 1)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Adding an entity to a timeline modifies the timeline (not the entity)
+        insert_timeline_entity(&mut transaction, &timeline_id, &entity_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            fetch_timeline_ids_modified_since(&mut transaction, 1)
+                .await
+                .unwrap(),
+            BTreeSet::from([timeline_id])
+        );
+        assert!(
+            fetch_entity_ids_modified_since(&mut transaction, 1)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Updating an entity modifies it
+        let mut entity = Entity::fetch_by_id(&mut transaction, &entity_id)
+            .await
+            .unwrap();
+        entity.set_name(Name::from("Renamed").unwrap());
+        entity.update(&mut transaction).await.unwrap();
+        assert_eq!(
+            fetch_entity_ids_modified_since(&mut transaction, 1)
+                .await
+                .unwrap(),
+            BTreeSet::from([entity_id])
+        );
+    }
+}
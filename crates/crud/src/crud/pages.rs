@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Fetching every entity or timeline a page at a time (e.g. so that syncing
+//! clients don't have to fetch everything at once).  Pages are ordered by ID &
+//! the cursor is the ID of the last item of the previous page, so items created
+//! while paging don't shift later pages.  Time-sortable IDs (see
+//! [`open_timeline_core::IdScheme`]) sort by when they were created, so with
+//! them new items are on the last page.
+//!
+
+use crate::{CrudError, Limit};
+use open_timeline_core::{IsReducedType, Name, OpenTimelineId, ReducedEntity, ReducedTimeline};
+use sqlx::{Sqlite, Transaction};
+
+/// Fetch the page of entities (ordered by ID) after the one with the given ID
+/// (or the first page)
+pub async fn fetch_entities_page(
+    transaction: &mut Transaction<'_, Sqlite>,
+    after: Option<&OpenTimelineId>,
+    limit: Limit,
+) -> Result<Vec<ReducedEntity>, CrudError> {
+    fetch_page(transaction, "entities", after, limit).await
+}
+
+/// Fetch the page of timelines (ordered by ID) after the one with the given ID
+/// (or the first page)
+pub async fn fetch_timelines_page(
+    transaction: &mut Transaction<'_, Sqlite>,
+    after: Option<&OpenTimelineId>,
+    limit: Limit,
+) -> Result<Vec<ReducedTimeline>, CrudError> {
+    fetch_page(transaction, "timelines", after, limit).await
+}
+
+/// Fetch the page of the table's rows (ordered by ID) after the one with the
+/// given ID
+async fn fetch_page<T: IsReducedType>(
+    transaction: &mut Transaction<'_, Sqlite>,
+    table: &'static str,
+    after: Option<&OpenTimelineId>,
+    Limit(limit): Limit,
+) -> Result<Vec<T>, CrudError> {
+    let sql = format!(
+        r#"
+            SELECT id, name
+            FROM {table}
+            WHERE ? IS NULL OR id > ?
+            ORDER BY id
+            LIMIT ?
+        "#
+    );
+    Ok(sqlx::query_as::<_, (OpenTimelineId, Name)>(&sql)
+        .bind(after)
+        .bind(after)
+        .bind(limit)
+        .fetch_all(&mut **transaction)
+        .await?
+        .into_iter()
+        .map(|(id, name)| T::from_id_and_name(id, name))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Create;
+    use open_timeline_core::{Date, Entity};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn pages_follow_time_sortable_ids(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+
+        // Create entities with time-sortable IDs (a few ms apart so they're in
+        // order)
+        let mut created = Vec::new();
+        for index in 0..5 {
+            let id = OpenTimelineId::new_time_sortable();
+            let mut entity = Entity::from(
+                Some(id),
+                Name::from(format!("Entity {index}")).unwrap(),
+                Date::from(None, None, 1900).unwrap(),
+                None,
+                None,
+            )
+            .unwrap();
+            entity.create(&mut transaction).await.unwrap();
+            created.push(id);
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        // Page through them 2 at a time
+        let mut paged = Vec::new();
+        let mut after = None;
+        loop {
+            let page = fetch_entities_page(&mut transaction, after.as_ref(), Limit(2))
+                .await
+                .unwrap();
+            assert!(page.len() <= 2);
+            let Some(last) = page.last() else {
+                break;
+            };
+            after = Some(last.id());
+            paged.extend(page.iter().map(|entity| entity.id()));
+        }
+        assert_eq!(paged, created);
+
+        // There are no timelines
+        assert!(
+            fetch_timelines_page(&mut transaction, None, Limit(2))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
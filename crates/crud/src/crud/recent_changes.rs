@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The most recently created & updated entities and timelines (e.g. to see what
+//! an import or merge changed).  Times are unix times (in seconds), as recorded
+//! by the database.
+//!
+
+use crate::CrudError;
+use open_timeline_core::{Name, OpenTimelineId};
+use sqlx::{Sqlite, Transaction};
+
+/// Whether a [`RecentChange`] is to an entity or a timeline
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[sqlx(rename_all = "lowercase")]
+pub enum RecentChangeKind {
+    Entity,
+    Timeline,
+}
+
+/// A recently created or updated entity or timeline
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecentChange {
+    id: OpenTimelineId,
+    name: Name,
+    kind: RecentChangeKind,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl RecentChange {
+    /// Get the entity's/timeline's ID
+    pub fn id(&self) -> OpenTimelineId {
+        self.id
+    }
+
+    /// Get the entity's/timeline's name
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// Get whether it is an entity or a timeline
+    pub fn kind(&self) -> RecentChangeKind {
+        self.kind
+    }
+
+    /// When it was created (unix time, in seconds)
+    pub fn created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    /// When it was last modified (unix time, in seconds)
+    pub fn updated_at(&self) -> i64 {
+        self.updated_at
+    }
+
+    /// Whether it hasn't been modified since it was created
+    pub fn is_new(&self) -> bool {
+        self.updated_at <= self.created_at
+    }
+}
+
+/// Fetch the most recently created or updated entities and timelines (most
+/// recent first)
+pub async fn fetch_recent_changes(
+    transaction: &mut Transaction<'_, Sqlite>,
+    limit: u32,
+) -> Result<Vec<RecentChange>, CrudError> {
+    let rows: Vec<(OpenTimelineId, Name, RecentChangeKind, i64, i64)> = sqlx::query_as(
+        r#"
+            SELECT id, name, 'entity' AS kind, created_at, updated_at
+            FROM entities
+            UNION ALL
+            SELECT id, name, 'timeline' AS kind, created_at, updated_at
+            FROM timelines
+            ORDER BY updated_at DESC, created_at DESC, name
+            LIMIT ?
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, kind, created_at, updated_at)| RecentChange {
+            id,
+            name,
+            kind,
+            created_at,
+            updated_at,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, Update};
+    use open_timeline_core::HasIdAndName;
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn most_recent_first(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut entities = valid_entities().into_iter();
+        let mut entity_1 = entities.next().unwrap();
+        let mut entity_2 = entities.next().unwrap();
+        entity_1.create(&mut transaction).await.unwrap();
+        entity_2.create(&mut transaction).await.unwrap();
+        let mut timeline = valid_timeline_no_subtimelines();
+        timeline.clear_entities();
+        timeline.create(&mut transaction).await.unwrap();
+
+        // Everything was just created
+        let changes = fetch_recent_changes(&mut transaction, 10).await.unwrap();
+        assert_eq!(changes.len(), 3);
+        assert!(changes.iter().all(|change| change.created_at() > 0));
+        assert!(changes.iter().all(RecentChange::is_new));
+
+        // Pretend they were created long ago, at different times
+        for (table, id, time) in [
+            ("entities", entity_1.id().unwrap(), 100),
+            ("entities", entity_2.id().unwrap(), 200),
+            ("timelines", timeline.id().unwrap(), 300),
+        ] {
+            let sql = format!("UPDATE {table} SET created_at = ?, updated_at = ? WHERE id = ?");
+            sqlx::query(&sql)
+                .bind(time)
+                .bind(time)
+                .bind(id)
+                .execute(&mut *transaction)
+                .await
+                .unwrap();
+        }
+        let changes = fetch_recent_changes(&mut transaction, 2).await.unwrap();
+        let ids: Vec<_> = changes.iter().map(RecentChange::id).collect();
+        assert_eq!(ids, [timeline.id().unwrap(), entity_2.id().unwrap()]);
+        assert_eq!(changes[0].kind(), RecentChangeKind::Timeline);
+
+        // Updating the first entity makes it the most recent change
+        entity_1.update(&mut transaction).await.unwrap();
+        let changes = fetch_recent_changes(&mut transaction, 1).await.unwrap();
+        assert_eq!(changes[0].id(), entity_1.id().unwrap());
+        assert_eq!(changes[0].kind(), RecentChangeKind::Entity);
+        assert_eq!(changes[0].created_at(), 100);
+        assert!(!changes[0].is_new());
+    }
+}
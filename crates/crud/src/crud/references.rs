@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! What references entities & timelines (e.g. so that the impact of deleting
+//! one can be shown before it's deleted)
+//!
+
+use crate::CrudError;
+use open_timeline_core::{IsReducedType, Name, OpenTimelineId, ReducedTimeline, ReducedTimelines};
+use sqlx::{Sqlite, Transaction};
+
+/// Fetch the timelines that the given entity is a direct member of (those that
+/// would lose it if it were deleted)
+pub async fn referencing_timelines(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+) -> Result<ReducedTimelines, CrudError> {
+    Ok(sqlx::query!(
+        r#"
+            SELECT DISTINCT
+                timelines.id AS "id: OpenTimelineId",
+                timelines.name AS "name: Name"
+            FROM timeline_entities
+            JOIN timelines ON timeline_entities.timeline_id = timelines.id
+            WHERE timeline_entities.entity_id=?
+        "#,
+        entity_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| ReducedTimeline::from_id_and_name(row.id, row.name))
+    .collect())
+}
+
+/// Fetch the timelines that have the given timeline as a direct subtimeline
+/// (those that would lose it if it were deleted)
+pub async fn referencing_parent_timelines(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+) -> Result<ReducedTimelines, CrudError> {
+    Ok(sqlx::query!(
+        r#"
+            SELECT DISTINCT
+                timelines.id AS "id: OpenTimelineId",
+                timelines.name AS "name: Name"
+            FROM subtimelines
+            JOIN timelines ON subtimelines.timeline_parent_id = timelines.id
+            WHERE subtimelines.timeline_child_id=?
+        "#,
+        timeline_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| ReducedTimeline::from_id_and_name(row.id, row.name))
+    .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use open_timeline_core::{HasIdAndName, IsReducedCollection};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn entity_and_subtimeline_references(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let timelines = seed_db_return_timelines(&mut transaction).await;
+
+        // Every timeline that has an entity references it
+        let timeline = timelines
+            .iter()
+            .find(|timeline| timeline.entities().is_some())
+            .unwrap();
+        let entity = timeline.entities().as_ref().unwrap().collection().first();
+        let entity_id = entity.unwrap().id();
+        let referencing = referencing_timelines(&mut transaction, &entity_id)
+            .await
+            .unwrap();
+        assert!(
+            referencing
+                .collection()
+                .iter()
+                .any(|referencing| referencing.id() == timeline.id().unwrap())
+        );
+
+        // Every timeline that has a subtimeline references it
+        let parent = timelines
+            .iter()
+            .find(|timeline| timeline.subtimelines().is_some())
+            .unwrap();
+        let child = parent.subtimelines().as_ref().unwrap().collection().first();
+        let child_id = child.unwrap().id();
+        let referencing = referencing_parent_timelines(&mut transaction, &child_id)
+            .await
+            .unwrap();
+        assert!(
+            referencing
+                .collection()
+                .iter()
+                .any(|referencing| referencing.id() == parent.id().unwrap())
+        );
+
+        // Nothing references a new ID
+        let referencing = referencing_timelines(&mut transaction, &OpenTimelineId::new())
+            .await
+            .unwrap();
+        assert!(referencing.collection().is_empty());
+    }
+}
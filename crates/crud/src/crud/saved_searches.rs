@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Named entity & timeline searches (for re-running with one click, and for
+//! telling when there are new matches)
+//!
+
+use crate::{
+    CrudError, FetchByPartialNameAndBoolTagExpr, Limit, fetch_entity_ids_modified_since,
+    fetch_timeline_ids_modified_since,
+};
+use open_timeline_core::{
+    IsReducedCollection, IsReducedType, Name, OpenTimelineId, ReducedEntities, ReducedTimelines,
+    TagQuery,
+};
+use sqlx::{Sqlite, Transaction};
+use std::collections::BTreeSet;
+
+/// Whether a [`SavedSearch`] is for entities or timelines
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[sqlx(rename_all = "lowercase")]
+pub enum SavedSearchKind {
+    Entity,
+    Timeline,
+}
+
+/// A named search by partial name and/or boolean tag expression
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SavedSearch {
+    name: Name,
+    kind: SavedSearchKind,
+    partial_name: Option<String>,
+    bool_tag_expr: Option<String>,
+    last_run_at: i64,
+}
+
+impl SavedSearch {
+    /// Create a new saved search (empty search terms aren't searched by)
+    pub fn new(
+        name: Name,
+        kind: SavedSearchKind,
+        partial_name: Option<String>,
+        bool_tag_expr: Option<String>,
+    ) -> Self {
+        let not_empty = |term: Option<String>| term.filter(|term| !term.trim().is_empty());
+        Self {
+            name,
+            kind,
+            partial_name: not_empty(partial_name),
+            bool_tag_expr: not_empty(bool_tag_expr),
+            last_run_at: 0,
+        }
+    }
+
+    /// Get the search's name
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// Get whether it searches entities or timelines
+    pub fn kind(&self) -> SavedSearchKind {
+        self.kind
+    }
+
+    /// Get the partial name searched by (if any)
+    pub fn partial_name(&self) -> Option<&str> {
+        self.partial_name.as_deref()
+    }
+
+    /// Get the boolean tag expression searched by (if any)
+    pub fn bool_tag_expr(&self) -> Option<&str> {
+        self.bool_tag_expr.as_deref()
+    }
+
+    /// Get when (unix time) the search was last run
+    pub fn last_run_at(&self) -> i64 {
+        self.last_run_at
+    }
+
+    /// Run the search
+    pub async fn run<T: FetchByPartialNameAndBoolTagExpr>(
+        &self,
+        transaction: &mut Transaction<'_, Sqlite>,
+        limit: Limit,
+    ) -> Result<T, CrudError> {
+        let tag_query = self
+            .bool_tag_expr
+            .as_ref()
+            .map(TagQuery::from)
+            .transpose()
+            .map_err(CrudError::TagQueryParse)?;
+        match (&self.partial_name, tag_query) {
+            (Some(partial_name), Some(tag_query)) => {
+                T::fetch_by_partial_name_and_tag_query(transaction, limit, partial_name, &tag_query)
+                    .await
+            }
+            (None, Some(tag_query)) => T::fetch_by_tag_query(transaction, limit, &tag_query).await,
+            (partial_name, None) => {
+                let partial_name = partial_name.as_deref().unwrap_or_default();
+                T::fetch_by_partial_name(transaction, limit, partial_name).await
+            }
+        }
+    }
+
+    /// Count the matches added (or changed) since the search was last run
+    pub async fn fetch_new_match_count(
+        &self,
+        transaction: &mut Transaction<'_, Sqlite>,
+    ) -> Result<usize, CrudError> {
+        // Anything modified in the second the search was run was seen by it
+        let since = self.last_run_at + 1;
+        let modified = match self.kind {
+            SavedSearchKind::Entity => fetch_entity_ids_modified_since(transaction, since).await?,
+            SavedSearchKind::Timeline => {
+                fetch_timeline_ids_modified_since(transaction, since).await?
+            }
+        };
+        if modified.is_empty() {
+            return Ok(0);
+        }
+        let matches: BTreeSet<OpenTimelineId> = match self.kind {
+            SavedSearchKind::Entity => ids(self
+                .run::<ReducedEntities>(transaction, Limit(u32::MAX))
+                .await?),
+            SavedSearchKind::Timeline => ids(self
+                .run::<ReducedTimelines>(transaction, Limit(u32::MAX))
+                .await?),
+        };
+        Ok(matches.intersection(&modified).count())
+    }
+}
+
+/// The IDs in a reduced collection
+fn ids<T: IsReducedCollection>(collection: T) -> BTreeSet<OpenTimelineId>
+where
+    <T as IsReducedCollection>::Item: IsReducedType,
+{
+    collection
+        .collection()
+        .iter()
+        .map(|item| item.id())
+        .collect()
+}
+
+/// Save a search (replacing any of the same kind with the same name).  Saving
+/// counts as running it.
+pub async fn save_search(
+    transaction: &mut Transaction<'_, Sqlite>,
+    saved_search: &SavedSearch,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            INSERT INTO saved_searches (name, kind, partial_name, bool_tag_expr)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (kind, name) DO UPDATE SET
+                partial_name = excluded.partial_name,
+                bool_tag_expr = excluded.bool_tag_expr,
+                last_run_at = excluded.last_run_at
+        "#,
+        saved_search.name,
+        saved_search.kind,
+        saved_search.partial_name,
+        saved_search.bool_tag_expr
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Fetch all saved searches (ordered by name)
+pub async fn fetch_saved_searches(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<Vec<SavedSearch>, CrudError> {
+    Ok(sqlx::query_as!(
+        SavedSearch,
+        r#"
+            SELECT
+                name AS "name!: Name",
+                kind AS "kind!: SavedSearchKind",
+                partial_name,
+                bool_tag_expr,
+                last_run_at
+            FROM saved_searches
+            ORDER BY name, kind
+        "#
+    )
+    .fetch_all(&mut **transaction)
+    .await?)
+}
+
+/// Record that a saved search has been run (so that only matches added after
+/// now are new)
+pub async fn record_saved_search_run(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SavedSearchKind,
+    name: &Name,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            UPDATE saved_searches
+            SET last_run_at = unixepoch()
+            WHERE kind=? AND name=?
+        "#,
+        kind,
+        name
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Delete a saved search
+pub async fn delete_saved_search(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SavedSearchKind,
+    name: &Name,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM saved_searches
+            WHERE kind=? AND name=?
+        "#,
+        kind,
+        name
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, Update};
+    use open_timeline_core::HasIdAndName;
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn saved_searches(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut entities = valid_entities().into_iter();
+        let mut entity_1 = entities.next().unwrap();
+        let mut entity_2 = entities.next().unwrap();
+        entity_1.create(&mut transaction).await.unwrap();
+        entity_2.create(&mut transaction).await.unwrap();
+
+        // Saving
+        let partial_name = entity_1.name().as_str().to_string();
+        let name = Name::from("Mine").unwrap();
+        let search = SavedSearch::new(
+            name.clone(),
+            SavedSearchKind::Entity,
+            Some(partial_name),
+            Some(String::from("  ")),
+        );
+        save_search(&mut transaction, &search).await.unwrap();
+        let saved = fetch_saved_searches(&mut transaction).await.unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].name(), &name);
+        assert_eq!(saved[0].bool_tag_expr(), None);
+
+        // Running
+        let results: ReducedEntities = saved[0].run(&mut transaction, Limit(10)).await.unwrap();
+        assert!(
+            results
+                .collection()
+                .iter()
+                .any(|entity| entity.id() == entity_1.id().unwrap())
+        );
+
+        // Nothing has changed since it was saved
+        assert_eq!(
+            saved[0]
+                .fetch_new_match_count(&mut transaction)
+                .await
+                .unwrap(),
+            0
+        );
+
+        // Changes since it was last run are new matches (only if they match)
+        sqlx::query("UPDATE saved_searches SET last_run_at = last_run_at - 10")
+            .execute(&mut *transaction)
+            .await
+            .unwrap();
+        let saved = fetch_saved_searches(&mut transaction).await.unwrap();
+        entity_1.update(&mut transaction).await.unwrap();
+        entity_2.update(&mut transaction).await.unwrap();
+        assert_eq!(
+            saved[0]
+                .fetch_new_match_count(&mut transaction)
+                .await
+                .unwrap(),
+            1
+        );
+        record_saved_search_run(&mut transaction, SavedSearchKind::Entity, &name)
+            .await
+            .unwrap();
+        let saved = fetch_saved_searches(&mut transaction).await.unwrap();
+        assert_eq!(
+            saved[0]
+                .fetch_new_match_count(&mut transaction)
+                .await
+                .unwrap(),
+            0
+        );
+
+        // Deleting
+        delete_saved_search(&mut transaction, SavedSearchKind::Entity, &name)
+            .await
+            .unwrap();
+        assert!(
+            fetch_saved_searches(&mut transaction)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
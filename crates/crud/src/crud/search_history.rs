@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Recently used & pinned search queries (for suggesting as they are typed)
+//!
+
+use crate::CrudError;
+use sqlx::{Sqlite, Transaction};
+
+/// The number of unpinned queries of each kind that are kept
+pub const SEARCH_HISTORY_LENGTH: u32 = 50;
+
+/// What a [`SearchQuery`] searches by
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[sqlx(rename_all = "snake_case")]
+pub enum SearchQueryKind {
+    Name,
+    BoolTagExpr,
+}
+
+/// A recently used and/or pinned search query
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SearchQuery {
+    kind: SearchQueryKind,
+    query: String,
+    pinned: bool,
+}
+
+impl SearchQuery {
+    /// Get what the query searches by
+    pub fn kind(&self) -> SearchQueryKind {
+        self.kind
+    }
+
+    /// Get the query
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Whether it has been pinned
+    pub fn pinned(&self) -> bool {
+        self.pinned
+    }
+}
+
+/// Record that a query has been used (making it the most recent), forgetting
+/// the oldest unpinned queries beyond [`SEARCH_HISTORY_LENGTH`]
+pub async fn record_search_query(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SearchQueryKind,
+    query: &str,
+) -> Result<(), CrudError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(());
+    }
+    sqlx::query!(
+        r#"
+            INSERT INTO search_history (kind, query, used_order)
+            VALUES (?, ?, (SELECT COALESCE(MAX(used_order), 0) + 1 FROM search_history))
+            ON CONFLICT (kind, query) DO UPDATE SET used_order = excluded.used_order
+        "#,
+        kind,
+        query
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+            DELETE FROM search_history
+            WHERE kind=? AND NOT pinned AND used_order NOT IN (
+                SELECT used_order
+                FROM search_history
+                WHERE kind=? AND NOT pinned
+                ORDER BY used_order DESC
+                LIMIT ?
+            )
+        "#,
+        kind,
+        kind,
+        SEARCH_HISTORY_LENGTH
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Pin (or unpin) a query (pinned queries are suggested first, and are never
+/// forgotten)
+pub async fn set_search_query_pinned(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SearchQueryKind,
+    query: &str,
+    pinned: bool,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            UPDATE search_history
+            SET pinned=?
+            WHERE kind=? AND query=?
+        "#,
+        pinned,
+        kind,
+        query
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Fetch the search history (pinned queries first, then the most recently
+/// used)
+pub async fn fetch_search_history(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<Vec<SearchQuery>, CrudError> {
+    Ok(sqlx::query_as!(
+        SearchQuery,
+        r#"
+            SELECT
+                kind AS "kind!: SearchQueryKind",
+                query,
+                pinned AS "pinned!: bool"
+            FROM search_history
+            ORDER BY pinned DESC, used_order DESC
+        "#
+    )
+    .fetch_all(&mut **transaction)
+    .await?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sqlx::Pool;
+
+    fn queries(history: &[SearchQuery]) -> Vec<&str> {
+        history.iter().map(SearchQuery::query).collect()
+    }
+
+    #[sqlx::test]
+    async fn search_history(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+
+        // Most recent first (& blank queries aren't recorded)
+        for query in ["ada", " ", "british & scientist", "bob", "ada"] {
+            let kind = match query.contains('&') {
+                true => SearchQueryKind::BoolTagExpr,
+                false => SearchQueryKind::Name,
+            };
+            record_search_query(&mut transaction, kind, query)
+                .await
+                .unwrap();
+        }
+        let history = fetch_search_history(&mut transaction).await.unwrap();
+        assert_eq!(queries(&history), vec!["ada", "bob", "british & scientist"]);
+        assert_eq!(history[2].kind(), SearchQueryKind::BoolTagExpr);
+
+        // Pinned first
+        set_search_query_pinned(&mut transaction, SearchQueryKind::Name, "bob", true)
+            .await
+            .unwrap();
+        let history = fetch_search_history(&mut transaction).await.unwrap();
+        assert_eq!(queries(&history), vec!["bob", "ada", "british & scientist"]);
+        assert!(history[0].pinned());
+
+        // Only the most recent unpinned queries are kept
+        for index in 0..SEARCH_HISTORY_LENGTH {
+            record_search_query(&mut transaction, SearchQueryKind::Name, &format!("{index}"))
+                .await
+                .unwrap();
+        }
+        let history = fetch_search_history(&mut transaction).await.unwrap();
+        let names: Vec<&str> = history
+            .iter()
+            .filter(|query| query.kind() == SearchQueryKind::Name)
+            .map(SearchQuery::query)
+            .collect();
+        assert_eq!(names.len(), SEARCH_HISTORY_LENGTH as usize + 1);
+        assert_eq!(names[0], "bob");
+        assert!(!names.contains(&"ada"));
+        assert!(queries(&history).contains(&"british & scientist"));
+    }
+}
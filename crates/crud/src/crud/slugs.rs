@@ -0,0 +1,381 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Slugs (URL-safe identifiers used in permalinks) for entities & timelines,
+//! and redirects from the slugs they used to have
+//!
+
+use crate::CrudError;
+use open_timeline_core::{Name, OpenTimelineId, Slug};
+use sqlx::{Row, Sqlite, Transaction};
+
+/// Whether a slug belongs to an entity or a timeline (entities & timelines can
+/// have the same slug)
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[sqlx(rename_all = "lowercase")]
+pub enum SlugKind {
+    Entity,
+    Timeline,
+}
+
+impl SlugKind {
+    /// The name of the table holding the slugs
+    fn table(&self) -> &'static str {
+        match self {
+            SlugKind::Entity => "entities",
+            SlugKind::Timeline => "timelines",
+        }
+    }
+}
+
+/// What a slug resolves to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SlugLookup {
+    /// The slug is the current slug of the entity/timeline with this ID
+    Current(OpenTimelineId),
+
+    /// The slug used to belong to the entity/timeline with this ID, which now
+    /// has the slug held
+    Redirect(OpenTimelineId, Slug),
+}
+
+/// Fetch the slug of an entity or timeline (none if it hasn't been given one
+/// yet)
+pub async fn fetch_slug(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SlugKind,
+    id: &OpenTimelineId,
+) -> Result<Option<Slug>, CrudError> {
+    let table = kind.table();
+    let row = sqlx::query(&format!("SELECT slug FROM {table} WHERE id = ?"))
+        .bind(id)
+        .fetch_optional(&mut **transaction)
+        .await?
+        .ok_or(CrudError::IdNotInDb)?;
+    Ok(row.get("slug"))
+}
+
+/// Find the entity or timeline a slug belongs to (or used to belong to)
+pub async fn id_from_slug(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SlugKind,
+    slug: &Slug,
+) -> Result<SlugLookup, CrudError> {
+    let table = kind.table();
+    let current: Option<OpenTimelineId> =
+        sqlx::query_scalar(&format!("SELECT id FROM {table} WHERE slug = ?"))
+            .bind(slug)
+            .fetch_optional(&mut **transaction)
+            .await?;
+    if let Some(id) = current {
+        return Ok(SlugLookup::Current(id));
+    }
+
+    let redirect = sqlx::query(&format!(
+        r#"
+            SELECT {table}.id AS id, {table}.slug AS slug
+            FROM slug_redirects
+            JOIN {table} ON {table}.id = slug_redirects.item_id
+            WHERE slug_redirects.kind = ? AND slug_redirects.slug = ?
+        "#
+    ))
+    .bind(kind)
+    .bind(slug)
+    .fetch_optional(&mut **transaction)
+    .await?;
+    match redirect {
+        Some(row) => match row.get::<Option<Slug>, _>("slug") {
+            Some(current_slug) => Ok(SlugLookup::Redirect(row.get("id"), current_slug)),
+            None => Ok(SlugLookup::Current(row.get("id"))),
+        },
+        None => Err(CrudError::NotInDb),
+    }
+}
+
+/// Set the slug of an entity or timeline.  The slug it had (if any) redirects
+/// to the new one.
+pub async fn set_slug(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SlugKind,
+    id: &OpenTimelineId,
+    slug: &Slug,
+) -> Result<(), CrudError> {
+    let old_slug = fetch_slug(transaction, kind, id).await?;
+    if old_slug.as_ref() == Some(slug) {
+        return Ok(());
+    }
+    if !is_slug_available(transaction, kind, id, slug).await? {
+        return Err(CrudError::SlugAlreadyInUse(slug.clone()));
+    }
+
+    // It may be reclaiming a slug it used to have
+    sqlx::query!(
+        r#"
+            DELETE FROM slug_redirects
+            WHERE kind = ? AND slug = ?
+        "#,
+        kind,
+        slug
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    let table = kind.table();
+    sqlx::query(&format!("UPDATE {table} SET slug = ? WHERE id = ?"))
+        .bind(slug)
+        .bind(id)
+        .execute(&mut **transaction)
+        .await?;
+
+    if let Some(old_slug) = old_slug {
+        sqlx::query!(
+            r#"
+                INSERT INTO slug_redirects (slug, kind, item_id)
+                VALUES (?, ?, ?)
+                ON CONFLICT (kind, slug) DO UPDATE SET item_id = excluded.item_id
+            "#,
+            old_slug,
+            kind,
+            id
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Give an entity or timeline a unique slug derived from its name (used when
+/// it is created or renamed)
+pub async fn generate_slug(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SlugKind,
+    id: &OpenTimelineId,
+    name: &Name,
+) -> Result<Slug, CrudError> {
+    let base = Slug::from_name(name);
+    let mut slug = base.clone();
+    let mut suffix = 1;
+    while !is_slug_available(transaction, kind, id, &slug).await? {
+        suffix += 1;
+        slug = base.with_suffix(suffix);
+    }
+    set_slug(transaction, kind, id, &slug).await?;
+    Ok(slug)
+}
+
+/// Give every entity & timeline that doesn't yet have a slug one (e.g. those
+/// created before slugs were)
+pub async fn generate_missing_slugs(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<(), CrudError> {
+    for kind in [SlugKind::Entity, SlugKind::Timeline] {
+        let table = kind.table();
+        let rows = sqlx::query(&format!("SELECT id, name FROM {table} WHERE slug IS NULL"))
+            .fetch_all(&mut **transaction)
+            .await?;
+        for row in rows {
+            let id: OpenTimelineId = row.get("id");
+            let name: Name = row.get("name");
+            generate_slug(transaction, kind, &id, &name).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete the redirects to an entity or timeline (done when it is deleted)
+pub(crate) async fn delete_slug_redirects(
+    transaction: &mut Transaction<'_, Sqlite>,
+    id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM slug_redirects
+            WHERE item_id=?
+        "#,
+        id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Whether a slug is free for an entity or timeline to use (i.e. no other one
+/// has, or used to have, it)
+async fn is_slug_available(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SlugKind,
+    id: &OpenTimelineId,
+    slug: &Slug,
+) -> Result<bool, CrudError> {
+    let table = kind.table();
+    let in_use: i64 = sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM {table} WHERE slug = ? AND id != ?"
+    ))
+    .bind(slug)
+    .bind(id)
+    .fetch_one(&mut **transaction)
+    .await?;
+    let redirected = sqlx::query_scalar!(
+        r#"
+            SELECT COUNT(*)
+            FROM slug_redirects
+            WHERE kind = ? AND slug = ? AND item_id != ?
+        "#,
+        kind,
+        slug,
+        id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+    Ok(in_use == 0 && redirected == 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, DeleteById, FetchById, Update};
+    use open_timeline_core::{Entity, HasIdAndName, TimelineEdit};
+    use sqlx::Pool;
+
+    fn slug(slug: &str) -> Slug {
+        Slug::from(slug).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn generated_on_create_and_unique(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut entity = valid_entity();
+        entity.create(&mut transaction).await.unwrap();
+        let id = entity.id().unwrap();
+        let expected = Slug::from_name(entity.name());
+        assert_eq!(
+            fetch_slug(&mut transaction, SlugKind::Entity, &id)
+                .await
+                .unwrap(),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            id_from_slug(&mut transaction, SlugKind::Entity, &expected)
+                .await
+                .unwrap(),
+            SlugLookup::Current(id)
+        );
+
+        // Another entity whose name has the same slug gets a suffix
+        let mut other = valid_entities().pop().unwrap();
+        other.set_name(Name::from(format!("{}!", entity.name())).unwrap());
+        other.create(&mut transaction).await.unwrap();
+        assert_eq!(
+            fetch_slug(&mut transaction, SlugKind::Entity, &other.id().unwrap())
+                .await
+                .unwrap(),
+            Some(expected.with_suffix(2))
+        );
+
+        // A timeline can have the same slug as an entity
+        let mut timeline = valid_timeline_no_subtimelines();
+        timeline.clear_entities();
+        timeline.set_name(entity.name().clone());
+        timeline.create(&mut transaction).await.unwrap();
+        assert_eq!(
+            fetch_slug(
+                &mut transaction,
+                SlugKind::Timeline,
+                &timeline.id().unwrap()
+            )
+            .await
+            .unwrap(),
+            Some(expected)
+        );
+    }
+
+    #[sqlx::test]
+    async fn renaming_redirects_old_slug(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut entity = valid_entity();
+        entity.create(&mut transaction).await.unwrap();
+        let id = entity.id().unwrap();
+        let old_slug = Slug::from_name(entity.name());
+
+        // Rename
+        let mut entity = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+        entity.set_name(Name::from("A Completely New Name").unwrap());
+        entity.update(&mut transaction).await.unwrap();
+        let new_slug = slug("a-completely-new-name");
+        assert_eq!(
+            fetch_slug(&mut transaction, SlugKind::Entity, &id)
+                .await
+                .unwrap(),
+            Some(new_slug.clone())
+        );
+        assert_eq!(
+            id_from_slug(&mut transaction, SlugKind::Entity, &old_slug)
+                .await
+                .unwrap(),
+            SlugLookup::Redirect(id, new_slug.clone())
+        );
+
+        // The old slug can't be taken by another entity
+        let mut other = valid_entities().pop().unwrap();
+        other.create(&mut transaction).await.unwrap();
+        assert_eq!(
+            set_slug(
+                &mut transaction,
+                SlugKind::Entity,
+                &other.id().unwrap(),
+                &old_slug
+            )
+            .await,
+            Err(CrudError::SlugAlreadyInUse(old_slug.clone()))
+        );
+
+        // Editing the slug back reclaims it (& redirects the newer one)
+        set_slug(&mut transaction, SlugKind::Entity, &id, &old_slug)
+            .await
+            .unwrap();
+        assert_eq!(
+            id_from_slug(&mut transaction, SlugKind::Entity, &old_slug)
+                .await
+                .unwrap(),
+            SlugLookup::Current(id)
+        );
+        assert_eq!(
+            id_from_slug(&mut transaction, SlugKind::Entity, &new_slug)
+                .await
+                .unwrap(),
+            SlugLookup::Redirect(id, old_slug)
+        );
+
+        // Deleting removes the redirects
+        Entity::delete_by_id(&mut transaction, &id).await.unwrap();
+        assert_eq!(
+            id_from_slug(&mut transaction, SlugKind::Entity, &new_slug).await,
+            Err(CrudError::NotInDb)
+        );
+    }
+
+    #[sqlx::test]
+    async fn missing_slugs_generated(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        seed_db(&mut transaction).await;
+        sqlx::query("UPDATE timelines SET slug = NULL")
+            .execute(&mut *transaction)
+            .await
+            .unwrap();
+        generate_missing_slugs(&mut transaction).await.unwrap();
+        for timeline in valid_timelines() {
+            let fetched = TimelineEdit::fetch_by_id(&mut transaction, &timeline.id().unwrap())
+                .await
+                .unwrap();
+            let slug = fetch_slug(
+                &mut transaction,
+                SlugKind::Timeline,
+                &timeline.id().unwrap(),
+            )
+            .await
+            .unwrap();
+            assert!(slug.is_some(), "{}", fetched.name());
+        }
+    }
+}
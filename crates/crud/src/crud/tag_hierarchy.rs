@@ -0,0 +1,364 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Hierarchical tags (see [`TagHierarchy`]).  Each tag's parent is held in the
+//! `tag_parents` table, & the `tag_ancestors` view holds every tag's ancestors
+//! (so that a tag in a boolean tag expression also matches its descendants).
+//!
+
+use crate::{CrudError, TagCounts};
+use bool_tag_expr::{Tag, TagName, TagValue};
+use open_timeline_core::TagHierarchy;
+use sqlx::{Row, Sqlite, Transaction};
+use std::collections::BTreeMap;
+
+/// Fetch the parents of all tags that have one
+pub async fn fetch_tag_hierarchy(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<TagHierarchy, CrudError> {
+    sqlx::query("SELECT name, value, parent_value FROM tag_parents")
+        .fetch_all(&mut **transaction)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let name: Option<String> = row.try_get("name")?;
+            let value: String = row.try_get("value")?;
+            let parent_value: String = row.try_get("parent_value")?;
+            let invalid = |error: String| CrudError::InvalidData(error);
+            let name = name
+                .map(|name| TagName::from(&name).map_err(|e| invalid(e.to_string())))
+                .transpose()?;
+            let value = TagValue::from(&value).map_err(|e| invalid(e.to_string()))?;
+            let parent_value = TagValue::from(&parent_value).map_err(|e| invalid(e.to_string()))?;
+            Ok((Tag::from(name, value), parent_value))
+        })
+        .collect()
+}
+
+/// Set (or remove) the tag's parent, which has the same name as the tag.  The
+/// parent can't be the tag or one of its descendants.
+pub async fn set_tag_parent(
+    transaction: &mut Transaction<'_, Sqlite>,
+    tag: &Tag,
+    parent: Option<&TagValue>,
+) -> Result<(), CrudError> {
+    if let Some(parent) = parent {
+        fetch_tag_hierarchy(transaction)
+            .await?
+            .set_parent(tag.clone(), parent.clone())?;
+    }
+    sqlx::query("DELETE FROM tag_parents WHERE name IS ? AND value = ?")
+        .bind(tag.name.as_ref().map(TagName::as_str))
+        .bind(tag.value.as_str())
+        .execute(&mut **transaction)
+        .await?;
+    if let Some(parent) = parent {
+        sqlx::query("INSERT INTO tag_parents (name, value, parent_value) VALUES (?, ?, ?)")
+            .bind(tag.name.as_ref().map(TagName::as_str))
+            .bind(tag.value.as_str())
+            .bind(parent.as_str())
+            .execute(&mut **transaction)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Keep the hierarchy when a tag is renamed.  The tag keeps its parent &
+/// children if its name is unchanged, but a tag's parent has the same name as
+/// it, so they are removed if the name changes.
+pub(crate) async fn rename_tag_in_hierarchy(
+    transaction: &mut Transaction<'_, Sqlite>,
+    old_tag: &Tag,
+    new_tag: &Tag,
+) -> Result<(), CrudError> {
+    let name = old_tag.name.as_ref().map(TagName::as_str);
+    if old_tag.name != new_tag.name {
+        return remove_tag_from_hierarchy(transaction, old_tag).await;
+    }
+
+    // The tag can't become its own parent (nor have two)
+    sqlx::query(
+        r#"
+            DELETE FROM tag_parents
+            WHERE name IS ?
+                AND ((value = ? AND parent_value = ?) OR value = ?)
+        "#,
+    )
+    .bind(name)
+    .bind(old_tag.value.as_str())
+    .bind(new_tag.value.as_str())
+    .bind(new_tag.value.as_str())
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query("UPDATE tag_parents SET value = ? WHERE name IS ? AND value = ?")
+        .bind(new_tag.value.as_str())
+        .bind(name)
+        .bind(old_tag.value.as_str())
+        .execute(&mut **transaction)
+        .await?;
+    sqlx::query("UPDATE tag_parents SET parent_value = ? WHERE name IS ? AND parent_value = ?")
+        .bind(new_tag.value.as_str())
+        .bind(name)
+        .bind(old_tag.value.as_str())
+        .execute(&mut **transaction)
+        .await?;
+    Ok(())
+}
+
+/// Remove the tag from the hierarchy (its children are given its parent, if it
+/// has one)
+pub(crate) async fn remove_tag_from_hierarchy(
+    transaction: &mut Transaction<'_, Sqlite>,
+    tag: &Tag,
+) -> Result<(), CrudError> {
+    let name = tag.name.as_ref().map(TagName::as_str);
+    let parent: Option<String> =
+        sqlx::query_scalar("SELECT parent_value FROM tag_parents WHERE name IS ? AND value = ?")
+            .bind(name)
+            .bind(tag.value.as_str())
+            .fetch_optional(&mut **transaction)
+            .await?;
+    sqlx::query("DELETE FROM tag_parents WHERE name IS ? AND value = ?")
+        .bind(name)
+        .bind(tag.value.as_str())
+        .execute(&mut **transaction)
+        .await?;
+    match parent {
+        Some(parent) => {
+            sqlx::query(
+                "UPDATE tag_parents SET parent_value = ? WHERE name IS ? AND parent_value = ?",
+            )
+            .bind(parent)
+            .bind(name)
+            .bind(tag.value.as_str())
+            .execute(&mut **transaction)
+            .await?
+        }
+        None => {
+            sqlx::query("DELETE FROM tag_parents WHERE name IS ? AND parent_value = ?")
+                .bind(name)
+                .bind(tag.value.as_str())
+                .execute(&mut **transaction)
+                .await?
+        }
+    };
+    Ok(())
+}
+
+/// A node in a [`TagTree`] (a tag & its children)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagTreeNode {
+    /// The tag the node represents.  This may not appear in the counts itself
+    /// (e.g. `region=europe` when only `region=france` is used)
+    tag: Tag,
+
+    /// The number of times the tag itself appears
+    count: i64,
+
+    /// The number of times the tag and its descendants appear
+    total_count: i64,
+
+    /// The children, keyed by their values
+    children: BTreeMap<String, TagTreeNode>,
+}
+
+impl TagTreeNode {
+    /// Create a node without any counts or children
+    fn new(tag: Tag) -> Self {
+        Self {
+            tag,
+            count: 0,
+            total_count: 0,
+            children: BTreeMap::new(),
+        }
+    }
+
+    /// Borrow the tag the node represents
+    pub fn tag(&self) -> &Tag {
+        &self.tag
+    }
+
+    /// The number of times the tag itself appears
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+
+    /// The number of times the tag and its descendants appear
+    pub fn total_count(&self) -> i64 {
+        self.total_count
+    }
+
+    /// Borrow the children, keyed by their values
+    pub fn children(&self) -> &BTreeMap<String, TagTreeNode> {
+        &self.children
+    }
+
+    /// Whether the node has any children
+    pub fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+/// [`TagCounts`] arranged as trees (per tag name) using a [`TagHierarchy`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagTree {
+    /// The root nodes, keyed by tag name and then value
+    roots: BTreeMap<Option<TagName>, BTreeMap<String, TagTreeNode>>,
+}
+
+impl TagTree {
+    /// Arrange the counted tags (& their ancestors) by the hierarchy
+    pub fn from(tag_counts: &TagCounts, tag_hierarchy: &TagHierarchy) -> Self {
+        let mut roots: BTreeMap<Option<TagName>, BTreeMap<String, TagTreeNode>> = BTreeMap::new();
+        for tag_count in tag_counts {
+            let tag = tag_count.tag();
+            let count = *tag_count.count();
+            let mut path = tag_hierarchy.ancestors(tag);
+            path.reverse();
+            path.push(tag.clone());
+            let mut nodes = roots.entry(tag.name.clone()).or_default();
+            let last = path.len() - 1;
+            for (i, tag) in path.into_iter().enumerate() {
+                let node = nodes
+                    .entry(tag.value.to_string())
+                    .or_insert_with(|| TagTreeNode::new(tag));
+                node.total_count += count;
+                if i == last {
+                    node.count += count;
+                }
+                nodes = &mut node.children;
+            }
+        }
+        Self { roots }
+    }
+
+    /// Borrow the root nodes, keyed by tag name and then value
+    pub fn roots(&self) -> &BTreeMap<Option<TagName>, BTreeMap<String, TagTreeNode>> {
+        &self.roots
+    }
+
+    /// Whether the tree has no tags
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Create, delete_all_matching_tags, fetch_all_entity_tag_counts,
+        fetch_entity_ids_by_bool_expr, update_all_matching_entity_tags,
+    };
+    use bool_tag_expr::BoolTagExpr;
+    use open_timeline_core::{Date, Entity, HasIdAndName, Name};
+    use sqlx::{Pool, Sqlite};
+
+    fn tag(value: &str) -> Tag {
+        Tag::from(None, TagValue::from(&value).unwrap())
+    }
+
+    fn value(value: &str) -> TagValue {
+        TagValue::from(&value).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn parent_matches_descendants(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut ids = Vec::new();
+        for (name, value) in [
+            ("Paris", "france"),
+            ("Rome", "italy"),
+            ("Tokyo", "japan"),
+            ("Normandy", "world-war-two"),
+        ] {
+            let mut entity = Entity::from(
+                None,
+                Name::from(name).unwrap(),
+                Date::from(None, None, 2000).unwrap(),
+                None,
+                Some([tag(value)].into_iter().collect()),
+            )
+            .unwrap();
+            entity.create(&mut transaction).await.unwrap();
+            ids.push(entity.id().unwrap());
+        }
+        for (child, parent) in [
+            ("france", "europe"),
+            ("italy", "europe"),
+            ("europe", "world"),
+            ("japan", "asia"),
+            ("asia", "world"),
+        ] {
+            set_tag_parent(&mut transaction, &tag(child), Some(&value(parent)))
+                .await
+                .unwrap();
+        }
+
+        // Parents match their descendants
+        let europe = BoolTagExpr::from("europe".to_string()).unwrap();
+        let mut entity_ids = fetch_entity_ids_by_bool_expr(&mut transaction, &europe)
+            .await
+            .unwrap();
+        entity_ids.sort();
+        let mut expected = vec![ids[0], ids[1]];
+        expected.sort();
+        assert_eq!(entity_ids, expected);
+
+        // ...but only through the hierarchy
+        let world = BoolTagExpr::from("world".to_string()).unwrap();
+        let entity_ids = fetch_entity_ids_by_bool_expr(&mut transaction, &world)
+            .await
+            .unwrap();
+        assert_eq!(entity_ids.len(), 3);
+        assert!(!entity_ids.contains(&ids[3]));
+
+        // Children don't match their parents
+        let japan = BoolTagExpr::from("japan".to_string()).unwrap();
+        let entity_ids = fetch_entity_ids_by_bool_expr(&mut transaction, &japan)
+            .await
+            .unwrap();
+        assert_eq!(entity_ids, vec![ids[2]]);
+
+        // A tag can't become its own descendant
+        let result = set_tag_parent(&mut transaction, &tag("world"), Some(&value("france"))).await;
+        assert!(matches!(result, Err(CrudError::TagHierarchy(_))));
+
+        // The tree
+        let tag_counts = fetch_all_entity_tag_counts(&mut transaction).await.unwrap();
+        let tag_hierarchy = fetch_tag_hierarchy(&mut transaction).await.unwrap();
+        let tree = TagTree::from(&tag_counts, &tag_hierarchy);
+        let root = &tree.roots()[&None]["world"];
+        assert_eq!(root.tag(), &tag("world"));
+        assert_eq!(root.count(), 0);
+        assert_eq!(root.total_count(), 3);
+        let europe = &root.children()["europe"];
+        assert_eq!(europe.total_count(), 2);
+        let france = &europe.children()["france"];
+        assert_eq!(france.tag(), &tag("france"));
+        assert_eq!(france.count(), 1);
+        assert!(france.is_leaf());
+        assert!(tree.roots()[&None]["world-war-two"].is_leaf());
+
+        // Renaming & deleting tags keeps the hierarchy
+        update_all_matching_entity_tags(&mut transaction, tag("france"), tag("gaul"))
+            .await
+            .unwrap();
+        delete_all_matching_tags(&mut transaction, tag("europe"))
+            .await
+            .unwrap();
+        let tag_hierarchy = fetch_tag_hierarchy(&mut transaction).await.unwrap();
+        assert_eq!(tag_hierarchy.parent(&tag("gaul")), Some(tag("world")));
+        assert_eq!(tag_hierarchy.parent(&tag("france")), None);
+        assert_eq!(tag_hierarchy.parent(&tag("europe")), None);
+
+        // Removing a parent
+        set_tag_parent(&mut transaction, &tag("japan"), None)
+            .await
+            .unwrap();
+        let entity_ids = fetch_entity_ids_by_bool_expr(&mut transaction, &world)
+            .await
+            .unwrap();
+        assert_eq!(entity_ids.len(), 2);
+    }
+}
@@ -4,9 +4,13 @@
 //! Tags
 //!
 
-use crate::{CrudError, FetchAll, RowsAffected, SortAlphabetically, SortByNumber};
+use crate::{
+    CrudError, FetchAll, Limit, RowsAffected, SortAlphabetically, SortByNumber,
+    remove_tag_from_hierarchy, rename_tag_in_hierarchy,
+};
 use async_trait::async_trait;
 use bool_tag_expr::{Tag, TagName, TagValue, Tags};
+use open_timeline_core::OpenTimelineId;
 use serde::{Deserialize, Serialize};
 use sqlx::{Sqlite, Transaction};
 
@@ -152,12 +156,14 @@ pub async fn fetch_all_timeline_tag_counts(
     .collect())
 }
 
-/// Update all entity tags that match (enables batch editing)
+/// Update all entity tags that match (enables batch editing).  The tag keeps
+/// its place in the tag hierarchy if its name is unchanged.
 pub async fn update_all_matching_entity_tags(
     transaction: &mut Transaction<'_, Sqlite>,
     old_tag: Tag,
     new_tag: Tag,
 ) -> Result<RowsAffected, CrudError> {
+    rename_tag_in_hierarchy(transaction, &old_tag, &new_tag).await?;
     Ok(sqlx::query!(
         r#"
             UPDATE entity_tags
@@ -181,11 +187,13 @@ pub async fn update_all_matching_entity_tags(
 }
 
 // TODO: return RowsAffected?
-/// Delete tag from database
+/// Delete tag from database (including from the tag hierarchy)
 pub async fn delete_all_matching_tags(
     transaction: &mut Transaction<'_, Sqlite>,
     tag: Tag,
 ) -> Result<(), CrudError> {
+    remove_tag_from_hierarchy(transaction, &tag).await?;
+
     // Delete entity tags
     sqlx::query!(
         r#"
@@ -220,3 +228,148 @@ pub async fn delete_all_matching_tags(
 
     Ok(())
 }
+
+/// Fetch the entity tags that most often appear alongside the given tags (i.e.
+/// on entities that have any of them), most common first.  The given tags
+/// aren't included, and neither are the tags of the excluded entity (e.g. the
+/// one being edited, whose saved tags may have since been removed).
+pub async fn fetch_co_occurring_entity_tags(
+    transaction: &mut Transaction<'_, Sqlite>,
+    tags: &Tags,
+    exclude_entity_id: Option<OpenTimelineId>,
+    Limit(limit): Limit,
+) -> Result<TagCounts, CrudError> {
+    if tags.is_empty() {
+        return Ok(TagCounts(Vec::new()));
+    }
+    let given: Vec<_> = tags
+        .iter()
+        .map(|tag| {
+            serde_json::json!({
+                "name": tag.name.as_ref().map(|name| name.as_str()),
+                "value": tag.value.as_str(),
+            })
+        })
+        .collect();
+    let given = serde_json::Value::from(given).to_string();
+    Ok(sqlx::query!(
+        r#"
+            WITH
+                given AS (
+                    SELECT
+                        json_extract(value, '$.name') AS name,
+                        json_extract(value, '$.value') AS value
+                    FROM json_each(?)
+                ),
+                similar AS (
+                    SELECT DISTINCT entity_tags.entity_id
+                    FROM entity_tags
+                    JOIN given
+                        ON entity_tags.value = given.value
+                        AND entity_tags.name IS given.name
+                    WHERE entity_tags.entity_id IS NOT ?
+                )
+            SELECT
+                name AS "name: TagName",
+                value AS "value!: TagValue",
+                COUNT(*) AS "count!: i64"
+            FROM entity_tags
+            WHERE entity_id IN (SELECT entity_id FROM similar)
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM given
+                    WHERE given.value = entity_tags.value
+                        AND given.name IS entity_tags.name
+                )
+            GROUP BY name, value
+            ORDER BY COUNT(*) DESC, value, name
+            LIMIT ?
+        "#,
+        given,
+        exclude_entity_id,
+        limit
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| TagCount {
+        tag: Tag::from(row.name, row.value),
+        count: row.count,
+    })
+    .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Create;
+    use bool_tag_expr::TagComponent;
+    use open_timeline_core::{Date, Entity, Name};
+    use sqlx::Pool;
+
+    fn tag(tag: &str) -> Tag {
+        match tag.split_once('=') {
+            Some((name, value)) => Tag::from(
+                Some(TagComponent::from(&name).unwrap()),
+                TagComponent::from(&value).unwrap(),
+            ),
+            None => Tag::from(None, TagComponent::from(&tag).unwrap()),
+        }
+    }
+
+    async fn create(transaction: &mut Transaction<'_, Sqlite>, name: &str, tags: &[&str]) {
+        let tags = tags.iter().map(|t| tag(t)).collect();
+        let mut entity = Entity::from(
+            None,
+            Name::from(name).unwrap(),
+            Date::from(None, None, 1900).unwrap(),
+            None,
+            Some(tags),
+        )
+        .unwrap();
+        entity.create(transaction).await.unwrap();
+    }
+
+    #[sqlx::test]
+    async fn co_occurring_entity_tags(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        create(
+            &mut transaction,
+            "Ada",
+            &["scientist", "country=uk", "woman"],
+        )
+        .await;
+        create(&mut transaction, "Alan", &["scientist", "country=uk"]).await;
+        create(
+            &mut transaction,
+            "Marie",
+            &["scientist", "country=france", "woman"],
+        )
+        .await;
+        create(&mut transaction, "Monet", &["painter", "country=france"]).await;
+
+        let given = Tags::from([tag("scientist")]);
+        let suggested = fetch_co_occurring_entity_tags(&mut transaction, &given, None, Limit(2))
+            .await
+            .unwrap();
+        let suggested: Vec<(String, i64)> = suggested
+            .into_iter()
+            .map(|count| (count.tag().to_string(), *count.count()))
+            .collect();
+        assert_eq!(
+            suggested,
+            vec![(String::from("country=uk"), 2), (String::from("=woman"), 2)]
+        );
+
+        // Named & unnamed tags with the same value are different
+        let given = Tags::from([tag("country=france"), tag("painter")]);
+        let suggested = fetch_co_occurring_entity_tags(&mut transaction, &given, None, Limit(10))
+            .await
+            .unwrap();
+        let suggested: Vec<String> = suggested
+            .into_iter()
+            .map(|count| count.tag().to_string())
+            .collect();
+        assert_eq!(suggested, vec!["=scientist", "=woman"]);
+    }
+}
@@ -4,16 +4,24 @@
 //! All CRUD functionality for timelines
 //!
 
+mod bookmarks;
 mod common;
 mod counts;
+mod duplicate;
+mod dynamic;
 mod edit;
 mod reduced_timeline;
 mod reduced_timelines;
+mod story;
 mod view;
 
+pub use bookmarks::*;
 pub use common::*;
 pub use counts::*;
+pub use duplicate::*;
+pub use dynamic::*;
 pub use edit::*;
 pub use reduced_timeline::*;
 pub use reduced_timelines::*;
+pub use story::*;
 pub use view::*;
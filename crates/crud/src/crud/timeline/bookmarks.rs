@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Named positions on a timeline (so the user can jump between them)
+//!
+
+use crate::CrudError;
+use open_timeline_core::{Name, OpenTimelineId};
+use sqlx::{Sqlite, Transaction};
+
+/// A named position on a timeline (what's in view, and how zoomed in it is)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineBookmark {
+    name: Name,
+    start_year: f64,
+    offset_y: f64,
+    zoom: f64,
+    datetime_scale: f64,
+}
+
+impl TimelineBookmark {
+    /// Create a new bookmark
+    pub fn from(
+        name: Name,
+        start_year: f64,
+        offset_y: f64,
+        zoom: f64,
+        datetime_scale: f64,
+    ) -> Self {
+        Self {
+            name,
+            start_year,
+            offset_y,
+            zoom,
+            datetime_scale,
+        }
+    }
+
+    /// Get the bookmark's name
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// Get the (fractional) year at the left edge of the view
+    pub fn start_year(&self) -> f64 {
+        self.start_year
+    }
+
+    /// Get the vertical offset of the view
+    pub fn offset_y(&self) -> f64 {
+        self.offset_y
+    }
+
+    /// Get the zoom level
+    pub fn zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    /// Get the datetime scale factor (stretch in x-direction)
+    pub fn datetime_scale(&self) -> f64 {
+        self.datetime_scale
+    }
+}
+
+/// Fetch all of a timeline's bookmarks (ordered by name)
+pub async fn fetch_timeline_bookmarks(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+) -> Result<Vec<TimelineBookmark>, CrudError> {
+    Ok(sqlx::query_as!(
+        TimelineBookmark,
+        r#"
+            SELECT
+                name AS "name: Name",
+                start_year AS "start_year: f64",
+                offset_y AS "offset_y: f64",
+                zoom AS "zoom: f64",
+                datetime_scale AS "datetime_scale: f64"
+            FROM timeline_bookmarks
+            WHERE timeline_id=?
+            ORDER BY name
+        "#,
+        timeline_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?)
+}
+
+/// Save a bookmark for a timeline (replacing any with the same name)
+pub async fn save_timeline_bookmark(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+    bookmark: &TimelineBookmark,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            INSERT INTO timeline_bookmarks
+                (timeline_id, name, start_year, offset_y, zoom, datetime_scale)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (timeline_id, name) DO UPDATE SET
+                start_year = excluded.start_year,
+                offset_y = excluded.offset_y,
+                zoom = excluded.zoom,
+                datetime_scale = excluded.datetime_scale
+        "#,
+        timeline_id,
+        bookmark.name,
+        bookmark.start_year,
+        bookmark.offset_y,
+        bookmark.zoom,
+        bookmark.datetime_scale,
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Delete one of a timeline's bookmarks
+pub async fn delete_timeline_bookmark(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+    name: &Name,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM timeline_bookmarks
+            WHERE timeline_id=? AND name=?
+        "#,
+        timeline_id,
+        name
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Delete all of a timeline's bookmarks (done when it is deleted)
+pub(crate) async fn delete_timeline_bookmarks(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM timeline_bookmarks
+            WHERE timeline_id=?
+        "#,
+        timeline_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, DeleteById};
+    use open_timeline_core::{HasIdAndName, TimelineEdit};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn save_fetch_and_delete(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut timeline = valid_timeline_no_subtimelines();
+        timeline.clear_entities();
+        timeline.create(&mut transaction).await.unwrap();
+        let timeline_id = timeline.id().unwrap();
+        let wwi = TimelineBookmark::from(Name::from("WWI").unwrap(), 1914.0, 0.0, 1.5, 2.0);
+        let revolution = TimelineBookmark::from(
+            Name::from("French Revolution").unwrap(),
+            1789.0,
+            -40.0,
+            1.0,
+            1.0,
+        );
+
+        // Saving (ordered by name)
+        save_timeline_bookmark(&mut transaction, &timeline_id, &wwi)
+            .await
+            .unwrap();
+        save_timeline_bookmark(&mut transaction, &timeline_id, &revolution)
+            .await
+            .unwrap();
+        let bookmarks = fetch_timeline_bookmarks(&mut transaction, &timeline_id)
+            .await
+            .unwrap();
+        assert_eq!(bookmarks, vec![revolution.clone(), wwi.clone()]);
+
+        // Saving with the same name replaces
+        let wwi = TimelineBookmark::from(Name::from("WWI").unwrap(), 1918.0, 0.0, 1.0, 1.0);
+        save_timeline_bookmark(&mut transaction, &timeline_id, &wwi)
+            .await
+            .unwrap();
+        let bookmarks = fetch_timeline_bookmarks(&mut transaction, &timeline_id)
+            .await
+            .unwrap();
+        assert_eq!(bookmarks, vec![revolution.clone(), wwi.clone()]);
+
+        // Deleting
+        delete_timeline_bookmark(&mut transaction, &timeline_id, wwi.name())
+            .await
+            .unwrap();
+        let bookmarks = fetch_timeline_bookmarks(&mut transaction, &timeline_id)
+            .await
+            .unwrap();
+        assert_eq!(bookmarks, vec![revolution]);
+
+        // Deleting the timeline deletes its bookmarks
+        TimelineEdit::delete_by_id(&mut transaction, &timeline_id)
+            .await
+            .unwrap();
+        assert!(
+            fetch_timeline_bookmarks(&mut transaction, &timeline_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Duplicating timelines (so that an existing timeline can be used as the
+//! starting point for a new one)
+//!
+
+use crate::{
+    Create, CrudError, FetchById, Update, fetch_timeline_direct_subtimeline_ids_by_timeline_id,
+    is_timeline_id_in_db, is_timeline_name_in_db,
+};
+use open_timeline_core::{
+    HasIdAndName, IsReducedType, Name, OpenTimelineId, ReducedTimeline, ReducedTimelines,
+    TimelineEdit,
+};
+use sqlx::{Sqlite, Transaction};
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+
+/// What to do with a timeline's subtimelines when it is duplicated
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SubtimelineDuplication {
+    /// The duplicate has the same subtimelines as the original
+    #[default]
+    Share,
+
+    /// The subtimelines (and theirs, and so on) are duplicated too
+    Copy,
+}
+
+/// Duplicate a timeline, giving the duplicate (and any duplicated subtimelines)
+/// new IDs.  If no name is given, the duplicate's name is the original's with a
+/// " (copy)" suffix (numbered if that name is taken).  Returns the duplicate's
+/// ID.
+pub async fn duplicate_timeline(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+    name: Option<Name>,
+    subtimelines: SubtimelineDuplication,
+) -> Result<OpenTimelineId, CrudError> {
+    if !is_timeline_id_in_db(transaction, timeline_id).await? {
+        return Err(CrudError::TimelineNotInDb);
+    }
+
+    // The timelines to duplicate (original ID -> duplicate ID)
+    let mut new_ids = BTreeMap::from([(*timeline_id, OpenTimelineId::new())]);
+    if subtimelines == SubtimelineDuplication::Copy {
+        let mut to_visit = vec![*timeline_id];
+        while let Some(id) = to_visit.pop() {
+            let subtimeline_ids =
+                fetch_timeline_direct_subtimeline_ids_by_timeline_id(transaction, &id)
+                    .await?
+                    .unwrap_or_default();
+            for subtimeline_id in subtimeline_ids {
+                if let Entry::Vacant(entry) = new_ids.entry(subtimeline_id) {
+                    entry.insert(OpenTimelineId::new());
+                    to_visit.push(subtimeline_id);
+                }
+            }
+        }
+    }
+
+    // Create the duplicates without subtimelines (a duplicate's subtimelines
+    // may not have been created yet)
+    let mut duplicates = Vec::new();
+    for (original_id, new_id) in &new_ids {
+        let mut timeline = TimelineEdit::fetch_by_id(transaction, original_id).await?;
+        let new_name = match &name {
+            Some(name) if original_id == timeline_id => name.clone(),
+            _ => unused_copy_name(transaction, timeline.name()).await?,
+        };
+        let subtimelines = timeline.subtimelines().clone();
+        timeline.set_id(*new_id);
+        timeline.set_name(new_name);
+        timeline.clear_subtimelines();
+        timeline.create(transaction).await?;
+        duplicates.push((timeline, subtimelines));
+    }
+
+    // Link the subtimelines (to the duplicates where there are some)
+    for (mut timeline, subtimelines) in duplicates {
+        let Some(subtimelines) = subtimelines else {
+            continue;
+        };
+        let linked: ReducedTimelines = subtimelines
+            .into_iter()
+            .map(|subtimeline| {
+                let id = new_ids.get(&subtimeline.id()).copied();
+                ReducedTimeline::from_id_and_name(
+                    id.unwrap_or(subtimeline.id()),
+                    subtimeline.name().clone(),
+                )
+            })
+            .collect();
+        timeline = TimelineEdit::from(
+            timeline.id(),
+            timeline.name().clone(),
+            timeline.bool_expr().clone(),
+            timeline.entities().clone(),
+            Some(linked),
+            timeline.tags().clone(),
+        )
+        .unwrap();
+        timeline.update(transaction).await?;
+    }
+
+    Ok(new_ids[timeline_id])
+}
+
+/// The first of "<name> (copy)", "<name> (copy 2)", "<name> (copy 3)", etc.
+/// that isn't a timeline name in the database
+async fn unused_copy_name(
+    transaction: &mut Transaction<'_, Sqlite>,
+    name: &Name,
+) -> Result<Name, CrudError> {
+    let mut number = 1;
+    loop {
+        let suffix = match number {
+            1 => String::from(" (copy)"),
+            _ => format!(" (copy {number})"),
+        };
+        let candidate = Name::from(format!("{name}{suffix}")).unwrap();
+        if !is_timeline_name_in_db(transaction, &candidate).await? {
+            return Ok(candidate);
+        }
+        number += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DatabaseRowCount;
+    use crate::test::*;
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn share_and_copy_subtimelines(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let timelines = seed_db_return_timelines(&mut transaction).await;
+        let original = timelines
+            .into_iter()
+            .find(|timeline| timeline.subtimelines().is_some())
+            .unwrap();
+        let original_id = original.id().unwrap();
+        let timeline_count = DatabaseRowCount::all(&mut transaction)
+            .await
+            .unwrap()
+            .timelines;
+
+        // Sharing subtimelines only creates one timeline
+        let id = duplicate_timeline(
+            &mut transaction,
+            &original_id,
+            None,
+            SubtimelineDuplication::Share,
+        )
+        .await
+        .unwrap();
+        let duplicate = TimelineEdit::fetch_by_id(&mut transaction, &id)
+            .await
+            .unwrap();
+        assert_ne!(id, original_id);
+        assert_eq!(
+            duplicate.name().as_str(),
+            format!("{} (copy)", original.name())
+        );
+        assert_eq!(
+            duplicate.subtimelines().as_ref().unwrap().ids(),
+            original.subtimelines().as_ref().unwrap().ids()
+        );
+        assert_eq!(duplicate.entities(), original.entities());
+        assert_eq!(duplicate.tags(), original.tags());
+        let row_counts = DatabaseRowCount::all(&mut transaction).await.unwrap();
+        assert_eq!(row_counts.timelines, timeline_count + 1);
+
+        // Copying subtimelines creates new ones (and the name is numbered)
+        let id = duplicate_timeline(
+            &mut transaction,
+            &original_id,
+            None,
+            SubtimelineDuplication::Copy,
+        )
+        .await
+        .unwrap();
+        let duplicate = TimelineEdit::fetch_by_id(&mut transaction, &id)
+            .await
+            .unwrap();
+        assert_eq!(
+            duplicate.name().as_str(),
+            format!("{} (copy 2)", original.name())
+        );
+        let original_subtimeline_ids = original.subtimelines().as_ref().unwrap().ids();
+        let subtimeline_ids = duplicate.subtimelines().as_ref().unwrap().ids();
+        assert_eq!(subtimeline_ids.len(), original_subtimeline_ids.len());
+        assert!(subtimeline_ids.is_disjoint(&original_subtimeline_ids));
+
+        // A name can be given
+        let name = Name::from("Branched").unwrap();
+        let id = duplicate_timeline(
+            &mut transaction,
+            &original_id,
+            Some(name.clone()),
+            SubtimelineDuplication::Share,
+        )
+        .await
+        .unwrap();
+        let duplicate = TimelineEdit::fetch_by_id(&mut transaction, &id)
+            .await
+            .unwrap();
+        assert_eq!(duplicate.name(), &name);
+
+        // The timeline must exist
+        assert!(
+            duplicate_timeline(
+                &mut transaction,
+                &OpenTimelineId::new(),
+                None,
+                SubtimelineDuplication::Share
+            )
+            .await
+            .is_err()
+        );
+    }
+}
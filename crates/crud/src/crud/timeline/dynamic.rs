@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! [`DynamicTimeline`] - a timeline defined purely by its boolean tag expr (it
+//! has no direct entities or subtimelines).  Its entities are found using the
+//! expression each time they're fetched, so it stays up to date as entities
+//! gain/lose tags.
+//!
+
+use crate::{
+    CrudError, FetchById, fetch_entities_by_ids, fetch_entity_ids_by_bool_expr,
+    fetch_timeline_bool_expr_string_by_timeline_id, timeline_name_from_id,
+};
+use bool_tag_expr::BoolTagExpr;
+use open_timeline_core::{Entity, Name, OpenTimelineId, TimelineView};
+use serde::Serialize;
+use sqlx::{Sqlite, Transaction};
+use std::collections::BTreeSet;
+
+/// A timeline defined purely by its boolean tag expr
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DynamicTimeline {
+    /// The timeline's ID
+    id: OpenTimelineId,
+
+    /// The timeline's name
+    name: Name,
+
+    /// The expression the timeline's entities match
+    bool_expr: BoolTagExpr,
+}
+
+impl DynamicTimeline {
+    /// Get the timeline's ID
+    pub fn id(&self) -> OpenTimelineId {
+        self.id
+    }
+
+    /// Borrow the timeline's name
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// Borrow the expression the timeline's entities match
+    pub fn bool_expr(&self) -> &BoolTagExpr {
+        &self.bool_expr
+    }
+
+    /// Fetch the IDs of the entities that currently match the timeline's
+    /// expression (sorted, like the entities of a [`TimelineView`])
+    pub async fn fetch_entity_ids(
+        &self,
+        transaction: &mut Transaction<'_, Sqlite>,
+    ) -> Result<Vec<OpenTimelineId>, CrudError> {
+        let entity_ids: BTreeSet<OpenTimelineId> =
+            fetch_entity_ids_by_bool_expr(transaction, &self.bool_expr)
+                .await?
+                .into_iter()
+                .collect();
+        Ok(entity_ids.into_iter().collect())
+    }
+
+    /// Fetch the entities that currently match the timeline's expression
+    pub async fn fetch_entities(
+        &self,
+        transaction: &mut Transaction<'_, Sqlite>,
+    ) -> Result<Vec<Entity>, CrudError> {
+        let entity_ids = self.fetch_entity_ids(transaction).await?;
+        fetch_entities_by_ids(transaction, &entity_ids).await
+    }
+
+    /// Fetch the timeline's current entities as a [`TimelineView`]
+    pub async fn fetch_view(
+        &self,
+        transaction: &mut Transaction<'_, Sqlite>,
+    ) -> Result<TimelineView, CrudError> {
+        let entities = self.fetch_entities(transaction).await?;
+        let entities = (!entities.is_empty()).then_some(entities);
+        Ok(TimelineView::from(self.id, self.name.clone(), entities))
+    }
+}
+
+impl FetchById for DynamicTimeline {
+    /// Errors with [`CrudError::NotADynamicTimeline`] if the timeline has
+    /// direct entities or subtimelines (or doesn't have a boolean tag expr)
+    async fn fetch_by_id(
+        transaction: &mut Transaction<'_, Sqlite>,
+        id: &OpenTimelineId,
+    ) -> Result<DynamicTimeline, CrudError> {
+        let name = timeline_name_from_id(transaction, id).await?;
+        if !fetch_dynamic_timeline_ids(transaction).await?.contains(id) {
+            Err(CrudError::NotADynamicTimeline)?
+        }
+        let bool_expr = fetch_timeline_bool_expr_string_by_timeline_id(transaction, id)
+            .await?
+            .ok_or(CrudError::NotADynamicTimeline)?;
+        let bool_expr = BoolTagExpr::from(bool_expr).map_err(CrudError::BoolExprParse)?;
+        Ok(DynamicTimeline {
+            id: *id,
+            name,
+            bool_expr,
+        })
+    }
+}
+
+/// Fetch from the database the IDs of all dynamic timelines
+pub async fn fetch_dynamic_timeline_ids(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<BTreeSet<OpenTimelineId>, CrudError> {
+    let timeline_ids: Vec<OpenTimelineId> = sqlx::query_scalar(
+        r#"
+            SELECT id
+            FROM timelines
+            WHERE bool_expression IS NOT NULL
+                AND bool_expression != ''
+                AND id NOT IN (SELECT timeline_id FROM timeline_entities)
+                AND id NOT IN (SELECT timeline_parent_id FROM subtimelines)
+        "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(timeline_ids.into_iter().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, fetch_timeline_entity_ids, insert_timeline_entity};
+    use open_timeline_core::HasIdAndName;
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn entities_come_from_the_bool_expr(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        seed_db(&mut transaction).await;
+
+        // A timeline with only a bool expr
+        let mut timeline = valid_timeline_with_bool_expr();
+        timeline.clear_id();
+        timeline.set_name(Name::from("Dynamic").unwrap());
+        timeline.clear_entities();
+        timeline.clear_subtimelines();
+        assert!(timeline.is_dynamic());
+        timeline.create(&mut transaction).await.unwrap();
+        let id = timeline.id().unwrap();
+        assert!(
+            fetch_dynamic_timeline_ids(&mut transaction)
+                .await
+                .unwrap()
+                .contains(&id)
+        );
+
+        // Its entities are those that match the expression
+        let dynamic = DynamicTimeline::fetch_by_id(&mut transaction, &id)
+            .await
+            .unwrap();
+        assert_eq!(Some(dynamic.bool_expr()), timeline.bool_expr().as_ref());
+        let entity_ids = dynamic.fetch_entity_ids(&mut transaction).await.unwrap();
+        assert!(!entity_ids.is_empty());
+        assert_eq!(
+            entity_ids,
+            fetch_timeline_entity_ids(&mut transaction, &id)
+                .await
+                .unwrap()
+        );
+        let view = dynamic.fetch_view(&mut transaction).await.unwrap();
+        assert_eq!(view.entities().as_ref().unwrap().len(), entity_ids.len());
+
+        // Adding an entity directly means it's no longer dynamic
+        let entity_id = valid_entity().id().unwrap();
+        insert_timeline_entity(&mut transaction, &id, &entity_id)
+            .await
+            .unwrap();
+        assert!(
+            !fetch_dynamic_timeline_ids(&mut transaction)
+                .await
+                .unwrap()
+                .contains(&id)
+        );
+        assert!(matches!(
+            DynamicTimeline::fetch_by_id(&mut transaction, &id).await,
+            Err(CrudError::NotADynamicTimeline)
+        ));
+    }
+}
@@ -5,10 +5,11 @@
 //!
 
 use crate::{
-    Create, CrudError, DeleteById, DeleteByName, FetchById, FetchByName, IsATimelineType, Update,
-    entity_name_from_id, fetch_timeline_bool_expr_string_by_timeline_id,
+    Create, CrudError, DeleteById, DeleteByName, FetchById, FetchByName, IsATimelineType, SlugKind,
+    Update, delete_favourite, delete_slug_redirects, delete_timeline_bookmarks,
+    delete_timeline_story, entity_name_from_id, fetch_timeline_bool_expr_string_by_timeline_id,
     fetch_timeline_direct_member_entity_ids_by_timeline_id,
-    fetch_timeline_direct_subtimeline_ids_by_timeline_id, fetch_timeline_tags,
+    fetch_timeline_direct_subtimeline_ids_by_timeline_id, fetch_timeline_tags, generate_slug,
     is_timeline_id_in_db, timeline_id_from_name, timeline_name_from_id,
 };
 use bool_tag_expr::{BoolTagExpr, Tags};
@@ -18,6 +19,7 @@ use open_timeline_core::{
 };
 use sqlx::{Sqlite, Transaction};
 use std::collections::BTreeSet;
+use tracing::instrument;
 
 // TODO: ensure:
 // - No duplicate entities in a timeline (by ID)
@@ -30,6 +32,7 @@ impl Create for TimelineEdit {
     // TODO: do anything with the rows_affected() count/value? Applies to other
     // execute()s too
     /// Create a Timeline
+    #[instrument(name = "create_timeline", skip_all, fields(name = %self.name()))]
     async fn create(&mut self, transaction: &mut Transaction<'_, Sqlite>) -> Result<(), CrudError> {
         // Note: don't throw away an ID if it's set.  If the ID should be thrown
         // away (e.g. when using the PUT /timeline API endpoint) it should be
@@ -47,6 +50,16 @@ impl Create for TimelineEdit {
             &self.bool_expr(),
         )
         .await?;
+        self.set_version(Some(0));
+
+        // Slug
+        generate_slug(
+            transaction,
+            SlugKind::Timeline,
+            &self.id().unwrap(),
+            self.name(),
+        )
+        .await?;
 
         // Save direct entities
         if let Some(entities) = self.entities() {
@@ -70,6 +83,7 @@ impl Create for TimelineEdit {
 }
 
 impl FetchByName for TimelineEdit {
+    #[instrument(name = "fetch_timeline", skip_all, fields(%name))]
     async fn fetch_by_name(
         transaction: &mut Transaction<'_, Sqlite>,
         name: &Name,
@@ -80,6 +94,7 @@ impl FetchByName for TimelineEdit {
 }
 
 impl FetchById for TimelineEdit {
+    #[instrument(name = "fetch_timeline", skip_all, fields(%id))]
     async fn fetch_by_id(
         transaction: &mut Transaction<'_, Sqlite>,
         id: &OpenTimelineId,
@@ -137,7 +152,19 @@ impl FetchById for TimelineEdit {
             Err(_) => Err(CrudError::FetchingTimelineTags)?,
         };
 
-        Ok(TimelineEdit::from(
+        // Version
+        let timeline_version = sqlx::query_scalar!(
+            r#"
+                SELECT version
+                FROM timelines
+                WHERE id=?
+            "#,
+            id
+        )
+        .fetch_one(&mut **transaction)
+        .await?;
+
+        let mut timeline = TimelineEdit::from(
             Some(*id),
             timeline_name,
             timeline_bool_expr,
@@ -145,12 +172,15 @@ impl FetchById for TimelineEdit {
             timeline_subtimelines,
             timeline_tags,
         )
-        .unwrap())
+        .unwrap();
+        timeline.set_version(Some(timeline_version));
+        Ok(timeline)
     }
 }
 
 impl Update for TimelineEdit {
     /// Update a Timeline
+    #[instrument(name = "update_timeline", skip_all, fields(name = %self.name()))]
     async fn update(&mut self, transaction: &mut Transaction<'_, Sqlite>) -> Result<(), CrudError> {
         // TODO: should this be an error?
         if self.id().is_none() {
@@ -159,33 +189,50 @@ impl Update for TimelineEdit {
         let timeline_id = self.id().unwrap();
         let timeline_name = self.name();
 
-        // Name & Bool expr
+        // Noted so that the slug can be regenerated if it is renamed
+        let old_name = timeline_name_from_id(transaction, &timeline_id).await.ok();
+
+        // Name & Bool expr (& version, which must still be the one fetched if
+        // it's known)
         {
             let bool_expr = self
                 .bool_expr()
                 .clone()
                 .map(|expr| expr.to_boolean_expression());
-            let result = sqlx::query!(
+            let expected_version = self.version();
+            let new_version = sqlx::query_scalar!(
                 r#"
                     UPDATE timelines
                     SET
-                        name = ?,
-                        bool_expression = ?
-                    WHERE id = ?
+                        name = ?1,
+                        bool_expression = ?2,
+                        version = version + 1
+                    WHERE id = ?3 AND (?4 IS NULL OR version = ?4)
+                    RETURNING version
                 "#,
                 timeline_name,
                 bool_expr,
                 timeline_id,
+                expected_version,
             )
-            .execute(&mut **transaction)
+            .fetch_optional(&mut **transaction)
             .await?;
-
-            // TODO: !=1 or >1?
-            if result.rows_affected() != 1 {
-                Err(CrudError::UpdatingName)?
+            match new_version {
+                Some(new_version) => self.set_version(Some(new_version)),
+                None if expected_version.is_some()
+                    && is_timeline_id_in_db(transaction, &timeline_id).await? =>
+                {
+                    Err(CrudError::StaleWrite)?
+                }
+                None => Err(CrudError::UpdatingName)?,
             }
         }
 
+        // Slug
+        if old_name.as_ref() != Some(self.name()) {
+            generate_slug(transaction, SlugKind::Timeline, &timeline_id, self.name()).await?;
+        }
+
         // Entities
         {
             // Delete
@@ -226,6 +273,7 @@ impl Update for TimelineEdit {
 }
 
 impl DeleteByName for TimelineEdit {
+    #[instrument(name = "delete_timeline", skip_all, fields(%name))]
     async fn delete_by_name(
         transaction: &mut Transaction<'_, Sqlite>,
         name: &Name,
@@ -236,6 +284,7 @@ impl DeleteByName for TimelineEdit {
 }
 
 impl DeleteById for TimelineEdit {
+    #[instrument(name = "delete_timeline", skip_all, fields(%id))]
     async fn delete_by_id(
         transaction: &mut Transaction<'_, Sqlite>,
         id: &OpenTimelineId,
@@ -245,6 +294,10 @@ impl DeleteById for TimelineEdit {
         delete_timeline_tags(transaction, id).await?;
         delete_timeline_direct_entities(transaction, id).await?;
         delete_all_subtimeline_links_for_timeline(transaction, id).await?;
+        delete_favourite(transaction, id).await?;
+        delete_slug_redirects(transaction, id).await?;
+        delete_timeline_bookmarks(transaction, id).await?;
+        delete_timeline_story(transaction, id).await?;
 
         // This must come last in order to satisfy FOREIGN KEY constraints
         delete_timeline_id_and_name_and_bool_expr(transaction, id).await?;
@@ -455,7 +508,7 @@ pub async fn insert_timeline_entity(
     )
     .execute(&mut **transaction)
     .await?;
-    Ok(())
+    increment_timeline_version(transaction, timeline_id).await
 }
 
 /// Remove an entity from a database using their IDs
@@ -477,9 +530,113 @@ pub async fn delete_timeline_entity(
     )
     .execute(&mut **transaction)
     .await?;
+    increment_timeline_version(transaction, timeline_id).await
+}
+
+/// Add a subtimeline to a timeline using their IDs (errors if the timeline
+/// would end up being a subtimeline of itself)
+pub async fn insert_timeline_subtimeline(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+    subtimeline_id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    if !is_timeline_id_in_db(transaction, timeline_id).await?
+        || !is_timeline_id_in_db(transaction, subtimeline_id).await?
+    {
+        return Err(CrudError::TimelineNotInDb);
+    }
+    if timeline_is_direct_subtimeline(transaction, timeline_id, subtimeline_id).await? {
+        return Ok(());
+    }
+    if timeline_is_subtimeline_or_self(transaction, subtimeline_id, timeline_id).await? {
+        return Err(CrudError::SubtimelineCycle);
+    }
+    insert_timeline_subtimelines(transaction, timeline_id, BTreeSet::from([*subtimeline_id]))
+        .await?;
+    increment_timeline_version(transaction, timeline_id).await
+}
+
+/// Remove a subtimeline from a timeline using their IDs
+pub async fn delete_timeline_subtimeline(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+    subtimeline_id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    let rows_affected = sqlx::query(
+        r#"
+            DELETE FROM subtimelines
+            WHERE timeline_parent_id=? AND timeline_child_id=?
+        "#,
+    )
+    .bind(timeline_id)
+    .bind(subtimeline_id)
+    .execute(&mut **transaction)
+    .await?
+    .rows_affected();
+    if rows_affected == 0 {
+        return Ok(());
+    }
+    increment_timeline_version(transaction, timeline_id).await
+}
+
+/// Increment a timeline's version (its ETag), so that writes based on what it
+/// was before its members changed are rejected as stale
+async fn increment_timeline_version(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query("UPDATE timelines SET version = version + 1 WHERE id = ?")
+        .bind(timeline_id)
+        .execute(&mut **transaction)
+        .await?;
     Ok(())
 }
 
+/// Check if a timeline is a direct subtimeline of another using their IDs
+async fn timeline_is_direct_subtimeline(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+    subtimeline_id: &OpenTimelineId,
+) -> Result<bool, CrudError> {
+    let count: i64 = sqlx::query_scalar(
+        r#"
+            SELECT COUNT(*)
+            FROM subtimelines
+            WHERE timeline_parent_id=? AND timeline_child_id=?
+        "#,
+    )
+    .bind(timeline_id)
+    .bind(subtimeline_id)
+    .fetch_one(&mut **transaction)
+    .await?;
+    Ok(count > 0)
+}
+
+/// Check if `other_id` is the timeline with `timeline_id` or one of its
+/// (direct or indirect) subtimelines
+async fn timeline_is_subtimeline_or_self(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+    other_id: &OpenTimelineId,
+) -> Result<bool, CrudError> {
+    let mut visited = BTreeSet::new();
+    let mut to_visit = vec![*timeline_id];
+    while let Some(id) = to_visit.pop() {
+        if id == *other_id {
+            return Ok(true);
+        }
+        if !visited.insert(id) {
+            continue;
+        }
+        if let Some(subtimeline_ids) =
+            fetch_timeline_direct_subtimeline_ids_by_timeline_id(transaction, &id).await?
+        {
+            to_visit.extend(subtimeline_ids);
+        }
+    }
+    Ok(false)
+}
+
 /// Check if a timeline has an entity as a direct member using their IDs
 async fn entity_is_direct_member_of_timeline(
     transaction: &mut Transaction<'_, Sqlite>,
@@ -753,4 +910,70 @@ mod test {
             assert!(deleted.is_err());
         }
     }
+
+    mod subtimelines {
+        use super::*;
+
+        #[sqlx::test]
+        async fn insert_and_delete(pool: Pool<Sqlite>) {
+            // Setup
+            let mut transaction = pool.begin().await.unwrap();
+            seed_db_with_entities(&mut transaction).await;
+
+            // Create 3 timelines (no subtimelines)
+            let mut ids = Vec::new();
+            for name in ["a", "b", "c"] {
+                let mut timeline = valid_timeline_no_subtimelines();
+                timeline.set_id(OpenTimelineId::new());
+                timeline.set_name(Name::from(name).unwrap());
+                timeline.create(&mut transaction).await.unwrap();
+                ids.push(timeline.id().unwrap());
+            }
+            let [a, b, c] = [ids[0], ids[1], ids[2]];
+
+            // a > b > c (adding one twice is fine)
+            insert_timeline_subtimeline(&mut transaction, &a, &b)
+                .await
+                .unwrap();
+            insert_timeline_subtimeline(&mut transaction, &a, &b)
+                .await
+                .unwrap();
+            insert_timeline_subtimeline(&mut transaction, &b, &c)
+                .await
+                .unwrap();
+            let subtimelines =
+                fetch_timeline_direct_subtimeline_ids_by_timeline_id(&mut transaction, &a)
+                    .await
+                    .unwrap();
+            assert_eq!(subtimelines, Some(vec![b]));
+
+            // A timeline can't be a subtimeline of itself
+            for (timeline_id, subtimeline_id) in [(c, a), (a, a)] {
+                assert_eq!(
+                    insert_timeline_subtimeline(&mut transaction, &timeline_id, &subtimeline_id)
+                        .await,
+                    Err(CrudError::SubtimelineCycle)
+                );
+            }
+
+            // Nor can a timeline that isn't in the database
+            assert_eq!(
+                insert_timeline_subtimeline(&mut transaction, &a, &OpenTimelineId::new()).await,
+                Err(CrudError::TimelineNotInDb)
+            );
+
+            // Once b is removed from a, a can be a subtimeline of c
+            delete_timeline_subtimeline(&mut transaction, &a, &b)
+                .await
+                .unwrap();
+            let subtimelines =
+                fetch_timeline_direct_subtimeline_ids_by_timeline_id(&mut transaction, &a)
+                    .await
+                    .unwrap();
+            assert_eq!(subtimelines, None);
+            insert_timeline_subtimeline(&mut transaction, &c, &a)
+                .await
+                .unwrap();
+        }
+    }
 }
@@ -5,13 +5,13 @@
 //!
 
 use crate::{
-    CrudError, FetchAll, FetchAllWithTag, FetchByBoolTagExpr, FetchById, FetchByPartialName,
-    FetchByPartialNameAndBoolTagExpr, Limit,
+    BoolTagExprSql, CrudError, FetchAll, FetchAllWithTag, FetchByBoolTagExpr, FetchByPartialName,
+    FetchByPartialNameAndBoolTagExpr, Limit, Tagged,
 };
 use async_trait::async_trait;
-use bool_tag_expr::{BoolTagExpr, Tag};
+use bool_tag_expr::Tag;
 use open_timeline_core::{
-    IsReducedCollection, IsReducedType, Name, OpenTimelineId, ReducedTimeline, ReducedTimelines,
+    IsReducedType, Name, OpenTimelineId, ReducedTimeline, ReducedTimelines, TagQuery,
 };
 use sqlx::{Sqlite, Transaction};
 
@@ -75,42 +75,34 @@ impl FetchAllWithTag for ReducedTimelines {
 
 #[async_trait]
 impl FetchByBoolTagExpr for ReducedTimelines {
-    // TODO: given we're generating SQL, this probably needs better error
-    // checking.  On the other hand, one can't SQL inject because of char
-    // restrictions
-    /// Fetch all entities that match a [`BoolTagExpr`]
-    async fn fetch_by_bool_tag_expr(
+    /// Fetch all timelines that match a [`TagQuery`]
+    async fn fetch_by_tag_query(
         transaction: &mut Transaction<'_, Sqlite>,
         Limit(limit): Limit,
-        bool_expr: BoolTagExpr,
+        tag_query: &TagQuery,
     ) -> Result<Self, CrudError> {
-        let table_info =
-            bool_tag_expr::DbTableInfo::from("timeline_tags", "timeline_id", "name", "value")
-                .unwrap();
-
-        let bool_expr_sql = bool_expr.to_sql(&table_info);
-
+        let bool_expr_sql =
+            BoolTagExprSql::compile_query(tag_query, Tagged::Timelines, "timelines.id");
         let sql = format!(
             r#"
-                SELECT DISTINCT timeline_id AS "timeline_id: OpenTimelineId"
-                FROM ({bool_expr_sql})
+                SELECT id, name
+                FROM timelines
+                WHERE {}
                 LIMIT ?
             "#,
+            bool_expr_sql.condition()
         );
-
-        // TODO: does this work? Needs testing
-        let timeline_ids: Vec<OpenTimelineId> = sqlx::query_scalar(&sql)
+        let mut query = sqlx::query_as::<_, (OpenTimelineId, Name)>(&sql);
+        for bind in bool_expr_sql.binds() {
+            query = query.bind(bind);
+        }
+        Ok(query
             .bind(limit)
             .fetch_all(&mut **transaction)
-            .await?;
-
-        let mut reduced_timelines = ReducedTimelines::new();
-        for timeline_id in timeline_ids {
-            let reduced_entity = ReducedTimeline::fetch_by_id(transaction, &timeline_id).await?;
-            reduced_timelines.collection_mut().insert(reduced_entity);
-        }
-
-        Ok(reduced_timelines)
+            .await?
+            .into_iter()
+            .map(|(id, name)| ReducedTimeline::from_id_and_name(id, name))
+            .collect())
     }
 }
 
@@ -143,25 +135,38 @@ impl FetchByPartialName for ReducedTimelines {
     }
 }
 
-// TODO: do properly with JOIN(s)
 #[async_trait]
 impl FetchByPartialNameAndBoolTagExpr for ReducedTimelines {
-    async fn fetch_by_partial_name_and_bool_tag_expr(
+    async fn fetch_by_partial_name_and_tag_query(
         transaction: &mut Transaction<'_, Sqlite>,
         Limit(limit): Limit,
         partial_name: &str,
-        bool_tag_expr: BoolTagExpr,
+        tag_query: &TagQuery,
     ) -> Result<Self, CrudError> {
-        let from_expr =
-            Self::fetch_by_bool_tag_expr(transaction, Limit(u32::MAX), bool_tag_expr).await?;
-        let from_name =
-            Self::fetch_by_partial_name(transaction, Limit(u32::MAX), partial_name).await?;
-        let both = from_expr
-            .collection()
-            .intersection(from_name.collection())
-            .take(limit as usize)
-            .cloned()
-            .collect();
-        Ok(both)
+        let bool_expr_sql =
+            BoolTagExprSql::compile_query(tag_query, Tagged::Timelines, "timelines.id");
+        let sql = format!(
+            r#"
+                SELECT id, name
+                FROM timelines
+                WHERE
+                    name LIKE CONCAT('%', ?, '%')
+                    AND {}
+                ORDER BY RANDOM()
+                LIMIT ?
+            "#,
+            bool_expr_sql.condition()
+        );
+        let mut query = sqlx::query_as::<_, (OpenTimelineId, Name)>(&sql).bind(partial_name);
+        for bind in bool_expr_sql.binds() {
+            query = query.bind(bind);
+        }
+        Ok(query
+            .bind(limit)
+            .fetch_all(&mut **transaction)
+            .await?
+            .into_iter()
+            .map(|(id, name)| ReducedTimeline::from_id_and_name(id, name))
+            .collect())
     }
 }
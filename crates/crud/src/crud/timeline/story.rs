@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! A timeline's story (a guided tour through it, one step at a time)
+//!
+
+use crate::CrudError;
+use open_timeline_core::{OpenTimelineId, StoryStep, StoryStepTarget};
+use sqlx::{Sqlite, Transaction};
+
+/// Fetch a timeline's story (its steps in order).  Timelines without a story
+/// have no steps.
+pub async fn fetch_timeline_story(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+) -> Result<Vec<StoryStep>, CrudError> {
+    let rows = sqlx::query!(
+        r#"
+            SELECT
+                entity_id AS "entity_id: OpenTimelineId",
+                start_year AS "start_year: f64",
+                end_year AS "end_year: f64",
+                caption
+            FROM timeline_story_steps
+            WHERE timeline_id=?
+            ORDER BY position
+        "#,
+        timeline_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let target = match (row.entity_id, row.start_year, row.end_year) {
+                (Some(entity_id), _, _) => StoryStepTarget::Entity(entity_id),
+                (None, Some(start_year), Some(end_year)) => StoryStepTarget::Years {
+                    start_year,
+                    end_year,
+                },
+                _ => return None,
+            };
+            Some(StoryStep::from(target, row.caption))
+        })
+        .collect())
+}
+
+/// Save a timeline's story (replacing any it already has)
+pub async fn save_timeline_story(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+    steps: &[StoryStep],
+) -> Result<(), CrudError> {
+    delete_timeline_story(transaction, timeline_id).await?;
+    for (position, step) in steps.iter().enumerate() {
+        let position = position as i64;
+        let (entity_id, start_year, end_year) = match step.target {
+            StoryStepTarget::Entity(entity_id) => (Some(entity_id), None, None),
+            StoryStepTarget::Years {
+                start_year,
+                end_year,
+            } => (None, Some(start_year), Some(end_year)),
+        };
+        sqlx::query!(
+            r#"
+                INSERT INTO timeline_story_steps
+                    (timeline_id, position, entity_id, start_year, end_year, caption)
+                VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            timeline_id,
+            position,
+            entity_id,
+            start_year,
+            end_year,
+            step.caption,
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Delete a timeline's story (done when it is deleted)
+pub(crate) async fn delete_timeline_story(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM timeline_story_steps
+            WHERE timeline_id=?
+        "#,
+        timeline_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Delete the story steps that show an entity (done when it is deleted)
+pub(crate) async fn delete_entity_story_steps(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: &OpenTimelineId,
+) -> Result<(), CrudError> {
+    sqlx::query!(
+        r#"
+            DELETE FROM timeline_story_steps
+            WHERE entity_id=?
+        "#,
+        entity_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use crate::{Create, DeleteById};
+    use open_timeline_core::{Entity, HasIdAndName, TimelineEdit};
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn save_fetch_and_delete(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut entity = valid_entity();
+        entity.create(&mut transaction).await.unwrap();
+        let entity_id = entity.id().unwrap();
+        let mut timeline = valid_timeline_no_subtimelines();
+        timeline.clear_entities();
+        timeline.create(&mut transaction).await.unwrap();
+        let timeline_id = timeline.id().unwrap();
+        let years = StoryStep::from(
+            StoryStepTarget::Years {
+                start_year: 1914.0,
+                end_year: 1918.5,
+            },
+            String::from("The war years"),
+        );
+        let entity_step = StoryStep::from(
+            StoryStepTarget::Entity(entity_id),
+            String::from("Where it started"),
+        );
+
+        // Saving (in order)
+        let story = vec![years.clone(), entity_step.clone()];
+        save_timeline_story(&mut transaction, &timeline_id, &story)
+            .await
+            .unwrap();
+        let fetched = fetch_timeline_story(&mut transaction, &timeline_id)
+            .await
+            .unwrap();
+        assert_eq!(fetched, story);
+
+        // Saving replaces
+        let story = vec![entity_step.clone(), years.clone()];
+        save_timeline_story(&mut transaction, &timeline_id, &story)
+            .await
+            .unwrap();
+        let fetched = fetch_timeline_story(&mut transaction, &timeline_id)
+            .await
+            .unwrap();
+        assert_eq!(fetched, story);
+
+        // Deleting the entity deletes its steps
+        Entity::delete_by_id(&mut transaction, &entity_id)
+            .await
+            .unwrap();
+        let fetched = fetch_timeline_story(&mut transaction, &timeline_id)
+            .await
+            .unwrap();
+        assert_eq!(fetched, vec![years]);
+
+        // Deleting the timeline deletes its story
+        TimelineEdit::delete_by_id(&mut transaction, &timeline_id)
+            .await
+            .unwrap();
+        assert!(
+            fetch_timeline_story(&mut transaction, &timeline_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
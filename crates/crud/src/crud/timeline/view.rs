@@ -6,14 +6,16 @@
 //!
 
 use crate::{
-    CrudError, FetchById, FetchByName, IsATimelineType,
+    BoolTagExprSql, CrudError, FetchById, FetchByName, IsATimelineType, Tagged,
     fetch_timeline_bool_expr_string_by_timeline_id,
     fetch_timeline_direct_member_entity_ids_by_timeline_id,
     fetch_timeline_direct_subtimeline_ids_by_timeline_id, timeline_id_from_name,
     timeline_name_from_id,
 };
 use bool_tag_expr::BoolTagExpr;
-use open_timeline_core::{Entity, HasIdAndName, Name, OpenTimelineId, TimelineView};
+use open_timeline_core::{
+    Entity, HasIdAndName, Name, OpenTimelineId, ReducedRenderEntity, TimelineView,
+};
 use sqlx::{Sqlite, Transaction};
 use std::collections::BTreeSet;
 
@@ -64,6 +66,62 @@ impl FetchById for TimelineView {
     }
 }
 
+/// Fetch from the database all entities in a timeline and all of its
+/// subtimelines, but only the data needed to render them (sorted by ID, like
+/// the entities of a [`TimelineView`])
+pub async fn fetch_timeline_render_entities(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+) -> Result<Option<Vec<ReducedRenderEntity>>, CrudError> {
+    // Errors if the timeline isn't in the database
+    timeline_name_from_id(transaction, timeline_id).await?;
+
+    let entity_ids =
+        match fetch_all_timeline_entity_ids_by_timeline_id(transaction, timeline_id).await {
+            Ok(Some(entity_ids)) => entity_ids,
+            Ok(None) => return Ok(None),
+            Err(_) => Err(CrudError::FetchingTimelineAllEntityIds)?,
+        };
+
+    // The IDs are held in a `BTreeSet`, so the entities are fetched in order
+    let mut entities = Vec::with_capacity(entity_ids.len());
+    for entity_id in entity_ids {
+        entities.push(ReducedRenderEntity::fetch_by_id(transaction, &entity_id).await?);
+    }
+    Ok(Some(entities))
+}
+
+/// Fetch from the database the IDs of all entities in a timeline and all of
+/// its subtimelines (sorted), so that the entities can be fetched a page at a
+/// time with [`fetch_entities_by_ids()`] (big timelines can then be shown
+/// before all of their entities have been fetched)
+pub async fn fetch_timeline_entity_ids(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline_id: &OpenTimelineId,
+) -> Result<Vec<OpenTimelineId>, CrudError> {
+    // Errors if the timeline isn't in the database
+    timeline_name_from_id(transaction, timeline_id).await?;
+
+    match fetch_all_timeline_entity_ids_by_timeline_id(transaction, timeline_id).await {
+        Ok(entity_ids) => Ok(entity_ids.unwrap_or_default().into_iter().collect()),
+        Err(_) => Err(CrudError::FetchingTimelineAllEntityIds),
+    }
+}
+
+/// Fetch from the database the entities with the given IDs (in the same
+/// order).  Full entities are fetched (rather than [`ReducedRenderEntity`]s)
+/// because the renderer's location filter needs their coordinates.
+pub async fn fetch_entities_by_ids(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_ids: &[OpenTimelineId],
+) -> Result<Vec<Entity>, CrudError> {
+    let mut entities = Vec::with_capacity(entity_ids.len());
+    for entity_id in entity_ids {
+        entities.push(Entity::fetch_by_id(transaction, entity_id).await?);
+    }
+    Ok(entities)
+}
+
 /// Fetch from the database the IDs of all entities in a timeline and all of
 /// its subtimelines
 async fn fetch_all_timeline_entity_ids_by_timeline_id(
@@ -154,26 +212,30 @@ async fn fetch_all_timelines_bool_exprs_entity_ids(
         return Ok(None);
     };
 
-    let table_info =
-        bool_tag_expr::DbTableInfo::from("entity_tags", "entity_id", "name", "value").unwrap();
-
-    // Vector of boolean expression SQL statements
-    let bool_expr_sql: String = bool_expr.to_sql(&table_info);
+    let entity_ids = fetch_entity_ids_by_bool_expr(transaction, &bool_expr).await?;
+    Ok((!entity_ids.is_empty()).then_some(entity_ids))
+}
 
-    // All entity IDs fetched using boolean expressions
-    let mut entity_ids = BTreeSet::new();
+/// Fetch from the database the IDs of all entities that match a boolean tag
+/// expression
+pub async fn fetch_entity_ids_by_bool_expr(
+    transaction: &mut Transaction<'_, Sqlite>,
+    bool_expr: &BoolTagExpr,
+) -> Result<Vec<OpenTimelineId>, CrudError> {
+    let bool_expr_sql = BoolTagExprSql::compile(bool_expr, Tagged::Entities, "entities.id");
     let sql = format!(
         r#"
-                SELECT DISTINCT entity_id AS "entity_id: OpenTimelineId"
-                FROM ({bool_expr_sql})
-            "#,
+            SELECT id AS "id: OpenTimelineId"
+            FROM entities
+            WHERE {}
+        "#,
+        bool_expr_sql.condition()
     );
-    let new_entity_ids: Vec<OpenTimelineId> = sqlx::query_scalar(&sql)
-        .fetch_all(&mut **transaction)
-        .await?;
-    entity_ids.extend(new_entity_ids);
-
-    Ok((!entity_ids.is_empty()).then_some(entity_ids.into_iter().collect()))
+    let mut query = sqlx::query_scalar(&sql);
+    for bind in bool_expr_sql.binds() {
+        query = query.bind(bind);
+    }
+    Ok(query.fetch_all(&mut **transaction).await?)
 }
 
 #[cfg(test)]
@@ -234,6 +296,65 @@ mod test {
         assert_ne!(entity_ids.len(), 0);
     }
 
+    #[sqlx::test]
+    async fn render_entities_match_view_entities(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        seed_db(&mut transaction).await;
+
+        for timeline in valid_timelines() {
+            let id = timeline.id().unwrap();
+            let view_entities = TimelineView::fetch_by_id(&mut transaction, &id)
+                .await
+                .unwrap()
+                .entities()
+                .clone()
+                .unwrap_or_default();
+            let render_entities = fetch_timeline_render_entities(&mut transaction, &id)
+                .await
+                .unwrap()
+                .unwrap_or_default();
+            assert_eq!(render_entities.len(), view_entities.len());
+            for (render_entity, entity) in render_entities.iter().zip(view_entities) {
+                assert_eq!(Some(render_entity.id()), entity.id());
+                assert_eq!(render_entity.name(), entity.name());
+                assert_eq!(render_entity.start(), entity.start());
+                assert_eq!(render_entity.end(), entity.end());
+                assert_eq!(render_entity.tags(), entity.tags());
+            }
+        }
+    }
+
+    #[sqlx::test]
+    async fn paged_entities_match_view_entities(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        seed_db(&mut transaction).await;
+
+        for timeline in valid_timelines() {
+            let id = timeline.id().unwrap();
+            let view_entities = TimelineView::fetch_by_id(&mut transaction, &id)
+                .await
+                .unwrap()
+                .entities()
+                .clone()
+                .unwrap_or_default();
+            let entity_ids = fetch_timeline_entity_ids(&mut transaction, &id)
+                .await
+                .unwrap();
+            let mut paged_entities = Vec::new();
+            for page in entity_ids.chunks(2) {
+                paged_entities.extend(fetch_entities_by_ids(&mut transaction, page).await.unwrap());
+            }
+            assert_eq!(paged_entities, view_entities);
+        }
+    }
+
+    #[sqlx::test]
+    async fn entity_ids_of_missing_timeline(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let result = fetch_timeline_entity_ids(&mut transaction, &OpenTimelineId::new()).await;
+        assert!(matches!(result, Err(CrudError::IdNotInDb)));
+    }
+
     mod fetch {
         use super::*;
 
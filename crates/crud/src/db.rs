@@ -4,9 +4,14 @@
 //! Create & migrate SQLite database files for OpenTimeline
 //!
 
-use log::info;
-use sqlx::{Sqlite, SqlitePool, migrate::MigrateDatabase};
+use crate::generate_missing_slugs;
+use sqlx::migrate::{MigrateDatabase, Migrator};
+use sqlx::{Pool, Sqlite, SqlitePool};
 use std::path::Path;
+use tracing::info;
+
+/// The migrations (embedded at compile time)
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
 /// Setup a database at the supplied path (ensure the file exists and run the
 /// migrations
@@ -31,7 +36,14 @@ pub async fn setup_database_at_path(path: &Path) -> Result<(), sqlx::Error> {
     let pool = SqlitePool::connect(&db_url).await?;
 
     // Run migrations (uses compile-time embedding of migrations)
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    MIGRATOR.run(&pool).await?;
+
+    // Give slugs to those entities & timelines that don't have them yet
+    let mut transaction = pool.begin().await?;
+    generate_missing_slugs(&mut transaction)
+        .await
+        .map_err(|error| sqlx::Error::Protocol(error.to_string()))?;
+    transaction.commit().await?;
 
     info!(
         "Migrations applied successfully to {}",
@@ -45,3 +57,18 @@ pub async fn setup_database_at_path(path: &Path) -> Result<(), sqlx::Error> {
 pub fn db_url_from_path(path: &Path) -> String {
     format!("sqlite://{}", path.to_string_lossy())
 }
+
+/// Get the versions of the migrations that haven't been (successfully) applied
+/// to the database
+pub async fn fetch_pending_migrations(pool: &Pool<Sqlite>) -> Result<Vec<i64>, sqlx::Error> {
+    let applied: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success = TRUE")
+            .fetch_all(pool)
+            .await?;
+    Ok(MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| migration.version)
+        .filter(|version| !applied.contains(version))
+        .collect())
+}
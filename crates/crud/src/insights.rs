@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Insights - how the entities in the database are distributed (lifespans,
+//! starts per decade, and the most common tags in each century)
+//!
+
+use crate::{BoolTagExprSql, CrudError, Tagged};
+use bool_tag_expr::{Tag, TagName, TagValue};
+use open_timeline_core::TagQuery;
+use sqlx::Row;
+use sqlx::Sqlite;
+use sqlx::Transaction;
+use std::collections::{BTreeMap, HashMap};
+
+/// The number of years covered by each lifespan bucket
+pub const LIFESPAN_BUCKET_YEARS: i64 = 10;
+
+/// The number of tags kept for each century
+pub const TOP_TAGS_PER_CENTURY: usize = 5;
+
+/// How the entities in the database are distributed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityInsights {
+    /// The number of entities (that have ended) whose lifespan falls in each
+    /// bucket of [`LIFESPAN_BUCKET_YEARS`] years (keyed by the bucket's
+    /// shortest lifespan)
+    pub lifespans: BTreeMap<i64, i64>,
+
+    /// The number of entities that start (e.g. are born) in each decade
+    /// (keyed by the decade's first year)
+    pub starts_per_decade: BTreeMap<i64, i64>,
+
+    /// The [`TOP_TAGS_PER_CENTURY`] most common tags of the entities that
+    /// start in each century, most common first (keyed by the century's first
+    /// year)
+    pub top_tags_per_century: BTreeMap<i64, Vec<(Tag, i64)>>,
+}
+
+impl EntityInsights {
+    /// Compute the insights for all entities in the database
+    pub async fn fetch(transaction: &mut Transaction<'_, Sqlite>) -> Result<Self, CrudError> {
+        Ok(Self {
+            lifespans: fetch_lifespans(transaction).await?,
+            starts_per_decade: entity_counts_per_decade(transaction, None).await?,
+            top_tags_per_century: fetch_top_tags_per_century(transaction).await?,
+        })
+    }
+}
+
+/// Count the entities in each lifespan bucket
+async fn fetch_lifespans(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<BTreeMap<i64, i64>, CrudError> {
+    let rows = sqlx::query(
+        r#"
+            SELECT end_year - start_year AS lifespan, COUNT(*) AS count
+            FROM entities
+            WHERE end_year IS NOT NULL
+            GROUP BY lifespan
+        "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    let mut lifespans = BTreeMap::new();
+    for row in rows {
+        let lifespan: i64 = row.get("lifespan");
+        let bucket = lifespan.max(0) / LIFESPAN_BUCKET_YEARS * LIFESPAN_BUCKET_YEARS;
+        *lifespans.entry(bucket).or_default() += row.get::<i64, _>("count");
+    }
+    Ok(lifespans)
+}
+
+/// Count the entities that start in each decade (keyed by the decade's first
+/// year), only counting those that match the tag query (if given)
+pub async fn entity_counts_per_decade(
+    transaction: &mut Transaction<'_, Sqlite>,
+    filter: Option<&TagQuery>,
+) -> Result<BTreeMap<i64, i64>, CrudError> {
+    let rows = match filter {
+        Some(filter) => {
+            let bool_expr_sql =
+                BoolTagExprSql::compile_query(filter, Tagged::Entities, "entities.id");
+            let sql = format!(
+                r#"
+                    SELECT start_year, COUNT(*) AS count
+                    FROM entities
+                    WHERE {}
+                    GROUP BY start_year
+                "#,
+                bool_expr_sql.condition()
+            );
+            let mut query = sqlx::query(&sql);
+            for bind in bool_expr_sql.binds() {
+                query = query.bind(bind);
+            }
+            query.fetch_all(&mut **transaction).await?
+        }
+        None => {
+            sqlx::query(
+                r#"
+                    SELECT start_year, COUNT(*) AS count
+                    FROM entities
+                    GROUP BY start_year
+                "#,
+            )
+            .fetch_all(&mut **transaction)
+            .await?
+        }
+    };
+
+    // Grouped here (not in SQL) so that BC years round down to their decade
+    let mut decades = BTreeMap::new();
+    for row in rows {
+        let decade = row.get::<i64, _>("start_year").div_euclid(10) * 10;
+        *decades.entry(decade).or_default() += row.get::<i64, _>("count");
+    }
+    Ok(decades)
+}
+
+/// Find the most common tags of the entities that start in each century
+async fn fetch_top_tags_per_century(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<BTreeMap<i64, Vec<(Tag, i64)>>, CrudError> {
+    let rows = sqlx::query(
+        r#"
+            SELECT entities.start_year, entity_tags.name, entity_tags.value, COUNT(*) AS count
+            FROM entity_tags
+            INNER JOIN entities ON entities.id = entity_tags.entity_id
+            GROUP BY entities.start_year, entity_tags.name, entity_tags.value
+        "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    let mut centuries: BTreeMap<i64, HashMap<Tag, i64>> = BTreeMap::new();
+    for row in rows {
+        let century = row.get::<i64, _>("start_year").div_euclid(100) * 100;
+        let tag = Tag::from(
+            row.get::<Option<TagName>, _>("name"),
+            row.get::<TagValue, _>("value"),
+        );
+        *centuries
+            .entry(century)
+            .or_default()
+            .entry(tag)
+            .or_default() += row.get::<i64, _>("count");
+    }
+
+    // Keep the most common (ties broken alphabetically so the order is stable)
+    Ok(centuries
+        .into_iter()
+        .map(|(century, tag_counts)| {
+            let mut tag_counts: Vec<(Tag, i64)> = tag_counts.into_iter().collect();
+            tag_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            tag_counts.truncate(TOP_TAGS_PER_CENTURY);
+            (century, tag_counts)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Create;
+    use open_timeline_core::{Date, Entity, Name};
+    use sqlx::Pool;
+
+    /// Create an entity with the given years and tags
+    async fn create_entity(
+        transaction: &mut Transaction<'_, Sqlite>,
+        name: &str,
+        start_year: i64,
+        end_year: Option<i64>,
+        tags: &[&str],
+    ) {
+        let tags = tags
+            .iter()
+            .map(|tag| Tag::from(None, TagValue::from(tag).unwrap()))
+            .collect();
+        let mut entity = Entity::from(
+            None,
+            Name::from(name).unwrap(),
+            Date::from(None, None, start_year).unwrap(),
+            end_year.map(|year| Date::from(None, None, year).unwrap()),
+            Some(tags),
+        )
+        .unwrap();
+        entity.create(transaction).await.unwrap();
+    }
+
+    #[sqlx::test]
+    async fn distributions(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        create_entity(&mut transaction, "A", 1805, Some(1870), &["person"]).await;
+        create_entity(&mut transaction, "B", 1809, Some(1882), &["person"]).await;
+        create_entity(&mut transaction, "C", 1812, Some(1815), &["war"]).await;
+        create_entity(&mut transaction, "D", 1914, None, &["war", "europe"]).await;
+        create_entity(&mut transaction, "E", -5, Some(30), &["person"]).await;
+
+        let insights = EntityInsights::fetch(&mut transaction).await.unwrap();
+        assert_eq!(
+            insights.lifespans,
+            BTreeMap::from([(0, 1), (30, 1), (60, 1), (70, 1)])
+        );
+        assert_eq!(
+            insights.starts_per_decade,
+            BTreeMap::from([(-10, 1), (1800, 2), (1810, 1), (1910, 1)])
+        );
+        let person = Tag::from(None, TagValue::from(&"person").unwrap());
+        let war = Tag::from(None, TagValue::from(&"war").unwrap());
+        let europe = Tag::from(None, TagValue::from(&"europe").unwrap());
+        assert_eq!(
+            insights.top_tags_per_century,
+            BTreeMap::from([
+                (-100, vec![(person.clone(), 1)]),
+                (1800, vec![(person, 2), (war.clone(), 1)]),
+                (1900, vec![(europe, 1), (war, 1)]),
+            ])
+        );
+
+        // The counts can be of only the entities matching a tag query
+        let filter = TagQuery::from("war").unwrap();
+        assert_eq!(
+            entity_counts_per_decade(&mut transaction, Some(&filter))
+                .await
+                .unwrap(),
+            BTreeMap::from([(1810, 1), (1910, 1)])
+        );
+        let filter = TagQuery::from("person & !war").unwrap();
+        assert_eq!(
+            entity_counts_per_decade(&mut transaction, Some(&filter))
+                .await
+                .unwrap(),
+            BTreeMap::from([(-10, 1), (1800, 2)])
+        );
+    }
+}
@@ -10,24 +10,31 @@
 //! - Provides types & functionality for editing & viewing timelines
 //! - Provides search functionality for entities and timelines by both text
 //! (name) and boolean expressions of tags.
-//! - Provides helpers to get table row counts
+//! - Provides helpers to get table row counts (and a cache of statistics)
 //! - Provides helpers to get information about the number of entities,
 //! timelines, tags, subtimelines.
 //! - Enables bulk tag editing opeation
+//! - Syncs a database with a remote OpenTimeline web API
 //!
 //! This crate makes use of the basic OpenTimeline `core` crate for primitive
 //! types, and is itself used by the `api` and `gui` crates.
 //!
 
 mod backup;
+mod cached_stats;
 mod crud;
 mod db;
+mod insights;
 mod stats;
+mod sync;
 
 pub use backup::*;
+pub use cached_stats::*;
 pub use crud::*;
 pub use db::*;
+pub use insights::*;
 pub use stats::*;
+pub use sync::*;
 
 use serde::{Deserialize, Serialize};
 
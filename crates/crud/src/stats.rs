@@ -8,6 +8,7 @@ use crate::CrudError;
 use sqlx::Row;
 use sqlx::Sqlite;
 use sqlx::Transaction;
+use std::collections::HashMap;
 
 /// Each variant maps to a table in the database
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -18,6 +19,15 @@ pub enum Table {
     /// Represents the `entity_tags` table
     EntityTags,
 
+    /// Represents the `entity_aliases` table
+    EntityAliases,
+
+    /// Represents the `sources` table
+    Sources,
+
+    /// Represents the `entity_sources` table
+    EntitySources,
+
     /// Represents the `timelines` table
     Timelines,
 
@@ -31,6 +41,23 @@ pub enum Table {
     TimelineTags,
 }
 
+impl Table {
+    /// The table's name in the database
+    fn name(&self) -> &'static str {
+        match self {
+            Table::Entities => "entities",
+            Table::EntityTags => "entity_tags",
+            Table::EntityAliases => "entity_aliases",
+            Table::Sources => "sources",
+            Table::EntitySources => "entity_sources",
+            Table::Timelines => "timelines",
+            Table::Subtimelines => "subtimelines",
+            Table::TimelineEntities => "timeline_entities",
+            Table::TimelineTags => "timeline_tags",
+        }
+    }
+}
+
 /// Holds database row counts
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct DatabaseRowCount {
@@ -40,6 +67,15 @@ pub struct DatabaseRowCount {
     /// The number of rows in the `entity_tags` table
     pub entity_tags: i64,
 
+    /// The number of rows in the `entity_aliases` table
+    pub entity_aliases: i64,
+
+    /// The number of rows in the `sources` table
+    pub sources: i64,
+
+    /// The number of rows in the `entity_sources` table
+    pub entity_sources: i64,
+
     /// The number of rows in the `timelines` table
     pub timelines: i64,
 
@@ -54,36 +90,103 @@ pub struct DatabaseRowCount {
 }
 
 impl DatabaseRowCount {
-    // TODO could store futures for lazy counting (ie remove the awaits)
-    /// Fetch the row count for all tables in the database
+    /// Fetch the row count for all tables in the database.  The counts are
+    /// materialised (kept up to date by triggers), so no tables are scanned.
     pub async fn all(transaction: &mut Transaction<'_, Sqlite>) -> Result<Self, CrudError> {
+        let rows = sqlx::query("SELECT table_name, row_count FROM table_row_counts")
+            .fetch_all(&mut **transaction)
+            .await?;
+        let counts: HashMap<String, i64> = rows
+            .into_iter()
+            .map(|row| (row.get("table_name"), row.get("row_count")))
+            .collect();
+        let count = |table: Table| counts.get(table.name()).copied().unwrap_or_default();
         Ok(Self {
-            entities: Self::table(transaction, Table::Entities).await?,
-            entity_tags: Self::table(transaction, Table::EntityTags).await?,
-            timelines: Self::table(transaction, Table::Timelines).await?,
-            subtimelines: Self::table(transaction, Table::Subtimelines).await?,
-            timeline_entities: Self::table(transaction, Table::TimelineEntities).await?,
-            timeline_tags: Self::table(transaction, Table::TimelineTags).await?,
+            entities: count(Table::Entities),
+            entity_tags: count(Table::EntityTags),
+            entity_aliases: count(Table::EntityAliases),
+            sources: count(Table::Sources),
+            entity_sources: count(Table::EntitySources),
+            timelines: count(Table::Timelines),
+            subtimelines: count(Table::Subtimelines),
+            timeline_entities: count(Table::TimelineEntities),
+            timeline_tags: count(Table::TimelineTags),
         })
     }
 
-    /// Get the row count for a specific table in the database
+    /// Count the rows in a specific table in the database (scanning the table)
     pub async fn table(
         transaction: &mut Transaction<'_, Sqlite>,
         table_name: Table,
     ) -> Result<i64, CrudError> {
-        let table_name = match table_name {
-            Table::Entities => "entities",
-            Table::EntityTags => "entity_tags",
-            Table::Timelines => "timelines",
-            Table::Subtimelines => "subtimelines",
-            Table::TimelineEntities => "timeline_entities",
-            Table::TimelineTags => "timeline_tags",
-        };
-
+        let table_name = table_name.name();
         let row = sqlx::query(&format!("SELECT COUNT(*) AS row_count FROM {table_name}"))
             .fetch_one(&mut **transaction)
             .await?;
         Ok(row.get("row_count"))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DeleteById;
+    use crate::test::*;
+    use open_timeline_core::{HasIdAndName, TimelineEdit};
+    use sqlx::Pool;
+
+    /// Count the rows of every table by scanning them
+    async fn scanned_row_counts(transaction: &mut Transaction<'_, Sqlite>) -> DatabaseRowCount {
+        DatabaseRowCount {
+            entities: DatabaseRowCount::table(transaction, Table::Entities)
+                .await
+                .unwrap(),
+            entity_tags: DatabaseRowCount::table(transaction, Table::EntityTags)
+                .await
+                .unwrap(),
+            entity_aliases: DatabaseRowCount::table(transaction, Table::EntityAliases)
+                .await
+                .unwrap(),
+            sources: DatabaseRowCount::table(transaction, Table::Sources)
+                .await
+                .unwrap(),
+            entity_sources: DatabaseRowCount::table(transaction, Table::EntitySources)
+                .await
+                .unwrap(),
+            timelines: DatabaseRowCount::table(transaction, Table::Timelines)
+                .await
+                .unwrap(),
+            subtimelines: DatabaseRowCount::table(transaction, Table::Subtimelines)
+                .await
+                .unwrap(),
+            timeline_entities: DatabaseRowCount::table(transaction, Table::TimelineEntities)
+                .await
+                .unwrap(),
+            timeline_tags: DatabaseRowCount::table(transaction, Table::TimelineTags)
+                .await
+                .unwrap(),
+        }
+    }
+
+    #[sqlx::test]
+    async fn materialised_row_counts_match_scanned(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let timelines = seed_db_return_timelines(&mut transaction).await;
+        let row_counts = DatabaseRowCount::all(&mut transaction).await.unwrap();
+        assert!(row_counts.entities > 0);
+        assert_eq!(row_counts, scanned_row_counts(&mut transaction).await);
+
+        // Deleting keeps them in step
+        let id = timelines
+            .iter()
+            .find(|timeline| timeline.subtimelines().is_none())
+            .unwrap()
+            .id()
+            .unwrap();
+        TimelineEdit::delete_by_id(&mut transaction, &id)
+            .await
+            .unwrap();
+        let row_counts = DatabaseRowCount::all(&mut transaction).await.unwrap();
+        assert_eq!(row_counts, scanned_row_counts(&mut transaction).await);
+    }
+}
@@ -0,0 +1,641 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Two-way syncing of entities & timelines with a remote OpenTimeline web API.
+//!
+//! Only what has been modified (on either side) since the last sync is pulled
+//! and pushed.  A hash of each item as it was when it was last synced is kept,
+//! so that it's known which side has changed it since.  Items changed on both
+//! sides (differently) are conflicts, which are resolved as requested (or left
+//! alone).  Deletions aren't synced.
+//!
+
+use crate::{
+    BackupRestoreMergeError, CrudError, FetchById, database_time, fetch_entity_ids_modified_since,
+    fetch_timeline_ids_modified_since, is_entity_id_in_db, is_timeline_id_in_db,
+    merge_entities_and_timelines,
+};
+use open_timeline_core::{Entity, HasIdAndName, Name, OpenTimelineId, TimelineEdit};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Sqlite, Transaction};
+use std::collections::{BTreeSet, HashMap};
+use thiserror::Error;
+
+/// Errors that can occur when syncing
+#[derive(Debug, Error)]
+pub enum SyncError {
+    /// A CRUD error occurred
+    #[error(transparent)]
+    CrudError(#[from] CrudError),
+
+    /// An error occurred when merging in the pulled entities & timelines
+    #[error(transparent)]
+    Merge(#[from] BackupRestoreMergeError),
+
+    /// An error occurred when reading or saving the sync state
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+
+    /// An error occurred when calling the remote web API
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+
+    /// The remote web API's response wasn't as expected
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+}
+
+/// The entities modified since a (unix) time, as sent by the web API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifiedEntities {
+    /// The database's (unix) time when they were fetched
+    pub fetched_at: i64,
+
+    /// The modified entities
+    pub entities: Vec<Entity>,
+}
+
+/// The timelines modified since a (unix) time, as sent by the web API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifiedTimelines {
+    /// The database's (unix) time when they were fetched
+    pub fetched_at: i64,
+
+    /// The modified timelines
+    pub timelines: Vec<TimelineEdit>,
+}
+
+/// Entities & timelines to be merged into a database (e.g. those pushed to the
+/// web API when syncing)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeRequest {
+    pub entities: Vec<Entity>,
+    pub timelines: Vec<TimelineEdit>,
+}
+
+/// A remote web API to sync with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncRemote {
+    /// The URL of the site serving the API (e.g. `https://www.open-timeline.org`)
+    url: String,
+
+    /// The token sent to the API as a bearer token (if it needs one)
+    token: Option<String>,
+}
+
+impl SyncRemote {
+    /// Create a new remote from the site's URL (and token, if the API needs
+    /// one)
+    pub fn new(url: &str, token: Option<String>) -> Self {
+        Self {
+            url: url.trim().trim_end_matches('/').to_owned(),
+            token: token.filter(|token| !token.trim().is_empty()),
+        }
+    }
+
+    /// Get the URL of the site serving the API
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Send a request to the API (with the token, if there is one)
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        method: reqwest::Method,
+        path: &str,
+    ) -> reqwest::RequestBuilder {
+        let request = client.request(method, format!("{}/api/v1{path}", self.url));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Fetch from the API what has been modified since the (unix) time
+    async fn fetch_modified<T: DeserializeOwned>(
+        &self,
+        client: &reqwest::Client,
+        path: &str,
+        since: i64,
+    ) -> Result<T, SyncError> {
+        let response = self
+            .request(client, reqwest::Method::GET, path)
+            .query(&[("modified_since", since)])
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+
+    /// Merge the entities & timelines into the remote's database
+    async fn merge(
+        &self,
+        client: &reqwest::Client,
+        merge_request: &MergeRequest,
+    ) -> Result<(), SyncError> {
+        self.request(client, reqwest::Method::PUT, "/merge")
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(merge_request)?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// How entities & timelines changed on both sides since the last sync are
+/// resolved
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncConflictResolution {
+    /// Leave them as they are on both sides (so that they can be resolved by
+    /// hand)
+    #[default]
+    Skip,
+
+    /// Keep the local version (pushing it to the remote)
+    PreferLocal,
+
+    /// Keep the remote version (pulling it from the remote)
+    PreferRemote,
+}
+
+/// Whether a synced item is an entity or a timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncItemKind {
+    Entity,
+    Timeline,
+}
+
+/// An entity or timeline changed differently on both sides since the last sync
+/// (and left as it is)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyncConflict {
+    /// Whether it's an entity or a timeline
+    pub kind: SyncItemKind,
+
+    /// Its ID
+    pub id: OpenTimelineId,
+
+    /// Its (local) name
+    pub name: Name,
+}
+
+/// What a sync did
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// The number of entities pulled from the remote
+    pub pulled_entities: usize,
+
+    /// The number of timelines pulled from the remote
+    pub pulled_timelines: usize,
+
+    /// The number of entities pushed to the remote
+    pub pushed_entities: usize,
+
+    /// The number of timelines pushed to the remote
+    pub pushed_timelines: usize,
+
+    /// The conflicts that weren't resolved
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// When the database last synced with a remote
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SyncState {
+    /// When changes were last pulled (the remote's unix time)
+    pub pulled_at: i64,
+
+    /// When changes were last pushed (this database's unix time)
+    pub pushed_at: i64,
+}
+
+/// Fetch from the database when it last synced with the remote (never, if
+/// there is no sync state)
+pub async fn fetch_sync_state(
+    transaction: &mut Transaction<'_, Sqlite>,
+    remote_url: &str,
+) -> Result<Option<SyncState>, CrudError> {
+    let state: Option<(i64, i64)> = sqlx::query_as(
+        r#"
+            SELECT pulled_at, pushed_at
+            FROM sync_state
+            WHERE remote_url=?
+        "#,
+    )
+    .bind(remote_url)
+    .fetch_optional(&mut **transaction)
+    .await?;
+    Ok(state.map(|(pulled_at, pushed_at)| SyncState {
+        pulled_at,
+        pushed_at,
+    }))
+}
+
+/// Save to the database when it last synced with the remote
+async fn save_sync_state(
+    transaction: &mut Transaction<'_, Sqlite>,
+    remote_url: &str,
+    state: SyncState,
+) -> Result<(), CrudError> {
+    sqlx::query(
+        r#"
+            INSERT INTO sync_state (remote_url, pulled_at, pushed_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT (remote_url)
+            DO UPDATE SET pulled_at = excluded.pulled_at, pushed_at = excluded.pushed_at
+        "#,
+    )
+    .bind(remote_url)
+    .bind(state.pulled_at)
+    .bind(state.pushed_at)
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Fetch from the database the hashes of the items as they were when last
+/// synced with the remote
+async fn fetch_synced_hashes(
+    transaction: &mut Transaction<'_, Sqlite>,
+    remote_url: &str,
+) -> Result<HashMap<OpenTimelineId, i64>, CrudError> {
+    let hashes: Vec<(OpenTimelineId, i64)> = sqlx::query_as(
+        r#"
+            SELECT item_id, content_hash
+            FROM sync_items
+            WHERE remote_url=?
+        "#,
+    )
+    .bind(remote_url)
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(hashes.into_iter().collect())
+}
+
+/// Save to the database the hash of an item as it is now that it's synced with
+/// the remote
+async fn save_synced_hash(
+    transaction: &mut Transaction<'_, Sqlite>,
+    remote_url: &str,
+    item_id: &OpenTimelineId,
+    content_hash: i64,
+) -> Result<(), CrudError> {
+    sqlx::query(
+        r#"
+            INSERT INTO sync_items (remote_url, item_id, content_hash)
+            VALUES (?, ?, ?)
+            ON CONFLICT (remote_url, item_id)
+            DO UPDATE SET content_hash = excluded.content_hash
+        "#,
+    )
+    .bind(remote_url)
+    .bind(item_id)
+    .bind(content_hash)
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Entities & timelines can be synced
+trait Syncable: FetchById + HasIdAndName + Clone + PartialEq + Serialize {
+    /// Whether it's an entity or a timeline
+    const KIND: SyncItemKind;
+
+    /// Whether the ID is this kind of item's in the database
+    async fn is_id_in_db(
+        transaction: &mut Transaction<'_, Sqlite>,
+        id: &OpenTimelineId,
+    ) -> Result<bool, CrudError>;
+
+    /// Forget the version fetched from the database (versions are local to a
+    /// database, so they aren't synced)
+    fn without_version(self) -> Self;
+
+    /// A hash of the item's content (which is the same in any database, &
+    /// with any build, as it's stored).  The first 8 bytes of the SHA-256 of
+    /// its JSON.
+    fn content_hash(&self) -> i64 {
+        let json = serde_json::to_vec(&self.clone().without_version())
+            .expect("entities & timelines can be serialised");
+        let digest = Sha256::digest(json);
+        i64::from_be_bytes(digest[..8].try_into().expect("SHA-256 is 32 bytes"))
+    }
+}
+
+impl Syncable for Entity {
+    const KIND: SyncItemKind = SyncItemKind::Entity;
+
+    async fn is_id_in_db(
+        transaction: &mut Transaction<'_, Sqlite>,
+        id: &OpenTimelineId,
+    ) -> Result<bool, CrudError> {
+        is_entity_id_in_db(transaction, id).await
+    }
+
+    fn without_version(mut self) -> Self {
+        self.set_version(None);
+        self
+    }
+}
+
+impl Syncable for TimelineEdit {
+    const KIND: SyncItemKind = SyncItemKind::Timeline;
+
+    async fn is_id_in_db(
+        transaction: &mut Transaction<'_, Sqlite>,
+        id: &OpenTimelineId,
+    ) -> Result<bool, CrudError> {
+        is_timeline_id_in_db(transaction, id).await
+    }
+
+    fn without_version(mut self) -> Self {
+        self.set_version(None);
+        self
+    }
+}
+
+/// The items to pull from & push to the remote, and the conflicts left alone
+struct SyncPlan<T> {
+    pull: Vec<T>,
+    push: Vec<T>,
+    unchanged: Vec<T>,
+    conflicts: Vec<SyncConflict>,
+}
+
+/// Work out what to do with the items modified on the remote and locally since
+/// the last sync
+async fn plan_sync<T: Syncable>(
+    transaction: &mut Transaction<'_, Sqlite>,
+    remote_items: Vec<T>,
+    mut local_ids: BTreeSet<OpenTimelineId>,
+    synced_hashes: &HashMap<OpenTimelineId, i64>,
+    resolution: SyncConflictResolution,
+) -> Result<SyncPlan<T>, CrudError> {
+    let mut plan = SyncPlan {
+        pull: Vec::new(),
+        push: Vec::new(),
+        unchanged: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    let is_changed =
+        |item: &T, id: &OpenTimelineId| synced_hashes.get(id) != Some(&item.content_hash());
+
+    // Items modified on the remote
+    for remote in remote_items {
+        let Some(id) = remote.id() else {
+            continue;
+        };
+        let locally_modified = local_ids.remove(&id);
+        let local = match T::is_id_in_db(transaction, &id).await? {
+            true => Some(T::fetch_by_id(transaction, &id).await?.without_version()),
+            false => None,
+        };
+        let remote = remote.without_version();
+        match local {
+            None => plan.pull.push(remote),
+            Some(local) if local == remote => plan.unchanged.push(local),
+            Some(local) if !locally_modified || !is_changed(&local, &id) => plan.pull.push(remote),
+            Some(local) if !is_changed(&remote, &id) => plan.push.push(local),
+            Some(local) => match resolution {
+                SyncConflictResolution::Skip => plan.conflicts.push(SyncConflict {
+                    kind: T::KIND,
+                    id,
+                    name: local.name().clone(),
+                }),
+                SyncConflictResolution::PreferLocal => plan.push.push(local),
+                SyncConflictResolution::PreferRemote => plan.pull.push(remote),
+            },
+        }
+    }
+
+    // Items only modified locally (that have changed since they were synced)
+    for id in local_ids {
+        let local = T::fetch_by_id(transaction, &id).await?.without_version();
+        if is_changed(&local, &id) {
+            plan.push.push(local);
+        }
+    }
+    Ok(plan)
+}
+
+/// Sync the database with the remote: pull what has been modified on the
+/// remote and push what has been modified locally since the last sync (which
+/// is everything, the first time).  Nothing is changed locally unless the
+/// transaction is committed, but the remote is changed regardless.
+pub async fn sync(
+    transaction: &mut Transaction<'_, Sqlite>,
+    remote: &SyncRemote,
+    resolution: SyncConflictResolution,
+) -> Result<SyncReport, SyncError> {
+    let state = fetch_sync_state(transaction, remote.url())
+        .await?
+        .unwrap_or_default();
+    let synced_hashes = fetch_synced_hashes(transaction, remote.url()).await?;
+
+    // What has been modified on each side
+    let client = reqwest::Client::new();
+    let remote_entities: ModifiedEntities = remote
+        .fetch_modified(&client, "/entities", state.pulled_at)
+        .await?;
+    let remote_timelines: ModifiedTimelines = remote
+        .fetch_modified(&client, "/timelines", state.pulled_at)
+        .await?;
+    let local_entity_ids = fetch_entity_ids_modified_since(transaction, state.pushed_at).await?;
+    let local_timeline_ids =
+        fetch_timeline_ids_modified_since(transaction, state.pushed_at).await?;
+
+    // What to do about it
+    let entities = plan_sync(
+        transaction,
+        remote_entities.entities,
+        local_entity_ids,
+        &synced_hashes,
+        resolution,
+    )
+    .await?;
+    let timelines = plan_sync(
+        transaction,
+        remote_timelines.timelines,
+        local_timeline_ids,
+        &synced_hashes,
+        resolution,
+    )
+    .await?;
+    let report = SyncReport {
+        pulled_entities: entities.pull.len(),
+        pulled_timelines: timelines.pull.len(),
+        pushed_entities: entities.push.len(),
+        pushed_timelines: timelines.push.len(),
+        conflicts: [entities.conflicts, timelines.conflicts].concat(),
+    };
+
+    // Push
+    let push = MergeRequest {
+        entities: entities.push,
+        timelines: timelines.push,
+    };
+    if !push.entities.is_empty() || !push.timelines.is_empty() {
+        remote.merge(&client, &push).await?;
+    }
+
+    // Pull
+    merge_entities_and_timelines(transaction, entities.pull.clone(), timelines.pull.clone())
+        .await?;
+
+    // Remember the synced items as they are now on both sides
+    let pushed_at = database_time(transaction).await?;
+    save_sync_state(
+        transaction,
+        remote.url(),
+        SyncState {
+            pulled_at: remote_entities.fetched_at.min(remote_timelines.fetched_at),
+            pushed_at,
+        },
+    )
+    .await?;
+    let entity_hashes = [entities.pull, push.entities, entities.unchanged]
+        .concat()
+        .into_iter()
+        .map(|entity| (entity.id(), entity.content_hash()));
+    let timeline_hashes = [timelines.pull, push.timelines, timelines.unchanged]
+        .concat()
+        .into_iter()
+        .map(|timeline| (timeline.id(), timeline.content_hash()));
+    for (id, content_hash) in entity_hashes.chain(timeline_hashes) {
+        if let Some(id) = id {
+            save_synced_hash(transaction, remote.url(), &id, content_hash).await?;
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::*;
+    use open_timeline_core::Date;
+    use sqlx::Pool;
+
+    #[sqlx::test]
+    async fn sync_state_round_trip(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        let remote = SyncRemote::new("https://example.com/ ", Some(String::from(" ")));
+        assert_eq!(remote, SyncRemote::new("https://example.com", None));
+        assert_eq!(
+            fetch_sync_state(&mut transaction, remote.url())
+                .await
+                .unwrap(),
+            None
+        );
+        for state in [
+            SyncState {
+                pulled_at: 1,
+                pushed_at: 2,
+            },
+            SyncState {
+                pulled_at: 3,
+                pushed_at: 4,
+            },
+        ] {
+            save_sync_state(&mut transaction, remote.url(), state)
+                .await
+                .unwrap();
+            assert_eq!(
+                fetch_sync_state(&mut transaction, remote.url())
+                    .await
+                    .unwrap(),
+                Some(state)
+            );
+        }
+    }
+
+    #[test]
+    fn content_hashes_are_stable() {
+        let mut entity = Entity::from(
+            Some(OpenTimelineId::from("5b3f9d4e-2a61-4c8e-9f0a-7d1e6c2b8a90").unwrap()),
+            Name::from("Entity").unwrap(),
+            Date::from(None, None, 1900).unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+        let hash = entity.content_hash();
+        entity.set_version(Some(2));
+        assert_eq!(entity.content_hash(), hash);
+
+        // Hashes are stored, so they mustn't change (e.g. with the Rust release)
+        assert_eq!(hash, -2074003387523333792);
+    }
+
+    #[sqlx::test]
+    async fn only_items_changed_on_both_sides_conflict(pool: Pool<Sqlite>) {
+        let mut transaction = pool.begin().await.unwrap();
+        seed_db_with_entities(&mut transaction).await;
+        let local = Entity::fetch_by_id(&mut transaction, &valid_entity().id().unwrap())
+            .await
+            .unwrap()
+            .without_version();
+        let id = local.id().unwrap();
+        let mut remote = local.clone();
+        remote.add_alias(Name::from("Remote").unwrap());
+        let local_ids = BTreeSet::from([id]);
+
+        // Never synced, so both sides have changed it
+        let plan = plan_sync(
+            &mut transaction,
+            vec![remote.clone()],
+            local_ids.clone(),
+            &HashMap::new(),
+            SyncConflictResolution::Skip,
+        )
+        .await
+        .unwrap();
+        assert!(plan.pull.is_empty() && plan.push.is_empty());
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].kind, SyncItemKind::Entity);
+
+        // Only the remote has changed it since it was synced
+        let synced = HashMap::from([(id, local.content_hash())]);
+        let plan = plan_sync(
+            &mut transaction,
+            vec![remote.clone()],
+            local_ids.clone(),
+            &synced,
+            SyncConflictResolution::Skip,
+        )
+        .await
+        .unwrap();
+        assert_eq!(plan.pull, vec![remote.clone()]);
+        assert!(plan.push.is_empty() && plan.conflicts.is_empty());
+
+        // Only the local database has changed it since it was synced
+        let synced = HashMap::from([(id, remote.content_hash())]);
+        let plan = plan_sync(
+            &mut transaction,
+            vec![remote.clone()],
+            local_ids.clone(),
+            &synced,
+            SyncConflictResolution::Skip,
+        )
+        .await
+        .unwrap();
+        assert_eq!(plan.push, vec![local.clone()]);
+        assert!(plan.pull.is_empty() && plan.conflicts.is_empty());
+
+        // Conflicts can be resolved either way
+        let plan = plan_sync(
+            &mut transaction,
+            vec![remote.clone()],
+            local_ids,
+            &HashMap::new(),
+            SyncConflictResolution::PreferRemote,
+        )
+        .await
+        .unwrap();
+        assert_eq!(plan.pull, vec![remote]);
+        assert!(plan.conflicts.is_empty());
+    }
+}
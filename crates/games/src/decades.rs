@@ -4,8 +4,11 @@
 //! Put entities into the correct decade
 //!
 
-use crate::{Answer, AnswerOption, GameError, GameManagement, Html, Stats, shuffle_answers};
+use crate::{
+    Answer, AnswerOption, GameConfig, GameError, GameManagement, Html, Stats, shuffle_answers,
+};
 use open_timeline_core::{Entity, HasIdAndName};
+use open_timeline_i18n::tr;
 use rand::{Rng, seq::SliceRandom, thread_rng};
 use std::collections::BTreeSet;
 
@@ -30,6 +33,7 @@ pub struct DecadesGame {
     pub current_options: Option<Vec<AnswerOption<Decade>>>,
     pub last_answer: Option<Answer>,
     pub game_variant: GameVariant,
+    pub config: GameConfig,
 }
 
 struct Question {
@@ -38,12 +42,15 @@ struct Question {
 }
 
 impl DecadesGame {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(config: GameConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
     }
 
     pub fn set_entity_pool(&mut self, entity_pool: Vec<Entity>) {
-        self.entity_pool = entity_pool
+        self.entity_pool = self.config.filter_pool(entity_pool)
     }
 
     pub fn generate_html_quiz(
@@ -141,20 +148,22 @@ impl GameManagement<Decade> for DecadesGame {
     }
 
     fn setup_next_round(&mut self) -> Result<(), GameError> {
+        self.config
+            .check_next_round(&self.stats, self.entity_pool.len())?;
         self.current_question = self.entity_pool.pop();
         let Some(entity) = self.current_question.as_ref() else {
             return Err(GameError::GeneratingQuestion);
         };
         self.stats.round += 1;
         let correct = start_decade_for_entity(entity.clone());
-        let answers = generate_answer_options(correct);
+        let answers = generate_answer_options(correct, self.config.options_per_question());
         self.correct_answer = Some(correct);
         self.current_options = Some(answers);
         Ok(())
     }
 
     fn description(&mut self) -> String {
-        String::from("Put entities into the correct decade")
+        tr!("game-decades-description")
     }
 }
 
@@ -163,9 +172,9 @@ fn generate_text_question(_entity: Entity) -> Result<Question, ()> {
     todo!()
 }
 
-/// Generate answer choices using the correct decade
-fn generate_answer_options(correct: Decade) -> Vec<AnswerOption<Decade>> {
-    let incorrect = generate_incorrect_decades(2, correct);
+/// Generate the given number of answer choices using the correct decade
+fn generate_answer_options(correct: Decade, count: usize) -> Vec<AnswerOption<Decade>> {
+    let incorrect = generate_incorrect_decades(count - 1, correct);
     let mut answers = vec![AnswerOption::Correct(correct)];
     incorrect
         .into_iter()
@@ -4,8 +4,9 @@
 //! Which started/ended first, left or right?
 //!
 
-use crate::{Answer, GameError, GameManagement, Stats};
+use crate::{Answer, GameConfig, GameError, GameManagement, Stats};
 use open_timeline_core::Entity;
+use open_timeline_i18n::tr;
 use rand::seq::SliceRandom;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,16 +31,20 @@ pub struct LeftRightGame {
     pub correct_answer: Option<LeftOrRight>,
     pub last_answer: Option<Answer>,
     pub variant: GameVariant,
+    pub config: GameConfig,
 }
 
 impl LeftRightGame {
     /// Create new LeftRightGame
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(config: GameConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
     }
 
     pub fn set_entity_pool(&mut self, entity_pool: Vec<Entity>) {
-        self.entity_pool = entity_pool;
+        self.entity_pool = self.config.filter_pool(entity_pool);
     }
 
     fn update_correct_answer(&mut self) {
@@ -95,6 +100,8 @@ impl GameManagement<LeftOrRight> for LeftRightGame {
 
     // TODO: what if their dates are equal? Generate a new Q
     fn setup_next_round(&mut self) -> Result<(), GameError> {
+        self.config
+            .check_next_round(&self.stats, self.entity_pool.len())?;
         if self.entity_pool.len() < 2 {
             self.current_question = None;
             return Err(GameError::PoolIsNotFullEnough);
@@ -109,8 +116,8 @@ impl GameManagement<LeftOrRight> for LeftRightGame {
 
     fn description(&mut self) -> String {
         match self.variant {
-            GameVariant::SelectFirstStarted => String::from("Which started first, left or right?"),
-            GameVariant::SelectFirstEnded => String::from("Which ended first, left or right?"),
+            GameVariant::SelectFirstStarted => tr!("game-left-right-description-first-started"),
+            GameVariant::SelectFirstEnded => tr!("game-left-right-description-first-ended"),
         }
     }
 }
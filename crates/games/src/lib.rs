@@ -14,12 +14,15 @@
 
 pub mod decades;
 pub mod left_right;
+pub mod multiplayer;
 pub mod order_entities;
 pub mod were_they_alive_when;
 pub mod which_date;
 
-use open_timeline_core::Date;
+use open_timeline_core::{Date, Entity};
+use open_timeline_i18n::tr;
 use rand::{Rng, seq::SliceRandom, thread_rng};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 /// Indicates answer correctness
@@ -29,6 +32,15 @@ pub enum Answer {
     Incorrect,
 }
 
+impl std::fmt::Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Correct => write!(f, "{}", tr!("game-answer-correct")),
+            Answer::Incorrect => write!(f, "{}", tr!("game-answer-incorrect")),
+        }
+    }
+}
+
 /// Implementing types are games that can be managed externally
 pub trait GameManagement<T> {
     // TODO: can this be derived for all games? I think they're all the same
@@ -51,6 +63,99 @@ pub enum GameError {
     NoCorrectAnswer,
     PoolIsNotFullEnough,
     GeneratingQuestion,
+    NoPlayers,
+    DuplicatePlayerName,
+    NoRoundsLeft,
+}
+
+/// Game settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    /// The number of answer options for each question (for games with
+    /// options, e.g. the number of decades to choose from, or the number of
+    /// entities to order)
+    pub options_per_question: usize,
+
+    /// The number of rounds in a game (unlimited if `None`)
+    pub rounds_per_game: Option<u32>,
+
+    /// The fewest entities the pool must hold for a game to be played
+    pub min_pool_size: usize,
+
+    /// Whether only entities that have ended are used
+    pub only_ended_entities: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            options_per_question: 3,
+            rounds_per_game: None,
+            min_pool_size: 1,
+            only_ended_entities: false,
+        }
+    }
+}
+
+impl GameConfig {
+    /// The fewest answer options allowed for each question
+    pub const MIN_OPTIONS_PER_QUESTION: usize = 2;
+
+    /// The most answer options allowed for each question
+    pub const MAX_OPTIONS_PER_QUESTION: usize = 15;
+
+    /// Create the default config with the given number of options per
+    /// question
+    pub fn with_options_per_question(options_per_question: usize) -> Self {
+        Self {
+            options_per_question,
+            ..Self::default()
+        }
+    }
+
+    /// Get the number of answer options for each question (within the allowed
+    /// range)
+    pub fn options_per_question(&self) -> usize {
+        self.options_per_question.clamp(
+            Self::MIN_OPTIONS_PER_QUESTION,
+            Self::MAX_OPTIONS_PER_QUESTION,
+        )
+    }
+
+    /// Whether another round can be played
+    pub fn has_rounds_left(&self, stats: &Stats) -> bool {
+        self.rounds_per_game
+            .is_none_or(|rounds| stats.round < rounds as i32)
+    }
+
+    /// Remove the entities that shouldn't be used from the pool
+    pub fn filter_pool(&self, mut entity_pool: Vec<Entity>) -> Vec<Entity> {
+        if self.only_ended_entities {
+            entity_pool.retain(|entity| entity.end().is_some());
+        }
+        entity_pool
+    }
+
+    /// Check that the pool holds enough entities for a game to be played
+    pub fn check_pool_size(&self, pool_size: usize) -> Result<(), GameError> {
+        match pool_size < self.min_pool_size.max(1) {
+            true => Err(GameError::PoolIsNotFullEnough),
+            false => Ok(()),
+        }
+    }
+
+    /// Check that a round can be set up: there are rounds left, and (for the
+    /// first round) the pool is full enough
+    pub fn check_next_round(&self, stats: &Stats, pool_size: usize) -> Result<(), GameError> {
+        if !self.has_rounds_left(stats) {
+            return Err(GameError::NoRoundsLeft);
+        }
+        if stats.round == 0 {
+            self.check_pool_size(pool_size)?;
+        }
+        Ok(())
+    }
 }
 
 /// Game stats
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Multiplayer "hot-seat" matches.  The players take turns answering the
+//! questions of any game, each with their own [`Stats`].
+//!
+
+use crate::{Answer, GameError, Stats};
+use std::collections::HashSet;
+
+/// A named player and their stats
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Player {
+    pub name: String,
+    pub stats: Stats,
+}
+
+/// A match between named players, who take turns answering questions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    players: Vec<Player>,
+    current_player: usize,
+}
+
+impl Match {
+    /// Create a new match (the names must be unique and not empty)
+    pub fn new<S: ToString>(names: impl IntoIterator<Item = S>) -> Result<Self, GameError> {
+        let names: Vec<String> = names
+            .into_iter()
+            .map(|name| name.to_string().trim().to_string())
+            .collect();
+        if names.is_empty() || names.iter().any(|name| name.is_empty()) {
+            return Err(GameError::NoPlayers);
+        }
+        if names.iter().collect::<HashSet<_>>().len() != names.len() {
+            return Err(GameError::DuplicatePlayerName);
+        }
+        Ok(Self {
+            players: names
+                .into_iter()
+                .map(|name| Player {
+                    name,
+                    stats: Stats::default(),
+                })
+                .collect(),
+            current_player: 0,
+        })
+    }
+
+    /// Get the players (in turn order)
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Get the player whose turn it is to answer
+    pub fn current_player(&self) -> &Player {
+        &self.players[self.current_player]
+    }
+
+    /// Record the current player's answer and pass the next question to the
+    /// next player
+    pub fn record_answer(&mut self, answer: Answer) {
+        let stats = &mut self.players[self.current_player].stats;
+        stats.round += 1;
+        match answer {
+            Answer::Correct => stats.correct_round_count += 1,
+            Answer::Incorrect => stats.incorrect_round_count += 1,
+        }
+        self.current_player = (self.current_player + 1) % self.players.len();
+    }
+
+    /// Get the players with the most correct answers (ties are broken by the
+    /// fewest incorrect answers).  There is more than one if it's a draw, and
+    /// none if no questions have been answered.
+    pub fn winners(&self) -> Vec<&Player> {
+        let best = self
+            .players
+            .iter()
+            .filter(|player| player.stats.round > 0)
+            .map(|player| score(&player.stats))
+            .max();
+        let Some(best) = best else {
+            return Vec::new();
+        };
+        self.players
+            .iter()
+            .filter(|player| player.stats.round > 0 && score(&player.stats) == best)
+            .collect()
+    }
+}
+
+/// The score used to rank players (higher is better)
+fn score(stats: &Stats) -> (i32, i32) {
+    (stats.correct_round_count, -stats.incorrect_round_count)
+}
@@ -4,9 +4,10 @@
 //! Order entities by their start/end date
 //!
 
-use crate::{Answer, GameError, GameManagement, Stats};
+use crate::{Answer, GameConfig, GameError, GameManagement, Stats};
 use open_timeline_core::Entity;
-use rand::{Rng, seq::SliceRandom, thread_rng};
+use open_timeline_i18n::tr;
+use rand::{seq::SliceRandom, thread_rng};
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub enum GameVariant {
@@ -23,23 +24,21 @@ pub struct OrderEntitiesGame {
     pub current_question: Option<Vec<Entity>>,
     correct_answer: Option<Vec<Entity>>,
     pub last_answer: Option<Answer>,
-    pub min_entities_per_round: usize,
-    pub max_entities_per_round: usize,
     pub variant: GameVariant,
+    pub config: GameConfig,
 }
 
 impl OrderEntitiesGame {
     /// Create new OrderEntitiesGame
-    pub fn new() -> Self {
+    pub fn new(config: GameConfig) -> Self {
         Self {
-            min_entities_per_round: 4,
-            max_entities_per_round: 15,
+            config,
             ..Default::default()
         }
     }
 
     pub fn set_entity_pool(&mut self, entity_pool: Vec<Entity>) {
-        self.entity_pool = entity_pool;
+        self.entity_pool = self.config.filter_pool(entity_pool);
     }
 }
 
@@ -68,11 +67,16 @@ impl GameManagement<Vec<Entity>> for OrderEntitiesGame {
     }
 
     fn setup_next_round(&mut self) -> Result<(), GameError> {
-        if self.entity_pool.len() < self.max_entities_per_round {
-            // TODO
+        self.config
+            .check_next_round(&self.stats, self.entity_pool.len())?;
+        if self.entity_pool.len() < 2 {
+            self.current_question = None;
+            return Err(GameError::PoolIsNotFullEnough);
         }
-        let entity_count =
-            rand::thread_rng().gen_range(self.min_entities_per_round..=self.max_entities_per_round);
+        let entity_count = self
+            .config
+            .options_per_question()
+            .min(self.entity_pool.len());
         let mut rng = rand::thread_rng();
         let mut next_q_entities = self
             .entity_pool
@@ -105,11 +109,9 @@ impl GameManagement<Vec<Entity>> for OrderEntitiesGame {
     fn description(&mut self) -> String {
         match self.variant {
             GameVariant::OrderByFirstStarted => {
-                String::from("Order the entities by their start date (earliest at the top)")
-            }
-            GameVariant::OrderByFirstEnded => {
-                String::from("Order the entities by their end date (earliest at the top)")
+                tr!("game-order-entities-description-first-started")
             }
+            GameVariant::OrderByFirstEnded => tr!("game-order-entities-description-first-ended"),
         }
     }
 }
@@ -8,8 +8,9 @@
 //! the answers so that they can be printed out (e.g. to give as homework)
 //!
 
-use crate::{Answer, GameError, GameManagement, Html, Stats};
+use crate::{Answer, GameConfig, GameError, GameManagement, Html, Stats};
 use open_timeline_core::{Entity, HasIdAndName};
+use open_timeline_i18n::tr;
 use rand::seq::{IteratorRandom, SliceRandom};
 use rand::{Rng, thread_rng};
 
@@ -22,6 +23,7 @@ pub struct WereTheyAliveWhenGame {
     pub current_question: Option<Question>,
     correct_answer: Option<bool>,
     pub last_answer: Option<Answer>,
+    pub config: GameConfig,
 }
 
 /// A "were they alive when" question
@@ -42,16 +44,19 @@ impl Question {
 
 impl WereTheyAliveWhenGame {
     /// Create new WereTheyAliveWhenGame
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(config: GameConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
     }
 
     pub fn set_people_entity_pool(&mut self, people_pool: Vec<Entity>) {
-        self.people_pool = people_pool;
+        self.people_pool = self.config.filter_pool(people_pool);
     }
 
     pub fn set_not_people_entity_pool(&mut self, not_people_pool: Vec<Entity>) {
-        self.not_people_pool = not_people_pool;
+        self.not_people_pool = self.config.filter_pool(not_people_pool);
     }
 
     pub fn generate_html_quiz(&mut self, question_count: usize) -> Result<(Html, Html), ()> {
@@ -130,6 +135,8 @@ impl GameManagement<bool> for WereTheyAliveWhenGame {
     }
 
     fn setup_next_round(&mut self) -> Result<(), GameError> {
+        let pool_size = self.people_pool.len().min(self.not_people_pool.len());
+        self.config.check_next_round(&self.stats, pool_size)?;
         let person = self.people_pool.iter().choose(&mut thread_rng()).cloned();
         let not_person = self
             .not_people_pool
@@ -148,7 +155,7 @@ impl GameManagement<bool> for WereTheyAliveWhenGame {
     }
 
     fn description(&mut self) -> String {
-        String::from("State whether the person was alive when some event happened/started/ended")
+        tr!("game-were-they-alive-when-description")
     }
 }
 
@@ -4,8 +4,9 @@
 //! Enter the year/decade in which the entity started/ended
 //!
 
-use crate::{Answer, GameError, GameManagement, Stats};
+use crate::{Answer, GameConfig, GameError, GameManagement, Stats};
 use open_timeline_core::{Date, Entity};
+use open_timeline_i18n::tr;
 use rand::prelude::SliceRandom;
 
 // TODO: also, not just exact year, but also decade option
@@ -34,16 +35,20 @@ pub struct WhichDateGame {
     pub current_selection: Option<Date>,
     pub correct_answer: Option<i32>,
     pub last_answer: Option<Answer>,
+    pub config: GameConfig,
 }
 
 impl WhichDateGame {
     /// Create new WhichDateGame
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(config: GameConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
     }
 
     pub fn set_entity_pool(&mut self, entity_pool: Vec<Entity>) {
-        self.entity_pool = entity_pool;
+        self.entity_pool = self.config.filter_pool(entity_pool);
     }
 
     fn update_correct_answer(&mut self) {
@@ -87,6 +92,8 @@ impl GameManagement<i32> for WhichDateGame {
     }
 
     fn setup_next_round(&mut self) -> Result<(), GameError> {
+        self.config
+            .check_next_round(&self.stats, self.entity_pool.len())?;
         if self.entity_pool.is_empty() {
             self.current_question = None;
             return Err(GameError::PoolIsNotFullEnough);
@@ -100,14 +107,18 @@ impl GameManagement<i32> for WhichDateGame {
     }
 
     fn description(&mut self) -> String {
-        let start_end = match self.variant {
+        let start_or_end = match self.variant {
             GameVariant::StartDate => "start",
             GameVariant::EndDate => "end",
         };
-        let year_decade = match self.year_or_decade {
+        let year_or_decade = match self.year_or_decade {
             YearOrDecade::Year => "year",
             YearOrDecade::Decade => "decade",
         };
-        format!("What is the {start_end} {year_decade}?")
+        tr!(
+            "game-which-date-description",
+            start_or_end = start_or_end,
+            year_or_decade = year_or_decade
+        )
     }
 }
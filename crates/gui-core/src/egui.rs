@@ -6,6 +6,7 @@
 
 mod button;
 mod consts;
+mod dialog;
 mod draw;
 mod error_style;
 mod helpers;
@@ -13,10 +14,12 @@ mod label;
 mod paginator;
 mod shortcuts;
 mod status;
+mod toasts;
 mod window;
 
 pub use button::*;
 pub use consts::*;
+pub use dialog::*;
 pub use draw::*;
 pub use error_style::*;
 pub use helpers::*;
@@ -24,4 +27,5 @@ pub use label::*;
 pub use paginator::*;
 pub use shortcuts::*;
 pub use status::*;
+pub use toasts::*;
 pub use window::*;
@@ -7,9 +7,10 @@
 use crate::{
     ADD_SYMBOL, BEGIN_NEW_SYMBOL, CREATE_BUTTON_WIDTH, CREATE_SYMBOL, DELETE_BUTTON_WIDTH,
     DELETE_SYMBOL, REMOVE_BUTTON_WIDTH, REMOVE_SYMBOL, RESET_BUTTON_WIDTH, RESET_SYMBOL,
-    UPDATE_BUTTON_WIDTH, UPDATE_SYMBOL, body_text_height,
+    UPDATE_BUTTON_WIDTH, UPDATE_SYMBOL, body_text_height, label_symbol_button,
 };
 use eframe::egui::{self, Response, RichText, Ui, Vec2};
+use open_timeline_i18n::tr;
 
 /// Helpers for button drawing to an `egui` context
 pub struct Button {}
@@ -18,56 +19,72 @@ impl Button {
     /// Draw the remove button and return the response
     pub fn remove(ui: &mut Ui) -> Response {
         let button_height = body_text_height(ui);
-        ui.add_sized(
+        let response = ui.add_sized(
             [REMOVE_BUTTON_WIDTH, button_height],
             egui::Button::new(REMOVE_SYMBOL),
-        )
+        );
+        label_symbol_button(response, &tr!("button-remove"))
+    }
+
+    /// Draw the close button and return the response
+    pub fn close(ui: &mut Ui) -> Response {
+        let button_height = body_text_height(ui);
+        let response = ui.add_sized(
+            [REMOVE_BUTTON_WIDTH, button_height],
+            egui::Button::new(REMOVE_SYMBOL),
+        );
+        label_symbol_button(response, &tr!("button-close"))
     }
 
     /// Draw the delete button and return the response
     pub fn delete(ui: &mut Ui) -> Response {
         let button_height = body_text_height(ui);
-        ui.add_sized(
+        let response = ui.add_sized(
             [DELETE_BUTTON_WIDTH, button_height],
             egui::Button::new(DELETE_SYMBOL),
-        )
+        );
+        label_symbol_button(response, &tr!("button-delete"))
     }
 
     /// Draw the reset button and return the response
     pub fn reset(ui: &mut Ui) -> Response {
         let button_height = body_text_height(ui);
-        ui.add_sized(
+        let response = ui.add_sized(
             [RESET_BUTTON_WIDTH, button_height],
             egui::Button::new(RESET_SYMBOL),
-        )
+        );
+        label_symbol_button(response, &tr!("button-reset"))
     }
 
     /// Draw the create button and return the response
     pub fn create(ui: &mut Ui) -> Response {
         let button_height = body_text_height(ui);
-        ui.add_sized(
+        let response = ui.add_sized(
             [CREATE_BUTTON_WIDTH, button_height],
             egui::Button::new(CREATE_SYMBOL),
-        )
+        );
+        label_symbol_button(response, &tr!("button-create"))
     }
 
     /// Draw the update button and return the response
     pub fn update(ui: &mut Ui) -> Response {
         let button_height = body_text_height(ui);
-        ui.add_sized(
+        let response = ui.add_sized(
             [UPDATE_BUTTON_WIDTH, button_height],
             egui::Button::new(UPDATE_SYMBOL),
-        )
+        );
+        label_symbol_button(response, &tr!("button-update"))
     }
 
     /// Draw the begin new button and return the response
     pub fn open_new(ui: &mut Ui) -> Response {
         let button_height = body_text_height(ui);
         let button_width = ui.available_width();
-        ui.add_sized(
+        let response = ui.add_sized(
             [button_width, button_height],
             egui::Button::new(BEGIN_NEW_SYMBOL),
-        )
+        );
+        label_symbol_button(response, &tr!("button-new"))
     }
 
     /// Helper to draw a tall button that fills the available GUI width
@@ -88,6 +105,7 @@ impl Button {
 
         // Display the button for adding a bool expr
         let button = egui::Button::new(ADD_SYMBOL);
-        ui.add_sized(button_size, button)
+        let response = ui.add_sized(button_size, button);
+        label_symbol_button(response, &tr!("button-add"))
     }
 }
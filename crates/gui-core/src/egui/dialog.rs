@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Confirmation dialogs
+//!
+
+use eframe::egui::{Context, Id, Modal};
+use open_timeline_i18n::tr;
+use std::hash::Hash;
+
+/// The choices offered when closing something that has unsaved changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsavedChangesChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+/// Helpers for drawing confirmation dialogs (as modals) to an `egui` context
+pub struct Dialog {}
+
+impl Dialog {
+    /// Draw a "Save / Discard / Cancel" dialog and return the choice made (if
+    /// one has been).  Saving is only offered if `can_save` is `true`, and
+    /// dismissing the dialog (e.g. pressing escape) is the same as cancelling.
+    pub fn unsaved_changes(
+        ctx: &Context,
+        id_salt: impl Hash,
+        message: &str,
+        can_save: bool,
+    ) -> Option<UnsavedChangesChoice> {
+        Self::save_discard_cancel(
+            ctx,
+            id_salt,
+            message,
+            can_save,
+            &tr!("dialog-save"),
+            &tr!("dialog-discard"),
+        )
+    }
+
+    /// The same as [`Self::unsaved_changes()`] but for many things at once
+    /// (e.g. all open windows when the application is closed)
+    pub fn unsaved_changes_all(
+        ctx: &Context,
+        id_salt: impl Hash,
+        message: &str,
+        can_save: bool,
+    ) -> Option<UnsavedChangesChoice> {
+        Self::save_discard_cancel(
+            ctx,
+            id_salt,
+            message,
+            can_save,
+            &tr!("dialog-save-all"),
+            &tr!("dialog-discard-all"),
+        )
+    }
+
+    /// Draw the "Save / Discard / Cancel" dialog with the given button text
+    fn save_discard_cancel(
+        ctx: &Context,
+        id_salt: impl Hash,
+        message: &str,
+        can_save: bool,
+        save_text: &str,
+        discard_text: &str,
+    ) -> Option<UnsavedChangesChoice> {
+        let mut choice = None;
+        let modal = Modal::new(Id::new(("unsaved_changes_dialog", id_salt))).show(ctx, |ui| {
+            crate::Label::sub_heading(ui, &tr!("dialog-unsaved-changes"));
+            ui.label(message);
+            if !can_save {
+                crate::Label::weak(ui, &tr!("dialog-unsaved-changes-cannot-save"));
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(can_save, eframe::egui::Button::new(save_text))
+                    .clicked()
+                {
+                    choice = Some(UnsavedChangesChoice::Save);
+                }
+                if ui.button(discard_text).clicked() {
+                    choice = Some(UnsavedChangesChoice::Discard);
+                }
+                if ui.button(tr!("dialog-cancel")).clicked() {
+                    choice = Some(UnsavedChangesChoice::Cancel);
+                }
+            });
+        });
+        if choice.is_none() && modal.should_close() {
+            choice = Some(UnsavedChangesChoice::Cancel);
+        }
+        choice
+    }
+}
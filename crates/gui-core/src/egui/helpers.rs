@@ -4,7 +4,7 @@
 //! Those things used across the OpenTimeline GUI crate
 //!
 
-use eframe::egui::{Context, TextStyle, Ui};
+use eframe::egui::{Context, Response, TextStyle, Ui, WidgetInfo, WidgetType};
 
 /// Whether the system is using Wayland or not
 pub fn using_wayland() -> bool {
@@ -36,3 +36,10 @@ pub fn widget_y_spacing(ui: &mut Ui) -> f32 {
 pub fn font_size(ctx: &Context) -> f32 {
     ctx.style().text_styles[&TextStyle::Body].size
 }
+
+/// Give a symbol-only button a label for screen readers, and show the same
+/// label as a tooltip
+pub fn label_symbol_button(response: Response, label: &str) -> Response {
+    response.widget_info(|| WidgetInfo::labeled(WidgetType::Button, response.enabled(), label));
+    response.on_hover_text(label)
+}
@@ -5,6 +5,7 @@
 //!
 
 use eframe::egui::{self, Response, RichText, TextStyle, Ui};
+use open_timeline_i18n::tr;
 
 /// Helpers for label drawing to an `egui` context
 pub struct Label {}
@@ -12,7 +13,7 @@ pub struct Label {}
 impl Label {
     /// Draw the "None" label and return the response
     pub fn none(ui: &mut Ui) -> Response {
-        let label = ui.label(RichText::new("None").weak());
+        let label = ui.label(RichText::new(tr!("none")).weak());
         ui.add_space(5.0);
         label
     }
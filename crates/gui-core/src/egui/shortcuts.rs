@@ -17,6 +17,19 @@ impl Shortcut {
     pub fn close_window(ctx: &Context) -> bool {
         keyboard_input_cmd_and_w(ctx)
     }
+
+    pub fn exit_focus(ctx: &Context) -> bool {
+        keyboard_input_escape(ctx)
+    }
+
+    pub fn undo(ctx: &Context) -> bool {
+        keyboard_input_cmd_and_z(ctx)
+    }
+}
+
+/// Has the user pressed `escape`
+pub fn keyboard_input_escape(ctx: &Context) -> bool {
+    ctx.input(|i| i.key_pressed(Key::Escape))
 }
 
 /// Has the user pressed `cmd` + `enter`
@@ -36,6 +49,13 @@ fn keyboard_input_cmd_and_s(ctx: &Context) -> bool {
     ctx.input(|i| i.key_pressed(Key::S) && (i.modifiers.mac_cmd || i.modifiers.command))
 }
 
+/// Has the user pressed `cmd` + `z` (without `shift`, which is redo)
+fn keyboard_input_cmd_and_z(ctx: &Context) -> bool {
+    ctx.input(|i| {
+        i.key_pressed(Key::Z) && !i.modifiers.shift && (i.modifiers.mac_cmd || i.modifiers.command)
+    })
+}
+
 /// Has the user pressed `cmd` + `s`
 fn keyboard_input_cmd_and_w(ctx: &Context) -> bool {
     ctx.input(|i| i.key_pressed(Key::W) && (i.modifiers.mac_cmd || i.modifiers.command))
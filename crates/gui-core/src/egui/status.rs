@@ -5,6 +5,7 @@
 //!
 
 use eframe::egui::{Response, Ui};
+use open_timeline_i18n::tr;
 
 /// Implementing types represent the status of something and can be drawn to an
 /// `egui` context
@@ -19,7 +20,7 @@ impl GuiStatus {
     /// Draw the given status
     pub fn display(ui: &mut Ui, status: &impl DisplayStatus) {
         ui.horizontal(|ui| {
-            crate::Label::strong(ui, "Status");
+            crate::Label::strong(ui, &tr!("status"));
             status.status_display(ui)
         });
     }
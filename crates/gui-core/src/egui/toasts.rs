@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! App-wide notifications ("toasts"): short messages shown in the corner of the
+//! window that disappear by themselves, & which can be clicked on (e.g. to
+//! open the window of what they're about)
+//!
+
+use eframe::egui::{
+    self, Align2, Color32, Context, Frame, Id, Order, RichText, Sense, Stroke, Visuals,
+};
+use open_timeline_i18n::tr;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The most toasts shown at once (the oldest are removed first)
+const MAX_SHOWN: usize = 5;
+
+/// How long a toast stays after the pointer stops hovering over it
+const HOVER_GRACE: Duration = Duration::from_secs(2);
+
+/// The widest a toast is drawn
+const MAX_WIDTH: f32 = 320.0;
+
+/// How serious a toast is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ToastSeverity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastSeverity {
+    /// How long toasts are shown for (longer for problems, so they aren't
+    /// missed)
+    fn duration(self) -> Duration {
+        match self {
+            Self::Info | Self::Success => Duration::from_secs(4),
+            Self::Warning => Duration::from_secs(8),
+            Self::Error => Duration::from_secs(12),
+        }
+    }
+
+    /// The icon drawn next to the message
+    fn icon(self) -> &'static str {
+        match self {
+            Self::Info => "ℹ",
+            Self::Success => "✔",
+            Self::Warning => "⚠",
+            Self::Error => "❌",
+        }
+    }
+
+    /// The colour of the icon & border
+    fn colour(self, visuals: &Visuals) -> Color32 {
+        match self {
+            Self::Info => visuals.hyperlink_color,
+            Self::Success => Color32::from_rgb(0x3c, 0xa3, 0x5a),
+            Self::Warning => visuals.warn_fg_color,
+            Self::Error => visuals.error_fg_color,
+        }
+    }
+}
+
+/// A notification, optionally with something to do (an action, e.g. open a
+/// window) if it's clicked on
+#[derive(Debug)]
+pub struct Toast<A> {
+    severity: ToastSeverity,
+    message: String,
+    action: Option<A>,
+}
+
+impl<A> Toast<A> {
+    /// Create a new toast
+    pub fn new(severity: ToastSeverity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            action: None,
+        }
+    }
+
+    /// Create a new informational toast
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(ToastSeverity::Info, message)
+    }
+
+    /// Create a new toast for something that succeeded
+    pub fn success(message: impl Into<String>) -> Self {
+        Self::new(ToastSeverity::Success, message)
+    }
+
+    /// Create a new warning toast
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(ToastSeverity::Warning, message)
+    }
+
+    /// Create a new toast for something that failed
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(ToastSeverity::Error, message)
+    }
+
+    /// Set what's done if the toast is clicked on (see [`Toasts::show()`])
+    pub fn with_action(mut self, action: A) -> Self {
+        self.action = Some(action);
+        self
+    }
+
+    /// How serious the toast is
+    pub fn severity(&self) -> ToastSeverity {
+        self.severity
+    }
+
+    /// The toast's message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// A toast being shown
+#[derive(Debug)]
+struct ShownToast<A> {
+    /// Distinguishes the toast from the others (for `egui`)
+    id: u64,
+
+    /// The toast
+    toast: Toast<A>,
+
+    /// When the toast is removed
+    expires: Instant,
+}
+
+/// The toasts being shown (newest last)
+#[derive(Debug)]
+pub struct Toasts<A> {
+    shown: VecDeque<ShownToast<A>>,
+    next_id: u64,
+}
+
+impl<A> Toasts<A> {
+    /// Create a new (empty) set of toasts
+    pub fn new() -> Self {
+        Self {
+            shown: VecDeque::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Show a toast (removing the oldest if too many are shown)
+    pub fn push(&mut self, toast: Toast<A>) {
+        if self.shown.len() == MAX_SHOWN {
+            self.shown.pop_front();
+        }
+        let expires = Instant::now() + toast.severity.duration();
+        self.shown.push_back(ShownToast {
+            id: self.next_id,
+            toast,
+            expires,
+        });
+        self.next_id += 1;
+    }
+
+    /// Whether any toasts are being shown
+    pub fn is_empty(&self) -> bool {
+        self.shown.is_empty()
+    }
+
+    /// Draw the toasts in the bottom right corner (newest at the bottom), &
+    /// return the action of the toast clicked on (if any).  Toasts are removed
+    /// once they expire or are clicked on, but don't expire while hovered over.
+    pub fn show(&mut self, ctx: &Context) -> Option<A> {
+        let now = Instant::now();
+        self.shown.retain(|shown| shown.expires > now);
+        if self.shown.is_empty() {
+            return None;
+        }
+
+        let mut clicked = None;
+        egui::Area::new(Id::new("toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .order(Order::Foreground)
+            .interactable(true)
+            .show(ctx, |ui| {
+                ui.set_max_width(MAX_WIDTH);
+                for shown in &mut self.shown {
+                    let colour = shown.toast.severity.colour(ui.visuals());
+                    let frame = Frame::popup(ui.style()).stroke(Stroke::new(1.5, colour));
+                    let response = frame
+                        .show(ui, |ui| {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(RichText::new(shown.toast.severity.icon()).color(colour));
+                                ui.label(&shown.toast.message);
+                            });
+                        })
+                        .response;
+                    let response =
+                        ui.interact(response.rect, Id::new(("toast", shown.id)), Sense::click());
+                    let hover_text = match shown.toast.action {
+                        Some(_) => tr!("toast-click-to-open"),
+                        None => tr!("toast-click-to-dismiss"),
+                    };
+                    let response = response.on_hover_text(hover_text);
+                    if response.hovered() {
+                        shown.expires = shown.expires.max(now + HOVER_GRACE);
+                    }
+                    if response.clicked() {
+                        clicked = Some(shown.id);
+                    }
+                }
+            });
+
+        // Remove the toast clicked on (if any), & do its action
+        let action = clicked.and_then(|id| {
+            let index = self.shown.iter().position(|shown| shown.id == id)?;
+            self.shown.remove(index)?.toast.action
+        });
+
+        // Make sure the toasts are removed when they expire
+        if let Some(next_expiry) = self.shown.iter().map(|shown| shown.expires).min() {
+            ctx.request_repaint_after(next_expiry.saturating_duration_since(now));
+        }
+        action
+    }
+}
+
+impl<A> Default for Toasts<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
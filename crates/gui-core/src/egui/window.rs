@@ -19,4 +19,28 @@ pub trait BreakOutWindow: Reload + CheckForUpdates {
     /// response to the deletion of the underluing data they're working with, and
     /// thus implementing types must also implement the `Deleted` trait.
     fn wants_to_be_closed(&mut self) -> bool;
+
+    /// Whether the window is used to edit something (editors can be opened as
+    /// tabs in a single window rather than each in their own)
+    fn is_editor(&self) -> bool {
+        false
+    }
+
+    /// Whether the window holds changes that haven't been saved (checked before
+    /// the window is closed so that they aren't silently discarded)
+    fn has_unsaved_changes(&mut self) -> bool {
+        false
+    }
+
+    /// Whether the unsaved changes can be saved (e.g. they are valid)
+    fn can_save(&mut self) -> bool {
+        false
+    }
+
+    /// Request that the unsaved changes are saved
+    fn save(&mut self) {}
+
+    /// The window's request to be closed was cancelled (e.g. so that unsaved
+    /// changes aren't discarded)
+    fn cancel_close(&mut self) {}
 }
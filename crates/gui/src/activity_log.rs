@@ -0,0 +1,207 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! A bounded, in-memory record of recent background activity (reloads, CRUD
+//! operations, backups, syncs, local API requests, & any warnings or errors).
+//! It's collected from the logs, so that what happens in background tasks
+//! (particularly failures) can be shown in the GUI.
+//!
+
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// The log target for activity that's worth showing the user (anything else is
+/// only recorded if it's a warning or an error)
+pub const ACTIVITY: &str = "open_timeline_gui::activity";
+
+/// The log target prefix of the API (whose requests are recorded when the
+/// local API server is running)
+const API_TARGET_PREFIX: &str = "open_timeline_www_api";
+
+/// The number of entries kept by default (the oldest are dropped first)
+const DEFAULT_CAPACITY: usize = 500;
+
+/// Something that happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityEntry {
+    /// When it happened
+    pub time: DateTime<Local>,
+
+    /// How serious it is
+    pub level: Level,
+
+    /// What happened
+    pub message: String,
+}
+
+/// Recent activity.  Cheap to clone (clones share the entries).
+#[derive(Debug, Clone)]
+pub struct ActivityLog {
+    /// The entries (oldest first)
+    entries: Arc<Mutex<VecDeque<ActivityEntry>>>,
+
+    /// The maximum number of entries kept
+    capacity: usize,
+}
+
+impl ActivityLog {
+    /// Create an empty activity log that keeps the most recent entries
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create an empty activity log that keeps (at most) `capacity` entries
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Get a layer that records activity in this log (to be used alongside
+    /// any other layers, whatever their levels)
+    pub fn layer<S>(&self) -> impl Layer<S>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        ActivityLayer { log: self.clone() }.with_filter(LevelFilter::INFO)
+    }
+
+    /// Record an entry (dropping the oldest if full)
+    fn push(&self, entry: ActivityEntry) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() == self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    }
+
+    /// Get the entries (newest first)
+    pub fn entries(&self) -> Vec<ActivityEntry> {
+        match self.entries.lock() {
+            Ok(entries) => entries.iter().rev().cloned().collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// Remove all entries
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+impl Default for ActivityLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether a log event should be recorded as activity
+fn is_activity(metadata: &Metadata) -> bool {
+    let target = metadata.target();
+    let level = *metadata.level();
+    target.starts_with("open_timeline")
+        && level <= Level::INFO
+        && (level <= Level::WARN || target == ACTIVITY || target.starts_with(API_TARGET_PREFIX))
+}
+
+/// Layer that records activity in an [`ActivityLog`]
+#[derive(Debug)]
+struct ActivityLayer {
+    log: ActivityLog,
+}
+
+impl<S: Subscriber> Layer<S> for ActivityLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        // Events from the `log` crate have their target in a field
+        let normalized_metadata = event.normalized_metadata();
+        let metadata = normalized_metadata
+            .as_ref()
+            .unwrap_or_else(|| event.metadata());
+        if is_activity(metadata) {
+            let mut message = Message::default();
+            event.record(&mut message);
+            self.log.push(ActivityEntry {
+                time: Local::now(),
+                level: *metadata.level(),
+                message: message.0,
+            });
+        }
+    }
+}
+
+/// An event's message, followed by its other fields (e.g. `status=200`)
+#[derive(Debug, Default)]
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" if self.0.is_empty() => {
+                let _ = write!(self.0, "{value:?}");
+            }
+            "message" => self.0.insert_str(0, &format!("{value:?} ")),
+            name if name.starts_with("log.") => (),
+            name => {
+                let separator = if self.0.is_empty() { "" } else { " " };
+                let _ = write!(self.0, "{separator}{name}={value:?}");
+            }
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn records_activity() {
+        let activity_log = ActivityLog::with_capacity(3);
+        let subscriber = tracing_subscriber::registry().with(activity_log.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            info!(target: ACTIVITY, "Saved entity");
+            debug!(target: ACTIVITY, "Not recorded");
+            info!(target: "open_timeline_gui::app", "Not recorded");
+            warn!(target: "eframe", "Not recorded");
+            warn!(target: "open_timeline_crud", "Failed");
+            info!(target: "open_timeline_www_api::metrics", status = 200, "GET");
+        });
+        let messages: Vec<String> = activity_log
+            .entries()
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(messages, ["GET status=200", "Failed", "Saved entity"]);
+
+        // Bounded (the oldest are dropped)
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(activity_log.layer()),
+            || error!(target: ACTIVITY, "Newest"),
+        );
+        let entries = activity_log.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].message, "Newest");
+        assert_eq!(entries[0].level, Level::ERROR);
+        assert_eq!(entries[2].message, "Failed");
+
+        activity_log.clear();
+        assert!(activity_log.entries().is_empty());
+    }
+}
@@ -5,30 +5,35 @@
 //!
 
 use crate::Config;
+use crate::activity_log::{ACTIVITY, ActivityLog};
 use crate::app_colours::{AppColours, ColourTheme};
+use crate::command_palette::CommandPalette;
 use crate::config::{RuntimeConfig, SharedConfig};
 use crate::games::{
     DecadesGameGui, LeftRightGameGui, OrderEntitiesGameGui, WereTheyAliveWhenGameGui,
     WhichDateGameGui,
 };
 use crate::primary_window::{
-    AppInfoGui, BackupMergeRestoreGui, EntityCountsGui, SearchGui, SettingsGui, StatsGui,
+    ActivityGui, AppInfoGui, BackupMergeRestoreGui, EntityCountsGui, FavouritesGui, ImportGui,
+    InsightsGui, RecentChangesGui, ScriptConsoleGui, SearchGui, SettingsGui, StatsGui, SyncGui,
     TagCountsGui, TimelineCountsGui,
 };
-use crate::shortcuts::global_shortcuts;
+use crate::shortcuts::{OpenTimelineShortcut, global_shortcuts};
+use crate::spawn_operation;
 use crate::windows::{
     AppColoursGui, BreakOutWindows, EntityEditGui, EntityViewGui, TagBulkEditGui, TagViewGui,
-    TimelineEditGui, TimelineViewGui,
+    TimelineCompareGui, TimelineEditGui, TimelineViewGui,
 };
 use bool_tag_expr::Tag;
 use eframe::App;
 use eframe::egui::{
     self, Align, Button, CentralPanel, Context, Layout, OpenUrl, Pos2, SidePanel, Ui, Vec2,
+    ViewportCommand,
 };
-use open_timeline_core::OpenTimelineId;
-use open_timeline_crud::db_url_from_path;
+use open_timeline_core::{Date, OpenTimelineId, ReducedTimeline};
+use open_timeline_crud::{CachedStats, db_url_from_path};
 use open_timeline_gui_core::{
-    BreakOutWindow, CheckForUpdates, Draw, Reload, using_wayland, widget_x_spacing,
+    BreakOutWindow, CheckForUpdates, Draw, Reload, Toast, Toasts, using_wayland, widget_x_spacing,
     widget_y_spacing,
 };
 use sqlx::{Pool, Sqlite, SqlitePool};
@@ -39,13 +44,21 @@ use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 /// Indicates which of the tabs in the main window is selected.
 #[derive(Debug, PartialEq, Eq, Clone)]
-enum MainTabSelected {
+pub(crate) enum MainTabSelected {
     Search,
+    Favourites,
+    RecentChanges,
     Entities,
     Tags,
     Timelines,
     Stats,
+    Insights,
     BackupRestoreMerge,
+    Sync,
+    ScriptConsole,
+    Import,
+    Activity,
+    Windows,
 
     GameDecades,
     GameLeftRight,
@@ -58,14 +71,47 @@ enum MainTabSelected {
 }
 
 impl MainTabSelected {
-    fn to_label_text(&self) -> String {
+    /// All of the tabs (in side bar order)
+    pub(crate) const ALL: [MainTabSelected; 21] = [
+        Self::Search,
+        Self::Favourites,
+        Self::RecentChanges,
+        Self::Entities,
+        Self::Tags,
+        Self::Timelines,
+        Self::Stats,
+        Self::Insights,
+        Self::BackupRestoreMerge,
+        Self::Sync,
+        Self::ScriptConsole,
+        Self::Import,
+        Self::Activity,
+        Self::Windows,
+        Self::GameDecades,
+        Self::GameLeftRight,
+        Self::GameOrderEntities,
+        Self::GameAliveWhen,
+        Self::GameWhichDate,
+        Self::Settings,
+        Self::AppInfo,
+    ];
+
+    pub(crate) fn to_label_text(&self) -> String {
         match self {
             Self::Search => String::from("Search"),
+            Self::Favourites => String::from("Favourites"),
+            Self::RecentChanges => String::from("Recent Changes"),
             Self::Entities => String::from("Entities"),
             Self::Tags => String::from("Tags"),
             Self::Timelines => String::from("Timelines"),
             Self::Stats => String::from("Stats"),
+            Self::Insights => String::from("Insights"),
             Self::BackupRestoreMerge => String::from("Backup | Merge | Restore"),
+            Self::Sync => String::from("Sync"),
+            Self::ScriptConsole => String::from("Script Console"),
+            Self::Import => String::from("Import"),
+            Self::Activity => String::from("Activity"),
+            Self::Windows => String::from("Windows"),
 
             Self::GameDecades => String::from("Decades"),
             Self::GameLeftRight => String::from("Left/Right"),
@@ -88,8 +134,25 @@ pub enum ActionRequest {
     Timeline(EntityOrTimelineActionRequest),
     Tag(TagActionRequest),
 
+    /// Compare the timeline with another (side by side)
+    CompareTimelines(ReducedTimeline),
+
+    /// Create an entity with its start date filled in (e.g. the year double
+    /// clicked on in a timeline)
+    CreateEntityStarting(Date),
+
     // TODO: shouldn't send a channel, I think
     AppColours(UnboundedSender<AppColours>),
+
+    /// Show a toast (which may request something else when clicked on)
+    Toast(Box<Toast<ActionRequest>>),
+}
+
+impl ActionRequest {
+    /// Request that the toast be shown
+    pub fn toast(toast: Toast<ActionRequest>) -> Self {
+        Self::Toast(Box::new(toast))
+    }
 }
 
 /// All possible action requests for entities and timelines
@@ -132,12 +195,21 @@ pub struct OpenTimelineApp {
     /// Which of the sidebar tabs in the main window is selected
     tab_selected: MainTabSelected,
 
+    /// The command palette overlay (Cmd/Ctrl + K)
+    command_palette: CommandPalette,
+
     /// All pop-out windows
     windows: BreakOutWindows,
 
     /// The search panel of the main window
     search_gui: SearchGui,
 
+    /// The starred & recently opened panel of the main window
+    favourites_gui: FavouritesGui,
+
+    /// The recently created & updated panel of the main window
+    recent_changes_gui: RecentChangesGui,
+
     /// The entity count panel of the main window
     entity_counts_gui: EntityCountsGui,
 
@@ -151,15 +223,33 @@ pub struct OpenTimelineApp {
     /// The stats panel of the main window
     stats_gui: StatsGui,
 
+    /// The insights panel of the main window
+    insights_gui: InsightsGui,
+
     /// The backup|merge|restore panel of the main window
     backup_merge_restore_gui: BackupMergeRestoreGui,
 
+    /// The sync panel of the main window
+    sync_gui: SyncGui,
+
+    /// The script console panel of the main window
+    script_console_gui: ScriptConsoleGui,
+
+    /// The import panel of the main window
+    import_gui: ImportGui,
+
+    /// The activity panel of the main window
+    activity_gui: ActivityGui,
+
     /// The settings panel of the main window
     settings_gui: SettingsGui,
 
     /// The app info panel of the main window
     app_info_gui: AppInfoGui,
 
+    /// The toasts shown (e.g. the results of saving & deleting)
+    toasts: Toasts<ActionRequest>,
+
     /// Unbounded channel for requesting actions on entites, timelines, and
     /// tags.  e.g. a request to edit an entity.
     channel_action_request: UnboundedChannel<ActionRequest>,
@@ -174,6 +264,9 @@ pub struct OpenTimelineApp {
     /// received on `channel_crud_operation_executed`)
     reload_required: bool,
 
+    /// The database statistics cache (invalidated after each CRUD operation)
+    cached_stats: CachedStats,
+
     /// The "decades" game panel of the main window
     game_decades: DecadesGameGui,
 
@@ -194,8 +287,9 @@ pub struct OpenTimelineApp {
 }
 
 impl OpenTimelineApp {
-    /// Create a new `OpenTimelineApp`
-    pub fn new() -> Self {
+    /// Create a new `OpenTimelineApp` (showing the activity recorded in the
+    /// activity log)
+    pub fn new(activity_log: ActivityLog) -> Self {
         let channel_action_request: UnboundedChannel<ActionRequest> =
             tokio::sync::mpsc::unbounded_channel().into();
         let channel_crud_operation_executed: UnboundedChannel<()> =
@@ -203,7 +297,7 @@ impl OpenTimelineApp {
 
         // Config
         let (tx, rx) = tokio::sync::oneshot::channel();
-        tokio::spawn(async move {
+        spawn_operation!(async move {
             let result = async move {
                 Config::ensure_setup().await?;
                 Config::load()
@@ -217,12 +311,15 @@ impl OpenTimelineApp {
             Err(error) => panic!("Initial config error: {error}"),
         };
 
+        // Language
+        open_timeline_i18n::set_language(config.language());
+
         // Path to database
         let db_path = Arc::new(RwLock::new(config.database_path()));
 
         // Database pool
         let (tx, rx) = tokio::sync::oneshot::channel();
-        tokio::spawn(async move {
+        spawn_operation!(async move {
             let result: Result<Pool<Sqlite>, sqlx::Error> = async move {
                 let db_path = db_path.read().await;
                 let db_url = db_url_from_path(&db_path);
@@ -236,18 +333,33 @@ impl OpenTimelineApp {
             Ok(db_pool) => db_pool,
             Err(error) => panic!("Initial SQLite pool error: {error}"),
         };
+        let cached_stats = CachedStats::default();
         let shared_config = Arc::new(RwLock::new(RuntimeConfig {
             db_pool: db_pool,
             config: config.clone(),
+            cached_stats: cached_stats.clone(),
         }));
 
         Self {
             position: None,
             tab_selected: MainTabSelected::Search,
+            command_palette: CommandPalette::new(
+                Arc::clone(&shared_config),
+                channel_action_request.tx.clone(),
+            ),
             windows: BreakOutWindows::default(),
             search_gui: SearchGui::new(
                 Arc::clone(&shared_config),
                 channel_action_request.tx.clone(),
+                channel_crud_operation_executed.tx.clone(),
+            ),
+            favourites_gui: FavouritesGui::new(
+                Arc::clone(&shared_config),
+                channel_action_request.tx.clone(),
+            ),
+            recent_changes_gui: RecentChangesGui::new(
+                Arc::clone(&shared_config),
+                channel_action_request.tx.clone(),
             ),
             entity_counts_gui: EntityCountsGui::new(
                 Arc::clone(&shared_config),
@@ -262,10 +374,26 @@ impl OpenTimelineApp {
                 channel_action_request.tx.clone(),
             ),
             stats_gui: StatsGui::new(Arc::clone(&shared_config)),
+            insights_gui: InsightsGui::new(Arc::clone(&shared_config)),
             backup_merge_restore_gui: BackupMergeRestoreGui::new(
                 Arc::clone(&shared_config),
                 channel_crud_operation_executed.tx.clone(),
+                channel_action_request.tx.clone(),
+            ),
+            sync_gui: SyncGui::new(
+                Arc::clone(&shared_config),
+                channel_crud_operation_executed.tx.clone(),
+                config.sync.clone(),
+            ),
+            script_console_gui: ScriptConsoleGui::new(
+                Arc::clone(&shared_config),
+                channel_crud_operation_executed.tx.clone(),
+            ),
+            import_gui: ImportGui::new(
+                Arc::clone(&shared_config),
+                channel_crud_operation_executed.tx.clone(),
             ),
+            activity_gui: ActivityGui::new(activity_log),
             settings_gui: SettingsGui::new(
                 config,
                 Arc::clone(&shared_config),
@@ -273,8 +401,10 @@ impl OpenTimelineApp {
                 channel_crud_operation_executed.tx.clone(),
             ),
             app_info_gui: AppInfoGui::new(),
+            toasts: Toasts::new(),
             channel_action_request,
             channel_crud_operation_executed,
+            cached_stats,
             reload_required: false,
             game_decades: DecadesGameGui::new(Arc::clone(&shared_config)),
             game_left_right: LeftRightGameGui::new(Arc::clone(&shared_config)),
@@ -285,6 +415,14 @@ impl OpenTimelineApp {
         }
     }
 
+    fn select_tab(&mut self, tab_variant: MainTabSelected) {
+        // Stars & opened windows don't trigger app-wide reloads
+        if tab_variant == MainTabSelected::Favourites {
+            self.favourites_gui.request_reload();
+        }
+        self.tab_selected = tab_variant;
+    }
+
     fn draw_side_bar_option(
         &mut self,
         _ctx: &Context,
@@ -299,7 +437,7 @@ impl OpenTimelineApp {
             );
             let tab = ui.add(tab);
             if tab.clicked() {
-                self.tab_selected = tab_variant;
+                self.select_tab(tab_variant);
             }
             if separator_after {
                 ui.separator();
@@ -350,11 +488,19 @@ impl OpenTimelineApp {
         ui.separator();
 
         self.draw_side_bar_option(ctx, ui, MainTabSelected::Search, true);
+        self.draw_side_bar_option(ctx, ui, MainTabSelected::Favourites, true);
+        self.draw_side_bar_option(ctx, ui, MainTabSelected::RecentChanges, true);
         self.draw_side_bar_option(ctx, ui, MainTabSelected::Entities, true);
         self.draw_side_bar_option(ctx, ui, MainTabSelected::Tags, true);
         self.draw_side_bar_option(ctx, ui, MainTabSelected::Timelines, true);
         self.draw_side_bar_option(ctx, ui, MainTabSelected::Stats, true);
+        self.draw_side_bar_option(ctx, ui, MainTabSelected::Insights, true);
         self.draw_side_bar_option(ctx, ui, MainTabSelected::BackupRestoreMerge, true);
+        self.draw_side_bar_option(ctx, ui, MainTabSelected::Sync, true);
+        self.draw_side_bar_option(ctx, ui, MainTabSelected::ScriptConsole, true);
+        self.draw_side_bar_option(ctx, ui, MainTabSelected::Import, true);
+        self.draw_side_bar_option(ctx, ui, MainTabSelected::Activity, true);
+        self.draw_side_bar_option(ctx, ui, MainTabSelected::Windows, true);
         ui.horizontal(|ui| {
             let space = widget_x_spacing(ui) / 2.0;
             ui.add_space(space);
@@ -381,8 +527,18 @@ impl OpenTimelineApp {
         match self.tab_selected {
             MainTabSelected::Search => {
                 self.windows.draw(ctx, ui);
+                self.search_gui
+                    .set_keyboard_navigation_enabled(!self.command_palette.is_open());
                 self.search_gui.draw(ctx, ui);
             }
+            MainTabSelected::Favourites => {
+                self.windows.draw(ctx, ui);
+                self.favourites_gui.draw(ctx, ui);
+            }
+            MainTabSelected::RecentChanges => {
+                self.windows.draw(ctx, ui);
+                self.recent_changes_gui.draw(ctx, ui);
+            }
             MainTabSelected::Entities => {
                 self.windows.draw(ctx, ui);
                 self.entity_counts_gui.draw(ctx, ui);
@@ -399,10 +555,30 @@ impl OpenTimelineApp {
                 self.windows.draw(ctx, ui);
                 self.stats_gui.draw(ctx, ui);
             }
+            MainTabSelected::Insights => {
+                self.windows.draw(ctx, ui);
+                self.insights_gui.draw(ctx, ui);
+            }
             MainTabSelected::BackupRestoreMerge => {
                 self.backup_merge_restore_gui.draw(ctx, ui);
             }
+            MainTabSelected::Sync => {
+                self.sync_gui.draw(ctx, ui);
+            }
+            MainTabSelected::ScriptConsole => {
+                self.script_console_gui.draw(ctx, ui);
+            }
+            MainTabSelected::Import => {
+                self.import_gui.draw(ctx, ui);
+            }
+            MainTabSelected::Activity => {
+                self.activity_gui.draw(ctx, ui);
+            }
 
+            MainTabSelected::Windows => {
+                self.windows.draw(ctx, ui);
+                self.windows.draw_open_windows(ctx, ui);
+            }
             MainTabSelected::GameDecades => self.game_decades.draw(ctx, ui),
             MainTabSelected::GameLeftRight => self.game_left_right.draw(ctx, ui),
             MainTabSelected::GameOrderEntities => self.game_order_entities.draw(ctx, ui),
@@ -441,7 +617,7 @@ impl OpenTimelineApp {
                         EntityEditGui::new_window_for_editing_entity(db, tx_req, tx_crud, id),
                     ),
                     EntityOrTimelineActionRequest::ViewExisting(id) => {
-                        Box::new(EntityViewGui::new(db, tx_req, id))
+                        Box::new(EntityViewGui::new(db, ctx, tx_req, id))
                     }
                 },
                 // Timeline windows
@@ -453,7 +629,7 @@ impl OpenTimelineApp {
                         TimelineEditGui::new_window_for_editing_timeline(db, tx_req, tx_crud, id),
                     ),
                     EntityOrTimelineActionRequest::ViewExisting(id) => {
-                        Box::new(TimelineViewGui::new(db, ctx, tx_req, id))
+                        Box::new(TimelineViewGui::new(db, ctx, tx_req, tx_crud, id))
                     }
                 },
                 // Tag windows
@@ -465,6 +641,16 @@ impl OpenTimelineApp {
                         Box::new(TagViewGui::new(db, tx_req, tag))
                     }
                 },
+                // Timeline comparison windows
+                ActionRequest::CompareTimelines(timeline) => {
+                    Box::new(TimelineCompareGui::new(db, ctx, tx_req, timeline))
+                }
+                // Entity creation windows with the start date filled in
+                ActionRequest::CreateEntityStarting(start) => {
+                    Box::new(EntityEditGui::new_window_for_creating_entity_starting(
+                        db, tx_req, tx_crud, start,
+                    ))
+                }
                 // Colour windows
                 ActionRequest::AppColours(tx_app_colours) => {
                     debug!("recv ActionRequest::AppColours");
@@ -472,6 +658,11 @@ impl OpenTimelineApp {
                     let config = self.shared_config.blocking_read().config.clone();
                     Box::new(AppColoursGui::new(config, tx_req, tx_app_colours))
                 }
+                // Toasts (not windows)
+                ActionRequest::Toast(toast) => {
+                    self.toasts.push(*toast);
+                    return;
+                }
             };
             self.windows.insert(ctx, self.position, window);
         }
@@ -498,20 +689,34 @@ impl App for OpenTimelineApp {
         // Check if there have been any CRUD operations and thus if a reload is in order
         if self.channel_crud_operation_executed.rx.try_recv().is_ok() {
             debug!("CRUD operation executed");
+            info!(target: ACTIVITY, "Reloading after the database changed");
+            self.cached_stats.invalidate();
             self.reload_required = true;
             self.windows.request_reload();
             self.search_gui.request_reload();
+            self.favourites_gui.request_reload();
+            self.recent_changes_gui.request_reload();
             self.entity_counts_gui.request_reload();
             self.entity_tag_counts_gui.request_reload();
             self.timeline_counts_gui.request_reload();
             self.entity_tag_counts_gui.request_reload();
             self.stats_gui.request_reload();
+            self.insights_gui.request_reload();
         }
 
         // Check for global shortcuts
         global_shortcuts(ctx, &mut self.channel_action_request.tx);
+        if OpenTimelineShortcut::command_palette(ctx) {
+            self.command_palette.open();
+        }
 
-        // Open any new windows that need to be opened
+        // Open any new windows that need to be opened (as tabs if so configured)
+        let editors_as_tabs = self
+            .shared_config
+            .blocking_read()
+            .config
+            .open_editors_as_tabs;
+        self.windows.set_editors_as_tabs(editors_as_tabs);
         self.create_any_new_windows(ctx);
 
         // Draw the side panel
@@ -524,6 +729,28 @@ impl App for OpenTimelineApp {
             self.draw_central_panel(ctx, ui);
         });
 
+        // Closing the application asks what to do with any unsaved changes
+        if ctx.input(|i| i.viewport().close_requested()) && !self.windows.request_exit() {
+            ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+        }
+        self.windows.draw_exit_dialog(ctx);
+
+        // Draw the command palette (if open)
+        if let Some(tab_variant) = self.command_palette.draw(ctx) {
+            self.select_tab(tab_variant);
+        }
+
+        // Draw the toasts (clicking one may e.g. open a window)
+        if let Some(action_request) = self.toasts.show(ctx) {
+            let _ = self.channel_action_request.tx.send(action_request);
+        }
+
+        // Handle any requests made while drawing (e.g. toasts) without waiting
+        // for input
+        if !self.channel_action_request.rx.is_empty() {
+            ctx.request_repaint();
+        }
+
         // The reload is requested in a single frame
         self.reload_required = false;
 
@@ -539,10 +766,16 @@ impl CheckForUpdates for OpenTimelineApp {
         // TODO: rename these fields
         // Main window panels
         self.backup_merge_restore_gui.check_for_updates();
+        self.sync_gui.check_for_updates();
+        self.script_console_gui.check_for_updates();
+        self.import_gui.check_for_updates();
         self.settings_gui.check_for_updates();
         self.stats_gui.check_for_updates();
+        self.insights_gui.check_for_updates();
         self.entity_counts_gui.check_for_updates();
         self.search_gui.check_for_updates();
+        self.favourites_gui.check_for_updates();
+        self.recent_changes_gui.check_for_updates();
         self.entity_tag_counts_gui.check_for_updates();
         self.timeline_counts_gui.check_for_updates();
 
@@ -553,10 +786,17 @@ impl CheckForUpdates for OpenTimelineApp {
     fn waiting_for_updates(&mut self) -> bool {
         // Main window panels
         if self.backup_merge_restore_gui.waiting_for_updates()
+            || self.sync_gui.waiting_for_updates()
+            || self.script_console_gui.waiting_for_updates()
+            || self.import_gui.waiting_for_updates()
             || self.settings_gui.waiting_for_updates()
             || self.stats_gui.waiting_for_updates()
+            || self.insights_gui.waiting_for_updates()
             || self.entity_counts_gui.waiting_for_updates()
             || self.search_gui.waiting_for_updates()
+            || self.favourites_gui.waiting_for_updates()
+            || self.recent_changes_gui.waiting_for_updates()
+            || self.command_palette.waiting_for_updates()
             || self.entity_tag_counts_gui.waiting_for_updates()
             || self.timeline_counts_gui.waiting_for_updates()
         {
@@ -17,6 +17,7 @@ pub enum ColourTheme {
     Light,
     Dark,
     Siphonophore,
+    HighContrast,
     Custom(AppColours),
 }
 
@@ -47,9 +48,15 @@ impl ColourTheme {
                 }
             }
             ColourTheme::Siphonophore => AppColours::siphonophore_theme().timeline_colours,
+            ColourTheme::HighContrast => TimelineColours::high_contrast(),
             ColourTheme::Custom(app_colours) => app_colours.timeline_colours,
         }
     }
+
+    /// Whether the theme calls for larger text & thicker lines
+    pub fn is_high_contrast(&self) -> bool {
+        *self == ColourTheme::HighContrast
+    }
 }
 
 /// Colours for the desktop GUI
@@ -99,6 +106,7 @@ impl AppColours {
             ColourTheme::Light => Self::use_default_light_theme(ctx),
             ColourTheme::Dark => Self::use_default_dark_theme(ctx),
             ColourTheme::Siphonophore => Self::use_siphonophore_theme(ctx),
+            ColourTheme::HighContrast => Self::use_high_contrast_theme(ctx),
             ColourTheme::Custom(app_colours) => Self::use_custom_theme(ctx, app_colours),
         }
     }
@@ -170,6 +178,36 @@ impl AppColours {
         Self::siphonophore_theme().set(ctx);
     }
 
+    /// White & yellow on black, with thicker widget outlines
+    fn use_high_contrast_theme(ctx: &Context) {
+        Self::use_default_dark_theme(ctx);
+        ctx.style_mut(|style| {
+            let visuals = &mut style.visuals;
+            visuals.override_text_color = Some(Color32::WHITE);
+            visuals.window_fill = Color32::BLACK;
+            visuals.panel_fill = Color32::BLACK;
+            visuals.extreme_bg_color = Color32::BLACK;
+            visuals.text_edit_bg_color = Some(Color32::BLACK);
+            visuals.hyperlink_color = Color32::YELLOW;
+            visuals.selection.bg_fill = Color32::YELLOW;
+            visuals.selection.stroke = Stroke::new(2.0, Color32::BLACK);
+            for widget in [
+                &mut visuals.widgets.noninteractive,
+                &mut visuals.widgets.inactive,
+                &mut visuals.widgets.hovered,
+                &mut visuals.widgets.active,
+                &mut visuals.widgets.open,
+            ] {
+                widget.bg_fill = Color32::BLACK;
+                widget.weak_bg_fill = Color32::BLACK;
+                widget.bg_stroke = Stroke::new(2.0, Color32::WHITE);
+                widget.fg_stroke = Stroke::new(2.0, Color32::WHITE);
+            }
+            visuals.widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+            visuals.widgets.active.bg_stroke = Stroke::new(2.0, Color32::YELLOW);
+        });
+    }
+
     fn use_custom_theme(ctx: &Context, app_colours: AppColours) {
         app_colours.set(ctx);
     }
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Copying & pasting entities as JSON (between windows, databases, and other
+//! apps)
+//!
+
+use eframe::egui::{Context, Event};
+use open_timeline_core::{Entity, ReducedEntity, ReducedTimeline};
+use open_timeline_gui_core::window_has_focus;
+
+/// Put the entity's JSON on the clipboard (the same JSON used for backups)
+pub fn copy_entity_to_clipboard(ctx: &Context, entity: &Entity) {
+    match serde_json::to_string_pretty(entity) {
+        Ok(json) => ctx.copy_text(json),
+        Err(error) => warn!("Unable to copy entity to the clipboard: {error}"),
+    }
+}
+
+/// Get the entities pasted into the window this frame (if any).  The JSON can
+/// be a single entity or a list of them.  Pasting into a text input doesn't
+/// count (so that pasting text still works as normal).
+pub fn pasted_entities(ctx: &Context) -> Option<Vec<Entity>> {
+    if !window_has_focus(ctx) || ctx.memory(|memory| memory.focused().is_some()) {
+        return None;
+    }
+    let text = ctx.input(|input| {
+        input.events.iter().find_map(|event| match event {
+            Event::Paste(text) => Some(text.clone()),
+            _ => None,
+        })
+    })?;
+    if let Ok(entity) = serde_json::from_str::<Entity>(&text) {
+        return Some(vec![entity]);
+    }
+    match serde_json::from_str::<Vec<Entity>>(&text) {
+        Ok(entities) if !entities.is_empty() => Some(entities),
+        _ => {
+            debug!("Pasted text isn't entity JSON");
+            None
+        }
+    }
+}
+
+/// Implementing types can be copied to the clipboard
+pub trait CopyToClipboard {
+    /// Whether this can be copied to the clipboard
+    fn can_copy_to_clipboard(&self) -> bool;
+}
+
+impl CopyToClipboard for ReducedEntity {
+    fn can_copy_to_clipboard(&self) -> bool {
+        true
+    }
+}
+
+impl CopyToClipboard for ReducedTimeline {
+    fn can_copy_to_clipboard(&self) -> bool {
+        false
+    }
+}
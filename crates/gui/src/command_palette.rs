@@ -0,0 +1,337 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The command palette (Cmd/Ctrl + K).  Fuzzy-search actions, main window tabs,
+//! entities and timelines, and run/open them from the keyboard.
+//!
+
+use crate::app::{ActionRequest, EntityOrTimelineActionRequest, MainTabSelected};
+use crate::config::SharedConfig;
+use crate::spawn_transaction_no_commit_send_result;
+use eframe::egui::{Context, Id, Key, Modal, Modifiers, RichText, ScrollArea, TextEdit};
+use open_timeline_core::{
+    IsReducedCollection, IsReducedType, OpenTimelineId, ReducedEntities, ReducedTimelines,
+};
+use open_timeline_crud::{CrudError, FetchAll};
+use sqlx::{Sqlite, Transaction};
+use std::sync::Arc;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
+
+/// The maximum number of matches shown
+const MAX_RESULTS: usize = 15;
+
+/// The width of the palette
+const PALETTE_WIDTH: f32 = 500.0;
+
+/// What happens when a palette item is chosen
+#[derive(Debug, Clone)]
+enum PaletteCommand {
+    NewEntity,
+    NewTimeline,
+    SwitchTab(MainTabSelected),
+    Entity(OpenTimelineId),
+    Timeline(OpenTimelineId),
+}
+
+/// Something that can be chosen in the palette
+#[derive(Debug, Clone)]
+struct PaletteItem {
+    /// What is matched against & shown
+    label: String,
+
+    /// What sort of item it is (shown alongside the label)
+    kind: &'static str,
+
+    /// What happens when it is chosen
+    command: PaletteCommand,
+}
+
+/// The command palette overlay shown in the main window
+#[derive(Debug)]
+pub struct CommandPalette {
+    /// Whether the palette is shown
+    open: bool,
+
+    /// The search input buffer
+    query: String,
+
+    /// Everything that can be chosen
+    items: Vec<PaletteItem>,
+
+    /// The indices (into `items`) of the best matches, best first
+    results: Vec<usize>,
+
+    /// The index (into `results`) of the highlighted match
+    selected: usize,
+
+    /// Receive all entities & timelines
+    rx_items: Option<Receiver<Result<(ReducedEntities, ReducedTimelines), CrudError>>>,
+
+    /// Used to request new windows
+    tx_action_request: UnboundedSender<ActionRequest>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl CommandPalette {
+    /// Create a new (closed) command palette
+    pub fn new(
+        shared_config: SharedConfig,
+        tx_action_request: UnboundedSender<ActionRequest>,
+    ) -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            items: vec![],
+            results: vec![],
+            selected: 0,
+            rx_items: None,
+            tx_action_request,
+            shared_config,
+        }
+    }
+
+    /// Open the palette (fetching the entities & timelines afresh)
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.items = static_items();
+        self.update_results();
+
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_items = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_items(transaction).await }
+        );
+    }
+
+    /// Whether the palette is open
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Whether the palette is waiting for the entities & timelines
+    pub fn waiting_for_updates(&self) -> bool {
+        self.rx_items.is_some()
+    }
+
+    /// Receive the entities & timelines (if they have been fetched)
+    fn check_items_response(&mut self) {
+        if let Some(rx) = self.rx_items.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_items = None;
+                    match result {
+                        Ok((entities, timelines)) => {
+                            self.items
+                                .extend(timelines.collection().iter().map(|timeline| {
+                                    PaletteItem {
+                                        label: timeline.name().to_string(),
+                                        kind: "Timeline",
+                                        command: PaletteCommand::Timeline(timeline.id()),
+                                    }
+                                }));
+                            self.items
+                                .extend(entities.collection().iter().map(|entity| PaletteItem {
+                                    label: entity.name().to_string(),
+                                    kind: "Entity",
+                                    command: PaletteCommand::Entity(entity.id()),
+                                }));
+                            self.update_results();
+                        }
+                        Err(error) => warn!("Command palette fetch error: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_items = None,
+            }
+        }
+    }
+
+    /// Find the best matches for the query
+    fn update_results(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                fuzzy_score(&self.query, &item.label).map(|score| (score, index))
+            })
+            .collect();
+        scored.sort_by(|(score_a, index_a), (score_b, index_b)| {
+            score_b.cmp(score_a).then_with(|| {
+                let (a, b) = (&self.items[*index_a].label, &self.items[*index_b].label);
+                a.len().cmp(&b.len())
+            })
+        });
+        self.results = scored
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, index)| index)
+            .collect();
+        self.selected = 0;
+    }
+
+    /// Draw the palette (if it's open).  Returns the main window tab to switch
+    /// to if one was chosen.
+    pub fn draw(&mut self, ctx: &Context) -> Option<MainTabSelected> {
+        if !self.open {
+            return None;
+        }
+        self.check_items_response();
+
+        // Keyboard navigation (consumed before the text input sees it)
+        let (down, up, edit, enter) = ctx.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::COMMAND, Key::Enter),
+                i.consume_key(Modifiers::NONE, Key::Enter),
+            )
+        });
+        if down && self.selected + 1 < self.results.len() {
+            self.selected += 1;
+        }
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        let mut chosen = (edit || enter)
+            .then(|| self.results.get(self.selected).copied())
+            .flatten()
+            .map(|index| (index, edit));
+
+        let modal = Modal::new(Id::new("command_palette")).show(ctx, |ui| {
+            ui.set_width(PALETTE_WIDTH);
+            let input = ui.add(
+                TextEdit::singleline(&mut self.query)
+                    .hint_text("Search actions, tabs, entities & timelines")
+                    .desired_width(f32::INFINITY),
+            );
+            input.request_focus();
+            if input.changed() {
+                self.update_results();
+            }
+            ui.separator();
+
+            if self.results.is_empty() {
+                open_timeline_gui_core::Label::none(ui);
+            }
+            ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (position, index) in self.results.iter().enumerate() {
+                    let item = &self.items[*index];
+                    let selected = position == self.selected;
+                    ui.horizontal(|ui| {
+                        let response = ui.selectable_label(selected, &item.label);
+                        ui.label(RichText::new(item.kind).weak());
+                        if response.clicked() {
+                            chosen = Some((*index, false));
+                        }
+                        if selected {
+                            response.scroll_to_me(None);
+                        }
+                    });
+                }
+            });
+            ui.separator();
+            ui.label(RichText::new("Enter to open/view • Cmd/Ctrl + Enter to edit").weak());
+        });
+
+        if modal.should_close() {
+            self.open = false;
+        }
+        let (index, edit) = chosen?;
+        self.open = false;
+        let command = self.items[index].command.clone();
+        self.execute(command, edit)
+    }
+
+    /// Run a command (returning the tab to switch to if there is one)
+    fn execute(&mut self, command: PaletteCommand, edit: bool) -> Option<MainTabSelected> {
+        let existing = |id| match edit {
+            true => EntityOrTimelineActionRequest::EditExisting(id),
+            false => EntityOrTimelineActionRequest::ViewExisting(id),
+        };
+        let request = match command {
+            PaletteCommand::SwitchTab(tab) => return Some(tab),
+            PaletteCommand::NewEntity => {
+                ActionRequest::Entity(EntityOrTimelineActionRequest::CreateNew)
+            }
+            PaletteCommand::NewTimeline => {
+                ActionRequest::Timeline(EntityOrTimelineActionRequest::CreateNew)
+            }
+            PaletteCommand::Entity(id) => ActionRequest::Entity(existing(id)),
+            PaletteCommand::Timeline(id) => ActionRequest::Timeline(existing(id)),
+        };
+        let _ = self.tx_action_request.send(request);
+        None
+    }
+}
+
+/// Fetch all entities & timelines
+async fn fetch_items(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<(ReducedEntities, ReducedTimelines), CrudError> {
+    let entities = ReducedEntities::fetch_all(transaction).await?;
+    let timelines = ReducedTimelines::fetch_all(transaction).await?;
+    Ok((entities, timelines))
+}
+
+/// The items that are always in the palette (i.e. not from the database)
+fn static_items() -> Vec<PaletteItem> {
+    let mut items = vec![
+        PaletteItem {
+            label: String::from("New Entity"),
+            kind: "Action",
+            command: PaletteCommand::NewEntity,
+        },
+        PaletteItem {
+            label: String::from("New Timeline"),
+            kind: "Action",
+            command: PaletteCommand::NewTimeline,
+        },
+    ];
+    items.extend(MainTabSelected::ALL.into_iter().map(|tab| PaletteItem {
+        label: tab.to_label_text(),
+        kind: "Tab",
+        command: PaletteCommand::SwitchTab(tab),
+    }));
+    items
+}
+
+/// How well the query fuzzy-matches the text (case insensitive), or `None` if
+/// the query's characters don't all appear in the text in order.  Higher is
+/// better: consecutive matches and matches at the start of words score more.
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let mut query = query.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut score = 0;
+    let mut previous_matched = false;
+    let mut previous_char = None;
+    for (position, c) in text.chars().enumerate() {
+        let Some(wanted) = query.peek() else {
+            break;
+        };
+        let matched = c.to_lowercase().eq(wanted.to_lowercase());
+        if matched {
+            query.next();
+            score += 1;
+            if previous_matched {
+                score += 5;
+            }
+            if previous_char.is_none_or(|previous: char| !previous.is_alphanumeric()) {
+                score += 3;
+            }
+            if position == 0 {
+                score += 2;
+            }
+        }
+        previous_matched = matched;
+        previous_char = Some(c);
+    }
+    query.peek().is_none().then_some(score)
+}
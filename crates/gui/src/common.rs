@@ -6,7 +6,10 @@
 
 use crate::config::SharedConfig;
 use open_timeline_core::{Name, OpenTimelineId};
-use open_timeline_crud::{Create, CrudError, DeleteById, FetchByName, Update};
+use open_timeline_crud::{
+    Cached, Create, CrudError, DeleteById, FavouriteKind, FetchByName, Stat, Update, is_starred,
+    record_opened, set_starred,
+};
 use open_timeline_gui_core::CreateOrEdit;
 use std::fmt::Debug;
 use tokio::sync::mpsc::Sender;
@@ -46,7 +49,7 @@ pub async fn save_crud<T>(
             CreateOrEdit::Edit => value.update(&mut transaction).await?,
         };
         // TODO: is this the correct error variant?
-        transaction.commit().await.map_err(|_| CrudError::DbError)?;
+        transaction.commit().await?;
         Ok(value)
     }
     .await;
@@ -87,9 +90,70 @@ pub async fn delete_from_id_crud<T>(
         let mut transaction = shared_config.read().await.db_pool.begin().await?;
         T::delete_by_id(&mut transaction, &id).await?;
         // TODO: is this the correct error variant?
-        transaction.commit().await.map_err(|_| CrudError::DbError)?;
+        transaction.commit().await?;
         Ok(())
     }
     .await;
     let _ = tx.send(result).await;
 }
+
+/// A helper function to record that an entity/timeline has been opened (for
+/// the recently opened list) which sends whether it is starred down a supplied
+/// channel.  The transaction is committed if recording is successful.
+pub async fn record_opened_crud(
+    shared_config: SharedConfig,
+    kind: FavouriteKind,
+    id: OpenTimelineId,
+    tx: Sender<Result<bool, CrudError>>,
+) {
+    let result = async {
+        let mut transaction = shared_config.read().await.db_pool.begin().await?;
+        record_opened(&mut transaction, kind, &id).await?;
+        let starred = is_starred(&mut transaction, &id).await?;
+        // TODO: is this the correct error variant?
+        transaction.commit().await?;
+        Ok(starred)
+    }
+    .await;
+    let _ = tx.send(result).await;
+}
+
+/// A helper function to star/unstar an entity/timeline.  Nothing waits on the
+/// result, so errors are only logged.
+pub async fn set_starred_crud(
+    shared_config: SharedConfig,
+    kind: FavouriteKind,
+    id: OpenTimelineId,
+    starred: bool,
+) {
+    let result: Result<(), CrudError> = async {
+        let mut transaction = shared_config.read().await.db_pool.begin().await?;
+        set_starred(&mut transaction, kind, &id, starred).await?;
+        // TODO: is this the correct error variant?
+        transaction.commit().await?;
+        Ok(())
+    }
+    .await;
+    if let Err(error) = result {
+        warn!("Error starring/unstarring: {error}");
+    }
+}
+
+/// A helper function to fetch a database statistic via the shared stats cache
+/// (the database is only queried if the cached statistic is out of date) which
+/// sends the `Result` down a supplied channel.
+pub async fn fetch_cached_stat_crud<S: Stat>(
+    shared_config: SharedConfig,
+    tx: Sender<Result<Cached<S::Value>, CrudError>>,
+) {
+    let result = async {
+        let (db_pool, cached_stats) = {
+            let config = shared_config.read().await;
+            (config.db_pool.clone(), config.cached_stats.clone())
+        };
+        let mut transaction = db_pool.begin().await?;
+        cached_stats.get::<S>(&mut transaction).await
+    }
+    .await;
+    let _ = tx.send(result).await;
+}
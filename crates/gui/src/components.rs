@@ -7,21 +7,33 @@
 mod bool_expr;
 mod common;
 mod dates;
+mod delete_confirmation;
+mod location;
 mod name;
+mod sources;
+mod star;
 mod subtimeline;
 mod subtimelines;
 mod tag;
+mod tag_suggestions;
 mod tags;
 mod timeline_entities;
 mod timeline_entity;
+mod typed_tags;
 
 pub use bool_expr::*;
 pub use common::*;
 pub use dates::*;
+pub use delete_confirmation::*;
+pub use location::*;
 pub use name::*;
+pub use sources::*;
+pub use star::*;
 pub use subtimeline::*;
 pub use subtimelines::*;
 pub use tag::*;
+pub use tag_suggestions::*;
 pub use tags::*;
 pub use timeline_entities::*;
 pub use timeline_entity::*;
+pub use typed_tags::*;
@@ -7,6 +7,7 @@
 use crate::{common::ToOpenTimelineType, consts::REMOVE_BUTTON_WIDTH};
 use bool_tag_expr::BoolTagExpr;
 use eframe::egui::{Context, TextEdit, Ui};
+use open_timeline_core::TagQuery;
 use open_timeline_crud::CrudError;
 use open_timeline_gui_core::{
     Draw, EmptyConsideredInvalid, ErrorStyle, ShowRemoveButton, Valid, ValidAsynchronous,
@@ -40,8 +41,16 @@ pub struct BooleanExpressionGui {
     // Hint text to show
     hint_text: HintText,
 
+    /// Whether comparisons of tags' values (e.g. `population > 1000000`) are
+    /// allowed (i.e. the expr is a [`TagQuery`])
+    ranges_allowed: bool,
+
     /// Whether the expr has been changed by user input
     changed: bool,
+
+    /// Whether the user has finished entering the expr (i.e. the input has
+    /// lost focus, which includes pressing enter)
+    submitted: bool,
 }
 
 impl BooleanExpressionGui {
@@ -58,7 +67,9 @@ impl BooleanExpressionGui {
             show_remove_button,
             hint_text,
             empty_considered_invalid,
+            ranges_allowed: false,
             changed: false,
+            submitted: false,
         };
         new.update_validity();
         new
@@ -77,19 +88,39 @@ impl BooleanExpressionGui {
             show_remove_button,
             hint_text,
             empty_considered_invalid,
+            ranges_allowed: false,
             changed: false,
+            submitted: false,
         };
         new.update_validity();
         new
     }
 
+    /// Allow comparisons of tags' values (e.g. `population > 1000000`), so
+    /// that the expr is a [`TagQuery`]
+    pub fn with_ranges(mut self) -> Self {
+        self.ranges_allowed = true;
+        self.update_validity();
+        self
+    }
+
     pub fn changed(&self) -> bool {
         self.changed
     }
 
+    pub fn submitted(&self) -> bool {
+        self.submitted
+    }
+
     pub fn expr(&self) -> &str {
         &self.expr
     }
+
+    /// Replace the expr (e.g. with a saved one)
+    pub fn set_expr(&mut self, expr: &str) {
+        self.expr = expr.to_string();
+        self.update_validity();
+    }
 }
 
 impl ErrorStyle for BooleanExpressionGui {}
@@ -108,9 +139,17 @@ impl ValidSynchronous for BooleanExpressionGui {
                 return;
             }
         }
-        if let Err(error) = BoolTagExpr::from(self.expr.clone()) {
+        let error = match self.ranges_allowed {
+            true => TagQuery::from(&self.expr)
+                .err()
+                .map(|error| error.to_string()),
+            false => BoolTagExpr::from(self.expr.clone())
+                .err()
+                .map(|error| error.to_string()),
+        };
+        if let Some(error) = error {
             self.validity
-                .set_synchronous(ValiditySynchronous::Invalid(error.to_string()));
+                .set_synchronous(ValiditySynchronous::Invalid(error));
             return;
         }
         self.validity.set_synchronous(ValiditySynchronous::Valid);
@@ -160,11 +199,14 @@ impl Draw for BooleanExpressionGui {
             };
 
             // Display the text input
-            let hint_text = match self.hint_text {
-                HintText::None => "",
-                HintText::Default => {
+            let hint_text = match (self.hint_text, self.ranges_allowed) {
+                (HintText::None, _) => "",
+                (HintText::Default, false) => {
                     r#"Tag Boolean Expression (e.g. "british & (scientist | painter)")"#
                 }
+                (HintText::Default, true) => {
+                    r#"Tag Boolean Expression (e.g. "british & population > 1000000")"#
+                }
             };
             let input_box = ui.add_sized(
                 [input_width, input_height],
@@ -173,6 +215,7 @@ impl Draw for BooleanExpressionGui {
                     .hint_text(hint_text),
             );
             self.changed = input_box.changed();
+            self.submitted = input_box.lost_focus();
 
             // Update validity
             {
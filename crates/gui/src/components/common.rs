@@ -4,9 +4,12 @@
 //! Everything needed to handle 1 boolean expression
 //!
 
-use crate::consts::{EDIT_BUTTON_WIDTH, EDIT_SYMBOL, VIEW_SYMBOL};
+use crate::consts::{
+    EDIT_BUTTON_WIDTH, EDIT_SYMBOL, STARRED_SYMBOL, UNSTARRED_SYMBOL, VIEW_SYMBOL,
+};
 use eframe::egui::{self, Response, RichText, Ui};
-use open_timeline_gui_core::body_text_height;
+use open_timeline_gui_core::{body_text_height, label_symbol_button};
+use open_timeline_i18n::tr;
 
 pub struct OpenTimelineButton {}
 
@@ -14,18 +17,34 @@ impl OpenTimelineButton {
     /// Draw an edit button and return the response
     pub fn edit(ui: &mut Ui) -> Response {
         let button_height = body_text_height(ui);
-        ui.add_sized(
+        let response = ui.add_sized(
             [EDIT_BUTTON_WIDTH, button_height],
             egui::Button::new(RichText::new(EDIT_SYMBOL)),
-        )
+        );
+        label_symbol_button(response, &tr!("button-edit"))
     }
 
     /// Draw a view button and return the response
     pub fn view(ui: &mut Ui) -> Response {
         let button_height = body_text_height(ui);
-        ui.add_sized(
+        let response = ui.add_sized(
             [EDIT_BUTTON_WIDTH, button_height],
             egui::Button::new(RichText::new(VIEW_SYMBOL)),
-        )
+        );
+        label_symbol_button(response, &tr!("button-view"))
+    }
+
+    /// Draw a star button (filled if starred) and return the response
+    pub fn star(ui: &mut Ui, starred: bool) -> Response {
+        let button_height = body_text_height(ui);
+        let (symbol, label) = match starred {
+            true => (STARRED_SYMBOL, tr!("button-unstar")),
+            false => (UNSTARRED_SYMBOL, tr!("button-star")),
+        };
+        let response = ui.add_sized(
+            [EDIT_BUTTON_WIDTH, button_height],
+            egui::Button::new(RichText::new(symbol)),
+        );
+        label_symbol_button(response, &label)
     }
 }
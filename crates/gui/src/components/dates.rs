@@ -7,7 +7,7 @@
 use crate::common::ToOpenTimelineType;
 use crate::consts::*;
 use eframe::egui::{Align, Color32, ComboBox, Context, Layout, TextEdit, Ui, Vec2};
-use open_timeline_core::Date;
+use open_timeline_core::{Calendar, Date};
 use open_timeline_crud::CrudError;
 use open_timeline_gui_core::{
     Draw, Valid, ValidAsynchronous, ValidSynchronous, ValidityAsynchronous, ValiditySynchronous,
@@ -23,6 +23,12 @@ enum StartEnd {
 
 #[derive(Debug)]
 pub struct DatesGui {
+    /// The free text start date input (e.g. "12 March 1945")
+    start_text: String,
+
+    /// Why the free text start date couldn't be parsed (if it couldn't be)
+    start_text_error: Option<String>,
+
     /// The start year input
     start_year: String,
 
@@ -32,6 +38,9 @@ pub struct DatesGui {
     /// The start dat input
     start_day: String,
 
+    /// The calendar the start date is in (if specified)
+    start_calendar: Option<Calendar>,
+
     /// The end year input
     end_year: String,
 
@@ -41,6 +50,15 @@ pub struct DatesGui {
     /// The end day input
     end_day: String,
 
+    /// The calendar the end date is in (if specified)
+    end_calendar: Option<Calendar>,
+
+    /// The free text end date input (e.g. "c. 500 BCE")
+    end_text: String,
+
+    /// Why the free text end date couldn't be parsed (if it couldn't be)
+    end_text_error: Option<String>,
+
     /// The validity of the dates as considered together (not just individually
     /// - e.g. is the end equal to or after the start as it should be)
     validity: ValitityStatus<(), CrudError>,
@@ -51,14 +69,20 @@ impl DatesGui {
     pub fn new() -> Self {
         let mut new = Self {
             // Start
+            start_text: String::new(),
+            start_text_error: None,
             start_year: String::new(),
             start_month: 0,
             start_day: String::new(),
+            start_calendar: None,
 
             // End
+            end_text: String::new(),
+            end_text_error: None,
             end_year: String::new(),
             end_month: 0,
             end_day: String::new(),
+            end_calendar: None,
 
             // Validity
             validity: ValitityStatus::from(ValiditySynchronous::Valid, Some(Ok(()))),
@@ -94,13 +118,24 @@ impl DatesGui {
         };
 
         // Get mut references to the data input buffers
-        let (day_buf, month_buf, year_buf) = match start_or_end {
+        let (text_buf, text_error, day_buf, month_buf, year_buf, calendar_buf) = match start_or_end
+        {
             StartEnd::Start => (
+                &mut self.start_text,
+                &mut self.start_text_error,
                 &mut self.start_day,
                 &mut self.start_month,
                 &mut self.start_year,
+                &mut self.start_calendar,
+            ),
+            StartEnd::End => (
+                &mut self.end_text,
+                &mut self.end_text_error,
+                &mut self.end_day,
+                &mut self.end_month,
+                &mut self.end_year,
+                &mut self.end_calendar,
             ),
-            StartEnd::End => (&mut self.end_day, &mut self.end_month, &mut self.end_year),
         };
 
         ui.push_id(&start_or_end, |ui| {
@@ -108,6 +143,35 @@ impl DatesGui {
                 // Draw the start or end subheading
                 open_timeline_gui_core::Label::sub_heading(ui, &label);
 
+                // Free text date (fills in the day, month & year inputs)
+                let text_input = TextEdit::singleline(text_buf)
+                    .hint_text("e.g. 12 March 1945 or c. 500 BCE")
+                    .desired_width(DESIRED_INPUT_TEXT_DATE_WIDTH);
+                if ui.add(text_input).changed() {
+                    *text_error = None;
+                    if text_buf.trim().is_empty() {
+                        // An empty end date means there is no end date
+                        if start_or_end == StartEnd::End {
+                            (*day_buf, *month_buf, *year_buf) = (String::new(), 0, String::new());
+                            *calendar_buf = None;
+                            update_validity = true;
+                        }
+                    } else {
+                        match text_buf.parse::<Date>() {
+                            Ok(date) => {
+                                (*day_buf, *month_buf, *year_buf) =
+                                    gui_date_components_from_date(date);
+                                *calendar_buf = date.calendar();
+                                update_validity = true;
+                            }
+                            Err(error) => *text_error = Some(error.to_string()),
+                        }
+                    }
+                }
+                if let Some(error) = text_error {
+                    ui.colored_label(Color32::LIGHT_RED, error.as_str());
+                }
+
                 // Indicate what each input box is for
                 ui.label("day / month / year");
 
@@ -176,6 +240,33 @@ impl DatesGui {
                         debug!("Year changed");
                         update_validity = true;
                     }
+
+                    // Calendar (unspecified dates are treated as Gregorian)
+                    let calendar_changed =
+                        ComboBox::from_id_salt(format!("{start_or_end:?}_calendar"))
+                            .selected_text(calendar_buf.map_or("", |calendar| calendar.name()))
+                            .show_ui(ui, |ui| {
+                                let mut changed =
+                                    ui.selectable_value(calendar_buf, None, "").changed();
+                                for calendar in Calendar::ALL {
+                                    if ui
+                                        .selectable_value(
+                                            calendar_buf,
+                                            Some(calendar),
+                                            calendar.name(),
+                                        )
+                                        .changed()
+                                    {
+                                        changed = true;
+                                    }
+                                }
+                                changed
+                            })
+                            .inner;
+                    if let Some(true) = calendar_changed {
+                        debug!("Calendar changed");
+                        update_validity = true;
+                    }
                 });
             });
         });
@@ -203,7 +294,7 @@ impl ValidSynchronous for DatesGui {
         ) {
             StartDateValidity::Valid(date) => {
                 debug!("Start date invalid");
-                date
+                date.with_calendar(self.start_calendar)
             }
             StartDateValidity::Invalid(error_msg) => {
                 debug!("Start date invalid: {error_msg}");
@@ -222,7 +313,7 @@ impl ValidSynchronous for DatesGui {
                 }
                 EndDateValidity::ValidDate(date) => {
                     debug!("End date is valid");
-                    Some(date)
+                    Some(date.with_calendar(self.end_calendar))
                 }
                 EndDateValidity::Invalid(error_msg) => {
                     debug!("End date is invalid");
@@ -280,7 +371,9 @@ impl ToOpenTimelineType<(Date, Option<Date>)> for DatesGui {
             .contains(&self.start_month)
             .then_some(self.start_month as i64);
         let start_year: i64 = self.start_year.trim().parse::<i64>().unwrap();
-        let start = Date::from(start_day, start_month, start_year).unwrap();
+        let start = Date::from(start_day, start_month, start_year)
+            .unwrap()
+            .with_calendar(self.start_calendar);
 
         // End
         let end_day: Option<i64> = self.end_day.trim().parse::<i64>().ok();
@@ -291,7 +384,9 @@ impl ToOpenTimelineType<(Date, Option<Date>)> for DatesGui {
         match self.end_year.trim().parse::<i64>() {
             Err(_) => (start, None),
             Ok(end_year) => {
-                let end = Date::from(end_day, end_month, end_year).unwrap();
+                let end = Date::from(end_day, end_month, end_year)
+                    .unwrap()
+                    .with_calendar(self.end_calendar);
                 (start, Some(end))
             }
         }
@@ -321,12 +416,20 @@ impl From<(Date, Option<Date>)> for DatesGui {
         };
 
         Self {
+            start_text: start.as_era_date_format(),
+            start_text_error: None,
             start_year,
             start_month,
             start_day,
+            start_calendar: start.calendar(),
             end_year,
             end_month,
             end_day,
+            end_calendar: end.and_then(|date| date.calendar()),
+            end_text: end
+                .map(|date| date.as_era_date_format())
+                .unwrap_or_default(),
+            end_text_error: None,
             validity: ValitityStatus::from(ValiditySynchronous::Valid, Some(Ok(()))),
         }
     }
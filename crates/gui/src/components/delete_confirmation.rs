@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The dialog shown before deleting an entity or timeline (listing the
+//! timelines that would be affected)
+//!
+
+use crate::components::EntityOrTimeline;
+use crate::config::SharedConfig;
+use crate::spawn_transaction_no_commit_send_result;
+use eframe::egui::{Context, Id, Modal, ScrollArea, Spinner};
+use open_timeline_core::{IsReducedCollection, IsReducedType, OpenTimelineId, ReducedTimelines};
+use open_timeline_crud::{CrudError, referencing_parent_timelines, referencing_timelines};
+use open_timeline_gui_core::CheckForUpdates;
+use std::sync::Arc;
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::error::TryRecvError;
+
+/// GUI component asking for confirmation before an entity/timeline is deleted.
+/// The timelines that reference it are fetched & listed first so that the user
+/// knows what deleting it will affect.
+#[derive(Debug)]
+pub struct DeleteConfirmationGui {
+    /// Whether an entity or a timeline is being deleted
+    entity_or_timeline: EntityOrTimeline,
+
+    /// The ID of the entity/timeline being deleted (if the dialog is open)
+    id: Option<OpenTimelineId>,
+
+    /// The timelines that reference the entity/timeline (`None` until known)
+    referencing: Option<Result<ReducedTimelines, CrudError>>,
+
+    /// Receive the timelines that reference the entity/timeline
+    rx_referencing: Option<Receiver<Result<ReducedTimelines, CrudError>>>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl DeleteConfirmationGui {
+    /// Create a new (closed) `DeleteConfirmationGui`
+    pub fn new(shared_config: SharedConfig, entity_or_timeline: EntityOrTimeline) -> Self {
+        Self {
+            entity_or_timeline,
+            id: None,
+            referencing: None,
+            rx_referencing: None,
+            shared_config,
+        }
+    }
+
+    /// Open the dialog for the entity/timeline and fetch what references it
+    pub fn open(&mut self, id: OpenTimelineId) {
+        self.id = Some(id);
+        self.referencing = None;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_referencing = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        match self.entity_or_timeline {
+            EntityOrTimeline::Entity => {
+                spawn_transaction_no_commit_send_result!(
+                    shared_config,
+                    bounded,
+                    tx,
+                    |transaction| async move { referencing_timelines(transaction, &id).await }
+                );
+            }
+            EntityOrTimeline::Timeline => {
+                spawn_transaction_no_commit_send_result!(
+                    shared_config,
+                    bounded,
+                    tx,
+                    |transaction| async move { referencing_parent_timelines(transaction, &id).await }
+                );
+            }
+        }
+    }
+
+    /// Close the dialog
+    fn close(&mut self) {
+        self.id = None;
+        self.referencing = None;
+        self.rx_referencing = None;
+    }
+
+    /// Draw the dialog (if open) and return whether the deletion has been
+    /// confirmed
+    pub fn draw(&mut self, ctx: &Context, name: &str) -> bool {
+        let Some(id) = self.id else {
+            return false;
+        };
+        let (kind, affected, unaffected) = match self.entity_or_timeline {
+            EntityOrTimeline::Entity => (
+                "entity",
+                "It will be removed from these timelines",
+                "No timelines include it",
+            ),
+            EntityOrTimeline::Timeline => (
+                "timeline",
+                "It will be removed from these timelines' subtimelines",
+                "No timelines have it as a subtimeline",
+            ),
+        };
+        let mut confirmed = false;
+        let mut cancelled = false;
+        let modal = Modal::new(Id::new(("delete_confirmation", id))).show(ctx, |ui| {
+            open_timeline_gui_core::Label::sub_heading(ui, &format!("Delete the {kind}?"));
+            open_timeline_gui_core::Label::strong(ui, name);
+            ui.separator();
+            match &self.referencing {
+                None => {
+                    ui.add(Spinner::new());
+                }
+                Some(Err(error)) => {
+                    ui.label(format!("Couldn't check what references it: {error}"));
+                }
+                Some(Ok(referencing)) if referencing.collection().is_empty() => {
+                    ui.label(unaffected);
+                }
+                Some(Ok(referencing)) => {
+                    let count = referencing.collection().len();
+                    ui.label(format!("{affected} ({count}):"));
+                    ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for timeline in referencing.collection() {
+                            ui.label(format!("• {}", timeline.name()));
+                        }
+                    });
+                }
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                // Only once the user has been shown what will be affected
+                ui.add_enabled_ui(self.referencing.is_some(), |ui| {
+                    if ui.button("Delete").clicked() {
+                        confirmed = true;
+                    }
+                });
+                if ui.button("Cancel").clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+        if confirmed || cancelled || modal.should_close() {
+            self.close();
+        }
+        confirmed
+    }
+}
+
+impl CheckForUpdates for DeleteConfirmationGui {
+    fn check_for_updates(&mut self) {
+        if let Some(rx) = self.rx_referencing.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_referencing = None;
+                    self.referencing = Some(result);
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    fn waiting_for_updates(&mut self) -> bool {
+        self.rx_referencing.is_some()
+    }
+}
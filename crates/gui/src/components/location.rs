@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Everything needed to work with where an entity happened/existed
+//!
+
+use crate::common::ToOpenTimelineType;
+use eframe::egui::{Context, TextEdit, Ui};
+use open_timeline_core::{Coordinates, Location, LocationError};
+use open_timeline_crud::CrudError;
+use open_timeline_gui_core::{
+    Draw, ErrorStyle, Valid, ValidAsynchronous, ValidSynchronous, ValiditySynchronous,
+    ValitityStatus, widget_x_spacing,
+};
+
+/// GUI component for an (optional) location
+#[derive(Debug)]
+pub struct LocationGui {
+    /// The place name input buffer
+    place: String,
+
+    /// The latitude input buffer
+    latitude: String,
+
+    /// The longitude input buffer
+    longitude: String,
+
+    /// Everything needed for validation
+    validity: ValitityStatus<(), CrudError>,
+}
+
+impl LocationGui {
+    /// Create a new (empty) `LocationGui`
+    pub fn new() -> Self {
+        Self {
+            place: String::new(),
+            latitude: String::new(),
+            longitude: String::new(),
+            validity: ValitityStatus::from(ValiditySynchronous::Valid, None),
+        }
+    }
+
+    /// Try to create a `Location` from the inputs (all inputs being empty
+    /// means there is no location)
+    fn location(&self) -> Result<Option<Location>, LocationError> {
+        let latitude = self.latitude.trim();
+        let longitude = self.longitude.trim();
+        let coordinates = if latitude.is_empty() && longitude.is_empty() {
+            None
+        } else {
+            let latitude = latitude.parse().map_err(|_| LocationError::Latitude)?;
+            let longitude = longitude.parse().map_err(|_| LocationError::Longitude)?;
+            Some(Coordinates::from(latitude, longitude)?)
+        };
+        if self.place.trim().is_empty() && coordinates.is_none() {
+            return Ok(None);
+        }
+        Location::from(Some(&self.place), coordinates).map(Some)
+    }
+}
+
+impl ValidSynchronous for LocationGui {
+    fn is_valid_synchronous(&self) -> bool {
+        self.validity.synchronous() == ValiditySynchronous::Valid
+    }
+
+    fn update_validity_synchronous(&mut self) {
+        match self.location() {
+            Ok(_) => self.validity.set_synchronous(ValiditySynchronous::Valid),
+            Err(error) => self
+                .validity
+                .set_synchronous(ValiditySynchronous::Invalid(error.to_string())),
+        }
+    }
+
+    fn validity_synchronous(&self) -> ValiditySynchronous {
+        self.validity.synchronous()
+    }
+}
+
+impl ValidAsynchronous for LocationGui {
+    type Error = CrudError;
+
+    fn check_for_asynchronous_validity_response(&mut self) {
+        //
+    }
+
+    fn is_valid_asynchronous(&self) -> Option<Result<(), Self::Error>> {
+        Some(Ok(()))
+    }
+
+    fn trigger_asynchronous_validity_update(&mut self) {
+        //
+    }
+}
+
+impl Valid for LocationGui {}
+
+impl ErrorStyle for LocationGui {}
+
+impl ToOpenTimelineType<Option<Location>> for LocationGui {
+    fn to_opentimeline_type(&self) -> Option<Location> {
+        self.location().unwrap()
+    }
+}
+
+impl Draw for LocationGui {
+    fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
+        // Draw sub-heading
+        open_timeline_gui_core::Label::sub_heading(ui, "Location");
+
+        let spacing = widget_x_spacing(ui);
+        let coordinate_width = 100.0;
+        let place_width = ui.available_width() - (coordinate_width + spacing) * 2.0;
+
+        let mut changed = false;
+        ui.scope(|ui| {
+            self.set_validity_styling(ctx, ui);
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut self.place)
+                            .hint_text("Place (optional)")
+                            .desired_width(place_width),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut self.latitude)
+                            .hint_text("Latitude")
+                            .desired_width(coordinate_width),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut self.longitude)
+                            .hint_text("Longitude")
+                            .desired_width(coordinate_width),
+                    )
+                    .changed();
+            });
+        });
+
+        if changed {
+            self.update_validity();
+        }
+    }
+}
+
+impl From<Option<Location>> for LocationGui {
+    fn from(location: Option<Location>) -> Self {
+        let Some(location) = location else {
+            return Self::new();
+        };
+        let coordinates = location.coordinates();
+        Self {
+            place: location.place().unwrap_or_default().to_string(),
+            latitude: coordinates
+                .map(|coordinates| coordinates.latitude().to_string())
+                .unwrap_or_default(),
+            longitude: coordinates
+                .map(|coordinates| coordinates.longitude().to_string())
+                .unwrap_or_default(),
+            validity: ValitityStatus::from(ValiditySynchronous::Valid, None),
+        }
+    }
+}
@@ -116,8 +116,9 @@ impl ValidAsynchronous for NameGui {
                     // TODO: return an enum rather than bool for readability
                     match msg {
                         Ok(false) => self.validity.asynchronous = Some(Ok(())),
-                        // TODO: use a different CrudError
-                        Ok(true) => self.validity.asynchronous = Some(Err(CrudError::Name)),
+                        Ok(true) => {
+                            self.validity.asynchronous = Some(Err(CrudError::NameAlreadyInUse))
+                        }
                         Err(error) => self.validity.asynchronous = Some(Err(error)),
                     }
                 }
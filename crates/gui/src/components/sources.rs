@@ -0,0 +1,259 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Everything needed to work with the sources cited for an entity
+//!
+
+use crate::common::ToOpenTimelineType;
+use crate::consts::REMOVE_BUTTON_WIDTH;
+use crate::{
+    impl_is_valid_method_for_iterable, impl_valid_asynchronous_macro_never_called,
+    impl_valid_synchronous_macro_never_called,
+};
+use eframe::egui::{ComboBox, Context, TextEdit, Ui};
+use open_timeline_core::{Source, SourceScope, Sources};
+use open_timeline_crud::CrudError;
+use open_timeline_gui_core::{
+    Draw, ErrorStyle, Valid, ValidAsynchronous, ValidSynchronous, ValidityAsynchronous,
+    ValiditySynchronous, ValitityStatus, widget_x_spacing,
+};
+
+/// GUI component for a source
+#[derive(Debug)]
+pub struct SourceGui {
+    /// The title input buffer
+    title: String,
+
+    /// The URL input buffer
+    url: String,
+
+    /// The citation input buffer
+    citation: String,
+
+    /// What the source is cited for
+    scope: SourceScope,
+
+    /// Whether the user has requested the source be removed
+    to_be_removed: bool,
+
+    /// Everything needed for validation
+    validity: ValitityStatus<(), CrudError>,
+}
+
+impl SourceGui {
+    /// Create a new (empty) `SourceGui`
+    pub fn new() -> Self {
+        let mut new = Self {
+            title: String::new(),
+            url: String::new(),
+            citation: String::new(),
+            scope: SourceScope::default(),
+            to_be_removed: false,
+            validity: ValitityStatus::from(ValiditySynchronous::Valid, None),
+        };
+        new.update_validity();
+        new
+    }
+
+    /// Create a new `SourceGui` from a `Source`
+    pub fn from_source(source: Source) -> Self {
+        Self {
+            title: source.title().to_string(),
+            url: source.url().unwrap_or_default().to_string(),
+            citation: source.citation().unwrap_or_default().to_string(),
+            scope: source.scope(),
+            to_be_removed: false,
+            validity: ValitityStatus::from(ValiditySynchronous::Valid, None),
+        }
+    }
+
+    /// Try to create a `Source` from the inputs
+    fn source(&self) -> Result<Source, open_timeline_core::SourceError> {
+        Source::from(
+            &self.title,
+            Some(&self.url),
+            Some(&self.citation),
+            self.scope,
+        )
+    }
+}
+
+impl ValidSynchronous for SourceGui {
+    fn is_valid_synchronous(&self) -> bool {
+        self.validity.synchronous() == ValiditySynchronous::Valid
+    }
+
+    fn update_validity_synchronous(&mut self) {
+        match self.source() {
+            Ok(_) => self.validity.set_synchronous(ValiditySynchronous::Valid),
+            Err(error) => self
+                .validity
+                .set_synchronous(ValiditySynchronous::Invalid(error.to_string())),
+        }
+    }
+
+    fn validity_synchronous(&self) -> ValiditySynchronous {
+        self.validity.synchronous()
+    }
+}
+
+impl ValidAsynchronous for SourceGui {
+    type Error = CrudError;
+
+    fn check_for_asynchronous_validity_response(&mut self) {
+        //
+    }
+
+    fn is_valid_asynchronous(&self) -> Option<Result<(), Self::Error>> {
+        Some(Ok(()))
+    }
+
+    fn trigger_asynchronous_validity_update(&mut self) {
+        //
+    }
+}
+
+impl Valid for SourceGui {}
+
+impl ErrorStyle for SourceGui {}
+
+impl ToOpenTimelineType<Source> for SourceGui {
+    fn to_opentimeline_type(&self) -> Source {
+        self.source().unwrap()
+    }
+}
+
+impl Draw for SourceGui {
+    fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
+        let spacing = widget_x_spacing(ui);
+        let scope_width = 100.0;
+        let input_width =
+            (ui.available_width() - scope_width - REMOVE_BUTTON_WIDTH - (spacing * 4.0)) / 2.0;
+
+        let mut changed = false;
+        ui.scope(|ui| {
+            self.set_validity_styling(ctx, ui);
+
+            // Title, URL, scope & remove button
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut self.title)
+                            .hint_text("Title")
+                            .desired_width(input_width),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut self.url)
+                            .hint_text("URL (optional)")
+                            .desired_width(input_width),
+                    )
+                    .changed();
+                ComboBox::from_id_salt("source_scope")
+                    .width(scope_width)
+                    .selected_text(self.scope.name())
+                    .show_ui(ui, |ui| {
+                        for scope in SourceScope::ALL {
+                            ui.selectable_value(&mut self.scope, scope, scope.name());
+                        }
+                    });
+                if open_timeline_gui_core::Button::remove(ui).clicked() {
+                    self.to_be_removed = true;
+                }
+            });
+
+            // Citation
+            changed |= ui
+                .add(
+                    TextEdit::multiline(&mut self.citation)
+                        .hint_text("Citation (optional, e.g. author, publisher, pages)")
+                        .desired_rows(1)
+                        .desired_width(f32::INFINITY),
+                )
+                .changed();
+        });
+
+        if changed {
+            self.update_validity();
+        }
+    }
+}
+
+/// GUI component that manages & draws `SourceGui`s
+#[derive(Debug)]
+pub struct SourcesGui {
+    /// All the sources held and shown to the user
+    sources: Vec<SourceGui>,
+}
+
+impl SourcesGui {
+    /// Create a new `SourcesGui`
+    pub fn new() -> Self {
+        Self { sources: vec![] }
+    }
+}
+
+impl_valid_synchronous_macro_never_called!(SourcesGui);
+impl_valid_asynchronous_macro_never_called!(SourcesGui);
+
+impl Valid for SourcesGui {
+    fn validity(&self) -> ValidityAsynchronous {
+        let validity: Vec<ValidityAsynchronous> = self
+            .sources
+            .iter()
+            .map(|source| source.validity())
+            .collect();
+        impl_is_valid_method_for_iterable!(validity)
+    }
+
+    fn update_validity(&mut self) {
+        // Do nothing.  Components update their validity themselves.
+        panic!()
+    }
+}
+
+impl ToOpenTimelineType<Sources> for SourcesGui {
+    fn to_opentimeline_type(&self) -> Sources {
+        self.sources
+            .iter()
+            .map(|source| source.to_opentimeline_type())
+            .collect()
+    }
+}
+
+impl Draw for SourcesGui {
+    fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
+        // Draw sub-heading
+        open_timeline_gui_core::Label::sub_heading(ui, "Sources");
+
+        if self.sources.is_empty() {
+            // Tell the user that there are no sources
+            open_timeline_gui_core::Label::none(ui);
+        } else {
+            for (i, source) in self.sources.iter_mut().enumerate() {
+                ui.push_id(i, |ui| source.draw(ctx, ui));
+                ui.add_space(5.0);
+            }
+            self.sources.retain(|source| !source.to_be_removed);
+        }
+        ui.add_space(5.0);
+
+        // Add source button
+        if open_timeline_gui_core::Button::add(ui).clicked() {
+            self.sources.push(SourceGui::new());
+        }
+    }
+}
+
+impl From<Option<Sources>> for SourcesGui {
+    fn from(sources: Option<Sources>) -> Self {
+        Self {
+            sources: sources
+                .into_iter()
+                .flatten()
+                .map(SourceGui::from_source)
+                .collect(),
+        }
+    }
+}
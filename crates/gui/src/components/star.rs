@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The star (favourite) button shown in entity & timeline view windows
+//!
+
+use crate::common::{record_opened_crud, set_starred_crud};
+use crate::components::OpenTimelineButton;
+use crate::config::SharedConfig;
+use crate::spawn_operation;
+use eframe::egui::{Context, Ui};
+use open_timeline_core::OpenTimelineId;
+use open_timeline_crud::{CrudError, FavouriteKind};
+use open_timeline_gui_core::{CheckForUpdates, Draw};
+use std::sync::Arc;
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::error::TryRecvError;
+
+/// GUI component for starring an entity/timeline.  Creating one records that
+/// the entity/timeline has been opened.
+#[derive(Debug)]
+pub struct StarGui {
+    /// Whether it is an entity or a timeline
+    kind: FavouriteKind,
+
+    /// The ID of the entity/timeline
+    id: OpenTimelineId,
+
+    /// Whether it is starred (`None` until known)
+    starred: Option<bool>,
+
+    /// Receive whether it is starred
+    rx_starred: Option<Receiver<Result<bool, CrudError>>>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl StarGui {
+    /// Create a new `StarGui` (recording that the entity/timeline has been
+    /// opened)
+    pub fn new(shared_config: SharedConfig, kind: FavouriteKind, id: OpenTimelineId) -> Self {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        spawn_operation!(record_opened_crud(Arc::clone(&shared_config), kind, id, tx));
+        Self {
+            kind,
+            id,
+            starred: None,
+            rx_starred: Some(rx),
+            shared_config,
+        }
+    }
+}
+
+impl Draw for StarGui {
+    fn draw(&mut self, _ctx: &Context, ui: &mut Ui) {
+        let Some(starred) = self.starred else {
+            return;
+        };
+        if OpenTimelineButton::star(ui, starred).clicked() {
+            self.starred = Some(!starred);
+            spawn_operation!(set_starred_crud(
+                Arc::clone(&self.shared_config),
+                self.kind,
+                self.id,
+                !starred,
+            ));
+        }
+    }
+}
+
+impl CheckForUpdates for StarGui {
+    fn check_for_updates(&mut self) {
+        if let Some(rx) = self.rx_starred.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_starred = None;
+                    match result {
+                        Ok(starred) => self.starred = Some(starred),
+                        Err(error) => warn!("Error recording opened: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_starred = None,
+            }
+        }
+    }
+
+    fn waiting_for_updates(&mut self) -> bool {
+        self.rx_starred.is_some()
+    }
+}
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Suggested entity tags (those that commonly appear alongside the tags already
+//! added), so that similar entities are tagged consistently
+//!
+
+use crate::components::TagsGui;
+use crate::config::SharedConfig;
+use crate::spawn_transaction_no_commit_send_result;
+use bool_tag_expr::{Tag, Tags};
+use eframe::egui::{Button, RichText, Ui};
+use open_timeline_core::OpenTimelineId;
+use open_timeline_crud::{CrudError, Limit, TagCounts, fetch_co_occurring_entity_tags};
+use std::sync::Arc;
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::error::TryRecvError;
+
+/// The maximum number of tags suggested
+const SUGGESTION_LIMIT: u32 = 8;
+
+/// GUI component that suggests tags for an entity (added with one click)
+#[derive(Debug)]
+pub struct TagSuggestionsGui {
+    /// The tags the suggestions are for
+    suggested_for: Tags,
+
+    /// The suggested tags
+    suggestions: Vec<Tag>,
+
+    /// Receive the suggested tags
+    rx_suggestions: Option<Receiver<Result<TagCounts, CrudError>>>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl TagSuggestionsGui {
+    /// Create a new `TagSuggestionsGui`
+    pub fn new(shared_config: SharedConfig) -> Self {
+        Self {
+            suggested_for: Tags::new(),
+            suggestions: Vec::new(),
+            rx_suggestions: None,
+            shared_config,
+        }
+    }
+
+    /// Whether the suggestions are being fetched
+    pub fn waiting_for_updates(&self) -> bool {
+        self.rx_suggestions.is_some()
+    }
+
+    /// Fetch the tags that commonly appear alongside the entity's tags
+    fn request_suggestions(&mut self, tags: Tags, entity_id: Option<OpenTimelineId>) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_suggestions = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move {
+                fetch_co_occurring_entity_tags(
+                    transaction,
+                    &tags,
+                    entity_id,
+                    Limit(SUGGESTION_LIMIT),
+                )
+                .await
+            }
+        );
+    }
+
+    /// Check for the suggested tags (if any)
+    fn check_suggestions_response(&mut self) {
+        if let Some(rx) = self.rx_suggestions.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_suggestions = None;
+                    match result {
+                        Ok(tag_counts) => {
+                            self.suggestions = tag_counts
+                                .into_iter()
+                                .map(|tag_count| tag_count.tag().clone())
+                                .collect()
+                        }
+                        Err(error) => warn!("Unable to fetch tag suggestions: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_suggestions = None,
+            }
+        }
+    }
+
+    /// Draw the suggestions for the entity's tags (which are re-fetched when
+    /// its tags change), adding any that are clicked
+    pub fn draw(&mut self, ui: &mut Ui, tags: &mut TagsGui, entity_id: Option<OpenTimelineId>) {
+        self.check_suggestions_response();
+        let current = tags.valid_tags();
+        if current != self.suggested_for {
+            self.suggested_for = current.clone();
+            match current.is_empty() {
+                true => {
+                    self.suggestions.clear();
+                    self.rx_suggestions = None;
+                }
+                false => self.request_suggestions(current.clone(), entity_id),
+            }
+        }
+
+        // Suggestions that have been added since they were fetched aren't shown
+        let suggestions: Vec<&Tag> = self
+            .suggestions
+            .iter()
+            .filter(|tag| !current.contains(tag))
+            .collect();
+        if suggestions.is_empty() {
+            return;
+        }
+        let mut to_add = None;
+        ui.horizontal_wrapped(|ui| {
+            open_timeline_gui_core::Label::weak(ui, "Suggested");
+            for tag in suggestions {
+                let text = match &tag.name {
+                    Some(name) => format!("+ {}={}", name.as_str(), tag.value.as_str()),
+                    None => format!("+ {}", tag.value.as_str()),
+                };
+                let button = ui
+                    .add(Button::new(RichText::new(text).small()).small())
+                    .on_hover_text("Often used alongside these tags");
+                if button.clicked() {
+                    to_add = Some(tag.clone());
+                }
+            }
+        });
+        if let Some(tag) = to_add {
+            tags.add_tag(tag);
+        }
+    }
+}
@@ -9,7 +9,7 @@ use crate::{
     components::{RequestedAction, TagFocusRequestTarget, TagGui},
     impl_is_valid_method_for_iterable, impl_valid_asynchronous_macro_never_called,
 };
-use bool_tag_expr::Tags;
+use bool_tag_expr::{Tag, Tags};
 use eframe::egui::{Context, Ui};
 use open_timeline_crud::CrudError;
 use open_timeline_gui_core::{
@@ -37,6 +37,21 @@ impl TagsGui {
         }
     }
 
+    /// The tags that are valid (i.e. excluding those still being typed)
+    pub fn valid_tags(&self) -> Tags {
+        self.tags
+            .iter()
+            .filter(|tag| tag.is_valid_synchronous())
+            .map(|tag| tag.to_opentimeline_type())
+            .collect()
+    }
+
+    /// Add a tag (e.g. a suggested one)
+    pub fn add_tag(&mut self, tag: Tag) {
+        self.tags.push(TagGui::from_tag(tag, ShowRemoveButton::Yes));
+        self.update_validity_synchronous();
+    }
+
     /// Add a new empty tag input to the list.  Passing along the focus target
     /// request
     fn add_empty_tag(&mut self, tag_focus_target: Option<TagFocusRequestTarget>) {
@@ -9,7 +9,7 @@ use crate::{
     impl_is_valid_method_for_iterable, impl_valid_asynchronous_macro_never_called,
 };
 use eframe::egui::{Context, Ui};
-use open_timeline_core::{IsReducedCollection, ReducedEntities};
+use open_timeline_core::{IsReducedCollection, Name, ReducedEntities};
 use open_timeline_gui_core::{
     Draw, Valid, ValidSynchronous, ValidityAsynchronous, ValiditySynchronous,
 };
@@ -40,6 +40,22 @@ impl TimelineEntitiesGui {
         self.entities.push(TimelineEntityGui::new(shared_config));
     }
 
+    /// Add the entity with the name to the list (unless it's already in it).
+    /// Returns whether it was added.
+    pub fn add_entity_by_name(&mut self, name: &Name) -> bool {
+        if self
+            .entities
+            .iter()
+            .any(|entity| entity.name() == name.as_str())
+        {
+            return false;
+        }
+        let shared_config = Arc::clone(&self.shared_config);
+        self.entities
+            .push(TimelineEntityGui::from_name(shared_config, name));
+        true
+    }
+
     pub fn from_reduced_entities(
         shared_config: SharedConfig,
         original_entities: Option<ReducedEntities>,
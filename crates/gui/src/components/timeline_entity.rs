@@ -62,6 +62,15 @@ impl TimelineEntityGui {
         new
     }
 
+    /// Create a new TimelineEntityGui for the entity with the name (which is
+    /// checked against the database)
+    pub fn from_name(shared_config: SharedConfig, name: &Name) -> Self {
+        let mut new = Self::new(shared_config);
+        new.name = name.to_string();
+        new.update_validity();
+        new
+    }
+
     pub fn from_reduced_entity(shared_config: SharedConfig, reduced_entity: ReducedEntity) -> Self {
         Self {
             search_results_dropdown_id: OpenTimelineId::new(),
@@ -81,6 +90,11 @@ impl TimelineEntityGui {
         self.to_be_removed
     }
 
+    /// The name of the entity (as input)
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     // TODO: nigh on identical to the code in entities.rs (use a macro to avoid generic hell)
     fn request_new_search_results(&mut self) {
         let (tx, rx) = tokio::sync::mpsc::channel(1);
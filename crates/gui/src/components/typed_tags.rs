@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Everything needed to work with an entity's typed tags (tags whose values
+//! are numbers or dates)
+//!
+
+use crate::common::ToOpenTimelineType;
+use crate::consts::REMOVE_BUTTON_WIDTH;
+use crate::{
+    impl_is_valid_method_for_iterable, impl_valid_asynchronous_macro_never_called,
+    impl_valid_synchronous_macro_never_called,
+};
+use eframe::egui::{ComboBox, Context, TextEdit, Ui};
+use open_timeline_core::{TagValueType, TypedTag, TypedTagError, TypedTags};
+use open_timeline_crud::CrudError;
+use open_timeline_gui_core::{
+    Draw, ErrorStyle, Valid, ValidAsynchronous, ValidSynchronous, ValidityAsynchronous,
+    ValiditySynchronous, ValitityStatus, widget_x_spacing,
+};
+
+/// GUI component for a typed tag
+#[derive(Debug)]
+pub struct TypedTagGui {
+    /// The name input buffer
+    name: String,
+
+    /// The type of the value
+    value_type: TagValueType,
+
+    /// The value input buffer
+    value: String,
+
+    /// Whether the user has requested the typed tag be removed
+    to_be_removed: bool,
+
+    /// Everything needed for validation
+    validity: ValitityStatus<(), CrudError>,
+}
+
+impl TypedTagGui {
+    /// Create a new (empty) `TypedTagGui`
+    pub fn new() -> Self {
+        let mut new = Self {
+            name: String::new(),
+            value_type: TagValueType::default(),
+            value: String::new(),
+            to_be_removed: false,
+            validity: ValitityStatus::from(ValiditySynchronous::Valid, None),
+        };
+        new.update_validity();
+        new
+    }
+
+    /// Create a new `TypedTagGui` from a `TypedTag`
+    pub fn from_typed_tag(typed_tag: TypedTag) -> Self {
+        Self {
+            name: typed_tag.name.to_string(),
+            value_type: typed_tag.value.value_type(),
+            value: typed_tag.value.to_string(),
+            to_be_removed: false,
+            validity: ValitityStatus::from(ValiditySynchronous::Valid, None),
+        }
+    }
+
+    /// Try to create a `TypedTag` from the inputs
+    fn typed_tag(&self) -> Result<TypedTag, TypedTagError> {
+        TypedTag::parse(&self.name, self.value_type, &self.value)
+    }
+}
+
+impl ValidSynchronous for TypedTagGui {
+    fn is_valid_synchronous(&self) -> bool {
+        self.validity.synchronous() == ValiditySynchronous::Valid
+    }
+
+    fn update_validity_synchronous(&mut self) {
+        match self.typed_tag() {
+            Ok(_) => self.validity.set_synchronous(ValiditySynchronous::Valid),
+            Err(error) => self
+                .validity
+                .set_synchronous(ValiditySynchronous::Invalid(error.to_string())),
+        }
+    }
+
+    fn validity_synchronous(&self) -> ValiditySynchronous {
+        self.validity.synchronous()
+    }
+}
+
+impl ValidAsynchronous for TypedTagGui {
+    type Error = CrudError;
+
+    fn check_for_asynchronous_validity_response(&mut self) {
+        //
+    }
+
+    fn is_valid_asynchronous(&self) -> Option<Result<(), Self::Error>> {
+        Some(Ok(()))
+    }
+
+    fn trigger_asynchronous_validity_update(&mut self) {
+        //
+    }
+}
+
+impl Valid for TypedTagGui {}
+
+impl ErrorStyle for TypedTagGui {}
+
+impl ToOpenTimelineType<TypedTag> for TypedTagGui {
+    fn to_opentimeline_type(&self) -> TypedTag {
+        self.typed_tag().unwrap()
+    }
+}
+
+impl Draw for TypedTagGui {
+    fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
+        let spacing = widget_x_spacing(ui);
+        let type_width = 100.0;
+        let input_width =
+            (ui.available_width() - type_width - REMOVE_BUTTON_WIDTH - (spacing * 3.0)) / 2.0;
+
+        let mut changed = false;
+        ui.scope(|ui| {
+            self.set_validity_styling(ctx, ui);
+
+            // Name, type, value & remove button
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut self.name)
+                            .hint_text("Name (e.g. population)")
+                            .desired_width(input_width),
+                    )
+                    .changed();
+                ComboBox::from_id_salt("typed_tag_value_type")
+                    .width(type_width)
+                    .selected_text(self.value_type.name())
+                    .show_ui(ui, |ui| {
+                        for value_type in TagValueType::TYPED {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut self.value_type,
+                                    value_type,
+                                    value_type.name(),
+                                )
+                                .changed();
+                        }
+                    });
+                changed |= ui
+                    .add(
+                        TextEdit::singleline(&mut self.value)
+                            .hint_text(self.value_type.example())
+                            .desired_width(input_width),
+                    )
+                    .changed();
+                if open_timeline_gui_core::Button::remove(ui).clicked() {
+                    self.to_be_removed = true;
+                }
+            });
+        });
+
+        if changed {
+            self.update_validity();
+        }
+    }
+}
+
+/// GUI component that manages & draws `TypedTagGui`s
+#[derive(Debug)]
+pub struct TypedTagsGui {
+    /// All the typed tags held and shown to the user
+    typed_tags: Vec<TypedTagGui>,
+}
+
+impl TypedTagsGui {
+    /// Create a new `TypedTagsGui`
+    pub fn new() -> Self {
+        Self { typed_tags: vec![] }
+    }
+}
+
+impl_valid_synchronous_macro_never_called!(TypedTagsGui);
+impl_valid_asynchronous_macro_never_called!(TypedTagsGui);
+
+impl Valid for TypedTagsGui {
+    fn validity(&self) -> ValidityAsynchronous {
+        let validity: Vec<ValidityAsynchronous> = self
+            .typed_tags
+            .iter()
+            .map(|typed_tag| typed_tag.validity())
+            .collect();
+        impl_is_valid_method_for_iterable!(validity)
+    }
+
+    fn update_validity(&mut self) {
+        // Do nothing.  Components update their validity themselves.
+        panic!()
+    }
+}
+
+impl ToOpenTimelineType<TypedTags> for TypedTagsGui {
+    fn to_opentimeline_type(&self) -> TypedTags {
+        self.typed_tags
+            .iter()
+            .map(|typed_tag| typed_tag.to_opentimeline_type())
+            .collect()
+    }
+}
+
+impl Draw for TypedTagsGui {
+    fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
+        // Draw sub-heading
+        open_timeline_gui_core::Label::sub_heading(ui, "Number & Date Tags");
+
+        if self.typed_tags.is_empty() {
+            // Tell the user that there are no typed tags
+            open_timeline_gui_core::Label::none(ui);
+        } else {
+            for (i, typed_tag) in self.typed_tags.iter_mut().enumerate() {
+                ui.push_id(i, |ui| typed_tag.draw(ctx, ui));
+            }
+            self.typed_tags.retain(|typed_tag| !typed_tag.to_be_removed);
+        }
+        ui.add_space(5.0);
+
+        // Add typed tag button
+        if open_timeline_gui_core::Button::add(ui).clicked() {
+            self.typed_tags.push(TypedTagGui::new());
+        }
+    }
+}
+
+impl From<Option<TypedTags>> for TypedTagsGui {
+    fn from(typed_tags: Option<TypedTags>) -> Self {
+        Self {
+            typed_tags: typed_tags
+                .into_iter()
+                .flatten()
+                .map(TypedTagGui::from_typed_tag)
+                .collect(),
+        }
+    }
+}
@@ -6,14 +6,19 @@
 
 use crate::app_colours::{AppColours, ColourTheme};
 use directories_next::ProjectDirs;
-use log::info;
-use open_timeline_crud::{CrudError, setup_database_at_path};
+use open_timeline_core::DateFormat;
+use open_timeline_crud::{CachedStats, CrudError, setup_database_at_path};
+use open_timeline_games::GameConfig;
+use open_timeline_i18n::Language;
+use open_timeline_renderer::FontConfig;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use std::fs::{self, File};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing::info;
+use tracing_subscriber::filter::Targets;
 
 const PROJECT_QUALIFIER: &str = "org";
 const ORG_NAME: &str = "OpenTimeline";
@@ -21,6 +26,9 @@ const APPLICATION_NAME: &str = "OpenTimeline";
 const CONFIG_FILE_NAME: &str = "config.json";
 const DEFAULT_DATABASE_FILE_NAME: &str = "timeline.sqlite";
 
+/// The log levels used by default (see [`Config::log_levels`])
+pub const DEFAULT_LOG_LEVELS: &str = "open_timeline=debug";
+
 pub type SharedConfig = Arc<RwLock<RuntimeConfig>>;
 
 /// The config that's available across the application at runtime
@@ -28,6 +36,9 @@ pub type SharedConfig = Arc<RwLock<RuntimeConfig>>;
 pub struct RuntimeConfig {
     pub db_pool: SqlitePool,
     pub config: Config,
+
+    /// Database statistics cache (shared by the stats & count panels)
+    pub cached_stats: CachedStats,
 }
 
 /// The config that's saved to disk
@@ -41,6 +52,119 @@ pub struct Config {
 
     /// The custom theme
     pub custom_theme: AppColours,
+
+    /// The language of the GUI's text
+    #[serde(default)]
+    pub language: Language,
+
+    /// How dates are displayed
+    #[serde(default)]
+    pub date_format: DateFormat,
+
+    /// Whether timelines draw a line at today's date
+    #[serde(default)]
+    pub show_now_line: bool,
+
+    /// Whether timelines say how long ago things were (e.g. "75 years ago")
+    /// rather than giving dates
+    #[serde(default)]
+    pub relative_time: bool,
+
+    /// The font used for timelines' text
+    #[serde(default)]
+    pub timeline_font: FontConfig,
+
+    /// The settings of each game
+    #[serde(default)]
+    pub games: GamesConfig,
+
+    /// The remote OpenTimeline API to sync with
+    #[serde(default)]
+    pub sync: SyncConfig,
+
+    /// Whether editors are opened as tabs in a single window (rather than each
+    /// in its own window)
+    #[serde(default)]
+    pub open_editors_as_tabs: bool,
+
+    /// The API server run inside the app (so that e.g. the browser renderer
+    /// can read the database)
+    #[serde(default)]
+    pub local_api: LocalApiConfig,
+
+    /// The level logged (to the terminal) at for each module, as a comma
+    /// separated list of `module=level` (e.g.
+    /// `open_timeline=info,sqlx::query=debug`)
+    #[serde(default = "default_log_levels")]
+    pub log_levels: String,
+}
+
+/// See [`DEFAULT_LOG_LEVELS`]
+fn default_log_levels() -> String {
+    String::from(DEFAULT_LOG_LEVELS)
+}
+
+/// The remote OpenTimeline API to sync with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConfig {
+    /// The URL of the site serving the API
+    pub url: String,
+
+    /// The token sent to the API (empty if it doesn't need one)
+    pub token: String,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            url: String::from("https://www.open-timeline.org"),
+            token: String::new(),
+        }
+    }
+}
+
+/// The API server run inside the app
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalApiConfig {
+    /// Whether the server is run (it's started with the app if so)
+    pub enabled: bool,
+
+    /// The port listened on (on localhost only)
+    pub port: u16,
+}
+
+impl Default for LocalApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 2408,
+        }
+    }
+}
+
+/// The settings of each game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GamesConfig {
+    pub decades: GameConfig,
+    pub left_right: GameConfig,
+    pub order_entities: GameConfig,
+    pub were_they_alive_when: GameConfig,
+    pub which_date: GameConfig,
+}
+
+impl Default for GamesConfig {
+    fn default() -> Self {
+        Self {
+            decades: GameConfig::default(),
+            left_right: GameConfig::default(),
+            order_entities: GameConfig::with_options_per_question(6),
+            were_they_alive_when: GameConfig::default(),
+            which_date: GameConfig::default(),
+        }
+    }
 }
 
 impl Config {
@@ -71,6 +195,29 @@ impl Config {
         self.colour_theme = colour_theme.to_owned();
     }
 
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+    }
+
+    pub fn date_format(&self) -> DateFormat {
+        self.date_format
+    }
+
+    pub fn set_date_format(&mut self, date_format: DateFormat) {
+        self.date_format = date_format;
+    }
+
+    /// Get the log filter (the default if the log levels aren't valid)
+    pub fn log_filter(&self) -> Targets {
+        self.log_levels
+            .parse()
+            .unwrap_or_else(|_| DEFAULT_LOG_LEVELS.parse().unwrap())
+    }
+
     pub fn database_path(&self) -> PathBuf {
         self.database_path.clone()
     }
@@ -120,6 +267,16 @@ fn default_config() -> Config {
         colour_theme: ColourTheme::System,
         database_path,
         custom_theme: AppColours::default(),
+        language: Language::default(),
+        date_format: DateFormat::default(),
+        show_now_line: false,
+        relative_time: false,
+        timeline_font: FontConfig::default(),
+        games: GamesConfig::default(),
+        sync: SyncConfig::default(),
+        open_editors_as_tabs: false,
+        local_api: LocalApiConfig::default(),
+        log_levels: default_log_levels(),
     }
 }
 
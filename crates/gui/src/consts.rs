@@ -10,6 +10,7 @@ pub struct WindowSizes {
     pub entity_view: WindowSize,
     pub timeline_edit: WindowSize,
     pub timeline_view: WindowSize,
+    pub timeline_compare: WindowSize,
     pub tag_edit: WindowSize,
     pub tag_view: WindowSize,
     pub app_colours: WindowSize,
@@ -31,7 +32,7 @@ pub const DEFAULT_WINDOW_SIZES: WindowSizes = WindowSizes {
     },
     entity_view: WindowSize {
         width: 250.0,
-        height: 300.0,
+        height: 450.0,
     },
     timeline_edit: WindowSize {
         width: 400.0,
@@ -41,6 +42,10 @@ pub const DEFAULT_WINDOW_SIZES: WindowSizes = WindowSizes {
         width: 850.0,
         height: 700.0,
     },
+    timeline_compare: WindowSize {
+        width: 850.0,
+        height: 850.0,
+    },
     tag_edit: WindowSize {
         width: 300.0,
         height: 300.0,
@@ -60,10 +65,25 @@ pub const DEFAULT_NEW_WINDOW_Y_OFFSET_FROM_MAIN_WINDOW: f32 = 30.0;
 
 pub const DESIRED_INPUT_TEXT_NUMBER_DAY_WIDTH: f32 = 30.0;
 pub const DESIRED_INPUT_TEXT_NUMBER_YEAR_WIDTH: f32 = 50.0;
+pub const DESIRED_INPUT_TEXT_DATE_WIDTH: f32 = 180.0;
+
+/// How much larger the timeline's text is when presenting it
+pub const PRESENTATION_FONT_SCALE: f32 = 1.5;
+
+/// How many entities are fetched at a time when opening a timeline view (the
+/// timeline is drawn once the first page has been fetched)
+pub const TIMELINE_VIEW_ENTITY_PAGE_SIZE: usize = 250;
 
 pub static VIEW_BUTTON_WIDTH: f32 = 30.0;
 pub static EDIT_BUTTON_WIDTH: f32 = 30.0;
 pub static REMOVE_BUTTON_WIDTH: f32 = 25.0;
+pub static SELECT_CHECKBOX_WIDTH: f32 = 20.0;
 
 pub static EDIT_SYMBOL: &str = "✏";
 pub static VIEW_SYMBOL: &str = "👁";
+pub static STARRED_SYMBOL: &str = "★";
+pub static UNSTARRED_SYMBOL: &str = "☆";
+
+/// Where timelines are viewed on the website (view links are
+/// `{TIMELINE_VIEW_LINK_URL}/{timeline_id}#{view_state}`)
+pub const TIMELINE_VIEW_LINK_URL: &str = "https://www.open-timeline.org/timeline";
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Drag & drop between parts of the GUI (e.g. dragging an entity from the
+//! search results onto a timeline)
+//!
+
+use eframe::egui::{Context, DragAndDrop};
+use open_timeline_core::{ReducedEntity, ReducedTimeline};
+
+/// Something being dragged
+#[derive(Debug, Clone)]
+pub enum DragPayload {
+    /// An entity (which can be dropped onto a timeline to add it)
+    Entity(ReducedEntity),
+}
+
+impl DragPayload {
+    /// Get whatever is currently being dragged (if anything)
+    pub fn current(ctx: &Context) -> Option<DragPayload> {
+        DragAndDrop::payload::<DragPayload>(ctx).map(|payload| (*payload).clone())
+    }
+
+    /// Stop dragging whatever is being dragged (e.g. because it was dropped)
+    pub fn clear(ctx: &Context) {
+        DragAndDrop::clear_payload(ctx);
+    }
+}
+
+/// Implementing types can be dragged
+pub trait ToDragPayload {
+    /// Get the payload to drag (if this can be dragged)
+    fn to_drag_payload(&self) -> Option<DragPayload>;
+}
+
+impl ToDragPayload for ReducedEntity {
+    fn to_drag_payload(&self) -> Option<DragPayload> {
+        Some(DragPayload::Entity(self.clone()))
+    }
+}
+
+impl ToDragPayload for ReducedTimeline {
+    fn to_drag_payload(&self) -> Option<DragPayload> {
+        None
+    }
+}
@@ -7,6 +7,7 @@
 mod common;
 mod decades;
 mod left_right;
+mod multiplayer;
 mod order_entities;
 mod were_they_alive_when;
 mod which_date;
@@ -14,6 +15,7 @@ mod which_date;
 pub use common::*;
 pub use decades::*;
 pub use left_right::*;
+pub use multiplayer::*;
 pub use order_entities::*;
 pub use were_they_alive_when::*;
 pub use which_date::*;
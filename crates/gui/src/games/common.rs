@@ -6,13 +6,15 @@
 
 use crate::common::ToOpenTimelineType;
 use crate::components::TimelineSubtimelineGui;
-use crate::config::SharedConfig;
+use crate::config::{GamesConfig, SharedConfig};
+use crate::spawn_operation;
 use crate::spawn_transaction_no_commit_send_result;
-use eframe::egui::{Context, Ui};
+use eframe::egui::{CollapsingHeader, Context, DragValue, Grid, Ui};
 use open_timeline_core::{IsReducedType, ReducedTimeline, TimelineView};
 use open_timeline_crud::{CrudError, FetchById};
-use open_timeline_games::Stats;
+use open_timeline_games::{GameConfig, Stats};
 use open_timeline_gui_core::{Draw, Valid, ValidityAsynchronous};
+use open_timeline_i18n::tr;
 use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
 
@@ -72,7 +74,7 @@ impl GameTimelineSearchAndFetch {
     pub fn draw_timeline_search_bar(&mut self, ctx: &Context, ui: &mut Ui, state: GameState) {
         if state == GameState::NotStarted {
             ui.horizontal(|ui| {
-                ui.label("Timeline");
+                ui.label(tr!("timeline"));
                 self.timeline_search_bar.draw(ctx, ui);
             });
             if self.timeline_search_bar.validity() == ValidityAsynchronous::Valid {
@@ -83,7 +85,7 @@ impl GameTimelineSearchAndFetch {
             }
         } else {
             ui.horizontal(|ui| {
-                ui.label("Timeline");
+                ui.label(tr!("timeline"));
                 open_timeline_gui_core::Label::strong(
                     ui,
                     self.timeline_playing_with.as_ref().unwrap().name().as_str(),
@@ -123,26 +125,92 @@ pub fn draw_stats(_ctx: &Context, ui: &mut Ui, stats: Stats) {
         let percent_correct = if stats.round > 1 {
             format!("{:.0}%", stats.percent_correct())
         } else {
-            String::from("N/A")
+            tr!("game-stats-not-applicable")
         };
         ui.horizontal(|ui| {
-            open_timeline_gui_core::Label::strong(ui, "Round");
+            open_timeline_gui_core::Label::strong(ui, &tr!("game-stats-round"));
             ui.label(format!("{}", stats.round));
         });
         ui.separator();
         ui.horizontal(|ui| {
-            open_timeline_gui_core::Label::strong(ui, "Correct");
+            open_timeline_gui_core::Label::strong(ui, &tr!("game-stats-correct"));
             ui.label(format!("{}", stats.correct_round_count));
         });
         ui.separator();
         ui.horizontal(|ui| {
-            open_timeline_gui_core::Label::strong(ui, "Incorrect");
+            open_timeline_gui_core::Label::strong(ui, &tr!("game-stats-incorrect"));
             ui.label(format!("{}", stats.incorrect_round_count));
         });
         ui.separator();
         ui.horizontal(|ui| {
-            open_timeline_gui_core::Label::strong(ui, "Correct (%)");
+            open_timeline_gui_core::Label::strong(ui, &tr!("game-stats-percent-correct"));
             ui.label(format!("{percent_correct}"));
         });
     });
 }
+
+/// Draw the inputs for a game's settings (the number of options per question
+/// is only shown for games that have options).  Returns whether any changed.
+pub fn draw_game_config(ui: &mut Ui, id: &str, config: &mut GameConfig, has_options: bool) -> bool {
+    let mut changed = false;
+    CollapsingHeader::new(tr!("game-settings"))
+        .id_salt(("game_settings", id))
+        .show(ui, |ui| {
+            Grid::new(("game_settings_grid", id)).show(ui, |ui| {
+                if has_options {
+                    ui.label(tr!("game-settings-options-per-question"));
+                    changed |= ui
+                        .add(DragValue::new(&mut config.options_per_question).range(
+                            GameConfig::MIN_OPTIONS_PER_QUESTION
+                                ..=GameConfig::MAX_OPTIONS_PER_QUESTION,
+                        ))
+                        .changed();
+                    ui.end_row();
+                }
+
+                ui.label(tr!("game-settings-rounds-per-game"));
+                ui.horizontal(|ui| {
+                    let mut limited = config.rounds_per_game.is_some();
+                    if ui
+                        .checkbox(&mut limited, tr!("game-settings-limit-rounds"))
+                        .changed()
+                    {
+                        config.rounds_per_game = limited.then_some(10);
+                        changed = true;
+                    }
+                    if let Some(rounds) = config.rounds_per_game.as_mut() {
+                        changed |= ui.add(DragValue::new(rounds).range(1..=1000)).changed();
+                    }
+                });
+                ui.end_row();
+
+                ui.label(tr!("game-settings-min-pool-size"));
+                changed |= ui
+                    .add(DragValue::new(&mut config.min_pool_size).range(1..=10000))
+                    .changed();
+                ui.end_row();
+
+                ui.label(tr!("game-settings-only-ended-entities"));
+                changed |= ui.checkbox(&mut config.only_ended_entities, "").changed();
+                ui.end_row();
+            });
+        });
+    changed
+}
+
+/// Update a game's settings in the shared config and save the config to disk
+pub fn save_game_config(
+    shared_config: SharedConfig,
+    update: impl FnOnce(&mut GamesConfig) + Send + 'static,
+) {
+    spawn_operation!(async move {
+        let config = {
+            let mut shared_config = shared_config.write().await;
+            update(&mut shared_config.config.games);
+            shared_config.config.clone()
+        };
+        if let Err(error) = config.save().await {
+            warn!("Error saving game settings: {error}");
+        }
+    });
+}
@@ -5,11 +5,16 @@
 //!
 
 use crate::config::SharedConfig;
-use crate::games::{GameState, GameTimelineSearchAndFetch, draw_stats};
+use crate::games::{
+    GameState, GameTimelineSearchAndFetch, MultiplayerGui, draw_game_config, draw_stats,
+    save_game_config,
+};
 use eframe::egui::{self, Context, Ui, Vec2};
 use open_timeline_core::HasIdAndName;
-use open_timeline_games::{AnswerOption, GameManagement, decades::DecadesGame};
+use open_timeline_games::{AnswerOption, GameError, GameManagement, decades::DecadesGame};
 use open_timeline_gui_core::{Draw, widget_x_spacing};
+use open_timeline_i18n::tr;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct DecadesGameGui {
@@ -21,15 +26,26 @@ pub struct DecadesGameGui {
 
     /// Search and fetch the timeline used to play the game
     game_timeline_search_and_fetch: GameTimelineSearchAndFetch,
+
+    /// The players (if multiplayer)
+    multiplayer: MultiplayerGui,
+
+    /// Database pool & settings
+    shared_config: SharedConfig,
 }
 
 impl DecadesGameGui {
     /// Create new DecadesGameGui
     pub fn new(shared_config: SharedConfig) -> Self {
+        let game = DecadesGame::new(shared_config.blocking_read().config.games.decades);
         Self {
-            game: DecadesGame::new(),
+            game,
             state: GameState::NotStarted,
-            game_timeline_search_and_fetch: GameTimelineSearchAndFetch::new(shared_config),
+            game_timeline_search_and_fetch: GameTimelineSearchAndFetch::new(Arc::clone(
+                &shared_config,
+            )),
+            multiplayer: MultiplayerGui::new(),
+            shared_config,
         }
     }
 
@@ -76,17 +92,17 @@ impl DecadesGameGui {
                     });
                 });
             } else {
-                open_timeline_gui_core::Label::weak(ui, "No options");
+                open_timeline_gui_core::Label::weak(ui, &tr!("game-no-options"));
                 self.draw_new_game_button(ui);
             }
         } else {
-            open_timeline_gui_core::Label::weak(ui, "No question");
+            open_timeline_gui_core::Label::weak(ui, &tr!("game-no-question"));
             self.draw_new_game_button(ui);
         }
     }
 
     fn draw_new_game_button(&mut self, ui: &mut Ui) {
-        if open_timeline_gui_core::Button::tall_full_width(ui, "New Game").clicked() {
+        if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-new")).clicked() {
             self.game.new_game();
             self.state = GameState::NotStarted;
         }
@@ -104,9 +120,24 @@ impl Draw for DecadesGameGui {
             .draw_timeline_search_bar(ctx, ui, self.state);
         ui.separator();
 
+        // Settings & players (chosen before the game starts)
+        if self.state == GameState::NotStarted {
+            if draw_game_config(ui, "decades", &mut self.game.config, true) {
+                let config = self.game.config;
+                save_game_config(Arc::clone(&self.shared_config), move |games| {
+                    games.decades = config
+                });
+            }
+            self.multiplayer.draw_setup(ui);
+            ui.separator();
+        }
+
         // Stats
         if self.state.has_started() {
-            draw_stats(ctx, ui, self.game.stats);
+            match self.multiplayer.is_playing() {
+                true => self.multiplayer.draw_scoreboard(ui),
+                false => draw_stats(ctx, ui, self.game.stats),
+            }
             ui.separator();
         }
 
@@ -116,11 +147,15 @@ impl Draw for DecadesGameGui {
                 ui.add_enabled_ui(
                     self.game_timeline_search_and_fetch
                         .timeline_playing_with()
-                        .is_some(),
+                        .is_some()
+                        && self.multiplayer.can_start(),
                     |ui| {
-                        if open_timeline_gui_core::Button::tall_full_width(ui, "Start").clicked() {
+                        if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-start"))
+                            .clicked()
+                        {
                             self.game.new_game();
                             self.game_timeline_search_and_fetch.request_fetch_timeline();
+                            self.multiplayer.start();
                             self.state = GameState::StartedWaitingForTimeline;
                         }
                     },
@@ -146,27 +181,36 @@ impl Draw for DecadesGameGui {
                 }
             }
             GameState::WaitingForAnswer => {
+                self.multiplayer.draw_current_player(ui);
                 self.draw_question(ctx, ui, true);
+                if self.state == GameState::WaitingForNextRound {
+                    self.multiplayer.record_answer(self.game.last_answer);
+                }
             }
             GameState::WaitingForNextRound => {
                 self.draw_question(ctx, ui, false);
                 ui.separator();
                 if let Some(last_answer) = self.game.last_answer.as_ref() {
                     ui.horizontal(|ui| {
-                        ui.label("Last Answer");
-                        open_timeline_gui_core::Label::strong(ui, &format!("{last_answer:?}"));
+                        ui.label(tr!("game-last-answer"));
+                        open_timeline_gui_core::Label::strong(ui, &last_answer.to_string());
                     });
                     ui.separator();
                 }
-                if open_timeline_gui_core::Button::tall_full_width(ui, "End").clicked() {
+                if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-end")).clicked() {
                     self.state = GameState::Finished;
                 }
-                if open_timeline_gui_core::Button::tall_full_width(ui, "Next Round").clicked() {
-                    let _ = self.game.setup_next_round();
-                    self.state = GameState::WaitingForAnswer;
+                if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-next-round"))
+                    .clicked()
+                {
+                    match self.game.setup_next_round() {
+                        Err(GameError::NoRoundsLeft) => self.state = GameState::Finished,
+                        _ => self.state = GameState::WaitingForAnswer,
+                    }
                 }
             }
             GameState::Finished => {
+                self.multiplayer.draw_winners(ui);
                 self.draw_new_game_button(ui);
             }
         }
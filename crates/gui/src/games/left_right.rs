@@ -5,12 +5,17 @@
 //!
 
 use crate::config::SharedConfig;
-use crate::games::{GameState, GameTimelineSearchAndFetch, draw_stats};
+use crate::games::{
+    GameState, GameTimelineSearchAndFetch, MultiplayerGui, draw_game_config, draw_stats,
+    save_game_config,
+};
 use eframe::egui::{self, Align, Context, Layout, TextWrapMode, Ui, Vec2};
 use open_timeline_core::HasIdAndName;
-use open_timeline_games::GameManagement;
 use open_timeline_games::left_right::{LeftOrRight, LeftRightGame};
+use open_timeline_games::{GameError, GameManagement};
 use open_timeline_gui_core::{Draw, widget_x_spacing};
+use open_timeline_i18n::tr;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct LeftRightGameGui {
@@ -25,16 +30,27 @@ pub struct LeftRightGameGui {
 
     /// Search and fetch the timeline used to play the game
     game_timeline_search_and_fetch: GameTimelineSearchAndFetch,
+
+    /// The players (if multiplayer)
+    multiplayer: MultiplayerGui,
+
+    /// Database pool & settings
+    shared_config: SharedConfig,
 }
 
 impl LeftRightGameGui {
     /// Create new LeftRightGameGui
     pub fn new(shared_config: SharedConfig) -> Self {
+        let game = LeftRightGame::new(shared_config.blocking_read().config.games.left_right);
         Self {
-            game: LeftRightGame::new(),
+            game,
             state: GameState::NotStarted,
             last_question_option_chosen: None,
-            game_timeline_search_and_fetch: GameTimelineSearchAndFetch::new(shared_config),
+            game_timeline_search_and_fetch: GameTimelineSearchAndFetch::new(Arc::clone(
+                &shared_config,
+            )),
+            multiplayer: MultiplayerGui::new(),
+            shared_config,
         }
     }
 
@@ -76,13 +92,13 @@ impl LeftRightGameGui {
                 });
             });
         } else {
-            open_timeline_gui_core::Label::weak(ui, "No questions");
+            open_timeline_gui_core::Label::weak(ui, &tr!("game-no-questions"));
             self.draw_new_game_button(ui);
         }
     }
 
     fn draw_new_game_button(&mut self, ui: &mut Ui) {
-        if open_timeline_gui_core::Button::tall_full_width(ui, "New Game").clicked() {
+        if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-new")).clicked() {
             self.game.new_game();
             self.state = GameState::NotStarted;
         }
@@ -100,9 +116,24 @@ impl Draw for LeftRightGameGui {
             .draw_timeline_search_bar(ctx, ui, self.state);
         ui.separator();
 
+        // Settings & players (chosen before the game starts)
+        if self.state == GameState::NotStarted {
+            if draw_game_config(ui, "left_right", &mut self.game.config, false) {
+                let config = self.game.config;
+                save_game_config(Arc::clone(&self.shared_config), move |games| {
+                    games.left_right = config
+                });
+            }
+            self.multiplayer.draw_setup(ui);
+            ui.separator();
+        }
+
         // Stats
         if self.state.has_started() {
-            draw_stats(ctx, ui, self.game.stats);
+            match self.multiplayer.is_playing() {
+                true => self.multiplayer.draw_scoreboard(ui),
+                false => draw_stats(ctx, ui, self.game.stats),
+            }
             ui.separator();
         }
 
@@ -112,11 +143,15 @@ impl Draw for LeftRightGameGui {
                 ui.add_enabled_ui(
                     self.game_timeline_search_and_fetch
                         .timeline_playing_with()
-                        .is_some(),
+                        .is_some()
+                        && self.multiplayer.can_start(),
                     |ui| {
-                        if open_timeline_gui_core::Button::tall_full_width(ui, "Start").clicked() {
+                        if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-start"))
+                            .clicked()
+                        {
                             self.game.new_game();
                             self.game_timeline_search_and_fetch.request_fetch_timeline();
+                            self.multiplayer.start();
                             self.state = GameState::StartedWaitingForTimeline;
                         }
                     },
@@ -142,27 +177,36 @@ impl Draw for LeftRightGameGui {
                 }
             }
             GameState::WaitingForAnswer => {
+                self.multiplayer.draw_current_player(ui);
                 self.draw_question(ctx, ui, true);
+                if self.state == GameState::WaitingForNextRound {
+                    self.multiplayer.record_answer(self.game.last_answer);
+                }
             }
             GameState::WaitingForNextRound => {
                 self.draw_question(ctx, ui, false);
                 ui.separator();
                 if let Some(last_answer) = self.game.last_answer.as_ref() {
                     ui.horizontal(|ui| {
-                        ui.label("Last Answer");
-                        open_timeline_gui_core::Label::strong(ui, &format!("{last_answer:?}"));
+                        ui.label(tr!("game-last-answer"));
+                        open_timeline_gui_core::Label::strong(ui, &last_answer.to_string());
                     });
                     ui.separator();
                 }
-                if open_timeline_gui_core::Button::tall_full_width(ui, "End").clicked() {
+                if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-end")).clicked() {
                     self.state = GameState::Finished;
                 }
-                if open_timeline_gui_core::Button::tall_full_width(ui, "Next Round").clicked() {
-                    let _ = self.game.setup_next_round();
-                    self.state = GameState::WaitingForAnswer;
+                if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-next-round"))
+                    .clicked()
+                {
+                    match self.game.setup_next_round() {
+                        Err(GameError::NoRoundsLeft) => self.state = GameState::Finished,
+                        _ => self.state = GameState::WaitingForAnswer,
+                    }
                 }
             }
             GameState::Finished => {
+                self.multiplayer.draw_winners(ui);
                 self.draw_new_game_button(ui);
             }
         }
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Multiplayer (hot-seat) support shared by all egui games
+//!
+
+use eframe::egui::{Button, Grid, Key, TextEdit, Ui};
+use open_timeline_games::multiplayer::Match;
+use open_timeline_games::{Answer, GameError};
+use open_timeline_i18n::tr;
+
+/// Entering player names, taking turns, and the scoreboard
+#[derive(Debug, Default)]
+pub struct MultiplayerGui {
+    /// Whether the game is played by multiple players
+    enabled: bool,
+
+    /// The names of the players (in turn order)
+    player_names: Vec<String>,
+
+    /// The name of the player being added
+    new_player_name: String,
+
+    /// The match being played (if multiplayer and the game has started)
+    game_match: Option<Match>,
+}
+
+impl MultiplayerGui {
+    /// Create a new (disabled) multiplayer GUI
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the game can be started (i.e. single player, or multiplayer
+    /// with valid player names)
+    pub fn can_start(&self) -> bool {
+        !self.enabled || Match::new(&self.player_names).is_ok()
+    }
+
+    /// Start a new match (if multiplayer)
+    pub fn start(&mut self) {
+        self.game_match = match self.enabled {
+            true => Match::new(&self.player_names).ok(),
+            false => None,
+        };
+    }
+
+    /// Record the answer given by the player whose turn it was
+    pub fn record_answer(&mut self, answer: Option<Answer>) {
+        if let (Some(game_match), Some(answer)) = (self.game_match.as_mut(), answer) {
+            game_match.record_answer(answer);
+        }
+    }
+
+    /// Whether a multiplayer match is being played
+    pub fn is_playing(&self) -> bool {
+        self.game_match.is_some()
+    }
+
+    /// Draw the inputs for choosing multiplayer & entering player names
+    pub fn draw_setup(&mut self, ui: &mut Ui) {
+        ui.checkbox(&mut self.enabled, tr!("game-multiplayer"));
+        if !self.enabled {
+            return;
+        }
+
+        // Players
+        let mut remove = None;
+        for (index, name) in self.player_names.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if open_timeline_gui_core::Button::remove(ui).clicked() {
+                    remove = Some(index);
+                }
+                ui.label(name);
+            });
+        }
+        if let Some(index) = remove {
+            self.player_names.remove(index);
+        }
+
+        // Add a player
+        ui.horizontal(|ui| {
+            let name = self.new_player_name.trim().to_string();
+            let add = ui.add_enabled(
+                !name.is_empty(),
+                Button::new(tr!("game-multiplayer-add-player")),
+            );
+            let input = ui.add(
+                TextEdit::singleline(&mut self.new_player_name)
+                    .hint_text(tr!("game-multiplayer-player-name"))
+                    .desired_width(f32::INFINITY),
+            );
+            let entered = input.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            if !name.is_empty() && (add.clicked() || entered) {
+                self.player_names.push(name);
+                self.new_player_name.clear();
+            }
+        });
+
+        // Let the user know why the game can't start
+        match Match::new(&self.player_names) {
+            Ok(_) => (),
+            Err(GameError::DuplicatePlayerName) => {
+                open_timeline_gui_core::Label::weak(ui, &tr!("game-multiplayer-duplicate-player"));
+            }
+            Err(_) => {
+                open_timeline_gui_core::Label::weak(ui, &tr!("game-multiplayer-no-players"));
+            }
+        }
+    }
+
+    /// Draw whose turn it is
+    pub fn draw_current_player(&self, ui: &mut Ui) {
+        if let Some(game_match) = self.game_match.as_ref() {
+            let player = &game_match.current_player().name;
+            open_timeline_gui_core::Label::strong(
+                ui,
+                &tr!("game-multiplayer-turn", player = player),
+            );
+        }
+    }
+
+    /// Draw every player's stats
+    pub fn draw_scoreboard(&self, ui: &mut Ui) {
+        let Some(game_match) = self.game_match.as_ref() else {
+            return;
+        };
+        Grid::new("game_multiplayer_scoreboard")
+            .striped(true)
+            .show(ui, |ui| {
+                open_timeline_gui_core::Label::strong(ui, &tr!("game-multiplayer-player"));
+                open_timeline_gui_core::Label::strong(ui, &tr!("game-stats-round"));
+                open_timeline_gui_core::Label::strong(ui, &tr!("game-stats-correct"));
+                open_timeline_gui_core::Label::strong(ui, &tr!("game-stats-incorrect"));
+                open_timeline_gui_core::Label::strong(ui, &tr!("game-stats-percent-correct"));
+                ui.end_row();
+                for player in game_match.players() {
+                    let stats = player.stats;
+                    let percent_correct = match stats.round > 0 {
+                        true => format!("{:.0}%", stats.percent_correct()),
+                        false => tr!("game-stats-not-applicable"),
+                    };
+                    ui.label(&player.name);
+                    ui.label(format!("{}", stats.round));
+                    ui.label(format!("{}", stats.correct_round_count));
+                    ui.label(format!("{}", stats.incorrect_round_count));
+                    ui.label(percent_correct);
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// Draw the winner(s) of the match
+    pub fn draw_winners(&self, ui: &mut Ui) {
+        let Some(game_match) = self.game_match.as_ref() else {
+            return;
+        };
+        let winners = game_match.winners();
+        let players = winners
+            .iter()
+            .map(|player| player.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let text = match winners.len() {
+            0 => return,
+            1 => tr!("game-multiplayer-winner", players = players),
+            _ => tr!("game-multiplayer-draw", players = players),
+        };
+        open_timeline_gui_core::Label::sub_heading(ui, &text);
+    }
+}
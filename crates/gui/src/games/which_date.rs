@@ -5,12 +5,17 @@
 //!
 
 use crate::config::SharedConfig;
-use crate::games::{GameState, GameTimelineSearchAndFetch, draw_stats};
+use crate::games::{
+    GameState, GameTimelineSearchAndFetch, MultiplayerGui, draw_game_config, draw_stats,
+    save_game_config,
+};
 use eframe::egui::{self, Context, FontId, RichText, TextEdit, Ui};
 use open_timeline_core::HasIdAndName;
-use open_timeline_games::GameManagement;
 use open_timeline_games::which_date::{GameVariant, WhichDateGame, YearOrDecade};
+use open_timeline_games::{GameError, GameManagement};
 use open_timeline_gui_core::Draw;
+use open_timeline_i18n::tr;
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct WhichDateGameGui {
@@ -25,16 +30,27 @@ pub struct WhichDateGameGui {
 
     /// Search and fetch the timeline used to play the game
     game_timeline_search_and_fetch: GameTimelineSearchAndFetch,
+
+    /// The players (if multiplayer)
+    multiplayer: MultiplayerGui,
+
+    /// Database pool & settings
+    shared_config: SharedConfig,
 }
 
 impl WhichDateGameGui {
     /// Create new WhichDateGameGui
     pub fn new(shared_config: SharedConfig) -> Self {
+        let game = WhichDateGame::new(shared_config.blocking_read().config.games.which_date);
         Self {
-            game: WhichDateGame::new(),
+            game,
             number_as_str: String::new(),
             state: GameState::NotStarted,
-            game_timeline_search_and_fetch: GameTimelineSearchAndFetch::new(shared_config),
+            game_timeline_search_and_fetch: GameTimelineSearchAndFetch::new(Arc::clone(
+                &shared_config,
+            )),
+            multiplayer: MultiplayerGui::new(),
+            shared_config,
         }
     }
 
@@ -64,7 +80,9 @@ impl WhichDateGameGui {
                 if enabled {
                     let answer = self.number_as_str.parse::<i32>();
                     ui.add_enabled_ui(answer.is_ok(), |ui| {
-                        if open_timeline_gui_core::Button::tall_full_width(ui, "Submit").clicked() {
+                        if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-submit"))
+                            .clicked()
+                        {
                             if let Ok(answer) = answer {
                                 let _ = self.game.check_answer(answer);
                                 self.state = GameState::WaitingForNextRound;
@@ -74,13 +92,13 @@ impl WhichDateGameGui {
                 }
             });
         } else {
-            open_timeline_gui_core::Label::weak(ui, "No question");
+            open_timeline_gui_core::Label::weak(ui, &tr!("game-no-question"));
             self.draw_new_game_button(ui);
         }
     }
 
     fn draw_new_game_button(&mut self, ui: &mut Ui) {
-        if open_timeline_gui_core::Button::tall_full_width(ui, "New Game").clicked() {
+        if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-new")).clicked() {
             self.game.new_game();
             self.state = GameState::NotStarted;
         }
@@ -105,31 +123,46 @@ impl Draw for WhichDateGameGui {
                 ui.radio_value(
                     &mut self.game.variant,
                     GameVariant::StartDate,
-                    "Enter start date",
+                    tr!("game-enter-start-date"),
                 );
                 ui.radio_value(
                     &mut self.game.variant,
                     GameVariant::EndDate,
-                    "Enter end date",
+                    tr!("game-enter-end-date"),
                 );
                 ui.separator();
                 ui.radio_value(
                     &mut self.game.year_or_decade,
                     YearOrDecade::Decade,
-                    "Enter decade",
+                    tr!("game-enter-decade"),
                 );
                 ui.radio_value(
                     &mut self.game.year_or_decade,
                     YearOrDecade::Year,
-                    "Enter year",
+                    tr!("game-enter-year"),
                 );
             });
         });
         ui.separator();
 
+        // Settings & players (chosen before the game starts)
+        if self.state == GameState::NotStarted {
+            if draw_game_config(ui, "which_date", &mut self.game.config, false) {
+                let config = self.game.config;
+                save_game_config(Arc::clone(&self.shared_config), move |games| {
+                    games.which_date = config
+                });
+            }
+            self.multiplayer.draw_setup(ui);
+            ui.separator();
+        }
+
         // Stats
         if self.state.has_started() {
-            draw_stats(ctx, ui, self.game.stats);
+            match self.multiplayer.is_playing() {
+                true => self.multiplayer.draw_scoreboard(ui),
+                false => draw_stats(ctx, ui, self.game.stats),
+            }
             ui.separator();
         }
 
@@ -139,11 +172,15 @@ impl Draw for WhichDateGameGui {
                 ui.add_enabled_ui(
                     self.game_timeline_search_and_fetch
                         .timeline_playing_with()
-                        .is_some(),
+                        .is_some()
+                        && self.multiplayer.can_start(),
                     |ui| {
-                        if open_timeline_gui_core::Button::tall_full_width(ui, "Start").clicked() {
+                        if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-start"))
+                            .clicked()
+                        {
                             self.game.new_game();
                             self.game_timeline_search_and_fetch.request_fetch_timeline();
+                            self.multiplayer.start();
                             self.state = GameState::StartedWaitingForTimeline;
                         }
                     },
@@ -169,28 +206,37 @@ impl Draw for WhichDateGameGui {
                 }
             }
             GameState::WaitingForAnswer => {
+                self.multiplayer.draw_current_player(ui);
                 self.draw_question(ctx, ui, true);
+                if self.state == GameState::WaitingForNextRound {
+                    self.multiplayer.record_answer(self.game.last_answer);
+                }
             }
             GameState::WaitingForNextRound => {
                 self.draw_question(ctx, ui, false);
                 ui.separator();
                 if let Some(last_answer) = self.game.last_answer.as_ref() {
                     ui.horizontal(|ui| {
-                        ui.label("Last Answer");
-                        open_timeline_gui_core::Label::strong(ui, &format!("{last_answer:?}"));
+                        ui.label(tr!("game-last-answer"));
+                        open_timeline_gui_core::Label::strong(ui, &last_answer.to_string());
                     });
                     ui.separator();
                 }
-                if open_timeline_gui_core::Button::tall_full_width(ui, "End").clicked() {
+                if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-end")).clicked() {
                     self.state = GameState::Finished;
                 }
-                if open_timeline_gui_core::Button::tall_full_width(ui, "Next Round").clicked() {
+                if open_timeline_gui_core::Button::tall_full_width(ui, tr!("game-next-round"))
+                    .clicked()
+                {
                     self.number_as_str.clear();
-                    let _ = self.game.setup_next_round();
-                    self.state = GameState::WaitingForAnswer;
+                    match self.game.setup_next_round() {
+                        Err(GameError::NoRoundsLeft) => self.state = GameState::Finished,
+                        _ => self.state = GameState::WaitingForAnswer,
+                    }
                 }
             }
             GameState::Finished => {
+                self.multiplayer.draw_winners(ui);
                 self.draw_new_game_button(ui);
             }
         }
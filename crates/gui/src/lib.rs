@@ -7,21 +7,27 @@
 //! used to build the OpenTimeline native GUI application.
 //!
 
+mod activity_log;
 mod app;
 mod app_colours;
+mod clipboard;
+mod command_palette;
 mod common;
 mod components;
 mod config;
 mod consts;
+mod drag_and_drop;
 mod games;
 mod macros;
+mod operation;
 mod primary_window;
 mod shortcuts;
 mod windows;
 
+pub use activity_log::ActivityLog;
 pub use app::OpenTimelineApp;
-pub use config::Config;
+pub use config::{Config, DEFAULT_LOG_LEVELS};
 pub use consts::DEFAULT_WINDOW_SIZES;
 
 #[macro_use]
-extern crate log;
+extern crate tracing;
@@ -4,6 +4,19 @@
 //! All macros
 //!
 
+/// Spawn a task that runs in the background as an operation with its own ID
+/// (see [`crate::operation`]), so that what it does can be followed through
+/// the logs
+#[macro_export]
+macro_rules! spawn_operation {
+    ($future:expr $(,)?) => {
+        tokio::spawn(tracing::Instrument::instrument(
+            $future,
+            $crate::operation::operation_span(module_path!()),
+        ))
+    };
+}
+
 // TODO: I used (unbounded, ...) on a bounded channel and got no errors or warnings etc (catch this)
 /// Run a code block that requires a database connection in a context in which
 /// one is made available.  The transaction is not committed and thus this
@@ -11,7 +24,7 @@
 #[macro_export]
 macro_rules! spawn_transaction_no_commit_send_result {
     ($shared_config:ident, bounded, $tx:ident, $fetch_fn:expr) => {
-        tokio::spawn(async move {
+        $crate::spawn_operation!(async move {
             // Get database path
             let db_pool = $shared_config.read().await.db_pool.clone();
 
@@ -29,7 +42,7 @@ macro_rules! spawn_transaction_no_commit_send_result {
     };
 
     ($shared_config:ident, unbounded, $tx:ident, $fetch_fn:expr) => {
-        tokio::spawn(async move {
+        $crate::spawn_operation!(async move {
             // Get database path
             let db_pool = $shared_config.read().await.db_pool.clone();
 
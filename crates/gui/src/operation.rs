@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Operations are the work done in the background for the GUI (e.g. saving an
+//! entity).  Each has its own ID, which is attached to everything logged while
+//! it runs (including by the CRUD layer & the SQL it runs), so that an action
+//! can be followed through the logs.  See [`crate::spawn_operation!`].
+//!
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::Span;
+
+/// The ID of the next operation
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Create the span of a new operation, started from the `source` module
+pub fn operation_span(source: &'static str) -> Span {
+    let id = NEXT_OPERATION_ID.fetch_add(1, Ordering::Relaxed);
+    info_span!("operation", id, source)
+}
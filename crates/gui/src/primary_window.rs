@@ -5,20 +5,34 @@
 //! (not including games)
 //!
 
+mod activity;
 mod app_info;
 mod backup_merge_restore;
 mod config;
 mod databse_stats;
 mod entity_counts;
+mod favourites;
+mod import;
+mod insights;
+mod recent_changes;
+mod script_console;
 mod search;
+mod sync;
 mod tag_counts;
 mod timeline_counts;
 
+pub use activity::*;
 pub use app_info::*;
 pub use backup_merge_restore::*;
 pub use config::*;
 pub use databse_stats::*;
 pub use entity_counts::*;
+pub use favourites::*;
+pub use import::*;
+pub use insights::*;
+pub use recent_changes::*;
+pub use script_console::*;
 pub use search::*;
+pub use sync::*;
 pub use tag_counts::*;
 pub use timeline_counts::*;
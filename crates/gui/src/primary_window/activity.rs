@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The activity GUI panel in the main window (recent background operations,
+//! local API requests, & any warnings or errors)
+//!
+
+use crate::activity_log::{ActivityEntry, ActivityLog};
+use eframe::egui::{self, Context, RichText, ScrollArea, Ui};
+use egui_extras::{Column, TableBuilder};
+use open_timeline_gui_core::{Draw, body_text_height, widget_x_spacing};
+use tracing::Level;
+
+/// The activity GUI panel in the main window
+#[derive(Debug)]
+pub struct ActivityGui {
+    /// Where the activity is recorded
+    activity_log: ActivityLog,
+
+    /// Whether only warnings & errors are shown
+    problems_only: bool,
+}
+
+impl ActivityGui {
+    /// Create a new activity GUI panel
+    pub fn new(activity_log: ActivityLog) -> Self {
+        Self {
+            activity_log,
+            problems_only: false,
+        }
+    }
+
+    /// Draw the entry's level (coloured if it's a warning or an error)
+    fn draw_level(ui: &mut Ui, entry: &ActivityEntry) {
+        let text = RichText::new(entry.level.as_str());
+        let text = match entry.level {
+            Level::ERROR => text.color(ui.visuals().error_fg_color),
+            Level::WARN => text.color(ui.visuals().warn_fg_color),
+            _ => text,
+        };
+        ui.label(text);
+    }
+}
+
+impl Draw for ActivityGui {
+    fn draw(&mut self, _ctx: &Context, ui: &mut Ui) {
+        // Description
+        let description = "Recent background activity (e.g. reloads, saves, backups, syncs, & requests to the local API server), newest first.  Failures are shown here as well as where they happened.";
+        open_timeline_gui_core::Label::description(ui, description);
+        ui.add_space(5.0);
+
+        // Options
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.problems_only, "Only warnings & errors");
+            if ui.button("Clear").clicked() {
+                self.activity_log.clear();
+            }
+        });
+        ui.separator();
+
+        let entries: Vec<ActivityEntry> = self
+            .activity_log
+            .entries()
+            .into_iter()
+            .filter(|entry| !self.problems_only || entry.level <= Level::WARN)
+            .collect();
+        if entries.is_empty() {
+            open_timeline_gui_core::Label::none(ui);
+            return;
+        }
+
+        // Sizes
+        let row_height = body_text_height(ui);
+        let spacing = widget_x_spacing(ui);
+        let time_col_width = 70.0;
+        let level_col_width = 50.0;
+        let message_col_width = ui.available_width() - time_col_width - level_col_width;
+        let message_col_width = (message_col_width - (spacing * 2.0)).max(0.0);
+
+        ScrollArea::horizontal().show(ui, |ui| {
+            TableBuilder::new(ui)
+                .id_salt("activity_table")
+                .striped(true)
+                .column(Column::exact(time_col_width))
+                .column(Column::exact(level_col_width))
+                .column(Column::exact(message_col_width))
+                .body(|body| {
+                    body.rows(row_height, entries.len(), |mut row| {
+                        let entry = &entries[row.index()];
+                        row.col(|ui| {
+                            ui.label(entry.time.format("%H:%M:%S").to_string())
+                                .on_hover_text(entry.time.to_rfc3339());
+                        });
+                        row.col(|ui| Self::draw_level(ui, entry));
+                        row.col(|ui| {
+                            ui.add(egui::Label::new(&entry.message).truncate());
+                        });
+                    });
+                });
+        });
+    }
+}
@@ -4,19 +4,27 @@
 //! Controls for backup/merge/restore to/from local files and web APIs
 //!
 
+use crate::activity_log::ACTIVITY;
+use crate::app::ActionRequest;
 use crate::config::SharedConfig;
-use eframe::egui::{self, Align, Context, Grid, Layout, Response, Spinner, TextEdit, Ui};
+use crate::spawn_operation;
+use eframe::egui::{
+    self, Align, Context, Grid, Layout, ProgressBar, Response, Spinner, TextEdit, Ui,
+};
 use open_timeline_core::{Entity, TimelineEdit};
-use open_timeline_crud::{BackupMergeRestore, BackupRestoreMergeError, backup, merge, restore};
-use open_timeline_gui_core::{CheckForUpdates, Draw};
+use open_timeline_crud::{
+    BackupMergeRestore, BackupRestoreMergeError, RestoreMonitor, RestorePhase, RestoreProgress,
+    backup, merge_with_progress, restore_with_progress,
+};
+use open_timeline_gui_core::{CheckForUpdates, Draw, Toast};
 use open_timeline_gui_core::{DisplayStatus, GuiStatus};
 use std::fs::File;
 use std::io::BufWriter;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tempdir::TempDir;
-use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::{Receiver, error::TryRecvError};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 /// The backup|merge|restore GUI panel in the main window
 #[derive(Debug)]
@@ -27,6 +35,15 @@ pub struct BackupMergeRestoreGui {
     /// Indicates which operation has been requested, if any.
     backup_merge_restore: Option<BackupMergeRestore>,
 
+    /// Receive the progress of a merge/restore
+    rx_progress: Option<UnboundedReceiver<RestoreProgress>>,
+
+    /// The latest progress of the merge/restore in progress (if any)
+    progress: Option<RestoreProgress>,
+
+    /// Used to cancel the merge/restore in progress (if any)
+    monitor: Option<RestoreMonitor>,
+
     /// The status of operations (which may be none)
     status: Status,
 
@@ -34,6 +51,9 @@ pub struct BackupMergeRestoreGui {
     /// operation has been executed successfully (i.e. reloads may be required)
     tx_crud_operation_executed: UnboundedSender<()>,
 
+    /// For notifying the user when an operation finishes
+    tx_action_request: UnboundedSender<ActionRequest>,
+
     /// Database pool
     shared_config: SharedConfig,
 
@@ -67,6 +87,9 @@ enum Status {
     /// The operation last requested has failed
     Failure(BackupRestoreMergeError),
 
+    /// The operation last requested was cancelled (nothing was changed)
+    Cancelled(BackupMergeRestore),
+
     /// The operation last requested is in progress
     InProgress,
 }
@@ -79,6 +102,9 @@ impl DisplayStatus for Status {
                 ui.add(egui::Label::new(format!("Success: {operation_requested:?}")).truncate())
             }
             Self::Failure(error) => ui.add(egui::Label::new(format!("Error: {error}")).truncate()),
+            Self::Cancelled(operation_requested) => {
+                ui.add(egui::Label::new(format!("Cancelled: {operation_requested:?}")).truncate())
+            }
             Self::InProgress => ui.add(Spinner::new()),
         }
     }
@@ -89,12 +115,17 @@ impl BackupMergeRestoreGui {
     pub fn new(
         shared_config: SharedConfig,
         tx_crud_operation_executed: UnboundedSender<()>,
+        tx_action_request: UnboundedSender<ActionRequest>,
     ) -> Self {
         Self {
             rx_backup_restore_merge_update: None,
             backup_merge_restore: None,
+            rx_progress: None,
+            progress: None,
+            monitor: None,
             status: Status::None,
             tx_crud_operation_executed,
+            tx_action_request,
             shared_config,
             open_timeline_api: ApiEndpoints {
                 entities: ApiEndpointConfig {
@@ -111,22 +142,47 @@ impl BackupMergeRestoreGui {
 
     /// Check for an update on the status of the operation requested
     fn check_for_msg(&mut self) {
+        if let Some(rx) = self.rx_progress.as_mut() {
+            while let Ok(progress) = rx.try_recv() {
+                self.progress = Some(progress);
+            }
+        }
         if let Some(backup_merge_restore) = &self.backup_merge_restore {
             if let Some(rx) = self.rx_backup_restore_merge_update.as_mut() {
                 match rx.try_recv() {
                     Ok(result) => {
                         debug!("Recv backup|merge|restore update response");
+                        let toast = match &result {
+                            Ok(()) => Toast::success(format!("Finished: {backup_merge_restore:?}")),
+                            Err(BackupRestoreMergeError::Cancelled) => {
+                                Toast::info(format!("Cancelled: {backup_merge_restore:?}"))
+                            }
+                            Err(error) => {
+                                Toast::error(format!("Failed: {backup_merge_restore:?}: {error}"))
+                            }
+                        };
+                        let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                         match result {
                             Ok(()) => {
+                                info!(target: ACTIVITY, "Finished: {backup_merge_restore:?}");
                                 self.rx_backup_restore_merge_update = None;
                                 self.status = Status::Success(backup_merge_restore.to_owned());
                                 let _ = self.tx_crud_operation_executed.send(());
                             }
+                            Err(BackupRestoreMergeError::Cancelled) => {
+                                info!(target: ACTIVITY, "Cancelled: {backup_merge_restore:?}");
+                                self.rx_backup_restore_merge_update = None;
+                                self.status = Status::Cancelled(backup_merge_restore.to_owned());
+                            }
                             Err(error) => {
+                                warn!(target: ACTIVITY, "Failed: {backup_merge_restore:?}: {error}");
                                 self.rx_backup_restore_merge_update = None;
                                 self.status = Status::Failure(error);
                             }
                         }
+                        self.rx_progress = None;
+                        self.progress = None;
+                        self.monitor = None;
                     }
                     Err(TryRecvError::Empty) => (),
                     Err(TryRecvError::Disconnected) => (),
@@ -140,6 +196,45 @@ impl BackupMergeRestoreGui {
         GuiStatus::display(ui, &self.status)
     }
 
+    /// Draw the progress of the merge/restore in progress (if any) with a
+    /// button to cancel it
+    fn draw_progress(&mut self, ui: &mut Ui) {
+        let (Some(progress), Some(monitor)) = (self.progress, self.monitor.as_ref()) else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            let cancelled = monitor.is_cancelled();
+            if ui
+                .add_enabled(!cancelled, egui::Button::new("Cancel"))
+                .clicked()
+            {
+                monitor.cancel();
+            }
+            let text = match cancelled {
+                true => String::from("Cancelling..."),
+                false => format!(
+                    "{}: {} / {}",
+                    phase_label(progress.phase),
+                    progress.processed,
+                    progress.total
+                ),
+            };
+            ui.add(ProgressBar::new(progress.fraction()).text(text));
+        });
+        ui.separator();
+    }
+
+    /// Set up progress reporting for a merge/restore, returning the monitor to
+    /// pass to it
+    fn start_progress(&mut self) -> RestoreMonitor {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let monitor = RestoreMonitor::new(tx);
+        self.rx_progress = Some(rx);
+        self.progress = None;
+        self.monitor = Some(monitor.clone());
+        monitor
+    }
+
     /// A helper to run the requested file operation.  This helps by providing a
     /// transaction to the target function, and commits it if the operation is
     /// successful.
@@ -152,14 +247,19 @@ impl BackupMergeRestoreGui {
         self.status = Status::InProgress;
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.rx_backup_restore_merge_update = Some(rx);
+        let monitor = self.start_progress();
         let shared_config = Arc::clone(&self.shared_config);
-        tokio::spawn(async move {
+        spawn_operation!(async move {
             let outer_result = async {
                 let mut transaction = shared_config.read().await.db_pool.begin().await?;
                 match backup_merge_restore {
                     BackupMergeRestore::Backup => backup(&mut transaction, target_dir).await?,
-                    BackupMergeRestore::Merge => merge(&mut transaction, target_dir).await?,
-                    BackupMergeRestore::Restore => restore(&mut transaction, target_dir).await?,
+                    BackupMergeRestore::Merge => {
+                        merge_with_progress(&mut transaction, target_dir, &monitor).await?
+                    }
+                    BackupMergeRestore::Restore => {
+                        restore_with_progress(&mut transaction, target_dir, &monitor).await?
+                    }
                 }
                 transaction
                     .commit()
@@ -180,12 +280,13 @@ impl BackupMergeRestoreGui {
         self.status = Status::InProgress;
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.rx_backup_restore_merge_update = Some(rx);
+        let monitor = self.start_progress();
         let shared_config = Arc::clone(&self.shared_config);
         let entities_url = self.open_timeline_api.entities.url.clone();
         let timelines_url = self.open_timeline_api.timelines.url.clone();
         debug!("entities_url = {entities_url}");
         debug!("timelines_url = {timelines_url}");
-        tokio::spawn(async move {
+        spawn_operation!(async move {
             let outer_result: Result<(), BackupRestoreMergeError> = async {
                 // Fetch
                 let (timelines, entities) = {
@@ -234,8 +335,12 @@ impl BackupMergeRestoreGui {
                 let mut transaction = shared_config.read().await.db_pool.begin().await?;
                 match backup_merge_restore {
                     BackupMergeRestore::Backup => (),
-                    BackupMergeRestore::Merge => merge(&mut transaction, dir).await?,
-                    BackupMergeRestore::Restore => restore(&mut transaction, dir).await?,
+                    BackupMergeRestore::Merge => {
+                        merge_with_progress(&mut transaction, dir, &monitor).await?
+                    }
+                    BackupMergeRestore::Restore => {
+                        restore_with_progress(&mut transaction, dir, &monitor).await?
+                    }
                 }
                 transaction
                     .commit()
@@ -337,6 +442,7 @@ impl Draw for BackupMergeRestoreGui {
         // Status
         self.draw_status(ui);
         ui.separator();
+        self.draw_progress(ui);
 
         // Description
         let description =
@@ -367,6 +473,17 @@ impl CheckForUpdates for BackupMergeRestoreGui {
     }
 }
 
+/// The text describing a merge/restore phase
+fn phase_label(phase: RestorePhase) -> &'static str {
+    match phase {
+        RestorePhase::Counting => "Counting",
+        RestorePhase::Clearing => "Clearing the database",
+        RestorePhase::Entities => "Entities",
+        RestorePhase::Timelines => "Timelines",
+        RestorePhase::Subtimelines => "Subtimelines",
+    }
+}
+
 /// Draw an API endpoint config
 fn draw_api_endpoint_config(ui: &mut Ui, label: &str, api_endpoint: &mut ApiEndpointConfig) {
     open_timeline_gui_core::Label::strong(ui, label);
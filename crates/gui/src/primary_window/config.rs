@@ -4,17 +4,29 @@
 //! Desktop GUI settings
 //!
 
+use crate::activity_log::ACTIVITY;
 use crate::app::{ActionRequest, UnboundedChannel};
 use crate::app_colours::{AppColours, ColourTheme};
-use crate::config::{Config, SharedConfig};
-use eframe::egui::{self, Context, Grid, Response, RichText, Spinner, Ui};
-use log::info;
-use open_timeline_crud::{CrudError, db_url_from_path};
+use crate::config::{Config, DEFAULT_LOG_LEVELS, SharedConfig};
+use crate::{spawn_operation, spawn_transaction_no_commit_send_result};
+use eframe::egui::{self, Context, FontFamily, Grid, Response, RichText, Spinner, TextEdit, Ui};
+use open_timeline_core::{Date, DateOrder, EraStyle, MonthStyle};
+use open_timeline_crud::{
+    CrudError, DatabaseSettings, db_url_from_path, fetch_database_settings,
+    update_database_settings,
+};
 use open_timeline_gui_core::{CheckForUpdates, Draw};
 use open_timeline_gui_core::{DisplayStatus, GuiStatus};
+use open_timeline_i18n::{Language, tr};
+use open_timeline_renderer::FontConfig;
+use open_timeline_www_api::EmbeddedApiServer;
 use sqlx::SqlitePool;
+use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::sync::mpsc::error::TryRecvError;
 use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender};
+use tracing::info;
+use tracing_subscriber::filter::Targets;
 
 /// The settings GUI panel in the main window
 #[derive(Debug)]
@@ -49,8 +61,45 @@ pub struct SettingsGui {
     /// Receive updates about theme selection saving
     rx_theme_update: Option<Receiver<Result<(), CrudError>>>,
 
+    /// Receive updates about language selection saving
+    rx_language_update: Option<Receiver<Result<(), CrudError>>>,
+
+    /// Receive updates about date format selection saving
+    rx_date_format_update: Option<Receiver<Result<(), CrudError>>>,
+
+    /// Receive updates about timeline font selection saving
+    rx_timeline_font_update: Option<Receiver<Result<(), CrudError>>>,
+
+    /// Receive updates about window settings saving
+    rx_windows_update: Option<Receiver<Result<(), CrudError>>>,
+
+    /// Receive updates about log levels saving
+    rx_log_levels_update: Option<Receiver<Result<(), CrudError>>>,
+
     /// Receive updates about theme selection saving
     rx_switch_database_update: Option<Receiver<Result<(), CrudError>>>,
+
+    /// The settings stored in the database in use (if fetched)
+    database_settings: Option<DatabaseSettings>,
+
+    /// Receive the settings stored in the database in use
+    rx_database_settings: Option<Receiver<Result<DatabaseSettings, CrudError>>>,
+
+    /// Receive updates about the settings stored in the database saving
+    rx_database_settings_update: Option<Receiver<Result<(), CrudError>>>,
+
+    /// Receive updates about local API server settings saving
+    rx_local_api_config_update: Option<Receiver<Result<(), CrudError>>>,
+
+    /// The local API server (if running)
+    local_api_server: Option<EmbeddedApiServer>,
+
+    /// The local API server's status
+    local_api_status: LocalApiStatus,
+
+    /// Receive the local API server once it has been (re)started (or `None` if
+    /// it has been stopped)
+    rx_local_api_update: Option<Receiver<Result<Option<EmbeddedApiServer>, String>>>,
 }
 
 /// The possible states of operation for the window
@@ -59,30 +108,79 @@ enum Status {
     Ready,
     WaitingForResponse,
     SuccessfullyChangedDatabase,
+    SuccessfullyChangedDatabaseSettings,
     DatabaseHasDifferentSchema,
     SuccessfullyChangedTheme,
+    SuccessfullyChangedLanguage,
+    SuccessfullyChangedDateFormat,
+    SuccessfullyChangedTimelineFont,
+    SuccessfullyChangedWindows,
+    SuccessfullyChangedLogLevels,
+    SuccessfullyChangedLocalApi,
     CrudError(CrudError),
 }
 
+/// The possible states of the local API server
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LocalApiStatus {
+    Stopped,
+    Changing,
+    Running(SocketAddr),
+    Failed(String),
+}
+
+impl DisplayStatus for LocalApiStatus {
+    fn status_display(&self, ui: &mut Ui) -> Response {
+        match &self {
+            Self::Stopped => ui.add(egui::Label::new(tr!("settings-local-api-stopped")).truncate()),
+            Self::Changing => ui.add(Spinner::new()),
+            Self::Running(address) => {
+                let url = format!("http://{address}/api/v1");
+                ui.add(egui::Label::new(tr!("settings-local-api-running", url = url)).truncate())
+            }
+            Self::Failed(error) => {
+                ui.add(egui::Label::new(tr!("status-error", error = error)).truncate())
+            }
+        }
+    }
+}
+
 impl DisplayStatus for Status {
     fn status_display(&self, ui: &mut Ui) -> Response {
         match &self {
-            Self::Ready => ui.add(egui::Label::new(String::from("Ready")).truncate()),
+            Self::Ready => ui.add(egui::Label::new(tr!("status-ready")).truncate()),
             Self::WaitingForResponse => ui.add(Spinner::new()),
             Self::SuccessfullyChangedDatabase => {
-                ui.add(egui::Label::new(String::from("Successfully switched database")).truncate())
+                ui.add(egui::Label::new(tr!("settings-status-changed-database")).truncate())
+            }
+            Self::SuccessfullyChangedDatabaseSettings => ui
+                .add(egui::Label::new(tr!("settings-status-changed-database-settings")).truncate()),
+            Self::DatabaseHasDifferentSchema => {
+                ui.add(egui::Label::new(tr!("settings-status-incompatible-schema")).truncate())
             }
-            Self::DatabaseHasDifferentSchema => ui.add(
-                egui::Label::new(String::from(
-                    "Error: selected database has incompatible schema",
-                ))
-                .truncate(),
-            ),
             Self::SuccessfullyChangedTheme => {
-                ui.add(egui::Label::new(String::from("Successfully switched theme")).truncate())
+                ui.add(egui::Label::new(tr!("settings-status-changed-theme")).truncate())
+            }
+            Self::SuccessfullyChangedLanguage => {
+                ui.add(egui::Label::new(tr!("settings-status-changed-language")).truncate())
+            }
+            Self::SuccessfullyChangedDateFormat => {
+                ui.add(egui::Label::new(tr!("settings-status-changed-date-format")).truncate())
+            }
+            Self::SuccessfullyChangedTimelineFont => {
+                ui.add(egui::Label::new(tr!("settings-status-changed-timeline-font")).truncate())
+            }
+            Self::SuccessfullyChangedWindows => {
+                ui.add(egui::Label::new(tr!("settings-status-changed-windows")).truncate())
+            }
+            Self::SuccessfullyChangedLogLevels => {
+                ui.add(egui::Label::new(tr!("settings-status-changed-log-levels")).truncate())
+            }
+            Self::SuccessfullyChangedLocalApi => {
+                ui.add(egui::Label::new(tr!("settings-status-changed-local-api")).truncate())
             }
             Self::CrudError(error) => {
-                ui.add(egui::Label::new(format!("Error: {error}")).truncate())
+                ui.add(egui::Label::new(tr!("status-error", error = error)).truncate())
             }
         }
     }
@@ -97,7 +195,7 @@ impl SettingsGui {
         tx_crud_operation_executed: UnboundedSender<()>,
     ) -> Self {
         debug!("New SettingsGui. config = {config:?}");
-        Self {
+        let mut new = Self {
             config,
             status: Status::Ready,
             shared_config,
@@ -107,8 +205,27 @@ impl SettingsGui {
             channel_app_colours: tokio::sync::mpsc::unbounded_channel().into(),
             rx_database_config_update: None,
             rx_theme_update: None,
+            rx_language_update: None,
+            rx_date_format_update: None,
+            rx_timeline_font_update: None,
+            rx_windows_update: None,
+            rx_log_levels_update: None,
             rx_switch_database_update: None,
+            database_settings: None,
+            rx_database_settings: None,
+            rx_database_settings_update: None,
+            rx_local_api_config_update: None,
+            local_api_server: None,
+            local_api_status: LocalApiStatus::Stopped,
+            rx_local_api_update: None,
+        };
+
+        // The local API server is started with the app (if enabled)
+        if new.config.local_api.enabled {
+            new.request_local_api_restart();
         }
+        new.request_database_settings();
+        new
     }
 
     /// Get the app theme
@@ -120,7 +237,7 @@ impl SettingsGui {
     /// settings
     fn draw_database_settings(&mut self, _ctx: &Context, ui: &mut Ui) {
         // Sub heading
-        open_timeline_gui_core::Label::sub_heading(ui, "Database File");
+        open_timeline_gui_core::Label::sub_heading(ui, &tr!("settings-database-file"));
 
         // Path of database file in use
         let database_path = self.config.database_path().to_string_lossy().to_string();
@@ -141,24 +258,61 @@ impl SettingsGui {
                 self.select_new_database(ui);
                 self.use_default_database(ui);
             });
+        ui.add_space(5.0);
+
+        // Settings stored in the database (so only usable once fetched)
+        let enabled =
+            self.database_settings.is_some() && self.rx_database_settings_update.is_none();
+        let mut automatic_period_tags = self
+            .database_settings
+            .is_some_and(|settings| settings.automatic_period_tags);
+        let changed = ui
+            .add_enabled(
+                enabled,
+                egui::Checkbox::new(
+                    &mut automatic_period_tags,
+                    tr!("settings-database-automatic-period-tags"),
+                ),
+            )
+            .on_hover_text(tr!("settings-database-automatic-period-tags-description"))
+            .changed();
         ui.add_space(10.0);
+
+        // Save the new setting to the database
+        if changed && let Some(settings) = self.database_settings.as_mut() {
+            settings.automatic_period_tags = automatic_period_tags;
+            let settings = *settings;
+            self.request_database_settings_update(settings);
+        }
     }
 
     /// Draw everything related to controlling the application's colours
     fn draw_app_colour_settings(&mut self, _ctx: &Context, ui: &mut Ui) {
-        open_timeline_gui_core::Label::sub_heading(ui, "Colour Theme");
+        open_timeline_gui_core::Label::sub_heading(ui, &tr!("settings-colour-theme"));
         let mut theme_changed = false;
 
         // Simple (system/light/dark)
         ui.horizontal(|ui| {
             theme_changed |= ui
-                .radio_value(&mut self.config.colour_theme, ColourTheme::System, "System")
+                .radio_value(
+                    &mut self.config.colour_theme,
+                    ColourTheme::System,
+                    tr!("settings-colour-theme-system"),
+                )
                 .changed();
             theme_changed |= ui
-                .radio_value(&mut self.config.colour_theme, ColourTheme::Light, "Light")
+                .radio_value(
+                    &mut self.config.colour_theme,
+                    ColourTheme::Light,
+                    tr!("settings-colour-theme-light"),
+                )
                 .changed();
             theme_changed |= ui
-                .radio_value(&mut self.config.colour_theme, ColourTheme::Dark, "Dark")
+                .radio_value(
+                    &mut self.config.colour_theme,
+                    ColourTheme::Dark,
+                    tr!("settings-colour-theme-dark"),
+                )
                 .changed();
         });
 
@@ -168,7 +322,14 @@ impl SettingsGui {
                 .radio_value(
                     &mut self.config.colour_theme,
                     ColourTheme::Siphonophore,
-                    "Siphonophore",
+                    tr!("settings-colour-theme-siphonophore"),
+                )
+                .changed();
+            theme_changed |= ui
+                .radio_value(
+                    &mut self.config.colour_theme,
+                    ColourTheme::HighContrast,
+                    tr!("settings-colour-theme-high-contrast"),
                 )
                 .changed();
         });
@@ -186,7 +347,7 @@ impl SettingsGui {
                 .radio_value(
                     &mut self.config.colour_theme,
                     ColourTheme::Custom(app_colours),
-                    "Custom",
+                    tr!("settings-colour-theme-custom"),
                 )
                 .changed();
         });
@@ -194,7 +355,12 @@ impl SettingsGui {
         // Show save button
         let mut user_requested_save_custom_colours = false;
         if self.show_save_colours_button {
-            if open_timeline_gui_core::Button::tall_full_width(ui, "Save Custom Theme").clicked() {
+            if open_timeline_gui_core::Button::tall_full_width(
+                ui,
+                tr!("settings-colour-theme-save-custom"),
+            )
+            .clicked()
+            {
                 user_requested_save_custom_colours = true;
             }
         }
@@ -211,7 +377,7 @@ impl SettingsGui {
             }
 
             // Update shared state
-            self.switch_shared_colour_theme();
+            self.update_shared_config();
 
             // Request save config to disk
             self.request_save(tx);
@@ -219,7 +385,11 @@ impl SettingsGui {
 
         // Draw request custom editor window
         if let ColourTheme::Custom(_) = self.config.colour_theme {
-            if open_timeline_gui_core::Button::tall_full_width(ui, "Edit Custom Colours").clicked()
+            if open_timeline_gui_core::Button::tall_full_width(
+                ui,
+                tr!("settings-colour-theme-edit-custom"),
+            )
+            .clicked()
             {
                 debug!("Requesting new window for custom app colour selection");
                 let _ = self.tx_action_request.send(ActionRequest::AppColours(
@@ -229,8 +399,255 @@ impl SettingsGui {
         };
     }
 
+    /// Draw everything related to controlling the application's language
+    fn draw_language_settings(&mut self, _ctx: &Context, ui: &mut Ui) {
+        open_timeline_gui_core::Label::sub_heading(ui, &tr!("settings-language"));
+        let mut language_changed = false;
+        ui.horizontal_wrapped(|ui| {
+            for language in Language::ALL {
+                language_changed |= ui
+                    .radio_value(&mut self.config.language, language, language.name())
+                    .changed();
+            }
+        });
+        ui.add_space(10.0);
+
+        // Switch language & save the config to disk
+        if language_changed {
+            open_timeline_i18n::set_language(self.config.language());
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            self.rx_language_update = Some(rx);
+            self.update_shared_config();
+            self.request_save(tx);
+        }
+    }
+
+    /// Draw everything related to controlling how dates are displayed
+    fn draw_date_format_settings(&mut self, _ctx: &Context, ui: &mut Ui) {
+        open_timeline_gui_core::Label::sub_heading(ui, &tr!("settings-date-format"));
+        let mut date_format_changed = false;
+        let date_format = &mut self.config.date_format;
+        Grid::new("date_format_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                // Order
+                ui.label(tr!("settings-date-format-order"));
+                ui.horizontal_wrapped(|ui| {
+                    for (order, text) in [
+                        (DateOrder::Dmy, tr!("settings-date-format-order-dmy")),
+                        (DateOrder::Mdy, tr!("settings-date-format-order-mdy")),
+                        (DateOrder::Iso, tr!("settings-date-format-order-iso")),
+                    ] {
+                        date_format_changed |= ui
+                            .radio_value(&mut date_format.order, order, text)
+                            .changed();
+                    }
+                });
+                ui.end_row();
+
+                // Month style (ISO dates always use numbers)
+                ui.label(tr!("settings-date-format-month"));
+                ui.add_enabled_ui(date_format.order != DateOrder::Iso, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for (month_style, text) in [
+                            (MonthStyle::Name, tr!("settings-date-format-month-name")),
+                            (MonthStyle::Number, tr!("settings-date-format-month-number")),
+                        ] {
+                            date_format_changed |= ui
+                                .radio_value(&mut date_format.month_style, month_style, text)
+                                .changed();
+                        }
+                    });
+                });
+                ui.end_row();
+
+                // Era style
+                ui.label(tr!("settings-date-format-era"));
+                ui.horizontal_wrapped(|ui| {
+                    for (era_style, text) in [
+                        (EraStyle::Negative, tr!("settings-date-format-era-negative")),
+                        (EraStyle::Bce, tr!("settings-date-format-era-bce")),
+                        (EraStyle::Bc, tr!("settings-date-format-era-bc")),
+                    ] {
+                        date_format_changed |= ui
+                            .radio_value(&mut date_format.era_style, era_style, text)
+                            .changed();
+                    }
+                });
+                ui.end_row();
+
+                // Timelines
+                ui.label(tr!("settings-date-format-timelines"));
+                ui.horizontal_wrapped(|ui| {
+                    date_format_changed |= ui
+                        .checkbox(
+                            &mut self.config.show_now_line,
+                            tr!("settings-date-format-now-line"),
+                        )
+                        .changed();
+                    date_format_changed |= ui
+                        .checkbox(
+                            &mut self.config.relative_time,
+                            tr!("settings-date-format-relative-time"),
+                        )
+                        .changed();
+                });
+                ui.end_row();
+            });
+
+        // Example
+        let example = Date::from(Some(12), Some(3), 1945).unwrap();
+        let example = self.config.date_format.format_date(&example);
+        ui.label(RichText::new(tr!("settings-date-format-example", example = example)).weak());
+        ui.add_space(10.0);
+
+        // Use the new format & save the config to disk
+        if date_format_changed {
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            self.rx_date_format_update = Some(rx);
+            self.update_shared_config();
+            self.request_save(tx);
+        }
+    }
+
+    /// Draw everything related to how break out windows are opened
+    fn draw_window_settings(&mut self, _ctx: &Context, ui: &mut Ui) {
+        open_timeline_gui_core::Label::sub_heading(ui, &tr!("settings-windows"));
+        let windows_changed = ui
+            .checkbox(
+                &mut self.config.open_editors_as_tabs,
+                tr!("settings-windows-editors-as-tabs"),
+            )
+            .changed();
+        ui.add_space(10.0);
+
+        // Use the new setting & save the config to disk
+        if windows_changed {
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            self.rx_windows_update = Some(rx);
+            self.update_shared_config();
+            self.request_save(tx);
+        }
+    }
+
+    /// Draw the log levels (which are used the next time the app is started)
+    fn draw_logging_settings(&mut self, _ctx: &Context, ui: &mut Ui) {
+        open_timeline_gui_core::Label::sub_heading(ui, &tr!("settings-logging"));
+        open_timeline_gui_core::Label::description(ui, &tr!("settings-logging-description"));
+        ui.add_space(5.0);
+        let response =
+            ui.add(TextEdit::singleline(&mut self.config.log_levels).hint_text(DEFAULT_LOG_LEVELS));
+        let valid = self.config.log_levels.parse::<Targets>().is_ok();
+        if !valid {
+            ui.colored_label(ui.visuals().error_fg_color, tr!("settings-logging-invalid"));
+        }
+        ui.add_space(10.0);
+
+        // Save the config to disk (once the levels have been entered)
+        if response.lost_focus() && valid {
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            self.rx_log_levels_update = Some(rx);
+            self.update_shared_config();
+            self.request_save(tx);
+        }
+    }
+
+    /// Draw everything related to the API server run inside the app
+    fn draw_local_api_settings(&mut self, _ctx: &Context, ui: &mut Ui) {
+        open_timeline_gui_core::Label::sub_heading(ui, &tr!("settings-local-api"));
+        open_timeline_gui_core::Label::description(ui, &tr!("settings-local-api-description"));
+        ui.add_space(5.0);
+
+        let changing = self.rx_local_api_update.is_some();
+        let mut enabled_changed = false;
+        Grid::new("local_api_settings")
+            .num_columns(2)
+            .show(ui, |ui| {
+                open_timeline_gui_core::Label::strong(ui, &tr!("settings-local-api-port"));
+                let port = egui::DragValue::new(&mut self.config.local_api.port).range(1..=65535);
+                ui.add_enabled(!changing && !self.config.local_api.enabled, port);
+                ui.end_row();
+
+                open_timeline_gui_core::Label::strong(ui, &tr!("settings-local-api-server"));
+                ui.add_enabled_ui(!changing, |ui| {
+                    enabled_changed = ui
+                        .checkbox(
+                            &mut self.config.local_api.enabled,
+                            tr!("settings-local-api-enabled"),
+                        )
+                        .changed();
+                });
+                ui.end_row();
+            });
+        GuiStatus::display(ui, &self.local_api_status);
+        ui.add_space(10.0);
+
+        // Start/stop the server & save the config to disk
+        if enabled_changed {
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            self.rx_local_api_config_update = Some(rx);
+            self.update_shared_config();
+            self.request_save(tx);
+            self.request_local_api_restart();
+        }
+    }
+
+    /// Draw the choice of font used for timelines' text (any fonts registered
+    /// with `egui` can be chosen, as well as its proportional & monospace
+    /// fonts)
+    fn draw_timeline_font_settings(&mut self, ctx: &Context, ui: &mut Ui) {
+        open_timeline_gui_core::Label::sub_heading(ui, &tr!("settings-timeline-font"));
+        let mut options = vec![
+            (
+                FontConfig::default(),
+                tr!("settings-timeline-font-proportional"),
+            ),
+            (
+                FontConfig::from_css_family_list("monospace", self.config.timeline_font.weight),
+                tr!("settings-timeline-font-monospace"),
+            ),
+        ];
+        let named_families: Vec<String> = ctx.fonts(|f| {
+            f.definitions()
+                .families
+                .keys()
+                .filter_map(|family| match family {
+                    FontFamily::Name(name) => Some(name.to_string()),
+                    _ => None,
+                })
+                .collect()
+        });
+        for name in named_families {
+            let font = FontConfig::from_css_family_list(&name, self.config.timeline_font.weight);
+            options.push((font, name));
+        }
+
+        let mut timeline_font_changed = false;
+        ui.horizontal_wrapped(|ui| {
+            for (font, text) in options {
+                timeline_font_changed |= ui
+                    .radio_value(&mut self.config.timeline_font, font, text)
+                    .changed();
+            }
+        });
+        ui.add_space(10.0);
+
+        // Use the new font & save the config to disk
+        if timeline_font_changed {
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            self.rx_timeline_font_update = Some(rx);
+            self.update_shared_config();
+            self.request_save(tx);
+        }
+    }
+
     fn select_existing_database(&mut self, ui: &mut Ui) {
-        if open_timeline_gui_core::Button::tall_full_width(ui, "Use Existing").clicked() {
+        if open_timeline_gui_core::Button::tall_full_width(
+            ui,
+            tr!("settings-database-use-existing"),
+        )
+        .clicked()
+        {
             if let Some(db_path) = rfd::FileDialog::new().pick_file() {
                 info!("Selected file: {}", db_path.display());
                 self.config.set_database_path(&db_path);
@@ -242,7 +659,9 @@ impl SettingsGui {
     }
 
     fn select_new_database(&mut self, ui: &mut Ui) {
-        if open_timeline_gui_core::Button::tall_full_width(ui, "Create & Use New").clicked() {
+        if open_timeline_gui_core::Button::tall_full_width(ui, tr!("settings-database-create-new"))
+            .clicked()
+        {
             if let Some(db_path) = rfd::FileDialog::new().save_file() {
                 self.config.set_database_path(&db_path);
                 let (tx, rx) = tokio::sync::mpsc::channel(1);
@@ -253,7 +672,9 @@ impl SettingsGui {
     }
 
     fn use_default_database(&mut self, ui: &mut Ui) {
-        if open_timeline_gui_core::Button::tall_full_width(ui, "Use Default").clicked() {
+        if open_timeline_gui_core::Button::tall_full_width(ui, tr!("settings-database-use-default"))
+            .clicked()
+        {
             self.config.set_to_default();
             let (tx, rx) = tokio::sync::mpsc::channel(1);
             self.rx_database_config_update = Some(rx);
@@ -264,8 +685,11 @@ impl SettingsGui {
     /// Attempt to save the config to disk
     fn request_save(&mut self, tx: Sender<Result<(), CrudError>>) {
         self.status = Status::WaitingForResponse;
-        let config = self.config.clone();
-        tokio::spawn(async move {
+        let mut config = self.config.clone();
+        let shared_config = self.shared_config.clone();
+        spawn_operation!(async move {
+            // The game settings are changed (& saved) by the games
+            config.games = shared_config.read().await.config.games.clone();
             let result = config.save().await;
             let _ = tx.send(result).await;
         });
@@ -277,11 +701,12 @@ impl SettingsGui {
         let db_path = self.config.database_path();
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.rx_switch_database_update = Some(rx);
-        tokio::spawn(async move {
+        spawn_operation!(async move {
             let result = async move {
                 let mut shared_config = shared_config.write().await;
                 let db_url = db_url_from_path(&db_path);
                 (*shared_config).db_pool = SqlitePool::connect(&db_url).await?;
+                shared_config.cached_stats.invalidate();
                 Ok(())
             }
             .await;
@@ -289,13 +714,74 @@ impl SettingsGui {
         });
     }
 
-    /// Switch the application's colour theme
-    fn switch_shared_colour_theme(&mut self) {
+    /// Fetch the settings stored in the database in use
+    fn request_database_settings(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_database_settings = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_database_settings(transaction).await }
+        );
+    }
+
+    /// Save the settings stored in the database in use (which may change
+    /// entities' tags)
+    fn request_database_settings_update(&mut self, settings: DatabaseSettings) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_database_settings_update = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                update_database_settings(&mut transaction, &settings).await?;
+                transaction.commit().await?;
+                Ok(())
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Stop the local API server (if running), then start it again (if enabled)
+    /// with the current database pool & port
+    fn request_local_api_restart(&mut self) {
+        self.local_api_status = LocalApiStatus::Changing;
+        let server = self.local_api_server.take();
+        let local_api = self.config.local_api.clone();
+        let shared_config = self.shared_config.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_local_api_update = Some(rx);
+        spawn_operation!(async move {
+            let result = async move {
+                if let Some(server) = server {
+                    server.stop().await?;
+                }
+                if !local_api.enabled {
+                    return Ok(None);
+                }
+                let pool = shared_config.read().await.db_pool.clone();
+                EmbeddedApiServer::start(pool, local_api.port)
+                    .await
+                    .map(Some)
+            }
+            .await;
+            let _ = tx.send(result.map_err(|error| error.to_string())).await;
+        });
+    }
+
+    /// Update the application's shared config (e.g. colour theme & language)
+    fn update_shared_config(&mut self) {
         let shared_config = self.shared_config.clone();
         let config = self.config.clone();
-        tokio::spawn(async move {
+        spawn_operation!(async move {
             let mut shared_config = shared_config.write().await;
+            // The game settings are changed (& saved) by the games
+            let games = shared_config.config.games.clone();
             (*shared_config).config = config;
+            (*shared_config).config.games = games;
             debug!("Updated shared config = {shared_config:?}");
         });
     }
@@ -347,6 +833,207 @@ impl SettingsGui {
         }
     }
 
+    /// Check for result of saving new date format choice to disk
+    fn check_for_date_format_selection_update(&mut self) {
+        if let Some(rx) = self.rx_date_format_update.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv date format selection update");
+                    self.rx_date_format_update = None;
+                    match result {
+                        Ok(()) => self.status = Status::SuccessfullyChangedDateFormat,
+                        Err(error) => {
+                            self.status = Status::CrudError(error.clone());
+                            warn!("Error: {error}");
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Check for result of saving new window settings to disk
+    fn check_for_windows_update(&mut self) {
+        if let Some(rx) = self.rx_windows_update.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv window settings update");
+                    self.rx_windows_update = None;
+                    match result {
+                        Ok(()) => self.status = Status::SuccessfullyChangedWindows,
+                        Err(error) => {
+                            self.status = Status::CrudError(error.clone());
+                            warn!("Error: {error}");
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Check for the settings stored in the database in use
+    fn check_for_database_settings(&mut self) {
+        if let Some(rx) = self.rx_database_settings.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv database settings");
+                    self.rx_database_settings = None;
+                    match result {
+                        Ok(settings) => self.database_settings = Some(settings),
+                        Err(error) => {
+                            self.status = Status::CrudError(error.clone());
+                            warn!("Error: {error}");
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Check for result of saving the settings stored in the database
+    fn check_for_database_settings_update(&mut self) {
+        if let Some(rx) = self.rx_database_settings_update.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv database settings update");
+                    self.rx_database_settings_update = None;
+                    match result {
+                        Ok(()) => {
+                            self.status = Status::SuccessfullyChangedDatabaseSettings;
+                            info!(target: ACTIVITY, "Changed the database's settings");
+                            info!("Requesting search refresh");
+                            let _ = self.tx_crud_operation_executed.send(());
+                        }
+                        Err(error) => {
+                            self.status = Status::CrudError(error.clone());
+                            warn!("Error: {error}");
+
+                            // The change wasn't saved
+                            self.request_database_settings();
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Check for result of saving new log levels to disk
+    fn check_for_log_levels_update(&mut self) {
+        if let Some(rx) = self.rx_log_levels_update.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv log levels update");
+                    self.rx_log_levels_update = None;
+                    match result {
+                        Ok(()) => self.status = Status::SuccessfullyChangedLogLevels,
+                        Err(error) => {
+                            self.status = Status::CrudError(error.clone());
+                            warn!("Error: {error}");
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Check for result of saving new timeline font choice to disk
+    fn check_for_timeline_font_selection_update(&mut self) {
+        if let Some(rx) = self.rx_timeline_font_update.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv timeline font selection update");
+                    self.rx_timeline_font_update = None;
+                    match result {
+                        Ok(()) => self.status = Status::SuccessfullyChangedTimelineFont,
+                        Err(error) => {
+                            self.status = Status::CrudError(error.clone());
+                            warn!("Error: {error}");
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Check for result of saving new language choice to disk
+    fn check_for_language_selection_update(&mut self) {
+        if let Some(rx) = self.rx_language_update.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv language selection update");
+                    self.rx_language_update = None;
+                    match result {
+                        Ok(()) => self.status = Status::SuccessfullyChangedLanguage,
+                        Err(error) => {
+                            self.status = Status::CrudError(error.clone());
+                            warn!("Error: {error}");
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Check for result of saving new local API server settings to disk
+    fn check_for_local_api_config_update(&mut self) {
+        if let Some(rx) = self.rx_local_api_config_update.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv local API settings update");
+                    self.rx_local_api_config_update = None;
+                    match result {
+                        Ok(()) => self.status = Status::SuccessfullyChangedLocalApi,
+                        Err(error) => {
+                            self.status = Status::CrudError(error.clone());
+                            warn!("Error: {error}");
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Check for the local API server having been (re)started or stopped
+    fn check_for_local_api_update(&mut self) {
+        if let Some(rx) = self.rx_local_api_update.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv local API server update");
+                    self.rx_local_api_update = None;
+                    match result {
+                        Ok(Some(server)) => {
+                            self.local_api_status = LocalApiStatus::Running(server.address());
+                            self.local_api_server = Some(server);
+                        }
+                        Ok(None) => self.local_api_status = LocalApiStatus::Stopped,
+                        Err(error) => {
+                            warn!("Local API server error: {error}");
+                            self.local_api_status = LocalApiStatus::Failed(error);
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_local_api_update = None,
+            }
+        }
+    }
+
     // TODO: how does this interact with the config saved to file status messages?
     /// Check if the result (if any) of the database pool switch over
     fn check_for_database_pool_switch_update(&mut self) {
@@ -358,9 +1045,15 @@ impl SettingsGui {
                     match result {
                         Ok(()) => {
                             self.status = Status::SuccessfullyChangedDatabase;
-                            info!("Database pool switched");
+                            info!(target: ACTIVITY, "Switched to {:?}", self.config.database_path());
                             info!("Requesting search refresh");
                             let _ = self.tx_crud_operation_executed.send(());
+                            self.request_database_settings();
+
+                            // Serve the new database
+                            if self.local_api_server.is_some() {
+                                self.request_local_api_restart();
+                            }
                         }
                         Err(error) => {
                             self.status = Status::CrudError(error.clone());
@@ -384,7 +1077,7 @@ impl SettingsGui {
                 debug!("Received app colours");
                 self.config.colour_theme = ColourTheme::Custom(app_colours);
                 self.show_save_colours_button = true;
-                self.switch_shared_colour_theme();
+                self.update_shared_config();
             }
             Err(TryRecvError::Empty) => (),
             Err(TryRecvError::Disconnected) => (),
@@ -401,6 +1094,12 @@ impl Draw for SettingsGui {
 
         ui.add_enabled_ui(self.status != Status::WaitingForResponse, |ui| {
             self.draw_database_settings(ctx, ui);
+            self.draw_language_settings(ctx, ui);
+            self.draw_date_format_settings(ctx, ui);
+            self.draw_timeline_font_settings(ctx, ui);
+            self.draw_window_settings(ctx, ui);
+            self.draw_local_api_settings(ctx, ui);
+            self.draw_logging_settings(ctx, ui);
             self.draw_app_colour_settings(ctx, ui);
         });
     }
@@ -410,14 +1109,32 @@ impl CheckForUpdates for SettingsGui {
     fn check_for_updates(&mut self) {
         self.check_for_database_selection_update();
         self.check_for_theme_selection_update();
+        self.check_for_language_selection_update();
+        self.check_for_date_format_selection_update();
+        self.check_for_timeline_font_selection_update();
+        self.check_for_windows_update();
+        self.check_for_log_levels_update();
         self.check_for_database_pool_switch_update();
+        self.check_for_database_settings();
+        self.check_for_database_settings_update();
+        self.check_for_local_api_config_update();
+        self.check_for_local_api_update();
         self.check_for_app_colours_update();
     }
 
     fn waiting_for_updates(&mut self) -> bool {
         let waiting = self.rx_database_config_update.is_some()
             || self.rx_switch_database_update.is_some()
-            || self.rx_theme_update.is_some();
+            || self.rx_database_settings.is_some()
+            || self.rx_database_settings_update.is_some()
+            || self.rx_theme_update.is_some()
+            || self.rx_language_update.is_some()
+            || self.rx_date_format_update.is_some()
+            || self.rx_timeline_font_update.is_some()
+            || self.rx_windows_update.is_some()
+            || self.rx_log_levels_update.is_some()
+            || self.rx_local_api_config_update.is_some()
+            || self.rx_local_api_update.is_some();
         if waiting {
             info!("SettingsGui is waiting for updates");
         }
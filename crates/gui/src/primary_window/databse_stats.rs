@@ -4,11 +4,12 @@
 //! Desktop GUI database stats
 //!
 
+use crate::common::fetch_cached_stat_crud;
 use crate::config::SharedConfig;
-use crate::spawn_transaction_no_commit_send_result;
+use crate::spawn_operation;
 use eframe::egui::{Align, Context, Layout, ScrollArea, Ui};
 use egui_extras::{Column, TableBuilder};
-use open_timeline_crud::{CrudError, DatabaseRowCount};
+use open_timeline_crud::{Cached, CrudError, DatabaseRowCount, RowCountsStat};
 use open_timeline_gui_core::{CheckForUpdates, Draw, Reload, body_text_height, widget_x_spacing};
 use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
@@ -17,11 +18,12 @@ use tokio::sync::mpsc::error::TryRecvError;
 /// The stats GUI panel in the main window
 #[derive(Debug)]
 pub struct StatsGui {
-    /// Holds the row counts of each of the tables in the database.
-    table_row_counts: Option<DatabaseRowCount>,
+    /// Holds the row counts of each of the tables in the database (& when they
+    /// were counted).
+    table_row_counts: Option<Cached<DatabaseRowCount>>,
 
     /// Receive up-to-date row counts.
-    rx_reload: Option<Receiver<Result<DatabaseRowCount, CrudError>>>,
+    rx_reload: Option<Receiver<Result<Cached<DatabaseRowCount>, CrudError>>>,
 
     /// Whether or not a reload has been requested (automatically done in
     /// response to a successful CRUD operation being executed elsewhere in the
@@ -51,16 +53,10 @@ impl Reload for StatsGui {
         self.requested_reload = true;
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.rx_reload = Some(rx);
-        let shared_config = Arc::clone(&self.shared_config);
-        spawn_transaction_no_commit_send_result!(
-            shared_config,
-            bounded,
+        spawn_operation!(fetch_cached_stat_crud::<RowCountsStat>(
+            Arc::clone(&self.shared_config),
             tx,
-            |transaction| async move {
-                // TODO: renmae this and tag_counts.rs!
-                DatabaseRowCount::all(transaction).await
-            }
-        );
+        ));
     }
 
     fn check_reload_response(&mut self) {
@@ -87,7 +83,11 @@ impl Reload for StatsGui {
 impl Draw for StatsGui {
     fn draw(&mut self, _ctx: &Context, ui: &mut Ui) {
         // Display stats
-        if let Some(row_counts) = self.table_row_counts.as_ref() {
+        if let Some(cached) = self.table_row_counts.as_ref() {
+            let row_counts = cached.value();
+            ui.weak(format!("Counted {}s ago", cached.age().as_secs()));
+            ui.separator();
+
             // Sizes
             let row_height = body_text_height(ui);
             let spacing = widget_x_spacing(ui);
@@ -99,6 +99,9 @@ impl Draw for StatsGui {
             let counts = vec![
                 (row_counts.entities, "Entities"),
                 (row_counts.entity_tags, "Entity Tags"),
+                (row_counts.entity_aliases, "Entity Aliases"),
+                (row_counts.sources, "Sources"),
+                (row_counts.entity_sources, "Entity Sources"),
                 (row_counts.timelines, "Timelines"),
                 (row_counts.subtimelines, "Subtimelines"),
                 (row_counts.timeline_entities, "Timeline Entities"),
@@ -4,16 +4,19 @@
 //! Desktop GUI entity counts
 //!
 
+use crate::spawn_operation;
 use crate::{
     app::{ActionRequest, EntityOrTimelineActionRequest},
+    common::fetch_cached_stat_crud,
     components::OpenTimelineButton,
     config::SharedConfig,
     consts::{EDIT_BUTTON_WIDTH, VIEW_BUTTON_WIDTH},
-    spawn_transaction_no_commit_send_result,
 };
 use eframe::egui::{self, Align, Context, Layout, ScrollArea, TextEdit, Ui, Vec2};
 use egui_extras::{Column, TableBuilder};
-use open_timeline_crud::{CrudError, EntityCounts, SortAlphabetically, SortByNumber};
+use open_timeline_crud::{
+    Cached, CrudError, EntityCounts, EntityCountsStat, SortAlphabetically, SortByNumber,
+};
 use open_timeline_gui_core::{
     CheckForUpdates, Draw, Paginator, Reload, body_text_height, widget_x_spacing, widget_y_spacing,
 };
@@ -65,7 +68,7 @@ pub struct EntityCountsGui {
     end_ordering: Option<SortByNumber>,
 
     /// Receive up-to-date `EntityCounts` after a reload requested
-    rx_reload: Option<Receiver<Result<EntityCounts, CrudError>>>,
+    rx_reload: Option<Receiver<Result<Cached<EntityCounts>, CrudError>>>,
 
     /// Whether a reload has been requested
     requested_reload: bool,
@@ -268,6 +271,9 @@ impl EntityCountsGui {
         // offset..=upper_limit would overflow/be out of bounds
         let entity_counts = &entity_counts[offset..upper_limit];
 
+        // How dates are displayed
+        let date_format = self.shared_config.blocking_read().config.date_format;
+
         // Layouts
         let right_to_left = Layout::right_to_left(Align::Center);
         let left_to_right = Layout::left_to_right(Align::Center);
@@ -278,10 +284,10 @@ impl EntityCountsGui {
                 begin_table(ui, "entity_entity_counts_body", table_sizes).body(|mut body| {
                     for entity_count in entity_counts {
                         let name = entity_count.name().as_str();
-                        let start = entity_count.start().as_short_date_format();
+                        let start = entity_count.start().format(&date_format);
                         let end = entity_count
                             .end()
-                            .map(|end| end.as_short_date_format())
+                            .map(|end| end.format(&date_format))
                             .unwrap_or_default();
 
                         body.row(table_sizes.row_height, |mut row| {
@@ -370,13 +376,10 @@ impl Reload for EntityCountsGui {
         self.requested_reload = true;
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.rx_reload = Some(rx);
-        let shared_config = Arc::clone(&self.shared_config);
-        spawn_transaction_no_commit_send_result!(
-            shared_config,
-            bounded,
+        spawn_operation!(fetch_cached_stat_crud::<EntityCountsStat>(
+            Arc::clone(&self.shared_config),
             tx,
-            |transaction| async move { EntityCounts::fetch_all(transaction).await }
-        );
+        ));
     }
 
     fn check_reload_response(&mut self) {
@@ -386,7 +389,7 @@ impl Reload for EntityCountsGui {
                     debug!("Recv entity tag counts response");
                     match msg {
                         Ok(entity_counts) => {
-                            self.entity_counts = Some(entity_counts);
+                            self.entity_counts = Some(entity_counts.into_value());
                             self.paginator.set_page_index(0);
                             self.update_filtered_entity_counts();
                             self.rx_reload = None;
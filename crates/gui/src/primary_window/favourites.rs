@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Desktop GUI starred & recently opened entities and timelines
+//!
+
+use crate::activity_log::ACTIVITY;
+use crate::app::{ActionRequest, EntityOrTimelineActionRequest};
+use crate::common::set_starred_crud;
+use crate::components::OpenTimelineButton;
+use crate::config::SharedConfig;
+use crate::consts::{EDIT_BUTTON_WIDTH, VIEW_BUTTON_WIDTH};
+use crate::spawn_operation;
+use crate::spawn_transaction_no_commit_send_result;
+use eframe::egui::{self, Align, Context, Layout, RichText, ScrollArea, Ui};
+use egui_extras::{Column, TableBuilder};
+use open_timeline_crud::{
+    CrudError, Favourite, FavouriteKind, fetch_recently_opened, fetch_starred,
+};
+use open_timeline_gui_core::{
+    CheckForUpdates, Draw, Reload, Toast, body_text_height, widget_x_spacing,
+};
+use sqlx::{Sqlite, Transaction};
+use std::sync::Arc;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
+
+/// The maximum number of recently opened entities & timelines listed
+const RECENTLY_OPENED_LIMIT: u32 = 25;
+
+/// The width of the column stating whether it's an entity or a timeline
+const KIND_WIDTH: f32 = 70.0;
+
+/// The starred & recently opened entities and timelines
+type Favourites = (Vec<Favourite>, Vec<Favourite>);
+
+/// What the user clicked in one of the lists
+enum FavouriteClicked {
+    View(Favourite),
+    Edit(Favourite),
+    Star(Favourite),
+}
+
+/// The favourites GUI panel in the main window
+#[derive(Debug)]
+pub struct FavouritesGui {
+    /// The starred entities & timelines (if fetched)
+    starred: Option<Vec<Favourite>>,
+
+    /// The recently opened entities & timelines (if fetched)
+    recently_opened: Option<Vec<Favourite>>,
+
+    /// Receive up-to-date favourites after a reload is requested
+    rx_reload: Option<Receiver<Result<Favourites, CrudError>>>,
+
+    /// Used to request new entity/timeline edit & view windows
+    tx_action_request: UnboundedSender<ActionRequest>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl FavouritesGui {
+    /// Create a new favourites GUI panel manager
+    pub fn new(
+        shared_config: SharedConfig,
+        tx_action_request: UnboundedSender<ActionRequest>,
+    ) -> Self {
+        let mut favourites_gui = Self {
+            starred: None,
+            recently_opened: None,
+            rx_reload: None,
+            tx_action_request,
+            shared_config,
+        };
+        favourites_gui.request_reload();
+        favourites_gui
+    }
+
+    /// Handle a click in one of the lists
+    fn handle_click(&mut self, clicked: FavouriteClicked) {
+        let request = |favourite: &Favourite, action| match favourite.kind() {
+            FavouriteKind::Entity => ActionRequest::Entity(action),
+            FavouriteKind::Timeline => ActionRequest::Timeline(action),
+        };
+        match clicked {
+            FavouriteClicked::View(favourite) => {
+                let action = EntityOrTimelineActionRequest::ViewExisting(favourite.id());
+                let _ = self.tx_action_request.send(request(&favourite, action));
+            }
+            FavouriteClicked::Edit(favourite) => {
+                let action = EntityOrTimelineActionRequest::EditExisting(favourite.id());
+                let _ = self.tx_action_request.send(request(&favourite, action));
+            }
+            FavouriteClicked::Star(favourite) => {
+                // Reload once the change has been saved
+                let (tx, rx) = tokio::sync::mpsc::channel(1);
+                self.rx_reload = Some(rx);
+                let shared_config = Arc::clone(&self.shared_config);
+                let starred = !favourite.starred();
+                spawn_operation!(async move {
+                    let (kind, id) = (favourite.kind(), favourite.id());
+                    set_starred_crud(Arc::clone(&shared_config), kind, id, starred).await;
+                    let result = async {
+                        let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                        fetch_favourites(&mut transaction).await
+                    }
+                    .await;
+                    let _ = tx.send(result).await;
+                });
+            }
+        }
+    }
+}
+
+impl Reload for FavouritesGui {
+    fn request_reload(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_reload = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_favourites(transaction).await }
+        );
+    }
+
+    fn check_reload_response(&mut self) {
+        if let Some(rx) = self.rx_reload.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv favourites response");
+                    self.rx_reload = None;
+                    match result {
+                        Ok((starred, recently_opened)) => {
+                            self.starred = Some(starred);
+                            self.recently_opened = Some(recently_opened);
+                        }
+                        Err(error) => {
+                            let msg = format!("Failed to reload favourites: {error}");
+                            warn!(target: ACTIVITY, "{msg}");
+                            let _ = self
+                                .tx_action_request
+                                .send(ActionRequest::toast(Toast::error(msg)));
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_reload = None,
+            }
+        }
+    }
+}
+
+impl Draw for FavouritesGui {
+    fn draw(&mut self, _ctx: &Context, ui: &mut Ui) {
+        self.check_reload_response();
+
+        let mut clicked = None;
+        let half_height = ui.available_height() / 2.0;
+
+        // Starred
+        open_timeline_gui_core::Label::sub_heading(ui, "Starred");
+        if let Some(clicked_row) = draw_favourites(ui, "starred", &self.starred, half_height) {
+            clicked = Some(clicked_row);
+        }
+        ui.separator();
+
+        // Recently opened
+        open_timeline_gui_core::Label::sub_heading(ui, "Recently Opened");
+        let height = ui.available_height();
+        if let Some(clicked_row) =
+            draw_favourites(ui, "recently_opened", &self.recently_opened, height)
+        {
+            clicked = Some(clicked_row);
+        }
+
+        if let Some(clicked) = clicked {
+            self.handle_click(clicked);
+        }
+    }
+}
+
+impl CheckForUpdates for FavouritesGui {
+    fn check_for_updates(&mut self) {
+        self.check_reload_response();
+    }
+
+    fn waiting_for_updates(&mut self) -> bool {
+        let waiting = self.rx_reload.is_some();
+        if waiting {
+            info!("FavouritesGui is waiting for updates");
+        }
+        waiting
+    }
+}
+
+/// Fetch the starred & recently opened entities and timelines
+async fn fetch_favourites(
+    transaction: &mut Transaction<'_, Sqlite>,
+) -> Result<Favourites, CrudError> {
+    let starred = fetch_starred(transaction).await?;
+    let recently_opened = fetch_recently_opened(transaction, RECENTLY_OPENED_LIMIT).await?;
+    Ok((starred, recently_opened))
+}
+
+/// Draw a list of favourites (returning what was clicked, if anything)
+fn draw_favourites(
+    ui: &mut Ui,
+    id: &str,
+    favourites: &Option<Vec<Favourite>>,
+    max_height: f32,
+) -> Option<FavouriteClicked> {
+    let Some(favourites) = favourites
+        .as_ref()
+        .filter(|favourites| !favourites.is_empty())
+    else {
+        open_timeline_gui_core::Label::none(ui);
+        return None;
+    };
+
+    // Sizes
+    let row_height = body_text_height(ui);
+    let spacing = widget_x_spacing(ui);
+    let name_width = ui.available_width()
+        - KIND_WIDTH
+        - EDIT_BUTTON_WIDTH
+        - EDIT_BUTTON_WIDTH
+        - VIEW_BUTTON_WIDTH
+        - (4.0 * spacing);
+    let name_width = name_width.max(0.0);
+
+    let mut clicked = None;
+    ScrollArea::vertical()
+        .id_salt(id)
+        .max_height(max_height)
+        .show(ui, |ui| {
+            TableBuilder::new(ui)
+                .id_salt(id)
+                .striped(true)
+                .column(Column::exact(KIND_WIDTH))
+                .column(Column::exact(name_width).clip(true))
+                .column(Column::exact(EDIT_BUTTON_WIDTH))
+                .column(Column::exact(EDIT_BUTTON_WIDTH))
+                .column(Column::exact(VIEW_BUTTON_WIDTH))
+                .body(|mut body| {
+                    for favourite in favourites {
+                        body.row(row_height, |mut row| {
+                            // Entity or timeline
+                            row.col(|ui| {
+                                let kind = match favourite.kind() {
+                                    FavouriteKind::Entity => "Entity",
+                                    FavouriteKind::Timeline => "Timeline",
+                                };
+                                ui.label(RichText::new(kind).weak());
+                            });
+                            // Name
+                            row.col(|ui| {
+                                ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
+                                    ui.add(egui::Label::new(favourite.name().as_str()).truncate());
+                                });
+                            });
+                            // Star
+                            row.col(|ui| {
+                                if OpenTimelineButton::star(ui, favourite.starred()).clicked() {
+                                    clicked = Some(FavouriteClicked::Star(favourite.clone()));
+                                }
+                            });
+                            // Edit
+                            row.col(|ui| {
+                                if OpenTimelineButton::edit(ui).clicked() {
+                                    clicked = Some(FavouriteClicked::Edit(favourite.clone()));
+                                }
+                            });
+                            // View
+                            row.col(|ui| {
+                                if OpenTimelineButton::view(ui).clicked() {
+                                    clicked = Some(FavouriteClicked::View(favourite.clone()));
+                                }
+                            });
+                        });
+                    }
+                });
+        });
+    clicked
+}
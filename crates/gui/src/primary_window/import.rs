@@ -0,0 +1,398 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Importing entities & timelines from other formats (previewed before they
+//! are saved)
+//!
+
+use crate::activity_log::ACTIVITY;
+use crate::config::SharedConfig;
+use crate::spawn_operation;
+use eframe::egui::{self, ComboBox, Context, Grid, Response, ScrollArea, Spinner, Ui};
+use open_timeline_core::{HasIdAndName, IsReducedCollection};
+use open_timeline_crud::CrudError;
+use open_timeline_gui_core::{CheckForUpdates, Draw};
+use open_timeline_gui_core::{DisplayStatus, GuiStatus};
+use open_timeline_import::{Import, ImportError, ImportReport, Importers, save_import};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{Receiver, error::TryRecvError};
+
+/// The import GUI panel in the main window
+#[derive(Debug)]
+pub struct ImportGui {
+    /// The available importers
+    importers: Arc<Importers>,
+
+    /// The name of the importer chosen (if none, it is worked out from the
+    /// file's extension)
+    format: Option<&'static str>,
+
+    /// The file being imported
+    path: Option<PathBuf>,
+
+    /// The parsed file waiting to be saved
+    preview: Option<Import>,
+
+    /// Receive the parsed file
+    rx_parse: Option<Receiver<Result<Import, ImportError>>>,
+
+    /// Receive the report of the import being saved
+    rx_save: Option<Receiver<Result<ImportReport, CrudError>>>,
+
+    /// The status of the import (which may be none)
+    status: Status,
+
+    /// Used to indirectly inform the rest of the application that a CRUD
+    /// operation has been executed successfully (i.e. reloads may be required)
+    tx_crud_operation_executed: UnboundedSender<()>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+/// The possible states of operation for the panel
+#[derive(Debug)]
+enum Status {
+    /// Nothing has been imported while the programme has been running
+    None,
+
+    /// The file has been parsed & is waiting to be saved
+    Previewing,
+
+    /// The import was saved
+    Saved(ImportReport),
+
+    /// The file couldn't be parsed
+    ParseFailure(ImportError),
+
+    /// The import couldn't be saved
+    SaveFailure(CrudError),
+
+    /// The file is being parsed or the import saved
+    InProgress,
+}
+
+impl DisplayStatus for Status {
+    fn status_display(&self, ui: &mut Ui) -> Response {
+        match &self {
+            Self::None => ui.add(egui::Label::new(String::from("Ready")).truncate()),
+            Self::Previewing => ui.add(
+                egui::Label::new(String::from(
+                    "Review the preview below, then import (nothing has been saved yet)",
+                ))
+                .truncate(),
+            ),
+            Self::Saved(report) => ui.add(
+                egui::Label::new(format!(
+                    "Success: imported {} entities & {} timelines",
+                    report.created_entities, report.created_timelines
+                ))
+                .truncate(),
+            ),
+            Self::ParseFailure(error) => {
+                ui.add(egui::Label::new(format!("Error: {error}")).truncate())
+            }
+            Self::SaveFailure(error) => ui
+                .add(egui::Label::new(format!("Error (nothing was imported): {error}")).truncate()),
+            Self::InProgress => ui.add(Spinner::new()),
+        }
+    }
+}
+
+impl ImportGui {
+    /// Create a new import GUI panel manager
+    pub fn new(
+        shared_config: SharedConfig,
+        tx_crud_operation_executed: UnboundedSender<()>,
+    ) -> Self {
+        Self {
+            importers: Arc::new(Importers::new()),
+            format: None,
+            path: None,
+            preview: None,
+            rx_parse: None,
+            rx_save: None,
+            status: Status::None,
+            tx_crud_operation_executed,
+            shared_config,
+        }
+    }
+
+    /// Read & parse the chosen file
+    fn request_parse(&mut self, path: PathBuf) {
+        self.status = Status::InProgress;
+        self.path = Some(path.clone());
+        self.preview = None;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_parse = Some(rx);
+        let importers = Arc::clone(&self.importers);
+        let format = self.format;
+        tokio::task::spawn_blocking(move || {
+            let importer = match format {
+                Some(format) => importers.by_name(format),
+                None => importers.for_path(&path),
+            };
+            let result = importer.and_then(|importer| {
+                let bytes = std::fs::read(&path)?;
+                importer.parse(&bytes)
+            });
+            let _ = tx.blocking_send(result);
+        });
+    }
+
+    /// Check for the parsed file (if any)
+    fn check_parse_response(&mut self) {
+        if let Some(rx) = self.rx_parse.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv import parse response");
+                    self.rx_parse = None;
+                    self.status = match result {
+                        Ok(import) => {
+                            self.preview = Some(import);
+                            Status::Previewing
+                        }
+                        Err(error) => Status::ParseFailure(error),
+                    };
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_parse = None,
+            }
+        }
+    }
+
+    /// Save the previewed import (everything is saved, or nothing)
+    fn request_save(&mut self) {
+        let Some(import) = self.preview.take() else {
+            return;
+        };
+        self.status = Status::InProgress;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_save = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                let report = save_import(&mut transaction, import).await?;
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(report)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Check for the report of the import being saved (if any)
+    fn check_save_response(&mut self) {
+        if let Some(rx) = self.rx_save.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv import save response");
+                    self.rx_save = None;
+                    self.status = match result {
+                        Ok(report) => {
+                            info!(
+                                target: ACTIVITY,
+                                "Imported {} entities & {} timelines",
+                                report.created_entities,
+                                report.created_timelines
+                            );
+                            if report.created_entities + report.created_timelines > 0 {
+                                let _ = self.tx_crud_operation_executed.send(());
+                            }
+                            Status::Saved(report)
+                        }
+                        Err(error) => {
+                            warn!(target: ACTIVITY, "Import failed: {error}");
+                            Status::SaveFailure(error)
+                        }
+                    };
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_save = None,
+            }
+        }
+    }
+
+    /// Draw the format & file choices
+    fn draw_file_choice(&mut self, ui: &mut Ui) {
+        open_timeline_gui_core::Label::sub_heading(ui, "File");
+        let description = self
+            .format
+            .and_then(|format| self.importers.by_name(format).ok())
+            .map(|importer| importer.description())
+            .unwrap_or("The format is worked out from the file's extension");
+        open_timeline_gui_core::Label::description(ui, description);
+        ui.add_space(5.0);
+
+        let busy = self.rx_parse.is_some() || self.rx_save.is_some();
+        ui.add_enabled_ui(!busy, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Format");
+                ComboBox::from_id_salt("import_format")
+                    .selected_text(self.format.unwrap_or("Automatic"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.format, None, "Automatic");
+                        for importer in self.importers.iter() {
+                            ui.selectable_value(
+                                &mut self.format,
+                                Some(importer.name()),
+                                importer.name(),
+                            );
+                        }
+                    });
+            });
+            ui.add_space(5.0);
+            if open_timeline_gui_core::Button::tall_full_width(ui, "Choose File…").clicked()
+                && let Some(path) = rfd::FileDialog::new().pick_file()
+            {
+                self.request_parse(path);
+            }
+        });
+        if let Some(path) = &self.path {
+            ui.label(path.display().to_string());
+        }
+    }
+
+    /// Draw what would be imported, and what was left out
+    fn draw_preview(&mut self, ui: &mut Ui) {
+        let Some(preview) = &self.preview else {
+            return;
+        };
+
+        open_timeline_gui_core::Label::sub_heading(ui, "Preview");
+        let description = format!(
+            "{} entities & {} timelines (any that already exist will be skipped)",
+            preview.entities.len(),
+            preview.timelines.len()
+        );
+        open_timeline_gui_core::Label::description(ui, &description);
+        ui.add_space(5.0);
+        ScrollArea::vertical()
+            .id_salt("import_preview")
+            .max_height(250.0)
+            .show(ui, |ui| {
+                Grid::new("import_preview_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for entity in &preview.entities {
+                            ui.label("Entity");
+                            ui.label(entity.name().as_str());
+                            let dates = match entity.end() {
+                                Some(end) => format!(
+                                    "{} - {}",
+                                    entity.start().as_era_date_format(),
+                                    end.as_era_date_format()
+                                ),
+                                None => entity.start().as_era_date_format(),
+                            };
+                            ui.label(dates);
+                            ui.end_row();
+                        }
+                        for timeline in &preview.timelines {
+                            ui.label("Timeline");
+                            ui.label(timeline.name().as_str());
+                            let count = timeline
+                                .entities()
+                                .as_ref()
+                                .map(|entities| entities.collection().len())
+                                .unwrap_or_default();
+                            ui.label(format!("{count} entities"));
+                            ui.end_row();
+                        }
+                    });
+            });
+        ui.add_space(15.0);
+
+        if !preview.issues.is_empty() {
+            open_timeline_gui_core::Label::sub_heading(ui, "Left Out");
+            open_timeline_gui_core::Label::description(
+                ui,
+                "These records couldn't be read, and won't be imported",
+            );
+            ui.add_space(5.0);
+            ScrollArea::vertical()
+                .id_salt("import_issues")
+                .max_height(150.0)
+                .show(ui, |ui| {
+                    for issue in &preview.issues {
+                        ui.label(issue.to_string());
+                    }
+                });
+            ui.add_space(15.0);
+        }
+
+        let can_import = !preview.entities.is_empty() || !preview.timelines.is_empty();
+        ui.add_enabled_ui(can_import, |ui| {
+            if open_timeline_gui_core::Button::tall_full_width(ui, "Import").clicked() {
+                self.request_save();
+            }
+        });
+    }
+
+    /// Draw what was skipped by the last import
+    fn draw_report(&mut self, ui: &mut Ui) {
+        let Status::Saved(report) = &self.status else {
+            return;
+        };
+        if report.skipped_entities.is_empty() && report.skipped_timelines.is_empty() {
+            return;
+        }
+
+        open_timeline_gui_core::Label::sub_heading(ui, "Skipped");
+        open_timeline_gui_core::Label::description(ui, "These already exist");
+        ui.add_space(5.0);
+        Grid::new("import_skipped").num_columns(2).show(ui, |ui| {
+            for name in &report.skipped_entities {
+                ui.label("Entity");
+                ui.label(name.as_str());
+                ui.end_row();
+            }
+            for name in &report.skipped_timelines {
+                ui.label("Timeline");
+                ui.label(name.as_str());
+                ui.end_row();
+            }
+        });
+    }
+}
+
+impl Draw for ImportGui {
+    fn draw(&mut self, _ctx: &Context, ui: &mut Ui) {
+        // Status
+        GuiStatus::display(ui, &self.status);
+        ui.separator();
+
+        // Description
+        let description = "This panel imports entities & timelines from other formats (e.g. TimelineJS).  The file is previewed first, and nothing is saved until you choose to import it";
+        open_timeline_gui_core::Label::description(ui, description);
+        ui.separator();
+
+        // File
+        self.draw_file_choice(ui);
+        ui.add_space(15.0);
+
+        // Preview & report
+        self.draw_preview(ui);
+        self.draw_report(ui);
+    }
+}
+
+impl CheckForUpdates for ImportGui {
+    fn check_for_updates(&mut self) {
+        self.check_parse_response();
+        self.check_save_response();
+    }
+
+    fn waiting_for_updates(&mut self) -> bool {
+        let waiting = self.rx_parse.is_some() || self.rx_save.is_some();
+        if waiting {
+            info!("ImportGui is waiting for updates");
+        }
+        waiting
+    }
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Desktop GUI insights (how the entities in the database are distributed)
+//!
+
+use crate::common::fetch_cached_stat_crud;
+use crate::config::SharedConfig;
+use crate::spawn_operation;
+use eframe::egui::{Align, Context, Grid, Layout, Pos2, Rect, ScrollArea, Sense, Ui, Vec2};
+use open_timeline_crud::{
+    Cached, CrudError, EntityInsights, EntityInsightsStat, LIFESPAN_BUCKET_YEARS,
+};
+use open_timeline_gui_core::{CheckForUpdates, Draw, Reload};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::error::TryRecvError;
+
+/// The height of each chart
+const CHART_HEIGHT: f32 = 150.0;
+
+/// The insights GUI panel in the main window
+#[derive(Debug)]
+pub struct InsightsGui {
+    /// The insights (& when they were computed)
+    insights: Option<Cached<EntityInsights>>,
+
+    /// Receive up-to-date insights
+    rx_reload: Option<Receiver<Result<Cached<EntityInsights>, CrudError>>>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl InsightsGui {
+    /// Create a new insights GUI panel manager
+    pub fn new(shared_config: SharedConfig) -> Self {
+        let mut insights_gui = Self {
+            insights: None,
+            rx_reload: None,
+            shared_config,
+        };
+        insights_gui.request_reload();
+        insights_gui
+    }
+}
+
+impl Reload for InsightsGui {
+    fn request_reload(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_reload = Some(rx);
+        spawn_operation!(fetch_cached_stat_crud::<EntityInsightsStat>(
+            Arc::clone(&self.shared_config),
+            tx,
+        ));
+    }
+
+    fn check_reload_response(&mut self) {
+        if let Some(rx) = self.rx_reload.as_mut() {
+            match rx.try_recv() {
+                Ok(msg) => {
+                    debug!("Recv insights response");
+                    self.rx_reload = None;
+                    match msg {
+                        Ok(insights) => self.insights = Some(insights),
+                        Err(error) => warn!("Error computing insights: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_reload = None,
+            }
+        }
+    }
+}
+
+impl Draw for InsightsGui {
+    fn draw(&mut self, _ctx: &Context, ui: &mut Ui) {
+        let Some(cached) = self.insights.as_ref() else {
+            ui.spinner();
+            return;
+        };
+        let insights = cached.value();
+        ui.weak(format!("Computed {}s ago", cached.age().as_secs()));
+        ui.separator();
+
+        ScrollArea::vertical().show(ui, |ui| {
+            // Lifespans
+            open_timeline_gui_core::Label::sub_heading(ui, "Lifespans");
+            let lifespans: Vec<(String, i64)> =
+                fill_gaps(&insights.lifespans, LIFESPAN_BUCKET_YEARS)
+                    .into_iter()
+                    .map(|(bucket, count)| {
+                        let label =
+                            format!("{bucket}–{} years", bucket + LIFESPAN_BUCKET_YEARS - 1);
+                        (label, count)
+                    })
+                    .collect();
+            draw_bar_chart(ui, &lifespans);
+            ui.separator();
+
+            // Starts (e.g. births) per decade
+            open_timeline_gui_core::Label::sub_heading(ui, "Starts per Decade");
+            let starts: Vec<(String, i64)> = fill_gaps(&insights.starts_per_decade, 10)
+                .into_iter()
+                .map(|(decade, count)| (period_label(decade), count))
+                .collect();
+            draw_bar_chart(ui, &starts);
+            ui.separator();
+
+            // Most common tags per century
+            open_timeline_gui_core::Label::sub_heading(ui, "Most Common Tags per Century");
+            if insights.top_tags_per_century.is_empty() {
+                open_timeline_gui_core::Label::weak(ui, "No entities have tags");
+                return;
+            }
+            Grid::new("insights_top_tags_per_century")
+                .striped(true)
+                .show(ui, |ui| {
+                    for (century, tag_counts) in &insights.top_tags_per_century {
+                        ui.label(period_label(*century));
+                        let tags = tag_counts
+                            .iter()
+                            .map(|(tag, count)| format!("{tag} ({count})"))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        ui.label(tags);
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+impl CheckForUpdates for InsightsGui {
+    fn check_for_updates(&mut self) {
+        self.check_reload_response();
+    }
+
+    fn waiting_for_updates(&mut self) -> bool {
+        let waiting = self.rx_reload.is_some();
+        if waiting {
+            info!("InsightsGui is waiting for updates");
+        }
+        waiting
+    }
+}
+
+/// Include the empty buckets between the first and last (so that the chart
+/// shows the gaps)
+fn fill_gaps(counts: &BTreeMap<i64, i64>, step: i64) -> Vec<(i64, i64)> {
+    let (Some(first), Some(last)) = (counts.keys().next(), counts.keys().next_back()) else {
+        return Vec::new();
+    };
+    (*first..=*last)
+        .step_by(step as usize)
+        .map(|bucket| (bucket, counts.get(&bucket).copied().unwrap_or_default()))
+        .collect()
+}
+
+/// Label the decade/century starting in `year` (e.g. "1800s" or "100s BC")
+fn period_label(year: i64) -> String {
+    match year < 0 {
+        true => format!("{}s BC", -year),
+        false => format!("{year}s"),
+    }
+}
+
+/// Draw a simple bar chart (hovering over a bar shows its label and value)
+fn draw_bar_chart(ui: &mut Ui, bars: &[(String, i64)]) {
+    let Some(max) = bars
+        .iter()
+        .map(|(_, value)| *value)
+        .max()
+        .filter(|max| *max > 0)
+    else {
+        open_timeline_gui_core::Label::weak(ui, "Nothing to show");
+        return;
+    };
+    let size = Vec2::new(ui.available_width(), CHART_HEIGHT);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+    let bar_width = rect.width() / bars.len() as f32;
+    let hovered = response
+        .hover_pos()
+        .map(|pos| ((pos.x - rect.left()) / bar_width) as usize);
+
+    // Bars
+    let painter = ui.painter_at(rect);
+    let gap = (bar_width * 0.1).min(2.0);
+    for (i, (_, value)) in bars.iter().enumerate() {
+        let height = rect.height() * (*value as f32 / max as f32);
+        let left = rect.left() + (i as f32 * bar_width);
+        let bar = Rect::from_min_max(
+            Pos2::new(left + gap, rect.bottom() - height),
+            Pos2::new(left + bar_width - gap, rect.bottom()),
+        );
+        let colour = match hovered == Some(i) {
+            true => ui.visuals().selection.bg_fill,
+            false => ui.visuals().weak_text_color(),
+        };
+        painter.rect_filled(bar, 0.0, colour);
+    }
+    if let Some((label, value)) = hovered.and_then(|i| bars.get(i)) {
+        response.on_hover_text(format!("{label}: {value}"));
+    }
+
+    // The range covered
+    if let (Some((first, _)), Some((last, _))) = (bars.first(), bars.last()) {
+        ui.horizontal(|ui| {
+            ui.weak(first);
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| ui.weak(last));
+        });
+    }
+}
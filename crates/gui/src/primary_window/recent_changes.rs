@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Desktop GUI recently created & updated entities and timelines
+//!
+
+use crate::activity_log::ACTIVITY;
+use crate::app::{ActionRequest, EntityOrTimelineActionRequest};
+use crate::components::OpenTimelineButton;
+use crate::config::SharedConfig;
+use crate::consts::{EDIT_BUTTON_WIDTH, VIEW_BUTTON_WIDTH};
+use crate::spawn_transaction_no_commit_send_result;
+use chrono::{DateTime, Local};
+use eframe::egui::{self, Align, Context, Layout, RichText, ScrollArea, Ui};
+use egui_extras::{Column, TableBuilder};
+use open_timeline_crud::{CrudError, RecentChange, RecentChangeKind, fetch_recent_changes};
+use open_timeline_gui_core::{
+    CheckForUpdates, Draw, Reload, Toast, body_text_height, widget_x_spacing,
+};
+use std::sync::Arc;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
+
+/// The maximum number of recent changes listed
+const RECENT_CHANGES_LIMIT: u32 = 100;
+
+/// The width of the column stating whether it's an entity or a timeline
+const KIND_WIDTH: f32 = 70.0;
+
+/// The width of the column stating whether it was created or updated
+const CHANGE_WIDTH: f32 = 70.0;
+
+/// The width of the column stating when it was changed
+const TIME_WIDTH: f32 = 130.0;
+
+/// What the user clicked in the list
+enum RecentChangeClicked {
+    View(RecentChange),
+    Edit(RecentChange),
+}
+
+/// The recent changes GUI panel in the main window
+#[derive(Debug)]
+pub struct RecentChangesGui {
+    /// The recently created & updated entities and timelines (if fetched)
+    recent_changes: Option<Vec<RecentChange>>,
+
+    /// Receive up-to-date recent changes after a reload is requested
+    rx_reload: Option<Receiver<Result<Vec<RecentChange>, CrudError>>>,
+
+    /// Used to request new entity/timeline edit & view windows
+    tx_action_request: UnboundedSender<ActionRequest>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl RecentChangesGui {
+    /// Create a new recent changes GUI panel manager
+    pub fn new(
+        shared_config: SharedConfig,
+        tx_action_request: UnboundedSender<ActionRequest>,
+    ) -> Self {
+        let mut recent_changes_gui = Self {
+            recent_changes: None,
+            rx_reload: None,
+            tx_action_request,
+            shared_config,
+        };
+        recent_changes_gui.request_reload();
+        recent_changes_gui
+    }
+
+    /// Handle a click in the list
+    fn handle_click(&mut self, clicked: RecentChangeClicked) {
+        let (change, action) = match clicked {
+            RecentChangeClicked::View(change) => {
+                let action = EntityOrTimelineActionRequest::ViewExisting(change.id());
+                (change, action)
+            }
+            RecentChangeClicked::Edit(change) => {
+                let action = EntityOrTimelineActionRequest::EditExisting(change.id());
+                (change, action)
+            }
+        };
+        let request = match change.kind() {
+            RecentChangeKind::Entity => ActionRequest::Entity(action),
+            RecentChangeKind::Timeline => ActionRequest::Timeline(action),
+        };
+        let _ = self.tx_action_request.send(request);
+    }
+}
+
+impl Reload for RecentChangesGui {
+    fn request_reload(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_reload = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_recent_changes(transaction, RECENT_CHANGES_LIMIT).await }
+        );
+    }
+
+    fn check_reload_response(&mut self) {
+        if let Some(rx) = self.rx_reload.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv recent changes response");
+                    self.rx_reload = None;
+                    match result {
+                        Ok(recent_changes) => self.recent_changes = Some(recent_changes),
+                        Err(error) => {
+                            let msg = format!("Failed to reload recent changes: {error}");
+                            warn!(target: ACTIVITY, "{msg}");
+                            let _ = self
+                                .tx_action_request
+                                .send(ActionRequest::toast(Toast::error(msg)));
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_reload = None,
+            }
+        }
+    }
+}
+
+impl Draw for RecentChangesGui {
+    fn draw(&mut self, _ctx: &Context, ui: &mut Ui) {
+        self.check_reload_response();
+        if let Some(clicked) = draw_recent_changes(ui, &self.recent_changes) {
+            self.handle_click(clicked);
+        }
+    }
+}
+
+impl CheckForUpdates for RecentChangesGui {
+    fn check_for_updates(&mut self) {
+        self.check_reload_response();
+    }
+
+    fn waiting_for_updates(&mut self) -> bool {
+        let waiting = self.rx_reload.is_some();
+        if waiting {
+            info!("RecentChangesGui is waiting for updates");
+        }
+        waiting
+    }
+}
+
+/// The (local) time of the unix time (in seconds)
+fn local_time(unix_time: i64) -> String {
+    match DateTime::from_timestamp(unix_time, 0) {
+        Some(time) => time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => String::from("Unknown"),
+    }
+}
+
+/// Draw the list of recent changes (returning what was clicked, if anything)
+fn draw_recent_changes(
+    ui: &mut Ui,
+    recent_changes: &Option<Vec<RecentChange>>,
+) -> Option<RecentChangeClicked> {
+    let Some(recent_changes) = recent_changes
+        .as_ref()
+        .filter(|recent_changes| !recent_changes.is_empty())
+    else {
+        open_timeline_gui_core::Label::none(ui);
+        return None;
+    };
+
+    // Sizes
+    let row_height = body_text_height(ui);
+    let spacing = widget_x_spacing(ui);
+    let name_width = ui.available_width()
+        - KIND_WIDTH
+        - CHANGE_WIDTH
+        - TIME_WIDTH
+        - EDIT_BUTTON_WIDTH
+        - VIEW_BUTTON_WIDTH
+        - (5.0 * spacing);
+    let name_width = name_width.max(0.0);
+
+    let mut clicked = None;
+    ScrollArea::vertical().show(ui, |ui| {
+        TableBuilder::new(ui)
+            .id_salt("recent_changes")
+            .striped(true)
+            .column(Column::exact(KIND_WIDTH))
+            .column(Column::exact(name_width).clip(true))
+            .column(Column::exact(CHANGE_WIDTH))
+            .column(Column::exact(TIME_WIDTH))
+            .column(Column::exact(EDIT_BUTTON_WIDTH))
+            .column(Column::exact(VIEW_BUTTON_WIDTH))
+            .body(|mut body| {
+                for change in recent_changes {
+                    body.row(row_height, |mut row| {
+                        // Entity or timeline
+                        row.col(|ui| {
+                            let kind = match change.kind() {
+                                RecentChangeKind::Entity => "Entity",
+                                RecentChangeKind::Timeline => "Timeline",
+                            };
+                            ui.label(RichText::new(kind).weak());
+                        });
+                        // Name
+                        row.col(|ui| {
+                            ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
+                                ui.add(egui::Label::new(change.name().as_str()).truncate());
+                            });
+                        });
+                        // Created or updated
+                        row.col(|ui| {
+                            let what = match change.is_new() {
+                                true => "Created",
+                                false => "Updated",
+                            };
+                            ui.label(what);
+                        });
+                        // When
+                        row.col(|ui| {
+                            let label = ui.label(local_time(change.updated_at()));
+                            if !change.is_new() {
+                                let created = local_time(change.created_at());
+                                label.on_hover_text(format!("Created {created}"));
+                            }
+                        });
+                        // Edit
+                        row.col(|ui| {
+                            if OpenTimelineButton::edit(ui).clicked() {
+                                clicked = Some(RecentChangeClicked::Edit(change.clone()));
+                            }
+                        });
+                        // View
+                        row.col(|ui| {
+                            if OpenTimelineButton::view(ui).clicked() {
+                                clicked = Some(RecentChangeClicked::View(change.clone()));
+                            }
+                        });
+                    });
+                }
+            });
+    });
+    clicked
+}
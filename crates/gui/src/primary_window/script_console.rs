@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Running Rhai scripts against the database (an advanced tool for bulk edits)
+//!
+
+use crate::activity_log::ACTIVITY;
+use crate::config::SharedConfig;
+use crate::spawn_operation;
+use eframe::egui::{self, Context, Grid, Response, ScrollArea, Spinner, TextEdit, TextStyle, Ui};
+use open_timeline_crud::CrudError;
+use open_timeline_gui_core::{CheckForUpdates, Draw};
+use open_timeline_gui_core::{DisplayStatus, GuiStatus};
+use open_timeline_scripting::{ScriptReport, run_script};
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{Receiver, error::TryRecvError};
+
+/// The script shown when the console is first opened
+const EXAMPLE_SCRIPT: &str = r#"// Tag every entity that starts in the 1700s
+for entity in all_entities() {
+    if entity.start_year >= 1700 && entity.start_year <= 1799 {
+        entity.add_tag("century=eighteenth");
+        update(entity);
+    }
+}
+"#;
+
+/// The script console GUI panel in the main window
+#[derive(Debug)]
+pub struct ScriptConsoleGui {
+    /// The script being edited
+    script: String,
+
+    /// Receive the report of the script being run (if any)
+    rx_run: Option<Receiver<Result<ScriptReport, CrudError>>>,
+
+    /// The status of the last run (which may be none)
+    status: Status,
+
+    /// Used to indirectly inform the rest of the application that a CRUD
+    /// operation has been executed successfully (i.e. reloads may be required)
+    tx_crud_operation_executed: UnboundedSender<()>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+/// The possible states of operation for the panel
+#[derive(Debug)]
+enum Status {
+    /// Nothing has been run while the programme has ben running
+    None,
+
+    /// The last run finished (though the script itself may have failed)
+    Finished(ScriptReport),
+
+    /// The database couldn't be used
+    Failure(CrudError),
+
+    /// A script is running
+    InProgress,
+}
+
+impl DisplayStatus for Status {
+    fn status_display(&self, ui: &mut Ui) -> Response {
+        match &self {
+            Self::None => ui.add(egui::Label::new(String::from("Ready")).truncate()),
+            Self::Finished(report) => {
+                let text = match (&report.error, report.committed) {
+                    (Some(error), _) => format!("Script error (nothing was changed): {error}"),
+                    (None, true) => format!("Success: made {} changes", report.changes.len()),
+                    (None, false) => {
+                        format!("Dry run: {} changes would be made", report.changes.len())
+                    }
+                };
+                ui.add(egui::Label::new(text).truncate())
+            }
+            Self::Failure(error) => ui.add(egui::Label::new(format!("Error: {error}")).truncate()),
+            Self::InProgress => ui.add(Spinner::new()),
+        }
+    }
+}
+
+impl ScriptConsoleGui {
+    /// Create a new script console GUI panel manager
+    pub fn new(
+        shared_config: SharedConfig,
+        tx_crud_operation_executed: UnboundedSender<()>,
+    ) -> Self {
+        Self {
+            script: String::from(EXAMPLE_SCRIPT),
+            rx_run: None,
+            status: Status::None,
+            tx_crud_operation_executed,
+            shared_config,
+        }
+    }
+
+    /// Run the script (committing only if it succeeds and isn't a dry run)
+    fn request_run(&mut self, dry_run: bool) {
+        self.status = Status::InProgress;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_run = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        let script = self.script.clone();
+        spawn_operation!(async move {
+            let db_pool = shared_config.read().await.db_pool.clone();
+            let result = run_script(&db_pool, &script, dry_run).await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Check for the report of the script being run (if any)
+    fn check_run_response(&mut self) {
+        if let Some(rx) = self.rx_run.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv script response");
+                    self.rx_run = None;
+                    self.status = match result {
+                        Ok(report) => {
+                            if report.committed && !report.changes.is_empty() {
+                                let changes = report.changes.len();
+                                info!(target: ACTIVITY, "Ran a script ({changes} changes)");
+                                let _ = self.tx_crud_operation_executed.send(());
+                            }
+                            Status::Finished(report)
+                        }
+                        Err(error) => {
+                            warn!(target: ACTIVITY, "Script failed: {error}");
+                            Status::Failure(error)
+                        }
+                    };
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_run = None,
+            }
+        }
+    }
+
+    /// Draw the script editor & the buttons to run it
+    fn draw_editor(&mut self, ui: &mut Ui) {
+        open_timeline_gui_core::Label::sub_heading(ui, "Script");
+        let description = "Use entity(id_or_name), entities(bool_tag_expr), all_entities(), timelines(bool_tag_expr), create_entity(name, start_year) & update(entity).  Entities have has_tag(tag), add_tag(tag) & remove_tag(tag) methods";
+        open_timeline_gui_core::Label::description(ui, description);
+        ui.add_space(5.0);
+
+        let running = self.rx_run.is_some();
+        let editor = TextEdit::multiline(&mut self.script)
+            .code_editor()
+            .desired_rows(15)
+            .desired_width(f32::INFINITY);
+        ui.add_enabled(!running, editor);
+        ui.add_space(5.0);
+
+        let can_run = !running && !self.script.trim().is_empty();
+        ui.add_enabled_ui(can_run, |ui| {
+            ui.columns(2, |columns| {
+                if open_timeline_gui_core::Button::tall_full_width(&mut columns[0], "Dry Run")
+                    .clicked()
+                {
+                    self.request_run(true);
+                }
+                if open_timeline_gui_core::Button::tall_full_width(&mut columns[1], "Run").clicked()
+                {
+                    self.request_run(false);
+                }
+            });
+        });
+    }
+
+    /// Draw what the last script printed & changed
+    fn draw_report(&mut self, ui: &mut Ui) {
+        let Status::Finished(report) = &self.status else {
+            return;
+        };
+
+        if !report.output.is_empty() {
+            open_timeline_gui_core::Label::sub_heading(ui, "Output");
+            ScrollArea::vertical()
+                .id_salt("script_output")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for line in &report.output {
+                        ui.label(egui::RichText::new(line).text_style(TextStyle::Monospace));
+                    }
+                });
+            ui.add_space(15.0);
+        }
+
+        open_timeline_gui_core::Label::sub_heading(ui, "Changes");
+        let description = if report.committed {
+            "Made to the database"
+        } else {
+            "Not made to the database"
+        };
+        open_timeline_gui_core::Label::description(ui, description);
+        ui.add_space(5.0);
+        if report.changes.is_empty() {
+            ui.label("None");
+            return;
+        }
+        Grid::new("script_changes").num_columns(1).show(ui, |ui| {
+            for change in &report.changes {
+                ui.label(change.to_string());
+                ui.end_row();
+            }
+        });
+    }
+}
+
+impl Draw for ScriptConsoleGui {
+    fn draw(&mut self, _ctx: &Context, ui: &mut Ui) {
+        // Status
+        GuiStatus::display(ui, &self.status);
+        ui.separator();
+
+        // Description
+        let description = "This panel runs Rhai scripts that query & edit the database.  Everything a script does happens at once (or not at all if it fails), and a dry run shows what would change without changing anything";
+        open_timeline_gui_core::Label::description(ui, description);
+        ui.separator();
+
+        // Editor
+        self.draw_editor(ui);
+        ui.add_space(15.0);
+
+        // Report
+        self.draw_report(ui);
+    }
+}
+
+impl CheckForUpdates for ScriptConsoleGui {
+    fn check_for_updates(&mut self) {
+        self.check_run_response();
+    }
+
+    fn waiting_for_updates(&mut self) -> bool {
+        let waiting = self.rx_run.is_some();
+        if waiting {
+            info!("ScriptConsoleGui is waiting for updates");
+        }
+        waiting
+    }
+}
@@ -4,26 +4,45 @@
 //! Full GUI search
 //!
 
+mod bulk_actions;
+mod on_this_day;
+mod saved_searches;
+mod search_history;
+
 use crate::app::{ActionRequest, EntityOrTimelineActionRequest};
+use crate::clipboard::{CopyToClipboard, copy_entity_to_clipboard};
+use crate::common::set_starred_crud;
 use crate::components::OpenTimelineButton;
 use crate::components::{BooleanExpressionGui, HintText};
 use crate::config::SharedConfig;
-use crate::consts::{EDIT_BUTTON_WIDTH, VIEW_BUTTON_WIDTH};
+use crate::consts::{EDIT_BUTTON_WIDTH, SELECT_CHECKBOX_WIDTH, VIEW_BUTTON_WIDTH};
+use crate::drag_and_drop::ToDragPayload;
+use crate::spawn_operation;
 use crate::spawn_transaction_no_commit_send_result;
-use bool_tag_expr::BoolTagExpr;
-use eframe::egui::{self, Align, Context, Layout, ScrollArea, TextEdit, Ui, Vec2};
+use bulk_actions::BulkActionsGui;
+use eframe::egui::{
+    self, Align, Context, Id, Key, Layout, Modifiers, ScrollArea, TextEdit, Ui, Vec2,
+};
 use egui_extras::{Column, TableBuilder};
+use on_this_day::OnThisDayGui;
 use open_timeline_core::{
-    IsReducedCollection, IsReducedType, OpenTimelineId, ReducedEntities, ReducedEntity,
-    ReducedTimeline, ReducedTimelines,
+    Entity, IsReducedCollection, IsReducedType, OpenTimelineId, ReducedEntities, ReducedEntity,
+    ReducedTimeline, ReducedTimelines, TagQuery,
+};
+use open_timeline_crud::{
+    CrudError, FavouriteKind, FetchById, FetchByPartialNameAndBoolTagExpr, Limit, SavedSearch,
+    SavedSearchKind, SearchQueryKind, fetch_starred_ids,
 };
-use open_timeline_crud::{CrudError, FetchByPartialNameAndBoolTagExpr, Limit};
 use open_timeline_gui_core::{
-    CheckForUpdates, Draw, EmptyConsideredInvalid, Reload, ShowRemoveButton, body_text_height,
-    widget_x_spacing,
+    CheckForUpdates, Draw, EmptyConsideredInvalid, Reload, ShowRemoveButton, ValidSynchronous,
+    body_text_height, widget_x_spacing,
 };
+use saved_searches::SavedSearchesGui;
+use search_history::SearchHistoryGui;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use std::u32;
+use tokio::sync::mpsc::error::TryRecvError;
 use tokio::sync::mpsc::{Receiver, UnboundedSender};
 
 /// The maximum number of search results shown for each results section
@@ -38,8 +57,45 @@ pub struct SearchGui {
     /// Timeline search terms & results
     timeline_search: SearchPartialNameAndBoolTagExpr<ReducedTimelines>,
 
+    /// Bulk actions on the selected entity search results
+    entity_bulk_actions: BulkActionsGui,
+
+    /// Bulk actions on the selected timeline search results
+    timeline_bulk_actions: BulkActionsGui,
+
+    /// Saved entity searches
+    entity_saved_searches: SavedSearchesGui,
+
+    /// Saved timeline searches
+    timeline_saved_searches: SavedSearchesGui,
+
+    /// Recently used & pinned queries (suggested under the search inputs)
+    search_history: SearchHistoryGui,
+
+    /// The entities that started or ended on a day of the year
+    on_this_day: OnThisDayGui,
+
+    /// The results column that keyboard navigation applies to
+    keyboard_column: SearchColumn,
+
+    /// Whether keyboard navigation is enabled (it isn't while something else,
+    /// such as the command palette, wants the arrow & enter keys)
+    keyboard_navigation_enabled: bool,
+
     /// Used request new windows for editing and viewing timelines and entities
     tx_action_request: UnboundedSender<ActionRequest>,
+
+    /// The IDs of the starred entities & timelines
+    starred: BTreeSet<OpenTimelineId>,
+
+    /// Receive the IDs of the starred entities & timelines
+    rx_starred: Option<Receiver<Result<BTreeSet<OpenTimelineId>, CrudError>>>,
+
+    /// Receive the entity to copy to the clipboard
+    rx_copy_entity: Option<Receiver<Result<Entity, CrudError>>>,
+
+    /// Database pool
+    shared_config: SharedConfig,
 }
 
 impl SearchGui {
@@ -47,6 +103,7 @@ impl SearchGui {
     pub fn new(
         shared_config: SharedConfig,
         tx_action_request: UnboundedSender<ActionRequest>,
+        tx_crud_operation_executed: UnboundedSender<()>,
     ) -> Self {
         let mut search = Self {
             entity_search: SearchPartialNameAndBoolTagExpr::<ReducedEntities>::new(Arc::clone(
@@ -55,37 +112,128 @@ impl SearchGui {
             timeline_search: SearchPartialNameAndBoolTagExpr::<ReducedTimelines>::new(Arc::clone(
                 &shared_config,
             )),
+            entity_bulk_actions: BulkActionsGui::new(
+                Arc::clone(&shared_config),
+                tx_crud_operation_executed.clone(),
+                FavouriteKind::Entity,
+            ),
+            timeline_bulk_actions: BulkActionsGui::new(
+                Arc::clone(&shared_config),
+                tx_crud_operation_executed,
+                FavouriteKind::Timeline,
+            ),
+            entity_saved_searches: SavedSearchesGui::new(
+                Arc::clone(&shared_config),
+                SavedSearchKind::Entity,
+            ),
+            timeline_saved_searches: SavedSearchesGui::new(
+                Arc::clone(&shared_config),
+                SavedSearchKind::Timeline,
+            ),
+            search_history: SearchHistoryGui::new(Arc::clone(&shared_config)),
+            on_this_day: OnThisDayGui::new(Arc::clone(&shared_config), tx_action_request.clone()),
+            keyboard_column: SearchColumn::Timelines,
+            keyboard_navigation_enabled: true,
             tx_action_request,
+            starred: BTreeSet::new(),
+            rx_starred: None,
+            rx_copy_entity: None,
+            shared_config,
         };
         search.request_reload();
         search
     }
 
+    /// Enable/disable keyboard navigation of the search results
+    pub fn set_keyboard_navigation_enabled(&mut self, enabled: bool) {
+        self.keyboard_navigation_enabled = enabled;
+    }
+
+    /// Get the keyboard navigation input (if any) and the results column it
+    /// applies to.  Navigation only happens when nothing but a name search bar
+    /// has focus (so typing into other inputs is unaffected).
+    fn keyboard_navigation(&mut self, ctx: &Context) -> Option<(SearchColumn, KeyboardNavigation)> {
+        if !self.keyboard_navigation_enabled {
+            return None;
+        }
+        match ctx.memory(|memory| memory.focused()) {
+            None => (),
+            Some(id) if id == self.timeline_search.name_search_id() => {
+                self.keyboard_column = SearchColumn::Timelines
+            }
+            Some(id) if id == self.entity_search.name_search_id() => {
+                self.keyboard_column = SearchColumn::Entities
+            }
+            Some(_) => return None,
+        }
+        let navigation = ctx.input_mut(|i| {
+            if i.consume_key(Modifiers::NONE, Key::ArrowDown) {
+                Some(KeyboardNavigation::Down)
+            } else if i.consume_key(Modifiers::NONE, Key::ArrowUp) {
+                Some(KeyboardNavigation::Up)
+            } else if i.consume_key(Modifiers::COMMAND, Key::Enter) {
+                Some(KeyboardNavigation::Edit)
+            } else if i.consume_key(Modifiers::NONE, Key::Enter) {
+                Some(KeyboardNavigation::View)
+            } else {
+                None
+            }
+        })?;
+        Some((self.keyboard_column, navigation))
+    }
+
     /// Display the entity search results fetched by partial name
-    fn show_entity_search_results(&mut self, ui: &mut Ui, ctx: &Context) {
-        let clicked = self.entity_search.show(ctx, ui);
+    fn show_entity_search_results(
+        &mut self,
+        ui: &mut Ui,
+        ctx: &Context,
+        navigation: Option<KeyboardNavigation>,
+    ) {
+        let navigated = navigation.and_then(|navigation| self.entity_search.navigate(navigation));
+        let clicked = self
+            .entity_search
+            .show(ctx, ui, &self.starred)
+            .or(navigated);
         match clicked {
             None => (),
+            Some(SearchResultButtonClicked::Star(entity, starred)) => {
+                self.set_starred(FavouriteKind::Entity, entity.id(), starred)
+            }
             Some(SearchResultButtonClicked::View(entity)) => {
                 self.request_view_entity(ctx, ui, &entity)
             }
             Some(SearchResultButtonClicked::Edit(entity)) => {
                 self.request_edit_entity(ctx, ui, &entity)
             }
+            Some(SearchResultButtonClicked::Copy(entity)) => self.request_copy_entity(&entity),
         }
     }
 
     /// Display the timeline search results fetched by partial name
-    fn show_timeline_search_results(&mut self, ui: &mut Ui, ctx: &Context) {
-        let clicked = self.timeline_search.show(ctx, ui);
+    fn show_timeline_search_results(
+        &mut self,
+        ui: &mut Ui,
+        ctx: &Context,
+        navigation: Option<KeyboardNavigation>,
+    ) {
+        let navigated = navigation.and_then(|navigation| self.timeline_search.navigate(navigation));
+        let clicked = self
+            .timeline_search
+            .show(ctx, ui, &self.starred)
+            .or(navigated);
         match clicked {
             None => (),
+            Some(SearchResultButtonClicked::Star(timeline, starred)) => {
+                self.set_starred(FavouriteKind::Timeline, timeline.id(), starred)
+            }
             Some(SearchResultButtonClicked::View(timeline)) => {
                 self.request_view_timeline(ctx, ui, &timeline)
             }
             Some(SearchResultButtonClicked::Edit(timeline)) => {
                 self.request_edit_timeline(ctx, ui, &timeline)
             }
+            // Timelines can't be copied
+            Some(SearchResultButtonClicked::Copy(_)) => (),
         }
     }
 
@@ -117,6 +265,51 @@ impl SearchGui {
         ));
     }
 
+    /// Fetch the (full) entity so that it can be copied to the clipboard
+    fn request_copy_entity(&mut self, entity: &ReducedEntity) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_copy_entity = Some(rx);
+        let entity_id = entity.id();
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { Entity::fetch_by_id(transaction, &entity_id).await }
+        );
+    }
+
+    /// Copy the fetched entity to the clipboard (once it has been fetched)
+    fn check_copy_entity_response(&mut self, ctx: &Context) {
+        if let Some(rx) = self.rx_copy_entity.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_copy_entity = None;
+                    match result {
+                        Ok(entity) => copy_entity_to_clipboard(ctx, &entity),
+                        Err(error) => warn!("Unable to copy entity: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_copy_entity = None,
+            }
+        }
+    }
+
+    /// Star/unstar an entity or timeline
+    fn set_starred(&mut self, kind: FavouriteKind, id: OpenTimelineId, starred: bool) {
+        match starred {
+            true => self.starred.insert(id),
+            false => self.starred.remove(&id),
+        };
+        spawn_operation!(set_starred_crud(
+            Arc::clone(&self.shared_config),
+            kind,
+            id,
+            starred,
+        ));
+    }
+
     /// Send an [`ActionRequest`] request to the main control loop
     fn send_action_request(&mut self, request: ActionRequest) {
         let _ = self.tx_action_request.send(request);
@@ -125,6 +318,17 @@ impl SearchGui {
 
 impl Draw for SearchGui {
     fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
+        self.check_copy_entity_response(ctx);
+        let navigation = self.keyboard_navigation(ctx);
+        let navigation_for = |column| {
+            navigation.and_then(|(navigation_column, navigation)| {
+                (navigation_column == column).then_some(navigation)
+            })
+        };
+
+        self.on_this_day.draw(ui);
+        ui.separator();
+
         ui.columns(2, |columns| {
             // Timeline Column
             columns[0].vertical(|ui| {
@@ -138,9 +342,17 @@ impl Draw for SearchGui {
                 }
 
                 ui.separator();
-                draw_search_bars(ctx, ui, &mut self.timeline_search);
+                draw_search_bars(ctx, ui, &mut self.timeline_search, &mut self.search_history);
+                ui.add_space(5.0);
+                draw_saved_searches(
+                    ui,
+                    &mut self.timeline_saved_searches,
+                    &mut self.timeline_search,
+                );
                 ui.separator();
-                self.show_timeline_search_results(ui, ctx);
+                self.timeline_bulk_actions
+                    .draw(ctx, ui, &mut self.timeline_search.selected);
+                self.show_timeline_search_results(ui, ctx, navigation_for(SearchColumn::Timelines));
             });
 
             // Entity column
@@ -155,9 +367,13 @@ impl Draw for SearchGui {
                 }
 
                 ui.separator();
-                draw_search_bars(ctx, ui, &mut self.entity_search);
+                draw_search_bars(ctx, ui, &mut self.entity_search, &mut self.search_history);
+                ui.add_space(5.0);
+                draw_saved_searches(ui, &mut self.entity_saved_searches, &mut self.entity_search);
                 ui.separator();
-                self.show_entity_search_results(ui, ctx);
+                self.entity_bulk_actions
+                    .draw(ctx, ui, &mut self.entity_search.selected);
+                self.show_entity_search_results(ui, ctx, navigation_for(SearchColumn::Entities));
             });
         });
     }
@@ -165,13 +381,26 @@ impl Draw for SearchGui {
 
 impl CheckForUpdates for SearchGui {
     fn check_for_updates(&mut self) {
-        self.timeline_search.check_reload_response();
-        self.entity_search.check_reload_response();
+        self.check_reload_response();
+        if self.entity_bulk_actions.check_for_crud_status_updates() {
+            self.entity_search.selected.clear();
+        }
+        if self.timeline_bulk_actions.check_for_crud_status_updates() {
+            self.timeline_search.selected.clear();
+        }
     }
 
     fn waiting_for_updates(&mut self) -> bool {
         let waiting = self.timeline_search.rx_search_results.is_some()
-            || self.entity_search.rx_search_results.is_some();
+            || self.entity_search.rx_search_results.is_some()
+            || self.entity_bulk_actions.waiting_for_updates()
+            || self.timeline_bulk_actions.waiting_for_updates()
+            || self.on_this_day.waiting_for_updates()
+            || self.search_history.waiting_for_updates()
+            || self.entity_saved_searches.waiting_for_updates()
+            || self.timeline_saved_searches.waiting_for_updates()
+            || self.rx_starred.is_some()
+            || self.rx_copy_entity.is_some();
         if waiting {
             info!("SearchGui is waiting for updates");
         }
@@ -179,33 +408,61 @@ impl CheckForUpdates for SearchGui {
     }
 }
 
-/// Draw search bars
+/// Draw search bars (with suggestions from the search history under each)
 fn draw_search_bars<T>(
     ctx: &Context,
     ui: &mut Ui,
     search_info: &mut SearchPartialNameAndBoolTagExpr<T>,
+    search_history: &mut SearchHistoryGui,
 ) where
     T: FetchByPartialNameAndBoolTagExpr + IsReducedCollection + Default + 'static,
 {
     let changed = {
         // Search bar for searching by entity name
+        let name_search_id = search_info.name_search_id();
         let name_search_input = ui.add(
             TextEdit::singleline(&mut search_info.name_search)
+                .id(name_search_id)
                 .desired_width(f32::INFINITY)
                 .hint_text("Name"),
         );
-        if name_search_input.changed() {
+        let mut name_changed = name_search_input.changed();
+        if name_search_input.lost_focus() {
+            search_history.record(SearchQueryKind::Name, &search_info.name_search);
+        }
+        if let Some(query) =
+            search_history.draw_suggestions(ui, SearchQueryKind::Name, &search_info.name_search)
+        {
+            search_info.name_search = query;
+            name_changed = true;
+        }
+        if name_changed {
             search_info.name_search_active = true;
         }
         ui.add_space(5.0);
 
         // Search bar for searching by entity tag bool expr
         search_info.tag_boolean_expr_search.draw(ctx, ui);
-        if search_info.tag_boolean_expr_search.changed() {
+        let mut expr_changed = search_info.tag_boolean_expr_search.changed();
+        if search_info.tag_boolean_expr_search.submitted()
+            && search_info.tag_boolean_expr_search.is_valid_synchronous()
+        {
+            let expr = search_info.tag_boolean_expr_search.expr();
+            search_history.record(SearchQueryKind::BoolTagExpr, expr);
+        }
+        if let Some(query) = search_history.draw_suggestions(
+            ui,
+            SearchQueryKind::BoolTagExpr,
+            search_info.tag_boolean_expr_search.expr(),
+        ) {
+            search_info.tag_boolean_expr_search.set_expr(&query);
+            expr_changed = true;
+        }
+        if expr_changed {
             search_info.tag_boolean_expr_search_active =
                 !search_info.tag_boolean_expr_search.expr().trim().is_empty();
         }
-        search_info.tag_boolean_expr_search.changed() || name_search_input.changed()
+        expr_changed || name_changed
     };
 
     // Refresh search if needed
@@ -214,15 +471,68 @@ fn draw_search_bars<T>(
     }
 }
 
+/// Draw the saved searches, running the one clicked (if any)
+fn draw_saved_searches<T>(
+    ui: &mut Ui,
+    saved_searches: &mut SavedSearchesGui,
+    search_info: &mut SearchPartialNameAndBoolTagExpr<T>,
+) where
+    T: FetchByPartialNameAndBoolTagExpr + IsReducedCollection + Default + 'static,
+{
+    // Only the search terms in use are saved
+    let partial_name = match search_info.name_search_active {
+        true => search_info.name_search.clone(),
+        false => String::new(),
+    };
+    let bool_tag_expr = match search_info.tag_boolean_expr_search_active {
+        true => search_info.tag_boolean_expr_search.expr().to_string(),
+        false => String::new(),
+    };
+    if let Some(saved_search) = saved_searches.draw(ui, &partial_name, &bool_tag_expr) {
+        search_info.apply_saved_search(&saved_search);
+        search_info.request_reload();
+    }
+}
+
 impl Reload for SearchGui {
     fn request_reload(&mut self) {
         self.entity_search.request_reload();
         self.timeline_search.request_reload();
+        self.entity_bulk_actions.request_reload();
+        self.on_this_day.request_reload();
+        self.search_history.request_reload();
+        self.entity_saved_searches.request_reload();
+        self.timeline_saved_searches.request_reload();
+
+        // Starred entities & timelines
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_starred = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_starred_ids(transaction).await }
+        );
     }
 
     fn check_reload_response(&mut self) {
         self.entity_search.check_reload_response();
         self.timeline_search.check_reload_response();
+        self.entity_bulk_actions.check_reload_response();
+        self.on_this_day.check_reload_response();
+        self.search_history.check_reload_response();
+        self.entity_saved_searches.check_reload_response();
+        self.timeline_saved_searches.check_reload_response();
+        if let Some(rx) = self.rx_starred.as_mut()
+            && let Ok(result) = rx.try_recv()
+        {
+            self.rx_starred = None;
+            match result {
+                Ok(starred) => self.starred = starred,
+                Err(error) => warn!("Error fetching starred IDs: {error}"),
+            }
+        }
     }
 }
 
@@ -250,6 +560,15 @@ where
     /// The search results
     search_results: T,
 
+    /// The IDs of the selected search results (for bulk actions)
+    selected: BTreeSet<OpenTimelineId>,
+
+    /// The index of the search result highlighted using the keyboard
+    highlighted: Option<usize>,
+
+    /// Whether to scroll the highlighted search result into view
+    scroll_to_highlighted: bool,
+
     /// Receive the search results
     rx_search_results: Option<Receiver<Result<T, CrudError>>>,
 
@@ -272,13 +591,31 @@ where
                 ShowRemoveButton::No,
                 EmptyConsideredInvalid::No,
                 HintText::Default,
-            ),
+            )
+            .with_ranges(),
             search_results: T::default(),
+            selected: BTreeSet::new(),
+            highlighted: None,
+            scroll_to_highlighted: false,
             rx_search_results: None,
             shared_config,
         }
     }
 
+    /// Use a saved search's terms (only searching by the terms it has)
+    fn apply_saved_search(&mut self, saved_search: &SavedSearch) {
+        self.name_search = saved_search.partial_name().unwrap_or_default().to_string();
+        self.name_search_active = saved_search.partial_name().is_some();
+        let bool_tag_expr = saved_search.bool_tag_expr().unwrap_or_default();
+        self.tag_boolean_expr_search.set_expr(bool_tag_expr);
+        self.tag_boolean_expr_search_active = saved_search.bool_tag_expr().is_some();
+    }
+
+    /// The ID of the name search bar (used to tell whether it has focus)
+    fn name_search_id(&self) -> Id {
+        Id::new(format!("{}_name_search", self.gui_component_id_source))
+    }
+
     /// Request a new search by just partial name
     fn request_new_search_by_partial_name(&mut self) {
         let partial_name = self.name_search.clone();
@@ -297,23 +634,22 @@ where
 
     /// Request a new search by just bool tag expr
     fn request_new_search_by_bool_tag_expr(&mut self) {
-        let bool_tag_expr_result = BoolTagExpr::from(self.tag_boolean_expr_search.expr());
+        let tag_query_result = TagQuery::from(self.tag_boolean_expr_search.expr());
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.rx_search_results = Some(rx);
         let shared_config = Arc::clone(&self.shared_config);
         // TODO: can we use our spawn_block_needs_transaction_send_block_result_down_tx!() macro here? (add other with extra preamble arg?)
-        tokio::spawn(async move {
-            let bool_tag_expr = match bool_tag_expr_result {
-                Ok(expr) => expr,
+        spawn_operation!(async move {
+            let tag_query = match tag_query_result {
+                Ok(tag_query) => tag_query,
                 Err(error) => {
-                    let _ = tx.send(Err(CrudError::BoolExprParse(error))).await;
+                    let _ = tx.send(Err(CrudError::TagQueryParse(error))).await;
                     return;
                 }
             };
             let result = async {
                 let mut transaction = shared_config.read().await.db_pool.begin().await?;
-                T::fetch_by_bool_tag_expr(&mut transaction, Limit(SEARCH_LIMIT), bool_tag_expr)
-                    .await
+                T::fetch_by_tag_query(&mut transaction, Limit(SEARCH_LIMIT), &tag_query).await
             }
             .await;
             let _ = tx.send(result).await;
@@ -329,24 +665,24 @@ where
 
         // Partial name & bool tag expr
         let partial_name = self.name_search.clone();
-        let bool_tag_expr_result = BoolTagExpr::from(self.tag_boolean_expr_search.expr());
+        let tag_query_result = TagQuery::from(self.tag_boolean_expr_search.expr());
 
         // TODO: can we use our spawn_block_needs_transaction_send_block_result_down_tx!() macro here? (add other with extra preamble arg?)
-        tokio::spawn(async move {
-            let bool_tag_expr = match bool_tag_expr_result {
-                Ok(expr) => expr,
+        spawn_operation!(async move {
+            let tag_query = match tag_query_result {
+                Ok(tag_query) => tag_query,
                 Err(error) => {
-                    let _ = tx.send(Err(CrudError::BoolExprParse(error))).await;
+                    let _ = tx.send(Err(CrudError::TagQueryParse(error))).await;
                     return;
                 }
             };
             let result = async {
                 let mut transaction = shared_config.read().await.db_pool.begin().await?;
-                T::fetch_by_partial_name_and_bool_tag_expr(
+                T::fetch_by_partial_name_and_tag_query(
                     &mut transaction,
                     Limit(SEARCH_LIMIT),
                     &partial_name,
-                    bool_tag_expr,
+                    &tag_query,
                 )
                 .await
             }
@@ -359,8 +695,43 @@ where
 impl<T> SearchPartialNameAndBoolTagExpr<T>
 where
     T: FetchByPartialNameAndBoolTagExpr + IsReducedCollection + Clone + Default + 'static,
-    <T as IsReducedCollection>::Item: Clone,
+    <T as IsReducedCollection>::Item: Clone + CopyToClipboard + ToDragPayload,
 {
+    /// Move the highlighted search result, or return the highlighted search
+    /// result if it is to be viewed/edited
+    fn navigate(
+        &mut self,
+        navigation: KeyboardNavigation,
+    ) -> Option<SearchResultButtonClicked<<T as IsReducedCollection>::Item>> {
+        let count = self.search_results.collection().len();
+        match navigation {
+            KeyboardNavigation::Down => {
+                if count > 0 {
+                    self.highlighted = Some(self.highlighted.map_or(0, |i| (i + 1).min(count - 1)));
+                    self.scroll_to_highlighted = true;
+                }
+                None
+            }
+            KeyboardNavigation::Up => {
+                self.highlighted = self.highlighted.map(|i| i.saturating_sub(1));
+                self.scroll_to_highlighted = true;
+                None
+            }
+            KeyboardNavigation::View | KeyboardNavigation::Edit => {
+                let item = self
+                    .search_results
+                    .collection()
+                    .iter()
+                    .nth(self.highlighted?)?
+                    .clone();
+                match navigation {
+                    KeyboardNavigation::Edit => Some(SearchResultButtonClicked::Edit(item)),
+                    _ => Some(SearchResultButtonClicked::View(item)),
+                }
+            }
+        }
+    }
+
     // TODO: show matches count?
     // TODO: impl Draw?
     /// Draw search results to a table
@@ -368,6 +739,7 @@ where
         &mut self,
         _ctx: &Context,
         ui: &mut Ui,
+        starred: &BTreeSet<OpenTimelineId>,
     ) -> Option<SearchResultButtonClicked<<T as IsReducedCollection>::Item>> {
         ui.horizontal(|ui| {
             open_timeline_gui_core::Label::strong(ui, "Search By");
@@ -406,13 +778,20 @@ where
                 // Sizes
                 let row_height = body_text_height(ui);
                 let spacing = widget_x_spacing(ui);
-                let text_width =
-                    available_width - VIEW_BUTTON_WIDTH - EDIT_BUTTON_WIDTH - (2.0 * spacing);
+                let text_width = available_width
+                    - SELECT_CHECKBOX_WIDTH
+                    - EDIT_BUTTON_WIDTH
+                    - EDIT_BUTTON_WIDTH
+                    - VIEW_BUTTON_WIDTH
+                    - (4.0 * spacing);
                 let text_width = text_width.max(0.0);
 
                 // Which to view or edit if there is one
                 let mut to_view_or_edit = None;
 
+                // Only scroll to the highlighted result when it has just moved
+                let scroll_to_highlighted = std::mem::take(&mut self.scroll_to_highlighted);
+
                 // Show the results in a table in a scrollable area
                 ScrollArea::vertical()
                     .max_height(table_height)
@@ -422,8 +801,10 @@ where
                         TableBuilder::new(ui)
                             .id_salt(format!("{}_table", self.gui_component_id_source))
                             .striped(true)
+                            .column(Column::exact(SELECT_CHECKBOX_WIDTH))
                             .column(Column::exact(text_width).clip(true))
                             .column(Column::exact(EDIT_BUTTON_WIDTH))
+                            .column(Column::exact(EDIT_BUTTON_WIDTH))
                             .column(Column::exact(VIEW_BUTTON_WIDTH))
                             .body(|mut body| {
                                 for (index, reduced_entity) in
@@ -432,16 +813,69 @@ where
                                     if index as u32 > SEARCH_LIMIT {
                                         break;
                                     }
+                                    let highlighted = self.highlighted == Some(index);
                                     body.row(row_height, |mut row| {
+                                        row.set_selected(highlighted);
+                                        // Select
+                                        row.col(|ui| {
+                                            let id = reduced_entity.id();
+                                            let mut selected = self.selected.contains(&id);
+                                            if ui.checkbox(&mut selected, "").changed() {
+                                                match selected {
+                                                    true => self.selected.insert(id),
+                                                    false => self.selected.remove(&id),
+                                                };
+                                            }
+                                        });
                                         // Name
                                         row.col(|ui| {
                                             let layout = Layout::left_to_right(Align::Center);
                                             let label =
                                                 egui::Label::new(reduced_entity.name().as_str());
                                             ui.with_layout(layout, |ui| {
-                                                ui.add(label.truncate());
+                                                // Entities can be dragged onto timelines
+                                                let response =
+                                                    match reduced_entity.to_drag_payload() {
+                                                        Some(payload) => {
+                                                            let id = Id::new((
+                                                                self.gui_component_id_source,
+                                                                "drag",
+                                                                reduced_entity.id(),
+                                                            ));
+                                                            ui.dnd_drag_source(id, payload, |ui| {
+                                                                ui.add(label.truncate())
+                                                            })
+                                                            .inner
+                                                        }
+                                                        None => ui.add(label.truncate()),
+                                                    };
+                                                if highlighted && scroll_to_highlighted {
+                                                    response.scroll_to_me(None);
+                                                }
+                                                if reduced_entity.can_copy_to_clipboard() {
+                                                    response.context_menu(|ui| {
+                                                        if ui.button("Copy JSON").clicked() {
+                                                            to_view_or_edit = Some(
+                                                                SearchResultButtonClicked::Copy(
+                                                                    reduced_entity.clone(),
+                                                                ),
+                                                            );
+                                                        }
+                                                    });
+                                                }
                                             });
                                         });
+                                        // Star
+                                        row.col(|ui| {
+                                            let is_starred = starred.contains(&reduced_entity.id());
+                                            if OpenTimelineButton::star(ui, is_starred).clicked() {
+                                                to_view_or_edit =
+                                                    Some(SearchResultButtonClicked::Star(
+                                                        reduced_entity.clone(),
+                                                        !is_starred,
+                                                    ));
+                                            }
+                                        });
                                         // Edit
                                         row.col(|ui| {
                                             if OpenTimelineButton::edit(ui).clicked() {
@@ -470,10 +904,29 @@ where
     }
 }
 
-/// Used to indicate whether the edit or the view button was clicked.
+/// The search results columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchColumn {
+    Timelines,
+    Entities,
+}
+
+/// Keyboard input for moving through & opening search results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyboardNavigation {
+    Down,
+    Up,
+    View,
+    Edit,
+}
+
+/// Used to indicate whether the edit, the view, or the star button was
+/// clicked.  The star variant holds whether the item should now be starred.
 pub enum SearchResultButtonClicked<T> {
     View(T),
     Edit(T),
+    Star(T, bool),
+    Copy(T),
 }
 
 impl<T> Reload for SearchPartialNameAndBoolTagExpr<T>
@@ -498,7 +951,14 @@ where
                 debug!("Recv search results");
                 self.rx_search_results = None;
                 match data {
-                    Ok(results) => self.search_results = results,
+                    Ok(results) => {
+                        // Only keep the selected results that are still present
+                        self.selected.retain(|id| {
+                            results.collection().iter().any(|result| result.id() == *id)
+                        });
+                        self.highlighted = None;
+                        self.search_results = results;
+                    }
                     Err(_) => (),
                 }
             }
@@ -0,0 +1,465 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Bulk actions on a selection of search results
+//!
+
+use crate::activity_log::ACTIVITY;
+use crate::common::ToOpenTimelineType;
+use crate::components::TagGui;
+use crate::config::SharedConfig;
+use crate::spawn_operation;
+use crate::spawn_transaction_no_commit_send_result;
+use bool_tag_expr::Tag;
+use eframe::egui::{Button, ComboBox, Context, DragValue, ScrollArea, Ui};
+use open_timeline_core::{
+    DateShift, IsReducedCollection, IsReducedType, OpenTimelineId, ReducedTimelines,
+};
+use open_timeline_crud::{
+    CrudError, FavouriteKind, FetchAll, ShiftedEntityDates, add_entities_to_timeline,
+    add_tag_to_entities, delete_entities, delete_timelines, preview_entity_date_shift,
+    remove_tag_from_entities, shift_entity_dates,
+};
+use open_timeline_gui_core::{Draw, Reload, ShowRemoveButton, Valid, ValidityAsynchronous};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
+
+/// An action to run on all of the selected search results
+#[derive(Debug, Clone)]
+enum BulkAction {
+    AddEntitiesToTimeline(OpenTimelineId),
+    AddTagToEntities(Tag),
+    RemoveTagFromEntities(Tag),
+    ShiftEntityDates(DateShift),
+    DeleteEntities,
+    DeleteTimelines,
+}
+
+impl BulkAction {
+    /// Describe the (successfully run) action for the user
+    fn done_msg(&self, count: usize) -> String {
+        match self {
+            Self::AddEntitiesToTimeline(_) => format!("Added {count} entities to the timeline"),
+            Self::AddTagToEntities(tag) => format!("Added '{tag}' to {count} entities"),
+            Self::RemoveTagFromEntities(tag) => format!("Removed '{tag}' from {count} entities"),
+            Self::ShiftEntityDates(_) => format!("Shifted the dates of {count} entities"),
+            Self::DeleteEntities => format!("Deleted {count} entities"),
+            Self::DeleteTimelines => format!("Deleted {count} timelines"),
+        }
+    }
+}
+
+/// What shifting the dates of the selected entities would do
+#[derive(Debug)]
+struct DateShiftPreview {
+    /// The shift the preview is for
+    shift: DateShift,
+
+    /// The entities the preview is for
+    ids: BTreeSet<OpenTimelineId>,
+
+    /// The entities' dates before and after the shift
+    shifted: Vec<ShiftedEntityDates>,
+}
+
+/// The bulk actions shown above a column of search results when some of them
+/// are selected
+#[derive(Debug)]
+pub struct BulkActionsGui {
+    /// Whether the selected results are entities or timelines
+    kind: FavouriteKind,
+
+    /// The timelines the selected entities can be added to
+    timelines: ReducedTimelines,
+
+    /// Receive the timelines the selected entities can be added to
+    rx_timelines: Option<Receiver<Result<ReducedTimelines, CrudError>>>,
+
+    /// The timeline to add the selected entities to (if one has been chosen)
+    timeline_id: Option<OpenTimelineId>,
+
+    /// The tag to add to/remove from the selected entities
+    tag_gui: TagGui,
+
+    /// How far to shift the dates of the selected entities
+    date_shift: DateShift,
+
+    /// What shifting the dates of the selected entities would do (the dates
+    /// can only be shifted once it has been previewed)
+    date_shift_preview: Option<DateShiftPreview>,
+
+    /// Receive the preview of shifting the dates of the selected entities
+    rx_date_shift_preview: Option<Receiver<Result<DateShiftPreview, CrudError>>>,
+
+    /// Whether the user has clicked delete once (and is being asked to confirm)
+    confirm_delete: bool,
+
+    /// The outcome of the last action (for the user)
+    status: Option<String>,
+
+    /// The action being run and the number of results it is being run on
+    running: Option<(BulkAction, usize)>,
+
+    /// Receive the result of the action being run
+    rx_result: Option<Receiver<Result<(), CrudError>>>,
+
+    /// Used to indirectly inform the rest of the application that a CRUD
+    /// operation has been executed
+    tx_crud_operation_executed: UnboundedSender<()>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl BulkActionsGui {
+    /// Create new `BulkActionsGui` for entities or timelines
+    pub fn new(
+        shared_config: SharedConfig,
+        tx_crud_operation_executed: UnboundedSender<()>,
+        kind: FavouriteKind,
+    ) -> Self {
+        Self {
+            kind,
+            timelines: ReducedTimelines::default(),
+            rx_timelines: None,
+            timeline_id: None,
+            tag_gui: TagGui::new(ShowRemoveButton::No, None),
+            date_shift: DateShift::default(),
+            date_shift_preview: None,
+            rx_date_shift_preview: None,
+            confirm_delete: false,
+            status: None,
+            running: None,
+            rx_result: None,
+            tx_crud_operation_executed,
+            shared_config,
+        }
+    }
+
+    /// Run an action on all of the selected results (committing if successful)
+    fn run(&mut self, action: BulkAction, ids: BTreeSet<OpenTimelineId>) {
+        self.confirm_delete = false;
+        self.status = None;
+        self.running = Some((action.clone(), ids.len()));
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_result = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                match action {
+                    BulkAction::AddEntitiesToTimeline(timeline_id) => {
+                        add_entities_to_timeline(&mut transaction, &timeline_id, &ids).await?
+                    }
+                    BulkAction::AddTagToEntities(tag) => {
+                        add_tag_to_entities(&mut transaction, &ids, &tag).await?
+                    }
+                    BulkAction::RemoveTagFromEntities(tag) => {
+                        remove_tag_from_entities(&mut transaction, &ids, &tag).await?
+                    }
+                    BulkAction::ShiftEntityDates(shift) => {
+                        shift_entity_dates(&mut transaction, &ids, &shift).await?;
+                    }
+                    BulkAction::DeleteEntities => delete_entities(&mut transaction, &ids).await?,
+                    BulkAction::DeleteTimelines => delete_timelines(&mut transaction, &ids).await?,
+                }
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(())
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Work out (without changing anything) what shifting the dates of the
+    /// selected entities would do
+    fn request_date_shift_preview(&mut self, ids: BTreeSet<OpenTimelineId>) {
+        self.status = None;
+        let shift = self.date_shift;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_date_shift_preview = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move {
+                let shifted = preview_entity_date_shift(transaction, &ids, &shift).await?;
+                Ok(DateShiftPreview {
+                    shift,
+                    ids,
+                    shifted,
+                })
+            }
+        );
+    }
+
+    /// Receive the preview of shifting the dates of the selected entities
+    fn check_for_date_shift_preview(&mut self) {
+        if let Some(rx) = self.rx_date_shift_preview.as_mut()
+            && let Ok(result) = rx.try_recv()
+        {
+            debug!("Recv search results date shift preview");
+            self.rx_date_shift_preview = None;
+            match result {
+                Ok(preview) => self.date_shift_preview = Some(preview),
+                Err(error) => self.status = Some(format!("Failed: {error}")),
+            }
+        }
+    }
+
+    /// Receive the result of the action being run.  Returns `true` if an
+    /// action has just completed successfully (and so the selection should be
+    /// cleared).
+    pub fn check_for_crud_status_updates(&mut self) -> bool {
+        self.check_for_date_shift_preview();
+        let Some(rx) = self.rx_result.as_mut() else {
+            return false;
+        };
+        let Ok(result) = rx.try_recv() else {
+            return false;
+        };
+        debug!("Recv search results bulk action response");
+        self.rx_result = None;
+        let Some((action, count)) = self.running.take() else {
+            return false;
+        };
+        match result {
+            Ok(()) => {
+                let done_msg = action.done_msg(count);
+                info!(target: ACTIVITY, "{done_msg}");
+                self.status = Some(done_msg);
+                self.date_shift_preview = None;
+                let _ = self.tx_crud_operation_executed.send(());
+                true
+            }
+            Err(error) => {
+                warn!(target: ACTIVITY, "Bulk action failed: {error}");
+                self.status = Some(format!("Failed: {error}"));
+                false
+            }
+        }
+    }
+
+    /// Whether an action is being run or the timelines are being fetched
+    pub fn waiting_for_updates(&self) -> bool {
+        self.rx_result.is_some()
+            || self.rx_timelines.is_some()
+            || self.rx_date_shift_preview.is_some()
+    }
+
+    /// Draw the bulk actions for the selected results
+    pub fn draw(&mut self, ctx: &Context, ui: &mut Ui, selected: &mut BTreeSet<OpenTimelineId>) {
+        if selected.is_empty() {
+            if let Some(status) = &self.status {
+                open_timeline_gui_core::Label::weak(ui, status);
+            }
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            open_timeline_gui_core::Label::strong(ui, &format!("{} selected", selected.len()));
+            if ui.button("Clear").clicked() {
+                selected.clear();
+                self.confirm_delete = false;
+            }
+        });
+
+        ui.add_enabled_ui(self.rx_result.is_none(), |ui| {
+            if self.kind == FavouriteKind::Entity {
+                self.draw_entity_actions(ctx, ui, selected);
+            }
+            self.draw_delete(ui, selected);
+        });
+
+        if let Some(status) = &self.status {
+            open_timeline_gui_core::Label::weak(ui, status);
+        }
+    }
+
+    /// Draw the actions that only apply to entities
+    fn draw_entity_actions(
+        &mut self,
+        ctx: &Context,
+        ui: &mut Ui,
+        selected: &BTreeSet<OpenTimelineId>,
+    ) {
+        // Add to a timeline
+        ui.horizontal(|ui| {
+            let selected_text = self
+                .timeline_id
+                .and_then(|id| {
+                    self.timelines
+                        .collection()
+                        .iter()
+                        .find(|timeline| timeline.id() == id)
+                })
+                .map(|timeline| timeline.name().to_string())
+                .unwrap_or_else(|| String::from("Timeline"));
+            ComboBox::from_id_salt(("bulk_add_to_timeline", ui.id()))
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for timeline in self.timelines.collection() {
+                        ui.selectable_value(
+                            &mut self.timeline_id,
+                            Some(timeline.id()),
+                            timeline.name().as_str(),
+                        );
+                    }
+                });
+            let button = ui.add_enabled(self.timeline_id.is_some(), Button::new("Add to Timeline"));
+            if button.clicked()
+                && let Some(timeline_id) = self.timeline_id
+            {
+                self.run(
+                    BulkAction::AddEntitiesToTimeline(timeline_id),
+                    selected.clone(),
+                );
+            }
+        });
+
+        // Add/remove a tag
+        self.tag_gui.draw(ctx, ui);
+        ui.horizontal(|ui| {
+            let valid = self.tag_gui.validity() == ValidityAsynchronous::Valid;
+            if ui.add_enabled(valid, Button::new("Add Tag")).clicked() {
+                let tag = self.tag_gui.to_opentimeline_type();
+                self.run(BulkAction::AddTagToEntities(tag), selected.clone());
+            }
+            if ui.add_enabled(valid, Button::new("Remove Tag")).clicked() {
+                let tag = self.tag_gui.to_opentimeline_type();
+                self.run(BulkAction::RemoveTagFromEntities(tag), selected.clone());
+            }
+        });
+
+        self.draw_date_shift(ui, selected);
+    }
+
+    /// Draw the date shift, which must be previewed before it can be run
+    fn draw_date_shift(&mut self, ui: &mut Ui, selected: &BTreeSet<OpenTimelineId>) {
+        ui.horizontal(|ui| {
+            ui.label("Shift dates by");
+            ui.add(DragValue::new(&mut self.date_shift.years).suffix(" y"));
+            ui.add(DragValue::new(&mut self.date_shift.months).suffix(" m"));
+            ui.add(DragValue::new(&mut self.date_shift.days).suffix(" d"));
+        });
+
+        // Forget the preview if it's no longer for what would be run
+        if self
+            .date_shift_preview
+            .as_ref()
+            .is_some_and(|preview| preview.shift != self.date_shift || preview.ids != *selected)
+        {
+            self.date_shift_preview = None;
+        }
+
+        ui.horizontal(|ui| {
+            let can_preview = !self.date_shift.is_zero() && self.rx_date_shift_preview.is_none();
+            if ui
+                .add_enabled(can_preview, Button::new("Preview"))
+                .clicked()
+            {
+                self.request_date_shift_preview(selected.clone());
+            }
+            let previewed = self.date_shift_preview.is_some();
+            if ui
+                .add_enabled(previewed, Button::new("Shift Dates"))
+                .clicked()
+            {
+                self.run(
+                    BulkAction::ShiftEntityDates(self.date_shift),
+                    selected.clone(),
+                );
+            }
+        });
+
+        let Some(preview) = &self.date_shift_preview else {
+            return;
+        };
+        let date_format = self.shared_config.blocking_read().config.date_format;
+        ScrollArea::vertical()
+            .id_salt(("bulk_date_shift_preview", ui.id()))
+            .max_height(150.0)
+            .show(ui, |ui| {
+                for shifted in &preview.shifted {
+                    let before =
+                        date_format.format_date_range(&shifted.start, shifted.end.as_ref());
+                    let after =
+                        date_format.format_date_range(&shifted.new_start, shifted.new_end.as_ref());
+                    open_timeline_gui_core::Label::weak(
+                        ui,
+                        &format!("{}: {before} → {after}", shifted.name),
+                    );
+                }
+            });
+    }
+
+    /// Draw the delete button (which must be clicked twice)
+    fn draw_delete(&mut self, ui: &mut Ui, selected: &BTreeSet<OpenTimelineId>) {
+        let action = match self.kind {
+            FavouriteKind::Entity => BulkAction::DeleteEntities,
+            FavouriteKind::Timeline => BulkAction::DeleteTimelines,
+        };
+        if !self.confirm_delete {
+            if open_timeline_gui_core::Button::delete(ui).clicked() {
+                self.confirm_delete = true;
+            }
+            return;
+        }
+        ui.horizontal(|ui| {
+            open_timeline_gui_core::Label::strong(
+                ui,
+                &format!("Delete {} (this can't be undone)?", selected.len()),
+            );
+            if ui.button("Confirm").clicked() {
+                self.run(action, selected.clone());
+            }
+            if ui.button("Cancel").clicked() {
+                self.confirm_delete = false;
+            }
+        });
+    }
+}
+
+impl Reload for BulkActionsGui {
+    fn request_reload(&mut self) {
+        // Only entities can be added to timelines
+        if self.kind != FavouriteKind::Entity {
+            return;
+        }
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_timelines = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { ReducedTimelines::fetch_all(transaction).await }
+        );
+    }
+
+    fn check_reload_response(&mut self) {
+        if let Some(rx) = self.rx_timelines.as_mut()
+            && let Ok(result) = rx.try_recv()
+        {
+            self.rx_timelines = None;
+            match result {
+                Ok(timelines) => {
+                    // Forget the chosen timeline if it has been deleted
+                    if !self.timeline_id.is_some_and(|id| {
+                        timelines
+                            .collection()
+                            .iter()
+                            .any(|timeline| timeline.id() == id)
+                    }) {
+                        self.timeline_id = None;
+                    }
+                    self.timelines = timelines;
+                }
+                Err(error) => warn!("Error fetching timelines: {error}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The entities that started or ended on a day of the year
+//!
+
+use crate::app::{ActionRequest, EntityOrTimelineActionRequest};
+use crate::config::SharedConfig;
+use crate::spawn_transaction_no_commit_send_result;
+use chrono::{Datelike, Local};
+use eframe::egui::{CollapsingHeader, DragValue, Ui};
+use open_timeline_core::{Date, Entity, HasIdAndName, OpenTimelineId, Year};
+use open_timeline_crud::{CrudError, OnThisDay};
+use open_timeline_gui_core::Reload;
+use std::sync::Arc;
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
+
+/// The maximum number of entities listed under each of started & ended
+const ON_THIS_DAY_LIMIT: usize = 10;
+
+/// Shows the entities that started or ended on a day of the year (today by
+/// default)
+#[derive(Debug)]
+pub struct OnThisDayGui {
+    /// The month (1 to 12)
+    month: u8,
+
+    /// The day of the month
+    day: u8,
+
+    /// The entities that started or ended on the day
+    on_this_day: Option<OnThisDay>,
+
+    /// Receive the entities that started or ended on the day
+    rx_on_this_day: Option<Receiver<Result<OnThisDay, CrudError>>>,
+
+    /// Used to request windows for viewing entities
+    tx_action_request: UnboundedSender<ActionRequest>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl OnThisDayGui {
+    /// Create a new "on this day" GUI for today
+    pub fn new(
+        shared_config: SharedConfig,
+        tx_action_request: UnboundedSender<ActionRequest>,
+    ) -> Self {
+        let today = Local::now();
+        Self {
+            month: today.month() as u8,
+            day: today.day() as u8,
+            on_this_day: None,
+            rx_on_this_day: None,
+            tx_action_request,
+            shared_config,
+        }
+    }
+
+    /// Whether the "on this day" entities are being fetched
+    pub fn waiting_for_updates(&self) -> bool {
+        self.rx_on_this_day.is_some()
+    }
+
+    /// Draw the day picker & the entities that started or ended on the day
+    pub fn draw(&mut self, ui: &mut Ui) {
+        CollapsingHeader::new("On This Day")
+            .id_salt("search_on_this_day")
+            .default_open(true)
+            .show(ui, |ui| {
+                let changed = ui
+                    .horizontal(|ui| {
+                        let day =
+                            ui.add(DragValue::new(&mut self.day).range(1..=31).prefix("Day "));
+                        let month = ui.add(
+                            DragValue::new(&mut self.month)
+                                .range(1..=12)
+                                .prefix("Month "),
+                        );
+                        day.changed() || month.changed()
+                    })
+                    .inner;
+                if changed {
+                    self.request_reload();
+                }
+
+                // Days that don't exist (e.g. 31st of April)
+                if Date::from(Some(self.day.into()), Some(self.month.into()), 2000).is_err() {
+                    open_timeline_gui_core::Label::weak(ui, "No such day");
+                    return;
+                }
+                let Some(on_this_day) = self.on_this_day.as_ref() else {
+                    ui.spinner();
+                    return;
+                };
+                if on_this_day.is_empty() {
+                    open_timeline_gui_core::Label::none(ui);
+                    return;
+                }
+                let mut view = None;
+                ui.columns(2, |columns| {
+                    let started = &on_this_day.started;
+                    let ended = &on_this_day.ended;
+                    view = view.or(draw_entities(
+                        &mut columns[0],
+                        "Started",
+                        started,
+                        |entity| Some(entity.start().year()),
+                    ));
+                    view = view.or(draw_entities(&mut columns[1], "Ended", ended, |entity| {
+                        entity.end().map(|end| end.year())
+                    }));
+                });
+                if let Some(entity_id) = view {
+                    let _ = self.tx_action_request.send(ActionRequest::Entity(
+                        EntityOrTimelineActionRequest::ViewExisting(entity_id),
+                    ));
+                }
+            });
+    }
+}
+
+impl Reload for OnThisDayGui {
+    fn request_reload(&mut self) {
+        self.on_this_day = None;
+        self.rx_on_this_day = None;
+        if Date::from(Some(self.day.into()), Some(self.month.into()), 2000).is_err() {
+            return;
+        }
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_on_this_day = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        let (month, day) = (self.month, self.day);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { OnThisDay::fetch(transaction, month, day).await }
+        );
+    }
+
+    fn check_reload_response(&mut self) {
+        if let Some(rx) = self.rx_on_this_day.as_mut()
+            && let Ok(result) = rx.try_recv()
+        {
+            self.rx_on_this_day = None;
+            match result {
+                Ok(on_this_day) => self.on_this_day = Some(on_this_day),
+                Err(error) => warn!("Error fetching on this day entities: {error}"),
+            }
+        }
+    }
+}
+
+/// List (some of) the entities, returning the ID of one if the user wants to
+/// view it
+fn draw_entities(
+    ui: &mut Ui,
+    heading: &str,
+    entities: &[Entity],
+    year: fn(&Entity) -> Option<Year>,
+) -> Option<OpenTimelineId> {
+    let mut view = None;
+    ui.strong(format!("{heading} ({})", entities.len()));
+    for entity in entities.iter().take(ON_THIS_DAY_LIMIT) {
+        let label = match year(entity) {
+            Some(year) => format!("{year}: {}", entity.name()),
+            None => entity.name().to_string(),
+        };
+        if ui.link(label).clicked() {
+            view = entity.id();
+        }
+    }
+    if entities.len() > ON_THIS_DAY_LIMIT {
+        ui.weak(format!("and {} more", entities.len() - ON_THIS_DAY_LIMIT));
+    }
+    view
+}
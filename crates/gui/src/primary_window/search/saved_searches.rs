@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Saved searches (re-run with one click, showing how many matches have been
+//! added or changed since they were last run)
+//!
+
+use crate::config::SharedConfig;
+use crate::spawn_operation;
+use crate::spawn_transaction_no_commit_send_result;
+use eframe::egui::{TextEdit, Ui};
+use open_timeline_core::Name;
+use open_timeline_crud::{
+    CrudError, SavedSearch, SavedSearchKind, delete_saved_search, fetch_saved_searches,
+    record_saved_search_run, save_search,
+};
+use open_timeline_gui_core::Reload;
+use sqlx::{Sqlite, Transaction};
+use std::sync::Arc;
+use tokio::sync::mpsc::Receiver;
+
+/// The saved searches of one kind, & the number of new matches of each
+type SavedSearchesWithNewMatches = Vec<(SavedSearch, usize)>;
+
+/// Lists the saved searches of one kind (entity or timeline), & saves the
+/// current search
+#[derive(Debug)]
+pub struct SavedSearchesGui {
+    /// Whether these are entity or timeline searches
+    kind: SavedSearchKind,
+
+    /// The saved searches & the number of new matches of each
+    saved_searches: SavedSearchesWithNewMatches,
+
+    /// The name to save the current search as
+    new_name: String,
+
+    /// Receive the saved searches
+    rx_saved_searches: Option<Receiver<Result<SavedSearchesWithNewMatches, CrudError>>>,
+
+    /// Receive the result of saving, running, or deleting a saved search
+    rx_changed: Option<Receiver<Result<(), CrudError>>>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl SavedSearchesGui {
+    /// Create a new saved searches GUI for the kind of search
+    pub fn new(shared_config: SharedConfig, kind: SavedSearchKind) -> Self {
+        let mut saved_searches = Self {
+            kind,
+            saved_searches: Vec::new(),
+            new_name: String::new(),
+            rx_saved_searches: None,
+            rx_changed: None,
+            shared_config,
+        };
+        saved_searches.request_reload();
+        saved_searches
+    }
+
+    /// Whether the saved searches are being fetched or changed
+    pub fn waiting_for_updates(&self) -> bool {
+        self.rx_saved_searches.is_some() || self.rx_changed.is_some()
+    }
+
+    /// Draw the saved searches & the input for saving the current search.
+    /// Returns the saved search to run if one was clicked.
+    pub fn draw(
+        &mut self,
+        ui: &mut Ui,
+        partial_name: &str,
+        bool_tag_expr: &str,
+    ) -> Option<SavedSearch> {
+        let mut to_run = None;
+        let mut to_delete = None;
+        if !self.saved_searches.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                for (saved_search, new_matches) in &self.saved_searches {
+                    let text = match new_matches {
+                        0 => saved_search.name().to_string(),
+                        new_matches => format!("{} ({new_matches} new)", saved_search.name()),
+                    };
+                    let response = ui.button(text).on_hover_text(describe(saved_search));
+                    if response.clicked() {
+                        to_run = Some(saved_search.clone());
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button("Delete").clicked() {
+                            to_delete = Some(saved_search.name().clone());
+                        }
+                    });
+                }
+            });
+            ui.add_space(5.0);
+        }
+
+        // Save the current search
+        ui.horizontal(|ui| {
+            let name = Name::from(self.new_name.trim());
+            let has_terms = !partial_name.trim().is_empty() || !bool_tag_expr.trim().is_empty();
+            let button_width = 50.0;
+            ui.add(
+                TextEdit::singleline(&mut self.new_name)
+                    .desired_width(ui.available_width() - button_width)
+                    .hint_text("Save search as"),
+            );
+            ui.add_enabled_ui(name.is_ok() && has_terms, |ui| {
+                if ui.button("Save").clicked()
+                    && let Ok(name) = name
+                {
+                    let saved_search = SavedSearch::new(
+                        name,
+                        self.kind,
+                        Some(partial_name.to_string()),
+                        Some(bool_tag_expr.to_string()),
+                    );
+                    self.new_name.clear();
+                    self.request_change(Change::Save(saved_search));
+                }
+            });
+        });
+
+        if let Some(saved_search) = &to_run {
+            // It has now been run, so there are no new matches
+            let name = saved_search.name().clone();
+            for (saved_search, new_matches) in &mut self.saved_searches {
+                if saved_search.name() == &name {
+                    *new_matches = 0;
+                }
+            }
+            self.request_change(Change::Run(name));
+        }
+        if let Some(name) = to_delete {
+            self.saved_searches
+                .retain(|(saved_search, _)| saved_search.name() != &name);
+            self.request_change(Change::Delete(name));
+        }
+        to_run
+    }
+
+    /// Save, run, or delete a saved search (then reload the saved searches)
+    fn request_change(&mut self, change: Change) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_changed = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        let kind = self.kind;
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                match change {
+                    Change::Save(saved_search) => {
+                        save_search(&mut transaction, &saved_search).await?
+                    }
+                    Change::Run(name) => {
+                        record_saved_search_run(&mut transaction, kind, &name).await?
+                    }
+                    Change::Delete(name) => {
+                        delete_saved_search(&mut transaction, kind, &name).await?
+                    }
+                }
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(())
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+}
+
+/// A change to the saved searches
+#[derive(Debug)]
+enum Change {
+    Save(SavedSearch),
+    Run(Name),
+    Delete(Name),
+}
+
+/// Describe what a saved search searches by
+fn describe(saved_search: &SavedSearch) -> String {
+    let terms: Vec<String> = [
+        saved_search
+            .partial_name()
+            .map(|partial_name| format!("Name: {partial_name}")),
+        saved_search
+            .bool_tag_expr()
+            .map(|bool_tag_expr| format!("Tags: {bool_tag_expr}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    terms.join("\n")
+}
+
+/// Fetch the saved searches of the kind, & count the new matches of each
+async fn fetch_saved_searches_with_new_matches(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SavedSearchKind,
+) -> Result<SavedSearchesWithNewMatches, CrudError> {
+    let mut saved_searches = Vec::new();
+    for saved_search in fetch_saved_searches(transaction).await? {
+        if saved_search.kind() == kind {
+            let new_matches = saved_search.fetch_new_match_count(transaction).await?;
+            saved_searches.push((saved_search, new_matches));
+        }
+    }
+    Ok(saved_searches)
+}
+
+impl Reload for SavedSearchesGui {
+    fn request_reload(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_saved_searches = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        let kind = self.kind;
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_saved_searches_with_new_matches(transaction, kind).await }
+        );
+    }
+
+    fn check_reload_response(&mut self) {
+        if let Some(rx) = self.rx_changed.as_mut()
+            && let Ok(result) = rx.try_recv()
+        {
+            self.rx_changed = None;
+            match result {
+                Ok(()) => self.request_reload(),
+                Err(error) => warn!("Error changing saved searches: {error}"),
+            }
+        }
+        if let Some(rx) = self.rx_saved_searches.as_mut()
+            && let Ok(result) = rx.try_recv()
+        {
+            self.rx_saved_searches = None;
+            match result {
+                Ok(saved_searches) => self.saved_searches = saved_searches,
+                Err(error) => warn!("Error fetching saved searches: {error}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Recently used & pinned search queries, suggested under the search inputs
+//!
+
+use crate::config::SharedConfig;
+use crate::spawn_operation;
+use crate::spawn_transaction_no_commit_send_result;
+use eframe::egui::{Button, RichText, Ui};
+use open_timeline_crud::{
+    CrudError, SearchQuery, SearchQueryKind, fetch_search_history, record_search_query,
+    set_search_query_pinned,
+};
+use open_timeline_gui_core::Reload;
+use std::sync::Arc;
+use tokio::sync::mpsc::Receiver;
+
+/// The maximum number of queries suggested under each search input
+const SUGGESTION_LIMIT: usize = 5;
+
+/// The search history (shared by the entity & timeline searches)
+#[derive(Debug)]
+pub struct SearchHistoryGui {
+    /// Pinned queries first, then the most recently used
+    history: Vec<SearchQuery>,
+
+    /// Receive the search history
+    rx_history: Option<Receiver<Result<Vec<SearchQuery>, CrudError>>>,
+
+    /// Receive the result of recording or pinning a query
+    rx_changed: Option<Receiver<Result<(), CrudError>>>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+impl SearchHistoryGui {
+    /// Create a new search history GUI
+    pub fn new(shared_config: SharedConfig) -> Self {
+        let mut search_history = Self {
+            history: Vec::new(),
+            rx_history: None,
+            rx_changed: None,
+            shared_config,
+        };
+        search_history.request_reload();
+        search_history
+    }
+
+    /// Whether the search history is being fetched or changed
+    pub fn waiting_for_updates(&self) -> bool {
+        self.rx_history.is_some() || self.rx_changed.is_some()
+    }
+
+    /// Draw the queries of the kind that contain what has been typed so far
+    /// (but aren't it).  Returns the query to use if one was clicked.
+    pub fn draw_suggestions(
+        &mut self,
+        ui: &mut Ui,
+        kind: SearchQueryKind,
+        typed: &str,
+    ) -> Option<String> {
+        let typed = typed.trim().to_lowercase();
+        let suggestions: Vec<&SearchQuery> = self
+            .history
+            .iter()
+            .filter(|query| query.kind() == kind)
+            .filter(|query| {
+                let query = query.query().to_lowercase();
+                query != typed && query.contains(&typed)
+            })
+            .take(SUGGESTION_LIMIT)
+            .collect();
+        if suggestions.is_empty() {
+            return None;
+        }
+
+        let mut clicked = None;
+        let mut to_pin = None;
+        ui.horizontal_wrapped(|ui| {
+            for query in suggestions {
+                let text = match query.pinned() {
+                    true => format!("📌 {}", query.query()),
+                    false => query.query().to_string(),
+                };
+                let response = ui
+                    .add(Button::new(RichText::new(text).small()).small())
+                    .on_hover_text("Right click to pin or unpin");
+                if response.clicked() {
+                    clicked = Some(query.query().to_string());
+                }
+                response.context_menu(|ui| {
+                    let text = match query.pinned() {
+                        true => "Unpin",
+                        false => "Pin",
+                    };
+                    if ui.button(text).clicked() {
+                        to_pin = Some((query.query().to_string(), !query.pinned()));
+                    }
+                });
+            }
+        });
+
+        if let Some((query, pinned)) = to_pin {
+            self.request_change(Change::Pin(kind, query, pinned));
+        }
+        if let Some(query) = &clicked {
+            self.record(kind, query);
+        }
+        clicked
+    }
+
+    /// Record that a query has been used
+    pub fn record(&mut self, kind: SearchQueryKind, query: &str) {
+        if !query.trim().is_empty() {
+            self.request_change(Change::Record(kind, query.to_string()));
+        }
+    }
+
+    /// Record or pin a query (then reload the search history)
+    fn request_change(&mut self, change: Change) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_changed = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                match change {
+                    Change::Record(kind, query) => {
+                        record_search_query(&mut transaction, kind, &query).await?
+                    }
+                    Change::Pin(kind, query, pinned) => {
+                        set_search_query_pinned(&mut transaction, kind, &query, pinned).await?
+                    }
+                }
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(())
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+}
+
+/// A change to the search history
+#[derive(Debug)]
+enum Change {
+    Record(SearchQueryKind, String),
+    Pin(SearchQueryKind, String, bool),
+}
+
+impl Reload for SearchHistoryGui {
+    fn request_reload(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_history = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_search_history(transaction).await }
+        );
+    }
+
+    fn check_reload_response(&mut self) {
+        if let Some(rx) = self.rx_changed.as_mut()
+            && let Ok(result) = rx.try_recv()
+        {
+            self.rx_changed = None;
+            match result {
+                Ok(()) => self.request_reload(),
+                Err(error) => warn!("Error changing the search history: {error}"),
+            }
+        }
+        if let Some(rx) = self.rx_history.as_mut()
+            && let Ok(result) = rx.try_recv()
+        {
+            self.rx_history = None;
+            match result {
+                Ok(history) => self.history = history,
+                Err(error) => warn!("Error fetching the search history: {error}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Two-way syncing of the database with a remote OpenTimeline web API
+//!
+
+use crate::activity_log::ACTIVITY;
+use crate::config::{SharedConfig, SyncConfig};
+use crate::spawn_operation;
+use eframe::egui::{self, Context, Grid, Response, Spinner, TextEdit, Ui};
+use open_timeline_crud::{
+    SyncConflictResolution, SyncError, SyncItemKind, SyncRemote, SyncReport, sync,
+};
+use open_timeline_gui_core::{CheckForUpdates, Draw};
+use open_timeline_gui_core::{DisplayStatus, GuiStatus};
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{Receiver, error::TryRecvError};
+
+/// The sync GUI panel in the main window
+#[derive(Debug)]
+pub struct SyncGui {
+    /// The remote API to sync with (saved to the config when syncing)
+    sync_config: SyncConfig,
+
+    /// How entities & timelines changed on both sides are resolved
+    resolution: SyncConflictResolution,
+
+    /// Receive the result of the sync in progress (if any)
+    rx_sync: Option<Receiver<Result<SyncReport, SyncError>>>,
+
+    /// The status of the last sync (which may be none)
+    status: Status,
+
+    /// Used to indirectly inform the rest of the application that a CRUD
+    /// operation has been executed successfully (i.e. reloads may be required)
+    tx_crud_operation_executed: UnboundedSender<()>,
+
+    /// Database pool
+    shared_config: SharedConfig,
+}
+
+/// The possible states of operation for the panel
+#[derive(Debug)]
+enum Status {
+    /// Nothing has been requested while the programme has ben running
+    None,
+
+    /// The last sync succeeded
+    Success(SyncReport),
+
+    /// The last sync failed
+    Failure(SyncError),
+
+    /// A sync is in progress
+    InProgress,
+}
+
+impl DisplayStatus for Status {
+    fn status_display(&self, ui: &mut Ui) -> Response {
+        match &self {
+            Self::None => ui.add(egui::Label::new(String::from("Ready")).truncate()),
+            Self::Success(report) => ui.add(
+                egui::Label::new(format!(
+                    "Success: pulled {} entities & {} timelines, pushed {} entities & {} timelines",
+                    report.pulled_entities,
+                    report.pulled_timelines,
+                    report.pushed_entities,
+                    report.pushed_timelines
+                ))
+                .truncate(),
+            ),
+            Self::Failure(error) => ui.add(egui::Label::new(format!("Error: {error}")).truncate()),
+            Self::InProgress => ui.add(Spinner::new()),
+        }
+    }
+}
+
+impl SyncGui {
+    /// Create a new sync GUI panel manager
+    pub fn new(
+        shared_config: SharedConfig,
+        tx_crud_operation_executed: UnboundedSender<()>,
+        sync_config: SyncConfig,
+    ) -> Self {
+        Self {
+            sync_config,
+            resolution: SyncConflictResolution::default(),
+            rx_sync: None,
+            status: Status::None,
+            tx_crud_operation_executed,
+            shared_config,
+        }
+    }
+
+    /// Save the remote to the config, then sync with it (committing only if
+    /// the whole sync succeeds)
+    fn request_sync(&mut self) {
+        self.status = Status::InProgress;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_sync = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        let sync_config = self.sync_config.clone();
+        let resolution = self.resolution;
+        spawn_operation!(async move {
+            let config = {
+                let mut shared_config = shared_config.write().await;
+                shared_config.config.sync = sync_config.clone();
+                shared_config.config.clone()
+            };
+            if let Err(error) = config.save().await {
+                warn!("Error saving sync settings: {error}");
+            }
+            let result: Result<SyncReport, SyncError> = async {
+                let remote = SyncRemote::new(&sync_config.url, Some(sync_config.token));
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                let report = sync(&mut transaction, &remote, resolution).await?;
+                transaction.commit().await?;
+                Ok(report)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Check for the result of the sync in progress (if any)
+    fn check_sync_response(&mut self) {
+        if let Some(rx) = self.rx_sync.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv sync response");
+                    self.rx_sync = None;
+                    self.status = match result {
+                        Ok(report) => {
+                            info!(
+                                target: ACTIVITY,
+                                "Synced with {}: pulled {} entities & {} timelines, pushed {} entities & {} timelines",
+                                self.sync_config.url,
+                                report.pulled_entities,
+                                report.pulled_timelines,
+                                report.pushed_entities,
+                                report.pushed_timelines
+                            );
+                            let _ = self.tx_crud_operation_executed.send(());
+                            Status::Success(report)
+                        }
+                        Err(error) => {
+                            warn!(target: ACTIVITY, "Sync failed: {error}");
+                            Status::Failure(error)
+                        }
+                    };
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_sync = None,
+            }
+        }
+    }
+
+    /// Draw the remote's settings
+    fn draw_settings(&mut self, ui: &mut Ui) {
+        open_timeline_gui_core::Label::sub_heading(ui, "Remote");
+        let description = "The site serving the OpenTimeline web API to sync with, and the token to send to it (if it needs one)";
+        open_timeline_gui_core::Label::description(ui, description);
+        ui.add_space(5.0);
+
+        let syncing = self.rx_sync.is_some();
+        Grid::new("sync_settings").num_columns(2).show(ui, |ui| {
+            open_timeline_gui_core::Label::strong(ui, "URL");
+            let input =
+                TextEdit::singleline(&mut self.sync_config.url).desired_width(f32::INFINITY);
+            ui.add_enabled(!syncing, input);
+            ui.end_row();
+
+            open_timeline_gui_core::Label::strong(ui, "Token");
+            let input = TextEdit::singleline(&mut self.sync_config.token)
+                .password(true)
+                .desired_width(f32::INFINITY);
+            ui.add_enabled(!syncing, input);
+            ui.end_row();
+
+            open_timeline_gui_core::Label::strong(ui, "Conflicts");
+            ui.add_enabled_ui(!syncing, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.resolution, SyncConflictResolution::Skip, "Skip");
+                    ui.radio_value(
+                        &mut self.resolution,
+                        SyncConflictResolution::PreferLocal,
+                        "Keep local",
+                    );
+                    ui.radio_value(
+                        &mut self.resolution,
+                        SyncConflictResolution::PreferRemote,
+                        "Keep remote",
+                    );
+                });
+            });
+            ui.end_row();
+        });
+        ui.add_space(5.0);
+
+        let can_sync = !syncing && !self.sync_config.url.trim().is_empty();
+        ui.add_enabled_ui(can_sync, |ui| {
+            if open_timeline_gui_core::Button::tall_full_width(ui, "Sync Now").clicked() {
+                self.request_sync();
+            }
+        });
+    }
+
+    /// Draw the conflicts left by the last sync (if any)
+    fn draw_conflicts(&mut self, ui: &mut Ui) {
+        let Status::Success(report) = &self.status else {
+            return;
+        };
+        if report.conflicts.is_empty() {
+            return;
+        }
+        open_timeline_gui_core::Label::sub_heading(ui, "Conflicts");
+        let description = "Changed both here and on the remote since the last sync, so left as they are on both sides";
+        open_timeline_gui_core::Label::description(ui, description);
+        ui.add_space(5.0);
+        Grid::new("sync_conflicts").num_columns(2).show(ui, |ui| {
+            for conflict in &report.conflicts {
+                let kind = match conflict.kind {
+                    SyncItemKind::Entity => "Entity",
+                    SyncItemKind::Timeline => "Timeline",
+                };
+                open_timeline_gui_core::Label::strong(ui, kind);
+                ui.label(conflict.name.as_str());
+                ui.end_row();
+            }
+        });
+    }
+}
+
+impl Draw for SyncGui {
+    fn draw(&mut self, _ctx: &Context, ui: &mut Ui) {
+        // Status
+        GuiStatus::display(ui, &self.status);
+        ui.separator();
+
+        // Description
+        let description = "This panel syncs entities & timelines both ways with a remote OpenTimeline web API: what has changed on either side since the last sync is pulled & pushed.  Deletions aren't synced";
+        open_timeline_gui_core::Label::description(ui, description);
+        ui.separator();
+
+        // Settings
+        self.draw_settings(ui);
+        ui.add_space(15.0);
+
+        // Conflicts
+        self.draw_conflicts(ui);
+    }
+}
+
+impl CheckForUpdates for SyncGui {
+    fn check_for_updates(&mut self) {
+        self.check_sync_response();
+    }
+
+    fn waiting_for_updates(&mut self) -> bool {
+        let waiting = self.rx_sync.is_some();
+        if waiting {
+            info!("SyncGui is waiting for updates");
+        }
+        waiting
+    }
+}
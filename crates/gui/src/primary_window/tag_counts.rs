@@ -6,15 +6,20 @@
 
 use crate::{
     app::{ActionRequest, TagActionRequest},
+    common::fetch_cached_stat_crud,
     components::OpenTimelineButton,
     config::SharedConfig,
     consts::{EDIT_BUTTON_WIDTH, VIEW_BUTTON_WIDTH},
-    spawn_transaction_no_commit_send_result,
 };
-use eframe::egui::{self, Align, Context, Layout, ScrollArea, TextEdit, Ui, Vec2};
+use crate::{spawn_operation, spawn_transaction_no_commit_send_result};
+use eframe::egui::{
+    self, Align, CollapsingHeader, Context, Layout, ScrollArea, TextEdit, Ui, Vec2,
+};
 use egui_extras::{Column, TableBuilder};
+use open_timeline_core::TagHierarchy;
 use open_timeline_crud::{
-    CrudError, SortAlphabetically, SortByNumber, TagCounts, fetch_all_entity_tag_counts,
+    Cached, CrudError, EntityTagCountsStat, SortAlphabetically, SortByNumber, TagCounts, TagTree,
+    TagTreeNode, fetch_tag_hierarchy,
 };
 use open_timeline_gui_core::{
     CheckForUpdates, Draw, Paginator, Reload, body_text_height, widget_x_spacing, widget_y_spacing,
@@ -50,6 +55,16 @@ pub struct TagCountsGui {
     /// fields in the same struct.
     filtered_tag_counts: Option<TagCounts>,
 
+    /// The filtered tag counts arranged using the tag hierarchy (e.g.
+    /// `region=france` under its parent `region=europe`)
+    filtered_tag_tree: Option<TagTree>,
+
+    /// Which tags are the children of which
+    tag_hierarchy: TagHierarchy,
+
+    /// Whether to show the tags as a tree (rather than a table)
+    show_tree: bool,
+
     // TODO: combine into a single enum
     /// How the count column should be ordered (if at all)
     count_ordering: Option<SortByNumber>,
@@ -61,7 +76,10 @@ pub struct TagCountsGui {
     value_ordering: Option<SortAlphabetically>,
 
     /// Receive up-to-date `TagCounts` after a reload requested
-    rx_reload: Option<Receiver<Result<TagCounts, CrudError>>>,
+    rx_reload: Option<Receiver<Result<Cached<TagCounts>, CrudError>>>,
+
+    /// Receive the up-to-date `TagHierarchy` after a reload requested
+    rx_reload_hierarchy: Option<Receiver<Result<TagHierarchy, CrudError>>>,
 
     /// Whether a reload has been requested
     requested_reload: bool,
@@ -89,10 +107,14 @@ impl TagCountsGui {
         let mut tag_count_gui = Self {
             tag_counts: None,
             filtered_tag_counts: None,
+            filtered_tag_tree: None,
+            tag_hierarchy: TagHierarchy::new(),
+            show_tree: false,
             count_ordering: None,
             name_ordering: None,
             value_ordering: None,
             rx_reload: None,
+            rx_reload_hierarchy: None,
             requested_reload: false,
             tx_action_request,
             filter_text: String::new(),
@@ -310,8 +332,71 @@ impl TagCountsGui {
             .filtered_tag_counts
             .take()
             .filter(|filtered_tag_counts| !filtered_tag_counts.is_empty());
+        self.filtered_tag_tree = self
+            .filtered_tag_counts
+            .as_ref()
+            .map(|tag_counts| TagTree::from(tag_counts, &self.tag_hierarchy));
         self.update_sort();
     }
+
+    /// Draw the filtered tags as a tree
+    fn draw_tree(&self, ui: &mut Ui) {
+        let Some(tag_tree) = self.filtered_tag_tree.as_ref() else {
+            return;
+        };
+        ScrollArea::vertical().show(ui, |ui| {
+            for (name, nodes) in tag_tree.roots() {
+                let name = name.as_ref().map(|name| name.as_str()).unwrap_or_default();
+                for (value, node) in nodes {
+                    let label = match name.is_empty() {
+                        true => value.to_string(),
+                        false => format!("{name}:{value}"),
+                    };
+                    self.draw_tree_node(ui, &label, node);
+                }
+            }
+        });
+    }
+
+    /// Draw a node of the tag tree (and its children)
+    fn draw_tree_node(&self, ui: &mut Ui, label: &str, node: &TagTreeNode) {
+        if node.is_leaf() {
+            ui.horizontal(|ui| {
+                ui.label(format!("{label} ({})", node.count()));
+                self.draw_tree_node_buttons(ui, node);
+            });
+            return;
+        }
+        CollapsingHeader::new(format!("{label} ({})", node.total_count()))
+            .id_salt(("tag_tree", node.tag().to_string()))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.weak(format!("{} tagged directly", node.count()));
+                    self.draw_tree_node_buttons(ui, node);
+                });
+                for (value, child) in node.children() {
+                    self.draw_tree_node(ui, value, child);
+                }
+            });
+    }
+
+    /// Draw the buttons to view (and bulk edit) a tag tree node's tag
+    fn draw_tree_node_buttons(&self, ui: &mut Ui, node: &TagTreeNode) {
+        let tag = node.tag();
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            if OpenTimelineButton::view(ui).clicked() {
+                let _ = self.tx_action_request.send(ActionRequest::Tag(
+                    TagActionRequest::ViewExisting(tag.clone()),
+                ));
+            }
+            // Only tags that appear themselves can be bulk edited
+            if node.count() > 0 && OpenTimelineButton::edit(ui).clicked() {
+                let _ = self.tx_action_request.send(ActionRequest::Tag(
+                    TagActionRequest::BulkEditExisting(tag.clone()),
+                ));
+            }
+        });
+    }
 }
 
 impl Reload for TagCountsGui {
@@ -319,12 +404,18 @@ impl Reload for TagCountsGui {
         self.requested_reload = true;
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.rx_reload = Some(rx);
+        spawn_operation!(fetch_cached_stat_crud::<EntityTagCountsStat>(
+            Arc::clone(&self.shared_config),
+            tx,
+        ));
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_reload_hierarchy = Some(rx);
         let shared_config = Arc::clone(&self.shared_config);
         spawn_transaction_no_commit_send_result!(
             shared_config,
             bounded,
             tx,
-            |transaction| async move { fetch_all_entity_tag_counts(transaction).await }
+            |transaction| async move { fetch_tag_hierarchy(transaction).await }
         );
     }
 
@@ -335,7 +426,7 @@ impl Reload for TagCountsGui {
                     debug!("Recv tag counts response");
                     match msg {
                         Ok(tag_counts) => {
-                            self.tag_counts = Some(tag_counts);
+                            self.tag_counts = Some(tag_counts.into_value());
                             self.paginator.set_page_index(0);
                             self.update_filtered_tag_counts();
                             self.rx_reload = None;
@@ -349,22 +440,54 @@ impl Reload for TagCountsGui {
                 Err(TryRecvError::Disconnected) => (),
             }
         }
+        if let Some(rx) = self.rx_reload_hierarchy.as_mut() {
+            match rx.try_recv() {
+                Ok(msg) => {
+                    debug!("Recv tag hierarchy response");
+                    match msg {
+                        Ok(tag_hierarchy) => {
+                            self.tag_hierarchy = tag_hierarchy;
+                            self.update_filtered_tag_counts();
+                            self.rx_reload_hierarchy = None;
+                        }
+                        Err(error) => warn!("Error fetching tag hierarchy: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
     }
 }
 
 impl Draw for TagCountsGui {
     fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
         // Input to filter by text
-        let filter_input = ui.add(
-            TextEdit::singleline(&mut self.filter_text)
-                .desired_width(f32::INFINITY)
-                .hint_text("Filter by tag name and tag value"),
-        );
+        let filter_input = ui
+            .horizontal(|ui| {
+                ui.checkbox(&mut self.show_tree, "Tree");
+                ui.add(
+                    TextEdit::singleline(&mut self.filter_text)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("Filter by tag name and tag value"),
+                )
+            })
+            .inner;
         if filter_input.changed() {
             self.update_filtered_tag_counts();
         }
         ui.separator();
 
+        // Tags tree
+        if self.show_tree {
+            if self.filtered_tag_tree.is_some() {
+                self.draw_tree(ui);
+            } else {
+                open_timeline_gui_core::Label::none(ui);
+            }
+            return;
+        }
+
         // Get number of tags.  If there aren't any let the user know and return
         if let Some(tags_counts) = &self.filtered_tag_counts {
             self.paginator.set_total_count(tags_counts.len());
@@ -423,7 +546,7 @@ impl CheckForUpdates for TagCountsGui {
     }
 
     fn waiting_for_updates(&mut self) -> bool {
-        let waiting = self.rx_reload.is_some();
+        let waiting = self.rx_reload.is_some() || self.rx_reload_hierarchy.is_some();
         if waiting {
             info!("TagCountsGui is waiting for updates");
         }
@@ -4,17 +4,21 @@
 //! Desktop GUI timeline counts
 //!
 
+use crate::spawn_operation;
 use crate::{
     app::{ActionRequest, EntityOrTimelineActionRequest},
+    common::fetch_cached_stat_crud,
     components::OpenTimelineButton,
     config::SharedConfig,
     consts::{EDIT_BUTTON_WIDTH, VIEW_BUTTON_WIDTH},
-    spawn_transaction_no_commit_send_result,
+    windows::draw_dynamic_timeline_badge,
 };
 use eframe::egui::{self, Align, Context, Layout, ScrollArea, TextEdit, Ui, Vec2};
 use egui_extras::{Column, TableBuilder};
 use open_timeline_core::HasIdAndName;
-use open_timeline_crud::{CrudError, SortAlphabetically, SortByNumber, TimelineCounts};
+use open_timeline_crud::{
+    Cached, CrudError, SortAlphabetically, SortByNumber, TimelineCounts, TimelineCountsStat,
+};
 use open_timeline_gui_core::{
     CheckForUpdates, Draw, Paginator, Reload, body_text_height, widget_x_spacing, widget_y_spacing,
 };
@@ -57,7 +61,7 @@ pub struct TimelineCountsGui {
     name_ordering: Option<SortAlphabetically>,
 
     /// Receive up-to-date `TimelineCounts` after a reload requested
-    rx_reload: Option<Receiver<Result<TimelineCounts, CrudError>>>,
+    rx_reload: Option<Receiver<Result<Cached<TimelineCounts>, CrudError>>>,
 
     /// Whether a reload has been requested
     requested_reload: bool,
@@ -226,9 +230,17 @@ impl TimelineCountsGui {
                                 });
                             });
 
-                            // Timeline name
+                            // Timeline name (and whether it's dynamic)
                             row.col(|ui| {
                                 ui.with_layout(left_to_right, |ui| {
+                                    if let Some(bool_expr) = timeline_count
+                                        .timeline()
+                                        .bool_expr()
+                                        .as_ref()
+                                        .filter(|_| timeline_count.timeline().is_dynamic())
+                                    {
+                                        draw_dynamic_timeline_badge(ui, bool_expr);
+                                    }
                                     ui.add(egui::Label::new(name).truncate());
                                 });
                             });
@@ -291,13 +303,10 @@ impl Reload for TimelineCountsGui {
         self.requested_reload = true;
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.rx_reload = Some(rx);
-        let shared_config = Arc::clone(&self.shared_config);
-        spawn_transaction_no_commit_send_result!(
-            shared_config,
-            bounded,
+        spawn_operation!(fetch_cached_stat_crud::<TimelineCountsStat>(
+            Arc::clone(&self.shared_config),
             tx,
-            |transaction| async move { TimelineCounts::fetch_all(transaction).await }
-        );
+        ));
     }
 
     fn check_reload_response(&mut self) {
@@ -307,7 +316,7 @@ impl Reload for TimelineCountsGui {
                     debug!("Recv timeline tag counts response");
                     match msg {
                         Ok(timeline_counts) => {
-                            self.timeline_counts = Some(timeline_counts);
+                            self.timeline_counts = Some(timeline_counts.into_value());
                             self.paginator.set_page_index(0);
                             self.update_filtered_timeline_counts();
                             self.rx_reload = None;
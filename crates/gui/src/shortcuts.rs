@@ -42,6 +42,24 @@ impl OpenTimelineShortcut {
         }
         shortcut_used
     }
+
+    /// Shortcut for opening the command palette (Cmd + K)
+    pub fn command_palette(ctx: &Context) -> bool {
+        let modifiers = if cfg!(target_os = "macos") {
+            Modifiers::MAC_CMD
+        } else {
+            Modifiers::CTRL
+        };
+        let command_palette_shortcut = KeyboardShortcut::new(modifiers, Key::K);
+        let shortcut_used = ctx.input_mut(|i| {
+            // Cmd + Shift + K is used elsewhere
+            !i.modifiers.shift && i.consume_shortcut(&command_palette_shortcut)
+        });
+        if shortcut_used {
+            debug!("Command palette shortcut");
+        }
+        shortcut_used
+    }
 }
 
 /// Check for use of a global shortcut
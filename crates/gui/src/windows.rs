@@ -9,6 +9,7 @@ mod entity_edit;
 mod entity_view;
 mod tag_edit;
 mod tag_view;
+mod timeline_compare;
 mod timeline_edit;
 mod timeline_view;
 
@@ -17,14 +18,20 @@ pub use entity_edit::*;
 pub use entity_view::*;
 pub use tag_edit::*;
 pub use tag_view::*;
+pub use timeline_compare::*;
 pub use timeline_edit::*;
 pub use timeline_view::*;
 
 use crate::consts::{
     DEFAULT_NEW_WINDOW_X_OFFSET_FROM_MAIN_WINDOW, DEFAULT_NEW_WINDOW_Y_OFFSET_FROM_MAIN_WINDOW,
 };
-use eframe::egui::{Context, Pos2, Ui, Vec2, ViewportBuilder, ViewportCommand, ViewportId};
-use open_timeline_gui_core::{BreakOutWindow, CheckForUpdates, Draw, Reload};
+use eframe::egui::{
+    Context, Grid, Pos2, TopBottomPanel, Ui, Vec2, ViewportBuilder, ViewportCommand, ViewportId,
+};
+use open_timeline_gui_core::{
+    BreakOutWindow, CheckForUpdates, Dialog, Draw, Reload, UnsavedChangesChoice,
+};
+use open_timeline_i18n::tr;
 use std::{collections::HashMap, hash::Hash, time::Instant};
 
 pub type DeletedAtInstant = Instant;
@@ -82,6 +89,38 @@ impl WindowInfo {
 #[derive(Default)]
 pub struct BreakOutWindows {
     windows: HashMap<ViewportId, (Box<dyn BreakOutWindow>, WindowInfo)>,
+
+    /// The order in which the windows were opened (so that lists & tabs don't
+    /// jump about)
+    order: Vec<ViewportId>,
+
+    /// Whether editors are drawn as tabs in a single window
+    editors_as_tabs: bool,
+
+    /// The editor tab selected (when editors are drawn as tabs)
+    selected_tab: Option<ViewportId>,
+
+    /// The position of the window holding the editor tabs (if it's open)
+    tabs_window_info: Option<WindowInfo>,
+
+    /// Windows asking whether to save, discard, or keep their unsaved changes
+    /// before closing
+    confirming_close: Vec<ViewportId>,
+
+    /// Windows that will close once they've finished saving
+    closing_after_save: Vec<ViewportId>,
+
+    /// Whether the application's closure is waiting for the user to decide
+    /// what to do with unsaved changes
+    confirming_exit: bool,
+
+    /// Whether the application will close once every window has finished
+    /// saving
+    exiting_after_save: bool,
+
+    /// Whether the application can close (unsaved changes have been dealt
+    /// with)
+    exit_confirmed: bool,
 }
 
 impl BreakOutWindows {
@@ -98,13 +137,333 @@ impl BreakOutWindows {
 
         // If already open, bring it to the fore
         if self.windows.contains_key(&window_id) {
-            ctx.send_viewport_cmd_to(window_id, ViewportCommand::Focus);
+            self.focus(ctx, window_id);
 
         // Otherwise create a new window (which will be brough to the fore for us)
         } else {
             let offset = main_window_pos.unwrap_or(Pos2::new(250.0, 150.0));
+            if self.is_tab(window.as_ref()) {
+                self.selected_tab = Some(window_id);
+                if self.tabs_window_info.is_some() {
+                    ctx.send_viewport_cmd_to(editor_tabs_viewport_id(), ViewportCommand::Focus);
+                }
+            }
             self.windows
                 .insert(window_id, (window, WindowInfo::new_with_offset(offset)));
+            self.order.push(window_id);
+        }
+    }
+
+    /// Set whether editors are drawn as tabs in a single window (rather than
+    /// each in its own window)
+    pub fn set_editors_as_tabs(&mut self, editors_as_tabs: bool) {
+        self.editors_as_tabs = editors_as_tabs;
+    }
+
+    /// Whether the window is drawn as a tab in the editor tabs window
+    fn is_tab(&self, window: &dyn BreakOutWindow) -> bool {
+        self.editors_as_tabs && window.is_editor()
+    }
+
+    /// Whether there are no windows open
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// Bring the window to the fore (selecting its tab if it's drawn as one)
+    pub fn focus(&mut self, ctx: &Context, id: ViewportId) {
+        let Some((window, _)) = self.windows.get(&id) else {
+            return;
+        };
+        if self.is_tab(window.as_ref()) {
+            self.selected_tab = Some(id);
+            ctx.send_viewport_cmd_to(editor_tabs_viewport_id(), ViewportCommand::Focus);
+        } else {
+            ctx.send_viewport_cmd_to(id, ViewportCommand::Focus);
+        }
+    }
+
+    /// Bring every window to the fore
+    pub fn bring_all_to_front(&mut self, ctx: &Context) {
+        for id in self.order.clone() {
+            self.focus(ctx, id);
+        }
+    }
+
+    /// Close the window unless it has unsaved changes, in which case the user
+    /// is asked what to do with them first
+    pub fn request_close(&mut self, ctx: &Context, id: ViewportId) {
+        let Some((window, _)) = self.windows.get_mut(&id) else {
+            return;
+        };
+        if !window.has_unsaved_changes() {
+            self.close(id);
+        } else if !self.confirming_close.contains(&id) && !self.closing_after_save.contains(&id) {
+            self.confirming_close.push(id);
+            self.focus(ctx, id);
+        }
+    }
+
+    /// Close every window (asking about any unsaved changes first)
+    pub fn request_close_all(&mut self, ctx: &Context) {
+        for id in self.order.clone() {
+            self.request_close(ctx, id);
+        }
+    }
+
+    /// Close the window (discarding any unsaved changes)
+    fn close(&mut self, id: ViewportId) {
+        self.windows.remove(&id);
+        self.order.retain(|open_id| *open_id != id);
+        self.confirming_close.retain(|open_id| *open_id != id);
+        self.closing_after_save.retain(|open_id| *open_id != id);
+        if self.selected_tab == Some(id) {
+            self.selected_tab = None;
+        }
+    }
+
+    /// Act on the choice made in a window's unsaved changes dialog
+    fn handle_unsaved_changes_choice(&mut self, id: ViewportId, choice: UnsavedChangesChoice) {
+        self.confirming_close.retain(|open_id| *open_id != id);
+        let Some((window, _)) = self.windows.get_mut(&id) else {
+            return;
+        };
+        match choice {
+            UnsavedChangesChoice::Save => {
+                window.save();
+                self.closing_after_save.push(id);
+            }
+            UnsavedChangesChoice::Discard => self.close(id),
+            UnsavedChangesChoice::Cancel => {
+                window.cancel_close();
+                self.exiting_after_save = false;
+            }
+        }
+    }
+
+    /// Close the windows that have finished saving.  Those whose changes
+    /// couldn't be saved (e.g. because of a database error) stay open (showing
+    /// the error).
+    fn check_closing_after_save(&mut self) {
+        for id in self.closing_after_save.clone() {
+            let Some((window, _)) = self.windows.get_mut(&id) else {
+                continue;
+            };
+            if window.waiting_for_updates() {
+                continue;
+            }
+            if window.has_unsaved_changes() {
+                warn!(
+                    "Couldn't save window (title = {}), so not closing",
+                    window.title()
+                );
+                window.cancel_close();
+                self.closing_after_save.retain(|open_id| *open_id != id);
+                self.exiting_after_save = false;
+            } else {
+                self.close(id);
+            }
+        }
+    }
+
+    /// Whether the application can close.  If windows have unsaved changes the
+    /// user is asked what to do with them first (see
+    /// [`Self::draw_exit_dialog()`]).
+    pub fn request_exit(&mut self) -> bool {
+        if self.exit_confirmed {
+            return true;
+        }
+        let has_unsaved_changes = self
+            .windows
+            .values_mut()
+            .any(|(window, _)| window.has_unsaved_changes());
+        if has_unsaved_changes {
+            self.confirming_exit = true;
+        }
+        !has_unsaved_changes
+    }
+
+    /// Draw the dialog (in the main window) asking what to do with unsaved
+    /// changes when the application is closed, and close the application once
+    /// they've been dealt with
+    pub fn draw_exit_dialog(&mut self, ctx: &Context) {
+        // Close once every window has saved
+        if self.exiting_after_save && self.closing_after_save.is_empty() {
+            self.exiting_after_save = false;
+            self.exit_confirmed = true;
+            ctx.send_viewport_cmd(ViewportCommand::Close);
+            return;
+        }
+        if !self.confirming_exit {
+            return;
+        }
+        let mut unsaved = Vec::new();
+        let mut can_save = true;
+        for id in &self.order {
+            if let Some((window, _)) = self.windows.get_mut(id)
+                && window.has_unsaved_changes()
+            {
+                can_save &= window.can_save();
+                unsaved.push(*id);
+            }
+        }
+        let message = tr!("dialog-unsaved-changes-app", count = unsaved.len());
+        let Some(choice) = Dialog::unsaved_changes_all(ctx, "exit", &message, can_save) else {
+            return;
+        };
+        self.confirming_exit = false;
+        match choice {
+            UnsavedChangesChoice::Save => {
+                for id in unsaved {
+                    self.handle_unsaved_changes_choice(id, UnsavedChangesChoice::Save);
+                }
+                self.exiting_after_save = true;
+            }
+            UnsavedChangesChoice::Discard => {
+                self.exit_confirmed = true;
+                ctx.send_viewport_cmd(ViewportCommand::Close);
+            }
+            UnsavedChangesChoice::Cancel => (),
+        }
+    }
+
+    /// Draw the list of open windows (each with buttons to bring it to the fore
+    /// & to close it) along with buttons to act on all of them at once
+    pub fn draw_open_windows(&mut self, ctx: &Context, ui: &mut Ui) {
+        if self.is_empty() {
+            ui.label("No windows open");
+            return;
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Bring All to Front").clicked() {
+                self.bring_all_to_front(ctx);
+            }
+            if ui.button("Close All").clicked() {
+                self.request_close_all(ctx);
+            }
+        });
+        ui.separator();
+        let mut id_to_focus = None;
+        let mut id_to_close = None;
+        Grid::new("open_windows")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                for id in &self.order {
+                    let Some((window, _)) = self.windows.get_mut(id) else {
+                        continue;
+                    };
+                    ui.label(window.title());
+                    if window.has_unsaved_changes() {
+                        open_timeline_gui_core::Label::weak(ui, "Unsaved");
+                    } else {
+                        ui.label("");
+                    }
+                    if ui.button("Focus").clicked() {
+                        id_to_focus = Some(*id);
+                    }
+                    if open_timeline_gui_core::Button::close(ui).clicked() {
+                        id_to_close = Some(*id);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(id) = id_to_focus {
+            self.focus(ctx, id);
+        }
+        if let Some(id) = id_to_close {
+            self.request_close(ctx, id);
+        }
+    }
+
+    /// Draw the window holding the editor tabs (a tab bar at the top with the
+    /// selected editor filling the rest of the window)
+    fn draw_editor_tabs(
+        &mut self,
+        ctx: &Context,
+        tab_ids: &[ViewportId],
+        window_ids_to_close: &mut Vec<ViewportId>,
+        choices: &mut Vec<(ViewportId, UnsavedChangesChoice)>,
+    ) {
+        // Make sure a tab that's open is selected (those asking about unsaved
+        // changes first)
+        let confirming = tab_ids
+            .iter()
+            .find(|id| self.confirming_close.contains(id))
+            .copied();
+        let selected = match (confirming, self.selected_tab) {
+            (Some(id), _) => id,
+            (None, Some(id)) if tab_ids.contains(&id) => id,
+            _ => tab_ids[0],
+        };
+        self.selected_tab = Some(selected);
+
+        let mut titles = Vec::with_capacity(tab_ids.len());
+        let mut any_unsaved_changes = false;
+        for id in tab_ids {
+            if let Some((window, _)) = self.windows.get_mut(id) {
+                titles.push((*id, window.title()));
+                any_unsaved_changes |= window.has_unsaved_changes();
+            }
+        }
+        let confirming_close = confirming.is_some();
+        let Some((window, window_info)) = self.windows.get_mut(&selected) else {
+            return;
+        };
+        let tabs_window_info = self
+            .tabs_window_info
+            .get_or_insert_with(|| window_info.clone());
+        let viewport = ViewportBuilder::default()
+            .with_title(window.title())
+            .with_position(tabs_window_info.offset)
+            .with_inner_size(window.default_size());
+        let mut selected_tab = selected;
+        ctx.show_viewport_immediate(editor_tabs_viewport_id(), viewport, |ctx, _| {
+            // Closing the window closes all of the editors in it
+            if ctx.input(|i| i.viewport().close_requested()) {
+                if any_unsaved_changes {
+                    ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+                }
+                window_ids_to_close.extend_from_slice(tab_ids);
+            }
+            if let Some(outer_rect) = ctx.input(|i| i.viewport().outer_rect) {
+                tabs_window_info.offset = outer_rect.min;
+            };
+            TopBottomPanel::top("editor_tabs").show(ctx, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for (id, title) in &titles {
+                        if ui.selectable_label(*id == selected, title).clicked() {
+                            selected_tab = *id;
+                        }
+                        if open_timeline_gui_core::Button::close(ui).clicked() {
+                            window_ids_to_close.push(*id);
+                        }
+                        ui.separator();
+                    }
+                });
+            });
+            if window.wants_to_be_closed() {
+                window_ids_to_close.push(selected);
+            }
+            window.draw(ctx);
+            if confirming_close {
+                let message = tr!("dialog-unsaved-changes-window", title = window.title());
+                let can_save = window.can_save();
+                if let Some(choice) = Dialog::unsaved_changes(ctx, selected, &message, can_save) {
+                    choices.push((selected, choice));
+                }
+            }
+        });
+        self.selected_tab = Some(selected_tab);
+
+        // Editors that aren't drawn can still want closing (e.g. if deleted)
+        for id in tab_ids {
+            if let Some((window, _)) = self.windows.get_mut(id)
+                && *id != selected
+                && window.wants_to_be_closed()
+            {
+                window_ids_to_close.push(*id);
+            }
         }
     }
 
@@ -124,14 +483,26 @@ impl BreakOutWindows {
     /// rather than a new one opened.
     fn update_viewport_ids_if_needed(&mut self) {
         let mut new_map = HashMap::with_capacity(self.windows.len());
-        for (_old_viewport_id, (mut window, window_info)) in self.windows.drain() {
+        for old_viewport_id in &mut self.order {
+            let Some((mut window, window_info)) = self.windows.remove(old_viewport_id) else {
+                continue;
+            };
             let new_viewport_id = window.viewport_id();
+            if self.selected_tab == Some(*old_viewport_id) {
+                self.selected_tab = Some(new_viewport_id);
+            }
+            *old_viewport_id = new_viewport_id;
             new_map.insert(new_viewport_id, (window, window_info));
         }
         self.windows = new_map;
     }
 }
 
+/// The viewport ID of the window holding the editor tabs
+fn editor_tabs_viewport_id() -> ViewportId {
+    ViewportId::from_hash_of("open_timeline_editor_tabs")
+}
+
 impl Reload for BreakOutWindows {
     fn request_reload(&mut self) {
         self.update_viewport_ids_if_needed();
@@ -150,6 +521,7 @@ impl CheckForUpdates for BreakOutWindows {
         for (window, _) in self.windows.values_mut() {
             window.check_for_updates()
         }
+        self.check_closing_after_save();
     }
 
     fn waiting_for_updates(&mut self) -> bool {
@@ -169,28 +541,57 @@ impl CheckForUpdates for BreakOutWindows {
 impl Draw for BreakOutWindows {
     fn draw(&mut self, ctx: &Context, _ui: &mut Ui) {
         let mut window_ids_to_close = Vec::new();
-        let window_ids: Vec<ViewportId> = self.windows.keys().cloned().collect();
-        for id in window_ids {
+        let mut choices = Vec::new();
+        let mut tab_ids = Vec::new();
+        for id in self.order.clone() {
             let Some((window, window_info)) = self.windows.get_mut(&id) else {
                 // TODO: panic here?
                 continue;
             };
+            if self.editors_as_tabs && window.is_editor() {
+                tab_ids.push(id);
+                continue;
+            }
             let viewport = ViewportBuilder::default()
                 .with_title(window.title())
                 .with_position(window_info.offset)
                 .with_inner_size(window.default_size());
+            let confirming_close = self.confirming_close.contains(&id);
             ctx.show_viewport_immediate(id, viewport, |ctx, _| {
-                if ctx.input(|i| i.viewport().close_requested()) || window.wants_to_be_closed() {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    // Keep the window open until the user decides what to do
+                    // with the unsaved changes
+                    if window.has_unsaved_changes() {
+                        ctx.send_viewport_cmd(ViewportCommand::CancelClose);
+                    }
+                    window_ids_to_close.push(id);
+                }
+                if window.wants_to_be_closed() {
                     window_ids_to_close.push(id);
                 }
                 if let Some(outer_rect) = ctx.input(|i| i.viewport().outer_rect) {
                     window_info.offset = outer_rect.min;
                 };
                 window.draw(ctx);
+                if confirming_close {
+                    let message = tr!("dialog-unsaved-changes-window", title = window.title());
+                    let can_save = window.can_save();
+                    if let Some(choice) = Dialog::unsaved_changes(ctx, id, &message, can_save) {
+                        choices.push((id, choice));
+                    }
+                }
             });
         }
+        if tab_ids.is_empty() {
+            self.tabs_window_info = None;
+        } else {
+            self.draw_editor_tabs(ctx, &tab_ids, &mut window_ids_to_close, &mut choices);
+        }
+        for (id, choice) in choices {
+            self.handle_unsaved_changes_choice(id, choice);
+        }
         for id in window_ids_to_close {
-            self.windows.remove(&id);
+            self.request_close(ctx, id);
         }
     }
 }
@@ -4,34 +4,42 @@
 //! The edit entity GUI
 //!
 
-use crate::app::ActionRequest;
+use crate::activity_log::ACTIVITY;
+use crate::app::{ActionRequest, EntityOrTimelineActionRequest};
+use crate::clipboard::pasted_entities;
 use crate::common::{CrudOperationRequested, ToOpenTimelineType, delete_from_id_crud, save_crud};
-use crate::components::{DatesGui, EntityOrTimeline, NameGui, TagsGui};
+use crate::components::{
+    DatesGui, DeleteConfirmationGui, EntityOrTimeline, LocationGui, NameGui, SourcesGui,
+    TagSuggestionsGui, TagsGui, TypedTagsGui,
+};
 use crate::config::SharedConfig;
 use crate::consts::DEFAULT_WINDOW_SIZES;
 use crate::shortcuts::global_shortcuts;
+use crate::spawn_operation;
+use crate::spawn_transaction_no_commit_send_result;
 use crate::windows::{Deleted, DeletedStatus};
-use crate::{
-    impl_is_valid_method_for_iterable, impl_valid_asynchronous_macro_never_called,
-    impl_valid_synchronous_macro_never_called, spawn_transaction_no_commit_send_result,
-};
+use bool_tag_expr::Tags;
 use eframe::egui::{
     self, CentralPanel, Context, Response, ScrollArea, Spinner, Ui, Vec2, ViewportId,
 };
-use log::info;
-use open_timeline_core::{Entity, HasIdAndName, OpenTimelineId};
+use open_timeline_core::{
+    Date, Entity, ErrorChain, HasIdAndName, Location, Name, OpenTimelineId, Sources, TypedTags,
+};
 use open_timeline_crud::{CrudError, FetchById};
 use open_timeline_gui_core::{
     BreakOutWindow, CheckForUpdates, CreateOrEdit, DisplayStatus, Draw, GuiStatus, Reload,
-    Shortcut, Valid, ValidityAsynchronous, window_has_focus,
+    Shortcut, Toast, Valid, ValidityAsynchronous, window_has_focus,
 };
+use open_timeline_macros::GuiComponent;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc::error::TryRecvError;
 use tokio::sync::mpsc::{Receiver, UnboundedSender};
+use tracing::info;
 
 /// Edit an entity
-#[derive(Debug)]
+#[derive(Debug, GuiComponent)]
+#[gui_component(opentimeline_type = "Entity", from_parts = "entity_from_parts")]
 pub struct EntityEditGui {
     /// The the entity being edited as it is in the database (if one is being
     /// edited, rather than created)
@@ -42,14 +50,32 @@ pub struct EntityEditGui {
     entity_id: Option<OpenTimelineId>,
 
     /// The GUI name element
+    #[gui_component]
     name: NameGui,
 
     /// The GUI dates element
+    #[gui_component]
     dates: DatesGui,
 
     /// The GUI tags element
+    #[gui_component]
     tags: TagsGui,
 
+    /// Tags suggested from similar entities
+    tag_suggestions: TagSuggestionsGui,
+
+    /// The GUI typed tags (number & date tags) element
+    #[gui_component]
+    typed_tags: TypedTagsGui,
+
+    /// The GUI location element
+    #[gui_component]
+    location: LocationGui,
+
+    /// The GUI sources element
+    #[gui_component]
+    sources: SourcesGui,
+
     /// Whether or not a reload has been requested
     requested_reload: bool,
 
@@ -72,9 +98,16 @@ pub struct EntityEditGui {
     /// Recevie updates on deletion
     rx_delete: Option<Receiver<Result<(), CrudError>>>,
 
+    /// Asks for confirmation before deleting (listing what will be affected)
+    delete_confirmation: DeleteConfirmationGui,
+
     /// Receive reloaded data
     rx_reload: Option<Receiver<Result<Entity, CrudError>>>,
 
+    /// Receive the latest version of the entity (so that the user's changes
+    /// can be re-applied to it after a conflicting edit)
+    rx_reapply: Option<Receiver<Result<Entity, CrudError>>>,
+
     /// Used to indirectly inform the rest of the application that a CRUD
     /// operation has been executed
     tx_crud_operation_executed: UnboundedSender<()>,
@@ -114,6 +147,8 @@ enum Status {
 
     Created,
     Updated,
+    Reapplied,
+    Pasted,
 
     Valid,
     Invalid(String),
@@ -139,10 +174,20 @@ impl DisplayStatus for Status {
             }
             Self::Created => String::from("Entity successfully created"),
             Self::Updated => String::from("Entity successfully updated"),
+            Self::Reapplied => String::from(
+                "Your changes have been re-applied to the latest entity (review & update)",
+            ),
+            Self::Pasted => String::from("The pasted entity has been filled in (review & save)"),
             Self::Valid => String::from("Entity is valid"),
             Self::Invalid(error) => format!("Entity is invalid: {error}"),
         };
-        ui.add(egui::Label::new(str).truncate())
+        let response = ui.add(egui::Label::new(str).truncate());
+        match &self {
+            Self::CreateError(error) | Self::UpdateError(error) | Self::DeleteError(error) => {
+                response.on_hover_text(ErrorChain(error).to_string())
+            }
+            _ => response,
+        }
     }
 }
 
@@ -165,6 +210,10 @@ impl EntityEditGui {
             name: NameGui::new(Arc::clone(&shared_config), EntityOrTimeline::Entity),
             dates: DatesGui::new(),
             tags: TagsGui::new(),
+            tag_suggestions: TagSuggestionsGui::new(Arc::clone(&shared_config)),
+            typed_tags: TypedTagsGui::new(),
+            location: LocationGui::new(),
+            sources: SourcesGui::new(),
             deleted_status: DeletedStatus::NotDeleted,
             requested_reload: false,
             create_or_edit: CreateOrEdit::Create,
@@ -172,7 +221,12 @@ impl EntityEditGui {
             crud_op_requested: None,
             rx_create_update: None,
             rx_delete: None,
+            delete_confirmation: DeleteConfirmationGui::new(
+                Arc::clone(&shared_config),
+                EntityOrTimeline::Entity,
+            ),
             rx_reload: None,
+            rx_reapply: None,
             tx_crud_operation_executed,
             tx_action_request,
             wants_to_be_closed: false,
@@ -183,6 +237,23 @@ impl EntityEditGui {
         }
     }
 
+    /// Create a new `EntityEditGui` for creating an entity, with the start date
+    /// filled in
+    pub fn new_window_for_creating_entity_starting(
+        shared_config: SharedConfig,
+        tx_action_request: UnboundedSender<ActionRequest>,
+        tx_crud_operation_executed: UnboundedSender<()>,
+        start: Date,
+    ) -> Self {
+        let mut entity_edit_gui = Self::new_window_for_creating_entity(
+            shared_config,
+            tx_action_request,
+            tx_crud_operation_executed,
+        );
+        entity_edit_gui.dates = (start, None).into();
+        entity_edit_gui
+    }
+
     /// Create a new `EntityEditGui` for editing an entity
     pub fn new_window_for_editing_entity(
         shared_config: SharedConfig,
@@ -196,6 +267,10 @@ impl EntityEditGui {
             name: NameGui::new(Arc::clone(&shared_config), EntityOrTimeline::Entity),
             dates: DatesGui::new(),
             tags: TagsGui::new(),
+            tag_suggestions: TagSuggestionsGui::new(Arc::clone(&shared_config)),
+            typed_tags: TypedTagsGui::new(),
+            location: LocationGui::new(),
+            sources: SourcesGui::new(),
             deleted_status: DeletedStatus::NotDeleted,
             requested_reload: false,
             create_or_edit: CreateOrEdit::Edit,
@@ -203,7 +278,12 @@ impl EntityEditGui {
             crud_op_requested: None,
             rx_create_update: None,
             rx_delete: None,
+            delete_confirmation: DeleteConfirmationGui::new(
+                Arc::clone(&shared_config),
+                EntityOrTimeline::Entity,
+            ),
             rx_reload: None,
+            rx_reapply: None,
             tx_crud_operation_executed,
             tx_action_request,
             wants_to_be_closed: false,
@@ -228,12 +308,33 @@ impl EntityEditGui {
         );
         self.dates = (entity.start(), entity.end()).into();
         self.tags = entity.tags().to_owned().into();
+        self.typed_tags = entity.typed_tags().to_owned().into();
+        self.location = entity.location().to_owned().into();
+        self.sources = entity.sources().to_owned().into();
         self.deleted_status = DeletedStatus::NotDeleted;
         self.create_or_edit = CreateOrEdit::Edit;
         self.crud_op_requested = None;
         self.rx_create_update = None;
         self.rx_delete = None;
         self.rx_reload = None;
+        self.rx_reapply = None;
+    }
+
+    /// Fill in the inputs from a pasted entity (e.g. copied from another
+    /// database).  Only the inputs change: the entity being created/edited
+    /// stays the same.
+    fn fill_from_pasted_entity(&mut self, entity: Entity) {
+        self.name = NameGui::from_name(
+            Arc::clone(&self.shared_config),
+            EntityOrTimeline::Entity,
+            entity.name().clone(),
+        );
+        self.dates = (entity.start(), entity.end()).into();
+        self.tags = entity.tags().to_owned().into();
+        self.typed_tags = entity.typed_tags().to_owned().into();
+        self.location = entity.location().to_owned().into();
+        self.sources = entity.sources().to_owned().into();
+        self.status = Status::Pasted;
     }
 
     // TODO: trait?
@@ -246,6 +347,78 @@ impl EntityEditGui {
         }
     }
 
+    /// Fetch the latest version of the entity so that the user's changes can
+    /// be re-applied to it (used after an update fails because the entity was
+    /// changed elsewhere)
+    fn request_reload_and_reapply(&mut self) {
+        if let Some(entity_id) = self.entity_id {
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            self.rx_reapply = Some(rx);
+            let shared_config = Arc::clone(&self.shared_config);
+            spawn_transaction_no_commit_send_result!(
+                shared_config,
+                bounded,
+                tx,
+                |transaction| async move { Entity::fetch_by_id(transaction, &entity_id).await }
+            );
+        }
+    }
+
+    /// Re-apply the user's changes (those that differ from the entity as it
+    /// was when fetched) to the latest version of the entity
+    fn reapply_changes(&mut self, latest: Entity) {
+        let Some(original) = self.database_entry.clone() else {
+            return self.set_from_entity(latest);
+        };
+        let edited = self.to_opentimeline_type();
+        let pick = |edited_changed: bool| if edited_changed { &edited } else { &latest };
+
+        let name = pick(edited.name() != original.name()).name().clone();
+        let dates = pick((edited.start(), edited.end()) != (original.start(), original.end()));
+        let tags = pick(edited.tags() != original.tags()).tags().clone();
+        let mut merged = Entity::from(latest.id(), name, dates.start(), dates.end(), tags).unwrap();
+        merged.set_location(
+            pick(edited.location() != original.location())
+                .location()
+                .clone(),
+        );
+        if let Some(typed_tags) = pick(edited.typed_tags() != original.typed_tags()).typed_tags() {
+            merged.set_typed_tags(typed_tags.clone());
+        }
+        if let Some(sources) = pick(edited.sources() != original.sources()).sources() {
+            merged.set_sources(sources.clone());
+        }
+        if let Some(aliases) = latest.aliases() {
+            merged.set_aliases(aliases.clone());
+        }
+        merged.set_version(latest.version());
+
+        self.set_from_entity(merged);
+        self.database_entry = Some(latest);
+        self.status = Status::Reapplied;
+    }
+
+    /// Draw the options available after an update fails because the entity
+    /// was changed elsewhere
+    fn draw_stale_write_options(&mut self, ui: &mut Ui) {
+        if !matches!(self.status, Status::UpdateError(CrudError::StaleWrite)) {
+            return;
+        }
+        ui.horizontal(|ui| {
+            if self.rx_reapply.is_some() {
+                ui.add(Spinner::new());
+            }
+            if ui.button("Reload & Re-apply My Changes").clicked() {
+                self.request_reload_and_reapply();
+            }
+            if ui.button("Discard My Changes").clicked() {
+                self.status = Status::NewWindowForEditing;
+                self.request_reload();
+            }
+        });
+        ui.separator();
+    }
+
     // TODO: use an enum instead of Option<bool>
     // TODO: trait?
     // TODO: identical to one in timeline_edit.rs
@@ -308,12 +481,15 @@ impl EntityEditGui {
                 } else {
                     ui.label("Input valid information for a new entity");
                 }
+                open_timeline_gui_core::Label::weak(ui, "(or paste an entity's JSON)");
             }
             CreateOrEdit::Edit => {
                 // Delete comes first so that it never moves (reduced likelihood
                 // of accidentally clicking it)
-                if open_timeline_gui_core::Button::delete(ui).clicked() {
-                    self.request_delete();
+                if open_timeline_gui_core::Button::delete(ui).clicked()
+                    && let Some(id) = self.entity_id
+                {
+                    self.delete_confirmation.open(id);
                 }
                 // Can be invalid or valid, but cannot be equal to the entry in the database
                 if self.differs_from_database_entry() != Some(false)
@@ -370,7 +546,9 @@ impl EntityEditGui {
                 CreateOrEdit::Create => Status::RequestingCreate,
                 CreateOrEdit::Edit => Status::RequestingUpdate,
             };
-            tokio::spawn(async move { save_crud(shared_config, create_or_edit, entity, tx).await });
+            spawn_operation!(
+                async move { save_crud(shared_config, create_or_edit, entity, tx).await }
+            );
         }
     }
 
@@ -382,9 +560,9 @@ impl EntityEditGui {
         let entity_id = self.entity_id.unwrap();
         let shared_config = Arc::clone(&self.shared_config);
         self.status = Status::RequestingDelete;
-        tokio::spawn(
-            async move { delete_from_id_crud::<Entity>(shared_config, entity_id, tx).await },
-        );
+        spawn_operation!(async move {
+            delete_from_id_crud::<Entity>(shared_config, entity_id, tx).await
+        });
     }
 
     // TODO: Nearly identical to that in timeline_edit.rs (make generic or macro)
@@ -406,10 +584,22 @@ impl EntityEditGui {
                                 CreateOrEdit::Edit => Status::Updated,
                             };
 
+                            info!(target: ACTIVITY, "Saved entity {}", entity.name());
+                            let toast = Toast::success(format!("Saved {}", entity.name()));
+                            let toast = match entity.id() {
+                                Some(id) => toast.with_action(ActionRequest::Entity(
+                                    EntityOrTimelineActionRequest::ViewExisting(id),
+                                )),
+                                None => toast,
+                            };
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.set_from_entity(entity);
                             let _ = self.tx_crud_operation_executed.send(());
                         }
                         Err(error) => {
+                            warn!(target: ACTIVITY, "Failed to save entity: {}", ErrorChain(&error));
+                            let toast = Toast::error(format!("Failed to save entity: {error}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.status = match self.create_or_edit {
                                 CreateOrEdit::Create => Status::CreateError(error),
                                 CreateOrEdit::Edit => Status::UpdateError(error),
@@ -422,6 +612,25 @@ impl EntityEditGui {
             }
         }
 
+        // Response to reload & re-apply request
+        if let Some(rx) = self.rx_reapply.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv entity reload & re-apply response");
+                    self.rx_reapply = None;
+                    match result {
+                        Ok(latest) => self.reapply_changes(latest),
+                        Err(CrudError::IdNotInDb) => {
+                            self.set_deleted_status(DeletedStatus::Deleted(Instant::now()))
+                        }
+                        Err(error) => self.status = Status::UpdateError(error),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+
         // Response to delete request
         if let Some(rx) = self.rx_delete.as_mut() {
             match rx.try_recv() {
@@ -431,10 +640,16 @@ impl EntityEditGui {
                     self.crud_op_requested = None;
                     match result {
                         Ok(()) => {
+                            info!(target: ACTIVITY, "Deleted entity");
+                            let toast = Toast::success("Deleted entity");
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.set_deleted_status(DeletedStatus::Deleted(Instant::now()));
                             let _ = self.tx_crud_operation_executed.send(());
                         }
                         Err(error) => {
+                            warn!(target: ACTIVITY, "Failed to delete entity: {}", ErrorChain(&error));
+                            let toast = Toast::error(format!("Failed to delete entity: {error}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.status = Status::DeleteError(error);
                         }
                     }
@@ -446,14 +661,34 @@ impl EntityEditGui {
     }
 }
 
-impl ToOpenTimelineType<Entity> for EntityEditGui {
-    fn to_opentimeline_type(&self) -> Entity {
-        let id = self.entity_id;
-        let name = self.name.to_opentimeline_type();
-        let (start, end) = self.dates.to_opentimeline_type();
-        let tags = self.tags.to_opentimeline_type();
-
-        Entity::from(id, name, start, end, tags).unwrap()
+impl EntityEditGui {
+    /// Build the entity from its (converted) components
+    fn entity_from_parts(
+        &self,
+        name: Name,
+        (start, end): (Date, Option<Date>),
+        tags: Option<Tags>,
+        typed_tags: TypedTags,
+        location: Option<Location>,
+        sources: Sources,
+    ) -> Entity {
+        let mut entity = Entity::from(self.entity_id, name, start, end, tags).unwrap();
+        entity.set_typed_tags(typed_tags);
+        entity.set_location(location);
+        entity.set_sources(sources);
+
+        // Updates only succeed if the entity hasn't changed since it was fetched
+        entity.set_version(self.database_entry.as_ref().and_then(Entity::version));
+
+        // Aliases aren't edited here, so keep those in the database
+        if let Some(aliases) = self
+            .database_entry
+            .as_ref()
+            .and_then(|entity| entity.aliases().clone())
+        {
+            entity.set_aliases(aliases);
+        }
+        entity
     }
 }
 
@@ -494,7 +729,11 @@ impl Reload for EntityEditGui {
                         Err(CrudError::IdNotInDb) => {
                             self.set_deleted_status(DeletedStatus::Deleted(Instant::now()))
                         }
-                        Err(_) => todo!(),
+                        Err(error) => {
+                            warn!(target: ACTIVITY, "Failed to reload entity: {}", ErrorChain(&error));
+                            let toast = Toast::error(format!("Failed to reload entity: {error}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
+                        }
                     }
                 }
                 Err(TryRecvError::Empty) => (),
@@ -514,33 +753,20 @@ impl Deleted for EntityEditGui {
     }
 }
 
-impl_valid_synchronous_macro_never_called!(EntityEditGui);
-impl_valid_asynchronous_macro_never_called!(EntityEditGui);
-
-impl Valid for EntityEditGui {
-    fn validity(&self) -> ValidityAsynchronous {
-        impl_is_valid_method_for_iterable!([
-            self.name.validity(),
-            self.dates.validity(),
-            self.tags.validity(),
-        ])
-    }
-
-    fn update_validity(&mut self) {
-        // Do nothing.  Components update their validity themselves.
-        panic!()
-    }
-}
-
 impl CheckForUpdates for EntityEditGui {
     fn check_for_updates(&mut self) {
         self.check_reload_response();
         self.check_for_crud_status_updates();
+        self.delete_confirmation.check_for_updates();
     }
 
     fn waiting_for_updates(&mut self) -> bool {
-        let waiting =
-            self.rx_reload.is_some() || self.rx_delete.is_some() || self.rx_create_update.is_some();
+        let waiting = self.rx_reload.is_some()
+            || self.rx_delete.is_some()
+            || self.delete_confirmation.waiting_for_updates()
+            || self.rx_create_update.is_some()
+            || self.rx_reapply.is_some()
+            || self.tag_suggestions.waiting_for_updates();
         if waiting {
             info!("EntityEditGui is waiting for updates");
         }
@@ -563,6 +789,15 @@ impl BreakOutWindow for EntityEditGui {
         // Check for global shortcuts
         global_shortcuts(ctx, &mut self.tx_action_request);
 
+        // Pasting an entity's JSON fills in the inputs
+        if !self.requested_reload
+            && !self.has_been_deleted()
+            && let Some(entity) =
+                pasted_entities(ctx).and_then(|entities| entities.into_iter().next())
+        {
+            self.fill_from_pasted_entity(entity);
+        }
+
         // Update status (TODO: needed or done elsewhere?)
         match self.validity() {
             ValidityAsynchronous::Invalid(error) => self.status = Status::Invalid(error),
@@ -604,6 +839,7 @@ impl BreakOutWindow for EntityEditGui {
             // Status
             self.draw_status(ui);
             ui.separator();
+            self.draw_stale_write_options(ui);
 
             // Create/Update/Delete buttons
             self.draw_toolbar(ui);
@@ -617,11 +853,24 @@ impl BreakOutWindow for EntityEditGui {
             self.dates.draw(ctx, ui);
             ui.separator();
 
-            // Tags
+            // Tags, location & sources
             ScrollArea::vertical().show(ui, |ui| {
                 self.tags.draw(ctx, ui);
+                self.tag_suggestions
+                    .draw(ui, &mut self.tags, self.entity_id);
+                ui.separator();
+                self.typed_tags.draw(ctx, ui);
+                ui.separator();
+                self.location.draw(ctx, ui);
+                ui.separator();
+                self.sources.draw(ctx, ui);
             });
         });
+
+        // Deleting must be confirmed
+        if self.delete_confirmation.draw(ctx, &self.name.name) {
+            self.request_delete();
+        }
     }
 
     fn default_size(&self) -> Vec2 {
@@ -655,4 +904,30 @@ impl BreakOutWindow for EntityEditGui {
     fn wants_to_be_closed(&mut self) -> bool {
         self.wants_to_be_closed
     }
+
+    fn is_editor(&self) -> bool {
+        true
+    }
+
+    fn has_unsaved_changes(&mut self) -> bool {
+        if self.requested_reload || self.has_been_deleted() {
+            return false;
+        }
+        match self.create_or_edit {
+            CreateOrEdit::Create => !self.name.name.is_empty(),
+            CreateOrEdit::Edit => self.differs_from_database_entry() != Some(false),
+        }
+    }
+
+    fn can_save(&mut self) -> bool {
+        self.can_be_saved()
+    }
+
+    fn save(&mut self) {
+        self.request_create_or_update();
+    }
+
+    fn cancel_close(&mut self) {
+        self.wants_to_be_closed = false;
+    }
 }
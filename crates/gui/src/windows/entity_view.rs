@@ -4,31 +4,53 @@
 //! The view entity GUI
 //!
 
-use crate::app::ActionRequest;
+use crate::activity_log::ACTIVITY;
+use crate::app::{ActionRequest, EntityOrTimelineActionRequest};
+use crate::clipboard::copy_entity_to_clipboard;
+use crate::components::StarGui;
 use crate::config::SharedConfig;
 use crate::consts::DEFAULT_WINDOW_SIZES;
 use crate::shortcuts::global_shortcuts;
 use crate::{
     spawn_transaction_no_commit_send_result,
-    windows::{Deleted, DeletedStatus},
+    windows::{
+        Deleted, DeletedStatus, update_timeline_colours, update_timeline_date_format,
+        update_timeline_font,
+    },
 };
 use eframe::egui::{
-    self, Align, CentralPanel, Context, Layout, RichText, ScrollArea, Ui, Vec2, ViewportId,
+    self, Align, CentralPanel, Color32, Context, Layout, RichText, ScrollArea, Ui, Vec2, ViewportId,
 };
 use egui_extras::{Column, TableBuilder};
-use open_timeline_core::{Entity, HasIdAndName, OpenTimelineId};
-use open_timeline_crud::{CrudError, FetchById};
+use open_timeline_core::{DateRange, Entity, HasIdAndName, Location, OpenTimelineId};
+use open_timeline_crud::{
+    CrudError, FavouriteKind, FetchById, Limit, contemporaries_of, entities_alive_during,
+};
 use open_timeline_gui_core::{
-    BreakOutWindow, CheckForUpdates, Reload, body_text_height, widget_x_spacing,
+    BreakOutWindow, CheckForUpdates, Draw, Reload, body_text_height, font_size, widget_x_spacing,
 };
-use open_timeline_gui_core::{Shortcut, window_has_focus};
+use open_timeline_gui_core::{Shortcut, Toast, window_has_focus};
+use open_timeline_renderer::TimelineInteractionEvent;
+use open_timeline_renderer::frontends::desktop_egui::OpenTimelineRendererEgui;
+use sqlx::{Sqlite, Transaction};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc::error::TryRecvError;
 use tokio::sync::mpsc::{Receiver, UnboundedSender};
 
+/// The maximum number of contemporaries listed
+const CONTEMPORARIES_LIMIT: u32 = 10;
+
+/// The maximum number of other entities shown on the mini-timeline
+const MINI_TIMELINE_ENTITIES_LIMIT: usize = 30;
+
+/// The height of the mini-timeline
+const MINI_TIMELINE_HEIGHT: f32 = 140.0;
+
+/// The entities shown on the mini-timeline, and the decades it covers
+type MiniTimelineEntities = (Vec<Entity>, DateRange);
+
 /// View an entity
-#[derive(Debug)]
 pub struct EntityViewGui {
     /// The ID of the entity being viewed
     entity_id: OpenTimelineId,
@@ -39,6 +61,23 @@ pub struct EntityViewGui {
     /// Receive reloaded data
     rx_reload: Option<Receiver<Result<Entity, CrudError>>>,
 
+    /// The entities that overlap the entity the longest.  This is `None` until
+    /// they have been fetched.
+    contemporaries: Option<Vec<Entity>>,
+
+    /// Receive the entity's contemporaries
+    rx_contemporaries: Option<Receiver<Result<Vec<Entity>, CrudError>>>,
+
+    /// A small timeline of the entity & the entities alive in the same decades
+    /// (drawn by its own engine)
+    mini_timeline: OpenTimelineRendererEgui,
+
+    /// Whether the mini-timeline is currently set up for high contrast
+    mini_timeline_high_contrast: bool,
+
+    /// Receive the entities to show on the mini-timeline
+    rx_mini_timeline: Option<Receiver<Result<MiniTimelineEntities, CrudError>>>,
+
     /// Send an action request to the main loop
     tx_action_request: UnboundedSender<ActionRequest>,
 
@@ -53,6 +92,9 @@ pub struct EntityViewGui {
     /// Whether this window should be closed or not
     wants_to_be_closed: bool,
 
+    /// Star (favourite) the entity
+    star: StarGui,
+
     /// Database pool
     shared_config: SharedConfig,
 }
@@ -61,17 +103,27 @@ impl EntityViewGui {
     /// Create new EntityViewGui
     pub fn new(
         shared_config: SharedConfig,
+        ctx: &Context,
         tx_action_request: UnboundedSender<ActionRequest>,
         entity_id: OpenTimelineId,
     ) -> Self {
+        let mut mini_timeline = OpenTimelineRendererEgui::new(ctx);
+        mini_timeline.set_font_size_px(font_size(ctx) as f64);
+        mini_timeline.set_sticky_text(true);
         let mut entity_view_gui = EntityViewGui {
             entity_id,
             entity: None,
             rx_reload: None,
+            contemporaries: None,
+            rx_contemporaries: None,
+            mini_timeline,
+            mini_timeline_high_contrast: false,
+            rx_mini_timeline: None,
             tx_action_request,
             requested_reload: false,
             deleted_status: DeletedStatus::NotDeleted,
             wants_to_be_closed: false,
+            star: StarGui::new(Arc::clone(&shared_config), FavouriteKind::Entity, entity_id),
             shared_config,
         };
         entity_view_gui.request_reload();
@@ -82,6 +134,203 @@ impl EntityViewGui {
     pub fn entity_id(&self) -> OpenTimelineId {
         self.entity_id
     }
+
+    fn request_contemporaries(&mut self) {
+        let entity_id = self.entity_id;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_contemporaries = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move {
+                contemporaries_of(transaction, &entity_id, Limit(CONTEMPORARIES_LIMIT)).await
+            }
+        );
+    }
+
+    fn check_contemporaries_response(&mut self) {
+        if let Some(rx) = self.rx_contemporaries.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv entity view contemporaries response");
+                    self.rx_contemporaries = None;
+                    match result {
+                        Ok(contemporaries) => self.contemporaries = Some(contemporaries),
+                        Err(error) => {
+                            warn!("Entity contemporaries fetch error: {error}");
+                            self.contemporaries = Some(Vec::new());
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Fetch the entity & the entities alive in the same decades as it (for
+    /// the mini-timeline)
+    fn request_mini_timeline(&mut self) {
+        let entity_id = self.entity_id;
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_mini_timeline = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_mini_timeline_entities(transaction, entity_id).await }
+        );
+    }
+
+    fn check_mini_timeline_response(&mut self) {
+        if let Some(rx) = self.rx_mini_timeline.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv entity view mini-timeline response");
+                    self.rx_mini_timeline = None;
+                    match result {
+                        Ok((entities, decades)) => {
+                            self.mini_timeline
+                                .set_date_limits(Some(decades.start()), decades.end());
+                            self.mini_timeline.set_entities(entities);
+                            self.mini_timeline.select_entities(vec![self.entity_id]);
+                        }
+                        Err(error) => warn!("Entity mini-timeline fetch error: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+    }
+
+    /// Draw the entity's span alongside the entities alive in the same decades
+    /// (clicking another entity views it)
+    fn draw_mini_timeline(&mut self, ctx: &Context, ui: &mut Ui) {
+        if self.rx_mini_timeline.is_some() {
+            ui.spinner();
+            return;
+        }
+        for event in self.mini_timeline.drain_interaction_events() {
+            match event {
+                TimelineInteractionEvent::SingleClick(entity_id)
+                | TimelineInteractionEvent::DoubleClick(entity_id)
+                | TimelineInteractionEvent::TripleClick(entity_id)
+                    if entity_id != self.entity_id =>
+                {
+                    let _ = self.tx_action_request.send(ActionRequest::Entity(
+                        EntityOrTimelineActionRequest::ViewExisting(entity_id),
+                    ));
+                }
+                _ => (),
+            }
+        }
+        update_timeline_colours(
+            ctx,
+            &self.shared_config,
+            &mut self.mini_timeline,
+            &mut self.mini_timeline_high_contrast,
+        );
+        update_timeline_date_format(&self.shared_config, &mut self.mini_timeline);
+        update_timeline_font(ctx, &self.shared_config, &mut self.mini_timeline);
+        let size = Vec2::new(ui.available_width(), MINI_TIMELINE_HEIGHT);
+        ui.allocate_ui(size, |ui| self.mini_timeline.draw(ctx, ui));
+    }
+
+    /// Draw the place name & coordinates (with a link to them on a map)
+    fn draw_location(location: &Location, ui: &mut Ui) {
+        ui.horizontal_wrapped(|ui| {
+            if let Some(place) = location.place() {
+                ui.label(place);
+            }
+            if let Some(coordinates) = location.coordinates() {
+                ui.label(
+                    RichText::new(format!(
+                        "{:.4}, {:.4}",
+                        coordinates.latitude(),
+                        coordinates.longitude()
+                    ))
+                    .weak(),
+                );
+                ui.hyperlink_to("Open map", coordinates.map_url());
+            }
+        });
+    }
+
+    /// Draw how well sourced the entity is and the sources themselves
+    fn draw_sources(entity: &Entity, ui: &mut Ui) {
+        let coverage = entity.source_coverage();
+        let (status, colour) = if coverage.is_unsourced() {
+            ("Unsourced", Color32::LIGHT_RED)
+        } else if coverage.is_well_sourced() {
+            ("Well sourced", Color32::DARK_GREEN)
+        } else {
+            ("Partially sourced", Color32::from_rgb(200, 130, 0))
+        };
+        ui.colored_label(
+            colour,
+            format!(
+                "{status} ({} of {} cited)",
+                coverage.cited, coverage.citable
+            ),
+        );
+        let Some(sources) = entity.sources() else {
+            return;
+        };
+        ScrollArea::vertical()
+            .id_salt("sources")
+            .max_height(ui.available_height() / 3.0)
+            .show(ui, |ui| {
+                for source in sources {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(RichText::new(source.scope().name()).weak());
+                        match source.url() {
+                            Some(url) => ui.hyperlink_to(source.title(), url),
+                            None => ui.label(source.title()),
+                        };
+                        if let Some(citation) = source.citation() {
+                            ui.label(RichText::new(citation).italics());
+                        }
+                    });
+                }
+            });
+    }
+
+    /// Draw the entities that overlap this one the longest (clicking one views
+    /// it)
+    fn draw_contemporaries(&self, ui: &mut Ui) {
+        let (Some(entity), Some(contemporaries)) = (&self.entity, &self.contemporaries) else {
+            ui.spinner();
+            return;
+        };
+        if contemporaries.is_empty() {
+            open_timeline_gui_core::Label::none(ui);
+            return;
+        }
+        let date_range = entity.date_range();
+        ScrollArea::vertical()
+            .id_salt("contemporaries")
+            .show(ui, |ui| {
+                for contemporary in contemporaries {
+                    let years = contemporary
+                        .date_range()
+                        .overlap(&date_range)
+                        .map_or(0, |overlap| overlap.whole_years());
+                    let unit = if years == 1 { "year" } else { "years" };
+                    let text = format!("{} ({years} {unit})", contemporary.name().as_str());
+                    if ui.link(text).clicked()
+                        && let Some(id) = contemporary.id()
+                    {
+                        let _ = self.tx_action_request.send(ActionRequest::Entity(
+                            EntityOrTimelineActionRequest::ViewExisting(id),
+                        ));
+                    }
+                }
+            });
+    }
 }
 
 impl Reload for EntityViewGui {
@@ -100,6 +349,8 @@ impl Reload for EntityViewGui {
             tx,
             |transaction| async move { Entity::fetch_by_id(transaction, &entity_id).await }
         );
+        self.request_contemporaries();
+        self.request_mini_timeline();
     }
 
     fn check_reload_response(&mut self) {
@@ -114,7 +365,13 @@ impl Reload for EntityViewGui {
                         Err(CrudError::IdNotInDb) => {
                             self.set_deleted_status(DeletedStatus::Deleted(Instant::now()))
                         }
-                        Err(_) => todo!(),
+                        Err(error) => {
+                            let msg = format!("Failed to reload entity: {error}");
+                            warn!(target: ACTIVITY, "{msg}");
+                            let _ = self
+                                .tx_action_request
+                                .send(ActionRequest::toast(Toast::error(msg)));
+                        }
                     }
                 }
                 Err(TryRecvError::Empty) => (),
@@ -137,10 +394,16 @@ impl Deleted for EntityViewGui {
 impl CheckForUpdates for EntityViewGui {
     fn check_for_updates(&mut self) {
         self.check_reload_response();
+        self.check_contemporaries_response();
+        self.check_mini_timeline_response();
+        self.star.check_for_updates();
     }
 
     fn waiting_for_updates(&mut self) -> bool {
-        let waiting = self.rx_reload.is_some();
+        let waiting = self.rx_reload.is_some()
+            || self.rx_contemporaries.is_some()
+            || self.rx_mini_timeline.is_some()
+            || self.star.waiting_for_updates();
         if waiting {
             info!("EntityViewGui is waiting for updates");
         }
@@ -187,64 +450,105 @@ impl BreakOutWindow for EntityViewGui {
 
             // Name
             open_timeline_gui_core::Label::heading(ui, entity.name().as_str());
-            ui.label(RichText::new("Entity").weak());
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Entity").weak());
+                self.star.draw(ctx, ui);
+                if ui
+                    .button("Copy JSON")
+                    .on_hover_text("Copy the entity as JSON (paste it into another window or app)")
+                    .clicked()
+                {
+                    copy_entity_to_clipboard(ctx, entity);
+                }
+            });
+            if let Some(aliases) = entity.aliases() {
+                let aliases = aliases
+                    .iter()
+                    .map(|alias| alias.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ui.add(egui::Label::new(format!("Also known as: {aliases}")).wrap());
+            }
             ui.separator();
 
             // Dates
-            let start_date_str = entity.start().as_long_date_format();
+            let date_format = self.shared_config.blocking_read().config.date_format;
+            let start_date_str = entity.start().format(&date_format);
             let end_date_str = entity
                 .end()
-                .map(|date| date.as_long_date_format())
+                .map(|date| date.format(&date_format))
                 .unwrap_or_default();
             let label_height = body_text_height(ui);
             ui.add_sized(
                 [available_width, label_height],
                 egui::Label::new(format!("{start_date_str}   –   {end_date_str}")),
             );
+            self.draw_mini_timeline(ctx, ui);
             ui.separator();
+            let entity = self.entity.as_ref().unwrap();
 
             // Tags
             open_timeline_gui_core::Label::sub_heading(ui, "Tags");
             if let Some(tags) = entity.tags() {
-                ScrollArea::vertical().show(ui, |ui| {
-                    TableBuilder::new(ui)
-                        .striped(true)
-                        .column(Column::exact(column_width))
-                        .column(Column::exact(column_width))
-                        .body(|mut body| {
-                            for tag in tags {
-                                body.row(row_height, |mut row| {
-                                    // Tag name
-                                    row.col(|ui| {
-                                        ui.with_layout(
-                                            Layout::right_to_left(Align::Center),
-                                            |ui| {
-                                                let name = match &tag.name {
-                                                    Some(name) => name.as_str(),
-                                                    None => "",
-                                                };
-                                                ui.add(egui::Label::new(name).truncate());
-                                            },
-                                        );
-                                    });
-                                    // Tag value
-                                    row.col(|ui: &mut Ui| {
-                                        ui.with_layout(
-                                            Layout::left_to_right(Align::Center),
-                                            |ui| {
-                                                ui.add(
-                                                    egui::Label::new(tag.value.as_str()).truncate(),
-                                                );
-                                            },
-                                        );
+                ScrollArea::vertical()
+                    .max_height(ui.available_height() / 2.0)
+                    .show(ui, |ui| {
+                        TableBuilder::new(ui)
+                            .striped(true)
+                            .column(Column::exact(column_width))
+                            .column(Column::exact(column_width))
+                            .body(|mut body| {
+                                for tag in tags {
+                                    body.row(row_height, |mut row| {
+                                        // Tag name
+                                        row.col(|ui| {
+                                            ui.with_layout(
+                                                Layout::right_to_left(Align::Center),
+                                                |ui| {
+                                                    let name = match &tag.name {
+                                                        Some(name) => name.as_str(),
+                                                        None => "",
+                                                    };
+                                                    ui.add(egui::Label::new(name).truncate());
+                                                },
+                                            );
+                                        });
+                                        // Tag value
+                                        row.col(|ui: &mut Ui| {
+                                            ui.with_layout(
+                                                Layout::left_to_right(Align::Center),
+                                                |ui| {
+                                                    ui.add(
+                                                        egui::Label::new(tag.value.as_str())
+                                                            .truncate(),
+                                                    );
+                                                },
+                                            );
+                                        });
                                     });
-                                });
-                            }
-                        });
-                });
+                                }
+                            });
+                    });
             } else {
                 open_timeline_gui_core::Label::none(ui);
             }
+            ui.separator();
+
+            // Location
+            if let Some(location) = entity.location() {
+                open_timeline_gui_core::Label::sub_heading(ui, "Location");
+                Self::draw_location(location, ui);
+                ui.separator();
+            }
+
+            // Sources
+            open_timeline_gui_core::Label::sub_heading(ui, "Sources");
+            Self::draw_sources(entity, ui);
+            ui.separator();
+
+            // Overlaps
+            open_timeline_gui_core::Label::sub_heading(ui, "Overlaps");
+            self.draw_contemporaries(ui);
         });
     }
 
@@ -273,3 +577,17 @@ impl BreakOutWindow for EntityViewGui {
         self.wants_to_be_closed
     }
 }
+
+/// Fetch the entity & (some of) the entities alive in the same decades as it
+async fn fetch_mini_timeline_entities(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity_id: OpenTimelineId,
+) -> Result<MiniTimelineEntities, CrudError> {
+    let entity = Entity::fetch_by_id(transaction, &entity_id).await?;
+    let decades = entity.date_range().decades();
+    let mut entities = entities_alive_during(transaction, &decades).await?;
+    entities.retain(|other| other.id() != Some(entity_id));
+    entities.truncate(MINI_TIMELINE_ENTITIES_LIMIT);
+    entities.push(entity);
+    Ok((entities, decades))
+}
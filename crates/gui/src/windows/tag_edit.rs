@@ -6,21 +6,27 @@
 
 // TODO: bulk edit timeline vs entity vs both tags
 
-use crate::app::ActionRequest;
+use crate::activity_log::ACTIVITY;
+use crate::app::{ActionRequest, TagActionRequest};
 use crate::common::*;
 use crate::components::TagGui;
 use crate::config::SharedConfig;
 use crate::consts::DEFAULT_WINDOW_SIZES;
 use crate::shortcuts::global_shortcuts;
 use crate::windows::{Deleted, DeletedStatus};
-use bool_tag_expr::Tag;
-use eframe::egui::{self, CentralPanel, Context, Response, Ui, Vec2, ViewportId};
-use open_timeline_crud::{CrudError, delete_all_matching_tags, update_all_matching_entity_tags};
+use crate::{spawn_operation, spawn_transaction_no_commit_send_result};
+use bool_tag_expr::{Tag, TagValue};
+use eframe::egui::{self, CentralPanel, Context, Response, TextEdit, Ui, Vec2, ViewportId};
+use open_timeline_core::{ErrorChain, TagHierarchy};
+use open_timeline_crud::{
+    CrudError, delete_all_matching_tags, fetch_tag_hierarchy, set_tag_parent,
+    update_all_matching_entity_tags,
+};
 use open_timeline_gui_core::{
     BreakOutWindow, CheckForUpdates, DisplayStatus, Draw, GuiStatus, Reload, Valid,
     ValidityAsynchronous, window_has_focus,
 };
-use open_timeline_gui_core::{Shortcut, ShowRemoveButton};
+use open_timeline_gui_core::{Shortcut, ShowRemoveButton, Toast};
 use std::hash::{DefaultHasher, Hash, Hasher};
 use std::sync::Arc;
 use std::time::Instant;
@@ -36,6 +42,12 @@ pub struct TagBulkEditGui {
     /// The GUI component for inputting/validating/etc the new tag
     new_tag_gui: TagGui,
 
+    /// The value of the tag's parent in the database (if it has one)
+    database_parent: Option<TagValue>,
+
+    /// The value of the tag's new parent (empty for none)
+    parent_input: String,
+
     /// Whether the tag has been completely deleted or not.  If it has been, the
     /// `Deleted` variant holds the `Instant` it was deleted.
     deleted_status: DeletedStatus,
@@ -49,6 +61,12 @@ pub struct TagBulkEditGui {
     /// Receive delete operation updates (if a deletion has been requested)
     rx_delete: Option<Receiver<Result<(), CrudError>>>,
 
+    /// Receive the tag hierarchy (if a reload has been requested)
+    rx_reload: Option<Receiver<Result<TagHierarchy, CrudError>>>,
+
+    /// Receive set parent operation updates (if setting it has been requested)
+    rx_set_parent: Option<Receiver<Result<Option<TagValue>, CrudError>>>,
+
     /// Send an action request to the main loop
     tx_action_request: UnboundedSender<ActionRequest>,
 
@@ -70,11 +88,14 @@ enum Status {
     NoChanges,
     InvalidUpdate(String),
     InvalidDelete(String),
+    InvalidParent(String),
     WaitingForValidity,
     FailedToUpdate(CrudError),
     FailedToDelete(Tag, CrudError),
+    FailedToSetParent(CrudError),
     SucessfullyUpdated,
     SucessfullyDeleted(Tag),
+    SucessfullySetParent,
 }
 
 impl DisplayStatus for Status {
@@ -83,15 +104,26 @@ impl DisplayStatus for Status {
             Self::NoChanges => String::from("No changes to save"),
             Self::InvalidUpdate(error) => format!("Tag can't be updated (error: {error})"),
             Self::InvalidDelete(error) => format!("Tag can't be deleted (error: {error})"),
+            Self::InvalidParent(error) => format!("Invalid parent (error: {error})"),
             Self::WaitingForValidity => String::from("Waiting for validation"),
             Self::FailedToUpdate(error) => format!("Failed to update tag: {error}"),
             Self::FailedToDelete(tag, error) => {
                 format!("Failed to delete '{tag}': {error}")
             }
+            Self::FailedToSetParent(error) => format!("Failed to set the parent: {error}"),
             Self::SucessfullyUpdated => String::from("Updated tag"),
             Self::SucessfullyDeleted(tag) => format!("Sucessfully deleted '{tag}'"),
+            Self::SucessfullySetParent => String::from("Set the tag's parent"),
         };
-        ui.add(egui::Label::new(str).truncate())
+        let response = ui.add(egui::Label::new(str).truncate());
+        match &self {
+            Self::FailedToUpdate(error)
+            | Self::FailedToDelete(_, error)
+            | Self::FailedToSetParent(error) => {
+                response.on_hover_text(ErrorChain(error).to_string())
+            }
+            _ => response,
+        }
     }
 }
 
@@ -103,18 +135,24 @@ impl TagBulkEditGui {
         tx_crud_operation_executed: UnboundedSender<()>,
         tag: Tag,
     ) -> Self {
-        TagBulkEditGui {
+        let mut tag_bulk_edit_gui = TagBulkEditGui {
             database_entry: tag.clone(),
             new_tag_gui: TagGui::from_tag(tag, ShowRemoveButton::No),
+            database_parent: None,
+            parent_input: String::new(),
             deleted_status: DeletedStatus::NotDeleted,
             status: Status::NoChanges,
             rx_update: None,
             rx_delete: None,
+            rx_reload: None,
+            rx_set_parent: None,
             tx_action_request,
             tx_crud_operation_executed,
             wants_to_be_closed: false,
             shared_config,
-        }
+        };
+        tag_bulk_edit_gui.request_reload();
+        tag_bulk_edit_gui
     }
 
     /// Get the tag being edited
@@ -162,12 +200,12 @@ impl TagBulkEditGui {
         self.rx_update = Some(rx);
         let old_tag = self.tag().to_owned();
         let shared_config = Arc::clone(&self.shared_config);
-        tokio::spawn(async move {
+        spawn_operation!(async move {
             let result = async {
                 let mut transaction = shared_config.read().await.db_pool.begin().await?;
                 let _ = update_all_matching_entity_tags(&mut transaction, old_tag, new_tag).await?;
                 // TODO: is this the correct error variant?
-                transaction.commit().await.map_err(|_| CrudError::DbError)?;
+                transaction.commit().await?;
                 Ok(())
             }
             .await;
@@ -175,16 +213,47 @@ impl TagBulkEditGui {
         });
     }
 
+    /// Set the tag's parent to the one input (none if the input is empty)
+    fn request_set_parent(&mut self) {
+        if self.has_been_deleted() {
+            return;
+        }
+        let parent = match self.parent_input.trim() {
+            "" => None,
+            parent => match TagValue::from(&parent) {
+                Ok(parent) => Some(parent),
+                Err(error) => {
+                    self.status = Status::InvalidParent(error.to_string());
+                    return;
+                }
+            },
+        };
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_set_parent = Some(rx);
+        let tag = self.tag().to_owned();
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                set_tag_parent(&mut transaction, &tag, parent.as_ref()).await?;
+                transaction.commit().await?;
+                Ok(parent)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
     fn delete(&mut self, tag: Tag) {
         let (tx, rx) = tokio::sync::mpsc::channel(1);
         self.rx_delete = Some(rx);
         let shared_config = Arc::clone(&self.shared_config);
-        tokio::spawn(async move {
+        spawn_operation!(async move {
             let result = async {
                 let mut transaction = shared_config.read().await.db_pool.begin().await?;
                 delete_all_matching_tags(&mut transaction, tag).await?;
                 // TODO: is this the correct error variant?
-                transaction.commit().await.map_err(|_| CrudError::DbError)?;
+                transaction.commit().await?;
                 Ok(())
             }
             .await;
@@ -206,9 +275,18 @@ impl TagBulkEditGui {
                             self.status = Status::SucessfullyUpdated;
                             // TODO: this could fail (become invalid) - send back the new tag from database
                             self.database_entry = self.new_tag_gui.to_opentimeline_type();
+                            info!(target: ACTIVITY, "Updated tag {}", self.database_entry);
+                            let tag = self.database_entry.clone();
+                            let toast = Toast::success(format!("Updated tag {tag}")).with_action(
+                                ActionRequest::Tag(TagActionRequest::ViewExisting(tag)),
+                            );
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             let _ = self.tx_crud_operation_executed.send(());
                         }
                         Err(error) => {
+                            warn!(target: ACTIVITY, "Failed to update tag: {}", ErrorChain(&error));
+                            let toast = Toast::error(format!("Failed to update tag: {error}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.status = Status::FailedToUpdate(error);
                         }
                     }
@@ -218,6 +296,33 @@ impl TagBulkEditGui {
             }
         }
 
+        // Response to set parent request
+        if let Some(rx) = self.rx_set_parent.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv tag bulk edit set parent request response");
+                    self.rx_set_parent = None;
+                    match result {
+                        Ok(parent) => {
+                            info!(target: ACTIVITY, "Set the parent of tag {}", self.database_entry);
+                            self.status = Status::SucessfullySetParent;
+                            self.database_parent = parent;
+                            let _ = self.tx_crud_operation_executed.send(());
+                        }
+                        Err(error) => {
+                            let msg = "Failed to set the tag's parent";
+                            warn!(target: ACTIVITY, "{msg}: {}", ErrorChain(&error));
+                            let toast = Toast::error(format!("{msg}: {error}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
+                            self.status = Status::FailedToSetParent(error);
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+
         // Response to delete request
         if let Some(rx) = self.rx_delete.as_mut() {
             let deleted_tag = self.database_entry.clone();
@@ -227,11 +332,20 @@ impl TagBulkEditGui {
                     self.rx_delete = None;
                     match result {
                         Ok(()) => {
+                            info!(target: ACTIVITY, "Deleted tag {deleted_tag}");
+                            let toast = Toast::success(format!("Deleted tag {deleted_tag}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.status = Status::SucessfullyDeleted(deleted_tag);
                             self.set_deleted_status(DeletedStatus::Deleted(Instant::now()));
                             let _ = self.tx_crud_operation_executed.send(());
                         }
-                        Err(error) => self.status = Status::FailedToDelete(deleted_tag, error),
+                        Err(error) => {
+                            let msg = format!("Failed to delete tag {deleted_tag}");
+                            warn!(target: ACTIVITY, "{msg}: {}", ErrorChain(&error));
+                            let toast = Toast::error(format!("{msg}: {error}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
+                            self.status = Status::FailedToDelete(deleted_tag, error);
+                        }
                     }
                 }
                 Err(TryRecvError::Empty) => (),
@@ -246,6 +360,12 @@ impl TagBulkEditGui {
         self.new_tag_gui = TagGui::from_tag(self.database_entry.clone(), ShowRemoveButton::No)
     }
 
+    /// Whether the parent input differs from the parent in the database
+    fn parent_differs_from_database(&self) -> bool {
+        let database_parent = self.database_parent.as_ref().map(TagValue::as_str);
+        self.parent_input.trim() != database_parent.unwrap_or_default()
+    }
+
     // TODO: use an enum instead of Option<bool>
     // TODO: trait
     /// Whether the tag differs from the one in the database
@@ -263,10 +383,41 @@ impl Reload for TagBulkEditGui {
     fn request_reload(&mut self) {
         // TODO: if the Tag is no longer in the database (deleted elsewhere),
         // close the window
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_reload = Some(rx);
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_tag_hierarchy(transaction).await }
+        );
     }
 
     fn check_reload_response(&mut self) {
-        // TODO: see request_reload()
+        if let Some(rx) = self.rx_reload.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv tag bulk edit reload response");
+                    self.rx_reload = None;
+                    match result {
+                        Ok(tag_hierarchy) => {
+                            self.database_parent = tag_hierarchy
+                                .parent(&self.database_entry)
+                                .map(|parent| parent.value);
+                            self.parent_input = self
+                                .database_parent
+                                .as_ref()
+                                .map(|parent| parent.to_string())
+                                .unwrap_or_default();
+                        }
+                        Err(error) => warn!("Error fetching tag hierarchy: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
     }
 }
 
@@ -287,7 +438,10 @@ impl CheckForUpdates for TagBulkEditGui {
     }
 
     fn waiting_for_updates(&mut self) -> bool {
-        let waiting = self.rx_update.is_some() || self.rx_delete.is_some();
+        let waiting = self.rx_update.is_some()
+            || self.rx_delete.is_some()
+            || self.rx_reload.is_some()
+            || self.rx_set_parent.is_some();
         if waiting {
             info!("TagBulkEditGui is waiting for updates");
         }
@@ -363,6 +517,22 @@ impl BreakOutWindow for TagBulkEditGui {
             ui.label(format!("{}", self.database_entry));
             ui.separator();
 
+            // Parent (a tag with the same name, which the tag is nested under)
+            open_timeline_gui_core::Label::sub_heading(ui, "Parent");
+            ui.horizontal(|ui| {
+                if self.parent_differs_from_database()
+                    && open_timeline_gui_core::Button::update(ui).clicked()
+                {
+                    self.request_set_parent();
+                }
+                ui.add(
+                    TextEdit::singleline(&mut self.parent_input)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("None"),
+                );
+            });
+            ui.separator();
+
             // New tag
             open_timeline_gui_core::Label::sub_heading(ui, "New");
             ui.add_enabled_ui(true, |ui| self.new_tag_gui.draw(ctx, ui));
@@ -391,4 +561,24 @@ impl BreakOutWindow for TagBulkEditGui {
     fn wants_to_be_closed(&mut self) -> bool {
         self.wants_to_be_closed
     }
+
+    fn is_editor(&self) -> bool {
+        true
+    }
+
+    fn has_unsaved_changes(&mut self) -> bool {
+        !self.has_been_deleted() && self.differs_from_database_entry() != Some(false)
+    }
+
+    fn can_save(&mut self) -> bool {
+        self.differs_from_database_entry() == Some(true)
+    }
+
+    fn save(&mut self) {
+        self.request_update();
+    }
+
+    fn cancel_close(&mut self) {
+        self.wants_to_be_closed = false;
+    }
 }
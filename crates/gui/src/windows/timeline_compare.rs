@@ -0,0 +1,417 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The compare timelines GUI
+//!
+
+use crate::app::{ActionRequest, EntityOrTimelineActionRequest};
+use crate::common::ToOpenTimelineType;
+use crate::components::TimelineSubtimelineGui;
+use crate::config::SharedConfig;
+use crate::consts::DEFAULT_WINDOW_SIZES;
+use crate::shortcuts::global_shortcuts;
+use crate::spawn_transaction_no_commit_send_result;
+use crate::windows::{update_timeline_colours, update_timeline_date_format, update_timeline_font};
+use eframe::egui::{CentralPanel, Context, Id, RichText, Slider, Ui, Vec2, ViewportId};
+use open_timeline_core::{IsReducedType, OpenTimelineId, ReducedTimeline, TimelineView};
+use open_timeline_crud::{CrudError, FetchById};
+use open_timeline_gui_core::{
+    BreakOutWindow, CheckForUpdates, Draw, Reload, Shortcut, ShowRemoveButton, Valid,
+    ValidityAsynchronous, font_size, window_has_focus,
+};
+use open_timeline_renderer::frontends::desktop_egui::OpenTimelineRendererEgui;
+use open_timeline_renderer::{
+    MAX_DATETIME_SCALE, MIN_DATETIME_SCALE, TimelineInteractionEvent, Viewport,
+};
+use std::sync::Arc;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
+
+/// The parts of a [`Viewport`] that timelines sharing an axis have in common
+/// (zoom, datetime scale, and horizontal offset)
+type XAxis = (f64, f64, f64);
+
+fn x_axis(viewport: Viewport) -> XAxis {
+    (viewport.zoom, viewport.datetime_scale, viewport.offset_x)
+}
+
+/// One of the timelines being compared
+struct ComparedTimeline {
+    /// Search for the timeline to show
+    picker: TimelineSubtimelineGui,
+
+    /// The timeline being shown (if one has been chosen)
+    timeline: Option<ReducedTimeline>,
+
+    /// The decades the timeline's entities span (if it has any)
+    years: Option<(i32, i32)>,
+
+    /// The renderer (engine frontend) that draws the timeline
+    renderer: OpenTimelineRendererEgui,
+
+    /// Receive reloaded data
+    rx_reload: Option<Receiver<Result<TimelineView, CrudError>>>,
+
+    /// Whether the renderer is currently set up for high contrast
+    high_contrast: bool,
+}
+
+impl ComparedTimeline {
+    fn new(shared_config: SharedConfig, ctx: &Context, timeline: Option<ReducedTimeline>) -> Self {
+        let picker = match timeline.clone() {
+            Some(timeline) => TimelineSubtimelineGui::from_reduced_timeline(
+                shared_config,
+                ShowRemoveButton::No,
+                timeline,
+            ),
+            None => TimelineSubtimelineGui::new(shared_config, ShowRemoveButton::No),
+        };
+        let mut renderer = OpenTimelineRendererEgui::new(ctx);
+        renderer.set_font_size_px(font_size(ctx) as f64);
+        Self {
+            picker,
+            timeline,
+            years: None,
+            renderer,
+            rx_reload: None,
+            high_contrast: false,
+        }
+    }
+
+    fn request_reload(&mut self, shared_config: &SharedConfig) {
+        let Some(timeline_id) = self.timeline.as_ref().map(|timeline| timeline.id()) else {
+            return;
+        };
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_reload = Some(rx);
+        let shared_config = Arc::clone(shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { TimelineView::fetch_by_id(transaction, &timeline_id).await }
+        );
+    }
+
+    /// Returns whether the timeline's entities changed
+    fn check_reload_response(&mut self) -> bool {
+        let Some(rx) = self.rx_reload.as_mut() else {
+            return false;
+        };
+        match rx.try_recv() {
+            Ok(result) => {
+                debug!("Recv timeline compare reload response");
+                self.rx_reload = None;
+                let entities = match result {
+                    Ok(timeline) => timeline.entities().clone().unwrap_or_default(),
+                    Err(CrudError::IdNotInDb) => {
+                        self.timeline = None;
+                        Vec::new()
+                    }
+                    Err(error) => {
+                        warn!("Timeline compare fetch error: {error}");
+                        return false;
+                    }
+                };
+                self.renderer.set_shared_axis_years(None);
+                self.renderer.set_entities(entities);
+                self.years =
+                    (self.renderer.entity_count() > 0).then(|| self.renderer.start_and_end_dates());
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Disconnected) => {
+                self.rx_reload = None;
+                false
+            }
+        }
+    }
+
+    /// Request the timeline chosen using the picker (if it's a different one)
+    fn check_picker(&mut self, shared_config: &SharedConfig) {
+        if self.picker.validity() != ValidityAsynchronous::Valid {
+            return;
+        }
+        let picked = self.picker.to_opentimeline_type();
+        if self.timeline.as_ref() != Some(&picked) {
+            self.timeline = Some(picked);
+            self.request_reload(shared_config);
+        }
+    }
+
+    fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
+        match (&self.timeline, self.years) {
+            (None, _) => {
+                open_timeline_gui_core::Label::weak(ui, "Choose a timeline to compare");
+            }
+            (Some(_), _) if self.rx_reload.is_some() => {
+                ui.spinner();
+            }
+            (Some(timeline), None) => {
+                let text = format!(
+                    "The '{}' timeline doesn't have any entities to show",
+                    timeline.name()
+                );
+                open_timeline_gui_core::Label::weak(ui, &text);
+            }
+            (Some(_), Some(_)) => self.renderer.draw(ctx, ui),
+        }
+    }
+}
+
+/// Compare two timelines, one above the other, with a shared time axis (moving
+/// or zooming either moves or zooms both)
+pub struct TimelineCompareGui {
+    /// Uniquely identifies this window (more than one can be open at once)
+    id: OpenTimelineId,
+
+    /// The timelines being compared (top and bottom)
+    timelines: [ComparedTimeline; 2],
+
+    /// The time axis the timelines were last lined up on
+    synced_x_axis: Option<XAxis>,
+
+    /// Used to request that an entity be viewed
+    tx_action_request: UnboundedSender<ActionRequest>,
+
+    /// Whether this window should be closed or not
+    wants_to_be_closed: bool,
+
+    /// Database pool
+    shared_config: SharedConfig,
+
+    datetime_scaling: f64,
+    sticky_text: bool,
+}
+
+impl TimelineCompareGui {
+    /// Create a new timeline comparison window, with the given timeline shown
+    /// at the top
+    pub fn new(
+        shared_config: SharedConfig,
+        ctx: &Context,
+        tx_action_request: UnboundedSender<ActionRequest>,
+        timeline: ReducedTimeline,
+    ) -> Self {
+        let mut timeline_compare_gui = Self {
+            id: OpenTimelineId::new(),
+            timelines: [
+                ComparedTimeline::new(Arc::clone(&shared_config), ctx, Some(timeline)),
+                ComparedTimeline::new(Arc::clone(&shared_config), ctx, None),
+            ],
+            synced_x_axis: None,
+            tx_action_request,
+            wants_to_be_closed: false,
+            shared_config,
+            datetime_scaling: 1.0,
+            sticky_text: true,
+        };
+        timeline_compare_gui.request_reload();
+        timeline_compare_gui
+    }
+
+    /// Make both timelines span the same decades so that their axes line up
+    fn update_shared_axis_years(&mut self) {
+        let years = self
+            .timelines
+            .iter()
+            .filter_map(|timeline| timeline.years)
+            .reduce(|(a_start, a_end), (b_start, b_end)| (a_start.min(b_start), a_end.max(b_end)));
+        for timeline in &mut self.timelines {
+            timeline.renderer.set_shared_axis_years(years);
+        }
+        self.synced_x_axis = None;
+    }
+
+    /// Keep the timelines' time axes lined up, following whichever one the user
+    /// last moved or zoomed
+    fn sync_x_axes(&mut self, ctx: &Context) {
+        let [top, bottom] = &mut self.timelines;
+        let (top_viewport, bottom_viewport) = (top.renderer.viewport(), bottom.renderer.viewport());
+        if self.synced_x_axis != Some(x_axis(top_viewport)) {
+            bottom.renderer.sync_x_axis(&top_viewport);
+        } else if self.synced_x_axis != Some(x_axis(bottom_viewport)) {
+            top.renderer.sync_x_axis(&bottom_viewport);
+        } else {
+            return;
+        }
+        self.synced_x_axis = Some(x_axis(top.renderer.viewport()));
+        ctx.request_repaint();
+    }
+
+    fn draw_controls(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            // Sticky text
+            if ui.checkbox(&mut self.sticky_text, "Sticky Text").changed() {
+                for timeline in &mut self.timelines {
+                    timeline.renderer.set_sticky_text(self.sticky_text);
+                }
+            }
+            ui.separator();
+
+            // Zoom (the other timeline follows)
+            let [top, _] = &mut self.timelines;
+            if ui.button("Zoom Out").clicked() {
+                top.renderer.zoom_out(1.1, 0.0, 0.0);
+            }
+            if ui.button("Zoom In").clicked() {
+                top.renderer.zoom_in(1.1, 0.0, 0.0);
+            }
+            ui.separator();
+
+            // x-scaling
+            ui.label("Scale Date");
+            ui.scope(|ui| {
+                let slider = Slider::new(
+                    &mut self.datetime_scaling,
+                    MIN_DATETIME_SCALE..=MAX_DATETIME_SCALE,
+                )
+                .logarithmic(true)
+                .show_value(false);
+                ui.spacing_mut().slider_width = ui.available_width();
+                if ui.add(slider).changed() {
+                    for timeline in &mut self.timelines {
+                        timeline.renderer.set_datetime_scale(self.datetime_scaling);
+                    }
+                };
+            });
+        });
+    }
+}
+
+impl Reload for TimelineCompareGui {
+    fn request_reload(&mut self) {
+        for timeline in &mut self.timelines {
+            timeline.request_reload(&self.shared_config);
+        }
+    }
+
+    fn check_reload_response(&mut self) {
+        let mut changed = false;
+        for timeline in &mut self.timelines {
+            changed |= timeline.check_reload_response();
+        }
+        if changed {
+            self.update_shared_axis_years();
+        }
+    }
+}
+
+impl CheckForUpdates for TimelineCompareGui {
+    fn check_for_updates(&mut self) {
+        self.check_reload_response();
+    }
+
+    fn waiting_for_updates(&mut self) -> bool {
+        let waiting = self
+            .timelines
+            .iter()
+            .any(|timeline| timeline.rx_reload.is_some());
+        if waiting {
+            info!("TimelineCompareGui is waiting for updates");
+        }
+        waiting
+    }
+}
+
+impl BreakOutWindow for TimelineCompareGui {
+    fn draw(&mut self, ctx: &Context) {
+        // Handle shortcuts
+        if window_has_focus(ctx) && Shortcut::close_window(ctx) {
+            self.wants_to_be_closed = true;
+        }
+
+        // Check for global shortcuts
+        global_shortcuts(ctx, &mut self.tx_action_request);
+
+        // Draw
+        CentralPanel::default().show(ctx, |ui| {
+            open_timeline_gui_core::Label::heading(ui, "Compare Timelines");
+            ui.label(RichText::new("Timelines").weak());
+            ui.separator();
+
+            // Choose the timelines
+            ui.columns(2, |columns| {
+                for (timeline, ui) in self.timelines.iter_mut().zip(columns) {
+                    timeline.picker.draw(ctx, ui);
+                    timeline.check_picker(&self.shared_config);
+                }
+            });
+            ui.separator();
+
+            // Controls
+            self.draw_controls(ui);
+            ui.separator();
+
+            // Update colours, date format & font
+            for timeline in &mut self.timelines {
+                update_timeline_colours(
+                    ctx,
+                    &self.shared_config,
+                    &mut timeline.renderer,
+                    &mut timeline.high_contrast,
+                );
+                update_timeline_date_format(&self.shared_config, &mut timeline.renderer);
+                update_timeline_font(ctx, &self.shared_config, &mut timeline.renderer);
+            }
+
+            // Draw the timelines, one above the other
+            let height = (ui.available_height() - ui.spacing().item_spacing.y * 2.0) / 2.0;
+            for (index, timeline) in self.timelines.iter_mut().enumerate() {
+                ui.push_id(index, |ui| {
+                    ui.allocate_ui(Vec2::new(ui.available_width(), height), |ui| {
+                        timeline.draw(ctx, ui);
+                    });
+                });
+                if index == 0 {
+                    ui.separator();
+                }
+            }
+            self.sync_x_axes(ctx);
+
+            // View clicked entities
+            for timeline in &mut self.timelines {
+                for event in timeline.renderer.drain_interaction_events() {
+                    match event {
+                        TimelineInteractionEvent::SingleClick(entity_id)
+                        | TimelineInteractionEvent::DoubleClick(entity_id)
+                        | TimelineInteractionEvent::TripleClick(entity_id) => {
+                            let _ = self.tx_action_request.send(ActionRequest::Entity(
+                                EntityOrTimelineActionRequest::ViewExisting(entity_id),
+                            ));
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        });
+    }
+
+    fn default_size(&self) -> Vec2 {
+        Vec2::new(
+            DEFAULT_WINDOW_SIZES.timeline_compare.width,
+            DEFAULT_WINDOW_SIZES.timeline_compare.height,
+        )
+    }
+
+    fn viewport_id(&mut self) -> ViewportId {
+        ViewportId(Id::from(format!("timeline_compare_{}", self.id)))
+    }
+
+    fn title(&mut self) -> String {
+        let names: Vec<_> = self
+            .timelines
+            .iter()
+            .filter_map(|timeline| timeline.timeline.as_ref())
+            .map(|timeline| timeline.name().as_str())
+            .collect();
+        match names.is_empty() {
+            true => String::from("Compare Timelines"),
+            false => format!("Compare Timelines • {}", names.join(" & ")),
+        }
+    }
+
+    fn wants_to_be_closed(&mut self) -> bool {
+        self.wants_to_be_closed
+    }
+}
@@ -4,36 +4,41 @@
 //! The edit timeline GUI
 //!
 
-use crate::app::ActionRequest;
+use crate::activity_log::ACTIVITY;
+use crate::app::{ActionRequest, EntityOrTimelineActionRequest};
+use crate::clipboard::pasted_entities;
 use crate::components::{
-    BooleanExpressionGui, EntityOrTimeline, HintText, NameGui, TagsGui, TimelineEntitiesGui,
-    TimelineSubtimelinesGui,
+    BooleanExpressionGui, DeleteConfirmationGui, EntityOrTimeline, HintText, NameGui, TagsGui,
+    TimelineEntitiesGui, TimelineSubtimelinesGui,
 };
 use crate::config::SharedConfig;
 use crate::consts::DEFAULT_WINDOW_SIZES;
 use crate::shortcuts::global_shortcuts;
+use crate::spawn_operation;
 use crate::windows::{Deleted, DeletedStatus};
-use crate::{
-    common::*, impl_is_valid_method_for_iterable, impl_valid_asynchronous_macro_never_called,
-    spawn_transaction_no_commit_send_result,
-};
+use crate::{common::*, spawn_transaction_no_commit_send_result};
+use bool_tag_expr::{BoolTagExpr, Tags};
 use eframe::egui::{
     self, CentralPanel, Context, Response, ScrollArea, Spinner, Ui, Vec2, ViewportId,
 };
-use open_timeline_core::{HasIdAndName, OpenTimelineId, TimelineEdit};
+use open_timeline_core::{
+    ErrorChain, HasIdAndName, Name, OpenTimelineId, ReducedEntities, ReducedTimelines, TimelineEdit,
+};
 use open_timeline_crud::{CrudError, FetchById};
 use open_timeline_gui_core::{
     BreakOutWindow, CheckForUpdates, CreateOrEdit, DisplayStatus, Draw, EmptyConsideredInvalid,
-    GuiStatus, Reload, Shortcut, ShowRemoveButton, Valid, ValidSynchronous, ValidityAsynchronous,
-    ValiditySynchronous, window_has_focus,
+    GuiStatus, Reload, Shortcut, ShowRemoveButton, Toast, Valid, ValidSynchronous,
+    ValidityAsynchronous, window_has_focus,
 };
+use open_timeline_macros::GuiComponent;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::{Receiver, error::TryRecvError};
 
 /// Edit a timeline
-#[derive(Debug)]
+#[derive(Debug, GuiComponent)]
+#[gui_component(opentimeline_type = "TimelineEdit", from_parts = "timeline_from_parts")]
 pub struct TimelineEditGui {
     /// The the timeline being edited as it is in the database (if one is being
     /// edited, rather than created)
@@ -44,18 +49,24 @@ pub struct TimelineEditGui {
     timeline_id: Option<OpenTimelineId>,
 
     /// The name input
+    #[gui_component]
     name: NameGui,
 
-    /// The bool expr input
+    /// The bool expr input (only considered when the timeline has an
+    /// expression)
+    #[gui_component(when = "has_expr")]
     bool_expr: BooleanExpressionGui,
 
     /// The entity inputs
+    #[gui_component]
     entities: TimelineEntitiesGui,
 
     /// The subtimeline inputs
+    #[gui_component]
     subtimelines: TimelineSubtimelinesGui,
 
     /// The tag inputs
+    #[gui_component]
     tags: TagsGui,
 
     /// Whether or not the a boolean expression is extant.  When editing a
@@ -84,9 +95,16 @@ pub struct TimelineEditGui {
     /// Recevie updates on deletion
     rx_delete: Option<Receiver<Result<(), CrudError>>>,
 
+    /// Asks for confirmation before deleting (listing what will be affected)
+    delete_confirmation: DeleteConfirmationGui,
+
     /// Receive reloaded data
     rx_reload: Option<Receiver<Result<TimelineEdit, CrudError>>>,
 
+    /// Receive the latest version of the timeline (so that the user's changes
+    /// can be re-applied to it after a conflicting edit)
+    rx_reapply: Option<Receiver<Result<TimelineEdit, CrudError>>>,
+
     /// Whether or not a reload has been requested
     requested_reload: bool,
 
@@ -119,6 +137,8 @@ enum Status {
 
     Created,
     Updated,
+    Reapplied,
+    Pasted(usize),
 
     Valid,
     Invalid(String),
@@ -144,76 +164,22 @@ impl DisplayStatus for Status {
             }
             Self::Created => String::from("Timeline successfully created"),
             Self::Updated => String::from("Timeline successfully updated"),
+            Self::Reapplied => String::from(
+                "Your changes have been re-applied to the latest timeline (review & update)",
+            ),
+            Self::Pasted(count) => format!(
+                "Added {count} pasted entities (any not in this database need creating first)"
+            ),
             Self::Valid => String::from("Timeline is valid"),
             Self::Invalid(error) => format!("Timeline is invalid: {error}"),
         };
-        ui.add(egui::Label::new(str).truncate())
-    }
-}
-
-///
-///
-/// We do not have to worry about the validity of self.bool_expr when the
-/// timeline doesn't have a bool expr because self.validity() handles it
-impl ValidSynchronous for TimelineEditGui {
-    fn is_valid_synchronous(&self) -> bool {
-        self.name.is_valid_synchronous()
-            && self.bool_expr.is_valid_synchronous()
-            && self.subtimelines.is_valid_synchronous()
-            && self.entities.is_valid_synchronous()
-            && self.tags.is_valid_synchronous()
-    }
-
-    fn update_validity_synchronous(&mut self) {
-        self.name.update_validity_synchronous();
-        self.bool_expr.update_validity_synchronous();
-        self.subtimelines.update_validity_synchronous();
-        self.entities.update_validity_synchronous();
-        self.tags.update_validity_synchronous();
-    }
-
-    fn validity_synchronous(&self) -> ValiditySynchronous {
-        for validity in [
-            self.name.validity_synchronous(),
-            self.bool_expr.validity_synchronous(),
-            self.subtimelines.validity_synchronous(),
-            self.entities.validity_synchronous(),
-            self.tags.validity_synchronous(),
-        ] {
-            match validity {
-                ValiditySynchronous::Invalid(error) => return ValiditySynchronous::Invalid(error),
-                ValiditySynchronous::Valid => continue,
+        let response = ui.add(egui::Label::new(str).truncate());
+        match &self {
+            Self::CreateError(error) | Self::UpdateError(error) | Self::DeleteError(error) => {
+                response.on_hover_text(ErrorChain(error).to_string())
             }
+            _ => response,
         }
-        ValiditySynchronous::Valid
-    }
-}
-
-impl_valid_asynchronous_macro_never_called!(TimelineEditGui);
-
-impl Valid for TimelineEditGui {
-    fn validity(&self) -> ValidityAsynchronous {
-        if self.has_expr {
-            impl_is_valid_method_for_iterable!([
-                self.name.validity(),
-                self.bool_expr.validity(),
-                self.entities.validity(),
-                self.subtimelines.validity(),
-                self.tags.validity(),
-            ])
-        } else {
-            impl_is_valid_method_for_iterable!([
-                self.name.validity(),
-                self.entities.validity(),
-                self.subtimelines.validity(),
-                self.tags.validity(),
-            ])
-        }
-    }
-
-    fn update_validity(&mut self) {
-        // Do nothing.  Components update their validity themselves.
-        panic!()
     }
 }
 
@@ -249,7 +215,12 @@ impl TimelineEditGui {
             tx_action_request,
             rx_create_update: None,
             rx_delete: None,
+            delete_confirmation: DeleteConfirmationGui::new(
+                Arc::clone(&shared_config),
+                EntityOrTimeline::Timeline,
+            ),
             rx_reload: None,
+            rx_reapply: None,
             requested_reload: false,
             tx_crud_operation_executed,
             wants_to_be_closed: false,
@@ -285,7 +256,12 @@ impl TimelineEditGui {
             tx_action_request,
             rx_create_update: None,
             rx_delete: None,
+            delete_confirmation: DeleteConfirmationGui::new(
+                Arc::clone(&shared_config),
+                EntityOrTimeline::Timeline,
+            ),
             rx_reload: None,
+            rx_reapply: None,
             requested_reload: false,
             tx_crud_operation_executed,
             wants_to_be_closed: false,
@@ -338,6 +314,76 @@ impl TimelineEditGui {
         self.rx_create_update = None;
         self.rx_delete = None;
         self.rx_reload = None;
+        self.rx_reapply = None;
+    }
+
+    /// Fetch the latest version of the timeline so that the user's changes can
+    /// be re-applied to it (used after an update fails because the timeline
+    /// was changed elsewhere)
+    fn request_reload_and_reapply(&mut self) {
+        if let Some(timeline_id) = self.timeline_id {
+            let (tx, rx) = tokio::sync::mpsc::channel(1);
+            self.rx_reapply = Some(rx);
+            let shared_config = Arc::clone(&self.shared_config);
+            spawn_transaction_no_commit_send_result!(
+                shared_config,
+                bounded,
+                tx,
+                |transaction| async move { TimelineEdit::fetch_by_id(transaction, &timeline_id).await }
+            );
+        }
+    }
+
+    /// Re-apply the user's changes (those that differ from the timeline as it
+    /// was when fetched) to the latest version of the timeline
+    fn reapply_changes(&mut self, latest: TimelineEdit) {
+        let Some(original) = self.database_entry.clone() else {
+            return self.set_from_timeline(latest);
+        };
+        let edited = self.to_opentimeline_type();
+        let pick = |edited_changed: bool| if edited_changed { &edited } else { &latest };
+
+        let mut merged = TimelineEdit::from(
+            latest.id(),
+            pick(edited.name() != original.name()).name().clone(),
+            pick(edited.bool_expr() != original.bool_expr())
+                .bool_expr()
+                .clone(),
+            pick(edited.entities() != original.entities())
+                .entities()
+                .clone(),
+            pick(edited.subtimelines() != original.subtimelines())
+                .subtimelines()
+                .clone(),
+            pick(edited.tags() != original.tags()).tags().clone(),
+        )
+        .unwrap();
+        merged.set_version(latest.version());
+
+        self.set_from_timeline(merged);
+        self.database_entry = Some(latest);
+        self.status = Status::Reapplied;
+    }
+
+    /// Draw the options available after an update fails because the timeline
+    /// was changed elsewhere
+    fn draw_stale_write_options(&mut self, ui: &mut Ui) {
+        if self.status != Status::UpdateError(CrudError::StaleWrite) {
+            return;
+        }
+        ui.horizontal(|ui| {
+            if self.rx_reapply.is_some() {
+                ui.add(Spinner::new());
+            }
+            if ui.button("Reload & Re-apply My Changes").clicked() {
+                self.request_reload_and_reapply();
+            }
+            if ui.button("Discard My Changes").clicked() {
+                self.status = Status::NewWindowForEditing;
+                self.request_reload();
+            }
+        });
+        ui.separator();
     }
 
     // TODO: same as in entity_edit
@@ -355,9 +401,9 @@ impl TimelineEditGui {
                 CreateOrEdit::Create => Status::RequestingCreate,
                 CreateOrEdit::Edit => Status::RequestingUpdate,
             };
-            tokio::spawn(
-                async move { save_crud(shared_config, create_or_edit, timeline, tx).await },
-            );
+            spawn_operation!(async move {
+                save_crud(shared_config, create_or_edit, timeline, tx).await
+            });
         }
     }
 
@@ -369,7 +415,7 @@ impl TimelineEditGui {
         let timeline_id = self.timeline_id.unwrap();
         let shared_config = Arc::clone(&self.shared_config);
         self.status = Status::RequestingDelete;
-        tokio::spawn(async move {
+        spawn_operation!(async move {
             delete_from_id_crud::<TimelineEdit>(shared_config, timeline_id, tx).await
         });
     }
@@ -394,10 +440,22 @@ impl TimelineEditGui {
                                 CreateOrEdit::Edit => Status::Updated,
                             };
 
+                            info!(target: ACTIVITY, "Saved timeline {}", timeline.name());
+                            let toast = Toast::success(format!("Saved {}", timeline.name()));
+                            let toast = match timeline.id() {
+                                Some(id) => toast.with_action(ActionRequest::Timeline(
+                                    EntityOrTimelineActionRequest::ViewExisting(id),
+                                )),
+                                None => toast,
+                            };
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.set_from_timeline(timeline);
                             let _ = self.tx_crud_operation_executed.send(());
                         }
                         Err(error) => {
+                            warn!(target: ACTIVITY, "Failed to save timeline: {}", ErrorChain(&error));
+                            let toast = Toast::error(format!("Failed to save timeline: {error}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.status = match self.create_or_edit {
                                 CreateOrEdit::Create => Status::CreateError(error),
                                 CreateOrEdit::Edit => Status::UpdateError(error),
@@ -410,6 +468,25 @@ impl TimelineEditGui {
             }
         }
 
+        // Response to reload & re-apply request
+        if let Some(rx) = self.rx_reapply.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    debug!("Recv timeline edit reload & re-apply response");
+                    self.rx_reapply = None;
+                    match result {
+                        Ok(latest) => self.reapply_changes(latest),
+                        Err(CrudError::IdNotInDb) => {
+                            self.set_deleted_status(DeletedStatus::Deleted(Instant::now()))
+                        }
+                        Err(error) => self.status = Status::UpdateError(error),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => (),
+            }
+        }
+
         // Response to delete request
         if let Some(rx) = self.rx_delete.as_mut() {
             match rx.try_recv() {
@@ -419,10 +496,16 @@ impl TimelineEditGui {
                     self.crud_op_requested = None;
                     match result {
                         Ok(()) => {
+                            info!(target: ACTIVITY, "Deleted timeline");
+                            let toast = Toast::success("Deleted timeline");
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.set_deleted_status(DeletedStatus::Deleted(Instant::now()));
                             let _ = self.tx_crud_operation_executed.send(());
                         }
                         Err(error) => {
+                            warn!(target: ACTIVITY, "Failed to delete timeline: {}", ErrorChain(&error));
+                            let toast = Toast::error(format!("Failed to delete timeline: {error}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
                             self.status = Status::DeleteError(error);
                         }
                     }
@@ -472,8 +555,10 @@ impl TimelineEditGui {
             CreateOrEdit::Edit => {
                 // Delete comes first so that it never moves (reduced likelihood
                 // of accidentally clicking it)
-                if open_timeline_gui_core::Button::delete(ui).clicked() {
-                    self.request_delete();
+                if open_timeline_gui_core::Button::delete(ui).clicked()
+                    && let Some(id) = self.timeline_id
+                {
+                    self.delete_confirmation.open(id);
                 }
                 // Can be invalid or valid, but cannot be equal to the entry in the database
                 if self.differs_from_database_entry() != Some(false)
@@ -500,21 +585,31 @@ impl TimelineEditGui {
     }
 }
 
-impl ToOpenTimelineType<TimelineEdit> for TimelineEditGui {
-    fn to_opentimeline_type(&self) -> TimelineEdit {
-        let id = self.timeline_id;
-        let name = self.name.to_opentimeline_type();
-        let bool_expr = if self.has_expr {
-            Some(self.bool_expr.to_opentimeline_type())
-        } else {
-            None
-        };
-        let entities = self.entities.to_opentimeline_type();
-        let subtimelines = self.subtimelines.to_opentimeline_type();
-        let tags = self.tags.to_opentimeline_type();
-
+impl TimelineEditGui {
+    /// Build the timeline from its (converted) components
+    fn timeline_from_parts(
+        &self,
+        name: Name,
+        bool_expr: Option<BoolTagExpr>,
+        entities: Option<ReducedEntities>,
+        subtimelines: Option<ReducedTimelines>,
+        tags: Option<Tags>,
+    ) -> TimelineEdit {
         // TODO: is this to returna result or not?
-        TimelineEdit::from(id, name, bool_expr, entities, subtimelines, tags).unwrap()
+        let mut timeline = TimelineEdit::from(
+            self.timeline_id,
+            name,
+            bool_expr,
+            entities,
+            subtimelines,
+            tags,
+        )
+        .unwrap();
+
+        // Updates only succeed if the timeline hasn't changed since it was
+        // fetched
+        timeline.set_version(self.database_entry.as_ref().and_then(TimelineEdit::version));
+        timeline
     }
 }
 
@@ -554,7 +649,11 @@ impl Reload for TimelineEditGui {
                         Err(CrudError::IdNotInDb) => {
                             self.set_deleted_status(DeletedStatus::Deleted(Instant::now()))
                         }
-                        Err(_) => todo!(),
+                        Err(error) => {
+                            warn!(target: ACTIVITY, "Failed to reload timeline: {}", ErrorChain(&error));
+                            let toast = Toast::error(format!("Failed to reload timeline: {error}"));
+                            let _ = self.tx_action_request.send(ActionRequest::toast(toast));
+                        }
                     }
                 }
                 Err(TryRecvError::Empty) => (),
@@ -578,11 +677,15 @@ impl CheckForUpdates for TimelineEditGui {
     fn check_for_updates(&mut self) {
         self.check_reload_response();
         self.check_for_crud_status_updates();
+        self.delete_confirmation.check_for_updates();
     }
 
     fn waiting_for_updates(&mut self) -> bool {
-        let waiting =
-            self.rx_reload.is_some() || self.rx_create_update.is_some() || self.rx_delete.is_some();
+        let waiting = self.rx_reload.is_some()
+            || self.rx_create_update.is_some()
+            || self.rx_delete.is_some()
+            || self.delete_confirmation.waiting_for_updates()
+            || self.rx_reapply.is_some();
         if waiting {
             info!("TimelineEditGui is waiting for updates");
         }
@@ -605,6 +708,20 @@ impl BreakOutWindow for TimelineEditGui {
         // Check for global shortcuts
         global_shortcuts(ctx, &mut self.tx_action_request);
 
+        // Pasting entities' JSON adds them to the timeline
+        if !self.requested_reload
+            && !self.has_been_deleted()
+            && let Some(entities) = pasted_entities(ctx)
+        {
+            let mut added = 0;
+            for entity in &entities {
+                if self.entities.add_entity_by_name(entity.name()) {
+                    added += 1;
+                }
+            }
+            self.status = Status::Pasted(added);
+        }
+
         // Update the status
         match self.validity() {
             ValidityAsynchronous::Invalid(error) => self.status = Status::Invalid(error),
@@ -646,6 +763,7 @@ impl BreakOutWindow for TimelineEditGui {
             // Status
             self.draw_status(ui);
             ui.separator();
+            self.draw_stale_write_options(ui);
 
             // Create/Update/Delete buttons
             self.draw_toolbar(ui);
@@ -690,6 +808,11 @@ impl BreakOutWindow for TimelineEditGui {
                 self.tags.draw(ctx, ui);
             });
         });
+
+        // Deleting must be confirmed
+        if self.delete_confirmation.draw(ctx, &self.name.name) {
+            self.request_delete();
+        }
     }
 
     fn default_size(&self) -> Vec2 {
@@ -724,4 +847,34 @@ impl BreakOutWindow for TimelineEditGui {
     fn wants_to_be_closed(&mut self) -> bool {
         self.wants_to_be_closed
     }
+
+    fn is_editor(&self) -> bool {
+        true
+    }
+
+    fn has_unsaved_changes(&mut self) -> bool {
+        if self.requested_reload || self.has_been_deleted() {
+            return false;
+        }
+        match self.create_or_edit {
+            CreateOrEdit::Create => !self.name.name.is_empty(),
+            CreateOrEdit::Edit => self.differs_from_database_entry() != Some(false),
+        }
+    }
+
+    fn can_save(&mut self) -> bool {
+        let differs = match self.create_or_edit {
+            CreateOrEdit::Create => true,
+            CreateOrEdit::Edit => self.differs_from_database_entry() == Some(true),
+        };
+        differs && self.validity() == ValidityAsynchronous::Valid
+    }
+
+    fn save(&mut self) {
+        self.request_create_or_update();
+    }
+
+    fn cancel_close(&mut self) {
+        self.wants_to_be_closed = false;
+    }
 }
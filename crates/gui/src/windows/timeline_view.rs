@@ -4,30 +4,148 @@
 //! The view timeline GUI
 //!
 
-use crate::app::ActionRequest;
-use crate::components::{BooleanExpressionGui, HintText};
+use crate::activity_log::ACTIVITY;
+use crate::app::{ActionRequest, EntityOrTimelineActionRequest};
+use crate::common::ToOpenTimelineType;
+use crate::components::{BooleanExpressionGui, HintText, StarGui, TagFocusRequestTarget, TagGui};
 use crate::config::SharedConfig;
-use crate::consts::DEFAULT_WINDOW_SIZES;
+use crate::consts::{
+    DEFAULT_WINDOW_SIZES, PRESENTATION_FONT_SCALE, TIMELINE_VIEW_ENTITY_PAGE_SIZE,
+    TIMELINE_VIEW_LINK_URL,
+};
+use crate::drag_and_drop::DragPayload;
 use crate::shortcuts::global_shortcuts;
+use crate::spawn_operation;
 use crate::spawn_transaction_no_commit_send_result;
 use crate::windows::{Deleted, DeletedStatus};
-use bool_tag_expr::BoolTagExpr;
+use bool_tag_expr::{BoolTagExpr, Tag, TagName};
 use eframe::egui::{
-    Align, CentralPanel, Context, DragValue, Id, Layout, RichText, Slider, Ui, Vec2, ViewportId,
+    self, Align, Button, CentralPanel, ComboBox, Context, DragValue, Frame, Id, Key, Layout, Order,
+    Pos2, RichText, Slider, StrokeKind, TextEdit, TopBottomPanel, Ui, Vec2, ViewportCommand,
+    ViewportId,
+};
+use open_timeline_core::{
+    Coordinates, Date, Entity, HasIdAndName, IsReducedType, MAX_YEAR, MIN_YEAR, Name,
+    OpenTimelineId, ReducedEntity, ReducedTimeline, StoryStep, StoryStepTarget, TagHierarchy,
+    TimelineJs, TimelineView,
+};
+use open_timeline_crud::{
+    CrudError, DynamicTimeline, FavouriteKind, FetchById, SubtimelineDuplication, TimelineBookmark,
+    add_tag_to_entities, delete_timeline_bookmark, delete_timeline_entity, duplicate_timeline,
+    fetch_entities_by_ids, fetch_tag_hierarchy, fetch_timeline_bookmarks,
+    fetch_timeline_entity_ids, fetch_timeline_story, insert_timeline_entity,
+    save_timeline_bookmark, save_timeline_story, timeline_name_from_id, update_entity_dates,
 };
-use open_timeline_core::{Date, MAX_YEAR, MIN_YEAR, Name, OpenTimelineId, TimelineView};
-use open_timeline_crud::{CrudError, FetchById};
 use open_timeline_gui_core::{
     BreakOutWindow, CheckForUpdates, Draw, Reload, body_text_height, font_size, window_has_focus,
 };
-use open_timeline_gui_core::{EmptyConsideredInvalid, Shortcut, ShowRemoveButton};
+use open_timeline_gui_core::{
+    EmptyConsideredInvalid, Shortcut, ShowRemoveButton, Toast, Valid, ValidityAsynchronous,
+};
 use open_timeline_renderer::frontends::desktop_egui::OpenTimelineRendererEgui;
-use open_timeline_renderer::{MAX_DATETIME_SCALE, MIN_DATETIME_SCALE, TimelineInteractionEvent};
+use open_timeline_renderer::{
+    CapturedViewport, HIGH_CONTRAST_MIN_FONT_SIZE_PX, LayoutStrategy, MAX_DATETIME_SCALE,
+    MIN_DATETIME_SCALE, ScalableLayoutParams, SortOrder, StackingOrder, TimelineInteractionEvent,
+};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc::error::TryRecvError;
 use tokio::sync::mpsc::{Receiver, UnboundedSender};
 
+/// Part of a timeline being loaded (sent in order: the timeline, then its
+/// entities a page at a time)
+#[derive(Debug)]
+enum TimelineLoad {
+    /// The timeline's name, its bool expr if it is dynamic, how many entities
+    /// it has, and the tag hierarchy (for the tag filter)
+    Timeline(Name, Option<BoolTagExpr>, usize, TagHierarchy),
+
+    /// The next page of the timeline's entities
+    Entities(Vec<Entity>),
+}
+
+/// Stepping through a timeline's entities in chronological order, fullscreen
+/// and without any controls (e.g. when teaching with a projector)
+#[derive(Debug)]
+struct Presentation {
+    /// The entities to step through (in chronological order)
+    entity_ids: Vec<OpenTimelineId>,
+
+    /// The index of the entity being highlighted (none until the first step)
+    step: Option<usize>,
+}
+
+/// A change to an entity's dates made on the timeline (by dragging the edge of
+/// its date box in edit mode)
+#[derive(Debug, Clone, Copy)]
+struct DatesChange {
+    /// The entity whose dates changed
+    entity_id: OpenTimelineId,
+
+    /// The entity's new dates
+    start: Date,
+    end: Option<Date>,
+
+    /// The entity's dates before the change
+    previous_start: Date,
+    previous_end: Option<Date>,
+}
+
+impl DatesChange {
+    /// The change that undoes this one
+    fn reversed(self) -> Self {
+        Self {
+            entity_id: self.entity_id,
+            start: self.previous_start,
+            end: self.previous_end,
+            previous_start: self.start,
+            previous_end: self.end,
+        }
+    }
+}
+
+/// An action chosen from the context menu of the timeline's entities
+#[derive(Debug, Clone)]
+enum ContextMenuAction {
+    /// Remove the entities from the timeline (as direct members)
+    RemoveFromTimeline(BTreeSet<OpenTimelineId>),
+
+    /// Add the tag to the entities
+    AddTag(BTreeSet<OpenTimelineId>, Tag),
+}
+
+impl ContextMenuAction {
+    /// Describe the (successfully run) action for the user
+    fn done_msg(&self) -> String {
+        match self {
+            Self::RemoveFromTimeline(ids) => {
+                format!("Removed {} entities from the timeline", ids.len())
+            }
+            Self::AddTag(ids, tag) => format!("Added '{tag}' to {} entities", ids.len()),
+        }
+    }
+}
+
+/// The context menu opened by right-clicking (or long-pressing) one of the
+/// timeline's entities.  It acts on all of the selected entities.
+#[derive(Debug)]
+struct EntityContextMenu {
+    /// The entity the menu was opened on
+    entity_id: OpenTimelineId,
+
+    /// The entities the menu acts on (the selection, which includes the
+    /// entity)
+    entity_ids: BTreeSet<OpenTimelineId>,
+
+    /// Where the menu is in the window
+    pos: Pos2,
+
+    /// The tag to add (once "Add tag…" has been clicked)
+    tag: Option<TagGui>,
+}
+
 /// View a timeline
 pub struct TimelineViewGui {
     /// The ID of the timeline being viewed
@@ -36,15 +154,74 @@ pub struct TimelineViewGui {
     /// The name of the timeline being viewed.
     timeline_name: Option<Name>,
 
+    /// The expression that defines the timeline's entities (if it is dynamic)
+    dynamic_bool_expr: Option<BoolTagExpr>,
+
     /// Send the ID of an `Entity` to be viewed
     tx_action_request: UnboundedSender<ActionRequest>,
 
-    /// Receive reloaded data
-    rx_reload: Option<Receiver<Result<TimelineView, CrudError>>>,
+    /// Receive reloaded data (the channel is closed once all of the
+    /// timeline's entities have been sent)
+    rx_reload: Option<Receiver<Result<TimelineLoad, CrudError>>>,
 
-    /// Whether or not a reload has been requested
+    /// Whether or not a reload has been requested (and nothing has been
+    /// received yet)
     requested_reload: bool,
 
+    /// How many of the timeline's entities have been loaded so far, and how
+    /// many there are in total
+    entities_loaded: usize,
+    entities_to_load: usize,
+
+    /// Receive the ID of the timeline's duplicate
+    rx_duplicate: Option<Receiver<Result<OpenTimelineId, CrudError>>>,
+
+    /// Whether duplicating the timeline also duplicates its subtimelines
+    duplicate_copies_subtimelines: bool,
+
+    /// Receive the path the timeline was exported to
+    rx_export: Option<Receiver<Result<PathBuf, CrudError>>>,
+
+    /// Receive the entity dropped onto the timeline once it has been added
+    rx_add_entity: Option<Receiver<Result<Entity, CrudError>>>,
+
+    /// The timeline's bookmarks (named positions that can be jumped to)
+    bookmarks: Vec<TimelineBookmark>,
+
+    /// Receive the timeline's bookmarks (after fetching, saving or deleting)
+    rx_bookmarks: Option<Receiver<Result<Vec<TimelineBookmark>, CrudError>>>,
+
+    /// The name to save the current position under
+    new_bookmark_name: String,
+
+    /// The timeline's story (a guided tour) as it is being edited
+    story: Vec<StoryStep>,
+
+    /// Receive the timeline's story (after fetching or saving)
+    rx_story: Option<Receiver<Result<Vec<StoryStep>, CrudError>>>,
+
+    /// Whether the story editor is shown
+    show_story_editor: bool,
+
+    /// Whether entities' dates can be changed by dragging the edges of their
+    /// date boxes
+    edit_dates: bool,
+
+    /// The changes made to entities' dates (most recent last) that can be
+    /// undone
+    dates_undo_stack: Vec<DatesChange>,
+
+    /// The change to an entity's dates being saved, and whether it undoes an
+    /// earlier change
+    saving_dates: Option<(DatesChange, bool)>,
+
+    /// Receive the entity once its changed dates have been saved
+    rx_save_dates: Option<Receiver<Result<Entity, CrudError>>>,
+
+    /// Used to indirectly inform the rest of the application that a CRUD
+    /// operation has been executed
+    tx_crud_operation_executed: UnboundedSender<()>,
+
     // TODO: we might want tags one day
     // tags: Tags,
 
@@ -73,6 +250,12 @@ pub struct TimelineViewGui {
 
     bool_tag_expr_filter_enabled: bool,
 
+    /// Only show entities near these coordinates (input buffers)
+    near_latitude: String,
+    near_longitude: String,
+    near_radius_km: f64,
+    near_filter_enabled: bool,
+
     ///
     start_date_limit: i64,
     start_date_limit_enabled: bool,
@@ -80,6 +263,44 @@ pub struct TimelineViewGui {
     end_date_limit_enabled: bool,
     datetime_scaling: f64,
     sticky_text: bool,
+
+    /// How the timeline's entities are packed into rows
+    layout_strategy: LayoutStrategy,
+
+    /// The order the timeline's entities are put into rows in
+    sort_order: SortOrder,
+
+    /// Whether entities are being sorted by a tag's value (the tag name may
+    /// not be valid yet, so this can't be worked out from `sort_order`)
+    sort_by_tag: bool,
+
+    /// The name of the tag whose value entities are sorted by
+    sort_tag_name: String,
+
+    /// Which entities are drawn on top where they overlap
+    stacking_order: StackingOrder,
+
+    /// How opaque the entities' date boxes are
+    date_box_alpha: f64,
+
+    /// Whether focusing on the selected entities hides all other entities
+    focus_hides_others: bool,
+
+    /// Whether the renderer is currently set up for high contrast (larger
+    /// text & thicker lines)
+    high_contrast: bool,
+
+    /// Star (favourite) the timeline
+    star: StarGui,
+
+    /// The presentation being given (if any)
+    presentation: Option<Presentation>,
+
+    /// The context menu open on the timeline's entities (if any)
+    context_menu: Option<EntityContextMenu>,
+
+    /// Receive the action chosen from the context menu once it has been run
+    rx_context_menu_action: Option<Receiver<Result<ContextMenuAction, CrudError>>>,
 }
 
 impl TimelineViewGui {
@@ -90,6 +311,7 @@ impl TimelineViewGui {
         shared_config: SharedConfig,
         ctx: &Context,
         tx_action_request: UnboundedSender<ActionRequest>,
+        tx_crud_operation_executed: UnboundedSender<()>,
         timeline_id: OpenTimelineId,
     ) -> Self {
         let bool_tag_expr_filter = BooleanExpressionGui::new(
@@ -101,12 +323,36 @@ impl TimelineViewGui {
         let mut renderer = OpenTimelineRendererEgui::new(ctx);
         renderer.set_font_size_px(font_size(ctx) as f64);
 
+        let star = StarGui::new(
+            Arc::clone(&shared_config),
+            FavouriteKind::Timeline,
+            timeline_id,
+        );
+
         let mut timeline_view_gui = TimelineViewGui {
             timeline_id,
             timeline_name: None,
+            dynamic_bool_expr: None,
             tx_action_request,
             rx_reload: None,
             requested_reload: false,
+            entities_loaded: 0,
+            entities_to_load: 0,
+            rx_duplicate: None,
+            duplicate_copies_subtimelines: false,
+            rx_export: None,
+            rx_add_entity: None,
+            bookmarks: Vec::new(),
+            rx_bookmarks: None,
+            new_bookmark_name: String::new(),
+            story: Vec::new(),
+            rx_story: None,
+            show_story_editor: false,
+            edit_dates: false,
+            dates_undo_stack: Vec::new(),
+            saving_dates: None,
+            rx_save_dates: None,
+            tx_crud_operation_executed,
             timeline_renderer: renderer,
             deleted_status: DeletedStatus::NotDeleted,
             wants_to_be_closed: false,
@@ -114,12 +360,28 @@ impl TimelineViewGui {
             show_controls: true,
             bool_tag_expr_filter,
             bool_tag_expr_filter_enabled: false,
+            near_latitude: String::new(),
+            near_longitude: String::new(),
+            near_radius_km: 100.0,
+            near_filter_enabled: false,
             start_date_limit: 1850,
             start_date_limit_enabled: false,
             end_date_limit: 2050,
             end_date_limit_enabled: false,
             datetime_scaling: 1.0,
             sticky_text: true,
+            layout_strategy: LayoutStrategy::default(),
+            sort_order: SortOrder::default(),
+            sort_by_tag: false,
+            sort_tag_name: String::new(),
+            stacking_order: StackingOrder::default(),
+            date_box_alpha: 1.0,
+            focus_hides_others: true,
+            high_contrast: false,
+            star,
+            presentation: None,
+            context_menu: None,
+            rx_context_menu_action: None,
         };
         timeline_view_gui.request_reload();
         timeline_view_gui
@@ -134,77 +396,1118 @@ impl TimelineViewGui {
     // TODO: really shouldn't use .blocking_read()
     ///
     fn check_for_timeline_colour_changes(&mut self, ctx: &Context) {
-        let colour_theme = self.shared_config.blocking_read().config.colour_theme;
-        let timeline_colours = colour_theme.timeline_colours(ctx);
-        self.timeline_renderer.set_colours(timeline_colours);
+        update_timeline_colours(
+            ctx,
+            &self.shared_config,
+            &mut self.timeline_renderer,
+            &mut self.high_contrast,
+        );
+    }
+
+    fn draw_filters(&mut self, ctx: &Context, ui: &mut Ui) -> (bool, bool, bool) {
+        let (date_limits_changed, tag_filter_changed) = ui
+            .horizontal(|ui| {
+                // Start date limit
+                let start_checkbox_response =
+                    ui.checkbox(&mut self.start_date_limit_enabled, "Start Date Limit");
+                let start_year_response = ui.add_enabled(
+                    self.start_date_limit_enabled,
+                    DragValue::new(&mut self.start_date_limit)
+                        .speed(1)
+                        .range(MIN_YEAR..=MAX_YEAR),
+                );
+                ui.separator();
+
+                // Start date limit
+                let end_checkbox_response =
+                    ui.checkbox(&mut self.end_date_limit_enabled, "End Date Limit");
+                let end_year_response = ui.add_enabled(
+                    self.end_date_limit_enabled,
+                    DragValue::new(&mut self.end_date_limit)
+                        .speed(1)
+                        .range(MIN_YEAR..=MAX_YEAR),
+                );
+                ui.separator();
+
+                // If value changed
+                if start_year_response.changed() {
+                    self.start_date_limit_enabled = true;
+                    self.start_date_limit = self.start_date_limit.min(self.end_date_limit);
+                }
+                if end_year_response.changed() {
+                    self.end_date_limit_enabled = true;
+                    self.end_date_limit = self.start_date_limit.max(self.end_date_limit);
+                }
+
+                // Whether the date limits have changed
+                let date_limits_changed = start_year_response.changed()
+                    || end_year_response.changed()
+                    || start_checkbox_response.changed()
+                    || end_checkbox_response.changed();
+
+                // Filter by boolean tag expr
+                let expr_filter_checkbox_response =
+                    ui.checkbox(&mut self.bool_tag_expr_filter_enabled, "Filter Entities");
+                self.bool_tag_expr_filter.draw(ctx, ui);
+                if self.bool_tag_expr_filter.changed() {
+                    self.bool_tag_expr_filter_enabled =
+                        !self.bool_tag_expr_filter.expr().trim().is_empty();
+                }
+
+                // Whether the bool expr filtering has changed
+                let tag_filter_changed =
+                    expr_filter_checkbox_response.changed() || self.bool_tag_expr_filter.changed();
+
+                //
+                (date_limits_changed, tag_filter_changed)
+            })
+            .inner;
+
+        // Filter by location
+        let location_filter_changed = ui
+            .horizontal(|ui| {
+                let checkbox_response = ui.checkbox(&mut self.near_filter_enabled, "Near Location");
+                let latitude_response = ui.add(
+                    TextEdit::singleline(&mut self.near_latitude)
+                        .hint_text("Latitude")
+                        .desired_width(100.0),
+                );
+                let longitude_response = ui.add(
+                    TextEdit::singleline(&mut self.near_longitude)
+                        .hint_text("Longitude")
+                        .desired_width(100.0),
+                );
+                let radius_response = ui.add(
+                    DragValue::new(&mut self.near_radius_km)
+                        .speed(1)
+                        .range(1.0..=20_000.0)
+                        .suffix(" km"),
+                );
+                let inputs_changed = latitude_response.changed()
+                    || longitude_response.changed()
+                    || radius_response.changed();
+                if inputs_changed {
+                    self.near_filter_enabled = self.near_coordinates().is_some();
+                }
+                checkbox_response.changed() || inputs_changed
+            })
+            .inner;
+
+        (
+            date_limits_changed,
+            tag_filter_changed,
+            location_filter_changed,
+        )
+    }
+
+    /// The coordinates entered for the location filter (if they are valid)
+    fn near_coordinates(&self) -> Option<Coordinates> {
+        let latitude = self.near_latitude.trim().parse().ok()?;
+        let longitude = self.near_longitude.trim().parse().ok()?;
+        Coordinates::from(latitude, longitude).ok()
+    }
+
+    /// Duplicate the timeline (the duplicate is opened for editing once it has
+    /// been created)
+    fn duplicate(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_duplicate = Some(rx);
+        let timeline_id = self.timeline_id;
+        let subtimelines = match self.duplicate_copies_subtimelines {
+            true => SubtimelineDuplication::Copy,
+            false => SubtimelineDuplication::Share,
+        };
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                let id =
+                    duplicate_timeline(&mut transaction, &timeline_id, None, subtimelines).await?;
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(id)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Handle the response to a duplication request
+    fn check_duplicate_response(&mut self) {
+        if let Some(rx) = self.rx_duplicate.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_duplicate = None;
+                    match result {
+                        Ok(id) => {
+                            let _ = self.tx_action_request.send(ActionRequest::Timeline(
+                                EntityOrTimelineActionRequest::EditExisting(id),
+                            ));
+                        }
+                        Err(error) => warn!("Timeline duplication error: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_duplicate = None,
+            }
+        }
+    }
+
+    /// Export the timeline to TimelineJS's JSON at the path
+    fn export_timelinejs(&mut self, path: PathBuf) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_export = Some(rx);
+        let timeline_id = self.timeline_id;
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                let timeline = TimelineView::fetch_by_id(&mut transaction, &timeline_id).await?;
+                let json = serde_json::to_string_pretty(&TimelineJs::from(&timeline))?;
+                std::fs::write(&path, json)?;
+                Ok(path)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Handle the response to an export request
+    fn check_export_response(&mut self) {
+        if let Some(rx) = self.rx_export.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_export = None;
+                    match result {
+                        Ok(path) => info!("Timeline exported to {}", path.display()),
+                        Err(error) => warn!("Timeline export error: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_export = None,
+            }
+        }
+    }
+
+    /// Handle an entity being dragged (e.g. from the search results) onto the
+    /// timeline.  The timeline is highlighted while the entity is over it, and
+    /// the entity is added as a direct member when dropped.
+    fn handle_entity_drop(&mut self, ctx: &Context, ui: &mut Ui) {
+        let Some(DragPayload::Entity(entity)) = DragPayload::current(ctx) else {
+            return;
+        };
+        let Some(pointer) = ctx.pointer_latest_pos() else {
+            return;
+        };
+        if !self.timeline_renderer.is_drop_target(pointer) {
+            return;
+        }
+
+        // Highlight the timeline
+        if let Some(canvas_rect) = self.timeline_renderer.canvas_rect() {
+            let stroke = ui.visuals().selection.stroke;
+            ui.painter()
+                .rect_stroke(canvas_rect, 0.0, stroke, StrokeKind::Inside);
+        }
+        let timeline_name = self.timeline_name.as_ref().map_or("", |name| name.as_str());
+        egui::Tooltip::always_open(
+            ctx.clone(),
+            ui.layer_id(),
+            Id::new("timeline_drop_tooltip"),
+            egui::PopupAnchor::Pointer,
+        )
+        .show(|ui| ui.label(format!("Add '{}' to '{timeline_name}'", entity.name())));
+
+        // Dropped
+        if ctx.input(|i| i.pointer.any_released()) {
+            DragPayload::clear(ctx);
+            self.add_entity(entity);
+        }
+    }
+
+    /// Add an entity to the timeline as a direct member (it is shown as soon
+    /// as it has been added)
+    fn add_entity(&mut self, entity: ReducedEntity) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_add_entity = Some(rx);
+        let timeline_id = self.timeline_id;
+        let entity_id = entity.id();
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                insert_timeline_entity(&mut transaction, &timeline_id, &entity_id).await?;
+                let entity = Entity::fetch_by_id(&mut transaction, &entity_id).await?;
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(entity)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Handle the response to adding a dropped entity
+    fn check_add_entity_response(&mut self) {
+        if let Some(rx) = self.rx_add_entity.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_add_entity = None;
+                    match result {
+                        // The entity may or may not be in a page still to be
+                        // loaded, so start again
+                        Ok(_) if self.is_loading_entities() => {
+                            info!(target: ACTIVITY, "Added an entity to the timeline");
+                            self.request_reload();
+                            let _ = self.tx_crud_operation_executed.send(());
+                        }
+                        Ok(entity) => {
+                            let already_shown = entity
+                                .id()
+                                .is_some_and(|id| self.timeline_renderer.contains_entity(id));
+                            if !already_shown {
+                                self.timeline_renderer.add_entities(vec![entity]);
+                            }
+                            info!(target: ACTIVITY, "Added an entity to the timeline");
+                            let _ = self.tx_crud_operation_executed.send(());
+                        }
+                        Err(error) => {
+                            warn!(target: ACTIVITY, "Error adding entity to timeline: {error}")
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_add_entity = None,
+            }
+        }
+    }
+
+    /// Save the change to the entity's dates (it's already shown on the
+    /// timeline, and is reverted if saving fails)
+    fn save_dates(&mut self, change: DatesChange, is_undo: bool) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_save_dates = Some(rx);
+        self.saving_dates = Some((change, is_undo));
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                let entity = update_entity_dates(
+                    &mut transaction,
+                    &change.entity_id,
+                    change.start,
+                    change.end,
+                )
+                .await?;
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(entity)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Undo the most recent change to an entity's dates
+    fn undo_dates_change(&mut self) {
+        if self.rx_save_dates.is_some() {
+            return;
+        }
+        let Some(change) = self.dates_undo_stack.pop() else {
+            return;
+        };
+        let undo = change.reversed();
+        self.timeline_renderer
+            .set_entity_dates(undo.entity_id, undo.start, undo.end);
+        self.save_dates(undo, true);
+    }
+
+    /// Handle the response to saving an entity's changed dates
+    fn check_save_dates_response(&mut self) {
+        if let Some(rx) = self.rx_save_dates.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_save_dates = None;
+                    let saving_dates = self.saving_dates.take();
+                    match result {
+                        Ok(_) => {
+                            info!(target: ACTIVITY, "Saved an entity's dates");
+                            let _ = self.tx_crud_operation_executed.send(());
+                        }
+                        Err(error) => {
+                            warn!(target: ACTIVITY, "Error saving entity dates: {error}");
+                            let Some((change, is_undo)) = saving_dates else {
+                                return;
+                            };
+                            self.timeline_renderer.set_entity_dates(
+                                change.entity_id,
+                                change.previous_start,
+                                change.previous_end,
+                            );
+                            if is_undo {
+                                self.dates_undo_stack.push(change.reversed());
+                            } else {
+                                self.dates_undo_stack.pop();
+                            }
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => {
+                    self.rx_save_dates = None;
+                    self.saving_dates = None;
+                }
+            }
+        }
+    }
+
+    /// Open the context menu for the selected entities where one of them was
+    /// right-clicked (or long-pressed).  The point is relative to the canvas.
+    fn open_context_menu(&mut self, entity_id: OpenTimelineId, x: f64, y: f64) {
+        let Some(canvas_rect) = self.timeline_renderer.canvas_rect() else {
+            return;
+        };
+        let mut entity_ids: BTreeSet<OpenTimelineId> = self
+            .timeline_renderer
+            .ids_of_selected_entities()
+            .iter()
+            .copied()
+            .collect();
+        entity_ids.insert(entity_id);
+        self.context_menu = Some(EntityContextMenu {
+            entity_id,
+            entity_ids,
+            pos: canvas_rect.min + Vec2::new(x as f32, y as f32),
+            tag: None,
+        });
+    }
+
+    /// Draw the context menu (if it is open), running whichever action is
+    /// chosen
+    fn draw_context_menu(&mut self, ctx: &Context) {
+        let Some(menu) = self.context_menu.as_mut() else {
+            return;
+        };
+        let is_dynamic = self.dynamic_bool_expr.is_some();
+        let count = menu.entity_ids.len();
+        let mut open_entity = false;
+        let mut action = None;
+        let area = egui::Area::new(Id::new("timeline_entity_context_menu").with(self.timeline_id))
+            .order(Order::Foreground)
+            .fixed_pos(menu.pos)
+            .show(ctx, |ui| {
+                Frame::menu(ui.style()).show(ui, |ui| {
+                    open_entity = ui.button("Open entity").clicked();
+
+                    // Dynamic timelines' entities can't be removed (they are
+                    // whichever match its bool expr)
+                    let remove_text = match count {
+                        1 => String::from("Remove from this timeline"),
+                        count => format!("Remove {count} entities from this timeline"),
+                    };
+                    if ui
+                        .add_enabled(!is_dynamic, Button::new(remove_text))
+                        .on_disabled_hover_text(
+                            "A dynamic timeline's entities are those matching its boolean expression",
+                        )
+                        .clicked()
+                    {
+                        action = Some(ContextMenuAction::RemoveFromTimeline(
+                            menu.entity_ids.clone(),
+                        ));
+                    }
+
+                    // Enter the tag to add, then add it
+                    match menu.tag.as_mut() {
+                        None => {
+                            if ui.button("Add tag…").clicked() {
+                                menu.tag = Some(TagGui::new(
+                                    ShowRemoveButton::No,
+                                    Some(TagFocusRequestTarget::Name),
+                                ));
+                            }
+                        }
+                        Some(tag_gui) => {
+                            ui.separator();
+                            tag_gui.draw(ctx, ui);
+                            let valid = tag_gui.validity() == ValidityAsynchronous::Valid;
+                            if ui.add_enabled(valid, Button::new("Add Tag")).clicked() {
+                                action = Some(ContextMenuAction::AddTag(
+                                    menu.entity_ids.clone(),
+                                    tag_gui.to_opentimeline_type(),
+                                ));
+                            }
+                        }
+                    }
+                });
+            });
+
+        // Anything chosen (or clicking elsewhere, or pressing escape) closes
+        // the menu
+        if open_entity {
+            let _ = self.tx_action_request.send(ActionRequest::Entity(
+                EntityOrTimelineActionRequest::ViewExisting(menu.entity_id),
+            ));
+        }
+        let close = open_entity
+            || action.is_some()
+            || area.response.clicked_elsewhere()
+            || ctx.input(|i| i.key_pressed(Key::Escape));
+        if close {
+            self.context_menu = None;
+        }
+        if let Some(action) = action {
+            self.run_context_menu_action(action);
+        }
+    }
+
+    /// Run the action chosen from the context menu (committing if successful)
+    fn run_context_menu_action(&mut self, action: ContextMenuAction) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_context_menu_action = Some(rx);
+        let timeline_id = self.timeline_id;
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                match &action {
+                    ContextMenuAction::RemoveFromTimeline(ids) => {
+                        for entity_id in ids {
+                            delete_timeline_entity(&mut transaction, &timeline_id, entity_id)
+                                .await?;
+                        }
+                    }
+                    ContextMenuAction::AddTag(ids, tag) => {
+                        add_tag_to_entities(&mut transaction, ids, tag).await?
+                    }
+                }
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(action)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Handle the response to running an action chosen from the context menu
+    /// (the timeline is reloaded once the database has changed)
+    fn check_context_menu_action_response(&mut self) {
+        if let Some(rx) = self.rx_context_menu_action.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_context_menu_action = None;
+                    match result {
+                        Ok(action) => {
+                            info!(target: ACTIVITY, "{}", action.done_msg());
+                            let _ = self.tx_crud_operation_executed.send(());
+                        }
+                        Err(error) => {
+                            let msg = format!("Failed to update the timeline's entities: {error}");
+                            warn!(target: ACTIVITY, "{msg}");
+                            let _ = self
+                                .tx_action_request
+                                .send(ActionRequest::toast(Toast::error(msg)));
+                        }
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_context_menu_action = None,
+            }
+        }
+    }
+
+    /// Whether some of the timeline's entities are still being loaded
+    fn is_loading_entities(&self) -> bool {
+        self.rx_reload.is_some() && self.entities_loaded < self.entities_to_load
     }
 
-    fn draw_filters(&mut self, ctx: &Context, ui: &mut Ui) -> (bool, bool) {
+    /// Add the next page of the timeline's entities to the renderer
+    fn add_loaded_entities(&mut self, entities: Vec<Entity>) {
+        // The first page replaces the entities shown before reloading.
+        // Dynamic timelines are sent in one page, and are synced so that the
+        // view is undisturbed as entities gain/lose tags.
+        self.entities_loaded += entities.len();
+        if self.requested_reload {
+            self.requested_reload = false;
+            match self.dynamic_bool_expr {
+                Some(_) => self.timeline_renderer.sync_entities(entities),
+                None => self.timeline_renderer.set_entities(entities),
+            }
+        } else {
+            self.timeline_renderer.add_entities(entities);
+        }
+        let (start, end) = self.timeline_renderer.start_and_end_dates();
+        self.start_date_limit = start as i64;
+        self.end_date_limit = end as i64;
+    }
+
+    /// Fetch the timeline's bookmarks
+    fn fetch_bookmarks(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_bookmarks = Some(rx);
+        let timeline_id = self.timeline_id;
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_timeline_bookmarks(transaction, &timeline_id).await }
+        );
+    }
+
+    /// Save (or delete if `None`) a bookmark and then refetch all of the
+    /// timeline's bookmarks
+    fn update_bookmark(&mut self, name: Name, captured: Option<CapturedViewport>) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_bookmarks = Some(rx);
+        let timeline_id = self.timeline_id;
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                match captured {
+                    Some(captured) => {
+                        let bookmark = TimelineBookmark::from(
+                            name,
+                            captured.start_year,
+                            captured.offset_y,
+                            captured.zoom,
+                            captured.datetime_scale,
+                        );
+                        save_timeline_bookmark(&mut transaction, &timeline_id, &bookmark).await?
+                    }
+                    None => delete_timeline_bookmark(&mut transaction, &timeline_id, &name).await?,
+                }
+                let bookmarks = fetch_timeline_bookmarks(&mut transaction, &timeline_id).await?;
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(bookmarks)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Handle the response to fetching/saving/deleting bookmarks
+    fn check_bookmarks_response(&mut self) {
+        if let Some(rx) = self.rx_bookmarks.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_bookmarks = None;
+                    match result {
+                        Ok(bookmarks) => self.bookmarks = bookmarks,
+                        Err(error) => warn!("Timeline bookmarks error: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_bookmarks = None,
+            }
+        }
+    }
+
+    /// Jump to a bookmarked position
+    fn go_to_bookmark(&mut self, bookmark: &TimelineBookmark) {
+        self.timeline_renderer.restore_viewport(&CapturedViewport {
+            start_year: bookmark.start_year(),
+            offset_y: bookmark.offset_y(),
+            zoom: bookmark.zoom(),
+            datetime_scale: bookmark.datetime_scale(),
+        });
+        self.datetime_scaling = self.timeline_renderer.capture_viewport().datetime_scale;
+    }
+
+    /// Draw the bookmarks (jump to, save and delete)
+    fn draw_bookmarks(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
-            // Start date limit
-            let start_checkbox_response =
-                ui.checkbox(&mut self.start_date_limit_enabled, "Start Date Limit");
-            let start_year_response = ui.add_enabled(
-                self.start_date_limit_enabled,
-                DragValue::new(&mut self.start_date_limit)
-                    .speed(1)
-                    .range(MIN_YEAR..=MAX_YEAR),
-            );
-            ui.separator();
+            let waiting = self.rx_bookmarks.is_some();
 
-            // Start date limit
-            let end_checkbox_response =
-                ui.checkbox(&mut self.end_date_limit_enabled, "End Date Limit");
-            let end_year_response = ui.add_enabled(
-                self.end_date_limit_enabled,
-                DragValue::new(&mut self.end_date_limit)
-                    .speed(1)
-                    .range(MIN_YEAR..=MAX_YEAR),
+            // Jump to/delete a bookmark
+            let mut go_to = None;
+            let mut delete = None;
+            ui.add_enabled_ui(!self.bookmarks.is_empty(), |ui| {
+                ui.menu_button("Bookmarks", |ui| {
+                    for bookmark in &self.bookmarks {
+                        ui.horizontal(|ui| {
+                            if ui.button(bookmark.name().as_str()).clicked() {
+                                go_to = Some(bookmark.clone());
+                                ui.close();
+                            }
+                            if ui
+                                .add_enabled(!waiting, Button::new("🗑"))
+                                .on_hover_text("Delete bookmark")
+                                .clicked()
+                            {
+                                delete = Some(bookmark.name().clone());
+                            }
+                        });
+                    }
+                })
+                .response
+                .on_disabled_hover_text("Save the current position to bookmark it");
+            });
+            if let Some(bookmark) = go_to {
+                self.go_to_bookmark(&bookmark);
+            }
+            if let Some(name) = delete {
+                self.update_bookmark(name, None);
+            }
+
+            // Save the current position
+            ui.add(
+                TextEdit::singleline(&mut self.new_bookmark_name)
+                    .hint_text("Bookmark name")
+                    .desired_width(150.0),
             );
-            ui.separator();
+            let name = Name::from(self.new_bookmark_name.trim()).ok();
+            let replaces = name
+                .as_ref()
+                .is_some_and(|name| self.bookmarks.iter().any(|b| b.name() == name));
+            let text = match replaces {
+                true => "Update Bookmark",
+                false => "Bookmark",
+            };
+            if ui
+                .add_enabled(name.is_some() && !waiting, Button::new(text))
+                .on_hover_text("Save the current position so that it can be jumped back to")
+                .clicked()
+                && let Some(name) = name
+            {
+                let captured = self.timeline_renderer.capture_viewport();
+                self.update_bookmark(name, Some(captured));
+                self.new_bookmark_name.clear();
+            }
+        });
+    }
+
+    /// Fetch the timeline's story
+    fn fetch_story(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_story = Some(rx);
+        let timeline_id = self.timeline_id;
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_transaction_no_commit_send_result!(
+            shared_config,
+            bounded,
+            tx,
+            |transaction| async move { fetch_timeline_story(transaction, &timeline_id).await }
+        );
+    }
+
+    /// Save the story being edited and then refetch it
+    fn save_story(&mut self) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        self.rx_story = Some(rx);
+        let timeline_id = self.timeline_id;
+        let steps = self.story.clone();
+        let shared_config = Arc::clone(&self.shared_config);
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                save_timeline_story(&mut transaction, &timeline_id, &steps).await?;
+                let story = fetch_timeline_story(&mut transaction, &timeline_id).await?;
+                // TODO: is this the correct error variant?
+                transaction.commit().await?;
+                Ok(story)
+            }
+            .await;
+            let _ = tx.send(result).await;
+        });
+    }
 
-            // If value changed
-            if start_year_response.changed() {
-                self.start_date_limit_enabled = true;
-                self.start_date_limit = self.start_date_limit.min(self.end_date_limit);
+    /// Handle the response to fetching/saving the story
+    fn check_story_response(&mut self) {
+        if let Some(rx) = self.rx_story.as_mut() {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.rx_story = None;
+                    match result {
+                        Ok(story) => {
+                            self.timeline_renderer.set_story(story.clone());
+                            self.story = story;
+                        }
+                        Err(error) => warn!("Timeline story error: {error}"),
+                    }
+                }
+                Err(TryRecvError::Empty) => (),
+                Err(TryRecvError::Disconnected) => self.rx_story = None,
+            }
+        }
+    }
+
+    /// Describe what a step of the story shows
+    fn story_step_target_text(&self, target: &StoryStepTarget) -> String {
+        match target {
+            StoryStepTarget::Entity(entity_id) => {
+                match self.timeline_renderer.entity_name(*entity_id) {
+                    Some(name) => name.to_string(),
+                    None => String::from("(entity not on the timeline)"),
+                }
             }
-            if end_year_response.changed() {
-                self.end_date_limit_enabled = true;
-                self.end_date_limit = self.start_date_limit.max(self.end_date_limit);
+            StoryStepTarget::Years {
+                start_year,
+                end_year,
+            } => format!("{start_year:.0} to {end_year:.0}"),
+        }
+    }
+
+    /// Draw the story (play it, and edit it if the editor is shown)
+    fn draw_story(&mut self, ui: &mut Ui) {
+        // Playback
+        ui.horizontal(|ui| {
+            let has_story = !self.timeline_renderer.story().is_empty();
+            let step = self.timeline_renderer.story_step();
+            let last = self.timeline_renderer.story().len().saturating_sub(1);
+            match step {
+                None => {
+                    if ui
+                        .add_enabled(has_story, Button::new("▶ Play Story"))
+                        .on_hover_text("Step through the story, moving to each step in turn")
+                        .on_disabled_hover_text("The timeline doesn't have a story")
+                        .clicked()
+                    {
+                        self.timeline_renderer.next_story_step();
+                    }
+                }
+                Some(step) => {
+                    if ui
+                        .add_enabled(step > 0, Button::new("⏴"))
+                        .on_hover_text("Previous step")
+                        .clicked()
+                    {
+                        self.timeline_renderer.previous_story_step();
+                    }
+                    if ui
+                        .add_enabled(step < last, Button::new("⏵"))
+                        .on_hover_text("Next step")
+                        .clicked()
+                    {
+                        self.timeline_renderer.next_story_step();
+                    }
+                    if ui.button("⏹").on_hover_text("Stop the story").clicked() {
+                        self.timeline_renderer.stop_story();
+                    }
+                }
             }
+            ui.checkbox(&mut self.show_story_editor, "Edit Story");
+        });
+        if let Some(step) = self.timeline_renderer.story_step()
+            && let Some(story_step) = self.timeline_renderer.story().get(step)
+        {
+            ui.horizontal_wrapped(|ui| {
+                let count = self.timeline_renderer.story().len();
+                ui.label(RichText::new(format!("{}/{count}", step + 1)).weak());
+                ui.label(&story_step.caption);
+            });
+        }
+        if self.show_story_editor {
+            self.draw_story_editor(ui);
+        }
+    }
 
-            // Whether the date limits have changed
-            let date_limits_changed = start_year_response.changed()
-                || end_year_response.changed()
-                || start_checkbox_response.changed()
-                || end_checkbox_response.changed();
+    /// Draw the story editor (add, caption, reorder and remove steps, then
+    /// save them)
+    fn draw_story_editor(&mut self, ui: &mut Ui) {
+        let waiting = self.rx_story.is_some();
+        let mut move_up = None;
+        let mut remove = None;
+        let targets: Vec<_> = self
+            .story
+            .iter()
+            .map(|step| self.story_step_target_text(&step.target))
+            .collect();
+        for (index, (step, target)) in self.story.iter_mut().zip(targets).enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(format!("{}.", index + 1)).weak());
+                ui.label(target);
+                ui.add(
+                    TextEdit::singleline(&mut step.caption)
+                        .hint_text("Caption")
+                        .desired_width(300.0),
+                );
+                if ui
+                    .add_enabled(index > 0, Button::new("⏶"))
+                    .on_hover_text("Move up")
+                    .clicked()
+                {
+                    move_up = Some(index);
+                }
+                if ui.button("🗑").on_hover_text("Remove step").clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = move_up {
+            self.story.swap(index - 1, index);
+        }
+        if let Some(index) = remove {
+            self.story.remove(index);
+        }
 
-            // Filter by boolean tag expr
-            let expr_filter_checkbox_response =
-                ui.checkbox(&mut self.bool_tag_expr_filter_enabled, "Filter Entities");
-            self.bool_tag_expr_filter.draw(ctx, ui);
-            if self.bool_tag_expr_filter.changed() {
-                self.bool_tag_expr_filter_enabled =
-                    !self.bool_tag_expr_filter.expr().trim().is_empty();
+        ui.horizontal(|ui| {
+            let selected = self.timeline_renderer.ids_of_selected_entities();
+            let selected_entity_id = match selected.as_slice() {
+                [entity_id] => Some(*entity_id),
+                _ => None,
+            };
+            if ui
+                .add_enabled(
+                    selected_entity_id.is_some(),
+                    Button::new("Add Selected Entity"),
+                )
+                .on_disabled_hover_text("Select one entity to add it as a step")
+                .clicked()
+                && let Some(entity_id) = selected_entity_id
+            {
+                let target = StoryStepTarget::Entity(entity_id);
+                self.story.push(StoryStep::from(target, String::new()));
+            }
+            if ui
+                .button("Add Current View")
+                .on_hover_text("Add the years currently in view as a step")
+                .clicked()
+            {
+                let viewport = self.timeline_renderer.viewport();
+                let target = StoryStepTarget::Years {
+                    start_year: viewport.start_year,
+                    end_year: viewport.end_year,
+                };
+                self.story.push(StoryStep::from(target, String::new()));
             }
+            let changed = self.story.as_slice() != self.timeline_renderer.story();
+            if ui
+                .add_enabled(changed && !waiting, Button::new("Save Story"))
+                .clicked()
+            {
+                self.save_story();
+            }
+            if ui
+                .add_enabled(changed && !waiting, Button::new("Revert"))
+                .clicked()
+            {
+                self.story = self.timeline_renderer.story().to_vec();
+            }
+        });
+    }
 
-            // Whether the bool expr filtering has changed
-            let tag_filter_changed =
-                expr_filter_checkbox_response.changed() || self.bool_tag_expr_filter.changed();
+    /// Start presenting the timeline: fullscreen, without the controls, with
+    /// larger text, stepping through the (visible) entities with the keyboard
+    fn start_presentation(&mut self, ctx: &Context) {
+        self.timeline_renderer.exit_focus();
+        self.timeline_renderer.clear_ids_of_selected_entities();
+        self.timeline_renderer
+            .set_font_size_px(self.font_size_px(ctx) * PRESENTATION_FONT_SCALE as f64);
+        ctx.send_viewport_cmd(ViewportCommand::Fullscreen(true));
+        self.presentation = Some(Presentation {
+            entity_ids: self
+                .timeline_renderer
+                .ids_of_visible_entities_in_date_order(),
+            step: None,
+        });
+    }
 
-            //
-            (date_limits_changed, tag_filter_changed)
-        })
-        .inner
+    /// Stop presenting the timeline (going back to how it was before)
+    fn stop_presentation(&mut self, ctx: &Context) {
+        if self.presentation.take().is_none() {
+            return;
+        }
+        self.timeline_renderer.exit_focus();
+        self.timeline_renderer.clear_ids_of_selected_entities();
+        self.timeline_renderer
+            .set_font_size_px(self.font_size_px(ctx));
+        ctx.send_viewport_cmd(ViewportCommand::Fullscreen(false));
+    }
+
+    /// The size of the timeline's text when not presenting
+    fn font_size_px(&self, ctx: &Context) -> f64 {
+        let font_size_px = font_size(ctx) as f64;
+        match self.high_contrast {
+            true => font_size_px.max(HIGH_CONTRAST_MIN_FONT_SIZE_PX),
+            false => font_size_px,
+        }
+    }
+
+    /// Highlight the entity at the step of the presentation (and move the
+    /// timeline to it)
+    fn go_to_presentation_step(&mut self, step: usize) {
+        let Some(presentation) = self.presentation.as_mut() else {
+            return;
+        };
+        let Some(entity_id) = presentation.entity_ids.get(step).copied() else {
+            return;
+        };
+        presentation.step = Some(step);
+        self.timeline_renderer
+            .set_ids_of_selected_entities(vec![entity_id]);
+        self.timeline_renderer.focus_on_selection(false);
     }
 
-    fn draw_controls(&mut self, _ctx: &Context, ui: &mut Ui) {
+    /// Step through the presentation with the keyboard
+    fn handle_presentation_keys(&mut self, ctx: &Context) {
+        let Some(presentation) = &self.presentation else {
+            return;
+        };
+        let last = presentation.entity_ids.len().saturating_sub(1);
+        let step = presentation.step;
+        let (next, previous, first, end, stop) = ctx.input(|i| {
+            (
+                i.key_pressed(Key::ArrowRight)
+                    || i.key_pressed(Key::ArrowDown)
+                    || i.key_pressed(Key::Space)
+                    || i.key_pressed(Key::PageDown),
+                i.key_pressed(Key::ArrowLeft)
+                    || i.key_pressed(Key::ArrowUp)
+                    || i.key_pressed(Key::Backspace)
+                    || i.key_pressed(Key::PageUp),
+                i.key_pressed(Key::Home),
+                i.key_pressed(Key::End),
+                i.key_pressed(Key::Escape),
+            )
+        });
+        if stop {
+            self.stop_presentation(ctx);
+        } else if next {
+            self.go_to_presentation_step(step.map_or(0, |step| (step + 1).min(last)));
+        } else if previous {
+            self.go_to_presentation_step(step.unwrap_or(0).saturating_sub(1));
+        } else if first {
+            self.go_to_presentation_step(0);
+        } else if end {
+            self.go_to_presentation_step(last);
+        }
+    }
+
+    /// Draw the timeline being presented (with a caption saying which entity is
+    /// highlighted)
+    fn draw_presentation(&mut self, ctx: &Context) {
+        if window_has_focus(ctx) {
+            self.handle_presentation_keys(ctx);
+        }
+        let Some(presentation) = &self.presentation else {
+            return;
+        };
+
+        // Caption
+        let caption = match presentation.step {
+            Some(step) => {
+                let description = self
+                    .timeline_renderer
+                    .entity_description(presentation.entity_ids[step])
+                    .unwrap_or_default();
+                format!(
+                    "{}/{}  •  {description}",
+                    step + 1,
+                    presentation.entity_ids.len()
+                )
+            }
+            None => format!(
+                "{} entities  •  → to step through them, Esc to stop presenting",
+                presentation.entity_ids.len()
+            ),
+        };
+        TopBottomPanel::bottom(Id::new(("presentation_caption", self.timeline_id))).show(
+            ctx,
+            |ui| {
+                ui.vertical_centered(|ui| {
+                    open_timeline_gui_core::Label::strong(ui, &caption);
+                });
+            },
+        );
+
+        // Timeline (clicking entities doesn't open them while presenting)
+        CentralPanel::default().show(ctx, |ui| {
+            let _ = self.timeline_renderer.drain_interaction_events();
+            self.check_for_timeline_colour_changes(ctx);
+            update_timeline_date_format(&self.shared_config, &mut self.timeline_renderer);
+            update_timeline_font(ctx, &self.shared_config, &mut self.timeline_renderer);
+            self.timeline_renderer.draw(ctx, ui);
+        });
+    }
+
+    fn draw_controls(&mut self, ctx: &Context, ui: &mut Ui) {
         ui.horizontal(|ui| {
             // Buttons
             // if ui.button("View Entity List").clicked() {
             //     // TODO
             // };
 
+            // Compare with another timeline
+            if ui.button("Compare…").clicked()
+                && let Some(timeline_name) = self.timeline_name.clone()
+            {
+                let timeline = ReducedTimeline::from_id_and_name(self.timeline_id, timeline_name);
+                let _ = self
+                    .tx_action_request
+                    .send(ActionRequest::CompareTimelines(timeline));
+            }
+
+            // Share exactly what's in view
+            if ui
+                .button("Copy View Link")
+                .on_hover_text(
+                    "Copy a link to the timeline as it is in view (filters and selection included)",
+                )
+                .clicked()
+            {
+                let view_state = self.timeline_renderer.serialize_view_state();
+                ctx.copy_text(format!(
+                    "{TIMELINE_VIEW_LINK_URL}/{}#{view_state}",
+                    self.timeline_id
+                ));
+            }
+
+            // Export the timeline to other formats
+            ui.add_enabled_ui(self.rx_export.is_none(), |ui| {
+                ui.menu_button("Export", |ui| {
+                    if ui
+                        .button("TimelineJS JSON…")
+                        .on_hover_text("For use with Knight Lab's TimelineJS")
+                        .clicked()
+                    {
+                        let file_name = match &self.timeline_name {
+                            Some(name) => format!("{name}.json"),
+                            None => String::from("timeline.json"),
+                        };
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("JSON", &["json"])
+                            .set_file_name(file_name)
+                            .save_file()
+                        {
+                            self.export_timelinejs(path);
+                        }
+                    }
+                });
+            });
+            ui.separator();
+
+            // Duplicate the timeline (to use as the starting point for another)
+            if ui
+                .add_enabled(self.rx_duplicate.is_none(), Button::new("Duplicate"))
+                .on_hover_text("Create a copy of this timeline and edit it")
+                .clicked()
+            {
+                self.duplicate();
+            }
+            ui.checkbox(&mut self.duplicate_copies_subtimelines, "Copy Subtimelines")
+                .on_hover_text("Also copy the subtimelines (rather than sharing them)");
+            ui.separator();
+
+            // Change entities' dates by dragging
+            ui.checkbox(&mut self.edit_dates, "Edit Dates")
+                .on_hover_text("Drag the ends of entities to change their dates");
+            let can_undo = !self.dates_undo_stack.is_empty() && self.rx_save_dates.is_none();
+            let undo_clicked = ui
+                .add_enabled(can_undo, Button::new("Undo"))
+                .on_hover_text("Undo the last change to an entity's dates")
+                .clicked();
+            let undo_pressed = window_has_focus(ctx)
+                && ctx.memory(|memory| memory.focused().is_none())
+                && Shortcut::undo(ctx);
+            if can_undo && (undo_clicked || undo_pressed) {
+                self.undo_dates_change();
+            }
+            ui.separator();
+
             // Stick text
             let sticky_text = ui.checkbox(&mut self.sticky_text, "Sticky Text");
             if sticky_text.changed() {
@@ -212,6 +1515,90 @@ impl TimelineViewGui {
             }
             ui.separator();
 
+            // Row packing
+            let layout_strategy = self.layout_strategy;
+            ComboBox::from_id_salt("layout_strategy")
+                .selected_text(self.layout_strategy.name())
+                .show_ui(ui, |ui| {
+                    for strategy in LayoutStrategy::ALL {
+                        ui.selectable_value(&mut self.layout_strategy, strategy, strategy.name());
+                    }
+                })
+                .response
+                .on_hover_text("How entities are packed into rows");
+            if self.layout_strategy != layout_strategy {
+                self.timeline_renderer
+                    .set_layout_strategy(self.layout_strategy);
+            }
+
+            // Sort order (which entities are put into the top rows)
+            let sort_order = self.sort_order.clone();
+            let selected_text = match self.sort_by_tag {
+                true => "Tag value",
+                false => self.sort_order.name(),
+            };
+            ComboBox::from_id_salt("sort_order")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for order in SortOrder::ALL {
+                        let selected = !self.sort_by_tag && self.sort_order == order;
+                        if ui.selectable_label(selected, order.name()).clicked() {
+                            self.sort_by_tag = false;
+                            self.sort_order = order;
+                        }
+                    }
+                    if ui.selectable_label(self.sort_by_tag, "Tag value").clicked() {
+                        self.sort_by_tag = true;
+                    }
+                })
+                .response
+                .on_hover_text("The order entities are put into rows in");
+            if self.sort_by_tag {
+                ui.add(
+                    TextEdit::singleline(&mut self.sort_tag_name)
+                        .hint_text("Tag name")
+                        .desired_width(80.0),
+                )
+                .on_hover_text("Entities are sorted by the value of their tag with this name");
+                if let Ok(name) = TagName::from(&self.sort_tag_name.trim()) {
+                    self.sort_order = SortOrder::TagValue(name);
+                }
+            }
+            if self.sort_order != sort_order {
+                self.timeline_renderer.set_sort(self.sort_order.clone());
+            }
+
+            // Stacking order (which overlapping entities are on top)
+            let stacking_order = self.stacking_order;
+            ComboBox::from_id_salt("stacking_order")
+                .selected_text(self.stacking_order.name())
+                .show_ui(ui, |ui| {
+                    for order in StackingOrder::ALL {
+                        ui.selectable_value(&mut self.stacking_order, order, order.name());
+                    }
+                })
+                .response
+                .on_hover_text("The order overlapping entities are drawn in");
+            if self.stacking_order != stacking_order {
+                self.timeline_renderer
+                    .set_stacking_order(self.stacking_order);
+            }
+
+            // Date box opacity
+            let date_box_alpha = ui
+                .add(
+                    DragValue::new(&mut self.date_box_alpha)
+                        .speed(0.01)
+                        .range(0.1..=1.0)
+                        .fixed_decimals(2),
+                )
+                .on_hover_text("Date box opacity (lower keeps overlapping entities legible)");
+            if date_box_alpha.changed() {
+                self.timeline_renderer
+                    .set_date_box_alpha(self.date_box_alpha);
+            }
+            ui.separator();
+
             // Zoom
             if ui.button("Zoom Out").clicked() {
                 self.timeline_renderer.zoom_out(1.1, 0.0, 0.0);
@@ -221,13 +1608,55 @@ impl TimelineViewGui {
             }
             ui.separator();
 
-            // x-scaling
+            // Focus on the selected entities
+            let has_selection = !self.timeline_renderer.ids_of_selected_entities().is_empty();
+            let focus_response = ui
+                .add_enabled(has_selection, Button::new("Focus on Selection"))
+                .on_disabled_hover_text("Select entities with ctrl/cmd or shift click");
+            if focus_response.clicked() {
+                self.timeline_renderer
+                    .focus_on_selection(self.focus_hides_others);
+            }
+            let hide_others_response = ui.checkbox(&mut self.focus_hides_others, "Hide Others");
+            if hide_others_response.changed() && self.timeline_renderer.is_focused() {
+                self.timeline_renderer
+                    .focus_on_selection(self.focus_hides_others);
+            }
+            let is_focused = self.timeline_renderer.is_focused();
+            if ui
+                .add_enabled(is_focused, Button::new("Exit Focus"))
+                .on_hover_text("Escape")
+                .clicked()
+            {
+                self.timeline_renderer.exit_focus();
+            }
+            if ui
+                .add_enabled(has_selection, Button::new("Clear Selection"))
+                .clicked()
+            {
+                self.timeline_renderer.clear_ids_of_selected_entities();
+            }
+            ui.separator();
+
+            // x-scaling (the slider follows the scale when it's automatic)
             ui.label("Scale Date");
+            let mut auto = self.timeline_renderer.datetime_scale_auto();
+            if ui
+                .checkbox(&mut auto, "Auto")
+                .on_hover_text("Fit the timeline's dates to the width of the window")
+                .changed()
+            {
+                self.timeline_renderer.set_datetime_scale_auto(auto);
+            }
+            if auto {
+                self.datetime_scaling = self.timeline_renderer.capture_viewport().datetime_scale;
+            }
             ui.scope(|ui| {
                 let slider = Slider::new(
                     &mut self.datetime_scaling,
                     MIN_DATETIME_SCALE..=MAX_DATETIME_SCALE,
                 )
+                .logarithmic(true)
                 .show_value(false);
                 ui.spacing_mut().slider_width = ui.available_width();
                 if ui.add(slider).changed() {
@@ -245,43 +1674,95 @@ impl Reload for TimelineViewGui {
             return;
         }
         self.requested_reload = true;
+        self.entities_loaded = 0;
+        self.entities_to_load = 0;
         let timeline_id = self.timeline_id;
-        let (tx, rx) = tokio::sync::mpsc::channel(1);
+
+        // Replacing the receiver stops any load already in progress (sending
+        // fails once it has been dropped)
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
         self.rx_reload = Some(rx);
         let shared_config = Arc::clone(&self.shared_config);
-        spawn_transaction_no_commit_send_result!(
-            shared_config,
-            bounded,
-            tx,
-            |transaction| async move { TimelineView::fetch_by_id(transaction, &timeline_id).await }
-        );
+        spawn_operation!(async move {
+            let result = async {
+                let mut transaction = shared_config.read().await.db_pool.begin().await?;
+                let name = timeline_name_from_id(&mut transaction, &timeline_id).await?;
+                let tag_hierarchy = fetch_tag_hierarchy(&mut transaction).await?;
+
+                // Dynamic timelines' entities are found using their bool expr
+                let dynamic =
+                    match DynamicTimeline::fetch_by_id(&mut transaction, &timeline_id).await {
+                        Ok(dynamic) => Some(dynamic),
+                        Err(CrudError::NotADynamicTimeline) => None,
+                        Err(error) => Err(error)?,
+                    };
+                if let Some(dynamic) = dynamic {
+                    let entities = dynamic.fetch_entities(&mut transaction).await?;
+                    let bool_expr = Some(dynamic.bool_expr().clone());
+                    let timeline =
+                        TimelineLoad::Timeline(name, bool_expr, entities.len(), tag_hierarchy);
+                    if tx.send(Ok(timeline)).await.is_ok() && !entities.is_empty() {
+                        let _ = tx.send(Ok(TimelineLoad::Entities(entities))).await;
+                    }
+                    return Ok(());
+                }
+
+                let entity_ids = fetch_timeline_entity_ids(&mut transaction, &timeline_id).await?;
+                let timeline = TimelineLoad::Timeline(name, None, entity_ids.len(), tag_hierarchy);
+                if tx.send(Ok(timeline)).await.is_err() {
+                    return Ok(());
+                }
+                for page in entity_ids.chunks(TIMELINE_VIEW_ENTITY_PAGE_SIZE) {
+                    let entities = fetch_entities_by_ids(&mut transaction, page).await?;
+                    if tx.send(Ok(TimelineLoad::Entities(entities))).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Ok(())
+            }
+            .await;
+            if let Err(error) = result {
+                let _ = tx.send(Err(error)).await;
+            }
+        });
+        self.fetch_bookmarks();
+        self.fetch_story();
     }
 
     fn check_reload_response(&mut self) {
-        if let Some(rx) = self.rx_reload.as_mut() {
+        // Handle everything received since the last frame
+        while let Some(rx) = self.rx_reload.as_mut() {
             match rx.try_recv() {
-                Ok(result) => {
-                    debug!("Recv timeline view reload response");
+                Ok(Ok(TimelineLoad::Timeline(name, bool_expr, entity_count, tag_hierarchy))) => {
+                    debug!("Recv timeline view reload response ({entity_count} entities)");
+                    self.timeline_name = Some(name);
+                    self.timeline_renderer.set_tag_hierarchy(tag_hierarchy);
+                    self.dynamic_bool_expr = bool_expr;
+                    self.entities_to_load = entity_count;
+                    if entity_count == 0 {
+                        self.requested_reload = false;
+                        self.timeline_renderer.set_entities(Vec::new());
+                    }
+                }
+                Ok(Ok(TimelineLoad::Entities(entities))) => self.add_loaded_entities(entities),
+                Ok(Err(error)) => {
                     self.rx_reload = None;
                     self.requested_reload = false;
-                    match result {
-                        Ok(timeline) => {
-                            self.timeline_name = Some(timeline.name().to_owned());
-                            if let Some(entities) = timeline.entities() {
-                                self.timeline_renderer.set_entities(entities.clone());
-                                let (start, end) = self.timeline_renderer.start_and_end_dates();
-                                self.start_date_limit = start as i64;
-                                self.end_date_limit = end as i64;
-                            }
-                        }
-                        Err(CrudError::IdNotInDb) => {
+                    match error {
+                        CrudError::IdNotInDb => {
                             self.set_deleted_status(DeletedStatus::Deleted(Instant::now()))
                         }
-                        Err(error) => warn!("Timeline view fetch error: {error}"),
+                        error => {
+                            let msg = format!("Failed to reload timeline: {error}");
+                            warn!(target: ACTIVITY, "{msg}");
+                            let _ = self
+                                .tx_action_request
+                                .send(ActionRequest::toast(Toast::error(msg)));
+                        }
                     }
                 }
-                Err(TryRecvError::Empty) => (),
-                Err(TryRecvError::Disconnected) => (),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.rx_reload = None,
             }
         }
     }
@@ -300,10 +1781,26 @@ impl Deleted for TimelineViewGui {
 impl CheckForUpdates for TimelineViewGui {
     fn check_for_updates(&mut self) {
         self.check_reload_response();
+        self.check_duplicate_response();
+        self.check_export_response();
+        self.check_add_entity_response();
+        self.check_bookmarks_response();
+        self.check_story_response();
+        self.check_save_dates_response();
+        self.check_context_menu_action_response();
+        self.star.check_for_updates();
     }
 
     fn waiting_for_updates(&mut self) -> bool {
-        let waiting = self.rx_reload.is_some();
+        let waiting = self.rx_reload.is_some()
+            || self.rx_duplicate.is_some()
+            || self.rx_export.is_some()
+            || self.rx_add_entity.is_some()
+            || self.rx_bookmarks.is_some()
+            || self.rx_story.is_some()
+            || self.rx_save_dates.is_some()
+            || self.rx_context_menu_action.is_some()
+            || self.star.waiting_for_updates();
         if waiting {
             info!("TimelineViewGui is waiting for updates");
         }
@@ -318,10 +1815,27 @@ impl BreakOutWindow for TimelineViewGui {
             self.wants_to_be_closed = true;
         }
 
+        // Presenting replaces everything else (unless the timeline has gone)
+        if self.presentation.is_some() {
+            if self.requested_reload || self.has_been_deleted() {
+                self.stop_presentation(ctx);
+            } else {
+                global_shortcuts(ctx, &mut self.tx_action_request);
+                self.draw_presentation(ctx);
+                return;
+            }
+        }
+
+        if window_has_focus(ctx) && self.timeline_renderer.is_focused() && Shortcut::exit_focus(ctx)
+        {
+            self.timeline_renderer.exit_focus();
+        }
+
         // Check for global shortcuts
         global_shortcuts(ctx, &mut self.tx_action_request);
 
         // Draw
+        let mut start_presentation = false;
         CentralPanel::default().show(ctx, |ui| {
             // While waiting for the timeline to be fetched
             if self.requested_reload {
@@ -345,10 +1859,25 @@ impl BreakOutWindow for TimelineViewGui {
             }
 
             // Title info (timeline name)
-            let timeline_name = self.timeline_name.as_ref().unwrap().as_str();
-            open_timeline_gui_core::Label::heading(ui, timeline_name);
+            let timeline_name = self.timeline_name.clone().unwrap();
+            open_timeline_gui_core::Label::heading(ui, timeline_name.as_str());
             ui.horizontal(|ui| {
                 ui.label(RichText::new("Timeline").weak());
+                if let Some(bool_expr) = &self.dynamic_bool_expr {
+                    draw_dynamic_timeline_badge(ui, bool_expr);
+                }
+                self.star.draw(ctx, ui);
+
+                // Big timelines are drawn before all of their entities have
+                // been loaded
+                if self.is_loading_entities() {
+                    ui.spinner();
+                    let text = format!(
+                        "Loading entities ({}/{})",
+                        self.entities_loaded, self.entities_to_load
+                    );
+                    open_timeline_gui_core::Label::weak(ui, &text);
+                }
 
                 // Toggle showing controls & filters
                 let height = body_text_height(ui);
@@ -357,6 +1886,13 @@ impl BreakOutWindow for TimelineViewGui {
                     Layout::right_to_left(Align::Center),
                     |ui| {
                         ui.checkbox(&mut self.show_controls, "Show Controls");
+                        let has_entities = self.timeline_renderer.entity_count() > 0;
+                        start_presentation = ui
+                            .add_enabled(has_entities, Button::new("Present"))
+                            .on_hover_text(
+                                "Fullscreen, stepping through the entities in date order with the arrow keys",
+                            )
+                            .clicked();
                     },
                 );
             });
@@ -372,11 +1908,14 @@ impl BreakOutWindow for TimelineViewGui {
 
             if self.show_controls {
                 // Timeline filters
-                let (date_limits_changed, tag_filter_changed) = self.draw_filters(ctx, ui);
+                let (date_limits_changed, tag_filter_changed, location_filter_changed) =
+                    self.draw_filters(ctx, ui);
                 ui.separator();
 
                 // Controls
                 self.draw_controls(ctx, ui);
+                self.draw_bookmarks(ui);
+                self.draw_story(ui);
                 ui.separator();
 
                 // Update timeline entity filter if appropriate
@@ -390,6 +1929,16 @@ impl BreakOutWindow for TimelineViewGui {
                     }
                 }
 
+                // Update the location entity filter if appropriate
+                if location_filter_changed {
+                    match self.near_coordinates() {
+                        Some(coordinates) if self.near_filter_enabled => self
+                            .timeline_renderer
+                            .set_location_entity_filter(coordinates, self.near_radius_km),
+                        _ => self.timeline_renderer.remove_location_entity_filter(),
+                    }
+                }
+
                 // Update date limits if appropriate
                 if date_limits_changed {
                     let start_limit = self
@@ -403,7 +1952,9 @@ impl BreakOutWindow for TimelineViewGui {
                 }
 
                 // Get events
-                for event in self.timeline_renderer.drain_interaction_events() {
+                let events: Vec<TimelineInteractionEvent> =
+                    self.timeline_renderer.drain_interaction_events().collect();
+                for event in events {
                     match event {
                         TimelineInteractionEvent::SingleClick(entity_id)
                         | TimelineInteractionEvent::DoubleClick(entity_id)
@@ -412,6 +1963,31 @@ impl BreakOutWindow for TimelineViewGui {
                                 crate::app::EntityOrTimelineActionRequest::ViewExisting(entity_id),
                             ));
                         }
+                        TimelineInteractionEvent::DatesChanged {
+                            entity_id,
+                            start,
+                            end,
+                            previous_start,
+                            previous_end,
+                        } => {
+                            let change = DatesChange {
+                                entity_id,
+                                start,
+                                end,
+                                previous_start,
+                                previous_end,
+                            };
+                            self.dates_undo_stack.push(change);
+                            self.save_dates(change, false);
+                        }
+                        TimelineInteractionEvent::EmptySpaceDoubleClick(start) => {
+                            let _ = self
+                                .tx_action_request
+                                .send(ActionRequest::CreateEntityStarting(start));
+                        }
+                        TimelineInteractionEvent::ContextMenu(entity_id, x, y) => {
+                            self.open_context_menu(entity_id, x, y);
+                        }
                         _ => (),
                     }
                 }
@@ -419,10 +1995,22 @@ impl BreakOutWindow for TimelineViewGui {
 
             // Update colours
             self.check_for_timeline_colour_changes(ctx);
+            update_timeline_date_format(&self.shared_config, &mut self.timeline_renderer);
+            update_timeline_font(ctx, &self.shared_config, &mut self.timeline_renderer);
+
+            // Only one change to an entity's dates is saved at a time
+            self.timeline_renderer.set_edit_mode(
+                self.show_controls && self.edit_dates && self.rx_save_dates.is_none(),
+            );
 
             // Draw the timeline
             self.timeline_renderer.draw(ctx, ui);
+            self.handle_entity_drop(ctx, ui);
         });
+        self.draw_context_menu(ctx);
+        if start_presentation {
+            self.start_presentation(ctx);
+        }
     }
 
     fn default_size(&self) -> Vec2 {
@@ -447,3 +2035,68 @@ impl BreakOutWindow for TimelineViewGui {
         self.wants_to_be_closed
     }
 }
+
+/// Mark a timeline as dynamic (hovering shows the expression that defines its
+/// entities)
+pub(crate) fn draw_dynamic_timeline_badge(ui: &mut Ui, bool_expr: &BoolTagExpr) {
+    ui.label(RichText::new("Dynamic").weak())
+        .on_hover_text(format!(
+            "Shows all entities matching: {}",
+            bool_expr.clone().to_boolean_expression()
+        ));
+}
+
+/// Use the configured date format for the timeline's headings and descriptions
+/// (including whether to use relative time and show the "now" line)
+pub(crate) fn update_timeline_date_format(
+    shared_config: &SharedConfig,
+    timeline_renderer: &mut OpenTimelineRendererEgui,
+) {
+    let config = &shared_config.blocking_read().config;
+    timeline_renderer.set_date_format(config.date_format);
+    timeline_renderer.set_show_now_line(config.show_now_line);
+    timeline_renderer.set_relative_time(config.relative_time);
+}
+
+/// Use the configured font for the timeline's text
+pub(crate) fn update_timeline_font(
+    ctx: &Context,
+    shared_config: &SharedConfig,
+    timeline_renderer: &mut OpenTimelineRendererEgui,
+) {
+    let font = shared_config.blocking_read().config.timeline_font.clone();
+    timeline_renderer.set_font(ctx, font);
+}
+
+/// Use the colour theme's timeline colours, and update the layout when
+/// switching to/from high contrast (`high_contrast` is whether the renderer is
+/// currently set up for high contrast)
+pub(crate) fn update_timeline_colours(
+    ctx: &Context,
+    shared_config: &SharedConfig,
+    timeline_renderer: &mut OpenTimelineRendererEgui,
+    high_contrast: &mut bool,
+) {
+    let colour_theme = shared_config.blocking_read().config.colour_theme;
+    let timeline_colours = colour_theme.timeline_colours(ctx);
+    timeline_renderer.set_colours(timeline_colours);
+
+    // Only update the layout when switching to/from high contrast
+    if colour_theme.is_high_contrast() != *high_contrast {
+        *high_contrast = colour_theme.is_high_contrast();
+        let font_size_px = font_size(ctx) as f64;
+        if *high_contrast {
+            timeline_renderer.set_layout_params(ScalableLayoutParams {
+                font_size_px: font_size_px.max(HIGH_CONTRAST_MIN_FONT_SIZE_PX),
+                ..ScalableLayoutParams::high_contrast()
+            });
+            timeline_renderer.set_min_font_size_px(HIGH_CONTRAST_MIN_FONT_SIZE_PX);
+        } else {
+            timeline_renderer.set_min_font_size_px(0.0);
+            timeline_renderer.set_layout_params(ScalableLayoutParams {
+                font_size_px,
+                ..ScalableLayoutParams::default()
+            });
+        }
+    }
+}
@@ -0,0 +1,162 @@
+# SPDX-License-Identifier: GPL-3.0-or-later
+#
+# OpenTimeline English (UK) translations.  This is the fallback language, so
+# every message used anywhere must be defined here.
+
+## Common
+
+status = Status
+status-ready = Ready
+status-error = Error: { $error }
+none = None
+timeline = Timeline
+
+## Symbol-only buttons (tooltips & screen reader labels)
+
+button-add = Add
+button-close = Close
+button-create = Create
+button-delete = Delete
+button-edit = Edit
+button-new = New
+button-remove = Remove
+button-reset = Reset
+button-star = Star
+button-unstar = Unstar
+button-update = Save changes
+button-view = View
+
+## Dialogs
+
+dialog-unsaved-changes = Unsaved Changes
+dialog-unsaved-changes-cannot-save = (they can't be saved until they're valid)
+dialog-unsaved-changes-window = "{ $title }" has changes that haven't been saved.
+dialog-unsaved-changes-app = Windows with changes that haven't been saved: { $count }
+dialog-save = Save
+dialog-save-all = Save All
+dialog-discard = Discard
+dialog-discard-all = Discard All
+dialog-cancel = Cancel
+
+## Toasts
+
+toast-click-to-open = Click to open
+toast-click-to-dismiss = Click to dismiss
+
+## Games (descriptions)
+
+game-decades-description = Put entities into the correct decade
+game-left-right-description-first-started = Which started first, left or right?
+game-left-right-description-first-ended = Which ended first, left or right?
+game-order-entities-description-first-started = Order the entities by their start date (earliest at the top)
+game-order-entities-description-first-ended = Order the entities by their end date (earliest at the top)
+game-were-they-alive-when-description = State whether the person was alive when some event happened/started/ended
+game-which-date-description = What is the { $start_or_end ->
+        [start] start
+       *[end] end
+    } { $year_or_decade ->
+        [year] year
+       *[decade] decade
+    }?
+
+## Games (controls & stats)
+
+game-start = Start
+game-end = End
+game-new = New Game
+game-next-round = Next Round
+game-submit = Submit
+game-last-answer = Last Answer
+game-answer-correct = Correct
+game-answer-incorrect = Incorrect
+game-yes = Yes
+game-no = No
+game-no-question = No question
+game-no-questions = No questions
+game-no-options = No options
+game-order-by-start-date = Order by start date
+game-order-by-end-date = Order by end date
+game-enter-start-date = Enter start date
+game-enter-end-date = Enter end date
+game-enter-decade = Enter decade
+game-enter-year = Enter year
+game-stats-round = Round
+game-stats-correct = Correct
+game-stats-incorrect = Incorrect
+game-stats-percent-correct = Correct (%)
+game-stats-not-applicable = N/A
+game-settings = Settings
+game-settings-options-per-question = Options per question
+game-settings-rounds-per-game = Rounds per game
+game-settings-limit-rounds = Limit
+game-settings-min-pool-size = Minimum entities
+game-settings-only-ended-entities = Only entities that have ended
+game-multiplayer = Multiplayer
+game-multiplayer-player-name = Player name
+game-multiplayer-add-player = Add Player
+game-multiplayer-no-players = Add at least one player
+game-multiplayer-duplicate-player = Player names must be unique
+game-multiplayer-player = Player
+game-multiplayer-turn = { $player }'s turn
+game-multiplayer-winner = Winner: { $players }
+game-multiplayer-draw = Draw: { $players }
+
+## Settings
+
+settings-database-file = Database File
+settings-database-use-existing = Use Existing
+settings-database-create-new = Create & Use New
+settings-database-use-default = Use Default
+settings-database-automatic-period-tags = Automatically tag entities with their decades & centuries
+settings-database-automatic-period-tags-description = Entities are given `decade` (e.g. decade=1910) & `century` (e.g. century=20) tags whenever their dates change, so they can be searched by period
+settings-colour-theme = Colour Theme
+settings-colour-theme-system = System
+settings-colour-theme-light = Light
+settings-colour-theme-dark = Dark
+settings-colour-theme-siphonophore = Siphonophore
+settings-colour-theme-high-contrast = High Contrast
+settings-colour-theme-custom = Custom
+settings-colour-theme-save-custom = Save Custom Theme
+settings-colour-theme-edit-custom = Edit Custom Colours
+settings-language = Language
+settings-date-format = Date Format
+settings-date-format-order = Order
+settings-date-format-order-dmy = Day Month Year
+settings-date-format-order-mdy = Month Day Year
+settings-date-format-order-iso = ISO (Year-Month-Day)
+settings-date-format-month = Months
+settings-date-format-month-name = Names
+settings-date-format-month-number = Numbers
+settings-date-format-era = Years Before 0
+settings-date-format-era-negative = -500
+settings-date-format-era-bce = 500 BCE
+settings-date-format-era-bc = 500 BC
+settings-date-format-timelines = Timelines
+settings-date-format-now-line = Line at Today
+settings-date-format-relative-time = Relative Time (e.g. 75 years ago)
+settings-date-format-example = e.g. { $example }
+settings-timeline-font = Timeline Font
+settings-timeline-font-proportional = Proportional
+settings-timeline-font-monospace = Monospace
+settings-windows = Windows
+settings-windows-editors-as-tabs = Open editors as tabs in a single window
+settings-local-api = Local API Server
+settings-local-api-description = Serve the database (read-only) on this computer, so that the browser renderer & other local tools can read it without it being exported
+settings-local-api-port = Port
+settings-local-api-server = Server
+settings-local-api-enabled = Run while the app is open
+settings-local-api-stopped = Stopped
+settings-local-api-running = Running at { $url }
+settings-logging = Logging
+settings-logging-description = The level logged at for each module (e.g. open_timeline=info,sqlx::query=debug).  Used the next time the app is started.
+settings-logging-invalid = Invalid log levels
+settings-status-changed-database = Successfully switched database
+settings-status-changed-database-settings = Successfully changed database settings
+settings-status-incompatible-schema = Error: selected database has incompatible schema
+settings-status-changed-theme = Successfully switched theme
+settings-status-changed-language = Successfully switched language
+settings-status-changed-date-format = Successfully changed date format
+settings-status-changed-timeline-font = Successfully changed timeline font
+settings-status-changed-windows = Successfully changed window settings
+settings-status-changed-local-api = Successfully changed local API server settings
+settings-status-changed-log-levels = Successfully changed log levels (used the next time the app is started)
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The languages OpenTimeline has been translated into
+//!
+
+use serde::{Deserialize, Serialize};
+use unic_langid::{LanguageIdentifier, langid};
+
+/// The languages the user-facing text is available in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    EnglishUk,
+}
+
+impl Language {
+    /// All available languages (useful for drawing a language picker)
+    pub const ALL: [Language; 1] = [Language::EnglishUk];
+
+    /// The name of the language, in that language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::EnglishUk => "English (UK)",
+        }
+    }
+
+    /// The language's Unicode language identifier
+    pub(crate) fn language_identifier(&self) -> LanguageIdentifier {
+        match self {
+            Language::EnglishUk => langid!("en-GB"),
+        }
+    }
+
+    /// The Fluent translation source for the language
+    pub(crate) fn fluent_source(&self) -> &'static str {
+        match self {
+            Language::EnglishUk => include_str!("../locales/en-GB/main.ftl"),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! *Part of the wider OpenTimeline project*
+//!
+//! This library crate provides localisation for the user-facing text in the
+//! OpenTimeline GUI and games.  Translations are written using
+//! [Fluent](https://projectfluent.org/) and live in `locales/<language>/`.
+//!
+//! Text is looked up using the [`tr!`] macro.  If a message is missing from
+//! the selected language the English message is used, and if it is missing
+//! from English too then the message ID itself is returned.
+//!
+
+mod language;
+mod translate;
+
+pub use language::*;
+pub use translate::*;
+
+#[doc(hidden)]
+pub use fluent_bundle::FluentArgs;
+
+/// Get the localised text for the given message ID, optionally passing
+/// arguments by name
+///
+/// ```
+/// use open_timeline_i18n::tr;
+///
+/// assert_eq!(tr!("status"), "Status");
+/// assert_eq!(tr!("status-error", error = "oops"), "Error: oops");
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($id:expr) => {
+        $crate::translate($id, None)
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = $crate::FluentArgs::new();
+        $(args.set(stringify!($name), $value.to_string());)+
+        $crate::translate($id, Some(&args))
+    }};
+}
+
+#[macro_use]
+extern crate log;
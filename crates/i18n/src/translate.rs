@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Message lookup & formatting
+//!
+
+use crate::Language;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// The language used when a message is missing from the selected language
+const FALLBACK_LANGUAGE: Language = Language::EnglishUk;
+
+/// The language currently in use
+static LANGUAGE: RwLock<Language> = RwLock::new(FALLBACK_LANGUAGE);
+
+/// A bundle of messages for every language (created on first use)
+static BUNDLES: OnceLock<HashMap<Language, FluentBundle<FluentResource>>> = OnceLock::new();
+
+/// Set the language used for all subsequent lookups
+pub fn set_language(language: Language) {
+    info!("Setting language to {language:?}");
+    *LANGUAGE.write().unwrap() = language;
+}
+
+/// Get the language currently in use
+pub fn language() -> Language {
+    *LANGUAGE.read().unwrap()
+}
+
+/// Get the localised text for the given message ID.  Prefer the [`crate::tr!`]
+/// macro.
+pub fn translate(id: &str, args: Option<&FluentArgs>) -> String {
+    let bundles = BUNDLES.get_or_init(create_bundles);
+    for language in [language(), FALLBACK_LANGUAGE] {
+        if let Some(text) = format_message(&bundles[&language], id, args) {
+            return text;
+        }
+        debug!("No '{id}' message for {language:?}");
+    }
+    warn!("No '{id}' message in any language");
+    id.to_string()
+}
+
+/// Format the message (if it exists in the bundle)
+fn format_message(
+    bundle: &FluentBundle<FluentResource>,
+    id: &str,
+    args: Option<&FluentArgs>,
+) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    let text = bundle.format_pattern(pattern, args, &mut errors);
+    for error in errors {
+        warn!("Error formatting '{id}': {error}");
+    }
+    Some(text.into_owned())
+}
+
+/// Create a bundle for every language
+fn create_bundles() -> HashMap<Language, FluentBundle<FluentResource>> {
+    Language::ALL
+        .into_iter()
+        .map(|language| (language, create_bundle(language)))
+        .collect()
+}
+
+/// Parse the language's translations into a bundle.  Any messages that fail to
+/// parse are skipped (and logged).
+fn create_bundle(language: Language) -> FluentBundle<FluentResource> {
+    let resource = match FluentResource::try_new(language.fluent_source().to_string()) {
+        Ok(resource) => resource,
+        Err((resource, errors)) => {
+            for error in errors {
+                warn!("Error parsing {language:?} translations: {error}");
+            }
+            resource
+        }
+    };
+    let mut bundle = FluentBundle::new_concurrent(vec![language.language_identifier()]);
+
+    // Unicode isolation marks show up as boxes in egui
+    bundle.set_use_isolating(false);
+
+    if let Err(errors) = bundle.add_resource(resource) {
+        for error in errors {
+            warn!("Error adding {language:?} translations: {error}");
+        }
+    }
+    bundle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tr;
+
+    #[test]
+    fn all_translations_parse() {
+        for language in Language::ALL {
+            assert!(
+                FluentResource::try_new(language.fluent_source().to_string()).is_ok(),
+                "{language:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(tr!("status"), "Status");
+    }
+
+    #[test]
+    fn lookup_with_args() {
+        assert_eq!(tr!("status-error", error = "oops"), "Error: oops");
+    }
+
+    #[test]
+    fn lookup_missing() {
+        assert_eq!(
+            tr!("this-message-does-not-exist"),
+            "this-message-does-not-exist"
+        );
+    }
+}
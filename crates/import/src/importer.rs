@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The [`Importer`] trait that each format implements
+//!
+
+use open_timeline_core::{Entity, TimelineEdit};
+use open_timeline_crud::CrudError;
+use std::fmt::Display;
+use thiserror::Error;
+
+/// Errors that can arise when importing
+#[derive(Debug, Error)]
+pub enum ImportError {
+    /// There is no importer registered for the format
+    #[error("No importer for the format '{0}'")]
+    UnknownFormat(String),
+
+    /// The input couldn't be parsed by the importer
+    #[error("Invalid input: {0}")]
+    Invalid(String),
+
+    /// A CRUD error occurred when saving the import
+    #[error(transparent)]
+    CrudError(#[from] CrudError),
+
+    /// An error occured when reading the input
+    #[error(transparent)]
+    StdIo(#[from] std::io::Error),
+}
+
+/// The entities & timelines parsed by an [`Importer`] (ready to be saved with
+/// [`crate::save_import()`])
+///
+/// Timelines' member entities & subtimelines are matched up by name when saved,
+/// so the IDs given to them don't matter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Import {
+    /// The entities to import
+    pub entities: Vec<Entity>,
+
+    /// The timelines to import
+    pub timelines: Vec<TimelineEdit>,
+
+    /// The problems with records in the input that were left out (so that
+    /// they can be reviewed before the import is saved)
+    pub issues: Vec<ImportIssue>,
+}
+
+/// A record in the input that couldn't be imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportIssue {
+    /// Where the record is in the input (e.g. "Row 3")
+    pub location: String,
+
+    /// What is wrong with it
+    pub error: String,
+}
+
+impl ImportIssue {
+    /// Create an [`ImportIssue`]
+    pub fn new<L: Display, E: Display>(location: L, error: E) -> Self {
+        Self {
+            location: location.to_string(),
+            error: error.to_string(),
+        }
+    }
+}
+
+impl Display for ImportIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.error)
+    }
+}
+
+/// Implementing types can parse a format into OpenTimeline entities & timelines
+pub trait Importer: Send + Sync {
+    /// A short, unique name for the format (used to choose the importer, e.g.
+    /// `opentimeline-json`)
+    fn name(&self) -> &'static str;
+
+    /// A description of the format that is shown to users
+    fn description(&self) -> &'static str;
+
+    /// The file extensions (without the `.`) files in the format usually have
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Parse the input into entities & timelines
+    fn parse(&self, input: &[u8]) -> Result<Import, ImportError>;
+}
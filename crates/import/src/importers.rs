@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The built-in importers (each behind a cargo feature of the same name)
+//!
+
+#[cfg(feature = "opentimeline-json")]
+mod opentimeline_json;
+#[cfg(feature = "timelinejs")]
+mod timelinejs;
+#[cfg(feature = "timelinejs-sheet")]
+mod timelinejs_sheet;
+
+#[cfg(feature = "opentimeline-json")]
+pub use opentimeline_json::*;
+#[cfg(feature = "timelinejs")]
+pub use timelinejs::*;
+#[cfg(feature = "timelinejs-sheet")]
+pub use timelinejs_sheet::*;
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Importing OpenTimeline's own JSON (e.g. as served by another site's API)
+//!
+
+use crate::{Import, ImportError, Importer};
+use open_timeline_core::{Entity, TimelineEdit};
+use serde::Deserialize;
+
+/// Imports either an array of entities, or an object holding arrays of
+/// `entities` and/or `timelines`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenTimelineJsonImporter;
+
+/// The shapes of JSON accepted
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OpenTimelineJson {
+    Entities(Vec<Entity>),
+    All {
+        #[serde(default)]
+        entities: Vec<Entity>,
+        #[serde(default)]
+        timelines: Vec<TimelineEdit>,
+    },
+}
+
+impl Importer for OpenTimelineJsonImporter {
+    fn name(&self) -> &'static str {
+        "opentimeline-json"
+    }
+
+    fn description(&self) -> &'static str {
+        "OpenTimeline JSON: an array of entities, or an object with arrays of entities and/or timelines"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn parse(&self, input: &[u8]) -> Result<Import, ImportError> {
+        let json: OpenTimelineJson = serde_json::from_slice(input)
+            .map_err(|error| ImportError::Invalid(error.to_string()))?;
+        Ok(match json {
+            OpenTimelineJson::Entities(entities) => Import {
+                entities,
+                ..Default::default()
+            },
+            OpenTimelineJson::All {
+                entities,
+                timelines,
+            } => Import {
+                entities,
+                timelines,
+                ..Default::default()
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use open_timeline_core::HasIdAndName;
+
+    #[test]
+    fn parse() {
+        let entities = r#"[{"name": "Ada Lovelace", "start": {"year": 1815}}]"#;
+        let import = OpenTimelineJsonImporter.parse(entities.as_bytes()).unwrap();
+        assert_eq!(import.entities.len(), 1);
+        assert_eq!(import.entities[0].name().as_str(), "Ada Lovelace");
+        assert!(import.timelines.is_empty());
+
+        let all = r#"{"timelines": [{"name": "Computing"}]}"#;
+        let import = OpenTimelineJsonImporter.parse(all.as_bytes()).unwrap();
+        assert!(import.entities.is_empty());
+        assert_eq!(import.timelines[0].name().as_str(), "Computing");
+
+        assert!(OpenTimelineJsonImporter.parse(b"{").is_err());
+    }
+}
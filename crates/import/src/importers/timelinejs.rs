@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Importing Knight Lab's TimelineJS JSON
+//!
+//! Each event becomes an entity (named after its headline), with its group (if
+//! it has one) becoming a `group=...` tag.  If the title slide has a headline,
+//! a timeline of that name is created holding all of the entities.
+//!
+
+use crate::{Import, ImportError, ImportIssue, Importer};
+use bool_tag_expr::{Tag, TagName, TagValue, Tags};
+use open_timeline_core::{
+    Date, Entity, HasIdAndName, IsReducedCollection, IsReducedType, Name, OpenTimelineId,
+    ReducedEntities, ReducedEntity, TimelineEdit, TimelineJs, TimelineJsDate, TimelineJsSlide,
+};
+
+/// The name of the tag that events' groups become
+const GROUP_TAG_NAME: &str = "group";
+
+/// Imports TimelineJS JSON
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimelineJsImporter;
+
+impl Importer for TimelineJsImporter {
+    fn name(&self) -> &'static str {
+        "timelinejs"
+    }
+
+    fn description(&self) -> &'static str {
+        "TimelineJS JSON: events become entities, and their groups become group tags"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+
+    fn parse(&self, input: &[u8]) -> Result<Import, ImportError> {
+        let timeline_js: TimelineJs = serde_json::from_slice(input)
+            .map_err(|error| ImportError::Invalid(error.to_string()))?;
+        let events = timeline_js
+            .events
+            .into_iter()
+            .enumerate()
+            .map(|(index, event)| (format!("Event {}", index + 1), event));
+        Ok(import_slides(timeline_js.title, events))
+    }
+}
+
+/// Turn the title & events (each with its location in the input) into an
+/// [`Import`]
+pub(crate) fn import_slides<I>(title: Option<TimelineJsSlide>, events: I) -> Import
+where
+    I: IntoIterator<Item = (String, TimelineJsSlide)>,
+{
+    let mut import = Import::default();
+    for (location, event) in events {
+        match entity_from_event(event) {
+            Ok(entity) => import.entities.push(entity),
+            Err(error) => import.issues.push(ImportIssue::new(location, error)),
+        }
+    }
+
+    let title = title
+        .and_then(|title| title.text)
+        .and_then(|text| text.headline)
+        .and_then(|headline| Name::from(strip_html(&headline)).ok());
+    if let Some(title) = title
+        && !import.entities.is_empty()
+    {
+        // The IDs are placeholders (the entities are matched up by name)
+        let mut entities = ReducedEntities::new();
+        for entity in &import.entities {
+            entities
+                .collection_mut()
+                .insert(ReducedEntity::from_id_and_name(
+                    OpenTimelineId::new(),
+                    entity.name().clone(),
+                ));
+        }
+        import
+            .timelines
+            .push(TimelineEdit::from(None, title, None, Some(entities), None, None).unwrap());
+    }
+
+    import
+}
+
+/// Turn a TimelineJS event into an entity
+fn entity_from_event(event: TimelineJsSlide) -> Result<Entity, String> {
+    let headline = event
+        .text
+        .and_then(|text| text.headline)
+        .map(|headline| strip_html(&headline))
+        .unwrap_or_default();
+    let name = Name::from(headline).map_err(|_| String::from("There is no headline"))?;
+    let start = match event.start_date {
+        Some(start) => date(start).map_err(|error| format!("Invalid start date: {error}"))?,
+        None => return Err(String::from("There is no start date")),
+    };
+    let end = match event.end_date {
+        Some(end) => Some(date(end).map_err(|error| format!("Invalid end date: {error}"))?),
+        None => None,
+    };
+    let tags = match event.group {
+        Some(group) if !group.trim().is_empty() => Some(Tags::from([group_tag(&group)?])),
+        _ => None,
+    };
+    Entity::from(None, name, start, end, tags).map_err(|error| error.to_string())
+}
+
+/// Turn a TimelineJS date into an OpenTimeline date
+fn date(date: TimelineJsDate) -> Result<Date, String> {
+    Date::from(date.day, date.month, date.year).map_err(|error| error.to_string())
+}
+
+/// Turn an event's group into a tag (tag values can only contain letters &
+/// hyphens, so everything else separates words)
+fn group_tag(group: &str) -> Result<Tag, String> {
+    let value = group
+        .to_lowercase()
+        .split(|char: char| !char.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let value = TagValue::from(&value)
+        .map_err(|_| format!("The group '{group}' can't be made into a tag"))?;
+    let name = TagName::from(&GROUP_TAG_NAME).unwrap();
+    Ok(Tag::from(Some(name), value))
+}
+
+/// Remove the HTML tags from the text, and unescape the common entities
+fn strip_html(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for char in text.chars() {
+        match char {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => stripped.push(char),
+            _ => (),
+        }
+    }
+    stripped
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TIMELINE_JS: &str = r#"{
+        "title": {"text": {"headline": "<b>Computing</b>"}},
+        "events": [
+            {
+                "start_date": {"year": "1815", "month": "12", "day": "10"},
+                "end_date": {"year": 1852},
+                "text": {"headline": "Ada Lovelace", "text": "<p>Mathematician</p>"},
+                "group": "People of Note"
+            },
+            {
+                "start_date": {"year": 1936},
+                "text": {"headline": "Turing &amp; the Entscheidungsproblem"}
+            },
+            {
+                "text": {"headline": "No date"}
+            },
+            {
+                "start_date": {"year": 1945, "month": 13}
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn parse() {
+        let import = TimelineJsImporter.parse(TIMELINE_JS.as_bytes()).unwrap();
+
+        assert_eq!(import.entities.len(), 2);
+        let ada = &import.entities[0];
+        assert_eq!(ada.name().as_str(), "Ada Lovelace");
+        assert_eq!(ada.start(), Date::from(Some(10), Some(12), 1815).unwrap());
+        assert_eq!(ada.end(), Some(Date::from(None, None, 1852).unwrap()));
+        let tags: Vec<String> = ada.tags().iter().flatten().map(Tag::to_string).collect();
+        assert_eq!(tags, vec![String::from("group=people-of-note")]);
+        assert_eq!(
+            import.entities[1].name().as_str(),
+            "Turing & the Entscheidungsproblem"
+        );
+
+        assert_eq!(
+            import.issues,
+            vec![
+                ImportIssue::new("Event 3", "There is no start date"),
+                ImportIssue::new("Event 4", "There is no headline"),
+            ]
+        );
+
+        assert_eq!(import.timelines.len(), 1);
+        let timeline = &import.timelines[0];
+        assert_eq!(timeline.name().as_str(), "Computing");
+        assert_eq!(timeline.entities().as_ref().unwrap().collection().len(), 2);
+    }
+}
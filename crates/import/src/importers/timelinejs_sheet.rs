@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Importing TimelineJS's Google Sheets template (downloaded as CSV)
+//!
+//! Rows are converted the same way as TimelineJS JSON's slides are (see
+//! [`crate::TimelineJsImporter`]).  Only the columns that OpenTimeline has
+//! equivalents for are read, and era rows are left out.
+//!
+
+use super::timelinejs::import_slides;
+use crate::{Import, ImportError, ImportIssue, Importer};
+use csv::StringRecord;
+use open_timeline_core::{TimelineJsDate, TimelineJsSlide, TimelineJsText};
+
+/// Imports TimelineJS's spreadsheet template saved as CSV
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimelineJsSheetImporter;
+
+/// The indices of the columns that are read
+struct Columns {
+    year: usize,
+    month: Option<usize>,
+    day: Option<usize>,
+    end_year: Option<usize>,
+    end_month: Option<usize>,
+    end_day: Option<usize>,
+    headline: Option<usize>,
+    text: Option<usize>,
+    slide_type: Option<usize>,
+    group: Option<usize>,
+}
+
+impl Columns {
+    /// Find the columns from the header row (ignoring case & surrounding
+    /// whitespace)
+    fn from_headers(headers: &StringRecord) -> Result<Self, ImportError> {
+        let find = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim().eq_ignore_ascii_case(name))
+        };
+        Ok(Self {
+            year: find("Year")
+                .ok_or_else(|| ImportError::Invalid(String::from("There is no 'Year' column")))?,
+            month: find("Month"),
+            day: find("Day"),
+            end_year: find("End Year"),
+            end_month: find("End Month"),
+            end_day: find("End Day"),
+            headline: find("Headline"),
+            text: find("Text"),
+            slide_type: find("Type"),
+            group: find("Group"),
+        })
+    }
+}
+
+/// What a row is
+enum Row {
+    Title(TimelineJsSlide),
+    Event(TimelineJsSlide),
+    Era,
+}
+
+impl Importer for TimelineJsSheetImporter {
+    fn name(&self) -> &'static str {
+        "timelinejs-sheet"
+    }
+
+    fn description(&self) -> &'static str {
+        "TimelineJS spreadsheet (CSV): rows become entities, and their groups become group tags"
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["csv"]
+    }
+
+    fn parse(&self, input: &[u8]) -> Result<Import, ImportError> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(input);
+        let headers = reader
+            .headers()
+            .map_err(|error| ImportError::Invalid(error.to_string()))?;
+        let columns = Columns::from_headers(headers)?;
+
+        let mut title = None;
+        let mut events = Vec::new();
+        let mut issues = Vec::new();
+        for (index, record) in reader.records().enumerate() {
+            // The header is row 1
+            let location = format!("Row {}", index + 2);
+            let row = record
+                .map_err(|error| error.to_string())
+                .and_then(|record| row(&columns, &record));
+            match row {
+                Ok(Row::Title(slide)) => title = Some(slide),
+                Ok(Row::Event(slide)) => events.push((location, slide)),
+                Ok(Row::Era) => issues.push(ImportIssue::new(location, "Eras aren't imported")),
+                Err(error) => issues.push(ImportIssue::new(location, error)),
+            }
+        }
+
+        let mut import = import_slides(title, events);
+        issues.append(&mut import.issues);
+        issues.sort_by_key(|issue| row_number(&issue.location));
+        import.issues = issues;
+        Ok(import)
+    }
+}
+
+/// Read a row
+fn row(columns: &Columns, record: &StringRecord) -> Result<Row, String> {
+    let cell = |column: Option<usize>| {
+        column
+            .and_then(|column| record.get(column))
+            .map(str::trim)
+            .filter(|cell| !cell.is_empty())
+    };
+
+    let slide_type = cell(columns.slide_type).map(str::to_lowercase);
+    if slide_type.as_deref() == Some("era") {
+        return Ok(Row::Era);
+    }
+
+    let start_date = match cell(Some(columns.year)) {
+        Some(year) => Some(TimelineJsDate {
+            year: number("Year", Some(year))?.unwrap(),
+            month: number("Month", cell(columns.month))?,
+            day: number("Day", cell(columns.day))?,
+        }),
+        None => None,
+    };
+    let end_date = match cell(columns.end_year) {
+        Some(year) => Some(TimelineJsDate {
+            year: number("End Year", Some(year))?.unwrap(),
+            month: number("End Month", cell(columns.end_month))?,
+            day: number("End Day", cell(columns.end_day))?,
+        }),
+        None => None,
+    };
+    let slide = TimelineJsSlide {
+        start_date,
+        end_date,
+        text: Some(TimelineJsText {
+            headline: cell(columns.headline).map(String::from),
+            text: cell(columns.text).map(String::from),
+        }),
+        group: cell(columns.group).map(String::from),
+        unique_id: None,
+    };
+
+    Ok(match slide_type.as_deref() {
+        Some("title") => Row::Title(slide),
+        _ => Row::Event(slide),
+    })
+}
+
+/// Parse a cell holding a whole number
+fn number(column: &str, cell: Option<&str>) -> Result<Option<i64>, String> {
+    cell.map(|cell| {
+        cell.parse()
+            .map_err(|_| format!("'{cell}' in the {column} column is not a whole number"))
+    })
+    .transpose()
+}
+
+/// The row number in a "Row N" location (so that issues can be listed in order)
+fn row_number(location: &str) -> usize {
+    location
+        .trim_start_matches("Row ")
+        .parse()
+        .unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bool_tag_expr::Tag;
+    use open_timeline_core::{Date, HasIdAndName, IsReducedCollection};
+
+    const SHEET: &str = "\
+Year,Month,Day,Time,End Year,End Month,End Day,End Time,Display Date,Headline,Text,Media,Media Credit,Media Caption,Media Thumbnail,Type,Group,Background
+,,,,,,,,,Computing,The history of computing,,,,,title,,
+1815,12,10,,1852,11,27,,,Ada Lovelace,Mathematician,,,,,,People,
+1939,,,,1945,,,,,World War II,,,,,,era,,
+1936,,,,,,,,,Turing Machines,,,,,,,Ideas,
+19x5,,,,,,,,,ENIAC,,,,,,,Machines,
+,,,,,,,,,Undated,,,,,,,,
+";
+
+    #[test]
+    fn parse() {
+        let import = TimelineJsSheetImporter.parse(SHEET.as_bytes()).unwrap();
+
+        assert_eq!(import.entities.len(), 2);
+        let ada = &import.entities[0];
+        assert_eq!(ada.name().as_str(), "Ada Lovelace");
+        assert_eq!(ada.start(), Date::from(Some(10), Some(12), 1815).unwrap());
+        assert_eq!(
+            ada.end(),
+            Some(Date::from(Some(27), Some(11), 1852).unwrap())
+        );
+        let tags: Vec<String> = ada.tags().iter().flatten().map(Tag::to_string).collect();
+        assert_eq!(tags, vec![String::from("group=people")]);
+        assert_eq!(import.entities[1].name().as_str(), "Turing Machines");
+
+        assert_eq!(
+            import.issues,
+            vec![
+                ImportIssue::new("Row 4", "Eras aren't imported"),
+                ImportIssue::new("Row 6", "'19x5' in the Year column is not a whole number"),
+                ImportIssue::new("Row 7", "There is no start date"),
+            ]
+        );
+
+        assert_eq!(import.timelines.len(), 1);
+        assert_eq!(import.timelines[0].name().as_str(), "Computing");
+        let entities = import.timelines[0].entities().as_ref().unwrap();
+        assert_eq!(entities.collection().len(), 2);
+    }
+
+    #[test]
+    fn year_column_is_required() {
+        assert!(TimelineJsSheetImporter.parse(b"Headline\nAda\n").is_err());
+    }
+}
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! *Part of the wider OpenTimeline project*
+//!
+//! This library crate imports entities & timelines from other formats (e.g.
+//! CSV variants, TimelineJS JSON, museum catalogue exports).
+//!
+//! Each format is handled by an implementation of the [`Importer`] trait, which
+//! only has to parse its input into an [`Import`] - saving the entities &
+//! timelines is done the same way for all formats by [`save_import()`].
+//! Importers are collected in an [`Importers`] registry, so new formats can be
+//! contributed either:
+//!
+//! - Here, in the `importers` module behind a cargo feature of their own (so
+//!   that their dependencies are only compiled in when wanted)
+//! - In another crate, registering their importer with
+//!   [`Importers::register()`] without having to modify this or the core crates
+//!
+
+mod importer;
+mod importers;
+mod registry;
+mod save;
+
+pub use importer::*;
+#[allow(unused_imports)] // Empty if no built-in importers are enabled
+pub use importers::*;
+pub use registry::*;
+pub use save::*;
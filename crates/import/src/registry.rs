@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The registry of available [`Importer`]s
+//!
+
+use crate::{ImportError, Importer};
+use std::fmt;
+use std::path::Path;
+
+/// The importers available to use
+pub struct Importers {
+    /// In the order registered
+    importers: Vec<Box<dyn Importer>>,
+}
+
+impl Importers {
+    /// Create a registry holding the built-in importers (those enabled by the
+    /// crate's features)
+    pub fn new() -> Self {
+        // Not mutated if no built-in importers are enabled
+        #[allow(unused_mut)]
+        let mut importers = Self::empty();
+        #[cfg(feature = "opentimeline-json")]
+        importers.register(crate::OpenTimelineJsonImporter);
+        #[cfg(feature = "timelinejs")]
+        importers.register(crate::TimelineJsImporter);
+        #[cfg(feature = "timelinejs-sheet")]
+        importers.register(crate::TimelineJsSheetImporter);
+        importers
+    }
+
+    /// Create a registry without any importers
+    pub fn empty() -> Self {
+        Self {
+            importers: Vec::new(),
+        }
+    }
+
+    /// Register an importer, replacing any with the same name
+    pub fn register<I: Importer + 'static>(&mut self, importer: I) {
+        self.importers
+            .retain(|registered| registered.name() != importer.name());
+        self.importers.push(Box::new(importer));
+    }
+
+    /// The importers (in the order registered)
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Importer> {
+        self.importers.iter().map(|importer| importer.as_ref())
+    }
+
+    /// Get the importer with the name
+    pub fn by_name(&self, name: &str) -> Result<&dyn Importer, ImportError> {
+        self.iter()
+            .find(|importer| importer.name() == name)
+            .ok_or_else(|| ImportError::UnknownFormat(name.to_owned()))
+    }
+
+    /// Get the first importer for files with the path's extension
+    pub fn for_path(&self, path: &Path) -> Result<&dyn Importer, ImportError> {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        self.iter()
+            .find(|importer| importer.extensions().contains(&extension.as_str()))
+            .ok_or(ImportError::UnknownFormat(extension))
+    }
+}
+
+impl Default for Importers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Importers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|importer| importer.name()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Import;
+    use std::path::PathBuf;
+
+    struct TestImporter(&'static str);
+
+    impl Importer for TestImporter {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn description(&self) -> &'static str {
+            self.0
+        }
+
+        fn extensions(&self) -> &'static [&'static str] {
+            &["test", "tst"]
+        }
+
+        fn parse(&self, _input: &[u8]) -> Result<Import, ImportError> {
+            Ok(Import::default())
+        }
+    }
+
+    #[test]
+    fn register_and_find() {
+        let mut importers = Importers::empty();
+        assert!(importers.by_name("test").is_err());
+
+        importers.register(TestImporter("first"));
+        importers.register(TestImporter("second"));
+        assert_eq!(importers.iter().count(), 1);
+        assert_eq!(importers.by_name("test").unwrap().description(), "second");
+
+        let path = PathBuf::from("dir/file.TST");
+        assert_eq!(importers.for_path(&path).unwrap().name(), "test");
+        let path = PathBuf::from("dir/file.csv");
+        assert!(importers.for_path(&path).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Saving imported entities & timelines to the database
+//!
+
+use crate::Import;
+use open_timeline_core::{
+    HasIdAndName, IsReducedCollection, IsReducedType, Name, ReducedEntities, ReducedEntity,
+    ReducedTimeline, ReducedTimelines, TimelineEdit,
+};
+use open_timeline_crud::{
+    Create, CrudError, entity_id_from_name, is_entity_id_in_db, is_entity_name_in_db,
+    is_timeline_id_in_db, is_timeline_name_in_db, timeline_id_from_name,
+};
+use sqlx::{Sqlite, Transaction};
+
+/// What was saved by [`save_import()`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// The number of entities created
+    pub created_entities: usize,
+
+    /// The number of timelines created
+    pub created_timelines: usize,
+
+    /// The entities not created because one with the same ID or name exists
+    pub skipped_entities: Vec<Name>,
+
+    /// The timelines not created because one with the same ID or name exists
+    pub skipped_timelines: Vec<Name>,
+}
+
+/// Create the imported entities & then timelines, skipping any that are
+/// already in the database (by ID or name).
+///
+/// Timelines' member entities & subtimelines are matched up by name (so they
+/// can be existing ones, or ones imported at the same time).
+pub async fn save_import(
+    transaction: &mut Transaction<'_, Sqlite>,
+    import: Import,
+) -> Result<ImportReport, CrudError> {
+    let mut report = ImportReport::default();
+
+    for mut entity in import.entities {
+        let id_in_db = match entity.id() {
+            Some(id) => is_entity_id_in_db(transaction, &id).await?,
+            None => false,
+        };
+        if id_in_db || is_entity_name_in_db(transaction, entity.name()).await? {
+            report.skipped_entities.push(entity.name().clone());
+            continue;
+        }
+        entity.set_version(None);
+        entity.create(transaction).await?;
+        report.created_entities += 1;
+    }
+
+    for timeline in import.timelines {
+        let id_in_db = match timeline.id() {
+            Some(id) => is_timeline_id_in_db(transaction, &id).await?,
+            None => false,
+        };
+        if id_in_db || is_timeline_name_in_db(transaction, timeline.name()).await? {
+            report.skipped_timelines.push(timeline.name().clone());
+            continue;
+        }
+        let mut timeline = with_members_matched_by_name(transaction, timeline).await?;
+        timeline.create(transaction).await?;
+        report.created_timelines += 1;
+    }
+
+    Ok(report)
+}
+
+/// Replace the IDs of the timeline's member entities & subtimelines with those
+/// of the ones in the database with the same names
+async fn with_members_matched_by_name(
+    transaction: &mut Transaction<'_, Sqlite>,
+    timeline: TimelineEdit,
+) -> Result<TimelineEdit, CrudError> {
+    let entities = match timeline.entities() {
+        None => None,
+        Some(imported) => {
+            let mut entities = ReducedEntities::new();
+            for entity in imported.collection() {
+                let id = entity_id_from_name(transaction, entity.name()).await?;
+                entities
+                    .collection_mut()
+                    .insert(ReducedEntity::from_id_and_name(id, entity.name().clone()));
+            }
+            Some(entities)
+        }
+    };
+
+    let subtimelines = match timeline.subtimelines() {
+        None => None,
+        Some(imported) => {
+            let mut subtimelines = ReducedTimelines::new();
+            for subtimeline in imported.collection() {
+                let id = timeline_id_from_name(transaction, subtimeline.name()).await?;
+                subtimelines
+                    .collection_mut()
+                    .insert(ReducedTimeline::from_id_and_name(
+                        id,
+                        subtimeline.name().clone(),
+                    ));
+            }
+            Some(subtimelines)
+        }
+    };
+
+    // Can't fail
+    Ok(TimelineEdit::from(
+        timeline.id(),
+        timeline.name().clone(),
+        timeline.bool_expr().clone(),
+        entities,
+        subtimelines,
+        timeline.tags().clone(),
+    )
+    .unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Importer, OpenTimelineJsonImporter};
+    use open_timeline_core::{Entity, OpenTimelineId};
+    use open_timeline_crud::FetchByName;
+    use sqlx::SqlitePool;
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn save(pool: SqlitePool) {
+        let json = format!(
+            r#"{{
+                "entities": [
+                    {{"name": "Ada Lovelace", "start": {{"year": 1815}}}},
+                    {{"name": "Alan Turing", "start": {{"year": 1912}}}}
+                ],
+                "timelines": [
+                    {{
+                        "name": "Computing",
+                        "entities": [
+                            {{"id": "{}", "name": "Ada Lovelace"}},
+                            {{"id": "{}", "name": "Alan Turing"}}
+                        ]
+                    }}
+                ]
+            }}"#,
+            OpenTimelineId::new(),
+            OpenTimelineId::new()
+        );
+        let import = OpenTimelineJsonImporter.parse(json.as_bytes()).unwrap();
+
+        let mut transaction = pool.begin().await.unwrap();
+        let report = save_import(&mut transaction, import.clone()).await.unwrap();
+        assert_eq!(report.created_entities, 2);
+        assert_eq!(report.created_timelines, 1);
+        assert!(report.skipped_entities.is_empty());
+
+        // The members were matched up by name
+        let ada = Entity::fetch_by_name(&mut transaction, &Name::from("Ada Lovelace").unwrap())
+            .await
+            .unwrap();
+        let timeline =
+            TimelineEdit::fetch_by_name(&mut transaction, &Name::from("Computing").unwrap())
+                .await
+                .unwrap();
+        let member_ids = timeline.entities().as_ref().unwrap().ids();
+        assert!(member_ids.contains(&ada.id().unwrap()));
+
+        // Importing again skips everything
+        let report = save_import(&mut transaction, import).await.unwrap();
+        assert_eq!(report.created_entities, 0);
+        assert_eq!(report.skipped_entities.len(), 2);
+        assert_eq!(report.skipped_timelines.len(), 1);
+    }
+}
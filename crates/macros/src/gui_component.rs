@@ -0,0 +1,278 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The `GuiComponent` derive macro
+//!
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Error, Fields, Ident, Lit, Meta, NestedMeta, Path, Result,
+};
+
+/// The name of the helper attribute
+const ATTRIBUTE: &str = "gui_component";
+
+/// A field marked `#[gui_component]`
+struct Component {
+    /// The field's name
+    ident: Ident,
+
+    /// The `bool` field that must be `true` for the component to be considered
+    /// (if there is one)
+    when: Option<Ident>,
+}
+
+/// What to convert the GUI to (from `#[gui_component(..)]` on the struct)
+struct Conversion {
+    /// The OpenTimeline type to convert to
+    opentimeline_type: Path,
+
+    /// The method that builds the OpenTimeline type from the converted
+    /// components
+    from_parts: Ident,
+}
+
+/// Generate the implementations for the struct
+pub fn expand(input: DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(name, "Expected named fields")),
+        },
+        _ => return Err(Error::new_spanned(name, "Expected a struct")),
+    };
+
+    let mut components = Vec::new();
+    for field in fields {
+        for attr in field.attrs.iter().filter(|attr| is_gui_component(attr)) {
+            components.push(Component {
+                ident: field.ident.clone().unwrap(),
+                when: parse_field_attribute(attr)?,
+            });
+        }
+    }
+    if components.is_empty() {
+        return Err(Error::new_spanned(
+            name,
+            "Expected at least 1 field marked #[gui_component]",
+        ));
+    }
+
+    let conversion = match input.attrs.iter().find(|attr| is_gui_component(attr)) {
+        Some(attr) => Some(parse_struct_attribute(attr)?),
+        None => None,
+    };
+
+    let valid_synchronous = expand_valid_synchronous(name, &components);
+    let valid_asynchronous = expand_valid_asynchronous(name);
+    let valid = expand_valid(name, &components);
+    let to_opentimeline_type = conversion
+        .map(|conversion| expand_to_opentimeline_type(name, &components, &conversion))
+        .unwrap_or_default();
+
+    Ok(quote! {
+        #valid_synchronous
+        #valid_asynchronous
+        #valid
+        #to_opentimeline_type
+    })
+}
+
+/// Whether the attribute is `#[gui_component..]`
+fn is_gui_component(attr: &Attribute) -> bool {
+    attr.path.is_ident(ATTRIBUTE)
+}
+
+/// Parse `#[gui_component]` or `#[gui_component(when = "field")]`
+fn parse_field_attribute(attr: &Attribute) -> Result<Option<Ident>> {
+    match attr.parse_meta()? {
+        Meta::Path(_) => Ok(None),
+        Meta::List(list) => {
+            let mut when = None;
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("when") =>
+                    {
+                        when = Some(parse_lit_str(&name_value.lit)?);
+                    }
+                    other => return Err(Error::new_spanned(other, "Expected `when = \"..\"`")),
+                }
+            }
+            Ok(when)
+        }
+        other => Err(Error::new_spanned(other, "Expected #[gui_component(..)]")),
+    }
+}
+
+/// Parse `#[gui_component(opentimeline_type = "..", from_parts = "..")]`
+fn parse_struct_attribute(attr: &Attribute) -> Result<Conversion> {
+    let Meta::List(list) = attr.parse_meta()? else {
+        return Err(Error::new_spanned(attr, "Expected #[gui_component(..)]"));
+    };
+    let mut opentimeline_type = None;
+    let mut from_parts = None;
+    for nested in &list.nested {
+        match nested {
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("opentimeline_type") =>
+            {
+                let Lit::Str(lit) = &name_value.lit else {
+                    return Err(Error::new_spanned(&name_value.lit, "Expected a string"));
+                };
+                opentimeline_type = Some(lit.parse::<Path>()?);
+            }
+            NestedMeta::Meta(Meta::NameValue(name_value))
+                if name_value.path.is_ident("from_parts") =>
+            {
+                from_parts = Some(parse_lit_str(&name_value.lit)?);
+            }
+            other => {
+                return Err(Error::new_spanned(
+                    other,
+                    "Expected `opentimeline_type = \"..\"` or `from_parts = \"..\"`",
+                ));
+            }
+        }
+    }
+    match (opentimeline_type, from_parts) {
+        (Some(opentimeline_type), Some(from_parts)) => Ok(Conversion {
+            opentimeline_type,
+            from_parts,
+        }),
+        _ => Err(Error::new_spanned(
+            list,
+            "Expected both `opentimeline_type` and `from_parts`",
+        )),
+    }
+}
+
+/// Parse a string literal holding an identifier
+fn parse_lit_str(lit: &Lit) -> Result<Ident> {
+    match lit {
+        Lit::Str(lit) => lit.parse::<Ident>(),
+        _ => Err(Error::new_spanned(lit, "Expected a string")),
+    }
+}
+
+/// Every component is synchronously valid
+fn expand_valid_synchronous(name: &Ident, components: &[Component]) -> TokenStream {
+    let idents: Vec<&Ident> = components
+        .iter()
+        .map(|component| &component.ident)
+        .collect();
+    quote! {
+        impl open_timeline_gui_core::ValidSynchronous for #name {
+            fn is_valid_synchronous(&self) -> bool {
+                true #(&& open_timeline_gui_core::ValidSynchronous::is_valid_synchronous(&self.#idents))*
+            }
+
+            fn update_validity_synchronous(&mut self) {
+                #(open_timeline_gui_core::ValidSynchronous::update_validity_synchronous(&mut self.#idents);)*
+            }
+
+            fn validity_synchronous(&self) -> open_timeline_gui_core::ValiditySynchronous {
+                for validity in [
+                    #(open_timeline_gui_core::ValidSynchronous::validity_synchronous(&self.#idents),)*
+                ] {
+                    if let open_timeline_gui_core::ValiditySynchronous::Invalid(error) = validity {
+                        return open_timeline_gui_core::ValiditySynchronous::Invalid(error);
+                    }
+                }
+                open_timeline_gui_core::ValiditySynchronous::Valid
+            }
+        }
+    }
+}
+
+/// Components update their asynchronous validity themselves, so this is never
+/// called
+fn expand_valid_asynchronous(name: &Ident) -> TokenStream {
+    quote! {
+        impl open_timeline_gui_core::ValidAsynchronous for #name {
+            type Error = open_timeline_crud::CrudError;
+
+            fn is_valid_asynchronous(&self) -> Option<Result<(), Self::Error>> {
+                // Do nothing.  Components update their validity themselves.
+                panic!()
+            }
+
+            fn check_for_asynchronous_validity_response(&mut self) {
+                // Do nothing.  Components update their validity themselves.
+                panic!()
+            }
+
+            fn trigger_asynchronous_validity_update(&mut self) {
+                // Do nothing.  Components update their validity themselves.
+                panic!()
+            }
+        }
+    }
+}
+
+/// The first invalid (or waiting) component's validity, else valid
+fn expand_valid(name: &Ident, components: &[Component]) -> TokenStream {
+    let validities = components.iter().map(|component| {
+        let ident = &component.ident;
+        let validity = quote! {
+            validities.push(open_timeline_gui_core::Valid::validity(&self.#ident));
+        };
+        match &component.when {
+            Some(when) => quote! {
+                if self.#when {
+                    #validity
+                }
+            },
+            None => validity,
+        }
+    });
+    quote! {
+        impl open_timeline_gui_core::Valid for #name {
+            fn validity(&self) -> open_timeline_gui_core::ValidityAsynchronous {
+                let mut validities = Vec::new();
+                #(#validities)*
+                for validity in validities {
+                    match validity {
+                        open_timeline_gui_core::ValidityAsynchronous::Valid => continue,
+                        other => return other,
+                    }
+                }
+                open_timeline_gui_core::ValidityAsynchronous::Valid
+            }
+
+            fn update_validity(&mut self) {
+                // Do nothing.  Components update their validity themselves.
+                panic!()
+            }
+        }
+    }
+}
+
+/// Build the OpenTimeline type from the converted components
+fn expand_to_opentimeline_type(
+    name: &Ident,
+    components: &[Component],
+    conversion: &Conversion,
+) -> TokenStream {
+    let opentimeline_type = &conversion.opentimeline_type;
+    let from_parts = &conversion.from_parts;
+    let parts = components.iter().map(|component| {
+        let ident = &component.ident;
+        let part = quote! {
+            crate::common::ToOpenTimelineType::to_opentimeline_type(&self.#ident)
+        };
+        match &component.when {
+            Some(when) => quote! { self.#when.then(|| #part) },
+            None => part,
+        }
+    });
+    quote! {
+        impl crate::common::ToOpenTimelineType<#opentimeline_type> for #name {
+            fn to_opentimeline_type(&self) -> #opentimeline_type {
+                self.#from_parts(#(#parts),*)
+            }
+        }
+    }
+}
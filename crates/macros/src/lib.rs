@@ -8,13 +8,12 @@
 
 extern crate proc_macro;
 
+mod gui_component;
+
+use open_timeline_consts::{MAX_DAY, MAX_MONTH, MAX_YEAR, MIN_DAY, MIN_MONTH, MIN_YEAR};
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{LitInt, parse_macro_input};
-
-// TODO: these are copied from elsewhere (so are not synced)
-const MIN_YEAR: i64 = -50000;
-const MAX_YEAR: i64 = 10000;
+use syn::{DeriveInput, LitInt, parse_macro_input};
 
 /// Generate the type with compile time bounds checking
 fn generate_const_checked_integer_macro(
@@ -53,13 +52,13 @@ fn generate_const_checked_integer_macro(
 /// Create a `Day`, using `day!(x)`, with compile time checking of the value.
 #[proc_macro]
 pub fn day(input: TokenStream) -> TokenStream {
-    generate_const_checked_integer_macro(input, "Day", 1, 31)
+    generate_const_checked_integer_macro(input, "Day", MIN_DAY, MAX_DAY)
 }
 
 /// Create a `Month`, using `month!(x)`, with compile time checking of the value.
 #[proc_macro]
 pub fn month(input: TokenStream) -> TokenStream {
-    generate_const_checked_integer_macro(input, "Month", 1, 12)
+    generate_const_checked_integer_macro(input, "Month", MIN_MONTH, MAX_MONTH)
 }
 
 /// Create a `Year`, using `year!(x)`, with compile time checking of the value.
@@ -67,3 +66,32 @@ pub fn month(input: TokenStream) -> TokenStream {
 pub fn year(input: TokenStream) -> TokenStream {
     generate_const_checked_integer_macro(input, "Year", MIN_YEAR, MAX_YEAR)
 }
+
+/// Derive the validity plumbing ([`ValidSynchronous`], [`ValidAsynchronous`],
+/// and [`Valid`]) for a GUI made up of components, and optionally its
+/// conversion to an OpenTimeline type ([`ToOpenTimelineType`]).  Only for use
+/// within the GUI crate.
+///
+/// Each component field is marked `#[gui_component]`.  The GUI is valid if
+/// all of its components are, with a component marked
+/// `#[gui_component(when = "field")]` only considered when the `bool` field is
+/// `true` (its synchronous validity is still updated regardless).
+///
+/// If the struct is marked
+/// `#[gui_component(opentimeline_type = "Entity", from_parts = "entity_from_parts")]`
+/// then `ToOpenTimelineType<Entity>` is implemented by calling
+/// `self.entity_from_parts(..)` with each component converted (in the order
+/// they are declared).  Conditional components are passed as `Option`s.
+///
+/// [`ValidSynchronous`]: ../open_timeline_gui_core/trait.ValidSynchronous.html
+/// [`ValidAsynchronous`]: ../open_timeline_gui_core/trait.ValidAsynchronous.html
+/// [`Valid`]: ../open_timeline_gui_core/trait.Valid.html
+/// [`ToOpenTimelineType`]: ../open_timeline_gui/common/trait.ToOpenTimelineType.html
+#[proc_macro_derive(GuiComponent, attributes(gui_component))]
+pub fn derive_gui_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match gui_component::expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Benchmarks for the [`Engine`]'s layout work (the work that has to be redone
+//! whenever the entities, zoom, font, etc. change).
+//!
+//! Run with:
+//!
+//! ```sh
+//! cargo bench -p open-timeline-renderer --bench engine
+//! ```
+//!
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use open_timeline_core::{Date, Entity, Name, OpenTimelineId};
+use open_timeline_renderer::Engine;
+use open_timeline_renderer::frontends::svg::approximate_measure_text;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hint::black_box;
+
+/// The numbers of entities each benchmark is run with
+const ENTITY_COUNTS: [usize; 3] = [1_000, 10_000, 50_000];
+
+/// A typical desktop timeline's size
+const CANVAS_SIZE: (f64, f64) = (1_920.0, 1_080.0);
+
+/// Entities with random dates (the same on every run)
+fn entities(count: usize) -> Vec<Entity> {
+    let mut rng = StdRng::seed_from_u64(count as u64);
+    (0..count)
+        .map(|index| {
+            let start = rng.gen_range(-1000..2000);
+            let end = rng.gen_bool(0.8).then(|| start + rng.gen_range(0..100));
+            Entity::from(
+                Some(OpenTimelineId::new()),
+                Name::from(format!("Entity {index}")).unwrap(),
+                Date::from(None, None, start).unwrap(),
+                end.map(|end| Date::from(None, None, end).unwrap()),
+                None,
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+/// An engine with the entities laid out
+fn engine(entities: Vec<Entity>) -> Engine {
+    let mut engine = Engine::new(approximate_measure_text);
+    engine.set_canvas_max(CANVAS_SIZE.0, CANVAS_SIZE.1);
+    engine.set_entities(entities);
+    engine
+}
+
+fn add_entities(c: &mut Criterion) {
+    let mut group = c.benchmark_group("add_entities");
+    group.sample_size(10);
+    for count in ENTITY_COUNTS {
+        let entities = entities(count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &entities,
+            |b, entities| {
+                b.iter_batched(
+                    || (engine(Vec::new()), entities.clone()),
+                    |(mut engine, entities)| {
+                        engine.add_entities(entities);
+                        engine
+                    },
+                    criterion::BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+/// `re_calculate()` is private, so it is run via `text_measurement_changed()`
+/// (which also empties the text measurement cache, so every name is
+/// re-measured - the worst case)
+fn re_calculate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("re_calculate");
+    group.sample_size(10);
+    for count in ENTITY_COUNTS {
+        let mut engine = engine(entities(count));
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.iter(|| engine.text_measurement_changed());
+        });
+    }
+    group.finish();
+}
+
+fn entities_for_drawing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("entities_for_drawing");
+    for count in ENTITY_COUNTS {
+        let engine = engine(entities(count));
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.iter(|| black_box(engine.entities_for_drawing()));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, add_entities, re_calculate, entities_for_drawing);
+criterion_main!(benches);
@@ -0,0 +1,133 @@
+//------------------------------------------------------------------------------
+// Renders a timeline on a <canvas> from a web worker
+//
+// For large timelines that would otherwise cause jank on the main thread.
+// Dispatches the same DOM events on the canvas as the main thread renderer
+// (`ot-entity-click`, `ot-entity-select`, `ot-entity-context-menu`, and
+// `ot-viewport-change`).
+//
+// ```js
+// const renderer = new OpenTimelineWorkerRenderer(document.querySelector("canvas"))
+// renderer.setEntities(entities)
+// ```
+//------------------------------------------------------------------------------
+
+export default class OpenTimelineWorkerRenderer {
+
+	constructor(canvas, worker_url = new URL("./worker.js", import.meta.url)) {
+		this.canvas = canvas
+		this.worker = new Worker(worker_url, { type: "module" })
+
+		//----------------------------------------------------------------------
+		// Hand the canvas over to the worker
+		//----------------------------------------------------------------------
+		const offscreen = canvas.transferControlToOffscreen()
+		this.worker.postMessage(
+			{ type: "init", canvas: offscreen, device_pixel_ratio: devicePixelRatio },
+			[offscreen]
+		)
+
+		//----------------------------------------------------------------------
+		// Re-dispatch the worker's replies as DOM events
+		//----------------------------------------------------------------------
+		this.worker.onmessage = (event) => {
+			const { type, detail } = event.data
+			canvas.dispatchEvent(new CustomEvent(type, { detail, bubbles: true }))
+		}
+
+		//----------------------------------------------------------------------
+		// Keep the worker's canvas the same size as the <canvas>
+		//----------------------------------------------------------------------
+		new ResizeObserver(() => {
+			this.post({
+				type: "resize",
+				width: canvas.clientWidth,
+				height: canvas.clientHeight,
+				device_pixel_ratio: devicePixelRatio,
+			})
+		}).observe(canvas)
+
+		this.listenForPointer()
+		this.listenForWheel()
+		this.listenForContextMenu()
+	}
+
+	setEntities(entities) {
+		this.post({ type: "set_entities", entities })
+	}
+
+	addEntities(entities) {
+		this.post({ type: "add_entities", entities })
+	}
+
+	clearEntities() {
+		this.post({ type: "clear_entities" })
+	}
+
+	// e.g. `setFont({ families: ["Noto Serif", "Georgia"], weight: 700 })`
+	setFont(font) {
+		this.post({ type: "set_font", font })
+	}
+
+	post(message) {
+		this.worker.postMessage(message)
+	}
+
+	//--------------------------------------------------------------------------
+	// Drag to pan, click to select
+	//--------------------------------------------------------------------------
+	listenForPointer() {
+		let dragging = false
+		let moved = false
+		this.canvas.addEventListener("pointerdown", (event) => {
+			dragging = true
+			moved = false
+			this.canvas.setPointerCapture(event.pointerId)
+		})
+		this.canvas.addEventListener("pointermove", (event) => {
+			if (!dragging || (event.movementX === 0 && event.movementY === 0)) {
+				return
+			}
+			moved = true
+			this.post({ type: "pan", dx: event.movementX, dy: event.movementY })
+		})
+		this.canvas.addEventListener("pointerup", (event) => {
+			dragging = false
+			if (!moved) {
+				this.post({ type: "click", x: event.offsetX, y: event.offsetY })
+			}
+		})
+	}
+
+	//--------------------------------------------------------------------------
+	// Scroll to pan, ctrl/cmd + scroll to zoom
+	//--------------------------------------------------------------------------
+	listenForWheel() {
+		this.canvas.addEventListener("wheel", (event) => {
+			event.preventDefault()
+			if (event.ctrlKey || event.metaKey) {
+				const factor = (Math.abs(event.deltaY) / 250) + 1
+				this.post({
+					type: "zoom",
+					factor: event.deltaY > 0 ? 1 / factor : factor,
+					x: event.offsetX,
+					y: event.offsetY,
+				})
+			} else {
+				this.post({ type: "pan", dx: -event.deltaX, dy: -event.deltaY })
+			}
+		}, { passive: false })
+	}
+
+	//--------------------------------------------------------------------------
+	// Right-click (or long-press) on an entity for a context menu.  The worker
+	// can't say in time whether there's an entity under the pointer, so the
+	// browser's menu is never shown over the canvas.
+	//--------------------------------------------------------------------------
+	listenForContextMenu() {
+		this.canvas.addEventListener("contextmenu", (event) => {
+			event.preventDefault()
+			this.post({ type: "context_menu", x: event.offsetX, y: event.offsetY })
+		})
+	}
+}
@@ -0,0 +1,41 @@
+//------------------------------------------------------------------------------
+// Web worker that renders a timeline into an OffscreenCanvas
+//
+// Started by `OpenTimelineWorkerRenderer` (don't use directly).  The first
+// message must be `{type: "init", canvas, device_pixel_ratio}` (with the
+// canvas transferred), after which messages are passed to the renderer and any
+// replies are posted back to the page.
+//------------------------------------------------------------------------------
+
+import init, { OpenTimelineRendererOffscreenCanvas } from "./../pkg/open_timeline_renderer.js"
+
+let renderer = null
+
+// Messages that arrive while the WASM is still loading
+let queued_messages = []
+
+self.onmessage = async (event) => {
+	const message = event.data
+	if (message.type === "init") {
+		await init()
+		renderer = new OpenTimelineRendererOffscreenCanvas(message.canvas, message.device_pixel_ratio)
+		queued_messages.forEach(handleMessage)
+		queued_messages = []
+		requestAnimationFrame(drawLoop)
+	} else if (renderer === null) {
+		queued_messages.push(message)
+	} else {
+		handleMessage(message)
+	}
+}
+
+function handleMessage(message) {
+	for (const reply of renderer.handle_message(message)) {
+		self.postMessage(reply)
+	}
+}
+
+function drawLoop() {
+	renderer.draw()
+	requestAnimationFrame(drawLoop)
+}
@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Loading a timeline's entities straight from the OpenTimeline API (rather
+//! than the embedding page fetching them and passing them in).
+//!
+//! Fetching itself is left to the frontends (as each platform does it
+//! differently), with this keeping the last entities received so that
+//! refreshing only touches the engine when they have changed.
+//!
+
+use open_timeline_core::Entity;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// A timeline whose entities are fetched from the OpenTimeline API
+#[derive(Debug, Clone)]
+pub struct ApiDataSource {
+    /// The base URL of the API (e.g. `https://example.org/api/v1`)
+    api_base_url: String,
+
+    /// The ID (or name) of the timeline
+    timeline_id: String,
+
+    /// A hash of the last response body received (if one has been)
+    last_body_hash: Option<u64>,
+
+    /// The entities last received
+    entities: Vec<Entity>,
+}
+
+impl ApiDataSource {
+    /// Create a data source for the timeline served by the API
+    pub fn new(api_base_url: &str, timeline_id: &str) -> Self {
+        Self {
+            api_base_url: api_base_url.trim().trim_end_matches('/').to_owned(),
+            timeline_id: timeline_id.trim().to_owned(),
+            last_body_hash: None,
+            entities: Vec::new(),
+        }
+    }
+
+    /// The URL to fetch the timeline's entities from
+    pub fn entities_url(&self) -> String {
+        format!(
+            "{}/timelines/{}/entities",
+            self.api_base_url,
+            encode_path_segment(&self.timeline_id)
+        )
+    }
+
+    /// The entities last received
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Whether any entities have been received yet
+    pub fn has_received(&self) -> bool {
+        self.last_body_hash.is_some()
+    }
+
+    /// Take in the body of a response from [`Self::entities_url()`], returning
+    /// the entities if they differ from those last received
+    pub fn receive(&mut self, body: &str) -> Result<Option<Vec<Entity>>, serde_json::Error> {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        let body_hash = hasher.finish();
+        if self.last_body_hash == Some(body_hash) {
+            return Ok(None);
+        }
+        let entities: Vec<Entity> = serde_json::from_str(body)?;
+        self.last_body_hash = Some(body_hash);
+        if entities == self.entities {
+            return Ok(None);
+        }
+        self.entities = entities.clone();
+        Ok(Some(entities))
+    }
+}
+
+/// Percent-encode everything but unreserved characters so that the string can
+/// be used as a URL path segment
+fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use open_timeline_core::{Date, Name, OpenTimelineId};
+
+    fn entities_json(names: &[&str]) -> String {
+        let entities: Vec<Entity> = names
+            .iter()
+            .map(|name| {
+                Entity::from(
+                    Some(OpenTimelineId::new()),
+                    Name::from(*name).unwrap(),
+                    Date::from(None, None, 1900).unwrap(),
+                    None,
+                    None,
+                )
+                .unwrap()
+            })
+            .collect();
+        serde_json::to_string(&entities).unwrap()
+    }
+
+    #[test]
+    fn entities_url() {
+        let data_source = ApiDataSource::new("https://example.org/api/v1/", "World War 2");
+        assert_eq!(
+            data_source.entities_url(),
+            "https://example.org/api/v1/timelines/World%20War%202/entities"
+        );
+    }
+
+    #[test]
+    fn only_changed_entities_are_returned() {
+        let mut data_source = ApiDataSource::new("https://example.org/api/v1", "timeline");
+        assert!(!data_source.has_received());
+
+        let body = entities_json(&["Ada Lovelace", "Alan Turing"]);
+        let entities = data_source.receive(&body).unwrap().unwrap();
+        assert_eq!(entities.len(), 2);
+        assert!(data_source.has_received());
+
+        // The same response again changes nothing
+        assert_eq!(data_source.receive(&body).unwrap(), None);
+        assert_eq!(data_source.entities(), entities.as_slice());
+
+        // A different response does
+        let body = entities_json(&["Grace Hopper"]);
+        let entities = data_source.receive(&body).unwrap().unwrap();
+        assert_eq!(entities.len(), 1);
+    }
+
+    #[test]
+    fn invalid_response_keeps_the_cache() {
+        let mut data_source = ApiDataSource::new("https://example.org/api/v1", "timeline");
+        let body = entities_json(&["Ada Lovelace"]);
+        data_source.receive(&body).unwrap();
+        assert!(data_source.receive("not json").is_err());
+        assert_eq!(data_source.entities().len(), 1);
+        assert_eq!(data_source.receive(&body).unwrap(), None);
+    }
+}
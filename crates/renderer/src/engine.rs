@@ -6,32 +6,64 @@
 
 mod colours;
 mod consts;
+mod date_drag;
 mod date_range;
+#[cfg(feature = "default-measurer")]
+mod default_measurer;
 mod entity;
 mod events;
+mod focus;
+mod font;
 mod heading;
 mod helpers;
+mod highlight;
+mod inertia;
 mod layout_params;
+mod layout_strategy;
 mod point;
 mod primitives;
+mod sort_order;
+mod stacking_order;
+mod text_measurements;
+mod view_state;
+mod viewport;
 
+pub(crate) use date_drag::DateDrag;
 pub(crate) use date_range::*;
+pub(crate) use focus::*;
 pub(crate) use helpers::*;
-pub(crate) use layout_params::*;
+pub(crate) use inertia::*;
+pub(crate) use layout_strategy::find_row;
+pub(crate) use text_measurements::TextMeasurements;
+pub(crate) use view_state::ViewState;
 
 pub use colours::*;
 pub use consts::*;
+pub use date_drag::DateBoxEdge;
+#[cfg(feature = "default-measurer")]
+pub use default_measurer::*;
 pub use entity::*;
 pub use events::*;
+pub use font::*;
 pub use heading::*;
-use log::{debug, trace};
+pub use highlight::*;
+pub use layout_params::*;
+pub use layout_strategy::LayoutStrategy;
 pub use point::*;
 pub use primitives::*;
+pub use sort_order::SortOrder;
+pub use stacking_order::StackingOrder;
+use tracing::{debug, trace};
+pub use view_state::ViewStateError;
+pub use viewport::*;
 
 use crate::colour::Colour;
 use bool_tag_expr::BoolTagExpr;
-use open_timeline_core::{Date, Day, Entity, HasIdAndName, Month, OpenTimelineId, Year};
-use std::collections::BTreeSet;
+use open_timeline_core::{
+    Coordinates, Date, DateFormat, Entity, EraStyle, HasIdAndName, OpenTimelineId, StoryStep,
+    StoryStepTarget, TagHierarchy, Year,
+};
+use std::collections::{BTreeMap, BTreeSet};
 
 /// The core `open-timeline-renderer` engine.  This manages all entities,
 /// calculations, measurements, interactions, etc, common to all timeline
@@ -43,6 +75,16 @@ pub struct Engine {
     /// The boolean tag expression to filter entities by (if any)
     entity_filter: Option<BoolTagExpr>,
 
+    /// The tag hierarchy, so that a tag in the filter matches its descendants
+    tag_hierarchy: TagHierarchy,
+
+    /// Only show entities within this many kilometres of the coordinates (if
+    /// set)
+    location_filter: Option<(Coordinates, f64)>,
+
+    /// The entities the timeline is focused on (if any)
+    focus: Option<Focus>,
+
     /// The timeline headings (e.g. decades)
     headings: Vec<Heading>,
 
@@ -54,6 +96,14 @@ pub struct Engine {
     /// i.e. `function(font_size, text) -> (height, width)`
     measure_text_fn: Box<dyn Fn(f64, String) -> (f64, f64)>,
 
+    /// The measurements made with the `measure_text_fn` (so that text isn't
+    /// re-measured every time the entities are laid out)
+    text_measurements: TextMeasurements,
+
+    /// The font text is measured and drawn with (frontends measure text with
+    /// this font in the `measure_text_fn`)
+    font: FontConfig,
+
     /// The timelines date range (e.g. min/max year/decade and the number of
     /// decades)
     date_range: TimelineDateRange,
@@ -74,6 +124,10 @@ pub struct Engine {
     /// The timeline's datetime scale factor (stretch in x-direction)
     datetime_scale: f64,
 
+    /// Whether the datetime scale is picked automatically (see
+    /// [`Engine::set_datetime_scale_auto()`])
+    datetime_scale_auto: bool,
+
     /// These layout parameters are measured using the `measure_text_fn`
     measured_layout_params: MeasuredLayoutParams,
 
@@ -86,6 +140,23 @@ pub struct Engine {
     /// values.
     zoomed_layout_params: ScalableLayoutParams,
 
+    /// How entities are packed into rows
+    layout_strategy: LayoutStrategy,
+
+    /// The order entities are put into rows in
+    sort_order: SortOrder,
+
+    /// The order entities are drawn in (i.e. which are on top where they
+    /// overlap)
+    stacking_order: StackingOrder,
+
+    /// How selected entities are highlighted
+    selection_highlight: SelectionHighlight,
+
+    /// How opaque entity date boxes are (so that overlapping date boxes don't
+    /// completely hide each other)
+    date_box_alpha: f64,
+
     /// All interaction events that an external programme might be interested in
     interaction_events: Vec<TimelineInteractionEvent>,
 
@@ -93,8 +164,52 @@ pub struct Engine {
     /// rather than disappear off it (space allowing)
     sticky_text: bool,
 
+    /// How dates and years are displayed (e.g. in headings and descriptions)
+    date_format: DateFormat,
+
+    /// Today's date (where the "now" line is drawn, and what relative times
+    /// are relative to)
+    today: Date,
+
+    /// Whether a line is drawn at today's date
+    show_now_line: bool,
+
+    /// Whether headings and descriptions say how long ago things were (e.g.
+    /// "75 years ago") rather than giving dates
+    relative_time: bool,
+
     /// The size of the canvas
     canvas_size: Point,
+
+    /// The smallest font size allowed, however far the timeline is zoomed out
+    min_font_size_px: f64,
+
+    /// Keeps the timeline moving after the user lets go of it
+    pan_inertia: PanInertia,
+
+    /// Reports viewport changes once the viewport stops changing
+    viewport_change: ViewportChangeDebouncer,
+
+    /// Moves the view smoothly to where it is going (e.g. the next step of
+    /// the story)
+    viewport_animation: Option<ViewportAnimation>,
+
+    /// The story that can be played (a guided tour of the timeline)
+    story: Vec<StoryStep>,
+
+    /// The step of the story being shown (if it is being played)
+    story_step: Option<usize>,
+
+    /// Whether entities' dates can be changed by dragging the edges of their
+    /// date boxes
+    edit_mode: bool,
+
+    /// The entity date being changed by dragging (if any)
+    date_drag: Option<DateDrag>,
+
+    /// How long (ms) the viewport must be unchanged before a change is
+    /// reported
+    viewport_changed_debounce_ms: f64,
 }
 
 impl Engine {
@@ -107,41 +222,227 @@ impl Engine {
         Self {
             working_entities: Vec::new(),
             entity_filter: None,
+            tag_hierarchy: TagHierarchy::new(),
+            location_filter: None,
+            focus: None,
             headings: Vec::new(),
             measure_text_fn: Box::new(measure_text_fn),
+            text_measurements: TextMeasurements::default(),
+            font: FontConfig::default(),
             date_range: TimelineDateRange::default(),
             ids_of_selected_entities: Vec::new(),
             colours: TimelineColours::default(),
             offset: TimelineOffset::default(),
             zoom: 1.0,
             datetime_scale: MIN_DATETIME_SCALE,
+            datetime_scale_auto: false,
             measured_layout_params: MeasuredLayoutParams::default(),
             fixed_layout_params: ScalableLayoutParams::default(),
             zoomed_layout_params: ScalableLayoutParams::default(),
+            layout_strategy: LayoutStrategy::default(),
+            sort_order: SortOrder::default(),
+            stacking_order: StackingOrder::default(),
+            selection_highlight: SelectionHighlight::default(),
+            date_box_alpha: 1.0,
             interaction_events: Vec::new(),
             sticky_text: true,
+            date_format: DateFormat::default(),
+            today: today(),
+            show_now_line: false,
+            relative_time: false,
             canvas_size: Point { x: 0.0, y: 0.0 },
+            min_font_size_px: 0.0,
+            pan_inertia: PanInertia::default(),
+            viewport_change: ViewportChangeDebouncer::default(),
+            viewport_animation: None,
+            story: Vec::new(),
+            story_step: None,
+            edit_mode: false,
+            date_drag: None,
+            viewport_changed_debounce_ms: VIEWPORT_CHANGED_DEBOUNCE_MS,
         }
     }
 
+    /// Create a new engine that measures text using a bundled font (see
+    /// [`DEFAULT_MEASURER_FONT`]), for when there's no other way to measure
+    /// text (e.g. servers, the CLI, and tests)
+    #[cfg(feature = "default-measurer")]
+    pub fn new_with_default_measurer() -> Self {
+        Self::new(default_measure_text)
+    }
+
     pub fn ids_of_selected_entities(&self) -> &Vec<OpenTimelineId> {
         &self.ids_of_selected_entities
     }
 
     pub fn set_ids_of_selected_entities(&mut self, entity_ids: Vec<OpenTimelineId>) {
-        self.ids_of_selected_entities = entity_ids
+        self.update_selection(|selected| *selected = entity_ids);
     }
 
     pub fn add_id_of_selected_entity(&mut self, entity_id: OpenTimelineId) {
-        self.ids_of_selected_entities.push(entity_id);
+        self.update_selection(|selected| selected.push(entity_id));
     }
 
     pub fn clear_ids_of_selected_entities(&mut self) {
-        self.ids_of_selected_entities.clear()
+        self.update_selection(|selected| selected.clear());
     }
 
     pub fn remove_id_from_selected_entities_list(&mut self, entity_id: OpenTimelineId) {
-        self.ids_of_selected_entities.retain(|id| *id != entity_id)
+        self.update_selection(|selected| selected.retain(|id| *id != entity_id));
+    }
+
+    /// Apply the change to the selected entity IDs, highlight them, and emit a
+    /// [`TimelineInteractionEvent::SelectionChange`] if they changed
+    fn update_selection<F: FnOnce(&mut Vec<OpenTimelineId>)>(&mut self, change: F) {
+        let before = self.ids_of_selected_entities.clone();
+        change(&mut self.ids_of_selected_entities);
+        if before != self.ids_of_selected_entities {
+            for entity in self.working_entities.iter_mut() {
+                entity.is_selected = entity
+                    .entity
+                    .id()
+                    .is_some_and(|id| self.ids_of_selected_entities.contains(&id));
+            }
+            self.interaction_events
+                .push(TimelineInteractionEvent::SelectionChange(
+                    self.ids_of_selected_entities.clone(),
+                ));
+        }
+    }
+
+    /// Get the entity with the given ID (if it's on the timeline)
+    pub fn entity(&self, entity_id: OpenTimelineId) -> Option<&Entity> {
+        self.working_entities
+            .iter()
+            .map(|entity| &entity.entity)
+            .find(|entity| entity.id() == Some(entity_id))
+    }
+
+    /// Get the part of the timeline currently in view
+    pub fn viewport(&self) -> Viewport {
+        let year_width = self.measured_layout_params.year_width;
+        let decade_range_start = f64::from(self.date_range.decade_range_start);
+        let (start_year, end_year) = if year_width > 0.0 {
+            let start_year = decade_range_start - (self.offset.x / year_width);
+            (start_year, start_year + (self.canvas_size.x / year_width))
+        } else {
+            (decade_range_start, decade_range_start)
+        };
+        Viewport {
+            offset_x: self.offset.x,
+            offset_y: self.offset.y,
+            zoom: self.zoom,
+            datetime_scale: self.datetime_scale,
+            width: self.canvas_size.x,
+            height: self.canvas_size.y,
+            start_year,
+            end_year,
+        }
+    }
+
+    /// Whether a point (relative to the canvas) is somewhere an entity can be
+    /// dropped onto the timeline, i.e. on the canvas but not on the headings
+    pub fn is_drop_target(&self, x: f64, y: f64) -> bool {
+        (0.0..=self.canvas_size.x).contains(&x)
+            && (self.headings_height()..=self.canvas_size.y).contains(&y)
+    }
+
+    /// The height of the headings at the top of the canvas (see
+    /// [`Engine::heading_rows()`])
+    fn headings_height(&self) -> f64 {
+        let header_height = self.measured_layout_params.row_height_no_padding
+            + (2.0 * self.zoomed_layout_params.padding_y);
+        header_height * self.heading_rows() as f64
+    }
+
+    /// The number of rows of headings: decades, then years, months, & days as
+    /// the timeline is stretched far enough for each
+    fn heading_rows(&self) -> usize {
+        [
+            DATETIME_SCALE_THRESHOLD_SHOW_YEARS,
+            DATETIME_SCALE_THRESHOLD_SHOW_MONTHS,
+            DATETIME_SCALE_THRESHOLD_SHOW_DAYS,
+        ]
+        .iter()
+        .filter(|threshold| self.datetime_scale() > **threshold)
+        .count()
+            + 1
+    }
+
+    /// Capture what's currently in view (so that it can be returned to with
+    /// [`Engine::restore_viewport()`])
+    pub fn capture_viewport(&self) -> CapturedViewport {
+        CapturedViewport {
+            start_year: self.viewport().start_year,
+            offset_y: self.offset.y,
+            zoom: self.zoom,
+            datetime_scale: self.datetime_scale,
+        }
+    }
+
+    /// Return to a captured view (see [`Engine::capture_viewport()`])
+    pub fn restore_viewport(&mut self, captured: &CapturedViewport) {
+        if self.zoom != captured.zoom || self.datetime_scale != captured.datetime_scale {
+            self.zoom = captured.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+            self.datetime_scale = captured
+                .datetime_scale
+                .clamp(MIN_DATETIME_SCALE, MAX_DATETIME_SCALE);
+            self.update_zoomed_layout_params();
+            self.re_calculate();
+        }
+        let year_width = self.measured_layout_params.year_width;
+        let decade_range_start = f64::from(self.date_range.decade_range_start);
+        self.offset.x = (decade_range_start - captured.start_year) * year_width;
+        self.offset.y = captured.offset_y;
+        self.stop_pan_inertia();
+        self.clamp_global_offset();
+    }
+
+    /// Serialise what's in view, how entities are filtered, and which are
+    /// selected as a compact, URL-safe string (so that the view can be shared
+    /// as a link and recreated with [`Engine::apply_view_state()`])
+    pub fn serialize_view_state(&self) -> String {
+        let captured = self.capture_viewport();
+        let (start_date_limit, end_date_limit) = self.date_limits();
+        ViewState {
+            version: VIEW_STATE_VERSION,
+            start_year: captured.start_year,
+            offset_y: captured.offset_y,
+            zoom: captured.zoom,
+            datetime_scale: captured.datetime_scale,
+            tag_filter: self
+                .entity_filter
+                .clone()
+                .map(|expr| expr.to_boolean_expression()),
+            location_filter: self.location_filter,
+            start_date_limit,
+            end_date_limit,
+            selected: self.ids_of_selected_entities.clone(),
+        }
+        .encode()
+    }
+
+    /// Recreate a view serialised with [`Engine::serialize_view_state()`]
+    /// (nothing changes if the string isn't valid)
+    pub fn apply_view_state(&mut self, view_state: &str) -> Result<(), ViewStateError> {
+        let view_state = ViewState::decode(view_state)?;
+        let tag_filter = match view_state.tag_filter {
+            Some(expr) => Some(BoolTagExpr::from(expr).map_err(|_| ViewStateError::TagFilter)?),
+            None => None,
+        };
+
+        // Filter first, as it changes the layout that the viewport is in
+        self.entity_filter = tag_filter;
+        self.location_filter = view_state.location_filter;
+        self.set_date_limits(view_state.start_date_limit, view_state.end_date_limit);
+        self.set_ids_of_selected_entities(view_state.selected);
+        self.restore_viewport(&CapturedViewport {
+            start_year: view_state.start_year,
+            offset_y: view_state.offset_y,
+            zoom: view_state.zoom,
+            datetime_scale: view_state.datetime_scale,
+        });
+        Ok(())
     }
 
     /// Get the current zoom level
@@ -165,14 +466,38 @@ impl Engine {
         self.colours = colours;
     }
 
+    /// Measure the string (width, height), using the cached measurement if
+    /// there is one
+    fn measure_str(&self, str: &str) -> (f64, f64) {
+        self.text_measurements.measure(
+            self.zoomed_layout_params.font_size_px,
+            str,
+            &self.measure_text_fn,
+        )
+    }
+
     /// Calculate the width of the string
     fn str_width(&self, str: &str) -> f64 {
-        (self.measure_text_fn)(self.zoomed_layout_params.font_size_px, str.to_string()).0
+        self.measure_str(str).0
     }
 
     /// Calculate the height of the string
     fn str_height(&self, str: &str) -> f64 {
-        (self.measure_text_fn)(self.zoomed_layout_params.font_size_px, str.to_string()).1
+        self.measure_str(str).1
+    }
+
+    /// Split the string into lines no wider than the max text width (if there
+    /// is one)
+    fn wrap_str(&self, str: &str) -> WrappedText {
+        let lines = match self.zoomed_layout_params.max_text_width {
+            Some(max_width) => wrap_text(str, max_width, |line| self.str_width(line)),
+            None => vec![str.to_string()],
+        };
+        let width = lines
+            .iter()
+            .map(|line| self.str_width(line))
+            .fold(0.0, f64::max);
+        WrappedText { lines, width }
     }
 
     /// To be called when the text size changes (e.g. font size changed, or
@@ -182,16 +507,28 @@ impl Engine {
         // Calculate the row height due to text
         let row_height = self.str_height("lpfHT");
         self.measured_layout_params.row_height_no_padding = row_height;
+        self.measured_layout_params.line_height = row_height * (1.0 + LINE_GAP_FRACTION);
 
-        // Calculate the year width due to heading width and padding
-        let decade_str_width = self.str_width("1234s");
+        // Set year width
+        self.measured_layout_params.year_width = self.year_width_at(self.datetime_scale);
+    }
+
+    /// The width of a year (at the current zoom) with the datetime scale
+    fn year_width_at(&self, datetime_scale: f64) -> f64 {
+        // Calculate the year width due to heading width and padding (decades
+        // before year 0 may have an era suffix, e.g. 1230s BCE)
+        let widest_decade_str = match self.date_format.era_style {
+            _ if self.relative_time => years_ago(1234),
+            EraStyle::Negative => String::from("1234s"),
+            _ if self.date_range.decade_range_start >= 0 => String::from("1234s"),
+            _ => self.date_format.format_decade(-1230),
+        };
+        let decade_str_width = self.str_width(&widest_decade_str);
 
         // Apply X scaling
-        let decade_str_width = decade_str_width * self.datetime_scale;
+        let decade_str_width = decade_str_width * datetime_scale;
 
-        // Set year width
-        self.measured_layout_params.year_width =
-            (decade_str_width + (self.zoomed_layout_params.padding_x * 2.0)) / 10.0;
+        (decade_str_width + (self.zoomed_layout_params.padding_x * 2.0)) / 10.0
     }
 
     /// To be called when the zoom level is changed
@@ -201,10 +538,15 @@ impl Engine {
             min_inline_spacing: self.fixed_layout_params.min_inline_spacing * self.zoom,
             padding_x: self.fixed_layout_params.padding_x * self.zoom,
             padding_y: self.fixed_layout_params.padding_y * self.zoom,
-            font_size_px: self.fixed_layout_params.font_size_px * self.zoom,
+            font_size_px: (self.fixed_layout_params.font_size_px * self.zoom)
+                .max(self.min_font_size_px),
             dividing_line_thickness: self.fixed_layout_params.dividing_line_thickness * self.zoom,
             entity_highlight_thickness: self.fixed_layout_params.entity_highlight_thickness
                 * self.zoom,
+            max_text_width: self
+                .fixed_layout_params
+                .max_text_width
+                .map(|max_text_width| max_text_width * self.zoom),
         };
     }
 
@@ -214,34 +556,179 @@ impl Engine {
     /// Adjust the global offset by some delta
     pub fn set_canvas_max(&mut self, x: f64, y: f64) {
         self.canvas_size = Point { x, y };
+        if self.auto_datetime_scale().is_some() {
+            self.re_calculate();
+            self.clamp_global_offset();
+        }
     }
 
     /// Adjust the global offset by some delta
     pub fn add_to_global_offset(&mut self, x_delta: f64, y_delta: f64) {
         trace!("add_to_global_offset {}, {}", x_delta, y_delta);
+        self.viewport_animation = None;
         self.offset.x += x_delta;
         self.offset.y += y_delta;
         self.clamp_global_offset();
     }
 
-    /// Get all information needed to draw the timeline entities
-    pub fn entities_for_drawing(&self) -> Vec<EntityOut> {
-        let header_height = self.measured_layout_params.row_height_no_padding
-            + (2.0 * self.zoomed_layout_params.padding_y);
+    /// Keep the timeline moving at the given velocity (pixels per millisecond)
+    /// after the user lets go of it.  The timeline slows to a stop as
+    /// [`Engine::animate()`] is called.
+    pub fn start_pan_inertia(&mut self, x_velocity: f64, y_velocity: f64) {
+        self.pan_inertia.start(Point {
+            x: x_velocity,
+            y: y_velocity,
+        });
+    }
 
-        // Auto y offset as additional header shown as a consequence of x scaling
-        let y_offset = if self.datetime_scale() > DATETIME_SCALE_THRESHOLD_SHOW_YEARS {
-            self.offset.y + header_height
-        } else {
-            self.offset.y
+    /// Stop any inertial panning (e.g. when the user touches the timeline)
+    pub fn stop_pan_inertia(&mut self) {
+        self.pan_inertia.stop();
+    }
+
+    /// Set how long (ms) the viewport must be unchanged before a
+    /// [`TimelineInteractionEvent::ViewportChanged`] event is emitted
+    pub fn set_viewport_changed_debounce_ms(&mut self, debounce_ms: f64) {
+        self.viewport_changed_debounce_ms = debounce_ms.max(0.0);
+    }
+
+    /// Set the story that can be played (stopping the one being played, if
+    /// any)
+    pub fn set_story(&mut self, steps: Vec<StoryStep>) {
+        self.stop_story();
+        self.story = steps;
+    }
+
+    /// Get the story that can be played
+    pub fn story(&self) -> &[StoryStep] {
+        &self.story
+    }
+
+    /// Get the index of the step of the story being shown (if it is being
+    /// played)
+    pub fn story_step(&self) -> Option<usize> {
+        self.story_step
+    }
+
+    /// Show a step of the story: move (smoothly, as [`Engine::animate()`] is
+    /// called) so that it fills the canvas, selecting its entity if it shows
+    /// one
+    pub fn go_to_story_step(&mut self, step: usize) {
+        let Some(target) = self.story.get(step).map(|story_step| story_step.target) else {
+            return;
         };
+        self.story_step = Some(step);
+        self.interaction_events
+            .push(TimelineInteractionEvent::StoryStepChange(Some(step)));
+
+        // Work out where the step is, then go back to animate getting there
+        let from = self.capture_viewport();
+        match target {
+            StoryStepTarget::Entity(entity_id) => {
+                self.set_ids_of_selected_entities(vec![entity_id]);
+                self.frame_entities(&[entity_id]);
+            }
+            StoryStepTarget::Years {
+                start_year,
+                end_year,
+            } => {
+                self.clear_ids_of_selected_entities();
+                self.frame_years(start_year, end_year);
+            }
+        }
+        let to = self.capture_viewport();
+        self.restore_viewport(&from);
+        self.viewport_animation = Some(ViewportAnimation::from(from, to, STORY_STEP_ANIMATION_MS));
+    }
+
+    /// Show the next step of the story (starting it if it isn't being played)
+    pub fn next_story_step(&mut self) {
+        let step = self.story_step.map_or(0, |step| step + 1);
+        if step < self.story.len() {
+            self.go_to_story_step(step);
+        }
+    }
+
+    /// Show the previous step of the story
+    pub fn previous_story_step(&mut self) {
+        if let Some(step) = self.story_step
+            && step > 0
+        {
+            self.go_to_story_step(step - 1);
+        }
+    }
+
+    /// Stop playing the story (the view stays where it is)
+    pub fn stop_story(&mut self) {
+        if self.story_step.take().is_some() {
+            self.viewport_animation = None;
+            self.clear_ids_of_selected_entities();
+            self.interaction_events
+                .push(TimelineInteractionEvent::StoryStepChange(None));
+        }
+    }
+
+    /// Advance any animations (e.g. inertial panning) by the time elapsed since
+    /// the last call, and emit any (debounced) viewport change.  Frontends
+    /// should call this once per frame.  Returns whether there is still
+    /// animation in progress (or a viewport change waiting to be emitted).
+    pub fn animate(&mut self, ms_elapsed: f64) -> bool {
+        // Move towards where the view is going
+        if let Some(animation) = self.viewport_animation.as_mut() {
+            let (viewport, finished) = animation.step(ms_elapsed);
+            self.restore_viewport(&viewport);
+            if finished {
+                self.viewport_animation = None;
+            }
+        }
+
+        if let Some(delta) = self.pan_inertia.step(ms_elapsed) {
+            let before = self.offset;
+            self.add_to_global_offset(delta.x, delta.y);
+
+            // Stop moving in any direction the timeline can't move any further
+            if (self.offset.x - before.x).abs() < (delta.x.abs() / 2.0) {
+                self.pan_inertia.stop_x();
+            }
+            if (self.offset.y - before.y).abs() < (delta.y.abs() / 2.0) {
+                self.pan_inertia.stop_y();
+            }
+        }
+
+        // Report the viewport once it has stopped changing
+        let viewport = self.viewport();
+        let debounce_ms = self.viewport_changed_debounce_ms;
+        if let Some(viewport) = self
+            .viewport_change
+            .update(viewport, ms_elapsed, debounce_ms)
+        {
+            self.interaction_events
+                .push(TimelineInteractionEvent::ViewportChanged {
+                    start_year: viewport.start_year,
+                    end_year: viewport.end_year,
+                    zoom: viewport.zoom,
+                });
+        }
+
+        self.pan_inertia.is_moving()
+            || self.viewport_animation.is_some()
+            || self.viewport_change.is_pending()
+    }
+
+    /// Get all information needed to draw the (visible) timeline entities, in
+    /// the order they are to be drawn.  The entities are views borrowing from
+    /// the engine, so only the list itself is allocated.
+    pub fn entities_for_drawing(&self) -> Vec<EntityOut<'_>> {
+        let y_offset = self.entities_y_offset();
 
         // Combine: end, start, year_width, x_offset, y_offset, row_margin, row_height, padding
-        self.working_entities
-            .clone()
-            .into_iter()
+        let mut entities: Vec<EntityOut> = self
+            .working_entities
+            .iter()
             .filter(|entity| !entity.is_filtered_out())
-            .map(|mut entity| {
+            .map(|working| {
+                let mut entity = working.out_with_added_offset(self.offset.x, y_offset);
+
                 // Text
                 entity.text.colour = self.colours.entity.text_colour;
 
@@ -251,22 +738,33 @@ impl Engine {
 
                 // Date box
                 entity.date_box.fill_colour = self.colours.entity.date_box.fill_colour;
+                entity.date_box.fill_alpha = self.date_box_alpha;
                 entity.date_box.border_style = self.colours.entity.date_box.border;
 
-                // Return entity
-                entity
-            })
-            .map(|mut entity| {
-                if entity.is_hovered_over {
+                if working.is_selected {
+                    let highlight = self.selection_highlight;
+                    if let Some(border) =
+                        highlight.border(SELECTED_ENTITY_BORDER_THICKNESS * self.zoom)
+                    {
+                        entity.text_box.border_style = Some(border);
+                        entity.date_box.border_style = Some(border);
+                    }
+                    entity.highlight = highlight.glow(
+                        &entity.text_box.position_and_size,
+                        &entity.date_box.position_and_size,
+                        self.zoomed_layout_params.entity_highlight_thickness,
+                    );
+                }
+                if working.is_hovered_over {
                     entity.text_box.fill_colour =
                         Colour::lightened_colour(entity.text_box.fill_colour);
                     entity.date_box.fill_colour =
                         Colour::lightened_colour(entity.date_box.fill_colour);
+                    entity.date_box.fill_alpha = 1.0;
                     entity.text.colour = Colour::lightened_colour(entity.text.colour);
                 }
-                let mut entity = entity.clone_with_added_offset(self.offset.x, y_offset);
                 if self.sticky_text {
-                    entity.adjust_sticky_text(self.zoomed_layout_params.padding_x);
+                    working.adjust_sticky_text(&mut entity, self.zoomed_layout_params.padding_x);
                 }
                 entity
             })
@@ -288,10 +786,50 @@ impl Engine {
 
                 is_visible(min, max, self.canvas_size)
             })
-            .map(|entity| entity.into())
+            .collect();
+
+        // Draw overlapping entities in the chosen order
+        self.stacking_order.sort(&mut entities);
+        entities
+    }
+
+    /// Get a plain text description of each visible entity, in chronological
+    /// order (e.g. for screen readers and other assistive technology)
+    pub fn visible_entities_text_description(&self) -> Vec<String> {
+        self.entities_for_drawing()
+            .iter()
+            .map(|entity| self.entity_description(entity.entity))
             .collect()
     }
 
+    /// Get the IDs of the (visible) entities in chronological order (by start
+    /// date, then end date), e.g. to step through them one at a time
+    pub fn ids_of_visible_entities_in_date_order(&self) -> Vec<OpenTimelineId> {
+        let mut entities: Vec<&Entity> = self
+            .working_entities
+            .iter()
+            .filter(|entity| !entity.is_filtered_out())
+            .map(|entity| &entity.entity)
+            .collect();
+        entities.sort_by(|a, b| a.start().cmp(&b.start()).then(a.end().cmp(&b.end())));
+        entities
+            .into_iter()
+            .filter_map(|entity| entity.id())
+            .collect()
+    }
+
+    /// Describe the entity in plain text (e.g. for tooltips), saying how long
+    /// ago it started if using relative time
+    pub fn entity_description(&self, entity: &Entity) -> String {
+        let description = entity_text_description(entity, &self.date_format);
+        if self.relative_time {
+            let years = self.today.year().value() - entity.start_year().value();
+            format!("{description} ({})", years_ago(years).to_lowercase())
+        } else {
+            description
+        }
+    }
+
     // TODO: should just be &self
     /// Get all information needed to draw the timeline headings
     pub fn headings_for_drawing(&mut self) -> Vec<Heading> {
@@ -367,9 +905,80 @@ impl Engine {
                 }
             }
         }
+
+        // Month-dividing (& then day-dividing) lines, lighter again, in the
+        // years on the canvas
+        if self.datetime_scale() > DATETIME_SCALE_THRESHOLD_SHOW_MONTH_LINES {
+            let thickness = self.zoomed_layout_params.dividing_line_thickness;
+            let month_colour = (0..3).fold(self.colours.dividing_line.colour, |colour, _| {
+                Colour::lightened_colour(colour)
+            });
+            let day_colour = Colour::lightened_colour(month_colour);
+            let show_days = self.datetime_scale() > DATETIME_SCALE_THRESHOLD_SHOW_DAY_LINES;
+            let year_width = self.measured_layout_params.year_width;
+            for year in self.visible_years() {
+                let year_min_x = self.year_min_x(year) + self.offset.x;
+                let divisions = match show_days {
+                    true => days_of_year(year),
+                    false => months_of_year(year),
+                };
+                for (day_or_month, start, _) in divisions.into_iter().skip(1) {
+                    let colour = match show_days && day_or_month != 1 {
+                        true => day_colour,
+                        false => month_colour,
+                    };
+                    lines.push(VerticalLine {
+                        x: year_min_x + (start * year_width),
+                        style: LineStyle { colour, thickness },
+                    });
+                }
+            }
+        }
         lines
     }
 
+    /// The years (at least partly) on the canvas
+    fn visible_years(&self) -> std::ops::Range<i32> {
+        let (Some(first), Some(last)) = (
+            self.fractional_year_at(0.0),
+            self.fractional_year_at(self.canvas_size.x),
+        ) else {
+            return 0..0;
+        };
+        let first = (first.floor() as i32).max(self.date_range.decade_range_start);
+        let last = (last.ceil() as i32).min(self.date_range.decade_range_end);
+        first..last
+    }
+
+    /// The x position (without the offset) of the start of the year
+    fn year_min_x(&self, year: i32) -> f64 {
+        f64::from(year - self.date_range.decade_range_start)
+            * self.measured_layout_params.year_width
+    }
+
+    /// Get all information needed to draw the "now" line (if it's shown and
+    /// today is on the timeline).  Frontends draw it over the entities so
+    /// that it's never hidden.
+    pub fn now_line_for_drawing(&self) -> Option<VerticalLine> {
+        if !self.show_now_line {
+            return None;
+        }
+        let today = self.today.to_gregorian();
+        let year = today.year().value();
+        if year < self.date_range.decade_range_start || year >= self.date_range.decade_range_end {
+            return None;
+        }
+        let offset_in_years =
+            f64::from(year - self.date_range.decade_range_start) + today.fraction_of_year();
+        Some(VerticalLine {
+            x: (offset_in_years * self.measured_layout_params.year_width) + self.offset.x,
+            style: LineStyle {
+                colour: self.colours.heading.rect.fill_colour,
+                thickness: NOW_LINE_THICKNESS * self.zoom,
+            },
+        })
+    }
+
     /// Get all information needed to draw the timeline backgrounds
     pub fn backgrounds_for_drawing(&self) -> Vec<Background> {
         let mut backgrounds = Vec::new();
@@ -389,6 +998,231 @@ impl Engine {
         backgrounds
     }
 
+    /// The y offset added to entities when they're drawn
+    fn entities_y_offset(&self) -> f64 {
+        let header_height = self.measured_layout_params.row_height_no_padding
+            + (2.0 * self.zoomed_layout_params.padding_y);
+
+        // Auto y offset as additional headers shown as a consequence of x
+        // scaling
+        self.offset.y + (header_height * (self.heading_rows() - 1) as f64)
+    }
+
+    /// Turn edit mode on/off.  In edit mode, dragging the edges of entities'
+    /// date boxes changes their dates (see [`Engine::start_date_drag()`]).
+    pub fn set_edit_mode(&mut self, edit_mode: bool) {
+        if !edit_mode {
+            self.cancel_date_drag();
+        }
+        self.edit_mode = edit_mode;
+    }
+
+    /// Whether edit mode is on
+    pub fn edit_mode(&self) -> bool {
+        self.edit_mode
+    }
+
+    /// Get the entity and the edge of its date box at the point (relative to
+    /// the canvas), if there is one and edit mode is on.  Only entities with
+    /// an end date have a draggable end edge.
+    pub fn date_box_edge_at(&self, x: f64, y: f64) -> Option<(OpenTimelineId, DateBoxEdge)> {
+        if !self.edit_mode || !self.is_drop_target(x, y) {
+            return None;
+        }
+        let y_offset = self.entities_y_offset();
+        self.working_entities
+            .iter()
+            .rev()
+            .filter(|entity| !entity.is_filtered_out())
+            .find_map(|entity| {
+                let date_box = entity.date_box.position_and_size;
+                let top = date_box.position.y + y_offset;
+                if !(top..=(top + date_box.height)).contains(&y) {
+                    return None;
+                }
+                let start_x = date_box.position.x + self.offset.x;
+                let end_x = start_x + date_box.width;
+                let near = |edge_x: f64| (x - edge_x).abs() <= DATE_DRAG_EDGE_TOLERANCE_PX;
+
+                // Prefer the nearest edge when the date box is narrow
+                let nearer_end = (x - end_x).abs() < (x - start_x).abs();
+                let edge = if entity.entity.end().is_some() && near(end_x) && nearer_end {
+                    DateBoxEdge::End
+                } else if near(start_x) {
+                    DateBoxEdge::Start
+                } else if entity.entity.end().is_some() && near(end_x) {
+                    DateBoxEdge::End
+                } else {
+                    return None;
+                };
+                entity.entity.id().map(|id| (id, edge))
+            })
+    }
+
+    /// Start changing the entity's date by dragging the edge of its date box
+    /// (only in edit mode).  Returns whether the drag started.
+    pub fn start_date_drag(&mut self, entity_id: OpenTimelineId, edge: DateBoxEdge) -> bool {
+        if !self.edit_mode {
+            return false;
+        }
+        let Some(entity) = self.entity(entity_id) else {
+            return false;
+        };
+        if edge == DateBoxEdge::End && entity.end().is_none() {
+            return false;
+        }
+        self.date_drag = Some(DateDrag {
+            entity_id,
+            edge,
+            original_start: entity.start(),
+            original_end: entity.end(),
+        });
+        self.stop_pan_inertia();
+        true
+    }
+
+    /// Whether an entity's date is being changed by dragging
+    pub fn is_dragging_date(&self) -> bool {
+        self.date_drag.is_some()
+    }
+
+    /// Move the dragged edge to the point (x relative to the canvas), snapping
+    /// the date to the nearest year (or month if zoomed in far enough).  Dates
+    /// that would end the entity before it starts are ignored.
+    pub fn update_date_drag(&mut self, x: f64) {
+        let Some(drag) = self.date_drag else {
+            return;
+        };
+        let Some(date) = self.snapped_date_at(x) else {
+            return;
+        };
+        let Some(entity) = self.entity_mut(drag.entity_id) else {
+            return;
+        };
+        let changed = match drag.edge {
+            DateBoxEdge::Start => entity.start() != date && entity.set_start(date).is_ok(),
+            DateBoxEdge::End => entity.end() != Some(date) && entity.set_end(date).is_ok(),
+        };
+        if changed {
+            self.re_calculate();
+        }
+    }
+
+    /// Finish dragging, emitting [`TimelineInteractionEvent::DatesChanged`]
+    /// if the entity's dates changed
+    pub fn end_date_drag(&mut self) {
+        let Some(drag) = self.date_drag.take() else {
+            return;
+        };
+        let Some(entity) = self.entity(drag.entity_id) else {
+            return;
+        };
+        let (start, end) = (entity.start(), entity.end());
+        if (start, end) != (drag.original_start, drag.original_end) {
+            self.interaction_events
+                .push(TimelineInteractionEvent::DatesChanged {
+                    entity_id: drag.entity_id,
+                    start,
+                    end,
+                    previous_start: drag.original_start,
+                    previous_end: drag.original_end,
+                });
+        }
+    }
+
+    /// Stop dragging, putting the entity's dates back to how they were
+    pub fn cancel_date_drag(&mut self) {
+        if let Some(drag) = self.date_drag.take() {
+            self.set_entity_dates(drag.entity_id, drag.original_start, drag.original_end);
+        }
+    }
+
+    /// Change the dates of an entity on the timeline (e.g. to undo dragging,
+    /// or if saving the change failed).  Returns whether the dates are valid
+    /// (nothing changes if not).
+    pub fn set_entity_dates(
+        &mut self,
+        entity_id: OpenTimelineId,
+        start: Date,
+        end: Option<Date>,
+    ) -> bool {
+        let Some(entity) = self.entity_mut(entity_id) else {
+            return false;
+        };
+        let mut updated = entity.clone();
+
+        // Change the end first if the start is moving past it
+        let is_valid = match end {
+            Some(end) if updated.end().is_some_and(|current| start > current) => {
+                updated.set_end(end).is_ok() && updated.set_start(start).is_ok()
+            }
+            Some(end) => updated.set_start(start).is_ok() && updated.set_end(end).is_ok(),
+            None => updated.end().is_none() && updated.set_start(start).is_ok(),
+        };
+        if is_valid {
+            *entity = updated;
+            self.re_calculate();
+        }
+        is_valid
+    }
+
+    /// The date at the point (x relative to the canvas) to the nearest year,
+    /// or month if zoomed in far enough for months to be dragged between
+    fn snapped_date_at(&self, x: f64) -> Option<Date> {
+        let year = self.fractional_year_at(x)?;
+        if self.measured_layout_params.year_width >= DATE_DRAG_MONTH_SNAP_MIN_YEAR_WIDTH {
+            let months = (year * 12.0).round() as i64;
+            Date::from(None, Some(months.rem_euclid(12) + 1), months.div_euclid(12)).ok()
+        } else {
+            Date::from(None, None, year.round() as i64).ok()
+        }
+    }
+
+    /// The (fractional) year at the point (x relative to the canvas)
+    fn fractional_year_at(&self, x: f64) -> Option<f64> {
+        let year_width = self.measured_layout_params.year_width;
+        if year_width <= 0.0 {
+            return None;
+        }
+        Some(f64::from(self.date_range.decade_range_start) + ((x - self.offset.x) / year_width))
+    }
+
+    /// Get the year (as a [`Date`]) at the point (relative to the canvas), if
+    /// the point is on the timeline (i.e. not on the headings)
+    pub fn date_at(&self, x: f64, y: f64) -> Option<Date> {
+        if !self.is_drop_target(x, y) {
+            return None;
+        }
+        let year = self.fractional_year_at(x)?.floor() as i64;
+        Date::from(None, None, year).ok()
+    }
+
+    /// Click on the timeline where there isn't an entity, which clears the
+    /// selection (emitting a [`TimelineInteractionEvent::SelectionChange`] if
+    /// anything was selected)
+    pub fn click_on_empty_space(&mut self) {
+        self.clear_ids_of_selected_entities();
+    }
+
+    /// Double click on the timeline where there isn't an entity (the point is
+    /// relative to the canvas), emitting
+    /// [`TimelineInteractionEvent::EmptySpaceDoubleClick`] with the year
+    /// clicked on
+    pub fn double_click_on_empty_space(&mut self, x: f64, y: f64) {
+        if let Some(date) = self.date_at(x, y) {
+            self.interaction_events
+                .push(TimelineInteractionEvent::EmptySpaceDoubleClick(date));
+        }
+    }
+
+    /// Get the entity with the given ID (if it's on the timeline) to change it
+    fn entity_mut(&mut self, entity_id: OpenTimelineId) -> Option<&mut Entity> {
+        self.working_entities
+            .iter_mut()
+            .map(|entity| &mut entity.entity)
+            .find(|entity| entity.id() == Some(entity_id))
+    }
+
     /// Get all events for dispatching & handling
     pub fn drain_interaction_events(&mut self) -> std::vec::Drain<'_, TimelineInteractionEvent> {
         self.interaction_events.drain(..)
@@ -409,6 +1243,18 @@ impl Engine {
             .push(TimelineInteractionEvent::TripleClick(entity_id));
     }
 
+    /// Ask for a context menu for the entity (e.g. on right-click or
+    /// long-press) at the point (relative to the canvas).  The entity is
+    /// selected (unless it already is, so that a menu can act on everything
+    /// selected), and a [`TimelineInteractionEvent::ContextMenu`] is emitted.
+    pub fn context_menu_on_entity(&mut self, entity_id: OpenTimelineId, x: f64, y: f64) {
+        if !self.ids_of_selected_entities.contains(&entity_id) {
+            self.set_ids_of_selected_entities(vec![entity_id]);
+        }
+        self.interaction_events
+            .push(TimelineInteractionEvent::ContextMenu(entity_id, x, y));
+    }
+
     pub fn hover_over_entity(&mut self, entity_id: Option<OpenTimelineId>) {
         match entity_id {
             Some(entity_id) => {
@@ -450,6 +1296,58 @@ impl Engine {
         self.sticky_text = sticky_text;
     }
 
+    /// Whether the text of an entity sticks to the left of the screen
+    pub fn sticky_text(&self) -> bool {
+        self.sticky_text
+    }
+
+    /// Set how dates and years are displayed
+    pub fn set_date_format(&mut self, date_format: DateFormat) {
+        if self.date_format != date_format {
+            self.date_format = date_format;
+            self.re_calculate();
+        }
+    }
+
+    /// How dates and years are displayed
+    pub fn date_format(&self) -> DateFormat {
+        self.date_format
+    }
+
+    /// Set today's date (it's the local date by default)
+    pub fn set_today(&mut self, today: Date) {
+        if self.today != today {
+            self.today = today;
+            // Ongoing entities' durations are until today
+            self.sort_entities();
+            self.re_calculate();
+        }
+    }
+
+    /// Set whether a line is drawn at today's date
+    pub fn set_show_now_line(&mut self, show_now_line: bool) {
+        self.show_now_line = show_now_line;
+    }
+
+    /// Whether a line is drawn at today's date
+    pub fn show_now_line(&self) -> bool {
+        self.show_now_line
+    }
+
+    /// Set whether headings and descriptions say how long ago things were (e.g.
+    /// "75 years ago") rather than giving dates
+    pub fn set_relative_time(&mut self, relative_time: bool) {
+        if self.relative_time != relative_time {
+            self.relative_time = relative_time;
+            self.re_calculate();
+        }
+    }
+
+    /// Whether headings and descriptions say how long ago things were
+    pub fn relative_time(&self) -> bool {
+        self.relative_time
+    }
+
     // TODO: rename (returns decade floor & ceil years, not dates)
     /// Get the timeline's earliest and latest dates
     pub fn start_and_end_dates(&self) -> (i32, i32) {
@@ -459,6 +1357,32 @@ impl Engine {
         )
     }
 
+    /// Make the timeline include the given years (start and end) even if it
+    /// has no entities in them, or not (`None`).  Timelines shown together
+    /// should share the same years so that their axes line up (see
+    /// [`Engine::sync_x_axis()`]).
+    pub fn set_shared_axis_years(&mut self, years: Option<(i32, i32)>) {
+        self.date_range.shared_axis_years = years;
+        self.re_calculate();
+    }
+
+    /// Line the timeline's time axis up with that of another timeline (i.e.
+    /// use its zoom, datetime scale, and horizontal offset).  The timelines
+    /// should share the same axis years (see
+    /// [`Engine::set_shared_axis_years()`]).
+    pub fn sync_x_axis(&mut self, viewport: &Viewport) {
+        if self.zoom != viewport.zoom || self.datetime_scale != viewport.datetime_scale {
+            self.zoom = viewport.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+            self.datetime_scale = viewport
+                .datetime_scale
+                .clamp(MIN_DATETIME_SCALE, MAX_DATETIME_SCALE);
+            self.update_zoomed_layout_params();
+            self.re_calculate();
+        }
+        self.offset.x = viewport.offset_x;
+        self.clamp_global_offset();
+    }
+
     /// Get the current date limts
     pub fn date_limits(&self) -> (Option<Date>, Option<Date>) {
         (
@@ -467,18 +1391,121 @@ impl Engine {
         )
     }
 
-    pub fn set_font_size_px(&mut self, font_size_px: f64) {
-        self.fixed_layout_params.font_size_px = font_size_px;
+    pub fn set_font_size_px(&mut self, font_size_px: f64) {
+        self.fixed_layout_params.font_size_px = font_size_px;
+        self.update_zoomed_layout_params();
+        self.re_calculate();
+    }
+
+    /// Get the layout params (as set, i.e. not accounting for zooming)
+    pub fn layout_params(&self) -> ScalableLayoutParams {
+        self.fixed_layout_params
+    }
+
+    pub fn set_layout_params(&mut self, layout_params: ScalableLayoutParams) {
+        self.fixed_layout_params = layout_params;
+        self.update_zoomed_layout_params();
+        self.re_calculate();
+    }
+
+    /// Set the width (before zooming) that entity names wrap at (names aren't
+    /// wrapped if `None`)
+    pub fn set_max_text_width(&mut self, max_text_width: Option<f64>) {
+        self.fixed_layout_params.max_text_width = max_text_width;
+        self.update_zoomed_layout_params();
+        self.re_calculate();
+    }
+
+    /// Get how entities are packed into rows
+    pub fn layout_strategy(&self) -> LayoutStrategy {
+        self.layout_strategy
+    }
+
+    /// Set how entities are packed into rows
+    pub fn set_layout_strategy(&mut self, layout_strategy: LayoutStrategy) {
+        if self.layout_strategy != layout_strategy {
+            self.layout_strategy = layout_strategy;
+            self.re_calculate();
+        }
+    }
+
+    /// Get the order entities are put into rows in
+    pub fn sort_order(&self) -> &SortOrder {
+        &self.sort_order
+    }
+
+    /// Set the order entities are put into rows in (re-packing the rows)
+    pub fn set_sort(&mut self, sort_order: SortOrder) {
+        if self.sort_order != sort_order {
+            self.sort_order = sort_order;
+            self.sort_entities();
+            self.re_calculate();
+        }
+    }
+
+    /// Get the order entities are drawn in
+    pub fn stacking_order(&self) -> StackingOrder {
+        self.stacking_order
+    }
+
+    /// Set the order entities are drawn in (i.e. which are on top where they
+    /// overlap)
+    pub fn set_stacking_order(&mut self, stacking_order: StackingOrder) {
+        self.stacking_order = stacking_order;
+    }
+
+    /// Get how selected entities are highlighted
+    pub fn selection_highlight(&self) -> SelectionHighlight {
+        self.selection_highlight
+    }
+
+    /// Set how selected entities are highlighted (e.g. a glow rather than a
+    /// border, and in which colour)
+    pub fn set_selection_highlight(&mut self, selection_highlight: SelectionHighlight) {
+        self.selection_highlight = selection_highlight;
+    }
+
+    /// Get how opaque entity date boxes are
+    pub fn date_box_alpha(&self) -> f64 {
+        self.date_box_alpha
+    }
+
+    /// Set how opaque entity date boxes are (`0.0` is fully transparent, `1.0`
+    /// is solid).  Translucent date boxes keep dense, overlapping parts of the
+    /// timeline legible.  Hovered over entities are always solid.
+    pub fn set_date_box_alpha(&mut self, alpha: f64) {
+        self.date_box_alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    /// Set the smallest font size allowed (zooming out won't shrink text below
+    /// this size)
+    pub fn set_min_font_size_px(&mut self, min_font_size_px: f64) {
+        self.min_font_size_px = min_font_size_px;
         self.update_zoomed_layout_params();
         self.re_calculate();
     }
 
-    pub fn set_layout_params(&mut self, layout_params: ScalableLayoutParams) {
-        self.fixed_layout_params = layout_params;
-        self.update_zoomed_layout_params();
+    /// To be called when the `measure_text_fn` would measure text differently
+    /// (e.g. the font family changed)
+    pub fn text_measurement_changed(&mut self) {
+        self.text_measurements.clear();
         self.re_calculate();
     }
 
+    /// Get the font text is measured and drawn with
+    pub fn font(&self) -> &FontConfig {
+        &self.font
+    }
+
+    /// Set the font text is measured and drawn with.  The frontend's
+    /// `measure_text_fn` must measure with the new font before this is called.
+    pub fn set_font(&mut self, font: FontConfig) {
+        if self.font != font {
+            self.font = font;
+            self.text_measurement_changed();
+        }
+    }
+
     /// Accounts for zooming (might not be the same as the font size that is set
     /// using `.set_font_size_px()`)
     pub fn effective_font_size_px(&self) -> f64 {
@@ -503,16 +1530,18 @@ impl Engine {
     }
 
     // TODO: Merge in the new entities, ignoring any duplicates
-    /// Add new entities to the timeline (ignores duplicates)
-    pub fn add_entities(&mut self, entities: Vec<Entity>) {
+    /// Add new entities to the timeline (ignores duplicates).  Entities without
+    /// IDs are given ephemeral ones (see [`assign_render_ids()`]).
+    pub fn add_entities(&mut self, mut entities: Vec<Entity>) {
+        assign_render_ids(&mut entities);
         for entity in entities {
-            let text_width = self.str_width(&entity.name().to_string());
+            let wrapped = self.wrap_str(entity.name().as_str());
             let entity_working = WorkingEntity::from(
                 entity,
                 self.colours,
                 self.measured_layout_params,
                 self.zoomed_layout_params,
-                text_width,
+                wrapped,
             );
             self.working_entities.push(entity_working);
         }
@@ -527,6 +1556,40 @@ impl Engine {
         self.add_entities(entities);
     }
 
+    /// Overwrite the list of entities drawn on the timeline with those in the
+    /// JSON array (e.g. to preview a file of entities, which needn't have IDs)
+    pub fn load_from_json(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        let entities: Vec<Entity> = serde_json::from_str(json)?;
+        self.set_entities(entities);
+        Ok(())
+    }
+
+    /// Update the entities drawn on the timeline to be `entities` (e.g. when a
+    /// dynamic timeline's entities change as entities gain/lose tags).  Unlike
+    /// [`Engine::set_entities()`], unchanged entities are kept as they are so
+    /// only new or changed entities need measuring (entities without IDs are
+    /// always new).
+    pub fn sync_entities(&mut self, mut entities: Vec<Entity>) {
+        assign_render_ids(&mut entities);
+        let mut entities: BTreeMap<OpenTimelineId, Entity> = entities
+            .into_iter()
+            .filter_map(|entity| entity.id().map(|id| (id, entity)))
+            .collect();
+        self.working_entities.retain(|working_entity| {
+            let Some(id) = working_entity.entity.id() else {
+                return false;
+            };
+            match entities.get(&id) {
+                Some(entity) if *entity == working_entity.entity => {
+                    entities.remove(&id);
+                    true
+                }
+                _ => false,
+            }
+        });
+        self.add_entities(entities.into_values().collect());
+    }
+
     /// Set the engine to filter entities by the given tag bool expression
     pub fn set_tag_bool_expr_entity_filter(&mut self, tag_bool_expr: BoolTagExpr) {
         self.entity_filter = Some(tag_bool_expr);
@@ -539,20 +1602,174 @@ impl Engine {
         self.re_calculate();
     }
 
+    /// Set the tag hierarchy used by the tag bool expression filter (a tag
+    /// matches its descendants)
+    pub fn set_tag_hierarchy(&mut self, tag_hierarchy: TagHierarchy) {
+        self.tag_hierarchy = tag_hierarchy;
+        self.re_calculate();
+    }
+
+    /// Set the engine to only show entities within `radius_km` kilometres of
+    /// the coordinates (entities without coordinates are hidden)
+    pub fn set_location_entity_filter(&mut self, coordinates: Coordinates, radius_km: f64) {
+        self.location_filter = Some((coordinates, radius_km));
+        self.re_calculate();
+    }
+
+    /// Remove the entity location filter
+    pub fn remove_location_entity_filter(&mut self) {
+        self.location_filter = None;
+        self.re_calculate();
+    }
+
+    /// The entity location filter (coordinates and radius in kilometres), if
+    /// there is one
+    pub fn location_entity_filter(&self) -> Option<(Coordinates, f64)> {
+        self.location_filter
+    }
+
+    /// Zoom and move the timeline to frame the selected entities, optionally
+    /// hiding all other entities until [`Engine::exit_focus()`] is called.
+    /// Does nothing if no entities are selected.
+    pub fn focus_on_selection(&mut self, hide_others: bool) {
+        if self.ids_of_selected_entities.is_empty() {
+            return;
+        }
+
+        // Re-focusing keeps the view from before the first focus so that
+        // exiting focus always goes back to where the user started
+        let (previous_zoom, previous_offset) = match self.focus.take() {
+            Some(focus) => (focus.previous_zoom, focus.previous_offset),
+            None => (self.zoom, self.offset),
+        };
+        self.focus = Some(Focus {
+            entity_ids: self.ids_of_selected_entities.iter().copied().collect(),
+            hide_others,
+            previous_zoom,
+            previous_offset,
+        });
+        self.re_calculate();
+
+        let entity_ids = self.ids_of_selected_entities.clone();
+        self.frame_entities(&entity_ids);
+    }
+
+    /// Show all entities again and go back to the view from before
+    /// [`Engine::focus_on_selection()`] was called
+    pub fn exit_focus(&mut self) {
+        if let Some(focus) = self.focus.take() {
+            self.zoom = focus.previous_zoom;
+            self.offset = focus.previous_offset;
+            self.update_zoomed_layout_params();
+            self.re_calculate();
+        }
+    }
+
+    /// Whether the timeline is focused on some entities
+    pub fn is_focused(&self) -> bool {
+        self.focus.is_some()
+    }
+
+    /// Zoom and move the timeline so that the (visible) entities fill the
+    /// canvas, as far as the zoom limits allow
+    pub fn frame_entities(&mut self, entity_ids: &[OpenTimelineId]) {
+        let row_height = self.row_height();
+        let available_width = self.canvas_size.x * (1.0 - (2.0 * FRAME_MARGIN_FRACTION));
+        let available_height =
+            (self.canvas_size.y - row_height) * (1.0 - (2.0 * FRAME_MARGIN_FRACTION));
+        for _ in 0..FRAME_ZOOM_ITERATIONS {
+            let Some((min, max)) = self.bounds_of_entities(entity_ids) else {
+                return;
+            };
+            let (width, height) = (max.x - min.x, max.y - min.y);
+            if width <= 0.0 || height <= 0.0 || available_width <= 0.0 || available_height <= 0.0 {
+                break;
+            }
+            let factor = (available_width / width).min(available_height / height);
+            if (factor - 1.0).abs() < 0.01 {
+                break;
+            }
+            self.set_zoom(self.zoom * factor);
+        }
+
+        // Centre the entities horizontally, with the top one in the first row
+        let Some((min, max)) = self.bounds_of_entities(entity_ids) else {
+            return;
+        };
+        self.offset.x = ((self.canvas_size.x - (max.x - min.x)) / 2.0) - min.x;
+        self.offset.y = self.row_height() - min.y;
+        self.clamp_global_offset();
+    }
+
+    /// Zoom and move the timeline so that the years fill the width of the
+    /// canvas, as far as the zoom limits allow (the vertical offset is kept)
+    pub fn frame_years(&mut self, start_year: f64, end_year: f64) {
+        let available_width = self.canvas_size.x * (1.0 - (2.0 * FRAME_MARGIN_FRACTION));
+        let years = end_year - start_year;
+        for _ in 0..FRAME_ZOOM_ITERATIONS {
+            let width = years * self.measured_layout_params.year_width;
+            if width <= 0.0 || available_width <= 0.0 {
+                break;
+            }
+            let factor = available_width / width;
+            if (factor - 1.0).abs() < 0.01 {
+                break;
+            }
+            self.set_zoom(self.zoom * factor);
+        }
+
+        // Centre the years
+        let year_width = self.measured_layout_params.year_width;
+        if year_width > 0.0 {
+            let centre_year = (start_year + end_year) / 2.0;
+            let left_year = centre_year - (self.canvas_size.x / year_width / 2.0);
+            let decade_range_start = f64::from(self.date_range.decade_range_start);
+            self.offset.x = (decade_range_start - left_year) * year_width;
+        }
+        self.clamp_global_offset();
+    }
+
+    /// The top left and bottom right corners (without the global offset) of
+    /// the smallest box around the (visible) entities
+    fn bounds_of_entities(&self, entity_ids: &[OpenTimelineId]) -> Option<(Point, Point)> {
+        self.working_entities
+            .iter()
+            .filter(|entity| !entity.is_filtered_out())
+            .filter(|entity| {
+                entity
+                    .entity
+                    .id()
+                    .is_some_and(|id| entity_ids.contains(&id))
+            })
+            .map(|entity| {
+                let min = Point {
+                    x: entity.min_x(),
+                    y: entity.text_box.position_and_size.position.y,
+                };
+                let max = Point {
+                    x: entity.max_x(),
+                    y: entity.max_y(),
+                };
+                (min, max)
+            })
+            .reduce(|(a_min, a_max), (b_min, b_max)| (a_min.min(b_min), a_max.max(b_max)))
+    }
+
     /// Re-run all calculations
     fn re_calculate(&mut self) {
         self.update_entities_filtered();
         self.update_timeline_date_range();
+        self.update_auto_datetime_scale();
         self.update_measured_layout_params();
 
         let mut cloned = self.working_entities.clone();
         for entity in cloned.iter_mut() {
-            let text_width = self.str_width(&entity.entity.name().to_string());
+            let wrapped = self.wrap_str(entity.entity.name().as_str());
             entity.update_if_appropriate(
                 self.colours,
                 self.measured_layout_params,
                 self.zoomed_layout_params,
-                text_width,
+                wrapped,
             );
         }
         self.working_entities = cloned;
@@ -562,8 +1779,10 @@ impl Engine {
     fn update_entities_filtered(&mut self) {
         let date_range = self.date_range;
         for entity in self.working_entities.iter_mut() {
-            entity.update_filtered_by_bool_tag_expr(&self.entity_filter);
+            entity.update_filtered_by_bool_tag_expr(&self.entity_filter, &self.tag_hierarchy);
+            entity.update_filtered_by_location(&self.location_filter);
             entity.update_filtered_by_date_range(&date_range);
+            entity.update_filtered_by_focus(&self.focus);
         }
     }
 
@@ -573,21 +1792,28 @@ impl Engine {
         self.offset.x = self.offset.x.min(0.0);
         self.offset.y = self.offset.y.min(0.0);
 
-        // Get max X and max Y points for entities
+        // Get max X and max Y points for entities (filtered out entities
+        // aren't given a row, so their y positions are stale)
         let max_x_entity = self.working_entities.iter().max_by(|a, b| {
             a.max_x()
                 .partial_cmp(&b.max_x())
                 .unwrap_or(std::cmp::Ordering::Less)
         });
-        let max_y_entity = self.working_entities.iter().max_by(|a, b| {
+        let visible_entities = self
+            .working_entities
+            .iter()
+            .filter(|entity| !entity.is_filtered_out());
+        let max_y_entity = visible_entities.max_by(|a, b| {
             a.max_y()
                 .partial_cmp(&b.max_y())
                 .unwrap_or(std::cmp::Ordering::Less)
         });
 
-        // Update offsets
+        // Update offsets (the headings span all decades, even those without
+        // entities)
         if let Some(max_x_entity) = max_x_entity {
-            let max_x = max_x_entity.max_x();
+            let headings_max_x = f64::from(self.date_range.decade_count) * self.decade_width();
+            let max_x = max_x_entity.max_x().max(headings_max_x);
             let timeline_is_wider_than_canvas = max_x > self.canvas_size.x;
             if timeline_is_wider_than_canvas {
                 self.offset.x = self.offset.x.max(self.canvas_size.x - max_x);
@@ -611,6 +1837,7 @@ impl Engine {
         // Limit the maximum zoom.  Adjust the factor to avoid the timeline jumping
         // when max zoom reached.  Float comparison is fine because we only set it
         // here and we set it to an exact value
+        self.viewport_animation = None;
         if self.zoom == MAX_ZOOM {
             return;
         }
@@ -639,6 +1866,7 @@ impl Engine {
         // Limit the minimum zoom.  Adjust the factor to avoid the timeline jumping
         // when max zoom reached.  Float comparison is fine because we only set it
         // here and we set it to an exact value
+        self.viewport_animation = None;
         if self.zoom == MIN_ZOOM {
             return;
         }
@@ -662,6 +1890,16 @@ impl Engine {
         self.re_calculate();
     }
 
+    /// Zoom in (factor greater than 1) or out (factor less than 1) around the
+    /// given point (e.g. the midpoint of a pinch)
+    pub fn zoom_by(&mut self, factor: f64, x_local_offset: f64, y_local_offset: f64) {
+        if factor > 1.0 {
+            self.zoom_in(factor, x_local_offset, y_local_offset);
+        } else if factor > 0.0 && factor < 1.0 {
+            self.zoom_out(1.0 / factor, x_local_offset, y_local_offset);
+        }
+    }
+
     /// Set the zoom (use for jumping to zoom level).  Values are clamped
     /// between `MIN_SCALE` and `MAX_SCALE`
     pub fn set_zoom(&mut self, zoom: f64) {
@@ -670,13 +1908,87 @@ impl Engine {
         self.re_calculate();
     }
 
-    /// Set the datetime scale (for zooming into years and out to decades)
+    /// Set the datetime scale (for zooming into years and out to decades).
+    /// This stops the datetime scale being picked automatically.
     pub fn set_datetime_scale(&mut self, scale: f64) {
+        self.datetime_scale_auto = false;
         self.datetime_scale = scale.clamp(MIN_DATETIME_SCALE, MAX_DATETIME_SCALE);
         self.update_zoomed_layout_params();
         self.re_calculate();
     }
 
+    /// Whether the datetime scale is picked automatically
+    pub fn datetime_scale_auto(&self) -> bool {
+        self.datetime_scale_auto
+    }
+
+    /// Set whether the datetime scale is picked automatically.  When it is,
+    /// the timeline is stretched so that its dates (those of the entities not
+    /// filtered out) fill the width of the canvas, which switches the headings
+    /// from decades to years when only a few decades are shown.  The scale is
+    /// picked again as the canvas is resized, the zoom changes, or entities
+    /// are filtered.
+    pub fn set_datetime_scale_auto(&mut self, auto: bool) {
+        self.datetime_scale_auto = auto;
+        if self.auto_datetime_scale().is_some() {
+            self.re_calculate();
+            self.clamp_global_offset();
+        }
+    }
+
+    /// The datetime scale that fills the canvas with the timeline's dates, if
+    /// it's picked automatically & it should change (by enough that it won't
+    /// flicker back)
+    fn auto_datetime_scale(&self) -> Option<f64> {
+        let decade_count = f64::from(self.date_range.decade_count);
+        if !self.datetime_scale_auto || decade_count == 0.0 || self.canvas_size.x <= 0.0 {
+            return None;
+        }
+
+        // The year width grows linearly with the scale, so solve for the scale
+        // at which the decades are as wide as the canvas
+        let unscaled = self.year_width_at(0.0);
+        let per_scale = self.year_width_at(1.0) - unscaled;
+        if per_scale <= 0.0 {
+            return None;
+        }
+        let year_width = self.canvas_size.x / (decade_count * 10.0);
+        let mut scale =
+            ((year_width - unscaled) / per_scale).clamp(MIN_DATETIME_SCALE, MAX_DATETIME_SCALE);
+
+        // Don't switch between showing years & decades unless well past the
+        // threshold
+        let threshold = DATETIME_SCALE_THRESHOLD_SHOW_YEARS;
+        let showing_years = self.datetime_scale > threshold;
+        if !showing_years && scale > threshold {
+            if scale < threshold * (1.0 + AUTO_DATETIME_SCALE_HYSTERESIS) {
+                scale = threshold;
+            }
+        } else if showing_years
+            && scale <= threshold
+            && scale > threshold * (1.0 - AUTO_DATETIME_SCALE_HYSTERESIS)
+        {
+            return None;
+        }
+
+        let change = (scale - self.datetime_scale).abs() / self.datetime_scale;
+        (change > AUTO_DATETIME_SCALE_HYSTERESIS).then_some(scale)
+    }
+
+    /// Change the datetime scale to the automatic one (if it should change),
+    /// keeping the middle of the canvas on the same date
+    fn update_auto_datetime_scale(&mut self) {
+        let Some(scale) = self.auto_datetime_scale() else {
+            return;
+        };
+        debug!("Automatically setting the datetime scale to {scale}");
+        let before = self.year_width_at(self.datetime_scale);
+        let after = self.year_width_at(scale);
+        let middle = self.canvas_size.x / 2.0;
+        self.offset.x = middle - ((middle - self.offset.x) * after / before);
+        self.datetime_scale = scale;
+    }
+
     /// Calculate the decade with using the measured year width (this accounts
     /// for padding)
     fn decade_width(&self) -> f64 {
@@ -687,13 +1999,16 @@ impl Engine {
     fn update_headings(&mut self) {
         let height = self.measured_layout_params.row_height_no_padding
             + (2.0 * self.zoomed_layout_params.padding_y);
-        let decade_str_width = self.str_width("1234s");
-
         let mut headings = Vec::new();
         let mut current_decade = self.date_range.decade_range_start;
         for decade_number in 0..self.date_range.decade_count {
             let decade_number = f64::from(decade_number);
-            let decade_string = format!("{current_decade}s");
+            let decade_string = if self.relative_time {
+                years_ago(self.today.year().value() - current_decade)
+            } else {
+                self.date_format.format_decade(current_decade)
+            };
+            let decade_str_width = self.str_width(&decade_string);
             let decade_width = self.decade_width();
             let x = decade_width * decade_number;
             let text_x = x + (decade_width - decade_str_width) / 2.0;
@@ -704,9 +2019,11 @@ impl Engine {
                         x: text_x,
                         y: (self.zoomed_layout_params.padding_y),
                     },
-                    text: decade_string,
+                    lines: vec![decade_string.clone()].into(),
+                    text: decade_string.into(),
                     colour: self.colours.heading.text_colour,
                     font_size: self.zoomed_layout_params.font_size_px,
+                    line_height: self.measured_layout_params.line_height,
                 },
                 text_box: FilledBox {
                     position_and_size: PositionAndSize {
@@ -715,6 +2032,7 @@ impl Engine {
                         height,
                     },
                     fill_colour: self.colours.heading.rect.fill_colour,
+                    fill_alpha: 1.0,
                     border_style: self.colours.heading.rect.border,
                 },
             });
@@ -732,8 +2050,10 @@ impl Engine {
                     // Derive the text string (e.g. '34 or 1234)
                     let text = if self.datetime_scale() < DATETIME_SCALE_THRESHOLD_SHOW_FULL_YEARS {
                         format!("'{:02}", year % 100)
+                    } else if self.relative_time {
+                        years_ago(self.today.year().value() - year)
                     } else {
-                        format!("{year}")
+                        self.date_format.format_year(year)
                     };
 
                     // Calculate the text width & min x position
@@ -747,9 +2067,11 @@ impl Engine {
                                 x: text_x,
                                 y: height + self.zoomed_layout_params.padding_y,
                             },
-                            text,
+                            lines: vec![text.clone()].into(),
+                            text: text.into(),
                             colour: self.colours.heading.text_colour,
                             font_size: self.zoomed_layout_params.font_size_px,
+                            line_height: self.measured_layout_params.line_height,
                         },
                         text_box: FilledBox {
                             position_and_size: PositionAndSize {
@@ -758,6 +2080,7 @@ impl Engine {
                                 height,
                             },
                             fill_colour: self.colours.heading.rect.fill_colour,
+                            fill_alpha: 1.0,
                             border_style: self.colours.heading.rect.border,
                         },
                     });
@@ -768,10 +2091,63 @@ impl Engine {
             current_decade += 10;
         }
 
+        // Months (& then days) of the years on the canvas
+        if self.datetime_scale() > DATETIME_SCALE_THRESHOLD_SHOW_MONTHS {
+            let show_days = self.datetime_scale() > DATETIME_SCALE_THRESHOLD_SHOW_DAYS;
+            let year_width = self.measured_layout_params.year_width;
+            for year in self.visible_years() {
+                let year_min_x = self.year_min_x(year);
+                for (month, start, end) in months_of_year(year) {
+                    let text = self.date_format.format_month(month);
+                    let x = year_min_x + (start * year_width);
+                    headings.push(self.sub_year_heading(text, x, (end - start) * year_width, 2));
+                }
+                if show_days {
+                    for (day, start, end) in days_of_year(year) {
+                        let x = year_min_x + (start * year_width);
+                        let width = (end - start) * year_width;
+                        headings.push(self.sub_year_heading(day.to_string(), x, width, 3));
+                    }
+                }
+            }
+        }
+
         // Set the headings
         self.headings = headings;
     }
 
+    /// A heading for part of a year (a month or day) in the row of headings
+    /// (0 being the decades)
+    fn sub_year_heading(&self, text: String, x: f64, width: f64, row: usize) -> Heading {
+        let height = self.measured_layout_params.row_height_no_padding
+            + (2.0 * self.zoomed_layout_params.padding_y);
+        let y = height * row as f64;
+        let text_x = x + (width - self.str_width(&text)) / 2.0;
+        Heading {
+            text: TextOut {
+                top_left: Point {
+                    x: text_x,
+                    y: y + self.zoomed_layout_params.padding_y,
+                },
+                lines: vec![text.clone()].into(),
+                text: text.into(),
+                colour: self.colours.heading.text_colour,
+                font_size: self.zoomed_layout_params.font_size_px,
+                line_height: self.measured_layout_params.line_height,
+            },
+            text_box: FilledBox {
+                position_and_size: PositionAndSize {
+                    position: Point { x, y },
+                    width,
+                    height,
+                },
+                fill_colour: self.colours.heading.rect.fill_colour,
+                fill_alpha: 1.0,
+                border_style: self.colours.heading.rect.border,
+            },
+        }
+    }
+
     // TODO: switch to using whole Date rather than just year
     /// Find and save the earliest (start) and latest (end) year
     fn update_earliest_and_latest_years(&mut self) {
@@ -834,6 +2210,14 @@ impl Engine {
             }
         };
 
+        // Include the years of any shared axis
+        let (start_year, end_year) = match self.date_range.shared_axis_years {
+            Some((shared_start, shared_end)) => {
+                (start_year.min(shared_start), end_year.max(shared_end))
+            }
+            None => (start_year, end_year),
+        };
+
         // Update the decade range values
         self.date_range.decade_range_start = floor_to_decade(start_year);
         self.date_range.decade_range_end = ceiling_to_decade(end_year);
@@ -846,55 +2230,54 @@ impl Engine {
             / 10;
     }
 
-    /// Sort entities by start year
-    ///
-    /// Cannot use a `.partial_cmp()` and declare them equal if none because
-    /// this doesn't result in a total ordering.
+    /// Sort entities into the sort order
     fn sort_entities(&mut self) {
-        self.working_entities
-            .sort_by(|a, b| a.entity.start().cmp(&b.entity.start()))
+        self.sort_order.sort(&mut self.working_entities, self.today);
     }
 
-    /// Put the working entities into place
+    /// Put the working entities into rows (according to the layout strategy)
     fn put_entities_in_rows(&mut self) {
-        let mut rows: Vec<f64> = Vec::new();
-        for entity in &mut self.working_entities {
-            if entity.is_filtered_out() {
-                continue;
+        let min_inline_spacing = self.zoomed_layout_params.min_inline_spacing;
+        let visible = self
+            .working_entities
+            .iter_mut()
+            .filter(|entity| !entity.is_filtered_out());
+        match self.layout_strategy {
+            LayoutStrategy::FirstFit | LayoutStrategy::BestFit => {
+                let best_fit = self.layout_strategy == LayoutStrategy::BestFit;
+                let mut rows: Vec<f64> = Vec::new();
+                for entity in visible {
+                    put_entity_in_row(entity, &mut rows, 0, min_inline_spacing, best_fit);
+                }
             }
-            let mut found_row = false;
-            for (i, row_current_max_x) in rows.iter_mut().enumerate() {
-                // Calculate the row's current max x value
-                let row_max_x = {
-                    let row_max_x = *row_current_max_x;
-                    let row_max_x = row_max_x + self.zoomed_layout_params.min_inline_spacing;
-                    round_f64_to_nearest_0_1(row_max_x)
-                };
-
-                // Calculate the entity's min x value
-                let entity_min = round_f64_to_nearest_0_1(entity.min_x());
-
-                // If the entity's min x value is greater than the row's current
-                // max x value, then it is to the right of whatever is in the
-                // row (the row may be empty).
-                if row_max_x < entity_min {
-                    // Update the row's current max x value to be equal to the
-                    // entity's max x value
-                    *row_current_max_x = entity.max_x();
-
-                    // Give the entity a row number
-                    entity.set_row(i);
-
-                    // End the search for a row for this entity
-                    found_row = true;
-                    break;
+            LayoutStrategy::Chronological => {
+                for (row, entity) in visible.enumerate() {
+                    entity.set_row(row);
                 }
             }
-            // If the entity doesn't fit into any of the rows, create a new one
-            // for it
-            if !found_row {
-                entity.set_row(rows.len());
-                rows.push(entity.max_x());
+            LayoutStrategy::GroupedByTag => {
+                // Group the entities by tag (untagged entities sort last)
+                let mut groups: BTreeMap<(bool, String), Vec<&mut WorkingEntity>> = BTreeMap::new();
+                for entity in visible {
+                    let tag =
+                        entity.entity.tags().as_ref().and_then(|tags| {
+                            tags.into_iter().map(|tag| tag.value.to_string()).min()
+                        });
+                    groups
+                        .entry((tag.is_none(), tag.unwrap_or_default()))
+                        .or_default()
+                        .push(entity);
+                }
+
+                // Pack each group into its own rows, below the previous group
+                let mut first_row = 0;
+                for entities in groups.into_values() {
+                    let mut rows: Vec<f64> = Vec::new();
+                    for entity in entities {
+                        put_entity_in_row(entity, &mut rows, first_row, min_inline_spacing, false);
+                    }
+                    first_row += rows.len();
+                }
             }
         }
     }
@@ -919,27 +2302,25 @@ impl Engine {
     /// be put into rows
     fn calculate_widths_for_entities(&mut self) {
         for entity in &mut self.working_entities {
+            // Dates recorded in other calendars are positioned by their
+            // Gregorian equivalent
+            let start = entity.entity.start().to_gregorian();
+            let end = entity.entity.end().map(|end| end.to_gregorian());
+
             // Get the end year of the entity, or the last year of the timeline
             // if it doesn't have one
-            let end_year = entity
-                .entity
-                .end_year()
+            let end_year = end
+                .map(|end| end.year())
                 .unwrap_or(Year::try_from(self.date_range.decade_range_end as i64).unwrap());
 
-            let start_month_and_day_as_fraction_of_year = month_and_day_as_fraction_of_year(
-                entity.entity.start_month(),
-                entity.entity.start_day(),
-            );
-            let end_month_and_day_as_fraction_of_year = month_and_day_as_fraction_of_year(
-                entity.entity.end_month(),
-                entity.entity.end_day(),
-            );
+            // How far through their years the start & end are (accounting for
+            // month lengths & leap years)
+            let start_fraction_of_year = start.fraction_of_year();
+            let end_fraction_of_year = end.map_or(0.0, |end| end.fraction_of_year());
 
             // Get the entity's lifespan (years)
-            let entity_number_of_years = (end_year.value() as f64
-                + end_month_and_day_as_fraction_of_year)
-                - (entity.entity.start_year().value() as f64
-                    + start_month_and_day_as_fraction_of_year);
+            let entity_number_of_years = (end_year.value() as f64 + end_fraction_of_year)
+                - (start.year().value() as f64 + start_fraction_of_year);
 
             // Calculate the entity's date box width using it's lifespan
             let date_box_width = (entity_number_of_years) * self.measured_layout_params.year_width;
@@ -959,14 +2340,13 @@ impl Engine {
         for entity in &mut self.working_entities {
             // Calculate the number of years between the timeline's start year and
             // the start year of the entity
-            let offset_in_years =
-                entity.entity.start_year().value() - self.date_range.decade_range_start;
+            // (dates recorded in other calendars are positioned by their
+            // Gregorian equivalent)
+            let start = entity.entity.start().to_gregorian();
+            let offset_in_years = start.year().value() - self.date_range.decade_range_start;
 
             // Calculate the x position of the entity
-            let start_month = entity.entity.start_month();
-            let start_day = entity.entity.start_day();
-            let x: f64 = ((offset_in_years as f64)
-                + month_and_day_as_fraction_of_year(start_month, start_day))
+            let x: f64 = ((offset_in_years as f64) + start.fraction_of_year())
                 * self.measured_layout_params.year_width;
 
             // Set the x positions
@@ -982,14 +2362,36 @@ impl Engine {
     /// Global offset is added later just before the entities are returned for
     /// drawing.
     fn calculate_y_position_for_entities(&mut self) {
-        for entity in &mut self.working_entities {
-            // Calculate the actual row height (add in padding and margin)
-            let row_height = self.measured_layout_params.row_height_no_padding
-                + self.zoomed_layout_params.row_margin
-                + (self.zoomed_layout_params.padding_y * 2.0);
+        // Each row is as tall as its tallest entity (entities with wrapped
+        // names take up more than one line)
+        let row_height = self.row_height();
+        let mut row_heights: Vec<f64> = Vec::new();
+        let visible = self
+            .working_entities
+            .iter()
+            .filter(|entity| !entity.is_filtered_out());
+        for entity in visible {
+            let row = entity.row();
+            if row_heights.len() <= row {
+                row_heights.resize(row + 1, row_height);
+            }
+            let height =
+                entity.text_box.position_and_size.height + self.zoomed_layout_params.row_margin;
+            row_heights[row] = row_heights[row].max(height);
+        }
+
+        // The first row is a row down (below the headings)
+        let mut row_ys = Vec::with_capacity(row_heights.len());
+        let mut y = row_height;
+        for height in row_heights {
+            row_ys.push(y);
+            y += height;
+        }
 
-            // Calculate the y position using the row height and the entity's row
-            let y = row_height * ((entity.row() + 1) as f64);
+        for entity in &mut self.working_entities {
+            // Get the y position of the entity's row (filtered out entities
+            // aren't given a row, so they're put below the others)
+            let y = row_ys.get(entity.row()).copied().unwrap_or(y);
 
             // Set the y positions
             entity.text.top_left.y = y + self.zoomed_layout_params.padding_y;
@@ -997,12 +2399,20 @@ impl Engine {
             entity.date_box.position_and_size.position.y = y;
         }
     }
+
+    /// The actual height of a single line row (including padding and margin)
+    fn row_height(&self) -> f64 {
+        self.measured_layout_params.row_height_no_padding
+            + self.zoomed_layout_params.row_margin
+            + (self.zoomed_layout_params.padding_y * 2.0)
+    }
 }
 
-fn month_and_day_as_fraction_of_year(month: Option<Month>, day: Option<Day>) -> f64 {
-    let month_number = month.map_or(1, |month| month.value()) - 1;
-    let day_number = day.map_or(1, |day| day.value()) - 1;
-    (month_number as f64 / 12.0) + (day_number as f64 / 365.0)
+/// Describe the entity in plain text, with dates in the given format (e.g.
+/// "Ada Lovelace, 10 Dec 1815 to 27 Nov 1852")
+pub fn entity_text_description(entity: &Entity, date_format: &DateFormat) -> String {
+    let dates = date_format.format_date_range(&entity.start(), entity.end().as_ref());
+    format!("{}, {dates}", entity.name())
 }
 
 /// Calculate whether the thing is visible on the canvas
@@ -1022,3 +2432,1047 @@ fn is_visible(thing_min: Point, thing_max: Point, canvas_size: Point) -> bool {
     }
     true
 }
+
+/// Put the entity into the first (or best) row it fits into, creating a new
+/// row for it if it doesn't fit into any.  `rows` holds each row's current max
+/// x value, and `first_row` is the row number of `rows[0]`.
+fn put_entity_in_row(
+    entity: &mut WorkingEntity,
+    rows: &mut Vec<f64>,
+    first_row: usize,
+    min_inline_spacing: f64,
+    best_fit: bool,
+) {
+    match find_row(rows, entity.min_x(), min_inline_spacing, best_fit) {
+        Some(i) => {
+            rows[i] = entity.max_x();
+            entity.set_row(first_row + i);
+        }
+        None => {
+            entity.set_row(first_row + rows.len());
+            rows.push(entity.max_x());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bool_tag_expr::{Tag, TagName, TagValue, Tags};
+    use open_timeline_core::{Location, Name};
+
+    /// A crude text measurer (every character is half as wide as it is tall)
+    fn measure_text(font_size: f64, text: String) -> (f64, f64) {
+        (font_size * text.len() as f64 * 0.5, font_size)
+    }
+
+    fn entity(name: &str, start: i64, end: Option<i64>) -> Entity {
+        Entity::from(
+            Some(OpenTimelineId::new()),
+            Name::from(name).unwrap(),
+            Date::from(None, None, start).unwrap(),
+            end.map(|end| Date::from(None, None, end).unwrap()),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn auto_datetime_scale() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(1_000.0, 500.0);
+        engine.set_entities(vec![entity("War", 1939, Some(1945))]);
+        assert_eq!(engine.datetime_scale(), MIN_DATETIME_SCALE);
+
+        // A couple of decades are stretched to fill the canvas (with years)
+        engine.set_datetime_scale_auto(true);
+        assert!(engine.datetime_scale_auto());
+        assert!(engine.datetime_scale() > DATETIME_SCALE_THRESHOLD_SHOW_YEARS);
+        let decades_width = f64::from(engine.date_range.decade_count) * engine.decade_width();
+        assert!((decades_width - 1_000.0).abs() < 1.0);
+
+        // Small changes to the canvas don't change the scale
+        let scale = engine.datetime_scale();
+        engine.set_canvas_max(1_020.0, 500.0);
+        assert_eq!(engine.datetime_scale(), scale);
+
+        // Centuries are shown as decades
+        engine.set_entities(vec![
+            entity("First", 1500, Some(1510)),
+            entity("Second", 1900, Some(1950)),
+        ]);
+        assert!(engine.datetime_scale() <= DATETIME_SCALE_THRESHOLD_SHOW_YEARS);
+
+        // Setting the scale stops it being picked automatically
+        engine.set_datetime_scale(20.0);
+        assert!(!engine.datetime_scale_auto());
+        engine.set_canvas_max(500.0, 500.0);
+        assert_eq!(engine.datetime_scale(), 20.0);
+    }
+
+    #[test]
+    fn month_and_day_headings() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(1_000.0, 500.0);
+        engine.set_entities(vec![entity("First", 1900, Some(1910))]);
+        assert_eq!(engine.heading_rows(), 1);
+        let count = |headings: &[Heading], text: &str| {
+            headings
+                .iter()
+                .filter(|heading| heading.text.text == text)
+                .count()
+        };
+
+        // Months are shown once stretched far enough (only for the years on
+        // the canvas)
+        engine.set_datetime_scale(DATETIME_SCALE_THRESHOLD_SHOW_MONTHS + 1.0);
+        assert_eq!(engine.heading_rows(), 3);
+        let headings = engine.headings_for_drawing();
+        let jans = count(&headings, "Jan");
+        assert!(jans >= 1 && jans <= engine.visible_years().len());
+        assert!(engine.headings_height() > engine.entities_y_offset());
+
+        // Then days
+        engine.set_datetime_scale(MAX_DATETIME_SCALE);
+        assert_eq!(engine.heading_rows(), 4);
+        let headings = engine.headings_for_drawing();
+        assert_eq!(engine.visible_years(), 1900..1901);
+        assert_eq!(count(&headings, "Jan"), 1);
+        assert!(count(&headings, "28") >= 1);
+
+        // With a line between each day
+        let lines = engine.lines_for_drawing();
+        let canvas = 0.0..=1_000.0;
+        let visible = lines.iter().filter(|line| canvas.contains(&line.x)).count();
+        let day_width = engine.measured_layout_params.year_width / 365.0;
+        assert!(visible as f64 >= (1_000.0 / day_width).floor() - 1.0);
+    }
+
+    #[test]
+    fn drop_target_excludes_headings() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(1_000.0, 500.0);
+        engine.set_entities(vec![entity("First", 1900, Some(1910))]);
+        let headings_height = engine.headings_height();
+        assert!(headings_height > 0.0);
+        assert!(engine.is_drop_target(500.0, headings_height + 1.0));
+        assert!(!engine.is_drop_target(500.0, headings_height - 1.0));
+        assert!(!engine.is_drop_target(-1.0, 250.0));
+        assert!(!engine.is_drop_target(500.0, 501.0));
+    }
+
+    #[test]
+    fn visible_entities_text_description_is_chronological() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_entities(vec![
+            entity("Second", 1950, None),
+            entity("First", 1900, Some(1910)),
+        ]);
+        assert_eq!(
+            engine.visible_entities_text_description(),
+            vec![
+                String::from("First, 1900 to 1910"),
+                String::from("Second, 1950 onwards"),
+            ]
+        );
+    }
+
+    #[test]
+    fn date_format_is_used_for_descriptions_and_headings() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_entities(vec![entity("Ancient", -500, Some(-450))]);
+        engine.set_date_format(DateFormat {
+            era_style: EraStyle::Bce,
+            ..DateFormat::default()
+        });
+        assert_eq!(
+            engine.visible_entities_text_description(),
+            vec![String::from("Ancient, 500 BCE to 450 BCE")]
+        );
+        let headings = engine.headings_for_drawing();
+        assert_eq!(headings[0].text.text, "500s BCE");
+    }
+
+    #[test]
+    fn pan_inertia_decays_to_a_stop() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(100.0, 100.0);
+        engine.set_entities(
+            (0..100)
+                .map(|i| entity(&format!("Entity {i}"), 1000 + (i * 10), None))
+                .collect(),
+        );
+        engine.start_pan_inertia(-1.0, 0.0);
+        assert!(engine.animate(16.0));
+        assert!(engine.offset.x < 0.0);
+        let mut frames = 0;
+        while engine.animate(16.0) {
+            frames += 1;
+            assert!(frames < 1000);
+        }
+    }
+
+    #[test]
+    fn pan_inertia_stops_at_the_timeline_edge() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(100.0, 100.0);
+        engine.set_entities(vec![entity("First", 1900, Some(1910))]);
+
+        // Can't move right/down past the top left of the timeline
+        engine.start_pan_inertia(1.0, 1.0);
+        assert!(!engine.animate(16.0));
+    }
+
+    #[test]
+    fn visible_entities_in_date_order() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        let later = entity("Later", 1950, Some(1960));
+        let longer = entity("Longer", 1900, Some(1990));
+        let shorter = entity("Shorter", 1900, Some(1910));
+        let ids: Vec<_> = [&shorter, &longer, &later]
+            .iter()
+            .map(|entity| entity.id().unwrap())
+            .collect();
+        engine.set_entities(vec![later, longer, shorter]);
+        assert_eq!(engine.ids_of_visible_entities_in_date_order(), ids);
+
+        // Filtered out entities aren't included
+        engine.set_date_limits(Some(Date::from(None, None, 1940).unwrap()), None);
+        assert_eq!(engine.ids_of_visible_entities_in_date_order(), ids[2..]);
+    }
+
+    #[test]
+    fn viewport_changed_is_debounced() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(100.0, 100.0);
+        engine.set_entities(
+            (0..100)
+                .map(|i| entity(&format!("Entity {i}"), 1000 + (i * 10), None))
+                .collect(),
+        );
+        engine.set_viewport_changed_debounce_ms(100.0);
+        assert!(!engine.animate(16.0));
+
+        // Keep panning for a while
+        for _ in 0..10 {
+            engine.add_to_global_offset(-5.0, 0.0);
+            assert!(engine.animate(16.0));
+        }
+        let count_viewport_changes = |engine: &mut Engine| {
+            engine
+                .drain_interaction_events()
+                .filter(|event| matches!(event, TimelineInteractionEvent::ViewportChanged { .. }))
+                .count()
+        };
+        assert_eq!(count_viewport_changes(&mut engine), 0);
+
+        // Reported once, after it has stopped
+        while engine.animate(16.0) {}
+        let viewport = engine.viewport();
+        let events: Vec<_> = engine.drain_interaction_events().collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            TimelineInteractionEvent::ViewportChanged { start_year, zoom, .. }
+                if start_year == viewport.start_year && zoom == viewport.zoom
+        ));
+        assert!(!engine.animate(16.0));
+        assert_eq!(count_viewport_changes(&mut engine), 0);
+    }
+
+    #[test]
+    fn story_animates_between_steps() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        let first = entity("First", 1900, Some(1910));
+        let first_id = first.id().unwrap();
+        engine.set_entities(vec![first, entity("Second", 1990, Some(2000))]);
+        engine.set_story(vec![
+            StoryStep::from(StoryStepTarget::Entity(first_id), String::from("First")),
+            StoryStep::from(
+                StoryStepTarget::Years {
+                    start_year: 1950.0,
+                    end_year: 1960.0,
+                },
+                String::from("Between"),
+            ),
+        ]);
+        assert_eq!(engine.story_step(), None);
+
+        // The view doesn't jump to the step, but gets there over time
+        engine.next_story_step();
+        assert_eq!(engine.story_step(), Some(0));
+        assert_eq!(*engine.ids_of_selected_entities(), vec![first_id]);
+        let before = engine.capture_viewport();
+        assert!(engine.animate(STORY_STEP_ANIMATION_MS / 2.0));
+        assert_ne!(engine.capture_viewport(), before);
+        while engine.animate(16.0) {}
+
+        // Going to the years deselects the entity and centres them in view
+        engine.next_story_step();
+        assert_eq!(engine.story_step(), Some(1));
+        assert!(engine.ids_of_selected_entities().is_empty());
+        while engine.animate(16.0) {}
+        let viewport = engine.viewport();
+        assert!(viewport.start_year < 1950.0 && viewport.end_year > 1960.0);
+        assert!((((viewport.start_year + viewport.end_year) / 2.0) - 1955.0).abs() < 0.5);
+
+        // There is no step after the last one
+        engine.next_story_step();
+        assert_eq!(engine.story_step(), Some(1));
+        let events: Vec<_> = engine
+            .drain_interaction_events()
+            .filter_map(|event| match event {
+                TimelineInteractionEvent::StoryStepChange(step) => Some(step),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(events, vec![Some(0), Some(1)]);
+
+        // Panning interrupts the animation, and stopping says so
+        engine.previous_story_step();
+        engine.add_to_global_offset(-5.0, 0.0);
+        engine.stop_story();
+        assert_eq!(engine.story_step(), None);
+        assert!(
+            engine
+                .drain_interaction_events()
+                .any(|event| matches!(event, TimelineInteractionEvent::StoryStepChange(None)))
+        );
+        while engine.animate(16.0) {}
+    }
+
+    #[test]
+    fn dragging_date_box_edge_changes_dates() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        let first = entity("First", 1900, Some(1910));
+        let first_id = first.id().unwrap();
+        engine.set_entities(vec![first, entity("Second", 1990, None)]);
+        let date_box = engine
+            .working_entities
+            .iter()
+            .find(|entity| entity.entity.id() == Some(first_id))
+            .unwrap()
+            .date_box
+            .position_and_size;
+        let end_x = date_box.position.x + date_box.width + engine.offset.x;
+        let y = date_box.position.y + engine.entities_y_offset() + (date_box.height / 2.0);
+
+        // Edges can only be dragged in edit mode
+        assert_eq!(engine.date_box_edge_at(end_x, y), None);
+        engine.set_edit_mode(true);
+        assert_eq!(
+            engine.date_box_edge_at(end_x, y),
+            Some((first_id, DateBoxEdge::End))
+        );
+
+        // Dragging the end edge to 1920 (snapped) changes the end date
+        assert!(engine.start_date_drag(first_id, DateBoxEdge::End));
+        let year_width = engine.measured_layout_params.year_width;
+        engine.update_date_drag(end_x + (10.0 * year_width) + 1.0);
+        let end = engine.entity(first_id).unwrap().end().unwrap();
+        assert_eq!(end.year(), Date::from(None, None, 1920).unwrap().year());
+
+        // The end can't be dragged before the start
+        engine.update_date_drag(end_x - (20.0 * year_width));
+        assert_eq!(engine.entity(first_id).unwrap().end(), Some(end));
+        engine.end_date_drag();
+        assert!(!engine.is_dragging_date());
+        let changes: Vec<_> = engine
+            .drain_interaction_events()
+            .filter_map(|event| match event {
+                TimelineInteractionEvent::DatesChanged {
+                    entity_id,
+                    end,
+                    previous_end,
+                    ..
+                } => Some((entity_id, end, previous_end)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![(first_id, Some(end), Date::from(None, None, 1910).ok())]
+        );
+
+        // Undoing puts the dates back
+        assert!(engine.set_entity_dates(
+            first_id,
+            Date::from(None, None, 1900).unwrap(),
+            Date::from(None, None, 1910).ok()
+        ));
+        assert_eq!(
+            engine.entity(first_id).unwrap().end(),
+            Date::from(None, None, 1910).ok()
+        );
+    }
+
+    #[test]
+    fn double_click_on_empty_space_gives_year() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        engine.set_entities(vec![entity("First", 1900, Some(1910))]);
+        let viewport = engine.viewport();
+        let year = ((viewport.start_year + viewport.end_year) / 2.0).floor() as i64;
+        let (x, y) = (400.0, engine.headings_height() + 1.0);
+        assert_eq!(engine.date_at(x, y), Date::from(None, None, year).ok());
+
+        // Double clicking on the headings does nothing
+        engine.double_click_on_empty_space(x, 1.0);
+        engine.double_click_on_empty_space(x, y);
+        let dates: Vec<_> = engine
+            .drain_interaction_events()
+            .filter_map(|event| match event {
+                TimelineInteractionEvent::EmptySpaceDoubleClick(date) => Some(date),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(dates, vec![Date::from(None, None, year).unwrap()]);
+    }
+
+    #[test]
+    fn view_state_round_trip() {
+        let entities: Vec<_> = (0..20)
+            .map(|i| {
+                entity(
+                    &format!("Entity {i}"),
+                    1800 + (i * 10),
+                    Some(1805 + (i * 10)),
+                )
+            })
+            .collect();
+        let selected = entities[3].id().unwrap();
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        engine.set_entities(entities.clone());
+        engine.set_date_limits(Some(Date::from(None, None, 1850).unwrap()), None);
+        engine.set_ids_of_selected_entities(vec![selected]);
+        engine.set_zoom(2.0);
+        engine.add_to_global_offset(-100.0, 0.0);
+        let view_state = engine.serialize_view_state();
+        assert!(
+            view_state
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+
+        // Another engine with the same entities ends up with the same view
+        let mut other = Engine::new(measure_text);
+        other.set_canvas_max(800.0, 400.0);
+        other.set_entities(entities);
+        other.apply_view_state(&view_state).unwrap();
+        let (expected, actual) = (engine.capture_viewport(), other.capture_viewport());
+        assert!((actual.start_year - expected.start_year).abs() < 1e-6);
+        assert_eq!(actual.offset_y, expected.offset_y);
+        assert_eq!(actual.zoom, expected.zoom);
+        assert_eq!(other.date_limits(), engine.date_limits());
+        assert_eq!(*other.ids_of_selected_entities(), vec![selected]);
+
+        // Nothing changes if the view state isn't valid
+        let captured = other.capture_viewport();
+        assert_eq!(
+            other.apply_view_state("not a view state"),
+            Err(ViewStateError::Invalid)
+        );
+        assert_eq!(other.capture_viewport(), captured);
+    }
+
+    #[test]
+    fn selection_change_emits_event_only_when_changed() {
+        let mut engine = Engine::new(measure_text);
+        let entity_id = OpenTimelineId::new();
+        engine.set_ids_of_selected_entities(vec![entity_id]);
+        engine.add_id_of_selected_entity(entity_id);
+        engine.remove_id_from_selected_entities_list(OpenTimelineId::new());
+        engine.clear_ids_of_selected_entities();
+        engine.clear_ids_of_selected_entities();
+        let events: Vec<_> = engine.drain_interaction_events().collect();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            TimelineInteractionEvent::SelectionChange(ids) if *ids == vec![entity_id]
+        ));
+        assert!(matches!(
+            &events[2],
+            TimelineInteractionEvent::SelectionChange(ids) if ids.is_empty()
+        ));
+    }
+
+    #[test]
+    fn selected_entities_are_highlighted() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        engine.set_entities(vec![
+            entity("First", 1900, Some(1910)),
+            entity("Second", 1905, Some(1915)),
+        ]);
+        let selected = engine.entities_for_drawing()[0].entity.id().unwrap();
+        let selected_out = |engine: &Engine| {
+            engine
+                .entities_for_drawing()
+                .into_iter()
+                .find(|entity| entity.entity.id() == Some(selected))
+                .map(|entity| (entity.text_box, entity.highlight))
+                .unwrap()
+        };
+        let colour = Colour::from_rgb(0, 200, 255);
+
+        // A border by default (in the highlight colour)
+        engine.set_ids_of_selected_entities(vec![selected]);
+        let (text_box, highlight) = selected_out(&engine);
+        assert_eq!(
+            text_box.border_style.unwrap().colour,
+            SelectionHighlight::default().colour
+        );
+        assert!(highlight.is_none());
+        assert!(
+            engine
+                .entities_for_drawing()
+                .iter()
+                .all(|entity| entity.entity.id() == Some(selected) || entity.highlight.is_none())
+        );
+
+        // A glow around both boxes
+        engine.set_selection_highlight(SelectionHighlight {
+            style: SelectionHighlightStyle::Glow,
+            colour,
+        });
+        let (text_box, highlight) = selected_out(&engine);
+        let highlight = highlight.unwrap();
+        let thickness = engine.zoomed_layout_params.entity_highlight_thickness;
+        assert_eq!(highlight.fill_colour, colour);
+        assert_eq!(
+            highlight.position_and_size.position.x,
+            text_box.position_and_size.position.x - thickness
+        );
+        assert!(highlight.position_and_size.width >= text_box.position_and_size.width);
+        assert_ne!(
+            text_box.border_style.map(|border| border.colour),
+            Some(colour)
+        );
+
+        // Clicking on empty space clears the selection
+        engine.drain_interaction_events().for_each(drop);
+        engine.click_on_empty_space();
+        assert!(engine.ids_of_selected_entities().is_empty());
+        assert!(selected_out(&engine).1.is_none());
+        assert!(matches!(
+            engine.drain_interaction_events().next(),
+            Some(TimelineInteractionEvent::SelectionChange(ids)) if ids.is_empty()
+        ));
+    }
+
+    #[test]
+    fn context_menu_selects_the_entity() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        engine.set_entities(vec![
+            entity("First", 1900, Some(1910)),
+            entity("Second", 1905, Some(1915)),
+        ]);
+        let ids: Vec<_> = engine
+            .entities_for_drawing()
+            .iter()
+            .filter_map(|entity| entity.entity.id())
+            .collect();
+
+        // The entity is selected, then the menu asked for
+        engine.context_menu_on_entity(ids[0], 10.0, 20.0);
+        assert_eq!(*engine.ids_of_selected_entities(), vec![ids[0]]);
+        let events: Vec<_> = engine.drain_interaction_events().collect();
+        assert!(matches!(
+            events[0],
+            TimelineInteractionEvent::SelectionChange(_)
+        ));
+        assert!(matches!(
+            events[1],
+            TimelineInteractionEvent::ContextMenu(id, 10.0, 20.0) if id == ids[0]
+        ));
+
+        // An existing (multiple) selection including the entity is kept
+        engine.set_ids_of_selected_entities(ids.clone());
+        engine.drain_interaction_events().for_each(drop);
+        engine.context_menu_on_entity(ids[1], 0.0, 0.0);
+        assert_eq!(*engine.ids_of_selected_entities(), ids);
+        let events: Vec<_> = engine.drain_interaction_events().collect();
+        assert_eq!(events.len(), 1);
+    }
+
+    #[test]
+    fn viewport_years_track_the_offset() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(100.0, 100.0);
+        engine.set_entities(vec![entity("First", 1900, Some(2000))]);
+        let viewport = engine.viewport();
+        assert_eq!(viewport.start_year, 1900.0);
+        assert!(viewport.end_year > viewport.start_year);
+
+        // Move the timeline left (i.e. view later years)
+        engine.add_to_global_offset(-50.0, 0.0);
+        let moved = engine.viewport();
+        assert!(moved.start_year > viewport.start_year);
+        assert_eq!(
+            moved.end_year - moved.start_year,
+            viewport.end_year - viewport.start_year
+        );
+    }
+
+    #[test]
+    fn min_font_size_is_respected_when_zooming_out() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_min_font_size_px(HIGH_CONTRAST_MIN_FONT_SIZE_PX);
+        engine.set_zoom(MIN_ZOOM);
+        assert_eq!(
+            engine.effective_font_size_px(),
+            HIGH_CONTRAST_MIN_FONT_SIZE_PX
+        );
+    }
+
+    #[test]
+    fn focus_on_selection_frames_and_hides_others() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        let focused = entity("Focused", 1900, Some(1910));
+        let focused_id = focused.id().unwrap();
+        engine.set_entities(vec![
+            entity("Early", 1500, Some(1600)),
+            focused,
+            entity("Late", 1990, None),
+        ]);
+        let zoom = engine.zoom();
+        let visible = engine.entities_for_drawing().len();
+
+        engine.set_ids_of_selected_entities(vec![focused_id]);
+        engine.focus_on_selection(true);
+        assert!(engine.is_focused());
+        let entities = engine.entities_for_drawing();
+        assert_eq!(entities.len(), 1);
+        let date_box = entities[0].date_box.position_and_size;
+        assert!(date_box.position.x >= 0.0 && date_box.max_x() <= 800.0);
+        assert!(date_box.max_y() <= 400.0);
+
+        engine.exit_focus();
+        assert!(!engine.is_focused());
+        assert_eq!(engine.zoom(), zoom);
+        assert_eq!(engine.entities_for_drawing().len(), visible);
+    }
+
+    #[test]
+    fn focus_needs_a_selection() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        engine.set_entities(vec![entity("First", 1900, Some(1910))]);
+        engine.focus_on_selection(true);
+        assert!(!engine.is_focused());
+    }
+
+    #[test]
+    fn synced_timelines_share_an_axis() {
+        let mut a = Engine::new(measure_text);
+        let mut b = Engine::new(measure_text);
+        for engine in [&mut a, &mut b] {
+            engine.set_canvas_max(400.0, 200.0);
+        }
+        a.set_entities(vec![entity("A", 1800, Some(1850))]);
+        b.set_entities(vec![entity("Much Later B", 1900, Some(1990))]);
+        a.set_shared_axis_years(Some((1800, 1990)));
+        b.set_shared_axis_years(Some((1800, 1990)));
+        assert_eq!(a.start_and_end_dates(), b.start_and_end_dates());
+
+        a.zoom_in(1.5, 0.0, 0.0);
+        a.add_to_global_offset(-100.0, 0.0);
+        b.sync_x_axis(&a.viewport());
+        let (a_viewport, b_viewport) = (a.viewport(), b.viewport());
+        assert_eq!(a_viewport.zoom, b_viewport.zoom);
+        assert_eq!(a_viewport.offset_x, b_viewport.offset_x);
+        assert_eq!(a_viewport.start_year, b_viewport.start_year);
+    }
+
+    #[test]
+    fn location_filter_hides_distant_entities() {
+        let london = Coordinates::from(51.5074, -0.1278).unwrap();
+        let paris = Coordinates::from(48.8566, 2.3522).unwrap();
+        let mut in_london = entity("London", 1900, Some(1910));
+        in_london.set_location(Some(Location::from(None, Some(london)).unwrap()));
+        let mut in_paris = entity("Paris", 1920, Some(1930));
+        in_paris.set_location(Some(Location::from(None, Some(paris)).unwrap()));
+        let unplaced = entity("Unplaced", 1940, Some(1950));
+
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_entities(vec![in_london, in_paris, unplaced]);
+        assert_eq!(engine.entities_for_drawing().len(), 3);
+
+        engine.set_location_entity_filter(london, 100.0);
+        let visible = engine.entities_for_drawing();
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].entity.name().as_str(), "London");
+
+        engine.remove_location_entity_filter();
+        assert_eq!(engine.entities_for_drawing().len(), 3);
+    }
+
+    /// The rows of the entities (in chronological order)
+    fn rows(engine: &Engine) -> Vec<usize> {
+        engine
+            .working_entities
+            .iter()
+            .map(|entity| entity.row())
+            .collect()
+    }
+
+    fn tagged(name: &str, start: i64, end: Option<i64>, tag: &str) -> Entity {
+        let mut entity = entity(name, start, end);
+        entity.set_tags(Tags::from([Tag::from(None, TagValue::from(&tag).unwrap())]));
+        entity
+    }
+
+    #[test]
+    fn layout_strategies_assign_rows() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_entities(vec![
+            tagged("A", 1800, Some(1810), "person"),
+            tagged("B", 1805, Some(1900), "battle"),
+            tagged("C", 1850, Some(1860), "person"),
+            entity("D", 1950, Some(1960)),
+        ]);
+        assert_eq!(engine.layout_strategy(), LayoutStrategy::FirstFit);
+        assert_eq!(rows(&engine), vec![0, 1, 0, 0]);
+
+        engine.set_layout_strategy(LayoutStrategy::Chronological);
+        assert_eq!(rows(&engine), vec![0, 1, 2, 3]);
+
+        // Battles first, then people, then untagged entities
+        engine.set_layout_strategy(LayoutStrategy::GroupedByTag);
+        assert_eq!(rows(&engine), vec![1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn sort_orders_re_pack_rows() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_today(Date::from(None, None, 2000).unwrap());
+        engine.set_layout_strategy(LayoutStrategy::Chronological);
+        let mut london = entity("london", 1900, Some(1990));
+        london.set_tags(Tags::from([Tag::from(
+            Some(TagName::from(&"city").unwrap()),
+            TagValue::from(&"london").unwrap(),
+        )]));
+        let mut berlin = entity("Berlin", 1850, Some(1860));
+        berlin.set_tags(Tags::from([Tag::from(
+            Some(TagName::from(&"city").unwrap()),
+            TagValue::from(&"berlin").unwrap(),
+        )]));
+        engine.set_entities(vec![
+            london,
+            entity("Ongoing", 1950, None),
+            berlin,
+            entity("Ancient", 1800, Some(1995)),
+        ]);
+        let names = |engine: &Engine| {
+            engine
+                .working_entities
+                .iter()
+                .map(|entity| entity.entity.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(engine.sort_order(), &SortOrder::StartDate);
+        assert_eq!(
+            names(&engine),
+            vec!["Ancient", "Berlin", "london", "Ongoing"]
+        );
+        assert_eq!(rows(&engine), vec![0, 1, 2, 3]);
+
+        // Entities without the value sorted by are last
+        engine.set_sort(SortOrder::EndDate);
+        assert_eq!(
+            names(&engine),
+            vec!["Berlin", "london", "Ancient", "Ongoing"]
+        );
+
+        // Ongoing entities last until today
+        engine.set_sort(SortOrder::Duration);
+        assert_eq!(
+            names(&engine),
+            vec!["Ancient", "london", "Ongoing", "Berlin"]
+        );
+
+        // Case is ignored
+        engine.set_sort(SortOrder::Alphabetical);
+        assert_eq!(
+            names(&engine),
+            vec!["Ancient", "Berlin", "london", "Ongoing"]
+        );
+
+        engine.set_sort(SortOrder::TagValue(TagName::from(&"city").unwrap()));
+        assert_eq!(
+            names(&engine),
+            vec!["Berlin", "london", "Ancient", "Ongoing"]
+        );
+        assert_eq!(rows(&engine), vec![0, 1, 2, 3]);
+
+        // Drawing is still chronological by default
+        let drawn: Vec<_> = engine
+            .entities_for_drawing()
+            .into_iter()
+            .map(|entity| entity.entity.name().to_string())
+            .collect();
+        assert_eq!(drawn, vec!["Ancient", "Berlin", "london", "Ongoing"]);
+    }
+
+    #[test]
+    fn best_fit_chooses_the_tightest_row() {
+        let spacing = 5.0;
+        let rows = [100.0, 400.0, 300.0];
+        assert_eq!(find_row(&rows, 350.0, spacing, false), Some(0));
+        assert_eq!(find_row(&rows, 350.0, spacing, true), Some(2));
+        assert_eq!(find_row(&rows, 50.0, spacing, true), None);
+    }
+
+    #[test]
+    fn stacking_order_and_date_box_alpha() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_entities(vec![
+            entity("Short", 1800, Some(1810)),
+            entity("Long", 1805, Some(1900)),
+            entity("Newest", 1850, Some(1860)),
+        ]);
+        let names = |engine: &Engine| {
+            engine
+                .entities_for_drawing()
+                .into_iter()
+                .map(|entity| entity.entity.name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&engine), vec!["Short", "Long", "Newest"]);
+
+        engine.set_stacking_order(StackingOrder::LongestFirst);
+        assert_eq!(names(&engine), vec!["Long", "Short", "Newest"]);
+
+        engine.set_stacking_order(StackingOrder::NewestFirst);
+        assert_eq!(names(&engine), vec!["Newest", "Long", "Short"]);
+
+        // Only date boxes are translucent
+        engine.set_date_box_alpha(1.5);
+        assert_eq!(engine.date_box_alpha(), 1.0);
+        engine.set_date_box_alpha(0.5);
+        for entity in engine.entities_for_drawing() {
+            assert_eq!(entity.date_box.fill_alpha, 0.5);
+            assert_eq!(entity.text_box.fill_alpha, 1.0);
+        }
+    }
+
+    #[test]
+    fn sync_entities_keeps_unchanged() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(1000.0, 1000.0);
+        let a = entity("A", 1900, Some(1950));
+        let b = entity("B", 1920, Some(1980));
+        let c = entity("C", 1930, None);
+        engine.set_entities(vec![a.clone(), b.clone()]);
+
+        // B's dates change, A is removed, and C is added
+        let b_changed = Entity::from(b.id(), b.name().clone(), b.start(), None, None).unwrap();
+        engine.sync_entities(vec![b_changed.clone(), c.clone()]);
+        assert_eq!(engine.entity_count(), 2);
+        assert!(engine.entity(a.id().unwrap()).is_none());
+        assert_eq!(engine.entity(b.id().unwrap()), Some(&b_changed));
+        assert_eq!(engine.entity(c.id().unwrap()), Some(&c));
+    }
+
+    #[test]
+    fn entities_without_ids_get_render_ids() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(1000.0, 1000.0);
+        engine
+            .load_from_json(
+                r#"[
+                    {"name": "A", "start": {"year": 1900}, "end": {"year": 1950}, "tags": null},
+                    {"name": "B", "start": {"year": 1920}, "end": null, "tags": null}
+                ]"#,
+            )
+            .unwrap();
+        assert_eq!(engine.entity_count(), 2);
+
+        // Each is given its own ID, so can be interacted with
+        let entities = engine.entities_for_drawing();
+        let ids: BTreeSet<OpenTimelineId> = entities
+            .iter()
+            .map(|entity| entity.entity.id().unwrap())
+            .collect();
+        assert_eq!(ids.len(), 2);
+        engine.hover_over_entity(entities[0].entity.id());
+
+        // As are those synced
+        let mut c = entity("C", 1930, None);
+        c.clear_id();
+        engine.sync_entities(vec![c]);
+        assert_eq!(engine.entity_count(), 1);
+        assert!(engine.entities_for_drawing()[0].entity.id().is_some());
+
+        assert!(engine.load_from_json("not json").is_err());
+    }
+
+    #[test]
+    fn long_names_wrap_and_rows_grow() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_layout_strategy(LayoutStrategy::Chronological);
+        engine.set_entities(vec![
+            entity("The Battle of Hastings", 1900, Some(1950)),
+            entity("Short", 1910, Some(1920)),
+        ]);
+        let entities = engine.entities_for_drawing();
+        assert_eq!(entities[0].text.lines, vec!["The Battle of Hastings"]);
+        let single_line_height = entities[0].text_box.position_and_size.height;
+
+        // Characters are 6px wide, so 10 fit on a line
+        engine.set_layout_params(ScalableLayoutParams {
+            max_text_width: Some(60.0),
+            ..ScalableLayoutParams::default()
+        });
+        let entities = engine.entities_for_drawing();
+        let (long, short) = (&entities[0], &entities[1]);
+        assert_eq!(long.text.lines, vec!["The Battle", "of", "Hastings"]);
+        assert_eq!(long.text.text, "The Battle of Hastings");
+        assert_eq!(long.text_box.position_and_size.width, 60.0 + 20.0);
+        let line_height = 12.0 * (1.0 + LINE_GAP_FRACTION);
+        let long_height = long.text_box.position_and_size.height;
+        assert_eq!(long_height, single_line_height + (2.0 * line_height));
+        assert_eq!(long.date_box.position_and_size.height, long_height);
+
+        // The row below is pushed down
+        let long_y = long.text_box.position_and_size.position.y;
+        let short_y = short.text_box.position_and_size.position.y;
+        assert_eq!(short_y - long_y, long_height + 5.0);
+        assert_eq!(short.text.lines, vec!["Short"]);
+    }
+
+    #[test]
+    fn captured_viewport_is_restored() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(500.0, 200.0);
+        engine.set_entities(
+            (0..50)
+                .map(|i| {
+                    entity(
+                        &format!("Entity {i}"),
+                        1500 + (i * 10),
+                        Some(1510 + (i * 10)),
+                    )
+                })
+                .collect(),
+        );
+        engine.zoom_in(1.5, 0.0, 0.0);
+        engine.add_to_global_offset(-300.0, -50.0);
+        let captured = engine.capture_viewport();
+
+        // Move somewhere else, then back
+        engine.zoom_out(1.5, 0.0, 0.0);
+        engine.add_to_global_offset(200.0, 30.0);
+        assert_ne!(engine.capture_viewport(), captured);
+        engine.restore_viewport(&captured);
+        let restored = engine.capture_viewport();
+        assert!((restored.start_year - captured.start_year).abs() < 1e-6);
+        assert_eq!(restored.offset_y, captured.offset_y);
+        assert_eq!(restored.zoom, captured.zoom);
+        assert_eq!(restored.datetime_scale, captured.datetime_scale);
+    }
+
+    #[test]
+    fn now_line_is_drawn_at_today() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_entities(vec![entity("Ongoing", 1990, None)]);
+        engine.set_today(Date::from(None, None, 2000).unwrap());
+        assert!(engine.now_line_for_drawing().is_none());
+
+        engine.set_show_now_line(true);
+        let line = engine.now_line_for_drawing().unwrap();
+        let decade = engine
+            .headings_for_drawing()
+            .into_iter()
+            .find(|heading| heading.text.text == "2000s")
+            .unwrap();
+        assert_eq!(line.x, decade.text_box.position_and_size.position.x);
+
+        // Today isn't on the timeline
+        engine.set_today(Date::from(None, None, 1800).unwrap());
+        assert!(engine.now_line_for_drawing().is_none());
+    }
+
+    #[test]
+    fn relative_time_is_used_for_headings_and_descriptions() {
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_entities(vec![entity("Past", 1950, Some(1960))]);
+        engine.set_today(Date::from(None, None, 2025).unwrap());
+        engine.set_relative_time(true);
+        assert_eq!(
+            engine.visible_entities_text_description(),
+            vec![String::from("Past, 1950 to 1960 (75 years ago)")]
+        );
+        let headings = engine.headings_for_drawing();
+        assert_eq!(headings[0].text.text, "75 years ago");
+    }
+
+    #[test]
+    fn text_is_only_re_measured_when_it_would_measure_differently() {
+        let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counted_calls = std::rc::Rc::clone(&calls);
+        let mut engine = Engine::new(move |font_size, text| {
+            counted_calls.set(counted_calls.get() + 1);
+            measure_text(font_size, text)
+        });
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_entities(vec![entity("First", 1900, Some(1910))]);
+
+        // Filtering doesn't change how text measures
+        let measured = calls.get();
+        engine.set_tag_bool_expr_entity_filter(BoolTagExpr::from("scientist").unwrap());
+        engine.remove_tag_bool_expr_entity_filter();
+        assert_eq!(calls.get(), measured);
+
+        // A new font or font size does
+        engine.text_measurement_changed();
+        assert!(calls.get() > measured);
+        let measured = calls.get();
+        engine.set_zoom(2.0);
+        assert!(calls.get() > measured);
+    }
+
+    #[test]
+    fn date_boxes_account_for_month_lengths() {
+        let dated = |name: &str, start: (i64, i64), end: (i64, i64)| {
+            Entity::from(
+                Some(OpenTimelineId::new()),
+                Name::from(name).unwrap(),
+                Date::from(Some(start.0), Some(start.1), 2023).unwrap(),
+                Some(Date::from(Some(end.0), Some(end.1), 2023).unwrap()),
+                None,
+            )
+            .unwrap()
+        };
+        let mut engine = Engine::new(measure_text);
+        engine.set_canvas_max(10_000.0, 10_000.0);
+        engine.set_entities(vec![
+            dated("January", (1, 1), (1, 2)),
+            dated("February", (1, 2), (1, 3)),
+        ]);
+
+        let widths: Vec<f64> = engine
+            .entities_for_drawing()
+            .iter()
+            .map(|entity| entity.date_box.position_and_size.width)
+            .collect();
+        let year_width = engine.measured_layout_params.year_width;
+        assert!((widths[0] - year_width * 31.0 / 365.0).abs() < 1e-9);
+        assert!((widths[1] - year_width * 28.0 / 365.0).abs() < 1e-9);
+    }
+}
@@ -58,6 +58,49 @@ pub struct TimelineColours {
     pub heading: HeadingStyle,
 }
 
+impl TimelineColours {
+    /// Black, white, and yellow colours with a thick dividing line, for those
+    /// who need more contrast than the default colours offer
+    pub fn high_contrast() -> Self {
+        let black = Colour::from_rgb(0, 0, 0);
+        let white = Colour::from_rgb(255, 255, 255);
+        let yellow = Colour::from_rgb(255, 230, 0);
+        Self {
+            background: BackgroundColours { a: black, b: black },
+            dividing_line: LineStyle {
+                colour: white,
+                thickness: 2.0,
+            },
+            entity: EntityStyle {
+                text_box: BoxStyle {
+                    fill_colour: black,
+                    border: Some(LineStyle {
+                        colour: white,
+                        thickness: 2.0,
+                    }),
+                },
+                date_box: BoxStyle {
+                    fill_colour: yellow,
+                    border: Some(LineStyle {
+                        colour: white,
+                        thickness: 2.0,
+                    }),
+                },
+                text_colour: white,
+                click_colour: TimelineEntityColourModifier::Lighten,
+                hover_colour: TimelineEntityColourModifier::Lighten,
+            },
+            heading: HeadingStyle {
+                rect: BoxStyle {
+                    fill_colour: white,
+                    border: None,
+                },
+                text_colour: black,
+            },
+        }
+    }
+}
+
 impl Default for TimelineColours {
     fn default() -> Self {
         Self {
@@ -7,10 +7,74 @@
 pub const MIN_ZOOM: f64 = 0.5;
 pub const MAX_ZOOM: f64 = 5.0;
 
+/// The smallest (zoomed) font size allowed when using high contrast settings
+pub const HIGH_CONTRAST_MIN_FONT_SIZE_PX: f64 = 14.0;
+
+/// How much of the inertial panning velocity remains after each millisecond
+pub const PAN_INERTIA_DECAY_PER_MS: f64 = 0.996;
+
+/// Inertial panning stops once it slows below this speed (pixels per
+/// millisecond)
+pub const PAN_INERTIA_MIN_SPEED: f64 = 0.02;
+
+/// How long (ms) the viewport must be unchanged before a viewport change is
+/// reported (by default)
+pub const VIEWPORT_CHANGED_DEBOUNCE_MS: f64 = 200.0;
+
+/// The thickness (unzoomed) of the border drawn around selected entities
+pub const SELECTED_ENTITY_BORDER_THICKNESS: f64 = 2.0;
+
+/// How opaque the glow drawn behind selected entities is
+pub const SELECTION_GLOW_ALPHA: f64 = 0.4;
+
+/// The thickness (unzoomed) of the line drawn at today's date
+pub const NOW_LINE_THICKNESS: f64 = 2.0;
+
+/// The fraction of the canvas left empty on each side when framing entities
+pub const FRAME_MARGIN_FRACTION: f64 = 0.05;
+
+/// The version of the view state format (see
+/// [`Engine::serialize_view_state()`]), bumped if it changes incompatibly
+///
+/// [`Engine::serialize_view_state()`]: crate::Engine::serialize_view_state
+pub const VIEW_STATE_VERSION: u8 = 1;
+
+/// How close (px) the pointer must be to the edge of an entity's date box to
+/// drag it (in edit mode)
+pub const DATE_DRAG_EDGE_TOLERANCE_PX: f64 = 4.0;
+
+/// When years are at least this wide (px), dragged dates snap to months
+/// rather than years
+pub const DATE_DRAG_MONTH_SNAP_MIN_YEAR_WIDTH: f64 = 120.0;
+
+/// How long (ms) it takes to move from one step of a story to the next
+pub const STORY_STEP_ANIMATION_MS: f64 = 800.0;
+
+/// How many times the zoom is refined when framing entities (text doesn't
+/// scale linearly with zoom)
+pub const FRAME_ZOOM_ITERATIONS: usize = 3;
+
+/// The gap between lines of wrapped text, as a fraction of the height of the
+/// text
+pub const LINE_GAP_FRACTION: f64 = 0.25;
+
 pub const MIN_DATETIME_SCALE: f64 = 1.0;
-pub const MAX_DATETIME_SCALE: f64 = 50.0;
+pub const MAX_DATETIME_SCALE: f64 = 3000.0;
 
 pub const DATETIME_SCALE_THRESHOLD_SHOW_YEARS: f64 = 8.0;
 pub const DATETIME_SCALE_THRESHOLD_SHOW_FULL_YEARS: f64 = 12.0;
 pub const DATETIME_SCALE_THRESHOLD_SHOW_YEAR_LINES_PARTAL: f64 = 3.0;
 pub const DATETIME_X_THRESHOLD_SHOW_YEAR_LINES_FULL: f64 = 5.0;
+pub const DATETIME_SCALE_THRESHOLD_SHOW_MONTH_LINES: f64 = 40.0;
+pub const DATETIME_SCALE_THRESHOLD_SHOW_MONTHS: f64 = 100.0;
+pub const DATETIME_SCALE_THRESHOLD_SHOW_DAY_LINES: f64 = 600.0;
+pub const DATETIME_SCALE_THRESHOLD_SHOW_DAYS: f64 = 2000.0;
+
+/// How far (as a fraction) the automatic datetime scale must move from the
+/// current one before it's changed, so that it doesn't flicker (e.g. while the
+/// canvas is resized, or around [`DATETIME_SCALE_THRESHOLD_SHOW_YEARS`])
+pub const AUTO_DATETIME_SCALE_HYSTERESIS: f64 = 0.1;
+
+/// The most text measurements cached (at one font size) before the cache is
+/// emptied, so that it can't grow without limit
+pub const TEXT_MEASUREMENT_CACHE_CAPACITY: usize = 100_000;
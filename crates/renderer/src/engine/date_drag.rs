@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Changing an entity's dates by dragging the edges of its date box (in edit
+//! mode)
+//!
+
+use open_timeline_core::{Date, OpenTimelineId};
+use serde::{Deserialize, Serialize};
+
+/// An edge of an entity's date box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateBoxEdge {
+    /// The left edge (the start date)
+    Start,
+
+    /// The right edge (the end date)
+    End,
+}
+
+/// An entity's date being changed by dragging an edge of its date box
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DateDrag {
+    /// The entity whose date is being changed
+    pub entity_id: OpenTimelineId,
+
+    /// The edge being dragged
+    pub edge: DateBoxEdge,
+
+    /// The entity's start date before the drag started
+    pub original_start: Date,
+
+    /// The entity's end date before the drag started
+    pub original_end: Option<Date>,
+}
@@ -31,6 +31,10 @@ pub(crate) struct TimelineDateRange {
     /// this, it isn't shown on the timeline
     pub end_date_cutoff: Option<Date>,
 
+    /// Optional years the timeline must include even if it has no entities in
+    /// them (e.g. so that timelines shown together share an axis)
+    pub shared_axis_years: Option<(i32, i32)>,
+
     /// The number of decades being shown on the timeline (not set by users)
     pub decade_count: i32,
 }
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! A built-in text measuring function (using a bundled font) for when the
+//! caller has no way of measuring text (e.g. servers, the CLI, and tests)
+//!
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use std::sync::LazyLock;
+
+/// The bundled font used by the default measurer (Ubuntu Light, the same font
+/// `egui` uses by default).  Renderers should draw with this font so that text
+/// fits where the [`Engine`](crate::Engine) expects it to.
+pub const DEFAULT_MEASURER_FONT: &[u8] = epaint_default_fonts::UBUNTU_LIGHT;
+
+/// The font family name of [`DEFAULT_MEASURER_FONT`]
+pub const DEFAULT_MEASURER_FONT_FAMILY: &str = "Ubuntu";
+
+static FONT: LazyLock<FontRef<'static>> = LazyLock::new(|| {
+    FontRef::try_from_slice(DEFAULT_MEASURER_FONT).expect("the bundled font is valid")
+});
+
+/// Measure text using the bundled font.  Returns `(width, height)`, where the
+/// height is that of the text's glyphs (as with a canvas's `measureText()`).
+pub fn default_measure_text(font_size: f64, text: String) -> (f64, f64) {
+    let font = FONT.as_scaled(PxScale::from(font_size as f32));
+    let mut x = 0.0;
+    let mut min_y = f32::MAX;
+    let mut max_y = f32::MIN;
+    let mut previous = None;
+    for c in text.chars() {
+        let glyph_id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            x += font.kern(previous, glyph_id);
+        }
+        // Glyphs share a baseline, so their (unscaled) vertical bounds can be
+        // compared directly.  The bounds are flipped (y up), hence checking
+        // both.
+        if let Some(outline) = FONT.outline(glyph_id) {
+            for y in [outline.bounds.min.y, outline.bounds.max.y] {
+                min_y = min_y.min(y);
+                max_y = max_y.max(y);
+            }
+        }
+        x += font.h_advance(glyph_id);
+        previous = Some(glyph_id);
+    }
+    let height = if max_y > min_y {
+        (max_y - min_y) * font.scale_factor().vertical
+    } else {
+        0.0
+    };
+    (x.into(), height.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::default_measure_text;
+
+    #[test]
+    fn measurements_scale_with_font_size() {
+        let (small_width, small_height) = default_measure_text(10.0, "1234s".to_string());
+        let (large_width, large_height) = default_measure_text(20.0, "1234s".to_string());
+        assert!(small_width > 0.0 && small_height > 0.0);
+        assert!((large_width - (small_width * 2.0)).abs() < 1.0);
+        assert!((large_height - (small_height * 2.0)).abs() < 1.0);
+    }
+
+    #[test]
+    fn longer_text_is_wider() {
+        let (short, _) = default_measure_text(12.0, "Ada".to_string());
+        let (long, _) = default_measure_text(12.0, "Ada Lovelace".to_string());
+        assert!(long > short);
+        assert_eq!(default_measure_text(12.0, String::new()), (0.0, 0.0));
+    }
+}
@@ -5,38 +5,40 @@
 //!
 
 use crate::{
-    Colour, FilledBox, MeasuredLayoutParams, Point, PositionAndSize, ScalableLayoutParams, TextOut,
-    TextWorking, TimelineColours, TimelineDateRange, colours::Colours,
+    Colour, FilledBox, Focus, MeasuredLayoutParams, Point, PositionAndSize, ScalableLayoutParams,
+    TextOut, TextWorking, TimelineColours, TimelineDateRange, WrappedText, colours::Colours,
 };
 use bool_tag_expr::BoolTagExpr;
-use open_timeline_core::{Date, Entity, HasIdAndName};
+use open_timeline_core::{Coordinates, Date, Entity, HasIdAndName, OpenTimelineId, TagHierarchy};
 use serde::Serialize;
+use std::borrow::Cow;
 use std::fmt::Debug;
 
+/// Give each entity without an ID (e.g. one from a JSON file rather than the
+/// database) an ephemeral ID, so that it can be drawn and interacted with like
+/// any other.  The IDs are random, so aren't the same between loads.
+pub fn assign_render_ids(entities: &mut [Entity]) {
+    for entity in entities.iter_mut().filter(|entity| entity.id().is_none()) {
+        entity.set_id(OpenTimelineId::new());
+    }
+}
+
 /// Information needed to draw an [`Entity`] on a timeline (for use outisde of
-/// the engine)
+/// the engine).  It borrows from the engine, so drawing doesn't have to copy
+/// every entity each frame.
 #[derive(Debug, Clone, Serialize)]
-pub struct EntityOut {
-    pub entity: Entity,
-    pub text: TextOut,
+pub struct EntityOut<'a> {
+    pub entity: &'a Entity,
+    pub text: TextOut<'a>,
     pub text_box: FilledBox,
     pub date_box: FilledBox,
-}
 
-impl From<WorkingEntity> for EntityOut {
-    fn from(value: WorkingEntity) -> Self {
-        EntityOut {
-            entity: value.entity,
-            text: TextOut {
-                top_left: value.text.top_left,
-                text: value.text.text,
-                colour: value.text.colour,
-                font_size: value.text.font_size,
-            },
-            text_box: value.text_box,
-            date_box: value.date_box,
-        }
-    }
+    /// The glow drawn behind the entity if it is selected (see
+    /// [`SelectionHighlightStyle::Glow`])
+    ///
+    /// [`SelectionHighlightStyle::Glow`]: crate::SelectionHighlightStyle::Glow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<FilledBox>,
 }
 
 /// Information needed when working/calculating with an entity (for internal use
@@ -56,6 +58,8 @@ pub(crate) struct WorkingEntity {
 
     is_filtered_out_by_date_range: bool,
     is_filtered_out_by_bool_expr: bool,
+    is_filtered_out_by_location: bool,
+    is_filtered_out_by_focus: bool,
 
     row: usize,
 
@@ -71,10 +75,11 @@ impl WorkingEntity {
         colours: TimelineColours,
         measured_layout_params: MeasuredLayoutParams,
         zoomed_layout_params: ScalableLayoutParams,
-        text_width: f64,
+        wrapped: WrappedText,
     ) -> Self {
-        let row_height_with_padding =
-            measured_layout_params.row_height_no_padding + (2.0 * zoomed_layout_params.padding_y);
+        let text_width = wrapped.width;
+        let height_with_padding =
+            wrapped.height(measured_layout_params) + (2.0 * zoomed_layout_params.padding_y);
 
         // Colours
         let entity_tag_colours = Colours::tag_colours();
@@ -99,7 +104,8 @@ impl WorkingEntity {
         // Text
         let text = TextWorking::from(
             entity.name().to_string(),
-            text_width,
+            wrapped,
+            measured_layout_params,
             zoomed_layout_params.font_size_px,
             colours.entity.text_colour,
         );
@@ -109,9 +115,10 @@ impl WorkingEntity {
             position_and_size: PositionAndSize {
                 position: Point { x: 0.0, y: 0.0 },
                 width: text_width + (2.0 * zoomed_layout_params.padding_x),
-                height: row_height_with_padding,
+                height: height_with_padding,
             },
             fill_colour: text_box,
+            fill_alpha: 1.0,
             border_style: colours.entity.text_box.border,
         };
 
@@ -120,9 +127,10 @@ impl WorkingEntity {
             position_and_size: PositionAndSize {
                 position: Point { x: 0.0, y: 0.0 },
                 width: 0.0,
-                height: row_height_with_padding,
+                height: height_with_padding,
             },
             fill_colour: date_box,
+            fill_alpha: 1.0,
             border_style: colours.entity.date_box.border,
         };
 
@@ -140,6 +148,8 @@ impl WorkingEntity {
             is_selected: false,
             is_filtered_out_by_date_range: false,
             is_filtered_out_by_bool_expr: false,
+            is_filtered_out_by_location: false,
+            is_filtered_out_by_focus: false,
             row: 0,
             start,
             end,
@@ -163,22 +173,25 @@ impl WorkingEntity {
         colours: TimelineColours,
         measured_layout_params: MeasuredLayoutParams,
         zoomed_layout_params: ScalableLayoutParams,
-        text_width: f64,
+        wrapped: WrappedText,
     ) {
-        let row_height_with_padding =
-            measured_layout_params.row_height_no_padding + (2.0 * zoomed_layout_params.padding_y);
+        let height_with_padding =
+            wrapped.height(measured_layout_params) + (2.0 * zoomed_layout_params.padding_y);
 
         // Text
-        self.text.width = text_width;
+        self.text.width = wrapped.width;
+        self.text.lines = wrapped.lines;
         self.text.font_size = zoomed_layout_params.font_size_px;
+        self.text.line_height = measured_layout_params.line_height;
 
         // Text box (position calculated elsewhere)
-        self.text_box.position_and_size.width = text_width + (2.0 * zoomed_layout_params.padding_x);
-        self.text_box.position_and_size.height = row_height_with_padding;
+        self.text_box.position_and_size.width =
+            self.text.width + (2.0 * zoomed_layout_params.padding_x);
+        self.text_box.position_and_size.height = height_with_padding;
         self.text_box.border_style = colours.entity.text_box.border;
 
         // Date box (position and width calculated elsewhere)
-        self.date_box.position_and_size.height = row_height_with_padding;
+        self.date_box.position_and_size.height = height_with_padding;
         self.date_box.border_style = colours.entity.date_box.border;
     }
 
@@ -190,7 +203,10 @@ impl WorkingEntity {
 
     ///
     pub fn is_filtered_out(&self) -> bool {
-        self.is_filtered_out_by_bool_expr || self.is_filtered_out_by_date_range
+        self.is_filtered_out_by_bool_expr
+            || self.is_filtered_out_by_location
+            || self.is_filtered_out_by_date_range
+            || self.is_filtered_out_by_focus
     }
 
     /// Calculate the entity's minimum x position/value
@@ -214,29 +230,42 @@ impl WorkingEntity {
             .max(self.date_box.position_and_size.max_y())
     }
 
-    /// Clone the entity and add an offset.  Used when moving the timeline so
-    /// that nothing else needs to be re-calculated
-    pub fn clone_with_added_offset(&mut self, x: f64, y: f64) -> Self {
-        let mut entity = self.clone();
+    /// A view of the entity for drawing, with an offset added.  Used when
+    /// moving the timeline so that nothing else needs to be re-calculated
+    pub(crate) fn out_with_added_offset(&self, x: f64, y: f64) -> EntityOut<'_> {
+        let mut entity = EntityOut {
+            entity: &self.entity,
+            text: TextOut {
+                top_left: self.text.top_left,
+                text: Cow::Borrowed(&self.text.text),
+                lines: Cow::Borrowed(&self.text.lines),
+                colour: self.text.colour,
+                font_size: self.text.font_size,
+                line_height: self.text.line_height,
+            },
+            text_box: self.text_box,
+            date_box: self.date_box,
+            highlight: None,
+        };
         entity.text.add_offset(x, y);
         entity.text_box.position_and_size.add_offset(x, y);
         entity.date_box.position_and_size.add_offset(x, y);
         entity
     }
 
-    // Offset the text inside the box so that it sticks to the left of the
-    // screen while there is space left to do so
-    pub(crate) fn adjust_sticky_text(&mut self, padding_x: f64) {
+    // Offset the text inside the box (of the view for drawing) so that it
+    // sticks to the left of the screen while there is space left to do so
+    pub(crate) fn adjust_sticky_text(&self, entity: &mut EntityOut, padding_x: f64) {
         let text_width = self.text.width;
-        let text_box_width = self.text_box.position_and_size.width;
-        let date_box_width = self.date_box.position_and_size.width;
+        let text_box_width = entity.text_box.position_and_size.width;
+        let date_box_width = entity.date_box.position_and_size.width;
         let box_width = text_box_width.max(date_box_width);
         let box_free_space = box_width - text_width - (2.0 * padding_x);
-        if self.text.top_left.x < padding_x {
-            if self.text.top_left.x < -(box_free_space - padding_x) {
-                self.text.top_left.x += box_free_space;
+        if entity.text.top_left.x < padding_x {
+            if entity.text.top_left.x < -(box_free_space - padding_x) {
+                entity.text.top_left.x += box_free_space;
             } else {
-                self.text.top_left.x = padding_x;
+                entity.text.top_left.x = padding_x;
             }
         }
     }
@@ -264,9 +293,27 @@ impl WorkingEntity {
     }
 
     ///
-    pub(crate) fn update_filtered_by_bool_tag_expr(&mut self, expr: &Option<BoolTagExpr>) {
-        self.is_filtered_out_by_bool_expr = expr
+    pub(crate) fn update_filtered_by_bool_tag_expr(
+        &mut self,
+        expr: &Option<BoolTagExpr>,
+        tag_hierarchy: &TagHierarchy,
+    ) {
+        self.is_filtered_out_by_bool_expr = expr.as_ref().map_or(false, |expr| {
+            !self.entity.matches_bool_tag_expr(expr, tag_hierarchy)
+        });
+    }
+
+    /// Hide the entity if it isn't within the location filter's radius
+    pub(crate) fn update_filtered_by_location(&mut self, filter: &Option<(Coordinates, f64)>) {
+        self.is_filtered_out_by_location = filter
+            .as_ref()
+            .is_some_and(|(coordinates, radius_km)| !self.entity.is_near(coordinates, *radius_km));
+    }
+
+    /// Hide the entity if the timeline is focused on other entities
+    pub(crate) fn update_filtered_by_focus(&mut self, focus: &Option<Focus>) {
+        self.is_filtered_out_by_focus = focus
             .as_ref()
-            .map_or(false, |expr| !self.entity.matches_bool_tag_expr(expr));
+            .is_some_and(|focus| focus.hides(self.entity.id()));
     }
 }
@@ -4,7 +4,7 @@
 //! Events
 //!
 
-use open_timeline_core::OpenTimelineId;
+use open_timeline_core::{Date, OpenTimelineId};
 use serde::Serialize;
 use std::fmt::Debug;
 
@@ -15,4 +15,59 @@ pub enum TimelineInteractionEvent {
     DoubleClick(OpenTimelineId),
     TripleClick(OpenTimelineId),
     Hover(OpenTimelineId),
+    SelectionChange(Vec<OpenTimelineId>),
+
+    /// A context menu was asked for (e.g. by right-clicking or long-pressing)
+    /// on the entity, at the point (relative to the canvas).  See
+    /// [`Engine::context_menu_on_entity()`].
+    ///
+    /// [`Engine::context_menu_on_entity()`]: crate::Engine::context_menu_on_entity
+    ContextMenu(OpenTimelineId, f64, f64),
+
+    /// Empty space (not an entity) was double clicked on, in the given year
+    /// (e.g. to create an entity there)
+    EmptySpaceDoubleClick(Date),
+
+    /// An entity's dates were changed by dragging the edge of its date box (in
+    /// edit mode).  See [`Engine::set_edit_mode()`].
+    ///
+    /// [`Engine::set_edit_mode()`]: crate::Engine::set_edit_mode
+    DatesChanged {
+        /// The entity whose dates changed
+        entity_id: OpenTimelineId,
+
+        /// The entity's new start date
+        start: Date,
+
+        /// The entity's new end date
+        end: Option<Date>,
+
+        /// The entity's start date before it was changed
+        previous_start: Date,
+
+        /// The entity's end date before it was changed
+        previous_end: Option<Date>,
+    },
+
+    /// A different step of the story is being shown (or none, if the story
+    /// has been stopped).  See [`Engine::go_to_story_step()`].
+    ///
+    /// [`Engine::go_to_story_step()`]: crate::Engine::go_to_story_step
+    StoryStepChange(Option<usize>),
+
+    /// The part of the timeline in view changed (e.g. it was panned or
+    /// zoomed).  Only emitted once the viewport has stopped changing (see
+    /// [`Engine::set_viewport_changed_debounce_ms()`]).
+    ///
+    /// [`Engine::set_viewport_changed_debounce_ms()`]: crate::Engine::set_viewport_changed_debounce_ms
+    ViewportChanged {
+        /// The (fractional) year at the left edge of the canvas
+        start_year: f64,
+
+        /// The (fractional) year at the right edge of the canvas
+        end_year: f64,
+
+        /// The zoom level
+        zoom: f64,
+    },
 }
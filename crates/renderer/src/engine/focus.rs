@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Focusing on a set of entities
+//!
+
+use crate::TimelineOffset;
+use open_timeline_core::OpenTimelineId;
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+
+/// The entities the timeline is focused on (see [`Engine::focus_on_selection()`])
+///
+/// [`Engine::focus_on_selection()`]: crate::Engine::focus_on_selection
+#[derive(Debug, Clone)]
+pub(crate) struct Focus {
+    /// The IDs of the entities in focus
+    pub entity_ids: BTreeSet<OpenTimelineId>,
+
+    /// Whether entities not in focus are hidden
+    pub hide_others: bool,
+
+    /// The zoom before focusing (restored when exiting focus)
+    pub previous_zoom: f64,
+
+    /// The global offset before focusing (restored when exiting focus)
+    pub previous_offset: TimelineOffset,
+}
+
+impl Focus {
+    /// Whether the entity should be hidden while focused
+    pub fn hides(&self, entity_id: Option<OpenTimelineId>) -> bool {
+        self.hide_others && entity_id.is_none_or(|id| !self.entity_ids.contains(&id))
+    }
+}
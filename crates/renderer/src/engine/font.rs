@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Font
+//!
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// The CSS generic font families (which are always available, so end every
+/// fallback chain)
+const GENERIC_FONT_FAMILIES: [&str; 8] = [
+    "serif",
+    "sans-serif",
+    "monospace",
+    "cursive",
+    "fantasy",
+    "system-ui",
+    "ui-serif",
+    "ui-sans-serif",
+];
+
+/// The font family used if none (or no generic family) is given
+pub const DEFAULT_FONT_FAMILY: &str = "serif";
+
+/// The normal font weight
+pub const DEFAULT_FONT_WEIGHT: u16 = 400;
+
+/// The font used to measure and draw all of the timeline's text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontConfig {
+    /// The font families in order of preference.  Later families are used
+    /// when earlier ones aren't available (the default family is used if none
+    /// are).
+    pub families: Vec<String>,
+
+    /// The font weight (`100` to `900`, where `400` is normal and `700` is
+    /// bold)
+    pub weight: u16,
+}
+
+impl Default for FontConfig {
+    fn default() -> Self {
+        Self {
+            families: vec![DEFAULT_FONT_FAMILY.to_string()],
+            weight: DEFAULT_FONT_WEIGHT,
+        }
+    }
+}
+
+impl FontConfig {
+    /// Create a font config from a CSS font family list (e.g. `"Georgia,
+    /// serif"`)
+    pub fn from_css_family_list(family_list: &str, weight: u16) -> Self {
+        let families = family_list
+            .split(',')
+            .map(|family| family.trim().trim_matches(['"', '\'']).trim())
+            .filter(|family| !family.is_empty())
+            .map(String::from)
+            .collect();
+        Self { families, weight }
+    }
+
+    /// Get the font weight (within the range CSS allows)
+    pub fn weight(&self) -> u16 {
+        self.weight.clamp(1, 1000)
+    }
+
+    /// Get the font families to try, in order.  The chain always ends with a
+    /// generic family so that there is always something to fall back on.
+    pub fn fallback_chain(&self) -> Vec<&str> {
+        let mut chain: Vec<&str> = self
+            .families
+            .iter()
+            .map(|family| family.trim())
+            .filter(|family| !family.is_empty())
+            .collect();
+        if !chain.iter().any(|family| is_generic_font_family(family)) {
+            chain.push(DEFAULT_FONT_FAMILY);
+        }
+        chain
+    }
+
+    /// Get the fallback chain as a CSS font family list (e.g. `"Noto Serif",
+    /// Georgia, serif`)
+    pub fn css_family_list(&self) -> String {
+        self.fallback_chain()
+            .into_iter()
+            .map(|family| match is_generic_font_family(family) {
+                true => family.to_string(),
+                false => format!("\"{}\"", family.replace('"', "")),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Get the CSS font shorthand for the font at the given size (e.g. `700
+    /// 12px Georgia, serif`)
+    pub fn css_font(&self, font_size_px: f64) -> String {
+        format!(
+            "{} {font_size_px}px {}",
+            self.weight(),
+            self.css_family_list()
+        )
+    }
+}
+
+/// Whether the family is a CSS generic font family (e.g. `serif`)
+pub fn is_generic_font_family(family: &str) -> bool {
+    GENERIC_FONT_FAMILIES.contains(&family.to_lowercase().as_str())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn css_family_list_ends_with_a_generic_family() {
+        let font = FontConfig::from_css_family_list("'Noto Serif', Georgia", 700);
+        assert_eq!(font.families, vec!["Noto Serif", "Georgia"]);
+        assert_eq!(font.css_family_list(), r#""Noto Serif", "Georgia", serif"#);
+        assert_eq!(
+            font.css_font(12.0),
+            r#"700 12px "Noto Serif", "Georgia", serif"#
+        );
+
+        let font = FontConfig::from_css_family_list("Helvetica, sans-serif", 400);
+        assert_eq!(font.fallback_chain(), vec!["Helvetica", "sans-serif"]);
+        assert_eq!(FontConfig::default().css_font(10.0), "400 10px serif");
+        assert_eq!(
+            FontConfig::from_css_family_list("", 400).fallback_chain(),
+            vec![DEFAULT_FONT_FAMILY]
+        );
+    }
+}
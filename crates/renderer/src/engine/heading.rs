@@ -11,7 +11,7 @@ use std::fmt::Debug;
 /// Information needed to draw the timeline's headings
 #[derive(Debug, Clone, Serialize)]
 pub struct Heading {
-    pub text: TextOut,
+    pub text: TextOut<'static>,
     pub text_box: FilledBox,
 }
 
@@ -4,6 +4,9 @@
 //! Helper functions
 //!
 
+use chrono::{Datelike, Local};
+use open_timeline_core::Date;
+
 // TODO: do we need this?  The JavaScript version had some entity flickering as
 // they switched rows as we dragged.  This I think fixed it.  However, we no
 // longer calculate positions/rows when the timeline is dragged.
@@ -32,10 +35,126 @@ pub(crate) fn ceiling_to_decade(value: i32) -> i32 {
     }
 }
 
+/// The months of the year, each as the month & the fractions of the year it
+/// starts & ends at (none if the year isn't valid)
+pub(crate) fn months_of_year(year: i32) -> Vec<(u8, f64, f64)> {
+    let starts: Vec<f64> = (1..=12)
+        .filter_map(|month| Date::from(Some(1), Some(month), i64::from(year)).ok())
+        .map(|date| date.fraction_of_year())
+        .collect();
+    if starts.len() != 12 {
+        return Vec::new();
+    }
+    (1..=12)
+        .zip(starts.iter())
+        .map(|(month, start)| {
+            let end = starts.get(usize::from(month)).copied().unwrap_or(1.0);
+            (month, *start, end)
+        })
+        .collect()
+}
+
+/// The days of the year, each as the day of the month & the fractions of the
+/// year it starts & ends at (none if the year isn't valid)
+pub(crate) fn days_of_year(year: i32) -> Vec<(u8, f64, f64)> {
+    let Ok(date) = Date::from(None, None, i64::from(year)) else {
+        return Vec::new();
+    };
+    let day_fraction = 1.0 / date.days_in_year() as f64;
+    let mut days = Vec::new();
+    for (_, start, end) in months_of_year(year) {
+        let day_count = ((end - start) / day_fraction).round() as u8;
+        for day in 1..=day_count {
+            let day_start = start + (f64::from(day - 1) * day_fraction);
+            days.push((day, day_start, day_start + day_fraction));
+        }
+    }
+    days
+}
+
+/// Today's (local) date
+pub(crate) fn today() -> Date {
+    let today = Local::now().date_naive();
+    Date::from(
+        Some(i64::from(today.day())),
+        Some(i64::from(today.month())),
+        i64::from(today.year()),
+    )
+    .unwrap_or_else(|_| Date::today())
+}
+
+/// Describe how many years ago something was (e.g. "75 years ago", or "In 3
+/// years" for the future)
+pub(crate) fn years_ago(years: i32) -> String {
+    match years {
+        0 => String::from("This year"),
+        1 => String::from("1 year ago"),
+        -1 => String::from("In 1 year"),
+        years if years < 0 => format!("In {} years", -years),
+        years => format!("{years} years ago"),
+    }
+}
+
+/// Split the text into lines no wider than `max_width` (according to
+/// `str_width`), breaking between words where possible.  Words that are wider
+/// than `max_width` by themselves are broken between characters.
+pub(crate) fn wrap_text<F: Fn(&str) -> f64>(
+    text: &str,
+    max_width: f64,
+    str_width: F,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        // Add the word to the current line if it fits
+        let joined = match line.is_empty() {
+            true => word.to_string(),
+            false => format!("{line} {word}"),
+        };
+        if str_width(&joined) <= max_width {
+            line = joined;
+            continue;
+        }
+
+        // Otherwise start a new line with the word (breaking it up if it's
+        // too wide)
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            line.push(c);
+            if line.chars().count() > 1 && str_width(&line) > max_width {
+                line.pop();
+                lines.push(std::mem::replace(&mut line, c.to_string()));
+            }
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn months_and_days_of_year() {
+        let months = months_of_year(2024);
+        assert_eq!(months.len(), 12);
+        assert_eq!(months[0], (1, 0.0, 31.0 / 366.0));
+        assert_eq!(months[11].2, 1.0);
+
+        // Leap years have a 29th of Feb
+        assert_eq!(days_of_year(2024).len(), 366);
+        assert_eq!(days_of_year(2023).len(), 365);
+        let days = days_of_year(2024);
+        assert_eq!(days[59].0, 29);
+        assert_eq!(days[60].0, 1);
+        assert!((days[365].2 - 1.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_floor_to_decade() {
         assert_eq!(floor_to_decade(-150), -150);
@@ -55,4 +174,31 @@ mod test {
         assert_eq!(ceiling_to_decade(151), 160);
         assert_eq!(ceiling_to_decade(159), 160);
     }
+
+    #[test]
+    fn test_years_ago() {
+        assert_eq!(years_ago(75), "75 years ago");
+        assert_eq!(years_ago(1), "1 year ago");
+        assert_eq!(years_ago(0), "This year");
+        assert_eq!(years_ago(-1), "In 1 year");
+        assert_eq!(years_ago(-10), "In 10 years");
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        let str_width = |str: &str| str.chars().count() as f64;
+        assert_eq!(
+            wrap_text("Ada Lovelace", 20.0, str_width),
+            vec!["Ada Lovelace"]
+        );
+        assert_eq!(
+            wrap_text("The Battle of Hastings", 10.0, str_width),
+            vec!["The Battle", "of", "Hastings"]
+        );
+        assert_eq!(
+            wrap_text("Supercalifragilistic", 8.0, str_width),
+            vec!["Supercal", "ifragili", "stic"]
+        );
+        assert_eq!(wrap_text("", 8.0, str_width), vec![""]);
+    }
 }
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! How selected entities are highlighted
+//!
+
+use crate::{Colour, FilledBox, LineStyle, Position, PositionAndSize, SELECTION_GLOW_ALPHA};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// The shape of the highlight drawn around selected entities
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionHighlightStyle {
+    /// A border around the entity's text and date boxes
+    #[default]
+    Border,
+
+    /// A translucent glow behind the entity (as wide as the layout params'
+    /// `entity_highlight_thickness`)
+    Glow,
+}
+
+/// How selected entities are highlighted (see
+/// [`Engine::set_selection_highlight()`])
+///
+/// [`Engine::set_selection_highlight()`]: crate::Engine::set_selection_highlight
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SelectionHighlight {
+    pub style: SelectionHighlightStyle,
+    pub colour: Colour,
+}
+
+impl Default for SelectionHighlight {
+    fn default() -> Self {
+        Self {
+            style: SelectionHighlightStyle::default(),
+            colour: Colour::from_rgb(255, 140, 0),
+        }
+    }
+}
+
+impl SelectionHighlight {
+    /// The border drawn around each of a selected entity's boxes (if the style
+    /// is a border)
+    pub(crate) fn border(&self, thickness: f64) -> Option<LineStyle> {
+        match self.style {
+            SelectionHighlightStyle::Border => Some(LineStyle {
+                colour: self.colour,
+                thickness,
+            }),
+            SelectionHighlightStyle::Glow => None,
+        }
+    }
+
+    /// The glow drawn behind a selected entity whose boxes are `a` and `b` (if
+    /// the style is a glow)
+    pub(crate) fn glow(
+        &self,
+        a: &PositionAndSize,
+        b: &PositionAndSize,
+        thickness: f64,
+    ) -> Option<FilledBox> {
+        if self.style != SelectionHighlightStyle::Glow {
+            return None;
+        }
+        let min_x = a.position.x.min(b.position.x) - thickness;
+        let min_y = a.position.y.min(b.position.y) - thickness;
+        let max_x = a.max_x().max(b.max_x()) + thickness;
+        let max_y = a.max_y().max(b.max_y()) + thickness;
+        Some(FilledBox {
+            position_and_size: PositionAndSize {
+                position: Position { x: min_x, y: min_y },
+                width: max_x - min_x,
+                height: max_y - min_y,
+            },
+            fill_colour: self.colour,
+            fill_alpha: SELECTION_GLOW_ALPHA,
+            border_style: None,
+        })
+    }
+}
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Inertial panning
+//!
+
+use crate::{PAN_INERTIA_DECAY_PER_MS, PAN_INERTIA_MIN_SPEED, Point};
+use std::fmt::Debug;
+
+/// The velocity (pixels per millisecond) the timeline keeps moving at after the
+/// user lets go of it.  The velocity decays over time until it is negligible.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PanInertia {
+    velocity: Option<Point>,
+}
+
+impl PanInertia {
+    /// Start moving at the given velocity (pixels per millisecond)
+    pub fn start(&mut self, velocity: Point) {
+        self.velocity = Some(velocity);
+        self.stop_if_negligible();
+    }
+
+    /// Stop moving
+    pub fn stop(&mut self) {
+        self.velocity = None;
+    }
+
+    /// Stop moving along one axis (e.g. when the timeline hits its limit)
+    pub fn stop_x(&mut self) {
+        if let Some(velocity) = self.velocity.as_mut() {
+            velocity.x = 0.0;
+        }
+        self.stop_if_negligible();
+    }
+
+    /// Stop moving along one axis (e.g. when the timeline hits its limit)
+    pub fn stop_y(&mut self) {
+        if let Some(velocity) = self.velocity.as_mut() {
+            velocity.y = 0.0;
+        }
+        self.stop_if_negligible();
+    }
+
+    /// Whether the timeline is still moving
+    pub fn is_moving(&self) -> bool {
+        self.velocity.is_some()
+    }
+
+    /// Get the distance to move in the elapsed time, and decay the velocity
+    pub fn step(&mut self, ms_elapsed: f64) -> Option<Point> {
+        let velocity = self.velocity.as_mut()?;
+        let delta = Point {
+            x: velocity.x * ms_elapsed,
+            y: velocity.y * ms_elapsed,
+        };
+        let decay = PAN_INERTIA_DECAY_PER_MS.powf(ms_elapsed);
+        velocity.x *= decay;
+        velocity.y *= decay;
+        self.stop_if_negligible();
+        Some(delta)
+    }
+
+    fn stop_if_negligible(&mut self) {
+        if let Some(velocity) = self.velocity
+            && velocity.x.hypot(velocity.y) < PAN_INERTIA_MIN_SPEED
+        {
+            self.velocity = None;
+        }
+    }
+}
@@ -5,7 +5,7 @@
 //!
 
 use crate::Position;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
 /// Layout parameters that are derived from the size of measured text
@@ -13,10 +13,14 @@ use std::fmt::Debug;
 pub(crate) struct MeasuredLayoutParams {
     pub year_width: f64,
     pub row_height_no_padding: f64,
+
+    /// The distance between the tops of consecutive lines of wrapped text
+    pub line_height: f64,
 }
 
 /// Layout parameters that users can adjust and which are multiplied by the scale
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ScalableLayoutParams {
     pub row_margin: f64,
     pub min_inline_spacing: f64,
@@ -25,6 +29,10 @@ pub struct ScalableLayoutParams {
     pub font_size_px: f64,
     pub dividing_line_thickness: f64,
     pub entity_highlight_thickness: f64,
+
+    /// Entity names wider than this are wrapped onto multiple lines (names
+    /// aren't wrapped if `None`)
+    pub max_text_width: Option<f64>,
 }
 
 impl Default for ScalableLayoutParams {
@@ -37,6 +45,19 @@ impl Default for ScalableLayoutParams {
             font_size_px: 12.0,
             dividing_line_thickness: 0.5,
             entity_highlight_thickness: 10.0,
+            max_text_width: None,
+        }
+    }
+}
+
+impl ScalableLayoutParams {
+    /// Layout parameters to go with high contrast colours (i.e. larger text and
+    /// thicker dividing lines)
+    pub fn high_contrast() -> Self {
+        ScalableLayoutParams {
+            font_size_px: 16.0,
+            dividing_line_thickness: 2.0,
+            ..Default::default()
         }
     }
 }
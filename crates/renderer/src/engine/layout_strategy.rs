@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Row packing strategies
+//!
+
+use crate::round_f64_to_nearest_0_1;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// How entities are packed into rows (see [`Engine::set_layout_strategy()`])
+///
+/// [`Engine::set_layout_strategy()`]: crate::Engine::set_layout_strategy
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutStrategy {
+    /// Put each entity in the first row it fits into
+    #[default]
+    FirstFit,
+
+    /// Put each entity in the row that leaves the smallest gap before it.
+    /// This uses the fewest rows and keeps rows tightly packed.
+    BestFit,
+
+    /// Give each entity its own row, in the sort order (chronological by
+    /// default, see [`Engine::set_sort()`])
+    ///
+    /// [`Engine::set_sort()`]: crate::Engine::set_sort
+    Chronological,
+
+    /// Pack entities sharing a tag into their own rows.  Groups are ordered by
+    /// tag (untagged entities last), and entities with several tags are
+    /// grouped by the alphabetically first.
+    GroupedByTag,
+}
+
+impl LayoutStrategy {
+    /// All strategies (useful for drawing a picker)
+    pub const ALL: [LayoutStrategy; 4] = [
+        LayoutStrategy::FirstFit,
+        LayoutStrategy::BestFit,
+        LayoutStrategy::Chronological,
+        LayoutStrategy::GroupedByTag,
+    ];
+
+    /// The name of the strategy
+    pub fn name(&self) -> &'static str {
+        match self {
+            LayoutStrategy::FirstFit => "First fit",
+            LayoutStrategy::BestFit => "Best fit",
+            LayoutStrategy::Chronological => "Chronological",
+            LayoutStrategy::GroupedByTag => "Grouped by tag",
+        }
+    }
+}
+
+/// Find the row (given each row's current max x value) that an entity starting
+/// at `entity_min_x` fits into, if any
+pub(crate) fn find_row(
+    rows: &[f64],
+    entity_min_x: f64,
+    min_inline_spacing: f64,
+    best_fit: bool,
+) -> Option<usize> {
+    let entity_min_x = round_f64_to_nearest_0_1(entity_min_x);
+
+    // An entity fits into a row if it is to the right of whatever is in the
+    // row (plus spacing)
+    let mut fitting_rows = rows.iter().enumerate().filter(|(_, row_max_x)| {
+        round_f64_to_nearest_0_1(**row_max_x + min_inline_spacing) < entity_min_x
+    });
+    if best_fit {
+        fitting_rows
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    } else {
+        fitting_rows.next().map(|(i, _)| i)
+    }
+}
@@ -4,17 +4,33 @@
 //! Primitives
 //!
 
-use crate::{LineStyle, Point, PositionAndSize, colour::Colour};
+use crate::{LineStyle, MeasuredLayoutParams, Point, PositionAndSize, colour::Colour};
 use serde::Serialize;
+use std::borrow::Cow;
 use std::fmt::Debug;
 
-/// Information needed to draw text
+/// Information needed to draw text (borrowed from the engine where possible)
 #[derive(Debug, Clone, Serialize)]
-pub struct TextOut {
+pub struct TextOut<'a> {
     pub top_left: Point,
-    pub text: String,
+    pub text: Cow<'a, str>,
+
+    /// The text split into the lines to draw (a single line unless the text
+    /// was wrapped)
+    pub lines: Cow<'a, [String]>,
+
     pub colour: Colour,
     pub font_size: f64,
+
+    /// The distance between the tops of consecutive lines
+    pub line_height: f64,
+}
+
+impl TextOut<'_> {
+    pub fn add_offset(&mut self, x_offset: f64, y_offset: f64) {
+        self.top_left.x += x_offset;
+        self.top_left.y += y_offset;
+    }
 }
 
 /// Information needed when working with text calculations
@@ -22,25 +38,50 @@ pub struct TextOut {
 pub(crate) struct TextWorking {
     pub top_left: Point,
     pub text: String,
+    pub lines: Vec<String>,
     pub width: f64,
     pub colour: Colour,
     pub font_size: f64,
+    pub line_height: f64,
 }
 
 impl TextWorking {
-    pub fn from(text: String, width: f64, font_size: f64, colour: Colour) -> Self {
+    pub fn from(
+        text: String,
+        wrapped: WrappedText,
+        measured_layout_params: MeasuredLayoutParams,
+        font_size: f64,
+        colour: Colour,
+    ) -> Self {
         Self {
             top_left: Point::default(),
             text,
-            width,
+            lines: wrapped.lines,
+            width: wrapped.width,
             colour,
             font_size,
+            line_height: measured_layout_params.line_height,
         }
     }
+}
 
-    pub fn add_offset(&mut self, x_offset: f64, y_offset: f64) {
-        self.top_left.x += x_offset;
-        self.top_left.y += y_offset;
+/// Text split into lines (each no wider than the max text width, where
+/// possible)
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WrappedText {
+    pub lines: Vec<String>,
+
+    /// The width of the widest line
+    pub width: f64,
+}
+
+impl WrappedText {
+    /// Calculate the height of the lines (the first line is as tall as the
+    /// measured text, and each following line adds a line height)
+    pub fn height(&self, measured_layout_params: MeasuredLayoutParams) -> f64 {
+        let extra_lines = self.lines.len().saturating_sub(1) as f64;
+        measured_layout_params.row_height_no_padding
+            + (extra_lines * measured_layout_params.line_height)
     }
 }
 
@@ -50,6 +91,10 @@ impl TextWorking {
 pub struct FilledBox {
     pub position_and_size: PositionAndSize,
     pub fill_colour: Colour,
+
+    /// How opaque the fill is (`0.0` is fully transparent, `1.0` is solid)
+    pub fill_alpha: f64,
+
     pub border_style: Option<LineStyle>,
 }
 
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! The order entities are put into rows in
+//!
+
+use crate::WorkingEntity;
+use bool_tag_expr::TagName;
+use open_timeline_core::{Date, HasIdAndName};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// The order entities are put into rows in (see [`Engine::set_sort()`]).
+/// Entities sorted first are put in the top rows.  Ties, and entities without
+/// the value being sorted by (which are sorted last), are in start date order.
+///
+/// [`Engine::set_sort()`]: crate::Engine::set_sort
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    /// Sort by start date (earliest first)
+    #[default]
+    StartDate,
+
+    /// Sort by end date (earliest first)
+    EndDate,
+
+    /// Sort by how long entities last (longest first).  Entities without an
+    /// end date are ongoing (i.e. last until today).
+    Duration,
+
+    /// Sort alphabetically by name (ignoring case)
+    Alphabetical,
+
+    /// Sort by the value of the entity's tag with this name (alphabetically)
+    TagValue(TagName),
+}
+
+impl SortOrder {
+    /// All sort orders that don't need any more information (useful for
+    /// drawing a picker)
+    pub const ALL: [SortOrder; 4] = [
+        SortOrder::StartDate,
+        SortOrder::EndDate,
+        SortOrder::Duration,
+        SortOrder::Alphabetical,
+    ];
+
+    /// The name of the sort order
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortOrder::StartDate => "Start date",
+            SortOrder::EndDate => "End date",
+            SortOrder::Duration => "Duration",
+            SortOrder::Alphabetical => "Alphabetical",
+            SortOrder::TagValue(_) => "Tag value",
+        }
+    }
+
+    /// Sort the entities (`today` is when entities without an end date last
+    /// until)
+    pub(crate) fn sort(&self, entities: &mut [WorkingEntity], today: Date) {
+        // Cannot use a `.partial_cmp()` and declare them equal if none because
+        // this doesn't result in a total ordering.  The sorts below are stable,
+        // so this is the order of ties.
+        entities.sort_by_key(|entity| entity.entity.start());
+        match self {
+            SortOrder::StartDate => (),
+            SortOrder::EndDate => {
+                entities.sort_by_key(|entity| (entity.entity.end().is_none(), entity.entity.end()))
+            }
+            SortOrder::Duration => entities.sort_by(|a, b| {
+                let a_years = years(a.entity.start(), a.entity.end().unwrap_or(today));
+                let b_years = years(b.entity.start(), b.entity.end().unwrap_or(today));
+                b_years.total_cmp(&a_years)
+            }),
+            SortOrder::Alphabetical => {
+                entities.sort_by_cached_key(|entity| entity.entity.name().as_str().to_lowercase())
+            }
+            SortOrder::TagValue(name) => entities.sort_by_cached_key(|entity| {
+                let value = entity.entity.tags().as_ref().and_then(|tags| {
+                    tags.iter()
+                        .filter(|tag| tag.name.as_ref() == Some(name))
+                        .map(|tag| tag.value.to_string())
+                        .min()
+                });
+                (value.is_none(), value)
+            }),
+        }
+    }
+}
+
+/// How many years (including fractions) there are from the start to the end
+fn years(start: Date, end: Date) -> f64 {
+    let as_years = |date: Date| {
+        let date = date.to_gregorian();
+        date.year().value() as f64 + date.fraction_of_year()
+    };
+    as_years(end) - as_years(start)
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! The order overlapping entities are drawn in
+//!
+
+use crate::EntityOut;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// The order entities are drawn in, and so which are on top where they overlap
+/// (see [`Engine::set_stacking_order()`]).  Entities drawn first are at the
+/// bottom.
+///
+/// [`Engine::set_stacking_order()`]: crate::Engine::set_stacking_order
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StackingOrder {
+    /// Draw entities in chronological order (later entities are on top)
+    #[default]
+    Chronological,
+
+    /// Draw the longest entities first (so shorter entities are on top)
+    LongestFirst,
+
+    /// Draw the newest entities first (so older entities are on top)
+    NewestFirst,
+}
+
+impl StackingOrder {
+    /// All stacking orders (useful for drawing a picker)
+    pub const ALL: [StackingOrder; 3] = [
+        StackingOrder::Chronological,
+        StackingOrder::LongestFirst,
+        StackingOrder::NewestFirst,
+    ];
+
+    /// The name of the stacking order
+    pub fn name(&self) -> &'static str {
+        match self {
+            StackingOrder::Chronological => "Chronological",
+            StackingOrder::LongestFirst => "Longest first",
+            StackingOrder::NewestFirst => "Newest first",
+        }
+    }
+
+    /// Sort entities (in the engine's sort order) into the order they are to
+    /// be drawn in
+    pub(crate) fn sort(&self, entities: &mut [EntityOut]) {
+        match self {
+            StackingOrder::Chronological => {
+                entities.sort_by(|a, b| a.entity.start().cmp(&b.entity.start()))
+            }
+            StackingOrder::LongestFirst => entities.sort_by(|a, b| {
+                let a_width = a.date_box.position_and_size.width;
+                let b_width = b.date_box.position_and_size.width;
+                b_width.total_cmp(&a_width)
+            }),
+            StackingOrder::NewestFirst => {
+                entities.sort_by(|a, b| b.entity.start().cmp(&a.entity.start()))
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Caching text measurements
+//!
+
+use crate::TEXT_MEASUREMENT_CACHE_CAPACITY;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// Memoises the `measure_text_fn`'s measurements (keyed by the text & font
+/// size), as measuring text can be expensive (e.g. on a canvas) and laying out
+/// the timeline re-measures every entity's name.
+///
+/// Only measurements at one font size are kept - they are dropped when text is
+/// measured at another size (e.g. after zooming), or when the text would be
+/// measured differently (e.g. the font changed).
+#[derive(Debug, Default)]
+pub(crate) struct TextMeasurements {
+    /// The font size the measurements are for
+    font_size_px: Cell<f64>,
+
+    /// The `(width, height)` of each text
+    measurements: RefCell<HashMap<String, (f64, f64)>>,
+}
+
+impl TextMeasurements {
+    /// Get the `(width, height)` of the text at the font size, measuring it
+    /// with `measure_text_fn` if it hasn't been already
+    pub fn measure<F>(&self, font_size_px: f64, text: &str, measure_text_fn: F) -> (f64, f64)
+    where
+        F: Fn(f64, String) -> (f64, f64),
+    {
+        let mut measurements = self.measurements.borrow_mut();
+        if self.font_size_px.get() != font_size_px {
+            self.font_size_px.set(font_size_px);
+            measurements.clear();
+        }
+        if let Some(measurement) = measurements.get(text) {
+            return *measurement;
+        }
+        if measurements.len() >= TEXT_MEASUREMENT_CACHE_CAPACITY {
+            measurements.clear();
+        }
+        let measurement = measure_text_fn(font_size_px, text.to_string());
+        measurements.insert(text.to_string(), measurement);
+        measurement
+    }
+
+    /// Drop all measurements (e.g. because the font changed)
+    pub fn clear(&mut self) {
+        self.measurements.get_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn measures_each_text_once_per_font_size() {
+        let calls = Cell::new(0);
+        let measure_text = |font_size: f64, text: String| {
+            calls.set(calls.get() + 1);
+            (font_size * text.len() as f64, font_size)
+        };
+        let mut measurements = TextMeasurements::default();
+
+        assert_eq!(
+            measurements.measure(10.0, "Ada", measure_text),
+            (30.0, 10.0)
+        );
+        assert_eq!(
+            measurements.measure(10.0, "Ada", measure_text),
+            (30.0, 10.0)
+        );
+        assert_eq!(calls.get(), 1);
+
+        // Another font size
+        assert_eq!(
+            measurements.measure(20.0, "Ada", measure_text),
+            (60.0, 20.0)
+        );
+        assert_eq!(calls.get(), 2);
+
+        // Cleared
+        measurements.clear();
+        measurements.measure(20.0, "Ada", measure_text);
+        assert_eq!(calls.get(), 3);
+    }
+}
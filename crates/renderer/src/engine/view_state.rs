@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! View state (what is in view, how it's filtered, and what's selected) that
+//! can be shared as a compact string (e.g. in a URL)
+//!
+
+use crate::VIEW_STATE_VERSION;
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use open_timeline_core::{Coordinates, Date, OpenTimelineId};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+/// Why a view state string couldn't be applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewStateError {
+    /// The string isn't a view state
+    Invalid,
+
+    /// The view state is from a different (newer) version of the format
+    Version,
+
+    /// The view state's tag filter isn't a valid boolean tag expression
+    TagFilter,
+}
+
+impl Display for ViewStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViewStateError::Invalid => write!(f, "Invalid view state"),
+            ViewStateError::Version => write!(f, "Unsupported view state version"),
+            ViewStateError::TagFilter => write!(f, "Invalid view state tag filter"),
+        }
+    }
+}
+
+impl std::error::Error for ViewStateError {}
+
+/// Everything needed to recreate a view of a timeline (see
+/// [`Engine::serialize_view_state()`]).  The field names are short to keep
+/// the serialised string short.
+///
+/// [`Engine::serialize_view_state()`]: crate::Engine::serialize_view_state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ViewState {
+    /// The version of the format
+    #[serde(rename = "v")]
+    pub version: u8,
+
+    /// The (fractional) year at the left edge of the canvas
+    #[serde(rename = "x")]
+    pub start_year: f64,
+
+    /// The timeline's global offset (y)
+    #[serde(rename = "y")]
+    pub offset_y: f64,
+
+    /// The zoom level
+    #[serde(rename = "z")]
+    pub zoom: f64,
+
+    /// The datetime scale factor
+    #[serde(rename = "d")]
+    pub datetime_scale: f64,
+
+    /// The boolean tag expression entities are filtered by
+    #[serde(rename = "t", default, skip_serializing_if = "Option::is_none")]
+    pub tag_filter: Option<String>,
+
+    /// The location (and radius in kilometres) entities are filtered by
+    #[serde(rename = "l", default, skip_serializing_if = "Option::is_none")]
+    pub location_filter: Option<(Coordinates, f64)>,
+
+    /// The earliest date shown
+    #[serde(rename = "s", default, skip_serializing_if = "Option::is_none")]
+    pub start_date_limit: Option<Date>,
+
+    /// The latest date shown
+    #[serde(rename = "e", default, skip_serializing_if = "Option::is_none")]
+    pub end_date_limit: Option<Date>,
+
+    /// The selected entities
+    #[serde(rename = "i", default, skip_serializing_if = "Vec::is_empty")]
+    pub selected: Vec<OpenTimelineId>,
+}
+
+impl ViewState {
+    /// Encode as a URL-safe string
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("view state is always serialisable");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Decode a string made by [`ViewState::encode()`]
+    pub fn decode(encoded: &str) -> Result<Self, ViewStateError> {
+        let json = URL_SAFE_NO_PAD
+            .decode(encoded.trim().trim_start_matches('#'))
+            .map_err(|_| ViewStateError::Invalid)?;
+        let view_state: ViewState =
+            serde_json::from_slice(&json).map_err(|_| ViewStateError::Invalid)?;
+        if view_state.version > VIEW_STATE_VERSION {
+            return Err(ViewStateError::Version);
+        }
+        Ok(view_state)
+    }
+}
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Viewport
+//!
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+/// The part of the timeline currently in view
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Viewport {
+    /// The timeline's global offset (x)
+    pub offset_x: f64,
+
+    /// The timeline's global offset (y)
+    pub offset_y: f64,
+
+    /// The zoom level
+    pub zoom: f64,
+
+    /// The datetime scale factor (stretch in x-direction)
+    pub datetime_scale: f64,
+
+    /// The width of the canvas
+    pub width: f64,
+
+    /// The height of the canvas
+    pub height: f64,
+
+    /// The (fractional) year at the left edge of the canvas
+    pub start_year: f64,
+
+    /// The (fractional) year at the right edge of the canvas
+    pub end_year: f64,
+}
+
+/// A position on the timeline that can be returned to later (see
+/// [`Engine::capture_viewport()`]).  Unlike a [`Viewport`], this doesn't
+/// depend on the size of the canvas.
+///
+/// [`Engine::capture_viewport()`]: crate::Engine::capture_viewport
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CapturedViewport {
+    /// The (fractional) year at the left edge of the canvas
+    pub start_year: f64,
+
+    /// The timeline's global offset (y)
+    pub offset_y: f64,
+
+    /// The zoom level
+    pub zoom: f64,
+
+    /// The datetime scale factor (stretch in x-direction)
+    pub datetime_scale: f64,
+}
+
+/// Reports changes to the viewport once it has stopped changing for a while
+/// (so that listeners aren't called on every frame of a pan or zoom)
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ViewportChangeDebouncer {
+    /// The viewport when it was last checked
+    last_seen: Option<Viewport>,
+
+    /// How long (ms) the viewport has been unchanged for, if there is a change
+    /// waiting to be reported
+    quiet_ms: Option<f64>,
+}
+
+impl ViewportChangeDebouncer {
+    /// Check the viewport (`ms_elapsed` after the last check).  Returns the
+    /// viewport if it changed and has since been unchanged for `debounce_ms`.
+    /// The first viewport checked is not a change.
+    pub fn update(
+        &mut self,
+        viewport: Viewport,
+        ms_elapsed: f64,
+        debounce_ms: f64,
+    ) -> Option<Viewport> {
+        match self.last_seen.replace(viewport) {
+            None => None,
+            Some(last_seen) if last_seen != viewport => {
+                self.quiet_ms = Some(0.0);
+                None
+            }
+            Some(_) => {
+                let quiet_ms = self.quiet_ms? + ms_elapsed;
+                if quiet_ms >= debounce_ms {
+                    self.quiet_ms = None;
+                    Some(viewport)
+                } else {
+                    self.quiet_ms = Some(quiet_ms);
+                    None
+                }
+            }
+        }
+    }
+
+    /// Whether there is a change waiting to be reported
+    pub fn is_pending(&self) -> bool {
+        self.quiet_ms.is_some()
+    }
+}
+
+/// Moving smoothly from one view to another (e.g. between the steps of a
+/// story)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ViewportAnimation {
+    /// Where the animation started
+    from: CapturedViewport,
+
+    /// Where the animation ends
+    to: CapturedViewport,
+
+    /// How long (ms) the animation has been running
+    elapsed_ms: f64,
+
+    /// How long (ms) the animation takes
+    duration_ms: f64,
+}
+
+impl ViewportAnimation {
+    /// Create a new animation between the views
+    pub fn from(from: CapturedViewport, to: CapturedViewport, duration_ms: f64) -> Self {
+        Self {
+            from,
+            to,
+            elapsed_ms: 0.0,
+            duration_ms,
+        }
+    }
+
+    /// Advance the animation by the elapsed time.  Returns the view to show,
+    /// and whether the animation has finished.
+    pub fn step(&mut self, ms_elapsed: f64) -> (CapturedViewport, bool) {
+        self.elapsed_ms += ms_elapsed;
+        if self.elapsed_ms >= self.duration_ms {
+            return (self.to, true);
+        }
+
+        // Ease in and out
+        let t = self.elapsed_ms / self.duration_ms;
+        let eased = t * t * (3.0 - (2.0 * t));
+        let lerp = |from: f64, to: f64| from + ((to - from) * eased);
+        let viewport = CapturedViewport {
+            start_year: lerp(self.from.start_year, self.to.start_year),
+            offset_y: lerp(self.from.offset_y, self.to.offset_y),
+            // Geometrically, so that zooming in looks like zooming out
+            zoom: self.from.zoom * (self.to.zoom / self.from.zoom).powf(eased),
+            datetime_scale: lerp(self.from.datetime_scale, self.to.datetime_scale),
+        };
+        (viewport, false)
+    }
+}
@@ -4,5 +4,10 @@
 //! Frontends for the rendering engine.
 //!
 
+mod canvas_2d;
 pub mod desktop_egui;
+pub mod egui;
 pub mod html_canvas;
+pub mod offscreen_canvas;
+pub mod renderer_frontend;
+pub mod svg;
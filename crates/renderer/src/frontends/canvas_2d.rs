@@ -0,0 +1,244 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Drawing a timeline onto a 2D canvas context.
+//!
+//! Shared by the HTML canvas frontend (main thread) and the offscreen canvas
+//! frontend (web worker)
+//!
+
+use crate::frontends::renderer_frontend::{self, RendererFrontend};
+use crate::{
+    Background, Colour, Engine, EntityOut, FilledBox, FontConfig, Heading, TextOut, VerticalLine,
+};
+use open_timeline_core::HasIdAndName;
+use std::cell::RefCell;
+use web_sys::{CanvasRenderingContext2d, OffscreenCanvasRenderingContext2d, TextMetrics};
+
+thread_local! {
+    /// The font used to measure and draw text (kept the same as the engine's)
+    pub(crate) static FONT: RefCell<FontConfig> = RefCell::new(FontConfig::default());
+}
+
+/// The parts of a 2D canvas context's API used to draw a timeline (i.e. the
+/// common ground between `CanvasRenderingContext2d` and
+/// `OffscreenCanvasRenderingContext2d`)
+pub(crate) trait Canvas2d {
+    fn set_font(&self, font: &str);
+    fn measure_text(&self, text: &str) -> Option<TextMetrics>;
+    fn set_fill_style_str(&self, style: &str);
+    fn set_stroke_style_str(&self, style: &str);
+    fn set_line_width(&self, width: f64);
+    fn set_global_alpha(&self, alpha: f64);
+    fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn fill_text(&self, text: &str, x: f64, y: f64);
+    fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64);
+    fn begin_path(&self);
+    fn move_to(&self, x: f64, y: f64);
+    fn line_to(&self, x: f64, y: f64);
+    fn stroke(&self);
+}
+
+/// Implement [`Canvas2d`] by forwarding to the context's own methods
+macro_rules! impl_canvas_2d {
+    ($context:ty) => {
+        impl Canvas2d for $context {
+            fn set_font(&self, font: &str) {
+                <$context>::set_font(self, font)
+            }
+            fn measure_text(&self, text: &str) -> Option<TextMetrics> {
+                <$context>::measure_text(self, text).ok()
+            }
+            fn set_fill_style_str(&self, style: &str) {
+                <$context>::set_fill_style_str(self, style)
+            }
+            fn set_stroke_style_str(&self, style: &str) {
+                <$context>::set_stroke_style_str(self, style)
+            }
+            fn set_line_width(&self, width: f64) {
+                <$context>::set_line_width(self, width)
+            }
+            fn set_global_alpha(&self, alpha: f64) {
+                <$context>::set_global_alpha(self, alpha)
+            }
+            fn fill_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+                <$context>::fill_rect(self, x, y, width, height)
+            }
+            fn fill_text(&self, text: &str, x: f64, y: f64) {
+                let _ = <$context>::fill_text(self, text, x, y);
+            }
+            fn clear_rect(&self, x: f64, y: f64, width: f64, height: f64) {
+                <$context>::clear_rect(self, x, y, width, height)
+            }
+            fn begin_path(&self) {
+                <$context>::begin_path(self)
+            }
+            fn move_to(&self, x: f64, y: f64) {
+                <$context>::move_to(self, x, y)
+            }
+            fn line_to(&self, x: f64, y: f64) {
+                <$context>::line_to(self, x, y)
+            }
+            fn stroke(&self) {
+                <$context>::stroke(self)
+            }
+        }
+    };
+}
+
+impl_canvas_2d!(CanvasRenderingContext2d);
+impl_canvas_2d!(OffscreenCanvasRenderingContext2d);
+
+/// Set the context's font (using the current font)
+pub(crate) fn set_font<C: Canvas2d>(ctx: &C, font_size_px: f64) {
+    FONT.with_borrow(|font| ctx.set_font(&font.css_font(font_size_px)));
+}
+
+/// Use the font to measure and draw text, and tell the engine (which
+/// re-measures everything)
+pub(crate) fn use_font(engine: &mut Engine, font: FontConfig) {
+    FONT.with_borrow_mut(|current| *current = font.clone());
+    engine.set_font(font);
+}
+
+/// Measure the text as the [`Engine`] expects (i.e. `(width, height)`)
+pub(crate) fn measure_text_for_engine<C: Canvas2d>(
+    ctx: &C,
+    font_size_px: f64,
+    text: &str,
+) -> (f64, f64) {
+    set_font(ctx, font_size_px);
+    match ctx.measure_text(text) {
+        Some(measurements) => (
+            measurements.width(),
+            measurements.actual_bounding_box_ascent() + measurements.actual_bounding_box_descent(),
+        ),
+        None => (0.0, 0.0),
+    }
+}
+
+/// Draws onto the visible context, and each entity in its hidden colour onto
+/// the invisible context (used to find what's under the pointer)
+struct Canvas2dFrontend<'a, C: Canvas2d> {
+    visible: &'a C,
+    invisible: &'a C,
+
+    /// The font size in canvas pixels
+    font_size_px: f64,
+}
+
+impl<C: Canvas2d> RendererFrontend for Canvas2dFrontend<'_, C> {
+    fn clear(&mut self, width: f64, height: f64) {
+        self.visible.clear_rect(0.0, 0.0, width, height);
+        self.invisible.clear_rect(0.0, 0.0, width, height);
+    }
+
+    fn draw_background(&mut self, background: &Background, height: f64) {
+        self.visible
+            .set_fill_style_str(&css_colour(background.colour));
+        self.visible
+            .fill_rect(background.x, 0.0, background.width, height);
+    }
+
+    fn draw_line(&mut self, line: &VerticalLine, height: f64) {
+        self.visible.begin_path();
+        self.visible.move_to(line.x, 0.0);
+        self.visible.line_to(line.x, height);
+        self.visible
+            .set_stroke_style_str(&css_colour(line.style.colour));
+        self.visible.set_line_width(line.style.thickness);
+        self.visible.stroke();
+    }
+
+    fn draw_rect(&mut self, rect: &FilledBox) {
+        draw_coloured_rect(self.visible, rect);
+    }
+
+    fn draw_text(&mut self, text: &TextOut) {
+        let ctx = self.visible;
+        set_font(ctx, self.font_size_px);
+        ctx.set_fill_style_str(&css_colour(text.colour));
+        for (i, line) in text.lines.iter().enumerate() {
+            let ascent = ctx
+                .measure_text(line)
+                .map(|measurements| measurements.actual_bounding_box_ascent())
+                .unwrap_or_default();
+            let y = text.top_left.y + (i as f64 * text.line_height);
+            ctx.fill_text(line, text.top_left.x, y + ascent);
+        }
+    }
+
+    fn entity_drawn(&mut self, entity: &EntityOut) {
+        // Always solid, so the hidden colour is exact
+        let hidden_colour = Colour::from_any_string(entity.entity.name().as_str());
+        for mut rect in [entity.text_box, entity.date_box] {
+            rect.fill_colour = hidden_colour;
+            rect.fill_alpha = 1.0;
+            rect.border_style = None;
+            draw_coloured_rect(self.invisible, &rect);
+        }
+    }
+
+    fn heading_drawn(&mut self, heading: &Heading) {
+        let mut rect = heading.text_box;
+        rect.fill_colour = Colour::from_rgb(0, 0, 0);
+        rect.border_style = None;
+        draw_coloured_rect(self.invisible, &rect);
+    }
+}
+
+/// Draw the timeline onto the visible context, and each entity in its hidden
+/// colour onto the invisible context (used to find what's under the pointer).
+///
+/// `font_scale` converts the engine's font sizes to canvas pixels (i.e. the
+/// device pixel ratio)
+pub(crate) fn draw_timeline<C: Canvas2d>(
+    engine: &mut Engine,
+    visible: &C,
+    invisible: &C,
+    width: f64,
+    height: f64,
+    font_scale: f64,
+) {
+    let mut frontend = Canvas2dFrontend {
+        visible,
+        invisible,
+        font_size_px: engine.effective_font_size_px() * font_scale,
+    };
+    renderer_frontend::draw_timeline(engine, &mut frontend, width, height);
+}
+
+fn draw_coloured_rect<C: Canvas2d>(ctx: &C, rect: &FilledBox) {
+    ctx.set_fill_style_str(&css_colour(rect.fill_colour));
+    ctx.set_global_alpha(rect.fill_alpha);
+    let x = rect.position_and_size.position.x;
+    let y = rect.position_and_size.position.y;
+    let width = rect.position_and_size.width;
+    let height = rect.position_and_size.height;
+    ctx.fill_rect(x, y, width, height);
+    ctx.set_global_alpha(1.0);
+
+    // The border is drawn inside the box (as `egui` does)
+    if let Some(border) = rect.border_style
+        && border.thickness > 0.0
+    {
+        let inset = border.thickness / 2.0;
+        let (min_x, min_y) = (x + inset, y + inset);
+        let (max_x, max_y) = (x + width - inset, y + height - inset);
+        ctx.set_stroke_style_str(&css_colour(border.colour));
+        ctx.set_line_width(border.thickness);
+        ctx.begin_path();
+        ctx.move_to(min_x, min_y);
+        ctx.line_to(max_x, min_y);
+        ctx.line_to(max_x, max_y);
+        ctx.line_to(min_x, max_y);
+        ctx.line_to(min_x, min_y);
+        ctx.stroke();
+    }
+}
+
+/// The colour as a CSS colour string
+fn css_colour(colour: Colour) -> String {
+    let (r, g, b) = colour.as_rgb();
+    format!("rgba({r}, {g}, {b}, 1.0)")
+}
@@ -8,18 +8,36 @@
 // on `Engine` is exposed in the same way for the front end - can we even reuse
 // the doc comment?
 
-use crate::{Colour, Engine, PositionAndSize, TimelineColours, TimelineInteractionEvent};
+use crate::frontends::renderer_frontend::{self, RendererFrontend};
+use crate::{
+    Background, CapturedViewport, Colour, Engine, EntityOut, FilledBox, FontConfig, LayoutStrategy,
+    PositionAndSize, ScalableLayoutParams, SelectionHighlight, SortOrder, StackingOrder, TextOut,
+    TimelineColours, TimelineInteractionEvent, VerticalLine, ViewStateError, Viewport,
+    is_generic_font_family,
+};
 use bool_tag_expr::BoolTagExpr;
 use eframe::egui::{
-    Align2, Color32, Context, FontId, Id, Pos2, Rect, Sense, Stroke, StrokeKind, Ui, Vec2,
+    Align2, Color32, Context, CursorIcon, FontFamily, FontId, Key, Painter, Pos2, Rect, Response,
+    Sense, Stroke, StrokeKind, Ui, Vec2, WidgetInfo, WidgetType,
+};
+use open_timeline_core::{
+    Coordinates, Date, DateFormat, Entity, HasIdAndName, Name, OpenTimelineId, StoryStep,
+    TagHierarchy,
 };
-use log::{debug, info};
-use open_timeline_core::{Date, Entity, HasIdAndName};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info};
 
 /// The HTML canvas engine for use on the web
 pub struct OpenTimelineRendererEgui {
     /// The underlying timeline [`Engine`].
     engine: Engine,
+
+    /// Where the timeline was last drawn (if it has been drawn)
+    canvas_rect: Option<Rect>,
+
+    /// The `egui` font family text is measured and drawn with (shared with
+    /// the engine's text measuring function)
+    font_family: Arc<Mutex<FontFamily>>,
 }
 
 impl OpenTimelineRendererEgui {
@@ -27,15 +45,38 @@ impl OpenTimelineRendererEgui {
     pub fn new(ctx: &Context) -> Self {
         info!("Constructing a new EguiRenderer in Rust");
         let ctx_clone = ctx.clone();
+        let font_family = Arc::new(Mutex::new(FontFamily::Proportional));
+        let measuring_font_family = Arc::clone(&font_family);
 
         // TODO: do this with a method?
-        let text_measurer =
-            move |font_size, text| measure_text_fn(ctx_clone.clone(), font_size, text);
+        let text_measurer = move |font_size, text| {
+            let font_family = measuring_font_family.lock().unwrap().clone();
+            measure_text_fn(ctx_clone.clone(), font_family, font_size, text)
+        };
         Self {
             engine: Engine::new(text_measurer),
+            canvas_rect: None,
+            font_family,
         }
     }
 
+    /// Get the font text is measured and drawn with
+    pub fn font(&self) -> &FontConfig {
+        self.engine.font()
+    }
+
+    /// Set the font text is measured and drawn with.  The first of its
+    /// families that `egui` has fonts for is used (generic families such as
+    /// `serif` use `egui`'s own fonts).  `egui` fonts have a single weight, so
+    /// the weight is ignored.
+    pub fn set_font(&mut self, ctx: &Context, font: FontConfig) {
+        if *self.engine.font() == font {
+            return;
+        }
+        *self.font_family.lock().unwrap() = egui_font_family(ctx, &font);
+        self.engine.set_font(font);
+    }
+
     pub fn clear_entities(&mut self, ctx: &Context, ui: &mut Ui) {
         self.engine.clear_entities();
 
@@ -47,6 +88,16 @@ impl OpenTimelineRendererEgui {
         self.engine.set_entities(entities);
     }
 
+    pub fn sync_entities(&mut self, entities: Vec<Entity>) {
+        self.engine.sync_entities(entities);
+    }
+
+    /// Replace the entities with those in the JSON array (e.g. the contents of
+    /// a file of entities to preview, which needn't have IDs)
+    pub fn load_from_json(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        self.engine.load_from_json(json)
+    }
+
     pub fn add_entities(&mut self, entities: Vec<Entity>) {
         // debug!("add_entities");
 
@@ -60,6 +111,46 @@ impl OpenTimelineRendererEgui {
         // debug!("redrawn with new font size");
     }
 
+    pub fn set_min_font_size_px(&mut self, min_font_size_px: f64) {
+        self.engine.set_min_font_size_px(min_font_size_px);
+    }
+
+    pub fn set_layout_params(&mut self, layout_params: ScalableLayoutParams) {
+        self.engine.set_layout_params(layout_params);
+    }
+
+    pub fn set_max_text_width(&mut self, max_text_width: Option<f64>) {
+        self.engine.set_max_text_width(max_text_width);
+    }
+
+    pub fn set_layout_strategy(&mut self, layout_strategy: LayoutStrategy) {
+        self.engine.set_layout_strategy(layout_strategy);
+    }
+
+    pub fn set_sort(&mut self, sort_order: SortOrder) {
+        self.engine.set_sort(sort_order);
+    }
+
+    pub fn set_stacking_order(&mut self, stacking_order: StackingOrder) {
+        self.engine.set_stacking_order(stacking_order);
+    }
+
+    pub fn selection_highlight(&self) -> SelectionHighlight {
+        self.engine.selection_highlight()
+    }
+
+    pub fn set_selection_highlight(&mut self, selection_highlight: SelectionHighlight) {
+        self.engine.set_selection_highlight(selection_highlight);
+    }
+
+    pub fn set_date_box_alpha(&mut self, alpha: f64) {
+        self.engine.set_date_box_alpha(alpha);
+    }
+
+    pub fn visible_entities_text_description(&self) -> Vec<String> {
+        self.engine.visible_entities_text_description()
+    }
+
     pub fn start_and_end_dates(&mut self) -> (i32, i32) {
         self.engine.start_and_end_dates()
     }
@@ -80,14 +171,112 @@ impl OpenTimelineRendererEgui {
         self.engine.remove_tag_bool_expr_entity_filter();
     }
 
+    pub fn set_tag_hierarchy(&mut self, tag_hierarchy: TagHierarchy) {
+        self.engine.set_tag_hierarchy(tag_hierarchy);
+    }
+
+    pub fn set_location_entity_filter(&mut self, coordinates: Coordinates, radius_km: f64) {
+        self.engine
+            .set_location_entity_filter(coordinates, radius_km);
+    }
+
+    pub fn remove_location_entity_filter(&mut self) {
+        self.engine.remove_location_entity_filter();
+    }
+
     pub fn set_date_limits(&mut self, start: Option<Date>, end: Option<Date>) {
         self.engine.set_date_limits(start, end);
     }
 
+    /// Set how long (ms) the viewport must be unchanged before a
+    /// [`TimelineInteractionEvent::ViewportChanged`] event is emitted
+    pub fn set_viewport_changed_debounce_ms(&mut self, debounce_ms: f64) {
+        self.engine.set_viewport_changed_debounce_ms(debounce_ms);
+    }
+
     pub fn drain_interaction_events(&mut self) -> std::vec::Drain<'_, TimelineInteractionEvent> {
         self.engine.drain_interaction_events()
     }
 
+    /// Describe the entity in plain text (if the timeline has it)
+    pub fn entity_description(&self, entity_id: OpenTimelineId) -> Option<String> {
+        self.engine
+            .entity(entity_id)
+            .map(|entity| self.engine.entity_description(entity))
+    }
+
+    /// Get the name of the entity (if the timeline has it)
+    pub fn entity_name(&self, entity_id: OpenTimelineId) -> Option<&Name> {
+        self.engine.entity(entity_id).map(|entity| entity.name())
+    }
+
+    /// Get the IDs of the (visible) entities in chronological order
+    pub fn ids_of_visible_entities_in_date_order(&self) -> Vec<OpenTimelineId> {
+        self.engine.ids_of_visible_entities_in_date_order()
+    }
+
+    /// Set the story that can be played
+    pub fn set_story(&mut self, steps: Vec<StoryStep>) {
+        self.engine.set_story(steps);
+    }
+
+    /// Get the story that can be played
+    pub fn story(&self) -> &[StoryStep] {
+        self.engine.story()
+    }
+
+    /// Get the index of the step of the story being shown (if it is being
+    /// played)
+    pub fn story_step(&self) -> Option<usize> {
+        self.engine.story_step()
+    }
+
+    /// Show a step of the story
+    pub fn go_to_story_step(&mut self, step: usize) {
+        self.engine.go_to_story_step(step);
+    }
+
+    /// Show the next step of the story (starting it if it isn't being played)
+    pub fn next_story_step(&mut self) {
+        self.engine.next_story_step();
+    }
+
+    /// Show the previous step of the story
+    pub fn previous_story_step(&mut self) {
+        self.engine.previous_story_step();
+    }
+
+    /// Stop playing the story
+    pub fn stop_story(&mut self) {
+        self.engine.stop_story();
+    }
+
+    /// Turn edit mode (dragging the edges of entities' date boxes to change
+    /// their dates) on/off
+    pub fn set_edit_mode(&mut self, edit_mode: bool) {
+        self.engine.set_edit_mode(edit_mode);
+    }
+
+    /// Whether edit mode is on
+    pub fn edit_mode(&self) -> bool {
+        self.engine.edit_mode()
+    }
+
+    /// Change the dates of an entity on the timeline (e.g. to undo dragging).
+    /// Returns whether the dates are valid.
+    pub fn set_entity_dates(
+        &mut self,
+        entity_id: OpenTimelineId,
+        start: Date,
+        end: Option<Date>,
+    ) -> bool {
+        self.engine.set_entity_dates(entity_id, start, end)
+    }
+
+    pub fn contains_entity(&self, entity_id: OpenTimelineId) -> bool {
+        self.engine.entity(entity_id).is_some()
+    }
+
     pub fn entity_count(&mut self) -> usize {
         self.engine.entity_count()
     }
@@ -96,15 +285,123 @@ impl OpenTimelineRendererEgui {
         self.engine.set_sticky_text(sticky_text)
     }
 
+    pub fn set_date_format(&mut self, date_format: DateFormat) {
+        self.engine.set_date_format(date_format)
+    }
+
+    pub fn set_show_now_line(&mut self, show_now_line: bool) {
+        self.engine.set_show_now_line(show_now_line)
+    }
+
+    pub fn set_relative_time(&mut self, relative_time: bool) {
+        self.engine.set_relative_time(relative_time)
+    }
+
     pub fn set_datetime_scale(&mut self, scale: f64) {
         self.engine.set_datetime_scale(scale)
     }
 
+    pub fn datetime_scale_auto(&self) -> bool {
+        self.engine.datetime_scale_auto()
+    }
+
+    pub fn set_datetime_scale_auto(&mut self, auto: bool) {
+        self.engine.set_datetime_scale_auto(auto)
+    }
+
+    pub fn ids_of_selected_entities(&self) -> &Vec<OpenTimelineId> {
+        self.engine.ids_of_selected_entities()
+    }
+
+    pub fn clear_ids_of_selected_entities(&mut self) {
+        self.engine.clear_ids_of_selected_entities();
+    }
+
+    pub fn set_ids_of_selected_entities(&mut self, entity_ids: Vec<OpenTimelineId>) {
+        self.engine.set_ids_of_selected_entities(entity_ids);
+    }
+
+    pub fn select_entities(&mut self, entity_ids: Vec<OpenTimelineId>) {
+        self.engine.select_entities(entity_ids);
+    }
+
+    pub fn focus_on_selection(&mut self, hide_others: bool) {
+        self.engine.focus_on_selection(hide_others);
+    }
+
+    pub fn exit_focus(&mut self) {
+        self.engine.exit_focus();
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.engine.is_focused()
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        self.engine.viewport()
+    }
+
+    pub fn set_shared_axis_years(&mut self, years: Option<(i32, i32)>) {
+        self.engine.set_shared_axis_years(years);
+    }
+
+    pub fn sync_x_axis(&mut self, viewport: &Viewport) {
+        self.engine.sync_x_axis(viewport);
+    }
+
+    /// Serialise the view (position, filters & selection) as a compact,
+    /// URL-safe string
+    pub fn serialize_view_state(&self) -> String {
+        self.engine.serialize_view_state()
+    }
+
+    /// Recreate a view serialised with [`Self::serialize_view_state()`]
+    pub fn apply_view_state(&mut self, view_state: &str) -> Result<(), ViewStateError> {
+        self.engine.apply_view_state(view_state)
+    }
+
+    pub fn capture_viewport(&self) -> CapturedViewport {
+        self.engine.capture_viewport()
+    }
+
+    pub fn restore_viewport(&mut self, captured: &CapturedViewport) {
+        self.engine.restore_viewport(captured);
+    }
+
     pub fn draw(&mut self, ctx: &Context, ui: &mut Ui) {
-        draw_timeline(ctx, ui, &mut self.engine);
+        self.draw_with_size(ctx, ui, ui.available_size());
         // debug!("[exit] .draw()");
     }
 
+    /// Draw the timeline at the given size (rather than filling the available
+    /// space), returning the response of the timeline's canvas
+    pub fn draw_with_size(&mut self, ctx: &Context, ui: &mut Ui, size: Vec2) -> Response {
+        // Advance any animation (and keep drawing until it has finished)
+        let ms_elapsed = ui.input(|input| input.unstable_dt) as f64 * 1000.0;
+        if self.engine.animate(ms_elapsed) {
+            ctx.request_repaint();
+        }
+
+        let font_family = self.font_family.lock().unwrap().clone();
+        let response = draw_timeline(ctx, ui, &mut self.engine, &font_family, size);
+        self.canvas_rect = Some(response.rect);
+        response
+    }
+
+    /// Where the timeline was last drawn (if it has been drawn)
+    pub fn canvas_rect(&self) -> Option<Rect> {
+        self.canvas_rect
+    }
+
+    /// Whether a point on the screen is somewhere an entity can be dropped
+    /// onto the timeline (see [`Engine::is_drop_target()`])
+    pub fn is_drop_target(&self, pos: Pos2) -> bool {
+        self.canvas_rect.is_some_and(|canvas_rect| {
+            let local = pos - canvas_rect.min;
+            self.engine.is_drop_target(local.x.into(), local.y.into())
+        })
+    }
+
     pub fn colours(&mut self) -> TimelineColours {
         self.engine.colours()
     }
@@ -121,145 +418,165 @@ impl OpenTimelineRendererEgui {
 
 /// Function supplied to the [`Engine`] so that it can measure text (used in its
 /// calculations)
-fn measure_text_fn(ctx: Context, font_size: f64, text: String) -> (f64, f64) {
+fn measure_text_fn(
+    ctx: Context,
+    font_family: FontFamily,
+    font_size: f64,
+    text: String,
+) -> (f64, f64) {
     let text_galley = ctx.fonts_mut(|f| {
-        f.layout_no_wrap(text, FontId::proportional(font_size as f32), Color32::BLACK)
+        f.layout_no_wrap(
+            text,
+            FontId::new(font_size as f32, font_family),
+            Color32::BLACK,
+        )
     });
     let text_width: f64 = text_galley.rect.width().into();
     let text_height: f64 = text_galley.rect.height().into();
     (text_width, text_height)
 }
 
-/// Draw the timeline in an `egui` application
-fn draw_timeline(_ctx: &Context, ui: &mut Ui, engine: &mut Engine) {
-    let width = ui.available_width();
-    let height = ui.available_height();
-    let (painter_response, painter) = ui.allocate_painter(Vec2::new(width, height), Sense::drag());
-
-    // Move the timeline if the user is dragging it
-    if painter_response.dragged() {
-        let delta = painter_response.drag_motion();
-        engine.add_to_global_offset(delta.x.into(), delta.y.into());
+/// The first of the font's families that `egui` has fonts for (generic
+/// families use `egui`'s built in proportional & monospace fonts)
+fn egui_font_family(ctx: &Context, font: &FontConfig) -> FontFamily {
+    for family in font.fallback_chain() {
+        if family.eq_ignore_ascii_case("monospace") {
+            return FontFamily::Monospace;
+        }
+        if is_generic_font_family(family) {
+            return FontFamily::Proportional;
+        }
+        let named = FontFamily::Name(family.into());
+        if ctx.fonts(|f| f.definitions().families.contains_key(&named)) {
+            return named;
+        }
     }
+    FontFamily::Proportional
+}
+
+/// Draw the timeline in an `egui` application, returning the response of the
+/// canvas it was drawn on
+fn draw_timeline(
+    _ctx: &Context,
+    ui: &mut Ui,
+    engine: &mut Engine,
+    font_family: &FontFamily,
+    size: Vec2,
+) -> Response {
+    let (painter_response, painter) = ui.allocate_painter(size, Sense::click_and_drag());
 
     let canvas_rect = painter_response.rect;
     let canvas_min = canvas_rect.min.to_vec2();
-    let canvas_max = canvas_rect.max.to_vec2();
-    let canvas_size = canvas_max - canvas_min;
-    engine.set_canvas_max(canvas_size.x.into(), canvas_size.y.into());
 
-    // Draw background stripes
-    for background in engine.backgrounds_for_drawing() {
-        let (r, g, b) = background.colour.as_rgb();
-        let min = Pos2::new(background.x as f32, 0.0);
-        let max = Pos2::new(background.x as f32 + background.width as f32, f32::MAX);
-        let rect = Rect::from_two_pos(min + canvas_min, max + canvas_min);
-        painter.rect(
-            rect,
-            0.0,
-            Color32::from_rgb(r, g, b),
-            Stroke::NONE,
-            StrokeKind::Inside,
-        );
+    // In edit mode, dragging the edge of an entity's date box changes its date
+    if engine.edit_mode() {
+        let edge_at = |engine: &Engine, pos: Pos2| {
+            engine.date_box_edge_at((pos.x - canvas_min.x).into(), (pos.y - canvas_min.y).into())
+        };
+        if painter_response.drag_started()
+            && let Some(origin) = ui.input(|i| i.pointer.press_origin())
+            && let Some((entity_id, edge)) = edge_at(engine, origin)
+        {
+            engine.start_date_drag(entity_id, edge);
+        }
+        let over_edge = painter_response
+            .hover_pos()
+            .is_some_and(|pos| edge_at(engine, pos).is_some());
+        if engine.is_dragging_date() || over_edge {
+            ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+        }
     }
 
-    // Draw lines
-    let clip = painter.clip_rect();
-    let top_y = clip.top();
-    let bottom_y = clip.bottom();
-    for line in engine.lines_for_drawing() {
-        painter.vline(
-            canvas_min.x + line.x as f32,
-            top_y..=bottom_y,
-            Stroke::new(
-                line.style.thickness as f32,
-                timeline_renderer_colour_to_egui_colour(line.style.colour),
-            ),
-        );
+    // Move the dragged date box edge, or the timeline if the user is dragging
+    // it
+    if painter_response.dragged() {
+        if engine.is_dragging_date() {
+            if let Some(pos) = painter_response.interact_pointer_pos() {
+                engine.update_date_drag((pos.x - canvas_min.x).into());
+            }
+        } else {
+            let delta = painter_response.drag_motion();
+            engine.add_to_global_offset(delta.x.into(), delta.y.into());
+        }
+    }
+    if painter_response.drag_stopped() {
+        engine.end_date_drag();
     }
 
-    let mut hovering_over_entities = false;
+    let canvas_max = canvas_rect.max.to_vec2();
+    let canvas_size = canvas_max - canvas_min;
+    engine.set_canvas_max(canvas_size.x.into(), canvas_size.y.into());
 
-    // TODO: can still click & hover over entities under the headings (fix in engine)
-    // Draw entities
-    for entity in engine.entities_for_drawing() {
-        // Draw text box
-        let text_box = &entity.text_box;
-        let (min, max) = timeline_renderer_position_and_size_to_min_and_max_egui_pos2(
-            &text_box.position_and_size,
-        );
-        let text_box_rect = Rect::from_two_pos(min + canvas_min, max + canvas_min);
-        let (thickness, colour) = {
-            if let Some(border_style) = text_box.border_style {
-                (border_style.thickness, border_style.colour)
-            } else {
-                (0.0, Colour::from_rgb(0, 0, 0))
-            }
-        };
-        painter.rect(
-            text_box_rect,
-            0.0,
-            timeline_renderer_colour_to_egui_colour(text_box.fill_colour),
-            Stroke::new(thickness as f32, colour),
-            StrokeKind::Inside,
-        );
+    // Draw
+    let mut frontend = EguiFrontend {
+        painter: &painter,
+        canvas_rect,
+        font_family,
+        entities: Vec::new(),
+    };
+    renderer_frontend::draw_timeline(
+        engine,
+        &mut frontend,
+        canvas_size.x.into(),
+        canvas_size.y.into(),
+    );
+    let drawn_entities = frontend.entities;
 
-        // Draw date box
-        let date_box = &entity.date_box;
-        let (min, max) = timeline_renderer_position_and_size_to_min_and_max_egui_pos2(
-            &date_box.position_and_size,
-        );
-        let date_box_rect = Rect::from_two_pos(min + canvas_min, max + canvas_min);
-        let (thickness, colour) = {
-            if let Some(border_style) = date_box.border_style {
-                (border_style.thickness, border_style.colour)
-            } else {
-                (0.0, Colour::from_rgb(0, 0, 0))
-            }
-        };
-        painter.rect(
-            date_box_rect,
-            0.0,
-            timeline_renderer_colour_to_egui_colour(date_box.fill_colour),
-            Stroke::new(thickness as f32, colour),
-            StrokeKind::Inside,
-        );
+    let mut hovering_over_entities = false;
 
+    // TODO: can still click & hover over entities under the headings (fix in engine)
+    // Hovering over & clicking on entities
+    for (entity_id, bounding_rect) in drawn_entities {
         // Don't sense clicking on things outside the canvas.  Without the
         // `.intersect()` with the canvas rect, one could move the timeline and
         // then click on one of the control buttons, only to have a timeline
         // entity view window to pop open
-        let bounding_rect = date_box_rect.union(text_box_rect);
         let visible_rect = painter_response.rect.intersect(bounding_rect);
         let entity_response = ui.interact(
             visible_rect,
-            Id::from(entity.entity.id().unwrap().to_string()),
+            painter_response.id.with(entity_id.to_string()),
             Sense::click(),
         );
 
+        // Label the entity for screen readers, and describe it when hovered
+        // over
+        let description = engine
+            .entity(entity_id)
+            .map(|entity| engine.entity_description(entity))
+            .unwrap_or_default();
+        entity_response
+            .widget_info(|| WidgetInfo::labeled(WidgetType::Button, true, description.clone()));
+        let entity_response = entity_response.on_hover_text(description);
+
         // Hover over entity
         if entity_response.hovered() {
             hovering_over_entities = true;
-            engine.hover_over_entity(Some(entity.entity.id().unwrap()));
+            engine.hover_over_entity(Some(entity_id));
         }
 
-        // Click on entity
+        // Click on entity (holding command/ctrl or shift toggles whether it's
+        // selected instead)
         if entity_response.clicked() {
-            if let Some(entity_id) = entity.entity.id() {
+            let toggle_selection = ui.input(|i| i.modifiers.command || i.modifiers.shift);
+            if !toggle_selection {
                 engine.click_on_entity(entity_id);
+            } else if engine.ids_of_selected_entities().contains(&entity_id) {
+                engine.remove_id_from_selected_entities_list(entity_id);
+            } else {
+                engine.add_id_of_selected_entity(entity_id);
             }
         }
 
-        // Write text
-        let text = &entity.text;
-        let pos = Pos2::new(text.top_left.x as f32, text.top_left.y as f32);
-        painter.text(
-            pos + canvas_min,
-            Align2::LEFT_TOP,
-            &text.text,
-            FontId::proportional(text.font_size as f32),
-            timeline_renderer_colour_to_egui_colour(text.colour),
-        );
+        // Right-click (or long-press on touch screens) for a context menu
+        if (entity_response.secondary_clicked() || entity_response.long_touched())
+            && let Some(pos) = entity_response.interact_pointer_pos()
+        {
+            engine.context_menu_on_entity(
+                entity_id,
+                (pos.x - canvas_min.x).into(),
+                (pos.y - canvas_min.y).into(),
+            );
+        }
     }
 
     //
@@ -267,47 +584,25 @@ fn draw_timeline(_ctx: &Context, ui: &mut Ui, engine: &mut Engine) {
         engine.hover_over_entity(None);
     }
 
-    // Draw headings
-    for heading in engine.headings_for_drawing() {
-        let text_box = &heading.text_box;
-        let (min, max) = timeline_renderer_position_and_size_to_min_and_max_egui_pos2(
-            &text_box.position_and_size,
-        );
-        let rect = Rect::from_two_pos(min + canvas_min, max + canvas_min);
-        let (thickness, colour) = {
-            if let Some(border_style) = text_box.border_style {
-                (border_style.thickness, border_style.colour)
-            } else {
-                (0.0, Colour::from_rgb(0, 0, 0))
-            }
-        };
-        painter.rect(
-            rect,
-            0.0,
-            timeline_renderer_colour_to_egui_colour(text_box.fill_colour),
-            Stroke::new(thickness as f32, colour),
-            StrokeKind::Inside,
-        );
+    // Clicking on empty space, or pressing escape, clears the selection
+    if (painter_response.clicked() && !hovering_over_entities)
+        || (painter_response.hovered() && ui.input(|i| i.key_pressed(Key::Escape)))
+    {
+        engine.click_on_empty_space();
+    }
 
-        // Write text
-        let text = &heading.text;
-        let pos = Pos2::new(text.top_left.x as f32, text.top_left.y as f32);
-        painter.text(
-            pos + canvas_min,
-            Align2::LEFT_TOP,
-            &text.text,
-            FontId::proportional(text.font_size as f32),
-            timeline_renderer_colour_to_egui_colour(text.colour),
+    // Double click on empty space (entities sense their own clicks, so the
+    // canvas only senses clicks that miss them)
+    if painter_response.double_clicked()
+        && !hovering_over_entities
+        && let Some(pos) = painter_response.interact_pointer_pos()
+    {
+        engine.double_click_on_empty_space(
+            (pos.x - canvas_min.x).into(),
+            (pos.y - canvas_min.y).into(),
         );
     }
 
-    // TODO
-    // let stroke = Stroke::new(1.0, Color32::LIGHT_RED);
-    let stroke = Stroke::NONE;
-
-    // Draw timeline border
-    painter.rect_stroke(canvas_rect, 0.0, stroke, StrokeKind::Inside);
-
     // Handle any scrolling & zooming input
     if painter_response.hovered() {
         let (x_scroll, y_scroll) = ui.input(|i| (i.smooth_scroll_delta.x, i.smooth_scroll_delta.y));
@@ -319,6 +614,116 @@ fn draw_timeline(_ctx: &Context, ui: &mut Ui, engine: &mut Engine) {
         }
         engine.add_to_global_offset(x_scroll.into(), y_scroll.into());
     }
+
+    painter_response
+}
+
+/// Draws with an `egui` painter, remembering where each entity was drawn (so
+/// that they can be hovered over & clicked on once everything is drawn)
+struct EguiFrontend<'a> {
+    painter: &'a Painter,
+
+    /// Where the canvas is in the window
+    canvas_rect: Rect,
+
+    font_family: &'a FontFamily,
+
+    /// The IDs of the entities drawn, and the rect each covers
+    entities: Vec<(OpenTimelineId, Rect)>,
+}
+
+impl EguiFrontend<'_> {
+    /// Where the box is in the window
+    fn rect(&self, position_and_size: &PositionAndSize) -> Rect {
+        let (min, max) =
+            timeline_renderer_position_and_size_to_min_and_max_egui_pos2(position_and_size);
+        Rect::from_two_pos(
+            min + self.canvas_rect.min.to_vec2(),
+            max + self.canvas_rect.min.to_vec2(),
+        )
+    }
+}
+
+impl RendererFrontend for EguiFrontend<'_> {
+    fn clear(&mut self, _width: f64, _height: f64) {
+        // Nothing to clear (`egui` draws everything afresh each frame)
+    }
+
+    fn draw_background(&mut self, background: &Background, _height: f64) {
+        let min = Pos2::new(background.x as f32, 0.0);
+        let max = Pos2::new(background.x as f32 + background.width as f32, f32::MAX);
+        let rect = Rect::from_two_pos(
+            min + self.canvas_rect.min.to_vec2(),
+            max + self.canvas_rect.min.to_vec2(),
+        );
+        self.painter.rect(
+            rect,
+            0.0,
+            timeline_renderer_colour_to_egui_colour(background.colour),
+            Stroke::NONE,
+            StrokeKind::Inside,
+        );
+    }
+
+    fn draw_line(&mut self, line: &VerticalLine, _height: f64) {
+        let clip = self.painter.clip_rect();
+        self.painter.vline(
+            self.canvas_rect.min.to_vec2().x + line.x as f32,
+            clip.top()..=clip.bottom(),
+            Stroke::new(
+                line.style.thickness as f32,
+                timeline_renderer_colour_to_egui_colour(line.style.colour),
+            ),
+        );
+    }
+
+    fn draw_rect(&mut self, rect: &FilledBox) {
+        let (thickness, colour) = match rect.border_style {
+            Some(border_style) => (border_style.thickness, border_style.colour),
+            None => (0.0, Colour::from_rgb(0, 0, 0)),
+        };
+        self.painter.rect(
+            self.rect(&rect.position_and_size),
+            0.0,
+            timeline_renderer_fill_to_egui_colour(rect),
+            Stroke::new(thickness as f32, colour),
+            StrokeKind::Inside,
+        );
+    }
+
+    fn draw_text(&mut self, text: &TextOut) {
+        // A line at a time, as the text may have been wrapped
+        for (i, line) in text.lines.iter().enumerate() {
+            let y = text.top_left.y + (i as f64 * text.line_height);
+            let pos = Pos2::new(text.top_left.x as f32, y as f32);
+            self.painter.text(
+                pos + self.canvas_rect.min.to_vec2(),
+                Align2::LEFT_TOP,
+                line,
+                FontId::new(text.font_size as f32, self.font_family.clone()),
+                timeline_renderer_colour_to_egui_colour(text.colour),
+            );
+        }
+    }
+
+    fn entity_drawn(&mut self, entity: &EntityOut) {
+        let text_box_rect = self.rect(&entity.text_box.position_and_size);
+        let date_box_rect = self.rect(&entity.date_box.position_and_size);
+        if let Some(entity_id) = entity.entity.id() {
+            self.entities
+                .push((entity_id, date_box_rect.union(text_box_rect)));
+        }
+    }
+
+    fn present(&mut self) {
+        // TODO
+        // let stroke = Stroke::new(1.0, Color32::LIGHT_RED);
+        let stroke = Stroke::NONE;
+
+        // Draw timeline border
+        self.painter
+            .rect_stroke(self.canvas_rect, 0.0, stroke, StrokeKind::Inside);
+    }
 }
 
 // TODO: move these
@@ -328,6 +733,13 @@ fn timeline_renderer_colour_to_egui_colour(colour: Colour) -> Color32 {
     Color32::from_rgb(r, g, b)
 }
 
+/// Get a [`FilledBox`]'s fill as a [`Color32`] (blended by its alpha)
+fn timeline_renderer_fill_to_egui_colour(filled_box: &FilledBox) -> Color32 {
+    let (r, g, b) = filled_box.fill_colour.as_rgb();
+    let alpha = (filled_box.fill_alpha.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color32::from_rgba_unmultiplied(r, g, b, alpha)
+}
+
 // TODO: impl Into?
 /// Convert a [`PositionAndSize`] into min and max [`Pos2`]s
 fn timeline_renderer_position_and_size_to_min_and_max_egui_pos2(
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! An `egui` widget for embedding a timeline in any `egui` application.
+//!
+//! ```ignore
+//! let mut timeline = TimelineWidget::new(ctx)
+//!     .with_entities(entities)
+//!     .on_entity_click(|entity_id| println!("Clicked {entity_id}"));
+//!
+//! // Each frame
+//! ui.add(&mut timeline);
+//! ```
+//!
+
+use crate::TimelineInteractionEvent;
+use crate::frontends::desktop_egui::OpenTimelineRendererEgui;
+use eframe::egui::{Context, Response, Ui, Vec2, Widget};
+use open_timeline_core::{Entity, OpenTimelineId};
+
+/// Called with the ID of the entity interacted with
+type EntityCallback = Box<dyn FnMut(OpenTimelineId)>;
+
+/// Called with the IDs of the selected entities
+type SelectionCallback = Box<dyn FnMut(&[OpenTimelineId])>;
+
+/// Called with an interaction event
+type EventCallback = Box<dyn FnMut(&TimelineInteractionEvent)>;
+
+/// A timeline that can be added to a [`Ui`] like any other widget.  It owns
+/// its [`Engine`](crate::Engine), maps dragging, scrolling, and hovering onto
+/// it, and calls the callbacks given as the user interacts with the timeline.
+pub struct TimelineWidget {
+    /// The renderer drawing the timeline (which owns the engine)
+    renderer: OpenTimelineRendererEgui,
+
+    /// The size to draw the timeline (all of the available space if not set)
+    desired_size: Option<Vec2>,
+
+    /// Called when an entity is clicked on
+    on_entity_click: Option<EntityCallback>,
+
+    /// Called when an entity is double clicked on
+    on_entity_double_click: Option<EntityCallback>,
+
+    /// Called when an entity is hovered over
+    on_entity_hover: Option<EntityCallback>,
+
+    /// Called with the IDs of the selected entities when they change
+    on_selection_change: Option<SelectionCallback>,
+
+    /// Called with every interaction event (including those above)
+    on_event: Option<EventCallback>,
+}
+
+impl TimelineWidget {
+    /// Create a new (empty) timeline widget
+    pub fn new(ctx: &Context) -> Self {
+        Self {
+            renderer: OpenTimelineRendererEgui::new(ctx),
+            desired_size: None,
+            on_entity_click: None,
+            on_entity_double_click: None,
+            on_entity_hover: None,
+            on_selection_change: None,
+            on_event: None,
+        }
+    }
+
+    /// Show the entities on the timeline
+    pub fn with_entities(mut self, entities: Vec<Entity>) -> Self {
+        self.renderer.set_entities(entities);
+        self
+    }
+
+    /// Draw the timeline at this size (rather than filling the available space)
+    pub fn with_desired_size(mut self, size: Vec2) -> Self {
+        self.desired_size = Some(size);
+        self
+    }
+
+    /// Call the function with the ID of each entity clicked on
+    pub fn on_entity_click(mut self, callback: impl FnMut(OpenTimelineId) + 'static) -> Self {
+        self.on_entity_click = Some(Box::new(callback));
+        self
+    }
+
+    /// Call the function with the ID of each entity double clicked on
+    pub fn on_entity_double_click(
+        mut self,
+        callback: impl FnMut(OpenTimelineId) + 'static,
+    ) -> Self {
+        self.on_entity_double_click = Some(Box::new(callback));
+        self
+    }
+
+    /// Call the function with the ID of each entity hovered over
+    pub fn on_entity_hover(mut self, callback: impl FnMut(OpenTimelineId) + 'static) -> Self {
+        self.on_entity_hover = Some(Box::new(callback));
+        self
+    }
+
+    /// Call the function with the IDs of the selected entities whenever they
+    /// change
+    pub fn on_selection_change(
+        mut self,
+        callback: impl FnMut(&[OpenTimelineId]) + 'static,
+    ) -> Self {
+        self.on_selection_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Call the function with every interaction event (the events are drained
+    /// by the widget, so this is the way to receive those without a callback
+    /// of their own)
+    pub fn on_event(mut self, callback: impl FnMut(&TimelineInteractionEvent) + 'static) -> Self {
+        self.on_event = Some(Box::new(callback));
+        self
+    }
+
+    /// The renderer drawing the timeline (for everything else the engine
+    /// offers, e.g. filtering, zooming, and colours)
+    pub fn renderer(&self) -> &OpenTimelineRendererEgui {
+        &self.renderer
+    }
+
+    /// The renderer drawing the timeline (for everything else the engine
+    /// offers, e.g. filtering, zooming, and colours)
+    pub fn renderer_mut(&mut self) -> &mut OpenTimelineRendererEgui {
+        &mut self.renderer
+    }
+
+    /// Call the callbacks for the interaction events since the last frame
+    fn dispatch_events(&mut self) {
+        let events: Vec<TimelineInteractionEvent> =
+            self.renderer.drain_interaction_events().collect();
+        for event in events {
+            let callback = match &event {
+                TimelineInteractionEvent::SingleClick(id) => self
+                    .on_entity_click
+                    .as_mut()
+                    .map(|callback| (callback, *id)),
+                TimelineInteractionEvent::DoubleClick(id) => self
+                    .on_entity_double_click
+                    .as_mut()
+                    .map(|callback| (callback, *id)),
+                TimelineInteractionEvent::Hover(id) => self
+                    .on_entity_hover
+                    .as_mut()
+                    .map(|callback| (callback, *id)),
+                TimelineInteractionEvent::SelectionChange(ids) => {
+                    if let Some(callback) = self.on_selection_change.as_mut() {
+                        callback(ids);
+                    }
+                    None
+                }
+                _ => None,
+            };
+            if let Some((callback, id)) = callback {
+                callback(id);
+            }
+            if let Some(callback) = self.on_event.as_mut() {
+                callback(&event);
+            }
+        }
+    }
+}
+
+impl Widget for &mut TimelineWidget {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let size = self.desired_size.unwrap_or_else(|| ui.available_size());
+        let ctx = ui.ctx().clone();
+        let response = self.renderer.draw_with_size(&ctx, ui, size);
+        self.dispatch_events();
+        response
+    }
+}
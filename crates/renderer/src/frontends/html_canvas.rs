@@ -9,20 +9,64 @@
 //! ```
 //!
 
-use crate::{Colour, Engine, FilledBox, Position, ScalableLayoutParams, TextOut};
+use super::canvas_2d;
+#[cfg(feature = "net")]
+use crate::data_source::ApiDataSource;
+use crate::{
+    CapturedViewport, Colour, Engine, FontConfig, LayoutStrategy, Position, ScalableLayoutParams,
+    SelectionHighlight, SortOrder, StackingOrder, TimelineColours, TimelineInteractionEvent,
+    assign_render_ids,
+};
 use chrono::Local;
-use log::{debug, info};
-use open_timeline_core::{Entity, HasIdAndName, OpenTimelineId};
+#[cfg(feature = "net")]
+use gloo_timers::callback::Interval;
+use open_timeline_core::{Date, DateFormat, Entity, HasIdAndName, OpenTimelineId, StoryStep};
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use tracing::{debug, error, info};
 use wasm_bindgen::prelude::{Closure, wasm_bindgen};
 use wasm_bindgen::{JsCast, JsValue, UnwrapThrowExt};
+#[cfg(feature = "net")]
+use web_sys::Response;
 use web_sys::{
-    CanvasRenderingContext2d, HtmlCanvasElement, HtmlElement, KeyboardEvent, MouseEvent,
-    TextMetrics, TouchEvent, WheelEvent,
+    CanvasRenderingContext2d, CustomEvent, CustomEventInit, HtmlCanvasElement, HtmlElement,
+    KeyboardEvent, MouseEvent, TextMetrics, Touch, TouchEvent, WheelEvent,
 };
 
+/// The DOM event dispatched when an entity is clicked (or tapped)
+pub const ENTITY_CLICK_EVENT: &str = "ot-entity-click";
+
+/// The DOM event dispatched when the selected entities change
+pub const ENTITY_SELECT_EVENT: &str = "ot-entity-select";
+
+/// The DOM event dispatched when a context menu is asked for on an entity
+/// (i.e. it is right-clicked or long-pressed)
+pub const ENTITY_CONTEXT_MENU_EVENT: &str = "ot-entity-context-menu";
+
+/// The DOM event dispatched when the part of the timeline in view changes
+/// (once it has stopped changing, see
+/// [`OpenTimelineRendererHtmlCanvas::set_viewport_changed_debounce_ms()`])
+pub const VIEWPORT_CHANGE_EVENT: &str = "ot-viewport-change";
+
+/// The DOM event dispatched when a different step of the story is shown (or
+/// the story is stopped)
+pub const STORY_STEP_EVENT: &str = "ot-story-step";
+
+/// Touch moves further apart than this (ms) don't count towards the inertial
+/// panning velocity
+const TOUCH_VELOCITY_MAX_MS: f64 = 100.0;
+
+/// How much weight the latest touch move has in the inertial panning velocity
+const TOUCH_VELOCITY_SMOOTHING: f64 = 0.8;
+
+/// How long (ms) a finger must be held down (without moving) for a long press
+const LONG_PRESS_MS: u32 = 500;
+
+/// A finger that moves further than this (CSS pixels) isn't long pressing
+const LONG_PRESS_MAX_MOVE_PX: f64 = 10.0;
+
 // TODO
 // - Use unwrap_throw() more (see what it does first)
 // - Ability to pass in CSS query selectors/other
@@ -88,8 +132,7 @@ pub fn measure_text(font_size: f64, text: &str) -> TextMetrics {
 
     let font_size = font_size * device_pixel_ratio();
 
-    let font_style = "serif";
-    ctx.set_font(&format!("{font_size}px {font_style}"));
+    canvas_2d::set_font(&ctx, font_size);
 
     ctx.measure_text(&text).unwrap()
 }
@@ -129,7 +172,7 @@ pub fn start() -> Result<(), JsValue> {
     // TODO: where to put this?
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
     console_log::init_with_level(log::Level::Debug).expect("error initializing log");
-    log::info!("Start OpenTimeline");
+    info!("Start OpenTimeline");
     Ok(())
 }
 
@@ -165,6 +208,189 @@ struct State {
 
     /// When was the user last draggin (i.e. moving the timeline)
     ms_time_of_last_dragging: i64,
+
+    /// The distance between the 2 touch points if the user is pinching
+    pinch_distance: Option<f64>,
+
+    /// The velocity (pixels per ms) of the most recent touch panning (used for
+    /// inertial panning once the user lets go)
+    touch_velocity: Position,
+
+    /// The timestamp (ms) of the last touch move
+    ms_time_of_last_touchmove: i64,
+
+    /// The ID of the touchstart event of a possible long press, and where (in
+    /// client coordinates) it started.  Cleared if the finger moves away or is
+    /// lifted.
+    long_press: Option<(i64, Position)>,
+
+    /// The timestamp (ms) of the last frame drawn (used for animation)
+    ms_time_of_last_frame: Option<i64>,
+}
+
+/// The earliest and latest dates to show (if limited)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DateLimits {
+    /// Nothing before this date is shown
+    start: Option<Date>,
+
+    /// Nothing after this date is shown
+    end: Option<Date>,
+}
+
+/// The renderer's style settings, as returned by `getConfig()`
+#[derive(Debug, Clone, Serialize)]
+struct Config {
+    /// The timeline's colours
+    colours: TimelineColours,
+
+    /// The CSS font family used for all text
+    font_family: String,
+
+    /// The font families (in order of preference) and weight used for all
+    /// text
+    font: FontConfig,
+
+    /// The font size (i.e. not accounting for zooming)
+    font_size_px: f64,
+
+    /// The spacing, padding, line thicknesses, etc
+    layout_params: ScalableLayoutParams,
+
+    /// How entities are packed into rows
+    layout_strategy: LayoutStrategy,
+
+    /// The order entities are put into rows in
+    sort_order: SortOrder,
+
+    /// The order entities are drawn in (i.e. which are on top where they
+    /// overlap)
+    stacking_order: StackingOrder,
+
+    /// How selected entities are highlighted
+    selection_highlight: SelectionHighlight,
+
+    /// How opaque entity date boxes are (`0.0` to `1.0`)
+    date_box_alpha: f64,
+
+    /// Whether entity text sticks to the left of the canvas
+    sticky_text: bool,
+
+    /// The earliest and latest dates shown
+    date_limits: DateLimits,
+
+    /// How dates and years are displayed
+    date_format: DateFormat,
+
+    /// Whether a line is drawn at today's date
+    show_now_line: bool,
+
+    /// Whether headings say how long ago things were (e.g. "75 years ago")
+    relative_time: bool,
+}
+
+/// The options accepted by `setConfig()`.  Anything left out is unchanged.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct ConfigOptions {
+    /// The timeline's colours
+    colours: Option<TimelineColours>,
+
+    /// The CSS font family used for all text (e.g. `"Georgia, serif"`)
+    font_family: Option<String>,
+
+    /// The font families (in order of preference) and weight used for all
+    /// text (e.g. `{families: ["Noto Serif", "Georgia"], weight: 700}`)
+    font: Option<FontConfig>,
+
+    /// The font size (i.e. not accounting for zooming)
+    font_size_px: Option<f64>,
+
+    /// The spacing, padding, line thicknesses, etc (missing params are set to
+    /// their defaults)
+    layout_params: Option<ScalableLayoutParams>,
+
+    /// How entities are packed into rows (e.g. `"best_fit"`)
+    layout_strategy: Option<LayoutStrategy>,
+
+    /// The order entities are put into rows in (e.g. `"alphabetical"`, or
+    /// `{tag_value: "country"}`)
+    sort_order: Option<SortOrder>,
+
+    /// The order entities are drawn in (e.g. `"longest_first"`)
+    stacking_order: Option<StackingOrder>,
+
+    /// How selected entities are highlighted (e.g. `{style: "glow", colour:
+    /// {r: 255, g: 140, b: 0}}`)
+    selection_highlight: Option<SelectionHighlight>,
+
+    /// How opaque entity date boxes are (`0.0` to `1.0`)
+    date_box_alpha: Option<f64>,
+
+    /// Whether entity text sticks to the left of the canvas
+    sticky_text: Option<bool>,
+
+    /// The earliest and latest dates shown
+    date_limits: Option<DateLimits>,
+
+    /// How dates and years are displayed
+    date_format: Option<DateFormat>,
+
+    /// Whether a line is drawn at today's date
+    show_now_line: Option<bool>,
+
+    /// Whether headings say how long ago things were (e.g. "75 years ago")
+    relative_time: Option<bool>,
+}
+
+/// The `detail` of an `ot-entity-click` DOM event
+#[derive(Debug, Serialize)]
+pub(crate) struct EntityClickDetail<'a> {
+    /// The ID of the clicked entity
+    pub id: OpenTimelineId,
+
+    /// The number of clicks (1, 2, or 3)
+    pub clicks: u8,
+
+    /// The clicked entity
+    pub entity: Option<&'a Entity>,
+}
+
+/// The `detail` of an `ot-entity-context-menu` DOM event
+#[derive(Debug, Serialize)]
+pub(crate) struct EntityContextMenuDetail<'a> {
+    /// The ID of the entity the menu is for
+    pub id: OpenTimelineId,
+
+    /// Where the menu was asked for (CSS pixels from the left of the canvas)
+    pub x: f64,
+
+    /// Where the menu was asked for (CSS pixels from the top of the canvas)
+    pub y: f64,
+
+    /// The entity the menu is for
+    pub entity: Option<&'a Entity>,
+}
+
+/// The `detail` of an `ot-story-step` DOM event
+#[derive(Debug, Serialize)]
+pub(crate) struct StoryStepDetail<'a> {
+    /// The index of the step being shown (`null` if the story was stopped)
+    pub step: Option<usize>,
+
+    /// The step being shown
+    pub story_step: Option<&'a StoryStep>,
+}
+
+/// The `detail` of an `ot-entity-select` DOM event
+#[derive(Debug, Serialize)]
+pub(crate) struct EntitySelectDetail<'a> {
+    /// The IDs of the selected entities
+    pub ids: &'a [OpenTimelineId],
+
+    /// The selected entities
+    pub entities: Vec<&'a Entity>,
 }
 
 /// Whether the event is to target the visible canvas or the window
@@ -241,6 +467,10 @@ pub struct OpenTimelineRendererHtmlCanvas {
 
     ///
     drawing_surfaces: Rc<RefCell<DrawingSurfaces>>,
+
+    /// Fetches the entities from the API (if they are being loaded from it)
+    #[cfg(feature = "net")]
+    api_loader: Option<ApiLoader>,
 }
 
 #[wasm_bindgen]
@@ -269,6 +499,7 @@ impl OpenTimelineRendererHtmlCanvas {
             font_size_px: 14.0,
             dividing_line_thickness: 0.5,
             entity_highlight_thickness: 10.0,
+            max_text_width: None,
         });
 
         //
@@ -284,8 +515,15 @@ impl OpenTimelineRendererHtmlCanvas {
                 dragging: false,
                 mouse_is_down: false,
                 ms_time_of_last_dragging: Local::now().timestamp_millis(),
+                pinch_distance: None,
+                touch_velocity: Position::default(),
+                ms_time_of_last_touchmove: 0,
+                long_press: None,
+                ms_time_of_last_frame: None,
             })),
             engine: Rc::new(RefCell::new(engine)),
+            #[cfg(feature = "net")]
+            api_loader: None,
         };
 
         info!("Setting up listeneres");
@@ -294,6 +532,7 @@ impl OpenTimelineRendererHtmlCanvas {
         html_canvas.listen_for_mousemove();
         html_canvas.listen_for_mouseleave();
         html_canvas.listen_for_click();
+        html_canvas.listen_for_contextmenu();
         html_canvas.listen_for_scroll();
         html_canvas.listen_for_touchstart();
         html_canvas.listen_for_touchmove();
@@ -318,15 +557,11 @@ impl OpenTimelineRendererHtmlCanvas {
     #[wasm_bindgen]
     pub fn add_entities(&mut self, entities: JsValue) -> Result<(), JsValue> {
         // debug!("add_entities");
-        let entities: Vec<Entity> = serde_wasm_bindgen::from_value(entities).unwrap();
+        let mut entities: Vec<Entity> = serde_wasm_bindgen::from_value(entities).unwrap();
         // debug!("got vec of entities");
 
-        for entity in &entities {
-            self.state.borrow_mut().map.insert(
-                Colour::from_any_string(entity.name().as_str()),
-                entity.id().unwrap(),
-            );
-        }
+        assign_render_ids(&mut entities);
+        map_hidden_colours(&self.state, &entities);
         // debug!("added hidden colours for entities");
 
         self.engine.borrow_mut().add_entities(entities);
@@ -336,15 +571,491 @@ impl OpenTimelineRendererHtmlCanvas {
         Ok(())
     }
 
+    /// Replace the entities with those in the JSON array (e.g. the contents of
+    /// a file of entities to preview, which needn't have IDs)
+    #[wasm_bindgen]
+    pub fn load_from_json(&mut self, json: &str) -> Result<(), JsValue> {
+        let mut entities: Vec<Entity> = serde_json::from_str(json)
+            .map_err(|err| JsValue::from_str(&format!("Invalid entities: {err}")))?;
+        assign_render_ids(&mut entities);
+        self.clear_entities();
+        map_hidden_colours(&self.state, &entities);
+        self.engine.borrow_mut().add_entities(entities);
+        self.draw();
+        Ok(())
+    }
+
+    /// Load the timeline's entities from the OpenTimeline API (e.g.
+    /// `https://example.org/api/v1`), fetching them again every
+    /// `refresh_interval_ms` if given.  The entities are only updated when they
+    /// have changed, and are drawn in the next frame.
+    #[cfg(feature = "net")]
+    #[wasm_bindgen]
+    pub fn load_from_api(
+        &mut self,
+        api_base_url: &str,
+        timeline_id: &str,
+        refresh_interval_ms: Option<u32>,
+    ) {
+        let data_source = ApiDataSource::new(api_base_url, timeline_id);
+        info!("Loading entities from {}", data_source.entities_url());
+        let api_loader = ApiLoader::new(self.engine.clone(), self.state.clone(), data_source);
+        api_loader.fetch();
+        self.api_loader = Some(api_loader.refreshing_every(refresh_interval_ms));
+    }
+
+    /// Fetch the entities from the API again now (if loading from it)
+    #[cfg(feature = "net")]
+    #[wasm_bindgen]
+    pub fn refresh_from_api(&self) {
+        if let Some(api_loader) = &self.api_loader {
+            api_loader.fetch();
+        }
+    }
+
+    /// Stop periodically fetching the entities from the API (those already
+    /// fetched are kept)
+    #[cfg(feature = "net")]
+    #[wasm_bindgen]
+    pub fn stop_refreshing_from_api(&mut self) {
+        if let Some(api_loader) = self.api_loader.as_mut() {
+            api_loader.refresh = None;
+        }
+    }
+
     //--------------------------------------------------------------------------
     //
     //--------------------------------------------------------------------------
     #[wasm_bindgen]
     pub fn draw(&mut self) {
+        // Advance any animation (e.g. inertial panning)
+        let now = Local::now().timestamp_millis();
+        let last_frame = self.state.borrow_mut().ms_time_of_last_frame.replace(now);
+        if let Some(last_frame) = last_frame {
+            self.engine.borrow_mut().animate((now - last_frame) as f64);
+        }
+
         draw_timeline(self.engine.clone(), self.drawing_surfaces.clone());
+        self.dispatch_dom_events();
         // debug!("[exit] .draw()");
     }
 
+    //--------------------------------------------------------------------------
+    // Styling
+    //--------------------------------------------------------------------------
+
+    /// Get the current style settings
+    #[wasm_bindgen(js_name = getConfig)]
+    pub fn get_config(&self) -> Result<JsValue, JsValue> {
+        let engine = self.engine.borrow();
+        let (start, end) = engine.date_limits();
+        to_js_value(&Config {
+            colours: engine.colours(),
+            font_family: engine.font().css_family_list(),
+            font: engine.font().clone(),
+            font_size_px: engine.layout_params().font_size_px,
+            layout_params: engine.layout_params(),
+            layout_strategy: engine.layout_strategy(),
+            sort_order: engine.sort_order().clone(),
+            stacking_order: engine.stacking_order(),
+            selection_highlight: engine.selection_highlight(),
+            date_box_alpha: engine.date_box_alpha(),
+            sticky_text: engine.sticky_text(),
+            date_limits: DateLimits { start, end },
+            date_format: engine.date_format(),
+            show_now_line: engine.show_now_line(),
+            relative_time: engine.relative_time(),
+        })
+    }
+
+    /// Update any of the style settings at once (i.e. an object with any of the
+    /// fields returned by `getConfig()`)
+    #[wasm_bindgen(js_name = setConfig)]
+    pub fn set_config(&mut self, options: JsValue) -> Result<(), JsValue> {
+        let options: ConfigOptions = serde_wasm_bindgen::from_value(options)?;
+        if let Some(colours) = options.colours {
+            self.engine.borrow_mut().set_colours(colours);
+        }
+        if let Some(layout_params) = options.layout_params {
+            self.engine.borrow_mut().set_layout_params(layout_params);
+        }
+        if let Some(layout_strategy) = options.layout_strategy {
+            self.engine
+                .borrow_mut()
+                .set_layout_strategy(layout_strategy);
+        }
+        if let Some(sort_order) = options.sort_order {
+            self.engine.borrow_mut().set_sort(sort_order);
+        }
+        if let Some(stacking_order) = options.stacking_order {
+            self.engine.borrow_mut().set_stacking_order(stacking_order);
+        }
+        if let Some(selection_highlight) = options.selection_highlight {
+            self.engine
+                .borrow_mut()
+                .set_selection_highlight(selection_highlight);
+        }
+        if let Some(date_box_alpha) = options.date_box_alpha {
+            self.engine.borrow_mut().set_date_box_alpha(date_box_alpha);
+        }
+        if let Some(font_size_px) = options.font_size_px {
+            self.set_font_size_px(font_size_px);
+        }
+        if let Some(font) = options.font {
+            canvas_2d::use_font(&mut self.engine.borrow_mut(), font);
+        }
+        if let Some(font_family) = options.font_family {
+            self.set_font_family(font_family);
+        }
+        if let Some(sticky_text) = options.sticky_text {
+            self.set_sticky_text(sticky_text);
+        }
+        if let Some(DateLimits { start, end }) = options.date_limits {
+            self.engine.borrow_mut().set_date_limits(start, end);
+        }
+        if let Some(date_format) = options.date_format {
+            self.engine.borrow_mut().set_date_format(date_format);
+        }
+        if let Some(show_now_line) = options.show_now_line {
+            self.set_show_now_line(show_now_line);
+        }
+        if let Some(relative_time) = options.relative_time {
+            self.set_relative_time(relative_time);
+        }
+        Ok(())
+    }
+
+    /// Set the timeline's colours (an object like `getConfig().colours`)
+    #[wasm_bindgen]
+    pub fn set_colours(&mut self, colours: JsValue) -> Result<(), JsValue> {
+        let colours: TimelineColours = serde_wasm_bindgen::from_value(colours)?;
+        self.engine.borrow_mut().set_colours(colours);
+        Ok(())
+    }
+
+    /// Set the CSS font family used for all text (e.g. `"Georgia, serif"`).
+    /// The font weight is unchanged.
+    #[wasm_bindgen]
+    pub fn set_font_family(&mut self, font_family: String) {
+        let mut engine = self.engine.borrow_mut();
+        let font = FontConfig::from_css_family_list(&font_family, engine.font().weight);
+        canvas_2d::use_font(&mut engine, font);
+    }
+
+    /// Set the font families (in order of preference) and weight used for all
+    /// text (an object like `getConfig().font`)
+    #[wasm_bindgen]
+    pub fn set_font(&mut self, font: JsValue) -> Result<(), JsValue> {
+        let font: FontConfig = serde_wasm_bindgen::from_value(font)?;
+        canvas_2d::use_font(&mut self.engine.borrow_mut(), font);
+        Ok(())
+    }
+
+    /// Set the font size (before zooming)
+    #[wasm_bindgen]
+    pub fn set_font_size_px(&mut self, font_size_px: f64) {
+        self.engine.borrow_mut().set_font_size_px(font_size_px);
+    }
+
+    /// Set the layout params (an object like `getConfig().layout_params`).
+    /// Missing params are set to their defaults.
+    #[wasm_bindgen]
+    pub fn set_layout_params(&mut self, layout_params: JsValue) -> Result<(), JsValue> {
+        let layout_params: ScalableLayoutParams = serde_wasm_bindgen::from_value(layout_params)?;
+        self.engine.borrow_mut().set_layout_params(layout_params);
+        Ok(())
+    }
+
+    /// Set how entities are packed into rows (`"first_fit"`, `"best_fit"`,
+    /// `"chronological"`, or `"grouped_by_tag"`)
+    #[wasm_bindgen]
+    pub fn set_layout_strategy(&mut self, layout_strategy: JsValue) -> Result<(), JsValue> {
+        let layout_strategy: LayoutStrategy = serde_wasm_bindgen::from_value(layout_strategy)?;
+        self.engine
+            .borrow_mut()
+            .set_layout_strategy(layout_strategy);
+        Ok(())
+    }
+
+    /// Set the order entities are put into rows in (`"start_date"`,
+    /// `"end_date"`, `"duration"`, `"alphabetical"`, or e.g.
+    /// `{tag_value: "country"}` to sort by the value of the `country` tag)
+    #[wasm_bindgen]
+    pub fn set_sort(&mut self, sort_order: JsValue) -> Result<(), JsValue> {
+        let sort_order: SortOrder = serde_wasm_bindgen::from_value(sort_order)?;
+        self.engine.borrow_mut().set_sort(sort_order);
+        Ok(())
+    }
+
+    /// Set the order entities are drawn in, i.e. which are on top where they
+    /// overlap (`"chronological"`, `"longest_first"`, or `"newest_first"`)
+    #[wasm_bindgen]
+    pub fn set_stacking_order(&mut self, stacking_order: JsValue) -> Result<(), JsValue> {
+        let stacking_order: StackingOrder = serde_wasm_bindgen::from_value(stacking_order)?;
+        self.engine.borrow_mut().set_stacking_order(stacking_order);
+        Ok(())
+    }
+
+    /// Set how selected entities are highlighted, e.g. `{style: "glow", colour:
+    /// {r: 255, g: 140, b: 0}}` (the style is `"border"` or `"glow"`)
+    #[wasm_bindgen]
+    pub fn set_selection_highlight(&mut self, selection_highlight: JsValue) -> Result<(), JsValue> {
+        let selection_highlight: SelectionHighlight =
+            serde_wasm_bindgen::from_value(selection_highlight)?;
+        self.engine
+            .borrow_mut()
+            .set_selection_highlight(selection_highlight);
+        Ok(())
+    }
+
+    /// Set how opaque entity date boxes are (`0.0` is fully transparent, `1.0`
+    /// is solid)
+    #[wasm_bindgen]
+    pub fn set_date_box_alpha(&mut self, alpha: f64) {
+        self.engine.borrow_mut().set_date_box_alpha(alpha);
+    }
+
+    /// Set whether entity text sticks to the left of the canvas
+    #[wasm_bindgen]
+    pub fn set_sticky_text(&mut self, sticky_text: bool) {
+        self.engine.borrow_mut().set_sticky_text(sticky_text);
+    }
+
+    /// Set whether a line is drawn at today's date
+    #[wasm_bindgen]
+    pub fn set_show_now_line(&mut self, show_now_line: bool) {
+        self.engine.borrow_mut().set_show_now_line(show_now_line);
+    }
+
+    /// Set whether the datetime scale is picked automatically (so that the
+    /// timeline's dates fill the width of the canvas)
+    #[wasm_bindgen]
+    pub fn set_datetime_scale_auto(&mut self, auto: bool) {
+        self.engine.borrow_mut().set_datetime_scale_auto(auto);
+    }
+
+    /// Set whether headings say how long ago things were (e.g. "75 years ago")
+    #[wasm_bindgen]
+    pub fn set_relative_time(&mut self, relative_time: bool) {
+        self.engine.borrow_mut().set_relative_time(relative_time);
+    }
+
+    /// Set the earliest and latest dates shown (e.g. `{start: {year: 1900}}`)
+    #[wasm_bindgen]
+    pub fn set_date_limits(&mut self, date_limits: JsValue) -> Result<(), JsValue> {
+        let DateLimits { start, end } = serde_wasm_bindgen::from_value(date_limits)?;
+        self.engine.borrow_mut().set_date_limits(start, end);
+        Ok(())
+    }
+
+    //--------------------------------------------------------------------------
+    // DOM events (for use by the web page)
+    //--------------------------------------------------------------------------
+
+    /// Call `callback` with the `detail` of each `ot-entity-click` event
+    #[wasm_bindgen]
+    pub fn on_entity_click(&mut self, callback: js_sys::Function) {
+        self.subscribe(ENTITY_CLICK_EVENT, callback);
+    }
+
+    /// Call `callback` with the `detail` of each `ot-entity-select` event
+    #[wasm_bindgen]
+    pub fn on_entity_select(&mut self, callback: js_sys::Function) {
+        self.subscribe(ENTITY_SELECT_EVENT, callback);
+    }
+
+    /// Call `callback` with the `detail` of each `ot-viewport-change` event
+    #[wasm_bindgen]
+    pub fn on_viewport_change(&mut self, callback: js_sys::Function) {
+        self.subscribe(VIEWPORT_CHANGE_EVENT, callback);
+    }
+
+    /// Set how long (ms) the part of the timeline in view must be unchanged
+    /// before an `ot-viewport-change` event is dispatched
+    #[wasm_bindgen]
+    pub fn set_viewport_changed_debounce_ms(&mut self, debounce_ms: f64) {
+        self.engine
+            .borrow_mut()
+            .set_viewport_changed_debounce_ms(debounce_ms);
+    }
+
+    /// Call `callback` with the `detail` of each `ot-story-step` event
+    #[wasm_bindgen]
+    pub fn on_story_step(&mut self, callback: js_sys::Function) {
+        self.subscribe(STORY_STEP_EVENT, callback);
+    }
+
+    /// Set the story that can be played (e.g. `[{target: {entity: "..."},
+    /// caption: "..."}]`)
+    #[wasm_bindgen]
+    pub fn set_story(&mut self, steps: JsValue) -> Result<(), JsValue> {
+        let steps: Vec<StoryStep> = serde_wasm_bindgen::from_value(steps)?;
+        self.engine.borrow_mut().set_story(steps);
+        Ok(())
+    }
+
+    /// Show a step of the story (the view moves to it as frames are drawn)
+    #[wasm_bindgen]
+    pub fn go_to_story_step(&mut self, step: usize) {
+        self.engine.borrow_mut().go_to_story_step(step);
+    }
+
+    /// Show the next step of the story (starting it if it isn't being played)
+    #[wasm_bindgen]
+    pub fn next_story_step(&mut self) {
+        self.engine.borrow_mut().next_story_step();
+    }
+
+    /// Show the previous step of the story
+    #[wasm_bindgen]
+    pub fn previous_story_step(&mut self) {
+        self.engine.borrow_mut().previous_story_step();
+    }
+
+    /// Stop playing the story
+    #[wasm_bindgen]
+    pub fn stop_story(&mut self) {
+        self.engine.borrow_mut().stop_story();
+    }
+
+    /// Get the IDs of the selected entities
+    #[wasm_bindgen]
+    pub fn selected_entity_ids(&self) -> Result<JsValue, JsValue> {
+        to_js_value(self.engine.borrow().ids_of_selected_entities())
+    }
+
+    /// Select the entities with the given IDs
+    #[wasm_bindgen]
+    pub fn set_selected_entity_ids(&mut self, ids: JsValue) -> Result<(), JsValue> {
+        let ids: Vec<OpenTimelineId> = serde_wasm_bindgen::from_value(ids)?;
+        self.engine.borrow_mut().set_ids_of_selected_entities(ids);
+        Ok(())
+    }
+
+    /// Clear the selection (e.g. for a "clear selection" button)
+    #[wasm_bindgen]
+    pub fn clear_selection(&mut self) {
+        self.engine.borrow_mut().clear_ids_of_selected_entities();
+    }
+
+    /// Get the part of the timeline currently in view
+    #[wasm_bindgen]
+    pub fn viewport(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&self.engine.borrow().viewport())
+    }
+
+    /// Capture what's currently in view (so that it can be returned to with
+    /// `restore_viewport()`)
+    #[wasm_bindgen]
+    pub fn capture_viewport(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&self.engine.borrow().capture_viewport())
+    }
+
+    /// Return to a view captured with `capture_viewport()`
+    #[wasm_bindgen]
+    pub fn restore_viewport(&mut self, captured: JsValue) -> Result<(), JsValue> {
+        let captured: CapturedViewport = serde_wasm_bindgen::from_value(captured)?;
+        self.engine.borrow_mut().restore_viewport(&captured);
+        Ok(())
+    }
+
+    /// Serialise the view (position, filters & selection) as a compact,
+    /// URL-safe string (e.g. for the URL's hash)
+    #[wasm_bindgen]
+    pub fn serialize_view_state(&self) -> String {
+        self.engine.borrow().serialize_view_state()
+    }
+
+    /// Recreate a view serialised with `serialize_view_state()`
+    #[wasm_bindgen]
+    pub fn apply_view_state(&mut self, view_state: &str) -> Result<(), JsValue> {
+        self.engine
+            .borrow_mut()
+            .apply_view_state(view_state)
+            .map_err(|error| JsValue::from_str(&error.to_string()))
+    }
+
+    /// Call `callback` with the `detail` of each `event_name` custom event
+    fn subscribe(&mut self, event_name: &str, callback: js_sys::Function) {
+        self.add_listener::<CustomEvent, _>(
+            EventListenTarget::VisibleCanvas,
+            event_name,
+            move |event: CustomEvent| {
+                if let Err(err) = callback.call1(&JsValue::NULL, &event.detail()) {
+                    error!("Event callback failed: {err:?}");
+                }
+            },
+        );
+    }
+
+    /// Dispatch DOM events for the interactions with (and changes to) the
+    /// timeline since the last frame
+    fn dispatch_dom_events(&mut self) {
+        // Collect the events first so that listeners are free to call back
+        // into the renderer
+        let mut events: Vec<(&str, Result<JsValue, JsValue>)> = Vec::new();
+        {
+            let mut engine = self.engine.borrow_mut();
+            let interaction_events: Vec<_> = engine.drain_interaction_events().collect();
+            for interaction_event in interaction_events {
+                let (id, clicks) = match interaction_event {
+                    TimelineInteractionEvent::SingleClick(id) => (id, 1),
+                    TimelineInteractionEvent::DoubleClick(id) => (id, 2),
+                    TimelineInteractionEvent::TripleClick(id) => (id, 3),
+                    TimelineInteractionEvent::SelectionChange(ids) => {
+                        let detail = EntitySelectDetail {
+                            ids: &ids,
+                            entities: ids.iter().filter_map(|id| engine.entity(*id)).collect(),
+                        };
+                        events.push((ENTITY_SELECT_EVENT, to_js_value(&detail)));
+                        continue;
+                    }
+                    TimelineInteractionEvent::ContextMenu(id, x, y) => {
+                        let detail = EntityContextMenuDetail {
+                            id,
+                            x: x / device_pixel_ratio(),
+                            y: y / device_pixel_ratio(),
+                            entity: engine.entity(id),
+                        };
+                        events.push((ENTITY_CONTEXT_MENU_EVENT, to_js_value(&detail)));
+                        continue;
+                    }
+                    TimelineInteractionEvent::ViewportChanged { .. } => {
+                        events.push((VIEWPORT_CHANGE_EVENT, to_js_value(&engine.viewport())));
+                        continue;
+                    }
+                    TimelineInteractionEvent::StoryStepChange(step) => {
+                        let detail = StoryStepDetail {
+                            step,
+                            story_step: step.and_then(|step| engine.story().get(step)),
+                        };
+                        events.push((STORY_STEP_EVENT, to_js_value(&detail)));
+                        continue;
+                    }
+                    // Edit mode (dragging date box edges) and creating
+                    // entities are only supported by the desktop frontend
+                    TimelineInteractionEvent::Hover(_)
+                    | TimelineInteractionEvent::EmptySpaceDoubleClick(_)
+                    | TimelineInteractionEvent::DatesChanged { .. } => continue,
+                };
+                let detail = EntityClickDetail {
+                    id,
+                    clicks,
+                    entity: engine.entity(id),
+                };
+                events.push((ENTITY_CLICK_EVENT, to_js_value(&detail)));
+            }
+        }
+
+        let canvas = self.drawing_surfaces.borrow().visible.canvas.clone();
+        for (event_name, detail) in events {
+            match detail {
+                Ok(detail) => dispatch_custom_event(&canvas, event_name, &detail),
+                Err(err) => error!("Unable to serialise {event_name} event: {err:?}"),
+            }
+        }
+    }
+
     //--------------------------------------------------------------------------
     // Manage events
     //--------------------------------------------------------------------------
@@ -478,8 +1189,10 @@ impl OpenTimelineRendererHtmlCanvas {
 
     /// Touch start event handler
     ///
-    /// Double tap to zoom in, triple tap to zoom out
+    /// Double tap to zoom in, triple tap to zoom out, long press on an entity
+    /// for a context menu
     pub fn listen_for_touchstart(&mut self) {
+        let drawing_surfaces = self.drawing_surfaces.clone();
         let engine = self.engine.clone();
         let state = self.state.clone();
         self.add_listener::<web_sys::TouchEvent, _>(
@@ -492,6 +1205,19 @@ impl OpenTimelineRendererHtmlCanvas {
                 event.stop_propagation();
                 event.prevent_default();
 
+                // Stop the timeline if it's still moving from a previous pan
+                engine.borrow_mut().stop_pan_inertia();
+                state.borrow_mut().touch_velocity = Position::default();
+
+                // A second finger starts a pinch (not a tap)
+                if let Some((first, second)) = first_two_touches(&event) {
+                    let mut state = state.borrow_mut();
+                    state.pinch_distance = Some(touch_distance(&first, &second));
+                    state.touch_position = None;
+                    state.long_press = None;
+                    return;
+                }
+
                 // Set touch coords
                 let touch = event.touches().get(0).unwrap();
                 let current_x = touch.client_x() as f64;
@@ -505,6 +1231,47 @@ impl OpenTimelineRendererHtmlCanvas {
                 let event_id = Local::now().timestamp_millis();
                 state.borrow_mut().most_recent_touchstart_event_id = event_id;
 
+                // If the finger stays put, it's a long press (for a context
+                // menu if it's on an entity)
+                let touch_start = Position {
+                    x: current_x,
+                    y: current_y,
+                };
+                state.borrow_mut().long_press = Some((event_id, touch_start));
+                {
+                    let drawing_surfaces = drawing_surfaces.clone();
+                    let engine = engine.clone();
+                    let state = state.clone();
+                    gloo_timers::callback::Timeout::new(LONG_PRESS_MS, move || {
+                        let is_this_press =
+                            |long_press: &mut (i64, Position)| long_press.0 == event_id;
+                        if state
+                            .borrow_mut()
+                            .long_press
+                            .take_if(is_this_press)
+                            .is_none()
+                        {
+                            return;
+                        }
+                        let canvas_rect = drawing_surfaces
+                            .borrow()
+                            .visible
+                            .canvas
+                            .get_bounding_client_rect();
+                        let x = (touch_start.x - canvas_rect.left()) * device_pixel_ratio();
+                        let y = (touch_start.y - canvas_rect.top()) * device_pixel_ratio();
+                        let Ok(colour_under_finger) = colour_at_point(&drawing_surfaces, x, y)
+                        else {
+                            return;
+                        };
+                        if let Some(id) = state.borrow().map.get(&colour_under_finger) {
+                            info!("Long press detected");
+                            engine.borrow_mut().context_menu_on_entity(*id, x, y);
+                        }
+                    })
+                    .forget();
+                }
+
                 // Set the cutoff limit
                 let cutoff_ms = 250;
 
@@ -568,7 +1335,10 @@ impl OpenTimelineRendererHtmlCanvas {
     }
 
     /// Touch move event handler
+    ///
+    /// One finger pans the timeline, two fingers pinch to zoom
     pub fn listen_for_touchmove(&mut self) {
+        let drawing_surfaces = self.drawing_surfaces.clone();
         let engine = self.engine.clone();
         let state = self.state.clone();
         self.add_listener::<web_sys::TouchEvent, _>(
@@ -579,12 +1349,38 @@ impl OpenTimelineRendererHtmlCanvas {
                 let mut state = state.borrow_mut();
                 event.stop_propagation();
                 event.prevent_default();
+
+                // Pinch to zoom around the midpoint of the 2 touches
+                if let (Some(pinch_distance), Some((first, second))) =
+                    (state.pinch_distance, first_two_touches(&event))
+                {
+                    let distance = touch_distance(&first, &second);
+                    if pinch_distance > 0.0 && distance > 0.0 {
+                        let canvas = &drawing_surfaces.borrow().visible.canvas;
+                        let canvas_rect = canvas.get_bounding_client_rect();
+                        let mid_x = (first.client_x() + second.client_x()) as f64 / 2.0;
+                        let mid_y = (first.client_y() + second.client_y()) as f64 / 2.0;
+                        let x = (mid_x - canvas_rect.left()) * device_pixel_ratio();
+                        let y = (mid_y - canvas_rect.top()) * device_pixel_ratio();
+                        engine.borrow_mut().zoom_by(distance / pinch_distance, x, y);
+                    }
+                    state.pinch_distance = Some(distance);
+                    return;
+                }
+
                 if let (Some(touch_position), Some(touch)) =
                     (state.touch_position, event.touches().get(0))
                 {
                     let current_x = touch.client_x() as f64;
                     let current_y = touch.client_y() as f64;
 
+                    // Moving away isn't long pressing
+                    if let Some((_, start)) = state.long_press
+                        && (current_x - start.x).hypot(current_y - start.y) > LONG_PRESS_MAX_MOVE_PX
+                    {
+                        state.long_press = None;
+                    }
+
                     let del_x = (touch_position.x - current_x) * device_pixel_ratio();
                     let del_y = (touch_position.y - current_y) * device_pixel_ratio();
 
@@ -596,6 +1392,20 @@ impl OpenTimelineRendererHtmlCanvas {
                         y: current_y,
                     });
 
+                    // Track the (smoothed) velocity for inertial panning
+                    let now = Local::now().timestamp_millis();
+                    let ms_elapsed = (now - state.ms_time_of_last_touchmove) as f64;
+                    state.ms_time_of_last_touchmove = now;
+                    if ms_elapsed > 0.0 && ms_elapsed < TOUCH_VELOCITY_MAX_MS {
+                        let smoothing = TOUCH_VELOCITY_SMOOTHING;
+                        state.touch_velocity = Position {
+                            x: (smoothing * -del_x / ms_elapsed)
+                                + ((1.0 - smoothing) * state.touch_velocity.x),
+                            y: (smoothing * -del_y / ms_elapsed)
+                                + ((1.0 - smoothing) * state.touch_velocity.y),
+                        };
+                    }
+
                     // draw_timeline(engine.clone(), drawing_surfaces.clone());
                 }
             },
@@ -603,16 +1413,45 @@ impl OpenTimelineRendererHtmlCanvas {
     }
 
     /// Touch end event handler
+    ///
+    /// Lifting the last finger after panning lets the timeline keep moving
     pub fn listen_for_touchend(&mut self) {
+        let engine = self.engine.clone();
         let state = self.state.clone();
         self.add_listener::<web_sys::TouchEvent, _>(
             EventListenTarget::VisibleCanvas,
             "touchend",
-            move |_event: TouchEvent| {
+            move |event: TouchEvent| {
                 // info!("touchend");
                 let mut state = state.borrow_mut();
                 state.dragging = false;
+                state.long_press = None;
+
+                // Lifting 1 of 2 fingers ends the pinch, but the remaining
+                // finger can carry on panning (without jumping)
+                if let Some(touch) = event.touches().get(0) {
+                    state.pinch_distance = None;
+                    state.touch_position = Some(Position {
+                        x: touch.client_x() as f64,
+                        y: touch.client_y() as f64,
+                    });
+                    state.touch_velocity = Position::default();
+                    return;
+                }
+
+                // Only carry on moving if the finger was moving when lifted
+                let was_panning = state.touch_position.is_some();
+                let ms_since_last_move =
+                    (Local::now().timestamp_millis() - state.ms_time_of_last_touchmove) as f64;
+                if was_panning && ms_since_last_move < TOUCH_VELOCITY_MAX_MS {
+                    let velocity = state.touch_velocity;
+                    engine
+                        .borrow_mut()
+                        .start_pan_inertia(velocity.x, velocity.y);
+                }
+                state.pinch_distance = None;
                 state.touch_position = None;
+                state.touch_velocity = Position::default();
             },
         );
     }
@@ -658,8 +1497,9 @@ impl OpenTimelineRendererHtmlCanvas {
     // TODO: add double & triple click detection (already have double & triple tap detection)
     /// Manage a click event on the timeline
     ///
-    /// Emit a custom event so that user can make use of the new
-    /// selection (eg fill a form)
+    /// Clicking an entity selects it, and clicking empty space clears the
+    /// selection.  Custom DOM events are dispatched (on the next draw) so that
+    /// the user can make use of the new selection (eg fill a form)
     pub fn listen_for_click(&mut self) {
         let drawing_surfaces = self.drawing_surfaces.clone();
         let engine = self.engine.clone();
@@ -671,11 +1511,39 @@ impl OpenTimelineRendererHtmlCanvas {
                 // info!("click");
                 let x = event.offset_x() as f64 * device_pixel_ratio();
                 let y = event.offset_y() as f64 * device_pixel_ratio();
-                if let Ok(colour_under_pointer) = colour_at_point(&drawing_surfaces, x, y) {
-                    if let Some(id) = state.borrow().map.get(&colour_under_pointer) {
+                let Ok(colour_under_pointer) = colour_at_point(&drawing_surfaces, x, y) else {
+                    return;
+                };
+                let mut engine = engine.borrow_mut();
+                match state.borrow().map.get(&colour_under_pointer) {
+                    Some(id) => {
                         debug!("Clicked on: {id:?}");
-                        engine.borrow_mut().click_on_entity(*id);
+                        engine.click_on_entity(*id);
+                        engine.set_ids_of_selected_entities(vec![*id]);
                     }
+                    None => engine.click_on_empty_space(),
+                }
+            },
+        );
+    }
+
+    /// Right-click on an entity for a context menu (instead of the browser's)
+    pub fn listen_for_contextmenu(&mut self) {
+        let drawing_surfaces = self.drawing_surfaces.clone();
+        let engine = self.engine.clone();
+        let state = self.state.clone();
+        self.add_listener::<web_sys::MouseEvent, _>(
+            EventListenTarget::VisibleCanvas,
+            "contextmenu",
+            move |event: MouseEvent| {
+                let x = event.offset_x() as f64 * device_pixel_ratio();
+                let y = event.offset_y() as f64 * device_pixel_ratio();
+                let Ok(colour_under_pointer) = colour_at_point(&drawing_surfaces, x, y) else {
+                    return;
+                };
+                if let Some(id) = state.borrow().map.get(&colour_under_pointer) {
+                    event.prevent_default();
+                    engine.borrow_mut().context_menu_on_entity(*id, x, y);
                 }
             },
         );
@@ -683,6 +1551,7 @@ impl OpenTimelineRendererHtmlCanvas {
 
     /// Manage a keydown event
     pub fn listen_for_keydown(&mut self) {
+        let engine = self.engine.clone();
         let drawing_surfaces = self.drawing_surfaces.clone();
         self.add_listener::<web_sys::KeyboardEvent, _>(
             EventListenTarget::Window,
@@ -705,148 +1574,65 @@ impl OpenTimelineRendererHtmlCanvas {
                         .request_fullscreen()
                         .unwrap();
                 }
+
+                // Clear the selection
+                if event.key() == "Escape" {
+                    engine.borrow_mut().clear_ids_of_selected_entities();
+                }
             },
         );
     }
 }
 
-// TODO: trait for frontends
+/// Draw the timeline onto the canvases (see [`canvas_2d::draw_timeline()`])
 fn draw_timeline(engine: Rc<RefCell<Engine>>, drawing_surfaces: Rc<RefCell<DrawingSurfaces>>) {
     // debug!("draw_timeline");
     set_canvas_sizes(&engine, &drawing_surfaces);
-    clear_timeline(&drawing_surfaces);
-    draw_backgrounds(&engine, &drawing_surfaces);
-    draw_lines(&engine, &drawing_surfaces);
-    draw_entities(&engine, &drawing_surfaces);
-    draw_headings(&engine, &drawing_surfaces);
-    // debug!("[exit] draw_timeline");
-}
-
-fn draw_headings(engine: &Rc<RefCell<Engine>>, drawing_surfaces: &Rc<RefCell<DrawingSurfaces>>) {
-    // debug!("draw_headings");
-    let headings_for_drawing = engine.borrow_mut().headings_for_drawing();
-    let font_size = engine.borrow().effective_font_size_px();
-    for mut heading in headings_for_drawing {
-        // Draw visible
-        draw_coloured_rect(&drawing_surfaces.borrow().visible.ctx, heading.text_box);
-
-        // Draw invisible
-        heading.text_box.fill_colour = Colour::from_rgb(0, 0, 0);
-        draw_coloured_rect(&drawing_surfaces.borrow().invisible.ctx, heading.text_box);
-
-        // Draw text
-        draw_text(
-            &drawing_surfaces.borrow().visible.ctx,
-            font_size,
-            heading.text,
-        );
-    }
-}
-
-fn draw_entities(engine: &Rc<RefCell<Engine>>, drawing_surfaces: &Rc<RefCell<DrawingSurfaces>>) {
-    // debug!("draw_entities");
     let surfaces = drawing_surfaces.borrow();
-    let visible_ctx = surfaces.visible.ctx.clone();
-    let invisible_ctx = surfaces.invisible.ctx.clone();
-    let entities_for_drawing = engine.borrow().entities_for_drawing();
-    let font_size = engine.borrow().effective_font_size_px();
-    // debug!(
-    //     "Entities for drawing count = {}",
-    //     entities_for_drawing.len()
-    // );
-    // debug!(
-    //     "Entity count in engine = {}",
-    //     engine.borrow().entity_count()
-    // );
-    for mut entity in entities_for_drawing {
-        // Draw visible
-        draw_coloured_rect(&visible_ctx, entity.text_box);
-        draw_coloured_rect(&visible_ctx, entity.date_box);
-        draw_text(&visible_ctx, font_size, entity.text);
-
-        // Draw invisible
-        let hidden_colour = Colour::from_any_string(entity.entity.name().as_str());
-        entity.text_box.fill_colour = hidden_colour;
-        entity.date_box.fill_colour = hidden_colour;
-
-        draw_coloured_rect(&invisible_ctx, entity.text_box);
-        draw_coloured_rect(&invisible_ctx, entity.date_box);
-    }
+    canvas_2d::draw_timeline(
+        &mut engine.borrow_mut(),
+        &surfaces.visible.ctx,
+        &surfaces.invisible.ctx,
+        surfaces.visible.canvas.width().into(),
+        surfaces.visible.canvas.height().into(),
+        device_pixel_ratio(),
+    );
+    // debug!("[exit] draw_timeline");
 }
 
-fn draw_backgrounds(engine: &Rc<RefCell<Engine>>, drawing_surfaces: &Rc<RefCell<DrawingSurfaces>>) {
-    // debug!("draw_backgrounds");
-    let visible_ctx = &drawing_surfaces.borrow().visible.ctx;
-    let visible_canvas_height = drawing_surfaces.borrow().visible.canvas.height();
-    let backgrounds_for_drawing = engine.borrow().backgrounds_for_drawing();
-    for background in backgrounds_for_drawing {
-        // Draw visible
-        let (r, g, b) = background.colour.as_rgb();
-        visible_ctx.set_fill_style_str(&format!("rgba({r}, {g}, {b}, 1.0)"));
-        visible_ctx.fill_rect(
-            background.x,
-            0.0,
-            background.width,
-            visible_canvas_height as f64,
-        );
-    }
+/// Serialise to a plain JS value (i.e. objects rather than `Map`s)
+pub(crate) fn to_js_value<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(JsValue::from)
 }
 
-fn draw_lines(engine: &Rc<RefCell<Engine>>, drawing_surfaces: &Rc<RefCell<DrawingSurfaces>>) {
-    // debug!("draw_lines");
-    let visible_ctx = &drawing_surfaces.borrow().visible.ctx;
-    let visible_canvas_height = drawing_surfaces.borrow().visible.canvas.height();
-    let lines_for_drawing = engine.borrow().lines_for_drawing();
-    for line in lines_for_drawing {
-        visible_ctx.begin_path();
-        visible_ctx.move_to(line.x, 0.0);
-        visible_ctx.line_to(line.x, visible_canvas_height as f64);
-        let (r, g, b) = line.style.colour.as_rgb();
-        visible_ctx.set_stroke_style_str(&format!("rgba({r}, {g}, {b}, 1.0)"));
-        visible_ctx.set_line_width(line.style.thickness);
-        visible_ctx.stroke();
+/// Dispatch a (bubbling) custom DOM event from the canvas
+fn dispatch_custom_event(canvas: &HtmlCanvasElement, event_name: &str, detail: &JsValue) {
+    let init = CustomEventInit::new();
+    init.set_bubbles(true);
+    init.set_detail(detail);
+    match CustomEvent::new_with_event_init_dict(event_name, &init) {
+        Ok(event) => {
+            if let Err(err) = canvas.dispatch_event(&event) {
+                error!("Unable to dispatch {event_name} event: {err:?}");
+            }
+        }
+        Err(err) => error!("Unable to create {event_name} event: {err:?}"),
     }
 }
 
-fn draw_coloured_rect(ctx: &CanvasRenderingContext2d, rect: FilledBox) {
-    // debug!("draw_coloured_rect");
-    // TODO: also the border colour and width
-    let (r, g, b) = rect.fill_colour.as_rgb();
-    ctx.set_fill_style_str(&format!("rgba({r}, {g}, {b}, 1.0)"));
-    let x = rect.position_and_size.position.x;
-    let y = rect.position_and_size.position.y;
-    let width = rect.position_and_size.width;
-    let height = rect.position_and_size.height;
-    ctx.fill_rect(x, y, width, height);
+/// Get the first 2 touches (if there are 2 or more)
+fn first_two_touches(event: &TouchEvent) -> Option<(Touch, Touch)> {
+    let touches = event.touches();
+    Some((touches.get(0)?, touches.get(1)?))
 }
 
-fn draw_text(ctx: &CanvasRenderingContext2d, font_size: f64, text: TextOut) {
-    // debug!("draw_text");
-    let x = text.top_left.x;
-    let mut y = text.top_left.y;
-    let measurements = measure_text(font_size, &text.text);
-    y += measurements.actual_bounding_box_ascent();
-    let (r, g, b) = text.colour.as_rgb();
-    ctx.set_fill_style_str(&format!("rgba({r}, {g}, {b}, 1.0)"));
-    let _ = ctx.fill_text(&text.text, x, y);
-}
-
-fn clear_timeline(drawing_surfaces: &Rc<RefCell<DrawingSurfaces>>) {
-    // debug!("clear_timeline");
-
-    let height = drawing_surfaces.borrow().visible.canvas.height().into();
-    let width = drawing_surfaces.borrow().visible.canvas.width().into();
-
-    drawing_surfaces
-        .borrow()
-        .visible
-        .ctx
-        .clear_rect(0.0, 0.0, width, height);
-    drawing_surfaces
-        .borrow()
-        .invisible
-        .ctx
-        .clear_rect(0.0, 0.0, width, height);
+/// The distance (CSS pixels) between 2 touches
+fn touch_distance(first: &Touch, second: &Touch) -> f64 {
+    let x = (first.client_x() - second.client_x()) as f64;
+    let y = (first.client_y() - second.client_y()) as f64;
+    x.hypot(y)
 }
 
 fn device_pixel_ratio() -> f64 {
@@ -873,6 +1659,130 @@ fn colour_at_point(
     Ok(colour)
 }
 
+/// Remember which entity each hidden canvas colour belongs to
+fn map_hidden_colours(state: &Rc<RefCell<State>>, entities: &[Entity]) {
+    let mut state = state.borrow_mut();
+    for entity in entities {
+        state.map.insert(
+            Colour::from_any_string(entity.name().as_str()),
+            entity.id().unwrap(),
+        );
+    }
+}
+
+/// The callbacks used to fetch a timeline's entities from the API
+#[cfg(feature = "net")]
+struct ApiFetchCallbacks {
+    /// Where the entities are fetched from (and the last ones received)
+    data_source: Rc<RefCell<ApiDataSource>>,
+
+    /// Called with the `Response` to a fetch
+    on_response: Closure<dyn FnMut(JsValue)>,
+
+    /// Called with anything that goes wrong fetching
+    on_error: Rc<Closure<dyn FnMut(JsValue)>>,
+}
+
+#[cfg(feature = "net")]
+impl ApiFetchCallbacks {
+    /// Fetch the entities, updating the engine if they have changed
+    fn fetch(&self) {
+        let url = self.data_source.borrow().entities_url();
+        let _ = web_sys::window()
+            .unwrap()
+            .fetch_with_str(&url)
+            .then(&self.on_response)
+            .catch(&self.on_error);
+    }
+}
+
+/// Loads a timeline's entities from the API into the engine, and refreshes
+/// them periodically (if asked to)
+#[cfg(feature = "net")]
+struct ApiLoader {
+    /// The callbacks used to fetch the entities
+    callbacks: Rc<ApiFetchCallbacks>,
+
+    /// Fetches the entities periodically (stops when dropped)
+    refresh: Option<Interval>,
+}
+
+#[cfg(feature = "net")]
+impl ApiLoader {
+    /// Create a loader that puts the entities from the data source into the
+    /// engine
+    fn new(
+        engine: Rc<RefCell<Engine>>,
+        state: Rc<RefCell<State>>,
+        data_source: ApiDataSource,
+    ) -> Self {
+        let data_source = Rc::new(RefCell::new(data_source));
+        let on_error: Rc<Closure<dyn FnMut(JsValue)>> = Rc::new(Closure::new(|err: JsValue| {
+            error!("Unable to fetch the entities: {err:?}");
+        }));
+
+        // The response's body (text) is received asynchronously too
+        let on_body: Closure<dyn FnMut(JsValue)> = {
+            let data_source = data_source.clone();
+            Closure::new(move |body: JsValue| {
+                let Some(body) = body.as_string() else {
+                    return;
+                };
+                let received = data_source.borrow_mut().receive(&body);
+                match received {
+                    Ok(Some(mut entities)) => {
+                        assign_render_ids(&mut entities);
+                        map_hidden_colours(&state, &entities);
+                        engine.borrow_mut().sync_entities(entities);
+                    }
+                    Ok(None) => debug!("The fetched entities are unchanged"),
+                    Err(err) => error!("Unable to parse the fetched entities: {err}"),
+                }
+            })
+        };
+
+        let on_response: Closure<dyn FnMut(JsValue)> = {
+            let on_error = on_error.clone();
+            Closure::new(move |response: JsValue| {
+                let response: Response = response.unchecked_into();
+                if !response.ok() {
+                    error!("Unable to fetch the entities ({})", response.status());
+                    return;
+                }
+                match response.text() {
+                    Ok(body) => {
+                        let _ = body.then(&on_body).catch(&on_error);
+                    }
+                    Err(err) => error!("Unable to read the fetched entities: {err:?}"),
+                }
+            })
+        };
+
+        Self {
+            callbacks: Rc::new(ApiFetchCallbacks {
+                data_source,
+                on_response,
+                on_error,
+            }),
+            refresh: None,
+        }
+    }
+
+    /// Fetch the entities every `interval_ms` (if given)
+    fn refreshing_every(mut self, interval_ms: Option<u32>) -> Self {
+        self.refresh = interval_ms.map(|interval_ms| {
+            let callbacks = self.callbacks.clone();
+            Interval::new(interval_ms, move || callbacks.fetch())
+        });
+        self
+    }
+
+    /// Fetch the entities now
+    fn fetch(&self) {
+        self.callbacks.fetch();
+    }
+}
+
 fn set_canvas_sizes(engine: &Rc<RefCell<Engine>>, drawing_surfaces: &Rc<RefCell<DrawingSurfaces>>) {
     let dpr = device_pixel_ratio();
 
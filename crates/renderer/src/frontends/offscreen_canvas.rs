@@ -0,0 +1,323 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! The offscreen canvas frontend.
+//!
+//! Renders into an `OffscreenCanvas` from a web worker so that large timelines
+//! don't block the page's main thread.  The page transfers control of its
+//! `<canvas>` to the worker and then drives the renderer with messages (see
+//! `js/OpenTimelineWorkerRenderer.js` and `js/worker.js`).
+//!
+//! The (main thread) [`OpenTimelineRendererHtmlCanvas`] remains the default.
+//!
+//! [`OpenTimelineRendererHtmlCanvas`]: super::html_canvas::OpenTimelineRendererHtmlCanvas
+//!
+
+use super::canvas_2d;
+use super::html_canvas::{
+    EntityClickDetail, EntityContextMenuDetail, EntitySelectDetail, to_js_value,
+};
+use crate::{Colour, Engine, FontConfig, TimelineInteractionEvent, Viewport, assign_render_ids};
+use open_timeline_core::{Entity, HasIdAndName, OpenTimelineId};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use tracing::debug;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{OffscreenCanvas, OffscreenCanvasRenderingContext2d};
+
+/// The messages the worker accepts from the page.  Positions and distances
+/// are in CSS pixels (i.e. as found on DOM events).
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WorkerMessage {
+    /// Replace all entities
+    SetEntities { entities: Vec<Entity> },
+
+    /// Add entities to those already on the timeline
+    AddEntities { entities: Vec<Entity> },
+
+    /// Remove all entities
+    ClearEntities,
+
+    /// The size of the `<canvas>` on the page changed
+    Resize {
+        width: f64,
+        height: f64,
+        device_pixel_ratio: f64,
+    },
+
+    /// Move the timeline
+    Pan { dx: f64, dy: f64 },
+
+    /// Zoom in (factor greater than 1) or out (factor less than 1) around a
+    /// point
+    Zoom { factor: f64, x: f64, y: f64 },
+
+    /// The user clicked the canvas
+    Click { x: f64, y: f64 },
+
+    /// The user right-clicked (or long-pressed) the canvas
+    ContextMenu { x: f64, y: f64 },
+
+    /// Use a different font (families in order of preference, and weight)
+    SetFont { font: FontConfig },
+}
+
+/// The messages the worker posts back to the page.  `type` is the name of the
+/// DOM event to dispatch (the same as those dispatched by the HTML canvas
+/// frontend), and `detail` is its detail.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "detail")]
+enum WorkerReply<'a> {
+    #[serde(rename = "ot-entity-click")]
+    EntityClick(EntityClickDetail<'a>),
+
+    #[serde(rename = "ot-entity-select")]
+    EntitySelect(EntitySelectDetail<'a>),
+
+    #[serde(rename = "ot-entity-context-menu")]
+    EntityContextMenu(EntityContextMenuDetail<'a>),
+
+    #[serde(rename = "ot-viewport-change")]
+    ViewportChange(Viewport),
+}
+
+/// An offscreen canvas and associated context
+#[derive(Debug, Clone)]
+struct OffscreenCanvasAndContext {
+    /// The canvas
+    canvas: OffscreenCanvas,
+
+    /// The context for the associated canvas
+    ctx: OffscreenCanvasRenderingContext2d,
+}
+
+impl OffscreenCanvasAndContext {
+    fn new(canvas: OffscreenCanvas) -> Result<Self, JsValue> {
+        let ctx = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("Unable to get a 2D offscreen canvas context"))?
+            .dyn_into::<OffscreenCanvasRenderingContext2d>()?;
+        Ok(Self { canvas, ctx })
+    }
+
+    fn set_size(&self, width: u32, height: u32) {
+        self.canvas.set_width(width);
+        self.canvas.set_height(height);
+    }
+}
+
+/// The offscreen canvas engine for use in a web worker
+#[wasm_bindgen]
+pub struct OpenTimelineRendererOffscreenCanvas {
+    /// The underlying timeline [`Engine`]
+    engine: Engine,
+
+    /// The canvas transferred from the page
+    visible: OffscreenCanvasAndContext,
+
+    /// The canvas that entities are drawn on in their hidden colours (to find
+    /// what's under the pointer)
+    invisible: OffscreenCanvasAndContext,
+
+    /// The page's device pixel ratio (shared with the engine's text measuring
+    /// function)
+    device_pixel_ratio: Rc<Cell<f64>>,
+
+    /// Maps the colours on the hidden canvas to an entity's ID
+    map: HashMap<Colour, OpenTimelineId>,
+
+    /// The viewport last reported in an `ot-viewport-change` reply
+    last_viewport: Option<Viewport>,
+}
+
+#[wasm_bindgen]
+impl OpenTimelineRendererOffscreenCanvas {
+    /// Create a new offscreen canvas engine that draws to the `canvas`
+    /// transferred from the page (see `HTMLCanvasElement.transferControlToOffscreen()`)
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas: OffscreenCanvas, device_pixel_ratio: f64) -> Result<Self, JsValue> {
+        let visible = OffscreenCanvasAndContext::new(canvas)?;
+        let invisible = OffscreenCanvasAndContext::new(OffscreenCanvas::new(
+            visible.canvas.width(),
+            visible.canvas.height(),
+        )?)?;
+        let device_pixel_ratio = Rc::new(Cell::new(device_pixel_ratio));
+
+        let measuring_ctx = visible.ctx.clone();
+        let measuring_dpr = device_pixel_ratio.clone();
+        let mut engine = Engine::new(move |font_size, text| {
+            canvas_2d::measure_text_for_engine(
+                &measuring_ctx,
+                font_size * measuring_dpr.get(),
+                &text,
+            )
+        });
+        engine.set_canvas_max(
+            visible.canvas.width().into(),
+            visible.canvas.height().into(),
+        );
+
+        Ok(Self {
+            engine,
+            visible,
+            invisible,
+            device_pixel_ratio,
+            map: HashMap::new(),
+            last_viewport: None,
+        })
+    }
+
+    /// Handle a message from the page (e.g. `{type: "pan", dx: 10, dy: 0}`).
+    /// Returns the replies (if any) to post back to the page.
+    #[wasm_bindgen]
+    pub fn handle_message(&mut self, message: JsValue) -> Result<JsValue, JsValue> {
+        let message: WorkerMessage = serde_wasm_bindgen::from_value(message)?;
+        let dpr = self.device_pixel_ratio.get();
+        match message {
+            WorkerMessage::SetEntities { entities } => {
+                self.clear_entities();
+                self.add_entities(entities);
+            }
+            WorkerMessage::AddEntities { entities } => self.add_entities(entities),
+            WorkerMessage::ClearEntities => self.clear_entities(),
+            WorkerMessage::Resize {
+                width,
+                height,
+                device_pixel_ratio,
+            } => {
+                self.device_pixel_ratio.set(device_pixel_ratio);
+                let width = (width * device_pixel_ratio) as u32;
+                let height = (height * device_pixel_ratio) as u32;
+                self.visible.set_size(width, height);
+                self.invisible.set_size(width, height);
+                self.engine.set_canvas_max(width.into(), height.into());
+                self.engine.text_measurement_changed();
+            }
+            WorkerMessage::Pan { dx, dy } => {
+                self.engine.stop_pan_inertia();
+                self.engine.add_to_global_offset(dx * dpr, dy * dpr);
+            }
+            WorkerMessage::Zoom { factor, x, y } => {
+                self.engine.zoom_by(factor, x * dpr, y * dpr);
+            }
+            WorkerMessage::Click { x, y } => match self.entity_at_point(x * dpr, y * dpr) {
+                Some(id) => {
+                    debug!("Clicked on: {id:?}");
+                    self.engine.click_on_entity(id);
+                    self.engine.set_ids_of_selected_entities(vec![id]);
+                }
+                None => self.engine.click_on_empty_space(),
+            },
+            WorkerMessage::ContextMenu { x, y } => {
+                if let Some(id) = self.entity_at_point(x * dpr, y * dpr) {
+                    self.engine.context_menu_on_entity(id, x * dpr, y * dpr);
+                }
+            }
+            WorkerMessage::SetFont { font } => canvas_2d::use_font(&mut self.engine, font),
+        }
+        self.replies()
+    }
+
+    /// Draw the timeline (call once per frame, e.g. using
+    /// `requestAnimationFrame()` in the worker)
+    #[wasm_bindgen]
+    pub fn draw(&mut self) {
+        canvas_2d::draw_timeline(
+            &mut self.engine,
+            &self.visible.ctx,
+            &self.invisible.ctx,
+            self.visible.canvas.width().into(),
+            self.visible.canvas.height().into(),
+            self.device_pixel_ratio.get(),
+        );
+    }
+}
+
+impl OpenTimelineRendererOffscreenCanvas {
+    fn clear_entities(&mut self) {
+        self.map.clear();
+        self.engine.clear_entities();
+    }
+
+    fn add_entities(&mut self, mut entities: Vec<Entity>) {
+        assign_render_ids(&mut entities);
+        for entity in &entities {
+            if let Some(id) = entity.id() {
+                self.map
+                    .insert(Colour::from_any_string(entity.name().as_str()), id);
+            }
+        }
+        self.engine.add_entities(entities);
+    }
+
+    /// Get the ID of the entity drawn at the point (canvas pixels) if there is
+    /// one
+    fn entity_at_point(&self, x: f64, y: f64) -> Option<OpenTimelineId> {
+        let image_data = self.invisible.ctx.get_image_data(x, y, 1.0, 1.0).ok()?;
+        let pixels = image_data.data();
+        let colour = Colour::from_rgb(pixels[0], pixels[1], pixels[2]);
+        self.map.get(&colour).copied()
+    }
+
+    /// The replies for the interactions with (and changes to) the timeline
+    /// since the last message
+    fn replies(&mut self) -> Result<JsValue, JsValue> {
+        let interaction_events: Vec<_> = self.engine.drain_interaction_events().collect();
+        let viewport = self.engine.viewport();
+        let viewport_changed = self.last_viewport != Some(viewport);
+        self.last_viewport = Some(viewport);
+
+        let mut replies = Vec::new();
+        for interaction_event in &interaction_events {
+            let (id, clicks) = match interaction_event {
+                TimelineInteractionEvent::SingleClick(id) => (*id, 1),
+                TimelineInteractionEvent::DoubleClick(id) => (*id, 2),
+                TimelineInteractionEvent::TripleClick(id) => (*id, 3),
+                TimelineInteractionEvent::SelectionChange(ids) => {
+                    replies.push(WorkerReply::EntitySelect(EntitySelectDetail {
+                        ids,
+                        entities: ids
+                            .iter()
+                            .filter_map(|id| self.engine.entity(*id))
+                            .collect(),
+                    }));
+                    continue;
+                }
+                TimelineInteractionEvent::ContextMenu(id, x, y) => {
+                    let dpr = self.device_pixel_ratio.get();
+                    replies.push(WorkerReply::EntityContextMenu(EntityContextMenuDetail {
+                        id: *id,
+                        x: x / dpr,
+                        y: y / dpr,
+                        entity: self.engine.entity(*id),
+                    }));
+                    continue;
+                }
+                // The worker only runs when it is sent a message, so it can't
+                // debounce viewport changes (`ot-viewport-change` is sent for
+                // every change instead) or animate stories (which it has no
+                // way to play).
+                // Edit mode and creating entities are only supported by the
+                // desktop frontend.
+                TimelineInteractionEvent::Hover(_)
+                | TimelineInteractionEvent::ViewportChanged { .. }
+                | TimelineInteractionEvent::StoryStepChange(_)
+                | TimelineInteractionEvent::EmptySpaceDoubleClick(_)
+                | TimelineInteractionEvent::DatesChanged { .. } => continue,
+            };
+            replies.push(WorkerReply::EntityClick(EntityClickDetail {
+                id,
+                clicks,
+                entity: self.engine.entity(id),
+            }));
+        }
+        if viewport_changed {
+            replies.push(WorkerReply::ViewportChange(viewport));
+        }
+        to_js_value(&replies)
+    }
+}
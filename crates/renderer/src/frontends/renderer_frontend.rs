@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! What a frontend must be able to draw, and the order in which a timeline is
+//! drawn with it.
+//!
+//! A new backend (e.g. OpenGL) only needs to implement [`RendererFrontend`]
+//! and call [`draw_timeline()`].
+//!
+
+use crate::{Background, Engine, EntityOut, FilledBox, Heading, TextOut, VerticalLine};
+
+/// A surface a timeline can be drawn onto
+pub trait RendererFrontend {
+    /// Clear whatever was drawn before (the surface is `width` x `height`)
+    fn clear(&mut self, width: f64, height: f64);
+
+    /// Draw a background stripe (from the top to the bottom of the surface)
+    fn draw_background(&mut self, background: &Background, height: f64);
+
+    /// Draw a vertical line (from the top to the bottom of the surface)
+    fn draw_line(&mut self, line: &VerticalLine, height: f64);
+
+    /// Draw a filled box (with its border, if it has one)
+    fn draw_rect(&mut self, rect: &FilledBox);
+
+    /// Draw text (a line at a time, as the engine may have wrapped it)
+    fn draw_text(&mut self, text: &TextOut);
+
+    /// Called once an entity has been drawn (e.g. so that the frontend can
+    /// make it clickable)
+    fn entity_drawn(&mut self, _entity: &EntityOut) {}
+
+    /// Called once a heading has been drawn (e.g. so that the frontend can
+    /// stop clicks going through it to the entities underneath)
+    fn heading_drawn(&mut self, _heading: &Heading) {}
+
+    /// Show what has been drawn
+    fn present(&mut self) {}
+}
+
+/// Draw the timeline with the frontend
+pub fn draw_timeline<F: RendererFrontend>(
+    engine: &mut Engine,
+    frontend: &mut F,
+    width: f64,
+    height: f64,
+) {
+    frontend.clear(width, height);
+
+    // Backgrounds
+    for background in engine.backgrounds_for_drawing() {
+        frontend.draw_background(&background, height);
+    }
+
+    // Lines
+    for line in engine.lines_for_drawing() {
+        frontend.draw_line(&line, height);
+    }
+
+    // Entities
+    for entity in engine.entities_for_drawing() {
+        if let Some(highlight) = &entity.highlight {
+            frontend.draw_rect(highlight);
+        }
+        frontend.draw_rect(&entity.text_box);
+        frontend.draw_rect(&entity.date_box);
+        frontend.draw_text(&entity.text);
+        frontend.entity_drawn(&entity);
+    }
+
+    // "Now" line (over the entities)
+    if let Some(line) = engine.now_line_for_drawing() {
+        frontend.draw_line(&line, height);
+    }
+
+    // Headings
+    for heading in engine.headings_for_drawing() {
+        frontend.draw_rect(&heading.text_box);
+        frontend.draw_text(&heading.text);
+        frontend.heading_drawn(&heading);
+    }
+
+    frontend.present();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frontends::svg::approximate_measure_text;
+    use crate::{SelectionHighlight, SelectionHighlightStyle};
+    use open_timeline_core::HasIdAndName;
+    use open_timeline_core::{Date, Entity, Name, OpenTimelineId};
+
+    /// Records what it's asked to draw
+    #[derive(Default)]
+    struct RecordingFrontend {
+        calls: Vec<&'static str>,
+    }
+
+    impl RendererFrontend for RecordingFrontend {
+        fn clear(&mut self, _width: f64, _height: f64) {
+            self.calls.push("clear");
+        }
+        fn draw_background(&mut self, _background: &Background, _height: f64) {
+            self.calls.push("background");
+        }
+        fn draw_line(&mut self, _line: &VerticalLine, _height: f64) {
+            self.calls.push("line");
+        }
+        fn draw_rect(&mut self, _rect: &FilledBox) {
+            self.calls.push("rect");
+        }
+        fn draw_text(&mut self, _text: &TextOut) {
+            self.calls.push("text");
+        }
+        fn entity_drawn(&mut self, _entity: &EntityOut) {
+            self.calls.push("entity");
+        }
+        fn present(&mut self) {
+            self.calls.push("present");
+        }
+    }
+
+    #[test]
+    fn entities_are_drawn_between_clear_and_present() {
+        let mut engine = Engine::new(approximate_measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        engine.set_entities(vec![
+            Entity::from(
+                Some(OpenTimelineId::new()),
+                Name::from("Ada Lovelace").unwrap(),
+                Date::from(None, None, 1815).unwrap(),
+                None,
+                None,
+            )
+            .unwrap(),
+        ]);
+        let mut frontend = RecordingFrontend::default();
+        draw_timeline(&mut engine, &mut frontend, 800.0, 400.0);
+
+        let calls = frontend.calls;
+        assert_eq!(calls.first(), Some(&"clear"));
+        assert_eq!(calls.last(), Some(&"present"));
+        let entity = calls.iter().position(|call| *call == "entity").unwrap();
+        assert_eq!(calls[entity - 3..entity], ["rect", "rect", "text"]);
+
+        // A selected entity's glow is drawn behind it
+        let id = engine.entities_for_drawing()[0].entity.id().unwrap();
+        engine.set_ids_of_selected_entities(vec![id]);
+        engine.set_selection_highlight(SelectionHighlight {
+            style: SelectionHighlightStyle::Glow,
+            ..Default::default()
+        });
+        let mut frontend = RecordingFrontend::default();
+        draw_timeline(&mut engine, &mut frontend, 800.0, 400.0);
+        let calls = frontend.calls;
+        let entity = calls.iter().position(|call| *call == "entity").unwrap();
+        assert_eq!(calls[entity - 4..entity], ["rect", "rect", "rect", "text"]);
+    }
+}
@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! The SVG frontend.
+//!
+//! Renders a timeline to an SVG document (e.g. for server-side rendering of
+//! previews).  There's no interaction, so only what's drawn is needed from the
+//! [`Engine`].
+//!
+
+use crate::frontends::renderer_frontend::{RendererFrontend, draw_timeline};
+use crate::{Background, Colour, Engine, FilledBox, TextOut, VerticalLine};
+use std::fmt::Write;
+
+/// Roughly how wide a character is relative to the font size (used by
+/// [`approximate_measure_text()`])
+const APPROXIMATE_CHAR_WIDTH_EM: f64 = 0.55;
+
+/// A text measuring function for the [`Engine`] when there's no font to
+/// measure with (e.g. without the `default-measurer` feature).  Assumes every
+/// character is the same width.
+pub fn approximate_measure_text(font_size: f64, text: String) -> (f64, f64) {
+    let width = text.chars().count() as f64 * font_size * APPROXIMATE_CHAR_WIDTH_EM;
+    (width, font_size)
+}
+
+/// Writes what's drawn as SVG elements
+struct SvgFrontend {
+    svg: String,
+
+    /// The font family (a CSS font family list, already escaped)
+    font_family: String,
+
+    /// The font size of text that doesn't give its own
+    font_size: f64,
+}
+
+impl RendererFrontend for SvgFrontend {
+    fn clear(&mut self, width: f64, height: f64) {
+        self.svg.clear();
+        let _ = writeln!(
+            self.svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="{}" font-size="{}">"#,
+            self.font_family, self.font_size
+        );
+    }
+
+    fn draw_background(&mut self, background: &Background, height: f64) {
+        let _ = writeln!(
+            self.svg,
+            r#"<rect x="{}" y="0" width="{}" height="{height}" fill="{}"/>"#,
+            background.x,
+            background.width,
+            svg_colour(background.colour)
+        );
+    }
+
+    fn draw_line(&mut self, line: &VerticalLine, height: f64) {
+        let _ = writeln!(
+            self.svg,
+            r#"<line x1="{x}" y1="0" x2="{x}" y2="{height}" stroke="{}" stroke-width="{}"/>"#,
+            svg_colour(line.style.colour),
+            line.style.thickness,
+            x = line.x,
+        );
+    }
+
+    fn draw_rect(&mut self, filled_box: &FilledBox) {
+        let position_and_size = filled_box.position_and_size;
+        let border = match filled_box.border_style {
+            Some(border) => format!(
+                r#" stroke="{}" stroke-width="{}""#,
+                svg_colour(border.colour),
+                border.thickness
+            ),
+            None => String::new(),
+        };
+        let opacity = match filled_box.fill_alpha < 1.0 {
+            true => format!(r#" fill-opacity="{}""#, filled_box.fill_alpha),
+            false => String::new(),
+        };
+        let _ = writeln!(
+            self.svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"{opacity}{border}/>"#,
+            position_and_size.position.x,
+            position_and_size.position.y,
+            position_and_size.width,
+            position_and_size.height,
+            svg_colour(filled_box.fill_colour),
+        );
+    }
+
+    fn draw_text(&mut self, text: &TextOut) {
+        // The engine positions text by its top left corner (each line of
+        // wrapped text is a line height below the previous one)
+        for (i, line) in text.lines.iter().enumerate() {
+            let _ = writeln!(
+                self.svg,
+                r#"<text x="{}" y="{}" font-size="{}" fill="{}" dominant-baseline="text-before-edge">{}</text>"#,
+                text.top_left.x,
+                text.top_left.y + (i as f64 * text.line_height),
+                text.font_size,
+                svg_colour(text.colour),
+                escape_xml(line),
+            );
+        }
+    }
+
+    fn present(&mut self) {
+        self.svg.push_str("</svg>\n");
+    }
+}
+
+/// Render the timeline as an SVG document of the given size.  The font family
+/// (a CSS font family list) should match the font the engine measures text
+/// with.
+pub fn render_svg(engine: &mut Engine, width: f64, height: f64, font_family: &str) -> String {
+    engine.set_canvas_max(width, height);
+    let mut frontend = SvgFrontend {
+        svg: String::new(),
+        font_family: escape_xml(font_family),
+        font_size: engine.effective_font_size_px(),
+    };
+    draw_timeline(engine, &mut frontend, width, height);
+    frontend.svg
+}
+
+/// The colour as an SVG colour string
+fn svg_colour(colour: Colour) -> String {
+    let (r, g, b) = colour.as_rgb();
+    format!("rgb({r}, {g}, {b})")
+}
+
+/// Escape the characters that can't appear as-is in XML text
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use open_timeline_core::{Date, Entity, Name, OpenTimelineId};
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(
+            escape_xml(r#"A & B <"C">"#),
+            "A &amp; B &lt;&quot;C&quot;&gt;"
+        );
+    }
+
+    #[test]
+    fn renders_entity_names() {
+        let mut engine = Engine::new(approximate_measure_text);
+        engine.set_canvas_max(800.0, 400.0);
+        engine.set_entities(vec![
+            Entity::from(
+                Some(OpenTimelineId::new()),
+                Name::from("Fish & Chips").unwrap(),
+                Date::from(None, None, 1860).unwrap(),
+                None,
+                None,
+            )
+            .unwrap(),
+        ]);
+        let svg = render_svg(&mut engine, 800.0, 400.0, "serif");
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("Fish &amp; Chips"));
+    }
+}
@@ -15,17 +15,23 @@
 //! - Handling and emitting events
 //! - Providing a simple API for frontends
 //!
-//! The rest of the crate holds code for various frontends.  There are currently
-//! only 2, but the number will grow over time (e.g. SVG, OpenGL, and WebGL).
-//! The 2 currently offered frontends are:
+//! The rest of the crate holds code for various frontends, each of which
+//! implements [`RendererFrontend`].  There are currently only 4, but the number
+//! will grow over time (e.g. OpenGL, and WebGL).  The 4 currently offered
+//! frontends are:
 //!
 //! - HTML Canvas for browser rendering
+//! - Offscreen Canvas for browser rendering from a web worker (for large
+//!   timelines)
 //! - `egui` for native desktop rendering
+//! - SVG for rendering to an image (e.g. on a server)
 //!
 //! ## Usage
 //!
 //! To use in a native `egui` desktop application the crate can simply be
-//! included like any other crate.
+//! included like any other crate, with
+//! [`TimelineWidget`](frontends::egui::TimelineWidget) offering a timeline that
+//! can be added to a `Ui` like any other widget.
 //!
 //! To use in a browser one can use the following to compile to WASM:
 //!
@@ -35,14 +41,26 @@
 //!
 //! One can then use the JavaScript module provided for easier interfacing.
 //!
+//! With the `net` feature enabled, the HTML Canvas frontend can also fetch a
+//! timeline's entities from the OpenTimeline API itself (see
+//! [`OpenTimelineRendererHtmlCanvas::load_from_api()`]), e.g.
+//!
+//! ```sh
+//! wasm-pack build --target web --features net
+//! ```
+//!
 
 extern crate console_error_panic_hook;
 
 pub mod colour;
 pub mod colours;
+#[cfg(feature = "net")]
+pub mod data_source;
 pub mod engine;
 pub mod frontends;
 
 pub use colour::*;
 pub use engine::*;
 pub use frontends::html_canvas::OpenTimelineRendererHtmlCanvas;
+pub use frontends::offscreen_canvas::OpenTimelineRendererOffscreenCanvas;
+pub use frontends::renderer_frontend::{RendererFrontend, draw_timeline};
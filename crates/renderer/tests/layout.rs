@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Layout tests for the [`Engine`].
+//!
+//! Uses a deterministic text measurer so that layouts are the same on every
+//! machine.  Golden snapshots of `entities_for_drawing()` live in
+//! `tests/golden`, and can be regenerated (after checking the change is
+//! intended) with:
+//!
+//! ```sh
+//! UPDATE_GOLDEN=1 cargo test -p open-timeline-renderer --test layout
+//! ```
+//!
+
+use open_timeline_core::{Date, Entity, Name, OpenTimelineId};
+use open_timeline_renderer::frontends::svg::approximate_measure_text;
+use open_timeline_renderer::{Engine, EntityOut, Heading};
+use proptest::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::path::PathBuf;
+
+/// Big enough that every entity is in view (so nothing is culled)
+const CANVAS_SIZE: f64 = 1_000_000.0;
+
+/// Allowance for floating point error when comparing positions
+const EPSILON: f64 = 1e-6;
+
+/// The seeds used for the golden snapshots
+const GOLDEN_SEEDS: [u64; 3] = [1, 2, 3];
+
+/// An entity whose ID is derived from `index` (so that layouts are
+/// reproducible)
+fn entity(index: usize, start: i64, end: Option<i64>) -> Entity {
+    Entity::from(
+        Some(OpenTimelineId::from(format!("00000000-0000-4000-8000-{index:012}")).unwrap()),
+        Name::from(format!("Entity {index}")).unwrap(),
+        Date::from(None, None, start).unwrap(),
+        end.map(|end| Date::from(None, None, end).unwrap()),
+        None,
+    )
+    .unwrap()
+}
+
+/// Lay out the entities (with everything in view)
+fn laid_out(entities: Vec<Entity>) -> Engine {
+    let mut engine = Engine::new(approximate_measure_text);
+    engine.set_canvas_max(CANVAS_SIZE, CANVAS_SIZE);
+    engine.set_entities(entities);
+    engine
+}
+
+/// Entities with random dates (the same for a given seed)
+fn seeded_entities(seed: u64) -> Vec<Entity> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..25)
+        .map(|index| {
+            let start = rng.gen_range(1500..2000);
+            let end = rng.gen_bool(0.8).then(|| start + rng.gen_range(0..100));
+            entity(index, start, end)
+        })
+        .collect()
+}
+
+/// The horizontal extent of the entity (text and date boxes)
+fn x_extent(entity: &EntityOut) -> (f64, f64) {
+    let text_box = entity.text_box.position_and_size;
+    let date_box = entity.date_box.position_and_size;
+    (
+        text_box.position.x.min(date_box.position.x),
+        text_box.max_x().max(date_box.max_x()),
+    )
+}
+
+/// Check that no two entities in the same row overlap
+fn assert_no_overlaps_in_rows(entities: &[EntityOut]) {
+    for (i, a) in entities.iter().enumerate() {
+        for b in &entities[(i + 1)..] {
+            let a_y = a.text_box.position_and_size.position.y;
+            let b_y = b.text_box.position_and_size.position.y;
+            if (a_y - b_y).abs() > EPSILON {
+                continue;
+            }
+            let (a_min, a_max) = x_extent(a);
+            let (b_min, b_max) = x_extent(b);
+            assert!(
+                a_max <= b_min + EPSILON || b_max <= a_min + EPSILON,
+                "{:?} ({a_min}..{a_max}) overlaps {:?} ({b_min}..{b_max}) in row y = {a_y}",
+                a.entity,
+                b.entity,
+            );
+        }
+    }
+}
+
+/// Check that every entity's dates are drawn within the timeline's decades
+fn assert_within_decade_range(headings: &[Heading], entities: &[EntityOut]) {
+    let min_x = headings
+        .iter()
+        .map(|heading| heading.text_box.position_and_size.position.x)
+        .fold(f64::INFINITY, f64::min);
+    let max_x = headings
+        .iter()
+        .map(|heading| heading.text_box.position_and_size.max_x())
+        .fold(f64::NEG_INFINITY, f64::max);
+    for entity in entities {
+        let date_box = entity.date_box.position_and_size;
+        assert!(
+            date_box.position.x >= min_x - EPSILON && date_box.max_x() <= max_x + EPSILON,
+            "{:?} ({}..{}) is outside the decade range ({min_x}..{max_x})",
+            entity.entity,
+            date_box.position.x,
+            date_box.max_x(),
+        );
+    }
+}
+
+fn golden_path(seed: u64) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("entities_for_drawing_seed_{seed}.json"))
+}
+
+#[test]
+fn entities_for_drawing_matches_golden_snapshots() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    for seed in GOLDEN_SEEDS {
+        let engine = laid_out(seeded_entities(seed));
+        let actual = serde_json::to_string_pretty(&engine.entities_for_drawing()).unwrap() + "\n";
+        let path = golden_path(seed);
+        if update {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, actual).unwrap();
+            continue;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("missing {path:?} (run with UPDATE_GOLDEN=1)"));
+        assert!(
+            actual == expected,
+            "layout for seed {seed} differs from {path:?} (run with UPDATE_GOLDEN=1 if intended)"
+        );
+    }
+}
+
+#[test]
+fn seeded_layouts_hold_invariants() {
+    for seed in GOLDEN_SEEDS {
+        let mut engine = laid_out(seeded_entities(seed));
+        let headings = engine.headings_for_drawing();
+        let entities = engine.entities_for_drawing();
+        assert_eq!(entities.len(), 25);
+        assert_no_overlaps_in_rows(&entities);
+        assert_within_decade_range(&headings, &entities);
+    }
+}
+
+proptest! {
+    #[test]
+    fn layouts_hold_invariants(
+        dates in prop::collection::vec((1000i64..2020, prop::option::of(0i64..200)), 1..40)
+    ) {
+        let entities = dates
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, length))| entity(index, start, length.map(|length| start + length)))
+            .collect::<Vec<_>>();
+        let count = entities.len();
+        let mut engine = laid_out(entities);
+        let headings = engine.headings_for_drawing();
+        let entities = engine.entities_for_drawing();
+        prop_assert_eq!(entities.len(), count);
+        assert_no_overlaps_in_rows(&entities);
+        assert_within_decade_range(&headings, &entities);
+    }
+}
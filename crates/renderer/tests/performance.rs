@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT
+
+//!
+//! Performance budgets for the [`Engine`], so that layout changes that would
+//! make large timelines sluggish to interact with fail the tests.
+//!
+//! The budgets are deliberately generous (so that slow CI machines pass) - the
+//! benchmarks in `benches/engine.rs` are for measuring, these are for catching
+//! regressions of an order of magnitude or so (e.g. an accidentally quadratic
+//! layout).
+//!
+
+use open_timeline_core::{Date, Entity, Name, OpenTimelineId};
+use open_timeline_renderer::Engine;
+use open_timeline_renderer::frontends::svg::approximate_measure_text;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// The number of entities the budgets are for
+const ENTITY_COUNT: usize = 10_000;
+
+/// How long re-laying out [`ENTITY_COUNT`] entities may take (unoptimised
+/// builds are much slower, so have a bigger budget)
+const RE_CALCULATE_BUDGET: Duration = if cfg!(debug_assertions) {
+    Duration::from_millis(2_000)
+} else {
+    Duration::from_millis(200)
+};
+
+/// The number of times to time the work (the fastest is used, so that a
+/// briefly busy machine doesn't cause a failure)
+const ATTEMPTS: usize = 3;
+
+/// How many allocations drawing may make (the list of entities to draw, and
+/// its growth)
+const ENTITIES_FOR_DRAWING_ALLOCATION_BUDGET: usize = 32;
+
+/// Counts the allocations made by each thread (so that tests running at the
+/// same time don't affect each other's counts)
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of allocations the current thread makes doing the work
+fn allocations<T, F: FnOnce() -> T>(work: F) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let output = work();
+    (output, ALLOCATIONS.with(Cell::get) - before)
+}
+
+/// Entities with random dates (the same on every run)
+fn entities(count: usize) -> Vec<Entity> {
+    let mut rng = StdRng::seed_from_u64(0);
+    (0..count)
+        .map(|index| {
+            let start = rng.gen_range(-1000..2000);
+            let end = rng.gen_bool(0.8).then(|| start + rng.gen_range(0..100));
+            Entity::from(
+                Some(OpenTimelineId::new()),
+                Name::from(format!("Entity {index}")).unwrap(),
+                Date::from(None, None, start).unwrap(),
+                end.map(|end| Date::from(None, None, end).unwrap()),
+                None,
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+/// The fastest of [`ATTEMPTS`] runs of the work
+fn fastest<F: FnMut()>(mut work: F) -> Duration {
+    (0..ATTEMPTS)
+        .map(|_| {
+            let start = Instant::now();
+            work();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+#[test]
+fn re_calculate_is_within_budget() {
+    let mut engine = Engine::new(approximate_measure_text);
+    engine.set_canvas_max(1_920.0, 1_080.0);
+    engine.set_entities(entities(ENTITY_COUNT));
+
+    // `text_measurement_changed()` re-runs the layout calculations (having
+    // emptied the text measurement cache, so this is the worst case)
+    let elapsed = fastest(|| engine.text_measurement_changed());
+    assert!(
+        elapsed <= RE_CALCULATE_BUDGET,
+        "Laying out {ENTITY_COUNT} entities took {elapsed:?} (the budget is {RE_CALCULATE_BUDGET:?})"
+    );
+}
+
+#[test]
+fn entities_for_drawing_borrows_the_entities() {
+    // Big enough that every entity is drawn
+    let mut engine = Engine::new(approximate_measure_text);
+    engine.set_canvas_max(1_000_000.0, 1_000_000.0);
+    engine.set_entities(entities(ENTITY_COUNT));
+
+    let (entities, allocations) = allocations(|| engine.entities_for_drawing());
+    assert_eq!(entities.len(), ENTITY_COUNT);
+    assert!(
+        allocations <= ENTITIES_FOR_DRAWING_ALLOCATION_BUDGET,
+        "Drawing {ENTITY_COUNT} entities made {allocations} allocations (the budget is {ENTITIES_FOR_DRAWING_ALLOCATION_BUDGET})"
+    );
+}
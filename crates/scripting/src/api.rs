@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The OpenTimeline API available to scripts
+//!
+//! | Function                          | Returns                               |
+//! |-----------------------------------|---------------------------------------|
+//! | `entity(id_or_name)`              | The entity                            |
+//! | `entities(bool_tag_expr)`         | The entities matching the expression  |
+//! | `all_entities()`                  | Every entity                          |
+//! | `timelines(bool_tag_expr)`        | `#{ id, name }` for matching timelines |
+//! | `create_entity(name, start_year)` | The newly created entity              |
+//! | `update(entity)`                  | Nothing (saves the entity's changes)  |
+//!
+//! Entities have the properties `id`, `name` (which can be set), `start_year`,
+//! `start_month`, `start_day`, `end_year`, `end_month`, `end_day` & `tags`, and
+//! the methods `has_tag(tag)`, `add_tag(tag)` & `remove_tag(tag)` (where tags
+//! are written as `name=value` or just `value`).
+//!
+
+use crate::{ScriptChange, ScriptState, SharedScriptState};
+use bool_tag_expr::{BoolTagExpr, Tag, TagName, TagValue, Tags};
+use open_timeline_core::{
+    Date, Entity, HasIdAndName, IsReducedCollection, IsReducedType, Name, ReducedEntities,
+    ReducedTimelines,
+};
+use open_timeline_crud::{
+    Create, CrudError, FetchAll, FetchByBoolTagExpr, FetchById, FetchByName, IdOrName, Limit,
+    Update, entity_id_or_name,
+};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map};
+use sqlx::{Sqlite, Transaction};
+use std::fmt::Display;
+use std::rc::Rc;
+
+/// The result of a function called by a script
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Register the OpenTimeline types & functions with the engine
+pub(crate) fn register(engine: &mut Engine, state: &SharedScriptState) {
+    register_entity_type(engine);
+
+    let shared = Rc::clone(state);
+    engine.register_fn("entity", move |id_or_name: &str| -> ScriptResult<Entity> {
+        let mut state = shared.borrow_mut();
+        let ScriptState {
+            transaction,
+            handle,
+            ..
+        } = &mut *state;
+        handle
+            .block_on(fetch_entity(transaction, id_or_name))
+            .map_err(script_error)
+    });
+
+    let shared = Rc::clone(state);
+    engine.register_fn("entities", move |bool_expr: &str| -> ScriptResult<Array> {
+        let bool_expr = BoolTagExpr::from(bool_expr.to_owned()).map_err(script_error)?;
+        let mut state = shared.borrow_mut();
+        let ScriptState {
+            transaction,
+            handle,
+            ..
+        } = &mut *state;
+        handle
+            .block_on(async {
+                let reduced = ReducedEntities::fetch_by_bool_tag_expr(
+                    transaction,
+                    Limit(u32::MAX),
+                    bool_expr,
+                )
+                .await?;
+                fetch_entities(transaction, reduced).await
+            })
+            .map_err(script_error)
+    });
+
+    let shared = Rc::clone(state);
+    engine.register_fn("all_entities", move || -> ScriptResult<Array> {
+        let mut state = shared.borrow_mut();
+        let ScriptState {
+            transaction,
+            handle,
+            ..
+        } = &mut *state;
+        handle
+            .block_on(async {
+                let reduced = ReducedEntities::fetch_all(transaction).await?;
+                fetch_entities(transaction, reduced).await
+            })
+            .map_err(script_error)
+    });
+
+    let shared = Rc::clone(state);
+    engine.register_fn("timelines", move |bool_expr: &str| -> ScriptResult<Array> {
+        let bool_expr = BoolTagExpr::from(bool_expr.to_owned()).map_err(script_error)?;
+        let mut state = shared.borrow_mut();
+        let ScriptState {
+            transaction,
+            handle,
+            ..
+        } = &mut *state;
+        let timelines = handle
+            .block_on(ReducedTimelines::fetch_by_bool_tag_expr(
+                transaction,
+                Limit(u32::MAX),
+                bool_expr,
+            ))
+            .map_err(script_error)?;
+        Ok(timelines
+            .collection()
+            .iter()
+            .map(|timeline| {
+                let mut map = Map::new();
+                map.insert("id".into(), timeline.id().to_string().into());
+                map.insert("name".into(), timeline.name().to_string().into());
+                Dynamic::from_map(map)
+            })
+            .collect())
+    });
+
+    let shared = Rc::clone(state);
+    engine.register_fn(
+        "create_entity",
+        move |name: &str, start_year: i64| -> ScriptResult<Entity> {
+            let name = Name::from(name).map_err(script_error)?;
+            let start = Date::from(None, None, start_year).map_err(script_error)?;
+            let mut entity = Entity::from(None, name, start, None, None).map_err(script_error)?;
+            let mut state = shared.borrow_mut();
+            let ScriptState {
+                transaction,
+                changes,
+                handle,
+            } = &mut *state;
+            handle
+                .block_on(entity.create(transaction))
+                .map_err(script_error)?;
+            changes.push(ScriptChange::CreatedEntity(entity.name().clone()));
+            Ok(entity)
+        },
+    );
+
+    let shared = Rc::clone(state);
+    engine.register_fn("update", move |entity: &mut Entity| -> ScriptResult<()> {
+        let mut state = shared.borrow_mut();
+        let ScriptState {
+            transaction,
+            changes,
+            handle,
+        } = &mut *state;
+        if let Some(change) = handle
+            .block_on(update_entity(transaction, entity))
+            .map_err(script_error)?
+        {
+            changes.push(change);
+        }
+        Ok(())
+    });
+}
+
+/// Register the [`Entity`] type, its properties & its methods
+fn register_entity_type(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Entity>("Entity")
+        .register_fn("to_string", |entity: &mut Entity| entity.name().to_string())
+        .register_fn("to_debug", |entity: &mut Entity| format!("{entity:?}"))
+        .register_get("id", |entity: &mut Entity| {
+            optional(entity.id().map(|id| id.to_string()))
+        })
+        .register_get("name", |entity: &mut Entity| entity.name().to_string())
+        .register_set(
+            "name",
+            |entity: &mut Entity, name: &str| -> ScriptResult<()> {
+                entity.set_name(Name::from(name).map_err(script_error)?);
+                Ok(())
+            },
+        )
+        .register_get("start_year", |entity: &mut Entity| {
+            i64::from(entity.start_year().value())
+        })
+        .register_get("start_month", |entity: &mut Entity| {
+            optional(entity.start_month().map(|month| i64::from(month.value())))
+        })
+        .register_get("start_day", |entity: &mut Entity| {
+            optional(entity.start_day().map(|day| i64::from(day.value())))
+        })
+        .register_get("end_year", |entity: &mut Entity| {
+            optional(entity.end_year().map(|year| i64::from(year.value())))
+        })
+        .register_get("end_month", |entity: &mut Entity| {
+            optional(entity.end_month().map(|month| i64::from(month.value())))
+        })
+        .register_get("end_day", |entity: &mut Entity| {
+            optional(entity.end_day().map(|day| i64::from(day.value())))
+        })
+        .register_get("tags", |entity: &mut Entity| -> Array {
+            entity
+                .tags()
+                .iter()
+                .flatten()
+                .map(|tag| tag.to_string().into())
+                .collect()
+        })
+        .register_fn(
+            "has_tag",
+            |entity: &mut Entity, tag: &str| -> ScriptResult<bool> {
+                let tag = parse_tag(tag)?;
+                Ok(entity
+                    .tags()
+                    .as_ref()
+                    .is_some_and(|tags| tags.contains(&tag)))
+            },
+        )
+        .register_fn(
+            "add_tag",
+            |entity: &mut Entity, tag: &str| -> ScriptResult<()> {
+                entity.add_tag(parse_tag(tag)?);
+                Ok(())
+            },
+        )
+        .register_fn(
+            "remove_tag",
+            |entity: &mut Entity, tag: &str| -> ScriptResult<()> {
+                entity.remove_tag(&parse_tag(tag)?);
+                Ok(())
+            },
+        );
+}
+
+/// Unit (which scripts check for with `== ()`) if there is no value
+fn optional<T: Into<Dynamic>>(value: Option<T>) -> Dynamic {
+    value.map(Into::into).unwrap_or(Dynamic::UNIT)
+}
+
+/// Turn an error into one that stops the script
+fn script_error<E: Display>(error: E) -> Box<EvalAltResult> {
+    error.to_string().into()
+}
+
+/// Parse a tag written as `name=value`, `=value` or `value`
+fn parse_tag(tag: &str) -> ScriptResult<Tag> {
+    let (name, value) = match tag.split_once('=') {
+        Some((name, value)) if name.trim().is_empty() => (None, value),
+        Some((name, value)) => (Some(TagName::from(&name).map_err(script_error)?), value),
+        None => (None, tag),
+    };
+    let value = TagValue::from(&value).map_err(script_error)?;
+    Ok(Tag::from(name, value))
+}
+
+/// Fetch an entity using its ID or name
+async fn fetch_entity(
+    transaction: &mut Transaction<'_, Sqlite>,
+    id_or_name: &str,
+) -> Result<Entity, CrudError> {
+    match entity_id_or_name(transaction, id_or_name.to_owned()).await? {
+        Some(IdOrName::Id(id)) => Entity::fetch_by_id(transaction, &id).await,
+        Some(IdOrName::Name(name)) => Entity::fetch_by_name(transaction, &name).await,
+        None => Err(CrudError::NeitherIdNorName),
+    }
+}
+
+/// Fetch the full entities for the reduced ones
+async fn fetch_entities(
+    transaction: &mut Transaction<'_, Sqlite>,
+    reduced_entities: ReducedEntities,
+) -> Result<Array, CrudError> {
+    let mut entities = Array::new();
+    for reduced_entity in reduced_entities.collection() {
+        let entity = Entity::fetch_by_id(transaction, &reduced_entity.id()).await?;
+        entities.push(Dynamic::from(entity));
+    }
+    Ok(entities)
+}
+
+/// Save the entity if it differs from the one in the database, returning the
+/// change made (if one was)
+async fn update_entity(
+    transaction: &mut Transaction<'_, Sqlite>,
+    entity: &mut Entity,
+) -> Result<Option<ScriptChange>, CrudError> {
+    let Some(id) = entity.id() else {
+        return Err(CrudError::IdNotSetForEntity(entity.name().clone()));
+    };
+    let existing = Entity::fetch_by_id(transaction, &id).await?;
+
+    let no_tags = Tags::new();
+    let old_tags = existing.tags().as_ref().unwrap_or(&no_tags);
+    let new_tags = entity.tags().as_ref().unwrap_or(&no_tags);
+    let added_tags: Tags = new_tags.difference(old_tags).cloned().collect();
+    let removed_tags: Tags = old_tags.difference(new_tags).cloned().collect();
+    let renamed_from = (existing.name() != entity.name()).then(|| existing.name().clone());
+    if added_tags.is_empty() && removed_tags.is_empty() && renamed_from.is_none() {
+        return Ok(None);
+    }
+
+    entity.update(transaction).await?;
+    Ok(Some(ScriptChange::UpdatedEntity {
+        name: entity.name().clone(),
+        renamed_from,
+        added_tags,
+        removed_tags,
+    }))
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The changes a script makes
+//!
+
+use bool_tag_expr::Tags;
+use open_timeline_core::Name;
+use std::fmt::Display;
+
+/// A change made by a script (shown to the user so that dry runs can be
+/// checked before being run for real)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptChange {
+    /// An entity was created
+    CreatedEntity(Name),
+
+    /// An entity was updated
+    UpdatedEntity {
+        /// The entity's (new) name
+        name: Name,
+
+        /// The entity's old name (if it was renamed)
+        renamed_from: Option<Name>,
+
+        /// The tags added
+        added_tags: Tags,
+
+        /// The tags removed
+        removed_tags: Tags,
+    },
+}
+
+impl Display for ScriptChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptChange::CreatedEntity(name) => write!(f, "Create '{name}'"),
+            ScriptChange::UpdatedEntity {
+                name,
+                renamed_from,
+                added_tags,
+                removed_tags,
+            } => {
+                let mut details = Vec::new();
+                if let Some(old_name) = renamed_from {
+                    details.push(format!("renamed from '{old_name}'"));
+                }
+                details.extend(added_tags.iter().map(|tag| format!("+{tag}")));
+                details.extend(removed_tags.iter().map(|tag| format!("-{tag}")));
+                write!(f, "Update '{name}'")?;
+                if !details.is_empty() {
+                    write!(f, ": {}", details.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
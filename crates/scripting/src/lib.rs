@@ -0,0 +1,290 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! *Part of the wider OpenTimeline project*
+//!
+//! This library crate lets power users automate bulk edits by running
+//! [Rhai](https://rhai.rs) scripts against an OpenTimeline database, e.g.:
+//!
+//! ```rhai
+//! for entity in all_entities() {
+//!     if entity.start_year >= 1700 && entity.start_year <= 1799 {
+//!         entity.add_tag("century=eighteenth");
+//!         update(entity);
+//!     }
+//! }
+//! ```
+//!
+//! Scripts are sandboxed: they can only use the API registered here (which
+//! sits on top of the `crud` traits), have no access to the filesystem, and are
+//! limited in how much work they can do.  Everything a script does happens
+//! within a single transaction, which is only committed if the script succeeds
+//! and it isn't a dry run.
+//!
+//! This crate is used by both the `db` CLI and the GUI's script console.
+//!
+
+mod api;
+mod change;
+
+pub use change::*;
+
+use open_timeline_crud::CrudError;
+use rhai::{Engine, EvalAltResult};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use std::cell::RefCell;
+use std::rc::Rc;
+use tokio::runtime::Handle;
+
+/// The maximum number of operations a script can perform
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// The maximum depth of function calls
+const MAX_CALL_LEVELS: usize = 64;
+
+/// The maximum length of strings created by a script
+const MAX_STRING_SIZE: usize = 1_000_000;
+
+/// The maximum number of elements in arrays & maps created by a script
+const MAX_COLLECTION_SIZE: usize = 1_000_000;
+
+/// What happened when a script was run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptReport {
+    /// Everything the script printed (including debug output)
+    pub output: Vec<String>,
+
+    /// The changes the script made (or would have made if it was a dry run)
+    pub changes: Vec<ScriptChange>,
+
+    /// The error that stopped the script (if it failed)
+    pub error: Option<String>,
+
+    /// Whether the changes were committed to the database
+    pub committed: bool,
+}
+
+/// The state shared by the functions the scripts can call
+pub(crate) struct ScriptState {
+    /// The transaction that everything happens within
+    pub(crate) transaction: Transaction<'static, Sqlite>,
+
+    /// The changes made so far
+    pub(crate) changes: Vec<ScriptChange>,
+
+    /// Used to run the async CRUD operations from the synchronous script
+    pub(crate) handle: Handle,
+}
+
+/// Shared (mutable) access to the [`ScriptState`]
+pub(crate) type SharedScriptState = Rc<RefCell<ScriptState>>;
+
+/// Run the script within a single transaction, which is rolled back if the
+/// script fails or if it is a dry run.
+///
+/// An `Err` is only returned if the database can't be used; errors in the
+/// script itself are part of the [`ScriptReport`].
+pub async fn run_script(
+    pool: &SqlitePool,
+    script: &str,
+    dry_run: bool,
+) -> Result<ScriptReport, CrudError> {
+    let transaction = pool.begin().await?;
+    let script = script.to_owned();
+    let handle = Handle::current();
+
+    // Scripts are synchronous, so they are run on a thread where blocking is
+    // allowed (the CRUD operations are then run with `Handle::block_on()`)
+    let (transaction, mut report) =
+        tokio::task::spawn_blocking(move || run_script_blocking(transaction, handle, &script))
+            .await
+            .unwrap_or_else(|error| std::panic::resume_unwind(error.into_panic()));
+
+    if dry_run || report.error.is_some() {
+        transaction.rollback().await?;
+    } else {
+        transaction.commit().await?;
+        report.committed = true;
+    }
+    Ok(report)
+}
+
+/// Run the script, handing back the transaction so that it can be committed or
+/// rolled back
+fn run_script_blocking(
+    transaction: Transaction<'static, Sqlite>,
+    handle: Handle,
+    script: &str,
+) -> (Transaction<'static, Sqlite>, ScriptReport) {
+    let state = Rc::new(RefCell::new(ScriptState {
+        transaction,
+        changes: Vec::new(),
+        handle,
+    }));
+    let output = Rc::new(RefCell::new(Vec::new()));
+
+    let engine = engine(&state, &output);
+    let error = engine
+        .run(script)
+        .err()
+        .map(|error: Box<EvalAltResult>| error.to_string());
+    drop(engine);
+
+    // Only the engine held other references to these
+    let ScriptState {
+        transaction,
+        changes,
+        ..
+    } = Rc::into_inner(state)
+        .expect("the engine has been dropped")
+        .into_inner();
+    let output = Rc::into_inner(output)
+        .expect("the engine has been dropped")
+        .into_inner();
+
+    let report = ScriptReport {
+        output,
+        changes,
+        error,
+        committed: false,
+    };
+    (transaction, report)
+}
+
+/// Create the sandboxed engine, with the OpenTimeline API registered
+fn engine(state: &SharedScriptState, output: &Rc<RefCell<Vec<String>>>) -> Engine {
+    let mut engine = Engine::new();
+
+    // Limits
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(MAX_COLLECTION_SIZE);
+    engine.disable_symbol("eval");
+
+    // Output
+    let print_output = Rc::clone(output);
+    engine.on_print(move |text| print_output.borrow_mut().push(text.to_owned()));
+    let debug_output = Rc::clone(output);
+    engine.on_debug(move |text, _, _| debug_output.borrow_mut().push(text.to_owned()));
+
+    api::register(&mut engine, state);
+    engine
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use open_timeline_core::{Date, Entity, HasIdAndName, Name};
+    use open_timeline_crud::{Create, FetchByName};
+
+    async fn create_entity(pool: &SqlitePool, name: &str, year: i64) {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut entity = Entity::from(
+            None,
+            Name::from(name).unwrap(),
+            Date::from(None, None, year).unwrap(),
+            None,
+            None,
+        )
+        .unwrap();
+        entity.create(&mut transaction).await.unwrap();
+        transaction.commit().await.unwrap();
+    }
+
+    async fn fetch_entity(pool: &SqlitePool, name: &str) -> Entity {
+        let mut transaction = pool.begin().await.unwrap();
+        Entity::fetch_by_name(&mut transaction, &Name::from(name).unwrap())
+            .await
+            .unwrap()
+    }
+
+    const ADD_CENTURY_TAG: &str = r#"
+        for entity in all_entities() {
+            if entity.start_year >= 1700 && entity.start_year <= 1799 {
+                entity.add_tag("century=eighteenth");
+                update(entity);
+                print(entity.name);
+            }
+        }
+    "#;
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn dry_run_changes_nothing(pool: SqlitePool) {
+        create_entity(&pool, "Isaac Newton", 1643).await;
+        create_entity(&pool, "Adam Smith", 1723).await;
+
+        let report = run_script(&pool, ADD_CENTURY_TAG, true).await.unwrap();
+        assert_eq!(report.error, None);
+        assert!(!report.committed);
+        assert_eq!(report.output, vec!["Adam Smith".to_owned()]);
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(
+            report.changes[0].to_string(),
+            "Update 'Adam Smith': +century=eighteenth"
+        );
+
+        let entity = fetch_entity(&pool, "Adam Smith").await;
+        assert!(entity.tags().as_ref().is_none_or(|tags| tags.is_empty()));
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn run_commits_changes(pool: SqlitePool) {
+        create_entity(&pool, "Adam Smith", 1723).await;
+
+        let report = run_script(&pool, ADD_CENTURY_TAG, false).await.unwrap();
+        assert_eq!(report.error, None);
+        assert!(report.committed);
+
+        let entity = fetch_entity(&pool, "Adam Smith").await;
+        let tags: Vec<String> = entity
+            .tags()
+            .iter()
+            .flatten()
+            .map(|tag| tag.to_string())
+            .collect();
+        assert_eq!(tags, vec!["century=eighteenth".to_owned()]);
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn failing_script_is_rolled_back(pool: SqlitePool) {
+        let script = r#"
+            let entity = create_entity("Mary Shelley", 1797);
+            print(entity.name);
+            throw "stop";
+        "#;
+        let report = run_script(&pool, script, false).await.unwrap();
+        assert!(report.error.is_some());
+        assert!(!report.committed);
+        assert_eq!(report.changes.len(), 1);
+
+        let mut transaction = pool.begin().await.unwrap();
+        let fetched =
+            Entity::fetch_by_name(&mut transaction, &Name::from("Mary Shelley").unwrap()).await;
+        assert!(fetched.is_err());
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn unchanged_entities_are_not_updated(pool: SqlitePool) {
+        create_entity(&pool, "Adam Smith", 1723).await;
+        let script = r#"
+            let entity = entity("Adam Smith");
+            update(entity);
+        "#;
+        let report = run_script(&pool, script, false).await.unwrap();
+        assert_eq!(report.error, None);
+        assert!(report.changes.is_empty());
+        assert_eq!(
+            fetch_entity(&pool, "Adam Smith").await.name().as_str(),
+            "Adam Smith"
+        );
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn runaway_scripts_are_stopped(pool: SqlitePool) {
+        let report = run_script(&pool, "loop {}", false).await.unwrap();
+        assert!(report.error.is_some());
+        assert!(!report.committed);
+    }
+}
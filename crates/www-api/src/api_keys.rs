@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! API keys.  If any are configured, write requests must send one of them (in
+//! the `x-api-key` header).  Reads don't need a key.
+//!
+
+use crate::ApiError;
+use crate::metrics::API_KEY_HEADER;
+use crate::read_only::is_read_method;
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The API keys that may be used for write requests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKeys(Arc<BTreeSet<String>>);
+
+impl ApiKeys {
+    /// Parse API keys, 1 per line (blank lines & lines starting with `#` are
+    /// ignored).  Returns `None` if there are no keys.
+    pub fn parse(text: &str) -> Option<Self> {
+        let keys: BTreeSet<String> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect();
+        (!keys.is_empty()).then(|| Self(Arc::new(keys)))
+    }
+
+    /// Read the API keys from a file (see [`ApiKeys::parse()`])
+    pub fn read(path: &Path) -> std::io::Result<Option<Self>> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Whether the key is one of the API keys
+    pub fn contains(&self, key: &str) -> bool {
+        self.0.contains(key)
+    }
+}
+
+/// Middleware that rejects write requests without a valid API key with a
+/// `401 Unauthorized`
+pub(crate) async fn require_api_key_for_writes(
+    State(api_keys): State<ApiKeys>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if is_read_method(request.method()) {
+        return next.run(request).await;
+    }
+    let api_key = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|api_key| api_key.to_str().ok());
+    let error_msg = match api_key {
+        Some(api_key) if api_keys.contains(api_key) => return next.run(request).await,
+        Some(_) => "The API key isn't valid",
+        None => "Write requests need an API key (in the `x-api-key` header)",
+    };
+    ApiError::new(StatusCode::UNAUTHORIZED, "unauthorized", error_msg).into_response()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ApiAccessMode, ApiConfig, from_config};
+    use axum::body::Body;
+    use axum::http::Method;
+    use sqlx::SqlitePool;
+    use tower::ServiceExt;
+
+    #[test]
+    fn parse() {
+        let api_keys = ApiKeys::parse("# Keys\nabc\n\n  def  \n").unwrap();
+        assert!(api_keys.contains("abc"));
+        assert!(api_keys.contains("def"));
+        assert!(!api_keys.contains("# Keys"));
+        assert_eq!(ApiKeys::parse("# None\n\n"), None);
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn writes_need_a_key(pool: SqlitePool) {
+        let mut config = ApiConfig::new(pool.connect_options().get_filename().to_path_buf());
+        config.access_mode = ApiAccessMode::ReadWrite;
+        config.api_keys = ApiKeys::parse("secret");
+        let router = from_config(&config).await.unwrap();
+        let send = |method: Method, uri: &str, api_key: Option<&str>| {
+            let mut request = Request::builder().method(method).uri(uri);
+            if let Some(api_key) = api_key {
+                request = request.header(API_KEY_HEADER, api_key);
+            }
+            router.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let uri = "/api/v1/entity/missing";
+        let response = send(Method::DELETE, uri, None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(Method::DELETE, uri, Some("wrong")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = send(Method::DELETE, uri, Some("secret")).await.unwrap();
+        assert_ne!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Reads don't need a key
+        let response = send(Method::GET, "/api/v1/tags", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
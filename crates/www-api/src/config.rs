@@ -0,0 +1,520 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The API's configuration, loaded from a TOML file and/or environment
+//! variables (which take precedence)
+//!
+
+use crate::{
+    ApiAccessMode, ApiKeys, ApiMode, CorsAllowedOrigins, CorsError, RateLimit, TenantConfig,
+    TenantSlug,
+};
+use open_timeline_core::IdScheme;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+use tracing_subscriber::filter::Targets;
+
+/// The address the API listens on by default
+pub const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0:2408";
+
+/// The log levels used by default (see [`ApiConfig::log_levels`])
+pub const DEFAULT_LOG_LEVELS: &str = "open_timeline=info";
+
+/// The environment variable holding the address to listen on
+pub const BIND_ADDRESS_ENV_VAR: &str = "OPEN_TIMELINE_API_BIND_ADDRESS";
+
+/// The environment variable holding the path to the database
+pub const DATABASE_ENV_VAR: &str = "OPEN_TIMELINE_API_DATABASE";
+
+/// The environment variable holding the access mode (`read` or `read-write`)
+pub const ACCESS_MODE_ENV_VAR: &str = "OPEN_TIMELINE_API_ACCESS_MODE";
+
+/// The environment variable holding the API mode (`static` or `dynamic`)
+pub const API_MODE_ENV_VAR: &str = "OPEN_TIMELINE_API_MODE";
+
+/// The environment variable holding the maximum number of requests a client
+/// can make per minute
+pub const RATE_LIMIT_ENV_VAR: &str = "OPEN_TIMELINE_API_RATE_LIMIT_PER_MINUTE";
+
+/// The environment variable holding the path to the API keys file
+pub const API_KEYS_FILE_ENV_VAR: &str = "OPEN_TIMELINE_API_KEYS_FILE";
+
+/// The environment variable holding the log levels (e.g.
+/// `open_timeline=info,sqlx::query=debug`)
+pub const LOG_LEVELS_ENV_VAR: &str = "OPEN_TIMELINE_API_LOG_LEVELS";
+
+/// The environment variable holding how new IDs are generated (`random` or
+/// `time-sortable`)
+pub const ID_SCHEME_ENV_VAR: &str = "OPEN_TIMELINE_API_ID_SCHEME";
+
+/// Errors that can arise when loading the API's configuration
+#[derive(Error, Debug)]
+pub enum ApiConfigError {
+    #[error("Couldn't read the config file {0}: {1}")]
+    ReadFile(PathBuf, std::io::Error),
+
+    #[error("Invalid config file: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("The path to the database must be set (`database` or {DATABASE_ENV_VAR})")]
+    MissingDatabase,
+
+    #[error("Invalid bind address: {0}")]
+    BindAddress(String),
+
+    #[error("Invalid access mode `{0}` (expected `read` or `read-write`)")]
+    AccessMode(String),
+
+    #[error("Invalid API mode `{0}` (expected `static` or `dynamic`)")]
+    ApiMode(String),
+
+    #[error(transparent)]
+    Cors(#[from] CorsError),
+
+    #[error("Invalid rate limit `{0}` (expected a number of requests per minute above 0)")]
+    RateLimit(String),
+
+    #[error("Couldn't read the API keys file {0}: {1}")]
+    ReadApiKeysFile(PathBuf, std::io::Error),
+
+    #[error("The API keys file {0} doesn't contain any keys")]
+    NoApiKeys(PathBuf),
+
+    #[error("Invalid log levels `{0}` (expected e.g. `open_timeline=info,sqlx::query=debug`)")]
+    LogLevels(String),
+
+    #[error("Invalid ID scheme `{0}` (expected `random` or `time-sortable`)")]
+    IdScheme(String),
+
+    #[error("Invalid tenant slug `{0}` (expected lowercase letters, digits, & hyphens)")]
+    TenantSlug(String),
+}
+
+impl FromStr for ApiAccessMode {
+    type Err = ApiConfigError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            "read" => Ok(ApiAccessMode::Read),
+            "read-write" => Ok(ApiAccessMode::ReadWrite),
+            _ => Err(ApiConfigError::AccessMode(text.to_string())),
+        }
+    }
+}
+
+impl FromStr for ApiMode {
+    type Err = ApiConfigError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            "static" => Ok(ApiMode::Static),
+            "dynamic" => Ok(ApiMode::Dynamic),
+            _ => Err(ApiConfigError::ApiMode(text.to_string())),
+        }
+    }
+}
+
+/// The API's (validated) configuration.  Load it with [`ApiConfig::load()`]
+/// from a TOML file like this (& environment variables):
+///
+/// ```toml
+/// bind_address = "0.0.0.0:2408"
+/// database = "/var/lib/open-timeline/open-timeline.sqlite"
+/// access_mode = "read"        # or "read-write"
+/// api_mode = "dynamic"        # or "static"
+/// cors_allowed_origins = "https://example.com, https://example.org"
+/// rate_limit_per_minute = 600
+/// api_keys_file = "/etc/open-timeline/api-keys"
+/// log_levels = "open_timeline=info,sqlx::query=debug"
+/// id_scheme = "time-sortable" # or "random"
+///
+/// # Each tenant is served under /api/v1/{tenant}/...
+/// [tenants.history-101]
+/// database = "/var/lib/open-timeline/history-101.sqlite"
+/// access_mode = "read-write"  # the top-level access_mode if not given
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApiConfig {
+    /// The address to listen on
+    pub bind_address: SocketAddr,
+
+    /// The path to the database
+    pub database: PathBuf,
+
+    /// Whether the database can be changed
+    pub access_mode: ApiAccessMode,
+
+    /// Whether the dynamic (query parameter) endpoints are served
+    pub api_mode: ApiMode,
+
+    /// The origins browsers may call the API from
+    pub cors_allowed_origins: CorsAllowedOrigins,
+
+    /// The maximum number of requests each client can make (if limited)
+    pub rate_limit: Option<RateLimit>,
+
+    /// The keys write requests must use (if any are required)
+    pub api_keys: Option<ApiKeys>,
+
+    /// The tenants (each with their own database)
+    pub tenants: BTreeMap<TenantSlug, TenantConfig>,
+
+    /// The level logged at for each module (see [`parse_log_levels()`])
+    pub log_levels: Targets,
+
+    /// How the IDs of new entities & timelines are generated
+    pub id_scheme: IdScheme,
+}
+
+/// The configuration as written in the file (or environment variables), before
+/// it is validated
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawApiConfig {
+    bind_address: Option<String>,
+    database: Option<PathBuf>,
+    access_mode: Option<String>,
+    api_mode: Option<String>,
+    cors_allowed_origins: Option<String>,
+    rate_limit_per_minute: Option<String>,
+    api_keys_file: Option<PathBuf>,
+    log_levels: Option<String>,
+    id_scheme: Option<String>,
+    #[serde(default)]
+    tenants: BTreeMap<String, RawTenantConfig>,
+}
+
+/// A tenant's configuration as written in the file, before it is validated
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTenantConfig {
+    database: PathBuf,
+    access_mode: Option<String>,
+}
+
+impl ApiConfig {
+    /// Create a configuration for the database with the defaults: read-only,
+    /// dynamic, no CORS, no rate limit, no API keys, no tenants, the default
+    /// log levels, & random IDs
+    pub fn new(database: PathBuf) -> Self {
+        Self {
+            bind_address: DEFAULT_BIND_ADDRESS.parse().unwrap(),
+            database,
+            access_mode: ApiAccessMode::Read,
+            api_mode: ApiMode::Dynamic,
+            cors_allowed_origins: CorsAllowedOrigins::None,
+            rate_limit: None,
+            api_keys: None,
+            tenants: BTreeMap::new(),
+            log_levels: DEFAULT_LOG_LEVELS.parse().unwrap(),
+            id_scheme: IdScheme::Random,
+        }
+    }
+
+    /// Load the configuration from the TOML file (if given) & the environment
+    /// variables (which take precedence)
+    pub fn load(path: Option<&Path>) -> Result<Self, ApiConfigError> {
+        let toml = match path {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|error| ApiConfigError::ReadFile(path.to_path_buf(), error))?,
+            None => String::new(),
+        };
+        Self::from_toml_and_env(&toml, |name| std::env::var(name).ok())
+    }
+
+    /// Load the configuration from TOML & environment variables (got using
+    /// `env`, which take precedence)
+    fn from_toml_and_env(
+        toml: &str,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ApiConfigError> {
+        // The rate limit may be written as a number in TOML
+        let mut table: toml::Table = toml::from_str(toml)?;
+        if let Some(toml::Value::Integer(rate_limit)) = table.get("rate_limit_per_minute") {
+            let rate_limit = toml::Value::String(rate_limit.to_string());
+            table.insert(String::from("rate_limit_per_minute"), rate_limit);
+        }
+        let mut raw: RawApiConfig = table.try_into()?;
+
+        // Environment variables take precedence
+        let override_with = |value: &mut Option<String>, name| {
+            if let Some(env_value) = env(name) {
+                *value = Some(env_value);
+            }
+        };
+        override_with(&mut raw.bind_address, BIND_ADDRESS_ENV_VAR);
+        override_with(&mut raw.access_mode, ACCESS_MODE_ENV_VAR);
+        override_with(&mut raw.api_mode, API_MODE_ENV_VAR);
+        override_with(
+            &mut raw.cors_allowed_origins,
+            crate::CORS_ALLOWED_ORIGINS_ENV_VAR,
+        );
+        override_with(&mut raw.rate_limit_per_minute, RATE_LIMIT_ENV_VAR);
+        override_with(&mut raw.log_levels, LOG_LEVELS_ENV_VAR);
+        override_with(&mut raw.id_scheme, ID_SCHEME_ENV_VAR);
+        if let Some(database) = env(DATABASE_ENV_VAR) {
+            raw.database = Some(PathBuf::from(database));
+        }
+        if let Some(api_keys_file) = env(API_KEYS_FILE_ENV_VAR) {
+            raw.api_keys_file = Some(PathBuf::from(api_keys_file));
+        }
+
+        raw.validate()
+    }
+}
+
+/// Parse log levels: a comma separated list of `module=level` (e.g.
+/// `open_timeline=info,sqlx::query=debug`), where a level on its own applies
+/// to every module
+pub fn parse_log_levels(text: &str) -> Result<Targets, ApiConfigError> {
+    text.trim()
+        .parse()
+        .map_err(|_| ApiConfigError::LogLevels(text.to_string()))
+}
+
+impl RawApiConfig {
+    /// Check the configuration is valid (and read the API keys)
+    fn validate(self) -> Result<ApiConfig, ApiConfigError> {
+        let database = self.database.ok_or(ApiConfigError::MissingDatabase)?;
+        let mut config = ApiConfig::new(database);
+        if let Some(bind_address) = self.bind_address {
+            config.bind_address = bind_address
+                .trim()
+                .parse()
+                .map_err(|_| ApiConfigError::BindAddress(bind_address))?;
+        }
+        if let Some(access_mode) = self.access_mode {
+            config.access_mode = access_mode.parse()?;
+        }
+        if let Some(api_mode) = self.api_mode {
+            config.api_mode = api_mode.parse()?;
+        }
+        if let Some(cors_allowed_origins) = self.cors_allowed_origins {
+            config.cors_allowed_origins = CorsAllowedOrigins::parse(&cors_allowed_origins)?;
+        }
+        if let Some(rate_limit) = self.rate_limit_per_minute {
+            let requests_per_minute = rate_limit
+                .trim()
+                .parse::<NonZeroU32>()
+                .map_err(|_| ApiConfigError::RateLimit(rate_limit))?;
+            config.rate_limit = Some(RateLimit {
+                requests_per_minute,
+            });
+        }
+        if let Some(log_levels) = self.log_levels {
+            config.log_levels = parse_log_levels(&log_levels)?;
+        }
+        if let Some(id_scheme) = self.id_scheme {
+            config.id_scheme = id_scheme
+                .parse()
+                .map_err(|_| ApiConfigError::IdScheme(id_scheme))?;
+        }
+        if let Some(path) = self.api_keys_file {
+            let api_keys = ApiKeys::read(&path)
+                .map_err(|error| ApiConfigError::ReadApiKeysFile(path.clone(), error))?;
+            config.api_keys = Some(api_keys.ok_or(ApiConfigError::NoApiKeys(path))?);
+        }
+        for (slug, tenant) in self.tenants {
+            let access_mode = match tenant.access_mode {
+                Some(access_mode) => access_mode.parse()?,
+                None => config.access_mode,
+            };
+            let tenant = TenantConfig {
+                database: tenant.database,
+                access_mode,
+            };
+            config.tenants.insert(slug.parse()?, tenant);
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Load the configuration from TOML & the (fake) environment variables
+    fn load(toml: &str, env: &[(&str, &str)]) -> Result<ApiConfig, ApiConfigError> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        ApiConfig::from_toml_and_env(toml, |name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn from_toml() {
+        let config = load(
+            r#"
+                bind_address = "127.0.0.1:8080"
+                database = "open-timeline.sqlite"
+                access_mode = "read-write"
+                api_mode = "static"
+                cors_allowed_origins = "https://example.com"
+                rate_limit_per_minute = 600
+            "#,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(config.bind_address.to_string(), "127.0.0.1:8080");
+        assert_eq!(config.database, PathBuf::from("open-timeline.sqlite"));
+        assert_eq!(config.access_mode, ApiAccessMode::ReadWrite);
+        assert_eq!(config.api_mode, ApiMode::Static);
+        assert!(matches!(
+            config.cors_allowed_origins,
+            CorsAllowedOrigins::List(_)
+        ));
+        assert_eq!(
+            config
+                .rate_limit
+                .map(|limit| limit.requests_per_minute.get()),
+            Some(600)
+        );
+        assert!(config.api_keys.is_none());
+        assert!(config.tenants.is_empty());
+        assert_eq!(config.id_scheme, IdScheme::Random);
+    }
+
+    #[test]
+    fn tenants() {
+        let config = load(
+            r#"
+                database = "a.sqlite"
+                access_mode = "read-write"
+
+                [tenants.history-101]
+                database = "history.sqlite"
+                access_mode = "read"
+
+                [tenants.physics]
+                database = "physics.sqlite"
+            "#,
+            &[],
+        )
+        .unwrap();
+        let access_modes: Vec<(String, ApiAccessMode)> = config
+            .tenants
+            .iter()
+            .map(|(slug, tenant)| (slug.to_string(), tenant.access_mode))
+            .collect();
+        assert_eq!(
+            access_modes,
+            [
+                (String::from("history-101"), ApiAccessMode::Read),
+                (String::from("physics"), ApiAccessMode::ReadWrite),
+            ]
+        );
+
+        let invalid = "database = 'a'\n[tenants.Entity]\ndatabase = 'b'";
+        assert!(matches!(
+            load(invalid, &[]),
+            Err(ApiConfigError::TenantSlug(_))
+        ));
+    }
+
+    #[test]
+    fn defaults_and_env_precedence() {
+        let config = load(r#"database = "a.sqlite""#, &[]).unwrap();
+        assert_eq!(config.bind_address.to_string(), DEFAULT_BIND_ADDRESS);
+        assert_eq!(config.access_mode, ApiAccessMode::Read);
+        assert_eq!(config.rate_limit, None);
+
+        let config = load(
+            r#"
+                database = "a.sqlite"
+                access_mode = "read"
+            "#,
+            &[
+                (DATABASE_ENV_VAR, "b.sqlite"),
+                (ACCESS_MODE_ENV_VAR, "read-write"),
+                (RATE_LIMIT_ENV_VAR, "10"),
+                (ID_SCHEME_ENV_VAR, "time-sortable"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.database, PathBuf::from("b.sqlite"));
+        assert_eq!(config.access_mode, ApiAccessMode::ReadWrite);
+        assert!(config.rate_limit.is_some());
+        assert_eq!(config.id_scheme, IdScheme::TimeSortable);
+    }
+
+    #[test]
+    fn log_levels() {
+        use tracing::Level;
+
+        let config = load(r#"database = "a.sqlite""#, &[]).unwrap();
+        assert!(
+            config
+                .log_levels
+                .would_enable("open_timeline_crud", &Level::INFO)
+        );
+        assert!(
+            !config
+                .log_levels
+                .would_enable("open_timeline_crud", &Level::DEBUG)
+        );
+        assert!(!config.log_levels.would_enable("sqlx::query", &Level::INFO));
+
+        let config = load(
+            r#"
+                database = "a.sqlite"
+                log_levels = "open_timeline=warn"
+            "#,
+            &[(LOG_LEVELS_ENV_VAR, "open_timeline=info,sqlx::query=debug")],
+        )
+        .unwrap();
+        assert!(
+            config
+                .log_levels
+                .would_enable("open_timeline_crud", &Level::INFO)
+        );
+        assert!(config.log_levels.would_enable("sqlx::query", &Level::DEBUG));
+        assert!(!config.log_levels.would_enable("sqlx::pool", &Level::DEBUG));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            load("", &[]),
+            Err(ApiConfigError::MissingDatabase)
+        ));
+        assert!(matches!(
+            load("database = 'a'\nbind_address = 'nowhere'", &[]),
+            Err(ApiConfigError::BindAddress(_))
+        ));
+        assert!(matches!(
+            load("database = 'a'\naccess_mode = 'write'", &[]),
+            Err(ApiConfigError::AccessMode(_))
+        ));
+        assert!(matches!(
+            load("database = 'a'", &[(API_MODE_ENV_VAR, "fast")]),
+            Err(ApiConfigError::ApiMode(_))
+        ));
+        assert!(matches!(
+            load("database = 'a'\nrate_limit_per_minute = 0", &[]),
+            Err(ApiConfigError::RateLimit(_))
+        ));
+        assert!(matches!(
+            load("database = 'a'\nlog_levels = 'open_timeline=loud'", &[]),
+            Err(ApiConfigError::LogLevels(_))
+        ));
+        assert!(matches!(
+            load("database = 'a'\nid_scheme = 'ulid'", &[]),
+            Err(ApiConfigError::IdScheme(_))
+        ));
+        assert!(matches!(
+            load("database = 'a'\napi_keys_file = '/does/not/exist'", &[]),
+            Err(ApiConfigError::ReadApiKeysFile(..))
+        ));
+        assert!(matches!(
+            load("database = 'a'\nunknown = 1", &[]),
+            Err(ApiConfigError::Toml(_))
+        ));
+    }
+}
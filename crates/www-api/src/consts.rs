@@ -11,3 +11,14 @@ pub const DEFAULT_LIMIT_RANDOM_TIMELINES: u32 = 50;
 pub const MAX_LIMIT_RANDOM_TIMELINES: u32 = 250;
 
 pub const DEFAULT_LIMIT_PARTIAL_NAME_QUERY: u32 = 25;
+
+pub const DEFAULT_LIMIT_PAGE: u32 = 100;
+pub const MAX_LIMIT_PAGE: u32 = 1000;
+
+pub const DEFAULT_LIMIT_NEAR_ENTITIES: u32 = 50;
+pub const MAX_LIMIT_NEAR_ENTITIES: u32 = 500;
+pub const DEFAULT_NEAR_RADIUS_KM: f64 = 50.0;
+
+pub const DEFAULT_RENDER_WIDTH: u32 = 1200;
+pub const DEFAULT_RENDER_HEIGHT: u32 = 630;
+pub const MAX_RENDER_DIMENSION: u32 = 4096;
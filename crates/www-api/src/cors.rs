@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Cross-Origin Resource Sharing (CORS) configuration
+//!
+
+use crate::ApiAccessMode;
+use axum::http::{HeaderValue, Method, header};
+use thiserror::Error;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// The environment variable holding the origins allowed to call the API from a
+/// browser (comma separated, or `*` for any origin)
+pub const CORS_ALLOWED_ORIGINS_ENV_VAR: &str = "OPEN_TIMELINE_CORS_ALLOWED_ORIGINS";
+
+/// Errors that can arise when configuring CORS
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CorsError {
+    #[error("Invalid CORS origin: {0}")]
+    InvalidOrigin(String),
+}
+
+/// The origins (other than the API's own) that browsers may call the API from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CorsAllowedOrigins {
+    /// Only the API's own origin (no CORS headers are sent)
+    #[default]
+    None,
+
+    /// Any origin
+    Any,
+
+    /// Only the listed origins (e.g. `https://example.com`)
+    List(Vec<HeaderValue>),
+}
+
+impl CorsAllowedOrigins {
+    /// Parse a comma separated list of origins (`*` allows any origin, and an
+    /// empty string allows none)
+    pub fn parse(origins: &str) -> Result<Self, CorsError> {
+        let origins: Vec<&str> = origins
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .collect();
+        if origins.is_empty() {
+            return Ok(Self::None);
+        }
+        if origins.contains(&"*") {
+            return Ok(Self::Any);
+        }
+        origins
+            .into_iter()
+            .map(|origin| {
+                let origin = origin.trim_end_matches('/');
+                if !(origin.starts_with("http://") || origin.starts_with("https://")) {
+                    return Err(CorsError::InvalidOrigin(origin.to_string()));
+                }
+                HeaderValue::from_str(origin)
+                    .map_err(|_| CorsError::InvalidOrigin(origin.to_string()))
+            })
+            .collect::<Result<_, _>>()
+            .map(Self::List)
+    }
+
+    /// Read the allowed origins from [`CORS_ALLOWED_ORIGINS_ENV_VAR`] (none if
+    /// it isn't set)
+    pub fn from_env() -> Result<Self, CorsError> {
+        match std::env::var(CORS_ALLOWED_ORIGINS_ENV_VAR) {
+            Ok(origins) => Self::parse(&origins),
+            Err(_) => Ok(Self::None),
+        }
+    }
+
+    /// The CORS layer to add to the router (if any origins are allowed)
+    pub(crate) fn layer(&self, access_mode: ApiAccessMode) -> Option<CorsLayer> {
+        let allow_origin = match self {
+            Self::None => return None,
+            Self::Any => AllowOrigin::any(),
+            Self::List(origins) => AllowOrigin::list(origins.clone()),
+        };
+        let methods = match access_mode {
+            ApiAccessMode::Read => vec![Method::GET, Method::HEAD],
+            ApiAccessMode::ReadWrite => vec![
+                Method::GET,
+                Method::HEAD,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ],
+        };
+        Some(
+            CorsLayer::new()
+                .allow_origin(allow_origin)
+                .allow_methods(methods)
+                .allow_headers([header::CONTENT_TYPE, header::IF_MATCH])
+                .expose_headers([header::ETAG]),
+        )
+    }
+}
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The API served from inside another application (e.g. the desktop app),
+//! sharing its database pool, so that local tools (e.g. the browser renderer)
+//! can read the live database without it being exported.  It only listens on
+//! the loopback interface, and is read-only.
+//!
+
+use crate::{ApiAccessMode, ApiMetrics, ApiMode, CorsAllowedOrigins, metrics, pool_router};
+use axum::Router;
+use axum::middleware::from_fn_with_state;
+use sqlx::SqlitePool;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tower_http::compression::CompressionLayer;
+
+/// A running embedded API server (stopped with [`EmbeddedApiServer::stop()`])
+#[derive(Debug)]
+pub struct EmbeddedApiServer {
+    /// The address being listened on
+    address: SocketAddr,
+
+    /// Tell the server to stop
+    tx_shutdown: oneshot::Sender<()>,
+
+    /// The task serving the API
+    task: JoinHandle<io::Result<()>>,
+}
+
+impl EmbeddedApiServer {
+    /// Start serving the database's pool (read-only) at `127.0.0.1:{port}`.
+    /// Port `0` uses any free port (see [`EmbeddedApiServer::address()`]).
+    pub async fn start(pool: SqlitePool, port: u16) -> io::Result<Self> {
+        let router = embedded_router(pool)?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await?;
+        let address = listener.local_addr()?;
+        let (tx_shutdown, rx_shutdown) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            axum::serve(listener, router)
+                .with_graceful_shutdown(async {
+                    let _ = rx_shutdown.await;
+                })
+                .await
+        });
+        tracing::info!("Embedded API serving http://{address}/api/v1");
+        Ok(Self {
+            address,
+            tx_shutdown,
+            task,
+        })
+    }
+
+    /// The address being listened on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Stop the server (once the requests in progress have been responded to)
+    pub async fn stop(self) -> io::Result<()> {
+        let _ = self.tx_shutdown.send(());
+        self.task.await.map_err(io::Error::other)??;
+        tracing::info!("Embedded API stopped serving http://{}", self.address);
+        Ok(())
+    }
+}
+
+/// Set up the read-only routes for the pool, with each request logged.
+/// Browsers may call them from any origin (e.g. the renderer opened from a
+/// file).
+fn embedded_router(pool: SqlitePool) -> io::Result<Router> {
+    let access_mode = ApiAccessMode::Read;
+    let apiv1 = pool_router(pool, access_mode, ApiMode::Dynamic).map_err(io::Error::other)?;
+    let metrics = ApiMetrics::new().map_err(io::Error::other)?;
+    let api = Router::new()
+        .nest("/api/v1", apiv1)
+        .layer(from_fn_with_state(
+            metrics,
+            metrics::log_and_measure_request,
+        ))
+        .layer(CompressionLayer::new());
+    Ok(match CorsAllowedOrigins::Any.layer(access_mode) {
+        Some(cors) => api.layer(cors),
+        None => api,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Send a request (with no body) & get the whole response
+    async fn send(address: SocketAddr, method: &str, path: &str) -> io::Result<String> {
+        let mut stream = TcpStream::connect(address).await?;
+        let request =
+            format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+        stream.write_all(request.as_bytes()).await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn serves_shared_pool(pool: SqlitePool) {
+        let server = EmbeddedApiServer::start(pool, 0).await.unwrap();
+        let address = server.address();
+        assert!(address.ip().is_loopback());
+
+        let response = send(address, "GET", "/api/v1/tags").await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("[]"), "{response}");
+
+        // Read-only
+        let response = send(address, "DELETE", "/api/v1/entity/a").await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 403"), "{response}");
+
+        // Nothing is served once stopped
+        server.stop().await.unwrap();
+        assert!(send(address, "GET", "/api/v1/tags").await.is_err());
+    }
+}
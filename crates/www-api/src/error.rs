@@ -1,24 +1,167 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 //!
-//! API error response
+//! API error response.  Errors are sent as a JSON envelope with a stable code
+//! (see [`ErrorCode`]) & a message for people, e.g.
+//!
+//! ```json
+//! { "error": { "code": "not_found", "message": "The ID is not in the database" } }
+//! ```
+//!
+//! Request bodies that can't be used also say which fields are wrong & why
+//! (see [`FieldError`]), e.g.
+//!
+//! ```json
+//! {
+//!   "error": {
+//!     "code": "invalid_payload",
+//!     "message": "The request body is invalid (name: Name cannot be empty)",
+//!     "fields": [
+//!       { "path": "name", "code": "invalid_name", "message": "Name cannot be empty" }
+//!     ]
+//!   }
+//! }
+//! ```
 //!
 
-use crate::helpers::ErrorMsg;
 use axum::{
     Json,
     http::StatusCode,
     response::{IntoResponse, Response},
 };
-use open_timeline_crud::CrudError;
+use open_timeline_core::{ErrorChain, ErrorCode};
+use open_timeline_crud::{BackupRestoreMergeError, CrudError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The JSON body of an error response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorEnvelope {
+    pub error: ErrorBody,
+}
+
+/// The error in an [`ErrorEnvelope`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorBody {
+    /// The stable, machine readable error code
+    pub code: String,
+
+    /// What went wrong (for people)
+    pub message: String,
+
+    /// The fields of the request body that are wrong (if any)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
+/// What's wrong with a field of a request body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Where the field is in the body (e.g. `entities[0].start.month`), or
+    /// empty if the problem is with the whole body
+    pub path: String,
+
+    /// The stable, machine readable error code (e.g. `missing_field`, or the
+    /// code of the core type's error, like `invalid_name`)
+    pub code: String,
+
+    /// What's wrong with the field (for people)
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.as_str() {
+            "" => write!(f, "{}", self.message),
+            path => write!(f, "{path}: {}", self.message),
+        }
+    }
+}
 
 /// Container for API errors.  Can be sent back to the client
-pub struct ApiError(pub (StatusCode, Json<ErrorMsg>));
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    fields: Vec<FieldError>,
+}
+
+impl ApiError {
+    /// Create a new API error
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Create a new error for a request body that can't be used
+    pub fn invalid_payload(fields: Vec<FieldError>) -> Self {
+        let details: Vec<String> = fields.iter().map(FieldError::to_string).collect();
+        let message = format!("The request body is invalid ({})", details.join("; "));
+        Self {
+            fields,
+            ..Self::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_payload", message)
+        }
+    }
+
+    /// Create a new error for a request that isn't valid
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    /// Create a new error for an update whose `If-Match` isn't the current
+    /// version's `ETag`
+    pub fn precondition_failed() -> Self {
+        Self::new(
+            StatusCode::PRECONDITION_FAILED,
+            "precondition_failed",
+            "It has been changed since it was fetched (the If-Match header isn't its ETag)",
+        )
+    }
+
+    /// Create a new error for a request that couldn't be fulfilled
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", message)
+    }
+}
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        self.0.into_response()
+        let envelope = ErrorEnvelope {
+            error: ErrorBody {
+                code: self.code.to_string(),
+                message: self.message,
+                fields: self.fields,
+            },
+        };
+        (self.status, Json(envelope)).into_response()
+    }
+}
+
+/// The status code for an error with the given code
+fn status_for_code(code: &str) -> StatusCode {
+    match code {
+        "not_found" => StatusCode::NOT_FOUND,
+        "already_exists" | "stale_write" => StatusCode::CONFLICT,
+        "missing_field" | "not_a_dynamic_timeline" => StatusCode::BAD_REQUEST,
+        code if code.starts_with("invalid_") => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Create an API error from an error with a code (logging the causes of server
+/// errors, which aren't sent to the client)
+fn from_error<E: std::error::Error + ErrorCode>(error: &E) -> ApiError {
+    let code = error.error_code();
+    let status = status_for_code(code);
+    if status.is_server_error() {
+        tracing::error!("{}", ErrorChain(error));
     }
+    ApiError::new(status, code, error.to_string())
 }
 
 impl From<sqlx::Error> for ApiError {
@@ -30,11 +173,76 @@ impl From<sqlx::Error> for ApiError {
 
 impl From<CrudError> for ApiError {
     fn from(value: CrudError) -> Self {
-        ApiError((
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorMsg {
-                error_msg: value.to_string(),
-            }),
-        ))
+        from_error(&value)
+    }
+}
+
+impl From<BackupRestoreMergeError> for ApiError {
+    fn from(value: BackupRestoreMergeError) -> Self {
+        from_error(&value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::body::to_bytes;
+    use open_timeline_core::NameError;
+
+    /// Get the status & envelope of an error's response
+    async fn response(error: ApiError) -> (StatusCode, ErrorEnvelope) {
+        let response = error.into_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn error_envelope() {
+        let (status, envelope) = response(CrudError::IdNotInDb.into()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(envelope.error.code, "not_found");
+        assert_eq!(envelope.error.message, "The ID is not in the database");
+
+        let (status, envelope) = response(CrudError::from(NameError::Empty).into()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(envelope.error.code, "invalid_name");
+        assert_eq!(envelope.error.message, "Name cannot be empty");
+
+        let (status, envelope) = response(CrudError::StaleWrite.into()).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(envelope.error.code, "stale_write");
+        assert!(envelope.error.fields.is_empty());
+
+        // The cause of database errors isn't sent
+        let error = sqlx::Error::Protocol(String::from("secret"));
+        let (status, envelope) = response(error.into()).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(envelope.error.code, "database_error");
+        assert!(!envelope.error.message.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn field_errors() {
+        let fields = vec![
+            FieldError {
+                path: String::from("name"),
+                code: String::from("invalid_name"),
+                message: String::from("Name cannot be empty"),
+            },
+            FieldError {
+                path: String::new(),
+                code: String::from("invalid_json"),
+                message: String::from("trailing characters"),
+            },
+        ];
+        let (status, envelope) = response(ApiError::invalid_payload(fields.clone())).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(envelope.error.code, "invalid_payload");
+        assert_eq!(
+            envelope.error.message,
+            "The request body is invalid (name: Name cannot be empty; trailing characters)"
+        );
+        assert_eq!(envelope.error.fields, fields);
     }
 }
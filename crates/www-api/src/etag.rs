@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Optimistic concurrency for updates.  Entities & timelines are sent with an
+//! `ETag` (their version in the database), & updates sent with it as the
+//! `If-Match` fail with a `412` if they've been changed since, e.g.
+//!
+//! ```text
+//! GET /api/v1/entity/Apollo 11      ->  ETag: "3"
+//! PATCH /api/v1/entity/Apollo 11    If-Match: "3"  ->  200, ETag: "4"
+//! PATCH /api/v1/entity/Apollo 11    If-Match: "3"  ->  412
+//! ```
+//!
+
+use crate::ApiError;
+use axum::Json;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::{HeaderValue, header};
+use axum::response::{IntoResponse, Response};
+use open_timeline_core::{Entity, TimelineEdit};
+use serde::Serialize;
+
+/// Something with a version in the database (which its `ETag` is)
+pub trait HasVersion {
+    /// The version in the database when it was fetched (if known)
+    fn version(&self) -> Option<i64>;
+
+    /// Set the version that updates expect to be in the database
+    fn set_version(&mut self, version: Option<i64>);
+}
+
+impl HasVersion for Entity {
+    fn version(&self) -> Option<i64> {
+        Entity::version(self)
+    }
+
+    fn set_version(&mut self, version: Option<i64>) {
+        Entity::set_version(self, version)
+    }
+}
+
+impl HasVersion for TimelineEdit {
+    fn version(&self) -> Option<i64> {
+        TimelineEdit::version(self)
+    }
+
+    fn set_version(&mut self, version: Option<i64>) {
+        TimelineEdit::set_version(self, version)
+    }
+}
+
+/// A JSON response with the `ETag` of its version (if known)
+#[derive(Debug)]
+pub struct Tagged<T>(pub T);
+
+impl<T: HasVersion + Serialize> IntoResponse for Tagged<T> {
+    fn into_response(self) -> Response {
+        let etag = self.0.version().map(etag);
+        let mut response = Json(self.0).into_response();
+        if let Some(etag) = etag {
+            response.headers_mut().insert(header::ETAG, etag);
+        }
+        response
+    }
+}
+
+/// The version a request's `If-Match` header expects (none if it doesn't have
+/// one, or if it's `*`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IfMatch(pub Option<i64>);
+
+impl IfMatch {
+    /// Make updates of the thing fail if its version isn't the expected one
+    pub fn apply<T: HasVersion>(&self, thing: &mut T) {
+        if let Some(version) = self.0 {
+            thing.set_version(Some(version));
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(Self(None));
+        };
+        let value = value
+            .to_str()
+            .map_err(|_| ApiError::bad_request("The If-Match header isn't valid"))?
+            .trim();
+        if value == "*" {
+            return Ok(Self(None));
+        }
+
+        // Anything other than one of our (strong) ETags can never match
+        version_from_etag(value)
+            .map(|version| Self(Some(version)))
+            .ok_or_else(ApiError::precondition_failed)
+    }
+}
+
+/// The `ETag` of the version
+fn etag(version: i64) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{version}\"")).expect("digits are a valid header")
+}
+
+/// The version in the `ETag` (if it's one of ours)
+fn version_from_etag(etag: &str) -> Option<i64> {
+    etag.strip_prefix('"')?.strip_suffix('"')?.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::{create_entity, read_write_router, send};
+    use axum::http::{Method, StatusCode};
+    use sqlx::{Pool, Sqlite};
+
+    #[test]
+    fn etags_round_trip() {
+        assert_eq!(etag(3), "\"3\"");
+        assert_eq!(version_from_etag("\"3\""), Some(3));
+        assert_eq!(version_from_etag("W/\"3\""), None);
+        assert_eq!(version_from_etag("3"), None);
+    }
+
+    /// Send a request for the entity, returning the status & the `ETag` (if any)
+    async fn send_for_entity(
+        router: &axum::Router,
+        method: Method,
+        if_match: Option<&str>,
+        body: String,
+    ) -> (StatusCode, Option<String>) {
+        let mut headers = vec![(header::CONTENT_TYPE, "application/json")];
+        if let Some(if_match) = if_match {
+            headers.push((header::IF_MATCH, if_match));
+        }
+        let response = send(router, method, "/entity/Entity", &headers, body).await;
+        let etag = response
+            .headers
+            .get(header::ETAG)
+            .map(|etag| etag.to_str().unwrap().to_string());
+        (response.status, etag)
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn stale_updates_are_rejected(pool: Pool<Sqlite>) {
+        let entity = create_entity(&pool, None).await;
+        let router = read_write_router(pool);
+        let body = serde_json::to_string(&entity).unwrap();
+
+        // Fetching gives the ETag
+        let (status, fetched_etag) =
+            send_for_entity(&router, Method::GET, None, String::new()).await;
+        assert_eq!(status, StatusCode::OK);
+        let fetched_etag = fetched_etag.unwrap();
+
+        // Updating with it works, & gives the new ETag
+        let (status, updated_etag) =
+            send_for_entity(&router, Method::PATCH, Some(&fetched_etag), body.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_ne!(updated_etag.as_ref(), Some(&fetched_etag));
+
+        // Updating with the old ETag (or one that isn't ours) fails
+        for if_match in [fetched_etag.as_str(), "W/\"0\"", "nonsense"] {
+            let (status, _) =
+                send_for_entity(&router, Method::PATCH, Some(if_match), body.clone()).await;
+            assert_eq!(status, StatusCode::PRECONDITION_FAILED, "{if_match}");
+        }
+
+        // Updating without an ETag (or with any) always works
+        for if_match in [None, Some("*")] {
+            let (status, _) = send_for_entity(&router, Method::PATCH, if_match, body.clone()).await;
+            assert_eq!(status, StatusCode::OK);
+        }
+    }
+}
@@ -21,7 +21,20 @@ pub fn router(api_mode: ApiMode) -> Result<Router<Arc<Pool<Sqlite>>>, sqlx::Erro
         .route("/entity/{id-or-name}/timelines", get(non_dynamic::entity::handle_get_entity_direct_member_of_which_timelines))
         .route("/timeline/{id-or-name}/edit",    get(non_dynamic::timeline::handle_get_timeline_for_edit))
         .route("/timeline/{id-or-name}/view",    get(non_dynamic::timeline::handle_get_timeline_for_view))
-        .route("/tags",                          get(non_dynamic::tags::handle_get_tags));
+        .route("/timeline/{id-or-name}/render",  get(non_dynamic::timeline::handle_get_timeline_render))
+        .route("/timeline/{id-or-name}/dynamic", get(non_dynamic::timeline::handle_get_dynamic_timeline))
+        .route("/timelines/{id-or-name}/entities", get(non_dynamic::timeline::handle_get_timeline_entities))
+        .route("/entity/{id-or-name}/slug",      get(non_dynamic::permalink::handle_get_entity_slug))
+        .route("/timeline/{id-or-name}/slug",    get(non_dynamic::permalink::handle_get_timeline_slug))
+        .route("/e/{slug}",                      get(non_dynamic::permalink::handle_get_entity_by_slug))
+        .route("/t/{slug}",                      get(non_dynamic::permalink::handle_get_timeline_by_slug))
+        .route("/timelines/dynamic",             get(non_dynamic::timelines::handle_get_dynamic_timelines))
+        .route("/entities",                      get(non_dynamic::entities::handle_get_entities_modified_since))
+        .route("/timelines",                     get(non_dynamic::timelines::handle_get_timelines_modified_since))
+        .route("/entities/per-decade",           get(non_dynamic::entities::handle_get_entity_counts_per_decade))
+        .route("/tags",                          get(non_dynamic::tags::handle_get_tags))
+        .route("/healthz",                       get(non_dynamic::health::handle_get_healthz))
+        .route("/readyz",                        get(non_dynamic::health::handle_get_readyz));
 
     let apiv1 = match api_mode {
         ApiMode::Static => {
@@ -39,7 +52,11 @@ pub fn router(api_mode: ApiMode) -> Result<Router<Arc<Pool<Sqlite>>>, sqlx::Erro
                 .route("/entities/reduced",      get(dynamic::entities::handle_get_entities_reduced))
                 .route("/timelines/reduced",     get(dynamic::timelines::handle_get_timelines_reduced))
                 .route("/entities/random",       get(dynamic::entities::handle_get_random_entities))
-                .route("/timelines/random",      get(dynamic::timelines::handle_get_random_timelines));
+                .route("/entities/near",         get(dynamic::entities::handle_get_entities_near))
+                .route("/entities/page",         get(dynamic::pages::handle_get_entities_page))
+                .route("/on-this-day",           get(dynamic::entities::handle_get_on_this_day))
+                .route("/timelines/random",      get(dynamic::timelines::handle_get_random_timelines))
+                .route("/timelines/page",        get(dynamic::pages::handle_get_timelines_page));
             apiv1
         }
     };
@@ -5,4 +5,5 @@
 //!
 
 pub mod entities;
+pub mod pages;
 pub mod timelines;
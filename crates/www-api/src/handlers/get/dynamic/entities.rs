@@ -4,15 +4,17 @@
 //! Web API for fetching more than 1 entity at a time
 //!
 
-use crate::helpers::ErrorMsg;
 use crate::{
-    ApiError, DEFAULT_LIMIT_RANDOM_ENTITIES, MAX_LIMIT_RANDOM_ENTITIES, PartialNameQueryParams,
+    ApiError, DEFAULT_LIMIT_RANDOM_ENTITIES, MAX_LIMIT_NEAR_ENTITIES, MAX_LIMIT_RANDOM_ENTITIES,
+    NearQueryParams, OnThisDayQueryParams, PartialNameQueryParams,
 };
 use axum::Json;
 use axum::extract::{Query, State};
-use axum::http::StatusCode;
-use open_timeline_core::{Entity, ReducedEntities};
-use open_timeline_crud::{FetchByPartialName, Limit, fetch_random_entities};
+use bool_tag_expr::BoolTagExpr;
+use open_timeline_core::{Coordinates, Date, Entity, ReducedEntities};
+use open_timeline_crud::{
+    FetchByPartialName, Limit, OnThisDay, entities_near, fetch_random_entities,
+};
 use sqlx::{Pool, Sqlite};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -27,12 +29,9 @@ pub async fn handle_get_entities_reduced(
 
     // TODO: is this correct?
     if params.partial_name.is_empty() {
-        return Err(ApiError((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorMsg {
-                error_msg: "No 'partial-name' or empty in query param".to_string(),
-            }),
-        )));
+        return Err(ApiError::bad_request(
+            "No 'partial-name' or empty in query param".to_string(),
+        ));
     }
 
     Ok(Json(
@@ -41,7 +40,8 @@ pub async fn handle_get_entities_reduced(
 }
 
 // TODO: what query string is accepted? I think it's `limit=X`
-/// Handle a request to fetch some random entities
+/// Handle a request to fetch some random entities (optionally only those that
+/// match the `bool-expr` query param)
 pub async fn handle_get_random_entities(
     State(pool): State<Arc<Pool<Sqlite>>>,
     Query(params): Query<HashMap<String, String>>,
@@ -56,6 +56,57 @@ pub async fn handle_get_random_entities(
             Limit(value.min(MAX_LIMIT_RANDOM_ENTITIES))
         });
 
+    let bool_expr = params
+        .get("bool-expr")
+        .map(|bool_expr| BoolTagExpr::from(bool_expr.clone()))
+        .transpose()
+        .map_err(|error| {
+            ApiError::bad_request(format!("Invalid 'bool-expr' query param: {error}"))
+        })?;
+
     // TODO: proper error checking
-    Ok(Json(fetch_random_entities(&mut transaction, limit).await?))
+    Ok(Json(
+        fetch_random_entities(&mut transaction, limit, bool_expr.as_ref()).await?,
+    ))
+}
+
+/// Handle a request to fetch the entities that started or ended on a day of
+/// the year
+pub async fn handle_get_on_this_day(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Query(params): Query<OnThisDayQueryParams>,
+) -> Result<Json<OnThisDay>, ApiError> {
+    // Get the transaction
+    let mut transaction = pool.begin().await.unwrap();
+
+    // Check the day exists (in a leap year, so that the 29th of Feb is allowed)
+    Date::from(Some(params.day.into()), Some(params.month.into()), 2000)
+        .map_err(|error| ApiError::bad_request(error.to_string()))?;
+
+    Ok(Json(
+        OnThisDay::fetch(&mut transaction, params.month, params.day).await?,
+    ))
+}
+
+/// Handle a request to fetch the entities near some coordinates (nearest first)
+pub async fn handle_get_entities_near(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Query(params): Query<NearQueryParams>,
+) -> Result<Json<Vec<Entity>>, ApiError> {
+    // Get the transaction
+    let mut transaction = pool.begin().await.unwrap();
+
+    let coordinates = Coordinates::from(params.latitude, params.longitude)
+        .map_err(|error| ApiError::bad_request(error.to_string()))?;
+    if !params.radius_km.is_finite() || params.radius_km < 0.0 {
+        return Err(ApiError::bad_request(
+            "The 'radius-km' query param must be positive".to_string(),
+        ));
+    }
+    let Limit(limit) = params.limit;
+    let limit = Limit(limit.min(MAX_LIMIT_NEAR_ENTITIES));
+
+    Ok(Json(
+        entities_near(&mut transaction, &coordinates, params.radius_km, limit).await?,
+    ))
 }
@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Web API for fetching every entity or timeline a page at a time.  The `next`
+//! of each page is the `after` query param of the next page.
+//!
+
+use crate::{ApiError, MAX_LIMIT_PAGE, PageQueryParams};
+use axum::Json;
+use axum::extract::{Query, State};
+use open_timeline_core::{IsReducedType, Name, OpenTimelineId};
+use open_timeline_crud::{Limit, fetch_entities_page, fetch_timelines_page};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::sync::Arc;
+
+/// An entity or timeline in a page
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PageItem {
+    pub id: OpenTimelineId,
+    pub name: Name,
+
+    /// When it was created (as a unix time in milliseconds), if its ID is
+    /// time-sortable
+    pub created_at: Option<u64>,
+}
+
+/// A page of entities or timelines (ordered by ID)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page {
+    pub items: Vec<PageItem>,
+
+    /// The cursor of the next page (if there may be one)
+    pub next: Option<OpenTimelineId>,
+}
+
+impl Page {
+    /// Create the page from what was fetched with the limit
+    fn from<T: IsReducedType>(items: Vec<T>, Limit(limit): Limit) -> Self {
+        let next = match items.len() == limit as usize {
+            true => items.last().map(|item| item.id()),
+            false => None,
+        };
+        let items = items
+            .into_iter()
+            .map(|item| PageItem {
+                id: item.id(),
+                name: item.name().clone(),
+                created_at: item.id().created_at(),
+            })
+            .collect();
+        Self { items, next }
+    }
+}
+
+/// Handle a request to fetch a page of entities
+pub async fn handle_get_entities_page(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Query(params): Query<PageQueryParams>,
+) -> Result<Json<Page>, ApiError> {
+    let mut transaction = pool.begin().await?;
+    let limit = page_limit(params.limit)?;
+    let entities =
+        fetch_entities_page(&mut transaction, params.after.as_ref(), limit.clone()).await?;
+    Ok(Json(Page::from(entities, limit)))
+}
+
+/// Handle a request to fetch a page of timelines
+pub async fn handle_get_timelines_page(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Query(params): Query<PageQueryParams>,
+) -> Result<Json<Page>, ApiError> {
+    let mut transaction = pool.begin().await?;
+    let limit = page_limit(params.limit)?;
+    let timelines =
+        fetch_timelines_page(&mut transaction, params.after.as_ref(), limit.clone()).await?;
+    Ok(Json(Page::from(timelines, limit)))
+}
+
+/// Check the limit isn't 0 (which would never reach the end), & cap it
+fn page_limit(Limit(limit): Limit) -> Result<Limit, ApiError> {
+    match limit {
+        0 => Err(ApiError::bad_request(
+            "The 'limit' query param must be above 0".to_string(),
+        )),
+        limit => Ok(Limit(limit.min(MAX_LIMIT_PAGE))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ApiAccessMode, ApiMode, pool_router};
+    use axum::body::{Body, to_bytes};
+    use axum::http::{Request, StatusCode};
+    use open_timeline_core::{Date, Entity};
+    use open_timeline_crud::Create;
+    use tower::ServiceExt;
+
+    /// Fetch a page of entities
+    async fn get_page(router: &axum::Router, uri: &str) -> (StatusCode, Option<Page>) {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).ok())
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn entities_are_paged(pool: Pool<Sqlite>) {
+        // 1 entity with a random ID & 2 with time-sortable IDs
+        let mut transaction = pool.begin().await.unwrap();
+        let ids = [
+            OpenTimelineId::new_random(),
+            OpenTimelineId::new_time_sortable(),
+            OpenTimelineId::new_time_sortable(),
+        ];
+        for (index, id) in ids.iter().enumerate() {
+            let mut entity = Entity::from(
+                Some(*id),
+                Name::from(format!("Entity {index}")).unwrap(),
+                Date::from(None, None, 1900).unwrap(),
+                None,
+                None,
+            )
+            .unwrap();
+            entity.create(&mut transaction).await.unwrap();
+        }
+        transaction.commit().await.unwrap();
+        let router = pool_router(pool, ApiAccessMode::Read, ApiMode::Dynamic).unwrap();
+
+        // Follow the cursors to the end
+        let mut paged = Vec::new();
+        let mut uri = String::from("/entities/page?limit=2");
+        loop {
+            let (status, page) = get_page(&router, &uri).await;
+            assert_eq!(status, StatusCode::OK);
+            let page = page.unwrap();
+            for item in &page.items {
+                assert_eq!(item.created_at, item.id.created_at());
+                paged.push(item.id);
+            }
+            match page.next {
+                Some(next) => uri = format!("/entities/page?limit=2&after={next}"),
+                None => break,
+            }
+        }
+        let mut sorted = ids.to_vec();
+        sorted.sort();
+        assert_eq!(paged, sorted);
+        assert_eq!(
+            paged.iter().filter(|id| id.created_at().is_some()).count(),
+            2
+        );
+
+        // A limit of 0 would never get anywhere
+        let (status, _) = get_page(&router, "/entities/page?limit=0").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -4,11 +4,11 @@
 //! Web API for fetching more than 1 timeline at a time
 //!
 
-use crate::{ApiError, PartialNameQueryParams, helpers::ErrorMsg};
+use crate::{ApiError, PartialNameQueryParams};
 use crate::{DEFAULT_LIMIT_RANDOM_TIMELINES, MAX_LIMIT_RANDOM_TIMELINES};
 use axum::Json;
 use axum::extract::Query;
-use axum::{extract::State, http::StatusCode};
+use axum::extract::State;
 use open_timeline_core::ReducedTimelines;
 use open_timeline_crud::{FetchByPartialName, Limit};
 use sqlx::{Pool, Sqlite};
@@ -24,12 +24,9 @@ pub async fn handle_get_timelines_reduced(
 
     // TODO: should this be an error? or return all (with default limit?)
     if params.partial_name.is_empty() {
-        return Err(ApiError((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorMsg {
-                error_msg: "No 'partial-name' or empty in query param".to_string(),
-            }),
-        )));
+        return Err(ApiError::bad_request(
+            "No 'partial-name' or empty in query param".to_string(),
+        ));
     }
     Ok(Json(
         ReducedTimelines::fetch_by_partial_name(
@@ -6,6 +6,8 @@
 
 pub mod entities;
 pub mod entity;
+pub mod health;
+pub mod permalink;
 pub mod tags;
 pub mod timeline;
 pub mod timelines;
@@ -4,12 +4,16 @@
 //! Static Web API for fetching more than 1 entity at a time
 //!
 
-use crate::ApiError;
+use crate::{ApiError, DecadeCountsQueryParams, ModifiedSinceQueryParams};
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Query, State};
 use open_timeline_core::{Entity, IsReducedType, ReducedEntities};
-use open_timeline_crud::{FetchAll, FetchById};
+use open_timeline_crud::{
+    FetchAll, FetchById, ModifiedEntities, database_time, entity_counts_per_decade,
+    fetch_entity_ids_modified_since,
+};
 use sqlx::{Pool, Sqlite};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 /// Handle a request to fetch all [`ReducedEntities`]
@@ -31,3 +35,34 @@ pub async fn handle_get_entities_full(
     }
     Ok(Json(full))
 }
+
+/// Handle a request to fetch the [`Entity`]s modified at or after a (unix)
+/// time (so that clients only fetch what has changed)
+pub async fn handle_get_entities_modified_since(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Query(params): Query<ModifiedSinceQueryParams>,
+) -> Result<Json<ModifiedEntities>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let fetched_at = database_time(&mut transaction).await?;
+    let mut entities = Vec::new();
+    for id in fetch_entity_ids_modified_since(&mut transaction, params.modified_since).await? {
+        entities.push(Entity::fetch_by_id(&mut transaction, &id).await?);
+    }
+    Ok(Json(ModifiedEntities {
+        fetched_at,
+        entities,
+    }))
+}
+
+/// Handle a request to count the entities that start in each decade (keyed by
+/// the decade's first year), optionally only those matching the `bool-expr`
+/// query param (e.g. for a chart of how densely the timeline is populated)
+pub async fn handle_get_entity_counts_per_decade(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Query(params): Query<DecadeCountsQueryParams>,
+) -> Result<Json<BTreeMap<i64, i64>>, ApiError> {
+    let mut transaction = pool.begin().await?;
+    Ok(Json(
+        entity_counts_per_decade(&mut transaction, params.bool_expr.as_ref()).await?,
+    ))
+}
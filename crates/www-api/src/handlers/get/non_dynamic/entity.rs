@@ -4,7 +4,7 @@
 //! Web API for a single entity
 //!
 
-use crate::{ApiError, helpers::*};
+use crate::{ApiError, Tagged, helpers::*};
 use axum::Json;
 use axum::extract::{Path, State};
 use open_timeline_core::{Entity, ReducedTimelines};
@@ -16,11 +16,11 @@ use std::sync::Arc;
 pub async fn handle_get_entity(
     State(pool): State<Arc<Pool<Sqlite>>>,
     Path(id_or_name): Path<String>,
-) -> Result<Json<Entity>, ApiError> {
+) -> Result<Tagged<Entity>, ApiError> {
     let mut transaction = pool.begin().await.unwrap();
     let id = entity_id_from_id_or_name(&mut transaction, id_or_name).await?;
     let entity = Entity::fetch_by_id(&mut transaction, &id).await?;
-    Ok(Json(entity))
+    Ok(Tagged(entity))
 }
 
 /// Handle a request to delete an entity
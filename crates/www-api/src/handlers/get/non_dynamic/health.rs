@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Health & readiness probes (for load balancers & container orchestrators)
+//!
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use open_timeline_crud::fetch_pending_migrations;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::sync::Arc;
+
+/// Whether the process is up
+#[derive(Debug, Serialize)]
+pub struct Health {
+    pub status: &'static str,
+}
+
+/// Whether the API is ready to serve requests (& why not if it isn't)
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    /// Whether the API is ready
+    pub ready: bool,
+
+    /// Whether the database could be queried
+    pub database_reachable: bool,
+
+    /// The versions of the migrations that haven't been applied to the database
+    /// (if they could be fetched)
+    pub pending_migrations: Option<Vec<i64>>,
+
+    /// The database connection pool's statistics
+    pub pool: PoolStats,
+
+    /// Why the API isn't ready (if it isn't)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_msg: Option<String>,
+}
+
+/// Statistics about the database connection pool
+#[derive(Debug, Serialize)]
+pub struct PoolStats {
+    /// The number of open connections
+    pub size: u32,
+
+    /// The number of open connections that aren't in use
+    pub idle: usize,
+
+    /// The maximum number of connections
+    pub max_connections: u32,
+}
+
+/// Handle a request to check the process is up (it doesn't touch the database)
+pub async fn handle_get_healthz() -> Json<Health> {
+    Json(Health { status: "ok" })
+}
+
+/// Handle a request to check the API is ready: the database can be reached, &
+/// all migrations have been applied to it.  Responds with `503 Service
+/// Unavailable` if not.
+pub async fn handle_get_readyz(State(pool): State<Arc<Pool<Sqlite>>>) -> Response {
+    let reachable = sqlx::query("SELECT 1").execute(&*pool).await;
+    let pending_migrations = match &reachable {
+        Ok(_) => Some(fetch_pending_migrations(&pool).await),
+        Err(_) => None,
+    };
+    let error_msg = match (&reachable, &pending_migrations) {
+        (Err(error), _) => Some(format!("The database can't be reached: {error}")),
+        (Ok(_), Some(Err(error))) => Some(format!("The migrations can't be checked: {error}")),
+        (Ok(_), Some(Ok(pending))) if !pending.is_empty() => Some(format!(
+            "{} migration(s) haven't been applied",
+            pending.len()
+        )),
+        _ => None,
+    };
+    let readiness = Readiness {
+        ready: error_msg.is_none(),
+        database_reachable: reachable.is_ok(),
+        pending_migrations: pending_migrations.and_then(Result::ok),
+        pool: PoolStats {
+            size: pool.size(),
+            idle: pool.num_idle(),
+            max_connections: pool.options().get_max_connections(),
+        },
+        error_msg,
+    };
+    let status = match readiness.ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (status, Json(readiness)).into_response()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::body::to_bytes;
+
+    /// Get a response's status & (JSON) body
+    async fn status_and_body(response: Response) -> (StatusCode, serde_json::Value) {
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn ready(pool: Pool<Sqlite>) {
+        let response = handle_get_readyz(State(Arc::new(pool))).await;
+        let (status, body) = status_and_body(response).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["ready"], true);
+        assert_eq!(body["database_reachable"], true);
+        assert_eq!(body["pending_migrations"], serde_json::json!([]));
+        assert!(body.get("error_msg").is_none());
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn not_ready_without_migrations(pool: Pool<Sqlite>) {
+        let response = handle_get_readyz(State(Arc::new(pool))).await;
+        let (status, body) = status_and_body(response).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["database_reachable"], true);
+    }
+}
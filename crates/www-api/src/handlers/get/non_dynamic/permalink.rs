@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Web API for permalinks (fetching entities & timelines by their slugs)
+//!
+
+use crate::{ApiError, helpers::*};
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Redirect, Response};
+use open_timeline_core::{Entity, Slug, TimelineView};
+use open_timeline_crud::{CrudError, FetchById, SlugKind, SlugLookup, fetch_slug, id_from_slug};
+use sqlx::{Pool, Sqlite, Transaction};
+use std::sync::Arc;
+
+/// Handle a request to fetch an entity by its slug.  Old slugs permanently
+/// redirect to the current one.
+pub async fn handle_get_entity_by_slug(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(slug): Path<String>,
+) -> Result<Response, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    match lookup(&mut transaction, SlugKind::Entity, slug).await? {
+        SlugLookup::Current(id) => {
+            let entity = Entity::fetch_by_id(&mut transaction, &id).await?;
+            Ok(Json(entity).into_response())
+        }
+        SlugLookup::Redirect(_, slug) => {
+            Ok(Redirect::permanent(&format!("/api/v1/e/{slug}")).into_response())
+        }
+    }
+}
+
+/// Handle a request to fetch a timeline (for viewing) by its slug.  Old slugs
+/// permanently redirect to the current one.
+pub async fn handle_get_timeline_by_slug(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(slug): Path<String>,
+) -> Result<Response, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    match lookup(&mut transaction, SlugKind::Timeline, slug).await? {
+        SlugLookup::Current(id) => {
+            let timeline = TimelineView::fetch_by_id(&mut transaction, &id).await?;
+            Ok(Json(timeline).into_response())
+        }
+        SlugLookup::Redirect(_, slug) => {
+            Ok(Redirect::permanent(&format!("/api/v1/t/{slug}")).into_response())
+        }
+    }
+}
+
+/// Handle a request to get the slug of an entity
+pub async fn handle_get_entity_slug(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(id_or_name): Path<String>,
+) -> Result<Json<SlugJson>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let id = entity_id_from_id_or_name(&mut transaction, id_or_name).await?;
+    let slug = fetch_slug(&mut transaction, SlugKind::Entity, &id)
+        .await?
+        .ok_or(CrudError::NotInDb)?;
+    Ok(Json(SlugJson { slug }))
+}
+
+/// Handle a request to get the slug of a timeline
+pub async fn handle_get_timeline_slug(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(id_or_name): Path<String>,
+) -> Result<Json<SlugJson>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let id = timeline_id_from_id_or_name(&mut transaction, id_or_name).await?;
+    let slug = fetch_slug(&mut transaction, SlugKind::Timeline, &id)
+        .await?
+        .ok_or(CrudError::NotInDb)?;
+    Ok(Json(SlugJson { slug }))
+}
+
+/// Find what a slug from a URL belongs to (an invalid slug can't belong to
+/// anything)
+async fn lookup(
+    transaction: &mut Transaction<'_, Sqlite>,
+    kind: SlugKind,
+    slug: String,
+) -> Result<SlugLookup, ApiError> {
+    let slug = Slug::from(slug).map_err(|_| CrudError::NotInDb)?;
+    Ok(id_from_slug(transaction, kind, &slug).await?)
+}
@@ -4,19 +4,31 @@
 //! Web API for a single timeline
 //!
 
-use crate::ApiError;
+use crate::{
+    ApiError, EntityFields, EntityFieldsQueryParams, ExportFormat, ExportQueryParams,
+    MAX_RENDER_DIMENSION, RenderFormat, RenderQueryParams, Tagged,
+    helpers::timeline_id_from_id_or_name,
+};
 use axum::Json;
-use axum::extract::{Path, State};
-use open_timeline_core::{TimelineEdit, TimelineView};
-use open_timeline_crud::{self, CrudError, FetchById, FetchByName, IdOrName, timeline_id_or_name};
-use sqlx::{Pool, Sqlite};
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use open_timeline_core::{DateFormat, TimelineEdit, TimelineJs, TimelineView};
+use open_timeline_crud::{
+    self, CrudError, DynamicTimeline, FetchById, FetchByName, IdOrName,
+    fetch_timeline_render_entities, timeline_id_or_name,
+};
+use open_timeline_renderer::frontends::svg::render_svg;
+use open_timeline_renderer::{DEFAULT_MEASURER_FONT, DEFAULT_MEASURER_FONT_FAMILY, Engine};
+use resvg::{tiny_skia, usvg};
+use sqlx::{Pool, Sqlite, Transaction};
 use std::sync::Arc;
 
 /// Handle a request to get a timeline for editing (i.e. a [`TimelineEdit`])
 pub async fn handle_get_timeline_for_edit(
     State(pool): State<Arc<Pool<Sqlite>>>,
     Path(id_or_name): Path<String>,
-) -> Result<Json<TimelineEdit>, ApiError> {
+) -> Result<Tagged<TimelineEdit>, ApiError> {
     let mut transaction = pool.begin().await.unwrap();
     let timeline = match timeline_id_or_name(&mut transaction, id_or_name).await? {
         Some(IdOrName::Id(id)) => Ok(TimelineEdit::fetch_by_id(&mut transaction, &id).await?),
@@ -25,22 +37,130 @@ pub async fn handle_get_timeline_for_edit(
         }
         None => Err(CrudError::NotInDb),
     }?;
-    Ok(Json(timeline))
+    Ok(Tagged(timeline))
 }
 
-/// Handle a request to get a timeline for viewing (i.e. a [`TimelineView`])
+/// Handle a request to get a timeline for viewing (i.e. a [`TimelineView`]).
+/// With `format=timelinejs` it is converted to TimelineJS's JSON.
 pub async fn handle_get_timeline_for_view(
     State(pool): State<Arc<Pool<Sqlite>>>,
     Path(id_or_name): Path<String>,
-) -> Result<Json<TimelineView>, ApiError> {
+    Query(params): Query<ExportQueryParams>,
+) -> Result<Response, ApiError> {
     let mut transaction = pool.begin().await.unwrap();
+    let timeline = fetch_timeline_view(&mut transaction, id_or_name).await?;
+    match params.format {
+        ExportFormat::OpenTimeline => Ok(Json(timeline).into_response()),
+        ExportFormat::TimelineJs => Ok(Json(TimelineJs::from(&timeline)).into_response()),
+    }
+}
+
+/// Handle a request to get a dynamic timeline (one defined purely by a boolean
+/// tag expression).  Its entities are fetched like any other timeline's.
+pub async fn handle_get_dynamic_timeline(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(id_or_name): Path<String>,
+) -> Result<Json<DynamicTimeline>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let id = timeline_id_from_id_or_name(&mut transaction, id_or_name).await?;
     Ok(Json(
-        match timeline_id_or_name(&mut transaction, id_or_name).await? {
-            Some(IdOrName::Id(id)) => Ok(TimelineView::fetch_by_id(&mut transaction, &id).await?),
-            Some(IdOrName::Name(name)) => {
-                Ok(TimelineView::fetch_by_name(&mut transaction, &name).await?)
-            }
-            None => Err(CrudError::NotInDb),
-        }?,
+        DynamicTimeline::fetch_by_id(&mut transaction, &id).await?,
     ))
 }
+
+/// Handle a request to get all the entities of a timeline (including those of
+/// its subtimelines).  With `fields=render` only what is needed to render each
+/// entity is fetched & sent.
+pub async fn handle_get_timeline_entities(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(id_or_name): Path<String>,
+    Query(params): Query<EntityFieldsQueryParams>,
+) -> Result<Response, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    match params.fields {
+        EntityFields::Full => {
+            let timeline = fetch_timeline_view(&mut transaction, id_or_name).await?;
+            let entities = timeline.entities().clone().unwrap_or_default();
+            Ok(Json(entities).into_response())
+        }
+        EntityFields::Render => {
+            let id = timeline_id_from_id_or_name(&mut transaction, id_or_name).await?;
+            let entities = fetch_timeline_render_entities(&mut transaction, &id)
+                .await?
+                .unwrap_or_default();
+            Ok(Json(entities).into_response())
+        }
+    }
+}
+
+/// Handle a request to render a timeline to an image (SVG or PNG)
+pub async fn handle_get_timeline_render(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(id_or_name): Path<String>,
+    Query(params): Query<RenderQueryParams>,
+) -> Result<Response, ApiError> {
+    let width = params.width.clamp(1, MAX_RENDER_DIMENSION);
+    let height = params.height.clamp(1, MAX_RENDER_DIMENSION);
+    let date_format = DateFormat {
+        order: params.date_order,
+        month_style: params.month_style,
+        era_style: params.era_style,
+    };
+
+    let mut transaction = pool.begin().await.unwrap();
+    let timeline = fetch_timeline_view(&mut transaction, id_or_name).await?;
+    let entities = timeline.entities().clone().unwrap_or_default();
+
+    // Rendering is CPU bound, so keep it off the async runtime
+    tokio::task::spawn_blocking(move || {
+        let mut engine = Engine::new_with_default_measurer();
+        engine.set_canvas_max(width.into(), height.into());
+        engine.set_date_format(date_format);
+        engine.set_entities(entities);
+        let font_family = format!("{DEFAULT_MEASURER_FONT_FAMILY}, sans-serif");
+        let svg = render_svg(&mut engine, width.into(), height.into(), &font_family);
+        match params.format {
+            RenderFormat::Svg => {
+                Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response())
+            }
+            RenderFormat::Png => {
+                let png = svg_to_png(&svg).map_err(render_error)?;
+                Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+            }
+        }
+    })
+    .await
+    .map_err(|error| render_error(error.to_string()))?
+}
+
+/// Fetch the [`TimelineView`] with the given ID or name
+async fn fetch_timeline_view(
+    transaction: &mut Transaction<'_, Sqlite>,
+    id_or_name: String,
+) -> Result<TimelineView, ApiError> {
+    match timeline_id_or_name(transaction, id_or_name).await? {
+        Some(IdOrName::Id(id)) => Ok(TimelineView::fetch_by_id(transaction, &id).await?),
+        Some(IdOrName::Name(name)) => Ok(TimelineView::fetch_by_name(transaction, &name).await?),
+        None => Err(CrudError::NotInDb.into()),
+    }
+}
+
+/// Rasterise an SVG document to a PNG
+fn svg_to_png(svg: &str) -> Result<Vec<u8>, String> {
+    // The bundled font is the one text was measured with
+    let mut options = usvg::Options::default();
+    options
+        .fontdb_mut()
+        .load_font_data(DEFAULT_MEASURER_FONT.to_vec());
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|error| error.to_string())?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| "Unable to allocate the image".to_string())?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|error| error.to_string())
+}
+
+/// An error response for a failed render
+fn render_error(error_msg: String) -> ApiError {
+    ApiError::internal(error_msg)
+}
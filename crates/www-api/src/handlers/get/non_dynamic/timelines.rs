@@ -4,11 +4,14 @@
 //! Static Web API for fetching more than 1 timeline at a time
 //!
 
-use crate::ApiError;
+use crate::{ApiError, ModifiedSinceQueryParams};
 use axum::Json;
-use axum::extract::State;
-use open_timeline_core::{IsReducedType, ReducedTimelines, TimelineEdit};
-use open_timeline_crud::{FetchAll, FetchById};
+use axum::extract::{Query, State};
+use open_timeline_core::{IsReducedCollection, IsReducedType, ReducedTimelines, TimelineEdit};
+use open_timeline_crud::{
+    FetchAll, FetchById, ModifiedTimelines, database_time, fetch_dynamic_timeline_ids,
+    fetch_timeline_ids_modified_since,
+};
 use sqlx::{Pool, Sqlite};
 use std::sync::Arc;
 
@@ -31,3 +34,35 @@ pub async fn handle_get_timelines_edit(
     }
     Ok(Json(full))
 }
+
+/// Handle a request to fetch the [`TimelineEdit`]s of the timelines modified
+/// at or after a (unix) time (so that clients only fetch what has changed)
+pub async fn handle_get_timelines_modified_since(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Query(params): Query<ModifiedSinceQueryParams>,
+) -> Result<Json<ModifiedTimelines>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let fetched_at = database_time(&mut transaction).await?;
+    let mut timelines = Vec::new();
+    for id in fetch_timeline_ids_modified_since(&mut transaction, params.modified_since).await? {
+        timelines.push(TimelineEdit::fetch_by_id(&mut transaction, &id).await?);
+    }
+    Ok(Json(ModifiedTimelines {
+        fetched_at,
+        timelines,
+    }))
+}
+
+/// Handle a request to fetch the [`ReducedTimelines`] of all dynamic timelines
+/// (those defined purely by a boolean tag expression)
+pub async fn handle_get_dynamic_timelines(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+) -> Result<Json<ReducedTimelines>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let dynamic_ids = fetch_dynamic_timeline_ids(&mut transaction).await?;
+    let mut timelines = ReducedTimelines::fetch_all(&mut transaction).await?;
+    timelines
+        .collection_mut()
+        .retain(|timeline| dynamic_ids.contains(&timeline.id()));
+    Ok(Json(timelines))
+}
@@ -5,13 +5,15 @@
 //!
 
 pub mod entity;
+pub mod merge;
 pub mod timeline;
 
 use axum::{
     Router,
-    routing::{patch, put},
+    routing::{delete, patch, post, put},
 };
 pub use entity::*;
+pub use merge::*;
 use sqlx::{Pool, Sqlite};
 use std::sync::Arc;
 pub use timeline::*;
@@ -24,11 +26,23 @@ pub fn router() -> Result<Router<Arc<Pool<Sqlite>>>, sqlx::Error> {
         .route("/entity",                                    put(handle_put_entity))
         .route("/entity/{id-or-name}",                       patch(handle_patch_entity)
                                                                                 .delete(handle_delete_entity))
+        .route("/entity/{id-or-name}/slug",                  patch(handle_patch_entity_slug))
+        .route("/merge",                                     put(handle_put_merge))
         .route("/timeline",                                  put(handle_put_timeline))
         .route("/timeline/{id-or-name}",                     patch(handle_patch_timeline)
                                                                                 .delete(handle_delete_timeline))
-        .route("/timeline/{id-or-name}/entity/{id-or-name}", put(handle_put_timeline_entity)
-                                                                                .delete(handle_delete_timeline_entity));
+        .route("/timeline/{id-or-name}/slug",                patch(handle_patch_timeline_slug))
+        .route("/timeline/{id-or-name}/entity/{entity-id-or-name}",
+                                                             put(handle_put_timeline_entity)
+                                                                                .delete(handle_delete_timeline_entity))
+        .route("/entities/{id-or-name}",                     patch(handle_merge_patch_entity))
+        .route("/timelines/{id-or-name}",                    patch(handle_merge_patch_timeline))
+        .route("/timelines/{id-or-name}/entities",           post(handle_post_timeline_entity))
+        .route("/timelines/{id-or-name}/entities/{entity-id-or-name}",
+                                                             delete(handle_delete_timeline_entity))
+        .route("/timelines/{id-or-name}/subtimelines",       post(handle_post_timeline_subtimeline))
+        .route("/timelines/{id-or-name}/subtimelines/{subtimeline-id-or-name}",
+                                                             delete(handle_delete_timeline_subtimeline));
 
     Ok(apiv1)
 }
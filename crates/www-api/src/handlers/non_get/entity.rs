@@ -4,19 +4,20 @@
 //! Web API for a single entity
 //!
 
-use crate::{ApiError, helpers::*};
+use crate::{ApiError, IfMatch, Payload, Tagged, helpers::*};
 use axum::Json;
 use axum::extract::{Path, State};
 use open_timeline_core::Entity;
-use open_timeline_crud::DeleteById;
+use open_timeline_crud::{DeleteById, FetchById, SlugKind, set_slug};
+use serde_json::Value;
 use sqlx::{Pool, Sqlite};
 use std::sync::Arc;
 
 /// Handle a request to create an entity
 pub async fn handle_put_entity(
     State(pool): State<Arc<Pool<Sqlite>>>,
-    Json(mut payload): Json<Entity>,
-) -> Result<Json<Entity>, ApiError> {
+    Payload(mut payload): Payload<Entity>,
+) -> Result<Tagged<Entity>, ApiError> {
     let mut transaction = pool.begin().await.unwrap();
 
     // TODO: move this into macro (was having difficulty)
@@ -26,13 +27,30 @@ pub async fn handle_put_entity(
     Ok(result)
 }
 
-/// Handle a request to update an entity
+/// Handle a request to update an entity (see [`IfMatch`])
 pub async fn handle_patch_entity(
     State(pool): State<Arc<Pool<Sqlite>>>,
-    Json(payload): Json<Entity>,
-) -> Result<Json<Entity>, ApiError> {
+    if_match: IfMatch,
+    Payload(payload): Payload<Entity>,
+) -> Result<Tagged<Entity>, ApiError> {
     let mut transaction = pool.begin().await.unwrap();
-    let result = patch(&mut transaction, payload).await?;
+    let result = patch(&mut transaction, payload, if_match).await?;
+    transaction.commit().await?;
+    Ok(result)
+}
+
+/// Handle a request to update some of an entity's fields (with a JSON merge
+/// patch, see [`merge_patch()`])
+pub async fn handle_merge_patch_entity(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(id_or_name): Path<String>,
+    if_match: IfMatch,
+    Payload(patch_json): Payload<Value>,
+) -> Result<Tagged<Entity>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let id = entity_id_from_id_or_name(&mut transaction, id_or_name).await?;
+    let current = Entity::fetch_by_id(&mut transaction, &id).await?;
+    let result = merge_patch(&mut transaction, current, patch_json, if_match).await?;
     transaction.commit().await?;
     Ok(result)
 }
@@ -49,3 +67,17 @@ pub async fn handle_delete_entity(
     // TODO: correct? Or wanted?
     Ok(Json(()))
 }
+
+/// Handle a request to change the slug of an entity (the old slug redirects to
+/// the new one)
+pub async fn handle_patch_entity_slug(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(id_or_name): Path<String>,
+    Payload(payload): Payload<SlugJson>,
+) -> Result<Json<SlugJson>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let id = entity_id_from_id_or_name(&mut transaction, id_or_name).await?;
+    set_slug(&mut transaction, SlugKind::Entity, &id, &payload.slug).await?;
+    transaction.commit().await?;
+    Ok(Json(payload))
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Web API for merging entities & timelines into the database (used when
+//! syncing)
+//!
+
+use crate::{ApiError, Payload};
+use axum::Json;
+use axum::extract::State;
+use open_timeline_crud::{MergeRequest, merge_entities_and_timelines};
+use sqlx::{Pool, Sqlite};
+use std::sync::Arc;
+
+/// Handle a request to merge entities & timelines into the database (adding
+/// those that don't exist and updating those that do)
+pub async fn handle_put_merge(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Payload(payload): Payload<MergeRequest>,
+) -> Result<Json<()>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    merge_entities_and_timelines(&mut transaction, payload.entities, payload.timelines).await?;
+    transaction.commit().await?;
+    Ok(Json(()))
+}
@@ -4,15 +4,18 @@
 //! Web API for a single timeline
 //!
 
-use crate::{ApiError, helpers::*};
+use crate::{ApiError, IfMatch, Payload, Tagged, helpers::*};
 use axum::Json;
 use axum::extract::{Path, State};
 use open_timeline_core::TimelineEdit;
-use open_timeline_crud::{CrudError, DeleteById, DeleteByName, IdOrName};
 use open_timeline_crud::{
-    delete_timeline_entity, entity_id_from_name, entity_id_or_name, insert_timeline_entity,
-    timeline_id_from_name, timeline_id_or_name,
+    CrudError, DeleteById, DeleteByName, FetchById, IdOrName, SlugKind, set_slug,
 };
+use open_timeline_crud::{
+    delete_timeline_entity, delete_timeline_subtimeline, insert_timeline_entity,
+    insert_timeline_subtimeline, timeline_id_or_name,
+};
+use serde_json::Value;
 use sqlx::{Pool, Sqlite};
 use std::sync::Arc;
 
@@ -21,8 +24,8 @@ use std::sync::Arc;
 /// Handle a request to create a timeline
 pub async fn handle_put_timeline(
     State(pool): State<Arc<Pool<Sqlite>>>,
-    Json(mut payload): Json<TimelineEdit>,
-) -> Result<Json<TimelineEdit>, ApiError> {
+    Payload(mut payload): Payload<TimelineEdit>,
+) -> Result<Tagged<TimelineEdit>, ApiError> {
     let mut transaction = pool.begin().await.unwrap();
 
     // TODO: correct? What if the ID is set and already exists? Should error?
@@ -33,13 +36,30 @@ pub async fn handle_put_timeline(
     Ok(result)
 }
 
-/// Handle a request to update a timeline
+/// Handle a request to update a timeline (see [`IfMatch`])
 pub async fn handle_patch_timeline(
     State(pool): State<Arc<Pool<Sqlite>>>,
-    Json(payload): Json<TimelineEdit>,
-) -> Result<Json<TimelineEdit>, ApiError> {
+    if_match: IfMatch,
+    Payload(payload): Payload<TimelineEdit>,
+) -> Result<Tagged<TimelineEdit>, ApiError> {
     let mut transaction = pool.begin().await.unwrap();
-    let result = patch(&mut transaction, payload).await?;
+    let result = patch(&mut transaction, payload, if_match).await?;
+    transaction.commit().await?;
+    Ok(result)
+}
+
+/// Handle a request to update some of a timeline's fields (with a JSON merge
+/// patch, see [`merge_patch()`])
+pub async fn handle_merge_patch_timeline(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(id_or_name): Path<String>,
+    if_match: IfMatch,
+    Payload(patch_json): Payload<Value>,
+) -> Result<Tagged<TimelineEdit>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let id = timeline_id_from_id_or_name(&mut transaction, id_or_name).await?;
+    let current = TimelineEdit::fetch_by_id(&mut transaction, &id).await?;
+    let result = merge_patch(&mut transaction, current, patch_json, if_match).await?;
     transaction.commit().await?;
     Ok(result)
 }
@@ -64,25 +84,26 @@ pub async fn handle_delete_timeline(
 /// Handle a request to add an entity to a timeline
 pub async fn handle_put_timeline_entity(
     State(pool): State<Arc<Pool<Sqlite>>>,
-    Path(timeline_id_or_name_str): Path<String>,
-    Path(entity_id_or_name_str): Path<String>,
+    Path((timeline_id_or_name, entity_id_or_name)): Path<(String, String)>,
 ) -> Result<Json<()>, ApiError> {
     let mut transaction = pool.begin().await.unwrap();
-
-    let timeline_id = match timeline_id_or_name(&mut transaction, timeline_id_or_name_str).await? {
-        Some(IdOrName::Id(id)) => id,
-        Some(IdOrName::Name(name)) => timeline_id_from_name(&mut transaction, &name).await?,
-        None => Err(CrudError::TimelineNotInDb)?,
-    };
-
-    let entity_id = match entity_id_or_name(&mut transaction, entity_id_or_name_str).await? {
-        Some(IdOrName::Id(id)) => id,
-        Some(IdOrName::Name(name)) => entity_id_from_name(&mut transaction, &name).await?,
-        None => Err(CrudError::EntityNotInDb)?,
-    };
-
+    let timeline_id = timeline_id_from_id_or_name(&mut transaction, timeline_id_or_name).await?;
+    let entity_id = entity_id_from_id_or_name(&mut transaction, entity_id_or_name).await?;
     insert_timeline_entity(&mut transaction, &timeline_id, &entity_id).await?;
+    transaction.commit().await?;
+    Ok(Json(()))
+}
 
+/// Handle a request to add an entity (given in the body) to a timeline
+pub async fn handle_post_timeline_entity(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(timeline_id_or_name): Path<String>,
+    Payload(payload): Payload<IdOrNameJson>,
+) -> Result<Json<()>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let timeline_id = timeline_id_from_id_or_name(&mut transaction, timeline_id_or_name).await?;
+    let entity_id = entity_id_from_id_or_name(&mut transaction, payload.id_or_name).await?;
+    insert_timeline_entity(&mut transaction, &timeline_id, &entity_id).await?;
     transaction.commit().await?;
     Ok(Json(()))
 }
@@ -90,25 +111,195 @@ pub async fn handle_put_timeline_entity(
 /// Handle a request to delete an entity from a timeline
 pub async fn handle_delete_timeline_entity(
     State(pool): State<Arc<Pool<Sqlite>>>,
-    Path(timeline_id_or_name_str): Path<String>,
-    Path(entity_id_or_name_str): Path<String>,
+    Path((timeline_id_or_name, entity_id_or_name)): Path<(String, String)>,
 ) -> Result<Json<()>, ApiError> {
     let mut transaction = pool.begin().await.unwrap();
+    let timeline_id = timeline_id_from_id_or_name(&mut transaction, timeline_id_or_name).await?;
+    let entity_id = entity_id_from_id_or_name(&mut transaction, entity_id_or_name).await?;
+    delete_timeline_entity(&mut transaction, &timeline_id, &entity_id).await?;
+    transaction.commit().await?;
+    Ok(Json(()))
+}
 
-    let timeline_id = match timeline_id_or_name(&mut transaction, timeline_id_or_name_str).await? {
-        Some(IdOrName::Id(id)) => id,
-        Some(IdOrName::Name(name)) => timeline_id_from_name(&mut transaction, &name).await?,
-        None => Err(CrudError::TimelineNotInDb)?,
-    };
+/// Handle a request to add a subtimeline (given in the body) to a timeline
+pub async fn handle_post_timeline_subtimeline(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(timeline_id_or_name): Path<String>,
+    Payload(payload): Payload<IdOrNameJson>,
+) -> Result<Json<()>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let timeline_id = timeline_id_from_id_or_name(&mut transaction, timeline_id_or_name).await?;
+    let subtimeline_id = timeline_id_from_id_or_name(&mut transaction, payload.id_or_name).await?;
+    insert_timeline_subtimeline(&mut transaction, &timeline_id, &subtimeline_id).await?;
+    transaction.commit().await?;
+    Ok(Json(()))
+}
 
-    let entity_id = match entity_id_or_name(&mut transaction, entity_id_or_name_str).await? {
-        Some(IdOrName::Id(id)) => id,
-        Some(IdOrName::Name(name)) => entity_id_from_name(&mut transaction, &name).await?,
-        None => Err(CrudError::EntityNotInDb)?,
+/// Handle a request to delete a subtimeline from a timeline
+pub async fn handle_delete_timeline_subtimeline(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path((timeline_id_or_name, subtimeline_id_or_name)): Path<(String, String)>,
+) -> Result<Json<()>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let timeline_id = timeline_id_from_id_or_name(&mut transaction, timeline_id_or_name).await?;
+    let subtimeline_id =
+        timeline_id_from_id_or_name(&mut transaction, subtimeline_id_or_name).await?;
+    delete_timeline_subtimeline(&mut transaction, &timeline_id, &subtimeline_id).await?;
+    transaction.commit().await?;
+    Ok(Json(()))
+}
+
+/// Handle a request to change the slug of a timeline (the old slug redirects
+/// to the new one)
+pub async fn handle_patch_timeline_slug(
+    State(pool): State<Arc<Pool<Sqlite>>>,
+    Path(id_or_name): Path<String>,
+    Payload(payload): Payload<SlugJson>,
+) -> Result<Json<SlugJson>, ApiError> {
+    let mut transaction = pool.begin().await.unwrap();
+    let id = timeline_id_from_id_or_name(&mut transaction, id_or_name).await?;
+    set_slug(&mut transaction, SlugKind::Timeline, &id, &payload.slug).await?;
+    transaction.commit().await?;
+    Ok(Json(payload))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::{create_entity, read_write_router, send, send_json};
+    use axum::http::{Method, StatusCode, header};
+    use open_timeline_core::{HasIdAndName, Name, OpenTimelineId};
+    use open_timeline_crud::{
+        Create, fetch_timeline_direct_member_entity_ids_by_timeline_id,
+        fetch_timeline_direct_subtimeline_ids_by_timeline_id,
     };
 
-    delete_timeline_entity(&mut transaction, &timeline_id, &entity_id).await?;
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn membership_is_changed_incrementally(pool: Pool<Sqlite>) {
+        // An entity & 2 timelines
+        let entity_id = create_entity(&pool, None).await.id().unwrap();
+        let mut transaction = pool.begin().await.unwrap();
+        let mut timeline_ids = Vec::new();
+        for name in ["Parent", "Child"] {
+            let mut timeline =
+                TimelineEdit::from(None, Name::from(name).unwrap(), None, None, None, None)
+                    .unwrap();
+            timeline.create(&mut transaction).await.unwrap();
+            timeline_ids.push(timeline.id().unwrap());
+        }
+        let [parent_id, child_id] = [timeline_ids[0], timeline_ids[1]];
+        transaction.commit().await.unwrap();
+        let router = read_write_router(pool.clone());
 
-    transaction.commit().await?;
-    Ok(Json(()))
+        // Helpers to check the membership
+        let entity_ids = || async {
+            let mut transaction = pool.begin().await.unwrap();
+            fetch_timeline_direct_member_entity_ids_by_timeline_id(&mut transaction, &parent_id)
+                .await
+                .unwrap()
+        };
+        let subtimeline_ids = |timeline_id: OpenTimelineId| {
+            let pool = pool.clone();
+            async move {
+                let mut transaction = pool.begin().await.unwrap();
+                fetch_timeline_direct_subtimeline_ids_by_timeline_id(&mut transaction, &timeline_id)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // Add & remove the entity (by name & by ID)
+        let uri = "/timelines/Parent/entities";
+        let body = r#"{"id_or_name": "Entity"}"#;
+        assert_eq!(
+            send_json(&router, Method::POST, uri, body).await.0,
+            StatusCode::OK
+        );
+        assert_eq!(entity_ids().await, Some(vec![entity_id]));
+        let uri = format!("/timelines/{parent_id}/entities/{entity_id}");
+        assert_eq!(
+            send_json(&router, Method::DELETE, &uri, "").await.0,
+            StatusCode::OK
+        );
+        assert_eq!(entity_ids().await, None);
+
+        // Add & remove the subtimeline
+        let uri = "/timelines/Parent/subtimelines";
+        let body = r#"{"id_or_name": "Child"}"#;
+        assert_eq!(
+            send_json(&router, Method::POST, uri, body).await.0,
+            StatusCode::OK
+        );
+        assert_eq!(subtimeline_ids(parent_id).await, Some(vec![child_id]));
+
+        // A timeline can't be a subtimeline of itself
+        let uri = "/timelines/Child/subtimelines";
+        let body = r#"{"id_or_name": "Parent"}"#;
+        assert_eq!(
+            send_json(&router, Method::POST, uri, body).await.0,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(subtimeline_ids(child_id).await, None);
+
+        let uri = "/timelines/Parent/subtimelines/Child";
+        assert_eq!(
+            send_json(&router, Method::DELETE, uri, "").await.0,
+            StatusCode::OK
+        );
+        assert_eq!(subtimeline_ids(parent_id).await, None);
+
+        // Missing timelines & entities
+        let uri = "/timelines/missing/entities";
+        let body = r#"{"id_or_name": "Entity"}"#;
+        assert_eq!(
+            send_json(&router, Method::POST, uri, body).await.0,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn membership_changes_make_etags_stale(pool: Pool<Sqlite>) {
+        create_entity(&pool, None).await;
+        let mut transaction = pool.begin().await.unwrap();
+        TimelineEdit::from(None, Name::from("Parent").unwrap(), None, None, None, None)
+            .unwrap()
+            .create(&mut transaction)
+            .await
+            .unwrap();
+        transaction.commit().await.unwrap();
+        let router = read_write_router(pool);
+
+        // Fetch the timeline & its ETag
+        let fetch = || async {
+            let response = send(&router, Method::GET, "/timeline/Parent/edit", &[], "").await;
+            assert_eq!(response.status, StatusCode::OK);
+            let etag = response.headers[header::ETAG].to_str().unwrap().to_string();
+            (response.body, etag)
+        };
+        let (body, etag) = fetch().await;
+
+        // Adding an entity changes the ETag, so updating with the old one fails
+        let uri = "/timelines/Parent/entities";
+        let entity = r#"{"id_or_name": "Entity"}"#;
+        assert_eq!(
+            send_json(&router, Method::POST, uri, entity).await.0,
+            StatusCode::OK
+        );
+        let headers = [
+            (header::CONTENT_TYPE, "application/json"),
+            (header::IF_MATCH, etag.as_str()),
+        ];
+        let response = send(&router, Method::PATCH, "/timeline/Parent", &headers, body).await;
+        assert_eq!(response.status, StatusCode::PRECONDITION_FAILED);
+
+        // Updating with the new one works
+        let (body, new_etag) = fetch().await;
+        assert_ne!(new_etag, etag);
+        let headers = [
+            (header::CONTENT_TYPE, "application/json"),
+            (header::IF_MATCH, new_etag.as_str()),
+        ];
+        let response = send(&router, Method::PATCH, "/timeline/Parent", &headers, body).await;
+        assert_eq!(response.status, StatusCode::OK);
+    }
 }
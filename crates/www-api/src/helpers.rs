@@ -4,39 +4,54 @@
 //! Generic helpers
 //!
 
-use crate::ApiError;
-use axum::{Json, http::StatusCode};
-use open_timeline_core::{HasIdAndName, OpenTimelineId};
+use crate::{ApiError, HasVersion, IfMatch, Tagged, merge_patched};
+use open_timeline_core::{HasIdAndName, OpenTimelineId, Slug};
 use open_timeline_crud::{
-    Create, FetchByName, IdOrName, Update, entity_id_from_name, entity_id_or_name,
+    Create, CrudError, FetchByName, IdOrName, Update, entity_id_from_name, entity_id_or_name,
+    timeline_id_from_name, timeline_id_or_name,
 };
-use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sqlx::{Sqlite, Transaction};
 
-// TODO: keep in this form? or type alias?
-#[derive(Serialize)]
-pub struct ErrorMsg {
-    pub error_msg: String,
+/// The slug of an entity or timeline (sent & received as JSON)
+#[derive(Serialize, Deserialize)]
+pub struct SlugJson {
+    pub slug: Slug,
+}
+
+/// The ID or name of an entity or timeline (sent as JSON)
+#[derive(Serialize, Deserialize)]
+pub struct IdOrNameJson {
+    pub id_or_name: String,
 }
 
 // TODO: check
-pub async fn save_new<T: Create + FetchByName + HasIdAndName>(
+pub async fn save_new<T: Create + FetchByName + HasIdAndName + HasVersion>(
     transaction: &mut Transaction<'_, Sqlite>,
     mut thing_to_create: T,
-) -> Result<Json<T>, ApiError> {
+) -> Result<Tagged<T>, ApiError> {
     thing_to_create.create(transaction).await?;
     let created = T::fetch_by_name(transaction, thing_to_create.name()).await?;
-    Ok(Json(created))
+    Ok(Tagged(created))
 }
 
 // TODO: check
-pub async fn patch<T: std::fmt::Debug + Update + FetchByName + HasIdAndName>(
+/// Update the thing (failing if it's changed since the version the `If-Match`
+/// expects)
+pub async fn patch<T: std::fmt::Debug + Update + FetchByName + HasIdAndName + HasVersion>(
     transaction: &mut Transaction<'_, Sqlite>,
     mut thing_to_patch: T,
-) -> Result<Json<T>, ApiError> {
-    thing_to_patch.update(transaction).await?;
+    if_match: IfMatch,
+) -> Result<Tagged<T>, ApiError> {
+    if_match.apply(&mut thing_to_patch);
+    match thing_to_patch.update(transaction).await {
+        Err(CrudError::StaleWrite) => Err(ApiError::precondition_failed())?,
+        result => result?,
+    }
     let updated = T::fetch_by_name(transaction, thing_to_patch.name()).await?;
-    Ok(Json(updated))
+    Ok(Tagged(updated))
 }
 
 // TODO: correct? could be simpler?
@@ -47,12 +62,44 @@ pub async fn entity_id_from_id_or_name(
     match entity_id_or_name(transaction, id_or_name).await? {
         Some(IdOrName::Id(id)) => Ok(id),
         Some(IdOrName::Name(name)) => Ok(entity_id_from_name(transaction, &name).await?),
-        None => Err(ApiError((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorMsg {
-                // todo: if ID say failed to delete entity with ID X, likewise if name
-                error_msg: "FAILED to fetch".to_string(),
-            }),
-        ))),
+        None => Err(CrudError::NotInDb.into()),
+    }
+}
+
+/// Apply a JSON merge patch to the current version of the thing & save it
+/// (failing if it's changed since the version the `If-Match` expects).  The ID
+/// can't be patched.
+pub async fn merge_patch<T>(
+    transaction: &mut Transaction<'_, Sqlite>,
+    current: T,
+    patch_json: Value,
+    if_match: IfMatch,
+) -> Result<Tagged<T>, ApiError>
+where
+    T: std::fmt::Debug
+        + Serialize
+        + DeserializeOwned
+        + Update
+        + FetchByName
+        + HasIdAndName
+        + HasVersion,
+{
+    let mut patched = merge_patched(&current, patch_json)?;
+    if let Some(id) = current.id() {
+        patched.set_id(id);
+    }
+    patched.set_version(current.version());
+    patch(transaction, patched, if_match).await
+}
+
+/// Get the ID of the timeline with the given ID or name
+pub async fn timeline_id_from_id_or_name(
+    transaction: &mut Transaction<'_, Sqlite>,
+    id_or_name: String,
+) -> Result<OpenTimelineId, ApiError> {
+    match timeline_id_or_name(transaction, id_or_name).await? {
+        Some(IdOrName::Id(id)) => Ok(id),
+        Some(IdOrName::Name(name)) => Ok(timeline_id_from_name(transaction, &name).await?),
+        None => Err(CrudError::NotInDb.into()),
     }
 }
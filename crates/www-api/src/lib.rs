@@ -10,19 +10,50 @@
 //! to OpenTimeline to be merged in.
 //!
 
+mod api_keys;
+mod config;
 mod consts;
+mod cors;
+mod embedded;
 mod error;
+mod etag;
 mod handlers;
 mod helpers;
+mod merge_patch;
+mod metrics;
+mod payload;
 mod queries;
+mod rate_limit;
+mod read_only;
+mod tenants;
+
+pub use api_keys::ApiKeys;
+pub use config::*;
+pub use cors::*;
+pub use embedded::EmbeddedApiServer;
+pub use error::{ErrorBody, ErrorEnvelope, FieldError};
+pub use metrics::{ApiMetrics, REQUEST_ID_HEADER};
+pub use rate_limit::RateLimit;
+pub use tenants::{TenantConfig, TenantSlug};
 
 use consts::*;
 use error::*;
+use etag::*;
+use merge_patch::*;
+use payload::*;
 use queries::*;
 
 use axum::Router;
+use axum::http::StatusCode;
+use axum::middleware::{from_fn, from_fn_with_state};
+use axum::routing::{any, get};
+use open_timeline_crud::generate_missing_slugs;
+use sqlx::SqlitePool;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::path::Path;
 use std::{str::FromStr, sync::Arc};
+use tenants::Tenants;
+use tower_http::compression::CompressionLayer;
 
 /// API access mode (read-only or read-write)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,16 +69,102 @@ pub enum ApiMode {
     Dynamic,
 }
 
-/// Set up and serve the API
+/// Set up and serve the API (see [`from_config()`]) with the default rate
+/// limit (none) and API keys (none)
 pub async fn prepare_api_router(
     db_url: &str,
     access_mode: ApiAccessMode,
     api_mode: ApiMode,
+    cors_allowed_origins: &CorsAllowedOrigins,
+) -> Result<Router, sqlx::Error> {
+    let database = SqliteConnectOptions::from_str(db_url)?
+        .get_filename()
+        .to_path_buf();
+    let config = ApiConfig {
+        access_mode,
+        api_mode,
+        cors_allowed_origins: cors_allowed_origins.clone(),
+        ..ApiConfig::new(database)
+    };
+    from_config(&config).await
+}
+
+/// Set up the API as configured.  Responses are compressed (gzip or brotli) if
+/// the client accepts it, and browsers may call the API from the allowed
+/// origins.  Each request is logged, and Prometheus metrics are served at
+/// `/metrics`.  New IDs are generated with the configured scheme.
+pub async fn from_config(config: &ApiConfig) -> Result<Router, sqlx::Error> {
+    open_timeline_core::set_id_scheme(config.id_scheme);
+
+    // The routes for the (default) database
+    let apiv1 = database_router(&config.database, config.access_mode, config.api_mode).await?;
+
+    // The routes for each tenant's database (which are set up when first used)
+    let apiv1 = match config.tenants.is_empty() {
+        true => apiv1,
+        false => apiv1.route(
+            "/{tenant}/{*path}",
+            any(tenants::handle_tenant_request)
+                .with_state(Tenants::new(&config.tenants, config.api_mode)),
+        ),
+    };
+
+    // Add URL path prefix
+    let api = Router::new().nest("/api/v1", apiv1);
+
+    // Write requests need an API key (if any are configured)
+    let api = match &config.api_keys {
+        Some(api_keys) => api.layer(from_fn_with_state(
+            api_keys.clone(),
+            api_keys::require_api_key_for_writes,
+        )),
+        None => api,
+    };
+
+    // Limit how often each client can make requests (if configured)
+    let api = match config.rate_limit {
+        Some(rate_limit) => api.layer(from_fn_with_state(
+            rate_limit::RateLimiter::new(rate_limit),
+            rate_limit::limit_request_rate,
+        )),
+        None => api,
+    };
+
+    // Log & measure requests (not including those for the metrics)
+    let metrics = ApiMetrics::new().expect("the API metrics are valid");
+    let api = api
+        .layer(from_fn_with_state(
+            metrics.clone(),
+            metrics::log_and_measure_request,
+        ))
+        .route(
+            "/metrics",
+            get(metrics::handle_get_metrics).with_state(metrics),
+        );
+
+    // Compress responses
+    let api = api.layer(CompressionLayer::new());
+
+    // Allow cross-origin requests
+    let api = match config.cors_allowed_origins.layer(config.access_mode) {
+        Some(cors) => api.layer(cors),
+        None => api,
+    };
+
+    // Return the router
+    Ok(api)
+}
+
+/// Set up the routes for a database (without the URL path prefix)
+async fn database_router(
+    database: &Path,
+    access_mode: ApiAccessMode,
+    api_mode: ApiMode,
 ) -> Result<Router, sqlx::Error> {
-    // TODO: test the read-only aspect?
     // Create connection options (whether the database is read-only or not)
-    let connect_options =
-        SqliteConnectOptions::from_str(db_url)?.read_only(access_mode == ApiAccessMode::Read);
+    let connect_options = SqliteConnectOptions::new()
+        .filename(database)
+        .read_only(access_mode == ApiAccessMode::Read);
 
     // Create a pool with those options
     let pool = SqlitePoolOptions::new()
@@ -55,15 +172,116 @@ pub async fn prepare_api_router(
         .connect_with(connect_options)
         .await?;
 
-    // Get the router
-    let apiv1 = handlers::router(access_mode, api_mode)?;
+    // Make sure a read-only API can't change the database
+    if access_mode == ApiAccessMode::Read {
+        read_only::check_database_is_read_only(&pool).await?;
+    }
 
-    // Add the state
-    let apiv1 = apiv1.with_state(Arc::new(pool));
+    // Give anything created before slugs existed a slug (for its permalink)
+    if access_mode == ApiAccessMode::ReadWrite {
+        let mut transaction = pool.begin().await?;
+        generate_missing_slugs(&mut transaction)
+            .await
+            .map_err(|error| sqlx::Error::Protocol(error.to_string()))?;
+        transaction.commit().await?;
+    }
 
-    // Add URL path prefix
-    let api = Router::new().nest("/api/v1", apiv1);
+    pool_router(pool, access_mode, api_mode)
+}
 
-    // Return the router
-    Ok(api)
+/// Set up the routes for a database's (already connected) pool (without the URL
+/// path prefix)
+fn pool_router(
+    pool: SqlitePool,
+    access_mode: ApiAccessMode,
+    api_mode: ApiMode,
+) -> Result<Router, sqlx::Error> {
+    // Get the router, & add the state.  The fallback means requests matching no
+    // route still pass through the middleware below (when nested).
+    let router = handlers::router(access_mode, api_mode)?
+        .with_state(Arc::new(pool))
+        .fallback(|| async { StatusCode::NOT_FOUND });
+
+    // Reject write requests (before they reach a handler) if read-only
+    Ok(match access_mode {
+        ApiAccessMode::Read => router.layer(from_fn(read_only::reject_write_requests)),
+        ApiAccessMode::ReadWrite => router,
+    })
+}
+
+#[cfg(test)]
+pub mod test {
+    use crate::{ApiAccessMode, ApiMode, pool_router};
+    use axum::Router;
+    use axum::body::{Body, to_bytes};
+    use axum::http::{HeaderMap, HeaderName, Method, Request, StatusCode, header};
+    use open_timeline_core::{Date, Entity, Name};
+    use open_timeline_crud::Create;
+    use sqlx::SqlitePool;
+    use tower::ServiceExt;
+
+    /// A response to a test request
+    pub struct TestResponse {
+        pub status: StatusCode,
+        pub headers: HeaderMap,
+        pub body: String,
+    }
+
+    /// Get a read-write router (for the dynamic API) for the test database
+    pub fn read_write_router(pool: SqlitePool) -> Router {
+        pool_router(pool, ApiAccessMode::ReadWrite, ApiMode::Dynamic).unwrap()
+    }
+
+    /// Create an entity called "Entity" starting in 1900 (& ending in the
+    /// year, if given)
+    pub async fn create_entity(pool: &SqlitePool, end_year: Option<i64>) -> Entity {
+        let mut transaction = pool.begin().await.unwrap();
+        let mut entity = Entity::from(
+            None,
+            Name::from("Entity").unwrap(),
+            Date::from(None, None, 1900).unwrap(),
+            end_year.map(|year| Date::from(None, None, year).unwrap()),
+            None,
+        )
+        .unwrap();
+        entity.create(&mut transaction).await.unwrap();
+        transaction.commit().await.unwrap();
+        entity
+    }
+
+    /// Send a request with the headers & body
+    pub async fn send(
+        router: &Router,
+        method: Method,
+        uri: &str,
+        headers: &[(HeaderName, &str)],
+        body: impl Into<Body>,
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        let request = request.body(body.into()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        TestResponse {
+            status,
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }
+    }
+
+    /// Send a request with a JSON body
+    pub async fn send_json(
+        router: &Router,
+        method: Method,
+        uri: &str,
+        body: &str,
+    ) -> (StatusCode, String) {
+        let headers = [(header::CONTENT_TYPE, "application/json")];
+        let response = send(router, method, uri, &headers, body.to_owned()).await;
+        (response.status, response.body)
+    }
 }
@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! JSON Merge Patch (RFC 7396) request bodies, for changing a few fields
+//! without sending the whole entity or timeline.  The patch's fields replace
+//! the current ones, `null`s remove them, & objects are merged, e.g.
+//!
+//! ```json
+//! { "name": "Apollo 11 mission", "end": null, "start": { "day": 16 } }
+//! ```
+//!
+
+use crate::{ApiError, from_slice};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// Apply the merge patch to the current version of something, checking the
+/// result is still valid (with the same errors as a [`Payload`])
+///
+/// [`Payload`]: crate::Payload
+pub(crate) fn merge_patched<T: Serialize + DeserializeOwned>(
+    current: &T,
+    patch: Value,
+) -> Result<T, ApiError> {
+    if !patch.is_object() {
+        return Err(ApiError::bad_request(
+            "The merge patch must be a JSON object",
+        ));
+    }
+    let mut value =
+        serde_json::to_value(current).map_err(|error| ApiError::internal(error.to_string()))?;
+    apply(&mut value, patch);
+    let bytes =
+        serde_json::to_vec(&value).map_err(|error| ApiError::internal(error.to_string()))?;
+    from_slice(&bytes).map_err(|error| ApiError::invalid_payload(vec![error]))
+}
+
+/// Apply the merge patch to the value
+fn apply(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!("the target was just made an object")
+    };
+    for (key, value) in patch {
+        match value {
+            Value::Null => {
+                target.remove(&key);
+            }
+            value => apply(target.entry(key).or_insert(Value::Null), value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ErrorEnvelope;
+    use crate::test::{create_entity, read_write_router, send};
+    use axum::http::{Method, StatusCode, header};
+    use open_timeline_core::{Date, Entity, HasIdAndName};
+    use open_timeline_crud::FetchById;
+    use serde_json::json;
+    use sqlx::{Pool, Sqlite};
+
+    // Some of the examples from the RFC
+    #[test]
+    fn patches_are_applied() {
+        let examples = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (
+                json!({"a": "b"}),
+                json!({"b": "c"}),
+                json!({"a": "b", "b": "c"}),
+            ),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (
+                json!({"a": "foo"}),
+                json!({"a": {"b": "c"}}),
+                json!({"a": {"b": "c"}}),
+            ),
+            (
+                json!({"a": {"b": "c", "d": "e"}}),
+                json!({"a": {"b": null, "f": "g"}}),
+                json!({"a": {"d": "e", "f": "g"}}),
+            ),
+            (
+                json!({}),
+                json!({"a": {"bb": {"ccc": null}}}),
+                json!({"a": {"bb": {}}}),
+            ),
+        ];
+        for (mut target, patch, expected) in examples {
+            apply(&mut target, patch);
+            assert_eq!(target, expected);
+        }
+    }
+
+    /// Merge patch the entity
+    async fn patch_entity(
+        router: &axum::Router,
+        if_match: Option<&str>,
+        patch: Value,
+    ) -> (StatusCode, String) {
+        let mut headers = vec![(header::CONTENT_TYPE, "application/merge-patch+json")];
+        if let Some(if_match) = if_match {
+            headers.push((header::IF_MATCH, if_match));
+        }
+        let uri = "/entities/Entity";
+        let response = send(router, Method::PATCH, uri, &headers, patch.to_string()).await;
+        (response.status, response.body)
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn entities_are_merge_patched(pool: Pool<Sqlite>) {
+        let id = create_entity(&pool, Some(1950)).await.id().unwrap();
+        let router = read_write_router(pool.clone());
+
+        // An invalid result isn't saved
+        let patch = json!({"start": {"month": 13}});
+        let (status, body) = patch_entity(&router, None, patch).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let envelope: ErrorEnvelope = serde_json::from_str(&body).unwrap();
+        assert_eq!(envelope.error.fields[0].path, "start");
+
+        // A stale ETag isn't saved either
+        let (status, _) = patch_entity(&router, Some("\"1000\""), json!({"end": null})).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+
+        // Only the patched fields change (& the ID can't)
+        let patch = json!({"id": null, "name": "Renamed", "end": null, "start": {"month": 6}});
+        let (status, _) = patch_entity(&router, None, patch).await;
+        assert_eq!(status, StatusCode::OK);
+        let mut transaction = pool.begin().await.unwrap();
+        let patched = Entity::fetch_by_id(&mut transaction, &id).await.unwrap();
+        assert_eq!(patched.name().as_str(), "Renamed");
+        assert_eq!(patched.start(), Date::from(None, Some(6), 1900).unwrap());
+        assert_eq!(patched.end(), None);
+    }
+}
@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Structured request logging & Prometheus metrics
+//!
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{HeaderValue, Method, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TEXT_FORMAT, TextEncoder,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::Instrument;
+
+/// The header clients may send an API key in
+pub(crate) const API_KEY_HEADER: &str = "x-api-key";
+
+/// The response header with the ID of the request (which is logged with
+/// everything done for it)
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The ID of the next request
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The label used for requests that didn't match a route (so that unknown
+/// paths don't each get their own time series)
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// The routes whose latencies are recorded as search latencies
+const SEARCH_ROUTES: [&str; 3] = [
+    "/api/v1/entities/reduced",
+    "/api/v1/timelines/reduced",
+    "/api/v1/entities/near",
+];
+
+/// The API's Prometheus metrics.  Cheap to clone (clones share the metrics).
+#[derive(Debug, Clone)]
+pub struct ApiMetrics(Arc<ApiMetricsInner>);
+
+/// See [`ApiMetrics`]
+#[derive(Debug)]
+struct ApiMetricsInner {
+    /// Holds all of the metrics below
+    registry: Registry,
+
+    /// The number of requests (by method, route, and status code)
+    http_requests: IntCounterVec,
+
+    /// How long requests took to handle (by method & route)
+    http_request_duration: HistogramVec,
+
+    /// The number of CRUD operations (by operation, type, and outcome)
+    crud_operations: IntCounterVec,
+
+    /// How long searches took (by route)
+    search_duration: HistogramVec,
+}
+
+impl ApiMetrics {
+    /// Create & register the metrics
+    pub fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new_custom(Some(String::from("open_timeline_api")), None)?;
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Number of HTTP requests handled"),
+            &["method", "route", "status"],
+        )?;
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to handle HTTP requests",
+            ),
+            &["method", "route"],
+        )?;
+        let crud_operations = IntCounterVec::new(
+            Opts::new(
+                "crud_operations_total",
+                "Number of CRUD operations requested",
+            ),
+            &["operation", "type", "outcome"],
+        )?;
+        let search_duration = HistogramVec::new(
+            HistogramOpts::new("search_duration_seconds", "Time taken to search"),
+            &["route"],
+        )?;
+        registry.register(Box::new(http_requests.clone()))?;
+        registry.register(Box::new(http_request_duration.clone()))?;
+        registry.register(Box::new(crud_operations.clone()))?;
+        registry.register(Box::new(search_duration.clone()))?;
+        Ok(Self(Arc::new(ApiMetricsInner {
+            registry,
+            http_requests,
+            http_request_duration,
+            crud_operations,
+            search_duration,
+        })))
+    }
+
+    /// Record a handled request
+    fn observe(&self, method: &Method, route: &str, status: StatusCode, seconds: f64) {
+        let metrics = &self.0;
+        metrics
+            .http_requests
+            .with_label_values(&[method.as_str(), route, status.as_str()])
+            .inc();
+        metrics
+            .http_request_duration
+            .with_label_values(&[method.as_str(), route])
+            .observe(seconds);
+        if let Some((operation, kind)) = crud_operation(method, route) {
+            let outcome = if status.is_success() {
+                "success"
+            } else {
+                "failure"
+            };
+            metrics
+                .crud_operations
+                .with_label_values(&[operation, kind, outcome])
+                .inc();
+        }
+        if method == Method::GET && SEARCH_ROUTES.contains(&route) {
+            metrics
+                .search_duration
+                .with_label_values(&[route])
+                .observe(seconds);
+        }
+    }
+
+    /// The metrics in the Prometheus text format
+    fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.0.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+/// The CRUD operation (& the type it's on) a request is for, if it's for one
+fn crud_operation(method: &Method, route: &str) -> Option<(&'static str, &'static str)> {
+    let operation = match *method {
+        Method::GET => "read",
+        Method::PUT => "create",
+        Method::PATCH => "update",
+        Method::DELETE => "delete",
+        _ => return None,
+    };
+    let route = route.strip_prefix("/api/v1/")?;
+    let kind = if route.starts_with("entit") {
+        "entity"
+    } else if route.starts_with("timeline") {
+        "timeline"
+    } else if route.starts_with("tags") {
+        "tag"
+    } else {
+        return None;
+    };
+    Some((operation, kind))
+}
+
+/// Only the last 4 characters of an API key are logged
+fn redact_api_key(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    let visible: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+    format!("…{visible}")
+}
+
+/// Middleware that logs each request (method, path, status, latency, & API
+/// key) and records it in the metrics.  Each request is given an ID, which is
+/// logged with everything done for it (including the SQL run) & sent back in
+/// the [`REQUEST_ID_HEADER`].
+pub async fn log_and_measure_request(
+    State(metrics): State<ApiMetrics>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_owned();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched_path| matched_path.as_str().to_owned())
+        .unwrap_or_else(|| String::from(UNMATCHED_ROUTE));
+    let api_key = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|api_key| api_key.to_str().ok())
+        .map(redact_api_key);
+
+    let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+    let span = tracing::info_span!("request", id, method = %method, path = %path);
+    let mut response = next.run(request).instrument(span.clone()).await;
+    response
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, HeaderValue::from(id));
+
+    let latency = start.elapsed();
+    let status = response.status();
+    tracing::info!(
+        parent: &span,
+        method = %method,
+        path = %path,
+        route = %route,
+        status = status.as_u16(),
+        latency_ms = latency.as_secs_f64() * 1000.0,
+        api_key = api_key.as_deref().unwrap_or("none"),
+        "request handled"
+    );
+    metrics.observe(&method, &route, status, latency.as_secs_f64());
+    response
+}
+
+/// Handle a request for the metrics (in the Prometheus text format)
+pub async fn handle_get_metrics(State(metrics): State<ApiMetrics>) -> Response {
+    match metrics.encode() {
+        Ok(body) => ([(header::CONTENT_TYPE, TEXT_FORMAT)], body).into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()).into_response(),
+    }
+}
@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! The JSON bodies of requests.  Bodies that can't be used (e.g. that aren't
+//! valid JSON, or that have a field that's missing or invalid) are rejected
+//! with a `422` & the error envelope, which says which field is wrong & why
+//! (see [`FieldError`]).
+//!
+
+use crate::{ApiError, FieldError};
+use axum::body::Bytes;
+use axum::extract::{FromRequest, Request};
+use axum::http::{HeaderMap, StatusCode, header};
+use serde::de::DeserializeOwned;
+
+/// A request's JSON body (like [`axum::Json`], but rejected with an
+/// [`ApiError`] saying which field is wrong)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Payload<T>(pub T);
+
+impl<T, S> FromRequest<S> for Payload<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(request.headers()) {
+            return Err(ApiError::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported_media_type",
+                "Expected request with `Content-Type: application/json`",
+            ));
+        }
+        let bytes = Bytes::from_request(request, state)
+            .await
+            .map_err(|rejection| ApiError::bad_request(rejection.body_text()))?;
+        from_slice(&bytes)
+            .map(Self)
+            .map_err(|error| ApiError::invalid_payload(vec![error]))
+    }
+}
+
+/// Whether the request says its body is JSON (e.g. `application/json` or
+/// `application/ld+json`)
+fn has_json_content_type(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .is_some_and(|mime| {
+            mime == "application/json"
+                || (mime.starts_with("application/") && mime.ends_with("+json"))
+        })
+}
+
+/// Deserialise JSON, keeping track of where in it anything goes wrong
+pub(crate) fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, FieldError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|error| {
+        let path = match error.path().iter().next() {
+            Some(_) => error.path().to_string(),
+            None => String::new(),
+        };
+        field_error(path, error.inner())
+    })?;
+    deserializer
+        .end()
+        .map_err(|error| field_error(String::new(), &error))?;
+    Ok(value)
+}
+
+/// Describe what's wrong with the field at the path (empty for the whole body)
+fn field_error(path: String, error: &serde_json::Error) -> FieldError {
+    // The position is only useful for syntax errors (& then only to people)
+    let message = error.to_string();
+    let position = format!(" at line {} column {}", error.line(), error.column());
+    let message = message.strip_suffix(&position).unwrap_or(&message);
+
+    let (path, code) = if error.is_syntax() || error.is_eof() {
+        (String::new(), "invalid_json")
+    } else if let Some(field) = missing_field(message) {
+        let path = match path.is_empty() {
+            true => field.to_owned(),
+            false => format!("{path}.{field}"),
+        };
+        (path, "missing_field")
+    } else if is_wrong_type(message) {
+        (path, "invalid_type")
+    } else {
+        // The value was the right type, but a core type rejected it
+        let code = validation_code(&path_keys(&path));
+        (path, code)
+    };
+    FieldError {
+        path,
+        code: code.to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+/// The name of the missing field, if that's the error
+fn missing_field(message: &str) -> Option<&str> {
+    message
+        .strip_prefix("missing field `")
+        .and_then(|field| field.strip_suffix('`'))
+}
+
+/// Whether the error is that a value is the wrong type (or shape)
+fn is_wrong_type(message: &str) -> bool {
+    [
+        "invalid type",
+        "invalid length",
+        "unknown variant",
+        "unknown field",
+    ]
+    .iter()
+    .any(|prefix| message.starts_with(prefix))
+}
+
+/// The names of the fields along a path (e.g. `entities[0].name` -> `entities`
+/// & `name`)
+fn path_keys(path: &str) -> Vec<&str> {
+    path.split(['.', '['])
+        .filter(|key| !key.is_empty() && !key.ends_with(']'))
+        .collect()
+}
+
+/// The code of the error of the core type that validates a field (the
+/// innermost field along the path that's validated by one)
+fn validation_code(keys: &[&str]) -> &'static str {
+    keys.iter()
+        .rev()
+        .find_map(|key| match *key {
+            "name" | "aliases" => Some("invalid_name"),
+            "start" | "end" | "day" | "month" | "year" | "calendar" => Some("invalid_date"),
+            "id" => Some("invalid_id"),
+            "slug" => Some("invalid_slug"),
+            "tags" => Some("invalid_tag"),
+            "typed_tags" => Some("invalid_typed_tag"),
+            "bool_expr" => Some("invalid_tag_query"),
+            "sources" => Some("invalid_source"),
+            "location" => Some("invalid_location"),
+            _ => None,
+        })
+        .unwrap_or("invalid_value")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ErrorEnvelope;
+    use crate::test::{read_write_router, send, send_json};
+    use axum::http::Method;
+    use sqlx::SqlitePool;
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn invalid_payloads_are_unprocessable(pool: SqlitePool) {
+        let router = read_write_router(pool);
+
+        // Each invalid payload, & the field that's wrong (& why)
+        #[rustfmt::skip]
+        let invalid = [
+            (Method::PUT,   "/entity",   r#"{"start":{"year":1900}}"#,                                "name",              "missing_field"),
+            (Method::PUT,   "/entity",   r#"{"name":" ","start":{"year":1900}}"#,                     "name",              "invalid_name"),
+            (Method::PUT,   "/entity",   r#"{"name":"A","start":{"year":"1900"}}"#,                   "start.year",        "invalid_type"),
+            (Method::PUT,   "/entity",   r#"{"name":"A","start":{"month":13,"year":1900}}"#,          "start",             "invalid_date"),
+            (Method::PUT,   "/entity",   r#"{"name":"A","start":{"year":1900},"end":{"month":2}}"#,   "end",               "invalid_date"),
+            (Method::PUT,   "/entity",   r#"{"name":"A","start":{"year":1900},"tags":[{"value":1}]}"#, "tags[0].value",    "invalid_type"),
+            (Method::PUT,   "/entity",   r#"{"name":"A","start":{"year":1900},"end":{"year":1800}}"#, "",                  "invalid_value"),
+            (Method::PUT,   "/entity",   r#"{"name":"A","start":{"year":1900"#,                       "",                  "invalid_json"),
+            (Method::PUT,   "/entity",   r#"{"name":"A","start":{"year":1900}} {}"#,                  "",                  "invalid_json"),
+            (Method::PATCH, "/entity/a", r#"{"name":"A","start":{"day":1,"year":1900}}"#,             "start",             "invalid_date"),
+            (Method::PUT,   "/timeline", r#"{"name":"A","entities":[{"id":"a","name":"B"}]}"#,        "entities[0].id",    "invalid_id"),
+            (Method::PUT,   "/timeline", r#"{"name":""}"#,                                            "name",              "invalid_name"),
+            (Method::PUT,   "/timeline", r#""A""#,                                                    "",                  "invalid_type"),
+            (Method::PUT,   "/merge",    r#"{"entities":[{"name":"A"}],"timelines":[]}"#,             "entities[0].start", "missing_field"),
+        ];
+        for (method, uri, body, path, code) in invalid {
+            let (status, response) = send_json(&router, method.clone(), uri, body).await;
+            assert_eq!(
+                status,
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "{method} {uri} {body}: {response}"
+            );
+            let envelope: ErrorEnvelope = serde_json::from_str(&response).unwrap();
+            assert_eq!(envelope.error.code, "invalid_payload");
+            let field = &envelope.error.fields[0];
+            assert_eq!(
+                (field.path.as_str(), field.code.as_str()),
+                (path, code),
+                "{body}"
+            );
+            assert!(!field.message.contains("line 1"), "{body}: {response}");
+        }
+
+        // The body must be said to be JSON
+        let body = r#"{"name":"A","start":{"year":1900}}"#;
+        let response = send(&router, Method::PUT, "/entity", &[], body).await;
+        assert_eq!(response.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        // Valid payloads are still accepted
+        let body = r#"{"name":"A","start":{"year":1900},"end":{"year":null},"tags":null}"#;
+        let (status, response) = send_json(&router, Method::PUT, "/entity", body).await;
+        assert_eq!(status, StatusCode::OK, "{response}");
+    }
+
+    #[test]
+    fn validation_codes() {
+        assert_eq!(
+            path_keys("entities[0].start.month"),
+            ["entities", "start", "month"]
+        );
+        assert_eq!(
+            validation_code(&path_keys("entities[0].name")),
+            "invalid_name"
+        );
+        assert_eq!(validation_code(&path_keys("tags[2].value")), "invalid_tag");
+        assert_eq!(validation_code(&path_keys("")), "invalid_value");
+    }
+}
@@ -4,7 +4,11 @@
 //! All query parameter structs
 //!
 
-use crate::DEFAULT_LIMIT_PARTIAL_NAME_QUERY;
+use crate::{
+    DEFAULT_LIMIT_NEAR_ENTITIES, DEFAULT_LIMIT_PAGE, DEFAULT_LIMIT_PARTIAL_NAME_QUERY,
+    DEFAULT_NEAR_RADIUS_KM, DEFAULT_RENDER_HEIGHT, DEFAULT_RENDER_WIDTH,
+};
+use open_timeline_core::{DateOrder, EraStyle, MonthStyle, OpenTimelineId, TagQuery};
 use open_timeline_crud::Limit;
 use serde::Deserialize;
 
@@ -26,3 +30,133 @@ impl Default for PartialNameQueryParams {
         }
     }
 }
+
+/// Query parameters used when fetching the entities near some coordinates
+#[derive(Deserialize)]
+pub struct NearQueryParams {
+    pub latitude: f64,
+    pub longitude: f64,
+    #[serde(rename = "radius-km", default = "default_near_radius_km")]
+    pub radius_km: f64,
+    #[serde(default = "default_near_limit")]
+    pub limit: Limit,
+}
+
+fn default_near_radius_km() -> f64 {
+    DEFAULT_NEAR_RADIUS_KM
+}
+
+fn default_near_limit() -> Limit {
+    Limit(DEFAULT_LIMIT_NEAR_ENTITIES)
+}
+
+/// Query parameters used when fetching a page of entities or timelines
+#[derive(Deserialize)]
+pub struct PageQueryParams {
+    /// The ID of the last item of the previous page (if not the first page)
+    pub after: Option<OpenTimelineId>,
+    #[serde(default = "default_page_limit")]
+    pub limit: Limit,
+}
+
+fn default_page_limit() -> Limit {
+    Limit(DEFAULT_LIMIT_PAGE)
+}
+
+/// Query parameters used when counting the entities that start in each decade
+#[derive(Deserialize)]
+pub struct DecadeCountsQueryParams {
+    /// Only count the entities matching this tag query (if given)
+    #[serde(rename = "bool-expr")]
+    pub bool_expr: Option<TagQuery>,
+}
+
+/// Query parameters used when fetching what has been modified since a (unix)
+/// time
+#[derive(Deserialize)]
+pub struct ModifiedSinceQueryParams {
+    pub modified_since: i64,
+}
+
+/// Query parameters used when fetching the entities that started or ended on a
+/// day of the year
+#[derive(Deserialize)]
+pub struct OnThisDayQueryParams {
+    pub month: u8,
+    pub day: u8,
+}
+
+/// The image formats a timeline can be rendered to
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderFormat {
+    #[default]
+    Svg,
+    Png,
+}
+
+/// Query parameters used when rendering a timeline to an image
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct RenderQueryParams {
+    pub format: RenderFormat,
+    pub width: u32,
+    pub height: u32,
+    #[serde(rename = "date-order")]
+    pub date_order: DateOrder,
+    #[serde(rename = "month-style")]
+    pub month_style: MonthStyle,
+    #[serde(rename = "era-style")]
+    pub era_style: EraStyle,
+}
+
+impl Default for RenderQueryParams {
+    fn default() -> Self {
+        RenderQueryParams {
+            format: RenderFormat::default(),
+            width: DEFAULT_RENDER_WIDTH,
+            height: DEFAULT_RENDER_HEIGHT,
+            date_order: DateOrder::default(),
+            month_style: MonthStyle::default(),
+            era_style: EraStyle::default(),
+        }
+    }
+}
+
+/// The formats a timeline can be fetched for viewing in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// OpenTimeline's own JSON
+    #[default]
+    OpenTimeline,
+
+    /// Knight Lab's TimelineJS JSON
+    TimelineJs,
+}
+
+/// Query parameters used when fetching a timeline for viewing
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct ExportQueryParams {
+    pub format: ExportFormat,
+}
+
+/// Which fields of each entity to include in a response
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityFields {
+    /// The full entity
+    #[default]
+    Full,
+
+    /// Only what is needed to render the entity (ID, name, dates & tags)
+    Render,
+}
+
+/// Query parameters used when fetching the entities of a timeline
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct EntityFieldsQueryParams {
+    pub fields: EntityFields,
+}
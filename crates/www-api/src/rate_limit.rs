@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Limit how many requests each client can make per minute.  Clients are told
+//! apart by their API key, or else by the `x-forwarded-for` header set by a
+//! reverse proxy (clients without either share a limit).
+//!
+
+use crate::ApiError;
+use crate::metrics::API_KEY_HEADER;
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long each client's count of requests lasts
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// The header a reverse proxy puts the client's address in
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// The maximum number of requests a client can make per minute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests_per_minute: NonZeroU32,
+}
+
+/// Counts each client's requests in the current window
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter {
+    rate_limit: RateLimit,
+    windows: Arc<Mutex<HashMap<String, (Instant, u32)>>>,
+}
+
+impl RateLimiter {
+    /// Create a new rate limiter
+    pub(crate) fn new(rate_limit: RateLimit) -> Self {
+        Self {
+            rate_limit,
+            windows: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Count a request from the client.  Returns how long until the client can
+    /// make another request if it has made too many.
+    fn check(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let mut windows = self.windows.lock().unwrap();
+        windows.retain(|_, (start, _)| now.duration_since(*start) < WINDOW);
+        let (start, count) = windows.entry(client.to_string()).or_insert((now, 0));
+        if *count >= self.rate_limit.requests_per_minute.get() {
+            return Err(WINDOW.saturating_sub(now.duration_since(*start)));
+        }
+        *count += 1;
+        Ok(())
+    }
+}
+
+/// Identify the client making a request
+fn client(headers: &HeaderMap) -> String {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    match (header(API_KEY_HEADER), header(FORWARDED_FOR_HEADER)) {
+        (Some(api_key), _) => format!("key:{api_key}"),
+        (None, Some(forwarded_for)) => {
+            let address = forwarded_for.split(',').next().unwrap_or_default();
+            format!("addr:{}", address.trim())
+        }
+        (None, None) => String::from("anonymous"),
+    }
+}
+
+/// Middleware that rejects requests from clients that have made too many with
+/// a `429 Too Many Requests`
+pub(crate) async fn limit_request_rate(
+    State(rate_limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match rate_limiter.check(&client(request.headers()), Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            let error_msg = String::from("Too many requests, please try again later");
+            let retry_after = retry_after.as_secs().max(1).to_string();
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after)],
+                ApiError::new(StatusCode::TOO_MANY_REQUESTS, "rate_limited", error_msg),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn limits_each_client() {
+        let requests_per_minute = NonZeroU32::new(2).unwrap();
+        let rate_limiter = RateLimiter::new(RateLimit {
+            requests_per_minute,
+        });
+        let now = Instant::now();
+        assert!(rate_limiter.check("a", now).is_ok());
+        assert!(rate_limiter.check("a", now).is_ok());
+        assert!(rate_limiter.check("a", now).is_err());
+        assert!(rate_limiter.check("b", now).is_ok());
+
+        // The count is reset once the window has passed
+        assert!(rate_limiter.check("a", now + WINDOW).is_ok());
+    }
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Enforce [`ApiAccessMode::Read`]: write requests are rejected before they are
+//! routed (so before the database is touched), and the database is checked at
+//! startup to make sure it really can't be written to.
+//!
+
+use crate::ApiAccessMode;
+use crate::ApiError;
+use axum::extract::Request;
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sqlx::{Pool, Sqlite};
+
+/// SQLite's (primary) result code for an attempt to write to a read-only
+/// database
+const SQLITE_READONLY: i32 = 8;
+
+/// Whether requests using the method only read (and so are allowed in
+/// [`ApiAccessMode::Read`])
+pub(crate) fn is_read_method(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Middleware that rejects write requests (anything other than `GET`, `HEAD`,
+/// & `OPTIONS`) with a `403 Forbidden`
+pub(crate) async fn reject_write_requests(request: Request, next: Next) -> Response {
+    if is_read_method(request.method()) {
+        return next.run(request).await;
+    }
+    let error_msg = format!(
+        "The API is read-only, so {} requests aren't allowed",
+        request.method()
+    );
+    ApiError::new(StatusCode::FORBIDDEN, "read_only", error_msg).into_response()
+}
+
+/// Check that writing to the database fails (as it should when the API is
+/// read-only).  The write changes nothing, & is rolled back regardless.
+pub(crate) async fn check_database_is_read_only(pool: &Pool<Sqlite>) -> Result<(), sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+    let result = sqlx::query("DELETE FROM entities WHERE FALSE")
+        .execute(&mut *transaction)
+        .await;
+    transaction.rollback().await?;
+    match result {
+        Err(sqlx::Error::Database(error)) if is_read_only_error(error.code().as_deref()) => Ok(()),
+        Err(error) => Err(error),
+        Ok(_) => Err(sqlx::Error::Configuration(
+            format!(
+                "the API is in {:?} mode but the database can be written to",
+                ApiAccessMode::Read
+            )
+            .into(),
+        )),
+    }
+}
+
+/// Whether the (extended) SQLite result code is one of `SQLITE_READONLY`'s
+fn is_read_only_error(code: Option<&str>) -> bool {
+    code.and_then(|code| code.parse::<i32>().ok())
+        .is_some_and(|code| code & 0xff == SQLITE_READONLY)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::send_json;
+    use crate::{ApiAccessMode, ApiMode, CorsAllowedOrigins, prepare_api_router};
+    use axum::Router;
+    use axum::http::{Method, StatusCode};
+    use sqlx::SqlitePool;
+
+    /// Every route that changes the database (with placeholder IDs/names)
+    const MUTATING_ROUTES: [(Method, &str); 17] = [
+        (Method::PUT, "/api/v1/entity"),
+        (Method::PATCH, "/api/v1/entity/missing"),
+        (Method::DELETE, "/api/v1/entity/missing"),
+        (Method::PATCH, "/api/v1/entity/missing/slug"),
+        (Method::PUT, "/api/v1/merge"),
+        (Method::PUT, "/api/v1/timeline"),
+        (Method::PATCH, "/api/v1/timeline/missing"),
+        (Method::DELETE, "/api/v1/timeline/missing"),
+        (Method::PATCH, "/api/v1/timeline/missing/slug"),
+        (Method::PUT, "/api/v1/timeline/missing/entity/missing"),
+        (Method::DELETE, "/api/v1/timeline/missing/entity/missing"),
+        (Method::PATCH, "/api/v1/entities/missing"),
+        (Method::PATCH, "/api/v1/timelines/missing"),
+        (Method::POST, "/api/v1/timelines/missing/entities"),
+        (Method::DELETE, "/api/v1/timelines/missing/entities/missing"),
+        (Method::POST, "/api/v1/timelines/missing/subtimelines"),
+        (
+            Method::DELETE,
+            "/api/v1/timelines/missing/subtimelines/missing",
+        ),
+    ];
+
+    /// Get the router for the (test) database
+    async fn router(pool: &SqlitePool, access_mode: ApiAccessMode) -> Router {
+        let db_url = format!(
+            "sqlite://{}",
+            pool.connect_options().get_filename().to_string_lossy()
+        );
+        let cors_allowed_origins = CorsAllowedOrigins::None;
+        prepare_api_router(
+            &db_url,
+            access_mode,
+            ApiMode::Dynamic,
+            &cors_allowed_origins,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn read_mode_rejects_writes(pool: SqlitePool) {
+        let router = router(&pool, ApiAccessMode::Read).await;
+        for (method, uri) in MUTATING_ROUTES {
+            let (status, body) = send_json(&router, method.clone(), uri, "{}").await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{method} {uri}");
+            assert!(body.contains("read-only"), "{method} {uri}: {body}");
+        }
+
+        // Reads are still allowed
+        let (status, _) = send_json(&router, Method::GET, "/api/v1/tags", "{}").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn read_write_mode_routes_writes(pool: SqlitePool) {
+        let router = router(&pool, ApiAccessMode::ReadWrite).await;
+        for (method, uri) in MUTATING_ROUTES {
+            let (status, body) = send_json(&router, method.clone(), uri, "{}").await;
+            assert_ne!(status, StatusCode::FORBIDDEN, "{method} {uri}: {body}");
+            assert_ne!(status, StatusCode::METHOD_NOT_ALLOWED, "{method} {uri}");
+            // Only the handlers' own "not found" errors (not missing routes)
+            assert!(
+                status != StatusCode::NOT_FOUND || body.contains(r#""code":"not_found""#),
+                "{method} {uri}: {body}"
+            );
+        }
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn startup_checks_database_is_read_only(pool: SqlitePool) {
+        // The database is opened read-only, so the check passes
+        let _ = router(&pool, ApiAccessMode::Read).await;
+
+        // A writable database fails the check
+        assert!(super::check_database_is_read_only(&pool).await.is_err());
+    }
+}
@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//!
+//! Multi-tenancy: each tenant has its own database (& access mode), served
+//! under `/api/v1/{tenant}/...`.  A tenant's database is connected to when it
+//! is first used.
+//!
+
+use crate::ApiError;
+use crate::{ApiAccessMode, ApiConfigError, ApiMode, database_router};
+use axum::Router;
+use axum::extract::{Path, Request, State};
+use axum::http::{StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tower::ServiceExt;
+
+/// The first parts of the (default) database's routes, which tenants can't use
+/// as their slugs
+const RESERVED_SLUGS: [&str; 13] = [
+    "e",
+    "entities",
+    "entity",
+    "healthz",
+    "merge",
+    "on-this-day",
+    "readyz",
+    "t",
+    "tags",
+    "timeline",
+    "timelines",
+    "metrics",
+    "api",
+];
+
+/// The slug of a tenant (the part of the URL path after `/api/v1/`), made of
+/// lowercase letters, digits, & hyphens
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TenantSlug(String);
+
+impl FromStr for TenantSlug {
+    type Err = ApiConfigError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let valid_chars = text
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        let valid = !text.is_empty()
+            && valid_chars
+            && !text.starts_with('-')
+            && !text.ends_with('-')
+            && !RESERVED_SLUGS.contains(&text);
+        match valid {
+            true => Ok(Self(text.to_string())),
+            false => Err(ApiConfigError::TenantSlug(text.to_string())),
+        }
+    }
+}
+
+impl Display for TenantSlug {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A tenant's configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TenantConfig {
+    /// The path to the tenant's database
+    pub database: PathBuf,
+
+    /// Whether the tenant's database can be changed
+    pub access_mode: ApiAccessMode,
+}
+
+/// A tenant, & its routes (once they've been set up)
+#[derive(Debug)]
+struct Tenant {
+    config: TenantConfig,
+    router: OnceCell<Router>,
+}
+
+/// All of the tenants.  Cheap to clone (clones share the tenants).
+#[derive(Debug, Clone)]
+pub(crate) struct Tenants {
+    tenants: Arc<BTreeMap<String, Tenant>>,
+    api_mode: ApiMode,
+}
+
+impl Tenants {
+    /// Create the tenants (without connecting to their databases)
+    pub(crate) fn new(tenants: &BTreeMap<TenantSlug, TenantConfig>, api_mode: ApiMode) -> Self {
+        let tenants = tenants
+            .iter()
+            .map(|(slug, config)| {
+                let tenant = Tenant {
+                    config: config.clone(),
+                    router: OnceCell::new(),
+                };
+                (slug.0.clone(), tenant)
+            })
+            .collect();
+        Self {
+            tenants: Arc::new(tenants),
+            api_mode,
+        }
+    }
+}
+
+/// Handle a request for a tenant by passing it (without the tenant's slug) to
+/// the tenant's routes, which are set up if this is the first request
+pub(crate) async fn handle_tenant_request(
+    State(tenants): State<Tenants>,
+    Path((slug, path)): Path<(String, String)>,
+    mut request: Request,
+) -> Response {
+    let Some(tenant) = tenants.tenants.get(&slug) else {
+        let error_msg = format!("No such tenant: {slug}");
+        return ApiError::new(StatusCode::NOT_FOUND, "not_found", error_msg).into_response();
+    };
+    let config = &tenant.config;
+    let router = tenant
+        .router
+        .get_or_try_init(|| database_router(&config.database, config.access_mode, tenants.api_mode))
+        .await;
+    let router = match router {
+        Ok(router) => router.clone(),
+        Err(db_error) => {
+            tracing::error!("Error setting up tenant '{slug}': {db_error}");
+            let error_msg = format!("Tenant '{slug}' is unavailable");
+            return ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "unavailable", error_msg)
+                .into_response();
+        }
+    };
+
+    // The tenant's routes don't include its slug
+    let uri = match request.uri().query() {
+        Some(query) => format!("/{path}?{query}"),
+        None => format!("/{path}"),
+    };
+    *request.uri_mut() = match uri.parse::<Uri>() {
+        Ok(uri) => uri,
+        Err(_) => return ApiError::bad_request(format!("Invalid path: {uri}")).into_response(),
+    };
+    router.oneshot(request).await.into_response()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ApiConfig, from_config};
+    use axum::body::{Body, to_bytes};
+    use axum::http::Method;
+    use sqlx::SqlitePool;
+
+    #[test]
+    fn slugs() {
+        for slug in ["history-101", "a", "year-7b"] {
+            assert!(TenantSlug::from_str(slug).is_ok(), "{slug}");
+        }
+        for slug in ["", "History", "-a", "a-", "a/b", "entity", "tags"] {
+            assert!(TenantSlug::from_str(slug).is_err(), "{slug}");
+        }
+    }
+
+    /// The path to a test database
+    fn database(pool: &SqlitePool) -> PathBuf {
+        pool.connect_options().get_filename().to_path_buf()
+    }
+
+    #[sqlx::test(migrations = "../crud/migrations")]
+    async fn tenants(pool: SqlitePool) {
+        let mut config = ApiConfig::new(database(&pool));
+        config.access_mode = ApiAccessMode::ReadWrite;
+        config.tenants.insert(
+            TenantSlug::from_str("history").unwrap(),
+            TenantConfig {
+                database: database(&pool),
+                access_mode: ApiAccessMode::Read,
+            },
+        );
+        config.tenants.insert(
+            TenantSlug::from_str("missing").unwrap(),
+            TenantConfig {
+                database: PathBuf::from("/does/not/exist.sqlite"),
+                access_mode: ApiAccessMode::Read,
+            },
+        );
+        let router = from_config(&config).await.unwrap();
+        let send = |method: Method, uri: &str| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            router.clone().oneshot(request)
+        };
+
+        // The tenant's routes are served under its slug
+        let response = send(Method::GET, "/api/v1/history/tags").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"[]");
+
+        // The tenant's access mode applies to it (not the default database's)
+        let response = send(Method::DELETE, "/api/v1/history/entity/a")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = send(Method::DELETE, "/api/v1/entity/a").await.unwrap();
+        assert_ne!(response.status(), StatusCode::FORBIDDEN);
+
+        // The default database's routes are still served
+        let response = send(Method::GET, "/api/v1/tags").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Unknown & unavailable tenants
+        let response = send(Method::GET, "/api/v1/nobody/tags").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = send(Method::GET, "/api/v1/missing/tags").await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}